@@ -135,7 +135,8 @@ fn encode_block(c: &mut Criterion) {
                 b.iter(|| {
                     // Serialize block
                     let (mut header, data) =
-                        ValueBlock::to_bytes_compressed(&items, BlockOffset(0), comp_type).unwrap();
+                        ValueBlock::to_bytes_compressed(&items, BlockOffset(0), comp_type, None)
+                            .unwrap();
                 });
             });
         }
@@ -176,7 +177,7 @@ fn load_value_block_from_disk(c: &mut Criterion) {
 
             // Serialize block
             let (mut header, data) =
-                ValueBlock::to_bytes_compressed(&items, BlockOffset(0), comp_type).unwrap();
+                ValueBlock::to_bytes_compressed(&items, BlockOffset(0), comp_type, None).unwrap();
 
             let mut file = tempfile::tempfile().unwrap();
             header.encode_into(&mut file).unwrap();
@@ -189,7 +190,8 @@ fn load_value_block_from_disk(c: &mut Criterion) {
 
             group.bench_function(format!("{block_size} KiB [{comp_type}]"), |b| {
                 b.iter(|| {
-                    let loaded_block = ValueBlock::from_file(&mut file, BlockOffset(0)).unwrap();
+                    let loaded_block =
+                        ValueBlock::from_file(&mut file, BlockOffset(0), None).unwrap();
 
                     assert_eq!(loaded_block.items.len(), expected_block.items.len());
                     assert_eq!(loaded_block.header.checksum, expected_block.header.checksum);
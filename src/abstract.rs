@@ -3,13 +3,21 @@
 // (found in the LICENSE-* files in the repository)
 
 use crate::{
-    compaction::CompactionStrategy, config::TreeType, tree::inner::MemtableId, AnyTree, BlobTree,
-    Config, KvPair, Memtable, Segment, SegmentId, SeqNo, Snapshot, Tree, UserKey, UserValue,
+    compaction::{CompactionScheduler, CompactionStrategy},
+    config::TreeType,
+    read_overlay::OverlayIter,
+    tree::inner::MemtableId,
+    AnyTree, BlobTree, Config, Cursor, Health, InternalValue, KeyRange, KvPair, MemoryUsage,
+    Memtable, PendingWork, ReadOverlay, ScanCursor, ScanPage, ScrubReport, Segment,
+    SegmentAccessStats, SegmentId, SeqNo, Snapshot, StallState, Tree, UserKey, UserValue,
+    ValueType, VerifyReport,
 };
 use enum_dispatch::enum_dispatch;
 use std::{
-    ops::RangeBounds,
+    ops::{Bound, RangeBounds},
+    path::Path,
     sync::{Arc, RwLockWriteGuard},
+    time::Duration,
 };
 
 pub type RangeItem = crate::Result<KvPair>;
@@ -21,8 +29,83 @@ pub trait AbstractTree {
     /// Gets the memory usage of all bloom filters in the tree.
     fn bloom_filter_size(&self) -> usize;
 
-    #[doc(hidden)]
-    fn verify(&self) -> crate::Result<usize>;
+    /// Gets the memory retained by block indexes and bloom filters, per level.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config};
+    ///
+    /// let tree = Config::new(folder).open()?;
+    /// tree.insert("a", "abc", 0);
+    /// tree.flush_active_memtable(0)?;
+    ///
+    /// let usage = tree.memory_usage();
+    /// assert_eq!(1, usage.levels[0].segment_count);
+    /// assert!(usage.block_index_size() > 0);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    fn memory_usage(&self) -> MemoryUsage;
+
+    /// Re-reads all segments (and blob files), checking block checksums,
+    /// the order of all items, and that the block indexes match the data blocks.
+    ///
+    /// Unlike [`AbstractTree::scrub`], this returns a detailed report
+    /// per segment, and does not quarantine corrupt segments.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config};
+    ///
+    /// let tree = Config::new(folder).open()?;
+    /// tree.insert("a", "abc", 0);
+    /// tree.flush_active_memtable(0)?;
+    ///
+    /// let report = tree.verify()?;
+    /// assert_eq!(1, report.segments.len());
+    /// assert!(report.is_ok());
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn verify(&self) -> crate::Result<VerifyReport>;
+
+    /// Verifies the checksums of all segments (and blob files) once.
+    ///
+    /// Unlike a compaction, this finds bit rot in data that is rarely rewritten.
+    /// Corrupt files are handled like the background scrubber does,
+    /// see [`Config::scrub_period`].
+    ///
+    /// Files are read at the rate of the [`Config::rate_limiter`], if one is set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config};
+    ///
+    /// let tree = Config::new(folder).open()?;
+    /// tree.insert("a", "abc", 0);
+    /// tree.flush_active_memtable(0)?;
+    ///
+    /// let report = tree.scrub()?;
+    /// assert_eq!(1, report.segments_checked);
+    /// assert!(report.is_clean());
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn scrub(&self) -> crate::Result<ScrubReport>;
 
     /// Synchronously flushes a memtable to a disk segment.
     ///
@@ -39,6 +122,68 @@ pub trait AbstractTree {
         seqno_threshold: SeqNo,
     ) -> crate::Result<Option<Segment>>;
 
+    /// Synchronously flushes multiple sealed memtables to disk segments,
+    /// and registers them into the tree in a single manifest update.
+    ///
+    /// Instead of fsyncing every segment file on its own, the files are synced
+    /// together after all memtables have been written, which greatly improves flush
+    /// throughput on high-latency storage when memtables pile up.
+    ///
+    /// Memtables that end up empty (because all their items were evicted)
+    /// do not produce a segment.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn flush_memtables(
+        &self,
+        memtables: &[(SegmentId, Arc<Memtable>)],
+        seqno_threshold: SeqNo,
+    ) -> crate::Result<Vec<Segment>>;
+
+    /// Synchronously flushes multiple sealed memtables into a single disk segment,
+    /// and registers it into the tree, removing all of the memtables.
+    ///
+    /// Bursty writes can queue up many small sealed memtables; flushing them one by one
+    /// creates a tiny L0 segment each, which then need to be compacted right away.
+    ///
+    /// The segment takes the ID of the last memtable, so memtables should
+    /// be passed in the order they were sealed in.
+    ///
+    /// Returns `None` if no memtables were given, or all their items were evicted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config};
+    ///
+    /// let tree = Config::new(folder).open()?;
+    ///
+    /// let mut memtables = vec![];
+    ///
+    /// for seqno in 0..3 {
+    ///     tree.insert("a", seqno.to_string(), seqno);
+    ///     memtables.extend(tree.rotate_memtable());
+    /// }
+    ///
+    /// tree.flush_memtables_coalesced(&memtables, 0)?;
+    /// assert_eq!(1, tree.segment_count());
+    /// assert_eq!(0, tree.sealed_memtable_count());
+    /// assert_eq!(Some("2".as_bytes().into()), tree.get("a", None)?);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn flush_memtables_coalesced(
+        &self,
+        memtables: &[(MemtableId, Arc<Memtable>)],
+        seqno_threshold: SeqNo,
+    ) -> crate::Result<Option<Segment>>;
+
     /// Atomically registers flushed disk segments into the tree, removing their associated sealed memtables.
     ///
     /// # Errors
@@ -58,11 +203,131 @@ pub trait AbstractTree {
     /// Returns the amount of sealed memtables.
     fn sealed_memtable_count(&self) -> usize;
 
+    /// Returns the approximate size of all sealed memtables in bytes.
+    fn sealed_memtables_size(&self) -> u64;
+
     /// Adds a sealed memtables.
     ///
     /// May be used to restore the LSM-tree's in-memory state from some journals.
     fn add_sealed_memtable(&self, id: MemtableId, memtable: Arc<Memtable>);
 
+    /// Marks the external write-ahead journal as durably persisted up to the given seqno.
+    ///
+    /// Once called, memtables are only flushed if all their writes are persisted
+    /// in the journal, so segments never contain writes that the journal could lose.
+    /// Flushing a memtable that contains newer writes fails with
+    /// [`crate::Error::JournalNotPersisted`].
+    ///
+    /// See [`crate::JournalObserver`].
+    fn mark_journal_persisted(&self, seqno: SeqNo);
+
+    /// Creates a named snapshot at the given seqno, which is persisted in the
+    /// manifest and survives restarts.
+    ///
+    /// Until the snapshot is released, flushes, compactions and blob garbage collection
+    /// keep all versions the snapshot can read, regardless of the seqno thresholds
+    /// they are given. If a snapshot with the same name exists, it is replaced.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config};
+    ///
+    /// let tree = Config::new(&folder).open()?;
+    /// tree.insert("a", "old", 0);
+    /// tree.create_named_snapshot("backup", 1)?;
+    ///
+    /// tree.insert("a", "new", 1);
+    /// tree.flush_active_memtable(2)?;
+    /// # drop(tree);
+    ///
+    /// let tree = Config::new(&folder).open()?;
+    /// let snapshot = tree.named_snapshot("backup").expect("should exist");
+    /// assert_eq!(Some("old".as_bytes().into()), snapshot.get("a")?);
+    ///
+    /// assert!(tree.release_named_snapshot("backup")?);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs, or the name is longer than 65535 bytes.
+    fn create_named_snapshot(&self, name: &str, seqno: SeqNo) -> crate::Result<Snapshot>;
+
+    /// Opens a named snapshot, see [`AbstractTree::create_named_snapshot`].
+    fn named_snapshot(&self, name: &str) -> Option<Snapshot>;
+
+    /// Releases a named snapshot, so the versions it kept can be garbage collected.
+    ///
+    /// Returns `false` if there is no snapshot with the given name.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn release_named_snapshot(&self, name: &str) -> crate::Result<bool>;
+
+    /// Returns the names and seqnos of all named snapshots, ordered by name.
+    fn list_named_snapshots(&self) -> Vec<(String, SeqNo)>;
+
+    /// Replays entries of an external journal into the active memtable, after tree recovery.
+    ///
+    /// Entries that are already persisted in segments are skipped, so the
+    /// entire journal may be replayed. Returns the amount of replayed entries.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, InternalValue, ValueType};
+    ///
+    /// let tree = Config::new(folder).open()?;
+    /// tree.insert("a", "abc", 0);
+    /// tree.flush_active_memtable(0)?;
+    ///
+    /// let journal = vec![
+    ///     InternalValue::from_components("a", "abc", 0, ValueType::Value),
+    ///     InternalValue::from_components("b", "def", 1, ValueType::Value),
+    /// ];
+    ///
+    /// assert_eq!(1, tree.replay_journal(journal));
+    /// assert!(tree.contains_key("b", None)?);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    fn replay_journal<I: IntoIterator<Item = InternalValue>>(&self, entries: I) -> usize {
+        let persisted_seqno = self.get_highest_persisted_seqno();
+        let mut count = 0;
+
+        for entry in entries {
+            if persisted_seqno.is_some_and(|persisted| entry.key.seqno <= persisted) {
+                continue;
+            }
+
+            let key = entry.key.user_key;
+            let seqno = entry.key.seqno;
+
+            match entry.key.value_type {
+                ValueType::Value => self.insert(key, entry.value, seqno),
+                ValueType::Tombstone => self.remove(key, seqno),
+                ValueType::WeakTombstone => self.remove_weak(key, seqno),
+                ValueType::Merge | ValueType::ValueWithExpiry => self
+                    .lock_active_memtable()
+                    .insert(InternalValue::from_components(
+                        key,
+                        entry.value,
+                        seqno,
+                        entry.key.value_type,
+                    )),
+            };
+
+            count += 1;
+        }
+
+        count
+    }
+
     /// Performs compaction on the tree's levels, blocking the caller until it's done.
     ///
     /// # Errors
@@ -74,6 +339,196 @@ pub trait AbstractTree {
         seqno_threshold: SeqNo,
     ) -> crate::Result<()>;
 
+    /// Performs major compaction, blocking the caller until it's done.
+    ///
+    /// All segments are merged into segments of `target_size` in the last level,
+    /// which drops tombstones and obsolete versions below the `seqno_threshold`.
+    ///
+    /// Background compactions are paused while the major compaction runs,
+    /// so it is not declined because some segments are being compacted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config};
+    ///
+    /// let tree = Config::new(folder).open()?;
+    ///
+    /// for seqno in 0..4 {
+    ///     tree.insert("a", "abc", seqno);
+    ///     tree.flush_active_memtable(0)?;
+    /// }
+    /// tree.remove("a", 4);
+    /// tree.flush_active_memtable(0)?;
+    ///
+    /// tree.major_compact(u64::MAX, 5)?;
+    /// assert_eq!(0, tree.segment_count());
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `target_size` is below 1024 bytes.
+    fn major_compact(&self, target_size: u64, seqno_threshold: SeqNo) -> crate::Result<()> {
+        log::info!("Starting major compaction");
+
+        let strategy = Arc::new(crate::compaction::Major::new(target_size));
+
+        // NOTE: The guard only resumes the scheduler once the last
+        // pauser is done, and never lifts a pause of the application
+        let _pause_guard = self.compaction_scheduler().map(|scheduler| {
+            let guard = scheduler.pause_guard();
+            scheduler.wait_for_idle();
+            guard
+        });
+
+        self.compact(strategy, seqno_threshold)
+    }
+
+    /// Atomically removes all data from the tree.
+    ///
+    /// All memtables, segments (and blob files) are dropped, and an empty level
+    /// manifest is written, which is much faster than deleting every key and
+    /// does not leave tombstones behind.
+    ///
+    /// Clearing waits for open iterators, like a memtable rotation. The segment files
+    /// are removed once the last reader lets go of them. Segments that are flushed or
+    /// compacted from cleared data while clearing are discarded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config};
+    ///
+    /// let tree = Config::new(folder).open()?;
+    /// tree.insert("a", "abc", 0);
+    /// tree.flush_active_memtable(0)?;
+    /// tree.insert("b", "abc", 1);
+    ///
+    /// tree.clear()?;
+    /// assert!(tree.is_empty(None, None)?);
+    /// assert_eq!(0, tree.segment_count());
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn clear(&self) -> crate::Result<()>;
+
+    /// Removes all items whose key starts with the given prefix.
+    ///
+    /// Segments that only contain keys of the prefix are dropped as a whole.
+    /// Other items of the prefix (in memtables, or in segments that span
+    /// other keys as well) are removed by a range tombstone with the given seqno,
+    /// see [`AbstractTree::remove_range`].
+    ///
+    /// With [`Config::partition_prefix_len`], compacted segments never span
+    /// multiple prefixes, so only recently flushed data needs tombstones.
+    /// For blob trees, blob files that are only referenced by dropped segments
+    /// become stale and are reclaimed by blob GC.
+    ///
+    /// Segments that are still read by a pinned snapshot that does not see
+    /// the range tombstone (or contain items newer than `seqno`) are not dropped;
+    /// they are cleaned up by compaction instead.
+    ///
+    /// Returns the amount of dropped segments.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config};
+    ///
+    /// let tree = Config::new(folder).partition_prefix_len(3).open()?;
+    /// tree.insert("t1#a", "abc", 0);
+    /// tree.insert("t2#a", "abc", 0);
+    /// tree.flush_active_memtable(0)?;
+    /// tree.major_compact(u64::MAX, 1)?;
+    ///
+    /// assert_eq!(1, tree.drop_prefix("t1#", 1)?);
+    /// assert_eq!(1, tree.len(None, None)?);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn drop_prefix<K: AsRef<[u8]>>(&self, prefix: K, seqno: SeqNo) -> crate::Result<usize>;
+
+    /// Returns the background work that is currently pending,
+    /// including what the given compaction strategy would schedule next.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, compaction::Leveled};
+    ///
+    /// let tree = Config::new(folder).open()?;
+    /// assert!(tree.pending_work(&Leveled::default()).is_idle());
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    fn pending_work(&self, strategy: &dyn CompactionStrategy) -> PendingWork;
+
+    /// Returns a compact health report of the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config};
+    ///
+    /// let tree = Config::new(folder).open()?;
+    /// assert!(tree.health().is_healthy());
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    fn health(&self) -> Health;
+
+    /// Returns the write stall state of the tree.
+    ///
+    /// Writes should be throttled once the amount of L0 segments or sealed
+    /// memtables reaches its slowdown threshold, and halted once it reaches
+    /// its stop threshold, see [`Config::l0_slowdown_threshold`]
+    /// and [`Config::sealed_memtable_slowdown_threshold`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, StallState};
+    ///
+    /// let tree = Config::new(folder).sealed_memtable_slowdown_threshold(1).open()?;
+    /// assert_eq!(StallState::None, tree.write_stall_state());
+    ///
+    /// tree.insert("a", "abc", 0);
+    /// let memtables = tree.rotate_memtable().into_iter().collect::<Vec<_>>();
+    /// assert_eq!(StallState::Slowdown, tree.write_stall_state());
+    ///
+    /// tree.flush_memtables(&memtables, 0)?;
+    /// assert_eq!(StallState::None, tree.write_stall_state());
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    fn write_stall_state(&self) -> StallState {
+        StallState::new(
+            self.tree_config(),
+            self.first_level_segment_count(),
+            self.sealed_memtable_count(),
+        )
+    }
+
     /// Returns the next segment's ID.
     fn get_next_segment_id(&self) -> SegmentId;
 
@@ -109,28 +564,137 @@ pub trait AbstractTree {
     /// Returns the amount of disk segments in the first level.
     fn first_level_segment_count(&self) -> usize;
 
-    /// Returns `true` if the first level is disjoint.
-    fn is_first_level_disjoint(&self) -> bool;
-
-    /// Approximates the amount of items in the tree.
-    fn approximate_len(&self) -> usize;
-
-    /// Returns the disk space usage.
-    fn disk_space(&self) -> u64;
-
-    /// Returns the highest sequence number of the active memtable.
-    fn get_highest_memtable_seqno(&self) -> Option<SeqNo>;
-
-    /// Returns the highest sequence number that is flushed to disk.
-    fn get_highest_persisted_seqno(&self) -> Option<SeqNo>;
-
-    /// Scans the entire tree, returning the amount of items.
+    /// Returns the estimated amount of distinct keys in the tree.
     ///
-    /// ###### Caution
+    /// Unlike [`AbstractTree::approximate_len`], keys that are stored in multiple
+    /// segments (or memtables) are only counted once. The estimate is derived from
+    /// key sketches stored in every segment and has an error of a few percent.
+    /// Deleted keys may be included.
     ///
-    /// This operation scans the entire tree: O(n) complexity!
+    /// Segments written without a sketch contribute their key count instead.
     ///
-    /// Never, under any circumstances, use .`len()` == 0 to check
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config};
+    ///
+    /// let tree = Config::new(folder).open()?;
+    ///
+    /// tree.insert("a", "abc", 0);
+    /// tree.flush_active_memtable(0)?;
+    /// tree.insert("a", "def", 1);
+    /// tree.insert("b", "def", 2);
+    ///
+    /// assert_eq!(2, tree.estimated_unique_keys()?);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if a key sketch could not be loaded.
+    fn estimated_unique_keys(&self) -> crate::Result<u64>;
+
+    /// Returns the estimated amount of distinct keys in the given key range,
+    /// see [`AbstractTree::estimated_unique_keys`].
+    ///
+    /// Every segment that overlaps with the range counts with all of its keys,
+    /// so segments that only partially overlap inflate the estimate.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if a key sketch could not be loaded.
+    fn estimated_unique_keys_in_range(&self, key_range: &KeyRange) -> crate::Result<u64>;
+
+    /// Returns the IDs of the segments that overlap with the given key range, per level.
+    ///
+    /// The outer list is indexed by level, so it contains an entry for every level,
+    /// even if no segment of that level overlaps.
+    fn overlapping_segment_ids(&self, key_range: &KeyRange) -> Vec<Vec<SegmentId>>;
+
+    /// Returns the access counters of all segments, ordered by level.
+    fn segment_access_stats(&self) -> Vec<SegmentAccessStats>;
+
+    /// Persists the access counters of all segments into the tree folder.
+    ///
+    /// The counters are restored when the tree is reopened.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn persist_access_stats(&self) -> crate::Result<()> {
+        crate::segment::access_stats::persist(
+            &self.tree_config().path,
+            &self.segment_access_stats(),
+        )
+    }
+
+    /// Returns `true` if the first level is disjoint.
+    fn is_first_level_disjoint(&self) -> bool;
+
+    /// Approximates the amount of items in the tree.
+    fn approximate_len(&self) -> usize;
+
+    /// Returns the disk space usage.
+    fn disk_space(&self) -> u64;
+
+    /// Estimates the space amplification (disk space / live data).
+    ///
+    /// The live data is estimated as the size of the last non-empty level,
+    /// which holds most of the data in a steady state leveled tree.
+    ///
+    /// Returns 0.0 if the tree is empty.
+    fn space_amp(&self) -> f32;
+
+    /// Returns the highest sequence number of the active memtable.
+    fn get_highest_memtable_seqno(&self) -> Option<SeqNo>;
+
+    /// Returns the highest sequence number that is flushed to disk.
+    fn get_highest_persisted_seqno(&self) -> Option<SeqNo>;
+
+    /// Returns `true` if the write with the given seqno is contained in a flushed segment.
+    fn is_persisted(&self, seqno: SeqNo) -> bool {
+        self.get_highest_persisted_seqno()
+            .is_some_and(|persisted| persisted >= seqno)
+    }
+
+    /// Blocks until the write with the given seqno is contained in a flushed segment.
+    ///
+    /// Returns `false` if the timeout expired before the seqno was persisted.
+    ///
+    /// This does not trigger a flush, so some other thread needs to flush the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config};
+    /// use std::time::Duration;
+    ///
+    /// let tree = Config::new(folder).open()?;
+    /// tree.insert("a", "abc", 0);
+    ///
+    /// assert!(!tree.wait_for_persisted(0, Some(Duration::from_millis(10))));
+    ///
+    /// std::thread::spawn({
+    ///     let tree = tree.clone();
+    ///     move || tree.flush_active_memtable(0)
+    /// });
+    ///
+    /// assert!(tree.wait_for_persisted(0, None));
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    fn wait_for_persisted(&self, seqno: SeqNo, timeout: Option<Duration>) -> bool;
+
+    /// Scans the entire tree, returning the amount of items.
+    ///
+    /// ###### Caution
+    ///
+    /// This operation scans the entire tree: O(n) complexity!
+    ///
+    /// Never, under any circumstances, use .`len()` == 0 to check
     /// if the tree is empty, use [`Tree::is_empty`] instead.
     ///
     /// # Examples
@@ -176,10 +740,457 @@ pub trait AbstractTree {
     /// use lsm_tree::{AbstractTree, Config, Tree};
     ///
     /// let tree = Config::new(folder).open()?;
-    /// assert!(tree.is_empty(None, None)?);
+    /// assert!(tree.is_empty(None, None)?);
+    ///
+    /// tree.insert("a", "abc", 0);
+    /// assert!(!tree.is_empty(None, None)?);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn is_empty(&self, seqno: Option<SeqNo>, index: Option<Arc<Memtable>>) -> crate::Result<bool> {
+        self.first_key_value(seqno, index).map(|x| x.is_none())
+    }
+
+    /// Returns the first key-value pair in the tree.
+    /// The key in this pair is the minimum key in the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use lsm_tree::Error as TreeError;
+    /// # use lsm_tree::{AbstractTree, Config, Tree};
+    /// #
+    /// # let folder = tempfile::tempdir()?;
+    /// let tree = Config::new(folder).open()?;
+    ///
+    /// tree.insert("1", "abc", 0);
+    /// tree.insert("3", "abc", 1);
+    /// tree.insert("5", "abc", 2);
+    ///
+    /// let (key, _) = tree.first_key_value(None, None)?.expect("item should exist");
+    /// assert_eq!(&*key, "1".as_bytes());
+    /// #
+    /// # Ok::<(), TreeError>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn first_key_value(
+        &self,
+        seqno: Option<SeqNo>,
+        index: Option<Arc<Memtable>>,
+    ) -> crate::Result<Option<KvPair>> {
+        self.iter(seqno, index).next().transpose()
+    }
+
+    /// Returns the last key-value pair in the tree.
+    /// The key in this pair is the maximum key in the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use lsm_tree::Error as TreeError;
+    /// # use lsm_tree::{AbstractTree, Config, Tree};
+    /// #
+    /// # let folder = tempfile::tempdir()?;
+    /// # let tree = Config::new(folder).open()?;
+    /// #
+    /// tree.insert("1", "abc", 0);
+    /// tree.insert("3", "abc", 1);
+    /// tree.insert("5", "abc", 2);
+    ///
+    /// let (key, _) = tree.last_key_value(None, None)?.expect("item should exist");
+    /// assert_eq!(&*key, "5".as_bytes());
+    /// #
+    /// # Ok::<(), TreeError>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn last_key_value(
+        &self,
+        seqno: Option<SeqNo>,
+        index: Option<Arc<Memtable>>,
+    ) -> crate::Result<Option<KvPair>> {
+        self.iter(seqno, index).next_back().transpose()
+    }
+
+    /// Returns an iterator that scans through the entire tree.
+    ///
+    /// Avoid using this function, or limit it as otherwise it may scan a lot of items.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, Tree};
+    ///
+    /// let tree = Config::new(folder).open()?;
+    ///
+    /// tree.insert("a", "abc", 0);
+    /// tree.insert("f", "abc", 1);
+    /// tree.insert("g", "abc", 2);
+    /// assert_eq!(3, tree.iter(None, None).count());
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    fn iter(
+        &self,
+        seqno: Option<SeqNo>,
+        index: Option<Arc<Memtable>>,
+    ) -> Box<dyn DoubleEndedIterator<Item = crate::Result<KvPair>> + 'static> {
+        self.range::<&[u8], _>(.., seqno, index)
+    }
+
+    /// Returns an iterator that scans through the entire tree, returning keys only.
+    ///
+    /// Avoid using this function, or limit it as otherwise it may scan a lot of items.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, Tree};
+    ///
+    /// let tree = Config::new(folder).open()?;
+    ///
+    /// tree.insert("a", "abc", 0);
+    /// tree.insert("f", "abc", 1);
+    /// tree.insert("g", "abc", 2);
+    /// assert_eq!(3, tree.keys(None, None).count());
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    fn keys(
+        &self,
+        seqno: Option<SeqNo>,
+        index: Option<Arc<Memtable>>,
+    ) -> Box<dyn DoubleEndedIterator<Item = crate::Result<UserKey>> + 'static>;
+
+    /// Returns an iterator over a range of keys.
+    ///
+    /// For blob trees, the value log is never read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, Tree};
+    ///
+    /// let tree = Config::new(folder).open()?;
+    ///
+    /// tree.insert("a", "abc", 0);
+    /// tree.insert("f", "abc", 1);
+    /// tree.insert("g", "abc", 2);
+    /// assert_eq!(2, tree.keys_range("a"..="f", None, None).count());
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    fn keys_range<K: AsRef<[u8]>, R: RangeBounds<K>>(
+        &self,
+        range: R,
+        seqno: Option<SeqNo>,
+        index: Option<Arc<Memtable>>,
+    ) -> Box<dyn DoubleEndedIterator<Item = crate::Result<UserKey>> + 'static>;
+
+    /// Returns an iterator over a prefixed set of keys.
+    ///
+    /// For blob trees, the value log is never read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, Tree};
+    ///
+    /// let tree = Config::new(folder).open()?;
+    ///
+    /// tree.insert("a", "abc", 0);
+    /// tree.insert("ab", "abc", 1);
+    /// tree.insert("abc", "abc", 2);
+    /// tree.insert("b", "abc", 3);
+    /// assert_eq!(2, tree.keys_prefix("ab", None, None).count());
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    fn keys_prefix<K: AsRef<[u8]>>(
+        &self,
+        prefix: K,
+        seqno: Option<SeqNo>,
+        index: Option<Arc<Memtable>>,
+    ) -> Box<dyn DoubleEndedIterator<Item = crate::Result<UserKey>> + 'static>;
+
+    /// Returns an iterator that scans through the entire tree, returning values only.
+    ///
+    /// Avoid using this function, or limit it as otherwise it may scan a lot of items.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, Tree};
+    ///
+    /// let tree = Config::new(folder).open()?;
+    ///
+    /// tree.insert("a", "abc", 0);
+    /// tree.insert("f", "abc", 1);
+    /// tree.insert("g", "abc", 2);
+    /// assert_eq!(3, tree.values(None, None).count());
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    fn values(
+        &self,
+        seqno: Option<SeqNo>,
+        index: Option<Arc<Memtable>>,
+    ) -> Box<dyn DoubleEndedIterator<Item = crate::Result<UserValue>> + 'static>;
+
+    /// Returns an iterator over a range of items.
+    ///
+    /// Avoid using full or unbounded ranges as they may scan a lot of items (unless limited).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, Tree};
+    ///
+    /// let tree = Config::new(folder).open()?;
+    ///
+    /// tree.insert("a", "abc", 0);
+    /// tree.insert("f", "abc", 1);
+    /// tree.insert("g", "abc", 2);
+    /// assert_eq!(2, tree.range("a"..="f", None, None).into_iter().count());
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    fn range<K: AsRef<[u8]>, R: RangeBounds<K>>(
+        &self,
+        range: R,
+        seqno: Option<SeqNo>,
+        index: Option<Arc<Memtable>>,
+    ) -> Box<dyn DoubleEndedIterator<Item = crate::Result<KvPair>> + 'static>;
+
+    /// Returns an iterator over a prefixed set of items.
+    ///
+    /// Avoid using an empty prefix as it may scan a lot of items (unless limited).
+    ///
+    /// The prefix is turned into a bounded range ending at the prefix successor,
+    /// so reverse iteration seeks directly to the end of the prefix.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, Tree};
+    ///
+    /// let tree = Config::new(folder).open()?;
+    ///
+    /// tree.insert("a", "abc", 0);
+    /// tree.insert("ab", "abc", 1);
+    /// tree.insert("abc", "abc", 2);
+    /// tree.insert("b", "abc", 3);
+    /// assert_eq!(2, tree.prefix("ab", None, None).count());
+    ///
+    /// let (key, _) = tree.prefix("ab", None, None).next_back().unwrap()?;
+    /// assert_eq!(b"abc", &*key);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    fn prefix<K: AsRef<[u8]>>(
+        &self,
+        prefix: K,
+        seqno: Option<SeqNo>,
+        index: Option<Arc<Memtable>>,
+    ) -> Box<dyn DoubleEndedIterator<Item = crate::Result<KvPair>> + 'static>;
+
+    /// Returns an iterator over the items of multiple ranges.
+    ///
+    /// The ranges are sorted, and overlapping ranges are merged, so every item is
+    /// returned once, in key order. Every range seeks to its own start, so the
+    /// items in the gaps between the ranges are never read.
+    ///
+    /// All ranges are read at the same seqno: if no seqno is given,
+    /// the ranges see the writes that were visible when this was called.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, Tree};
+    ///
+    /// let tree = Config::new(folder).open()?;
+    ///
+    /// tree.insert("a", "abc", 0);
+    /// tree.insert("b", "abc", 1);
+    /// tree.insert("c", "abc", 2);
+    /// tree.insert("d", "abc", 3);
+    /// assert_eq!(2, tree.multi_range(["c"..="c", "a"..="a"], None).count());
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    fn multi_range<K: AsRef<[u8]>, R: RangeBounds<K>, I: IntoIterator<Item = R>>(
+        &self,
+        ranges: I,
+        seqno: Option<SeqNo>,
+    ) -> Box<dyn DoubleEndedIterator<Item = crate::Result<KvPair>> + 'static> {
+        let ranges = crate::multi_range::sort_and_merge(
+            ranges
+                .into_iter()
+                .map(|range| crate::range::range_bounds_to_owned(&range))
+                .collect(),
+        );
+
+        if ranges.is_empty() {
+            return Box::new(std::iter::empty());
+        }
+
+        // NOTE: The ranges are opened lazily, so pin the seqno
+        // (and keep the versions it reads from being compacted away)
+        let seqno = seqno.unwrap_or_else(|| self.get_highest_seqno().map_or(0, |seqno| seqno + 1));
+        let snapshot = self.snapshot(seqno);
+
+        Box::new(
+            ranges
+                .into_iter()
+                .flat_map(move |range| snapshot.range::<UserKey, _>(range)),
+        )
+    }
+
+    /// Returns up to `limit` items of a range, starting after the position of `cursor`.
+    ///
+    /// The returned page contains a cursor to fetch the next page with, which
+    /// can be serialized and resumed later, even after reopening the tree.
+    /// The same range should be passed for every page of a scan.
+    ///
+    /// Compaction only keeps old versions that are visible to a snapshot, so pin the
+    /// seqno of the cursor while the scan is in progress, otherwise later pages
+    /// may miss keys that were overwritten in the meantime, see [`ScanPage::pinned`].
+    /// A named snapshot also survives reopening the tree,
+    /// see [`AbstractTree::create_named_snapshot`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, ScanCursor};
+    ///
+    /// let tree = Config::new(folder).open()?;
+    ///
+    /// tree.insert("a", "abc", 0);
+    /// tree.insert("b", "abc", 1);
+    /// tree.insert("c", "abc", 2);
+    ///
+    /// // NOTE: Keep the versions of the scan around until it is done
+    /// tree.create_named_snapshot("scan", 3)?;
+    ///
+    /// let page = tree.scan_page::<&str, _>(.., &ScanCursor::new(3), 2)?;
+    /// assert_eq!(2, page.items.len());
+    ///
+    /// let page = tree.scan_page::<&str, _>(.., &page.next.expect("should have more"), 2)?;
+    /// assert_eq!(1, page.items.len());
+    /// assert!(page.next.is_none());
+    ///
+    /// tree.release_named_snapshot("scan")?;
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `limit` is 0.
+    fn scan_page<K: AsRef<[u8]>, R: RangeBounds<K>>(
+        &self,
+        range: R,
+        cursor: &ScanCursor,
+        limit: usize,
+    ) -> crate::Result<ScanPage> {
+        // NOTE: An empty page could never advance the cursor
+        assert!(limit > 0, "page limit should be at least 1");
+
+        // TODO: Bound::map: 1.77
+        let into_key = |bound: Bound<&K>| match bound {
+            Bound::Included(key) => Bound::Included(UserKey::from(key.as_ref())),
+            Bound::Excluded(key) => Bound::Excluded(UserKey::from(key.as_ref())),
+            Bound::Unbounded => Bound::Unbounded,
+        };
+
+        let start = cursor.start_bound(into_key(range.start_bound()));
+        let end = into_key(range.end_bound());
+
+        let mut iter = self.range((start, end), Some(cursor.seqno()), None);
+
+        let mut items = Vec::with_capacity(limit.min(1_024));
+
+        for item in iter.by_ref().take(limit) {
+            items.push(item?);
+        }
+
+        let next = match items.last() {
+            Some((key, _)) if iter.next().transpose()?.is_some() => {
+                Some(cursor.advance(key.clone()))
+            }
+            _ => None,
+        };
+
+        // NOTE: Compaction keeps every version at or above the GC watermark
+        let pinned = cursor.seqno() >= self.gc_watermark();
+
+        Ok(ScanPage {
+            items,
+            next,
+            pinned,
+        })
+    }
+
+    /// Returns the size of a value if it exists.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, Tree};
+    ///
+    /// let tree = Config::new(folder).open()?;
+    /// tree.insert("a", "my_value", 0);
+    ///
+    /// let size = tree.size_of("a", None)?.unwrap_or_default();
+    /// assert_eq!("my_value".len() as u32, size);
+    ///
+    /// let size = tree.size_of("b", None)?.unwrap_or_default();
+    /// assert_eq!(0, size);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn size_of<K: AsRef<[u8]>>(&self, key: K, seqno: Option<SeqNo>) -> crate::Result<Option<u32>>;
+
+    /// Retrieves an item from the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, Tree};
+    ///
+    /// let tree = Config::new(folder).open()?;
+    /// tree.insert("a", "my_value", 0);
     ///
-    /// tree.insert("a", "abc", 0);
-    /// assert!(!tree.is_empty(None, None)?);
+    /// let item = tree.get("a", None)?;
+    /// assert_eq!(Some("my_value".as_bytes().into()), item);
     /// #
     /// # Ok::<(), lsm_tree::Error>(())
     /// ```
@@ -187,131 +1198,152 @@ pub trait AbstractTree {
     /// # Errors
     ///
     /// Will return `Err` if an IO error occurs.
-    fn is_empty(&self, seqno: Option<SeqNo>, index: Option<Arc<Memtable>>) -> crate::Result<bool> {
-        self.first_key_value(seqno, index).map(|x| x.is_none())
-    }
+    fn get<K: AsRef<[u8]>>(&self, key: K, seqno: Option<SeqNo>)
+        -> crate::Result<Option<UserValue>>;
 
-    /// Returns the first key-value pair in the tree.
-    /// The key in this pair is the minimum key in the tree.
+    /// Retrieves multiple items from the tree.
+    ///
+    /// The keys are looked up as a sorted batch, so the memtables and segments
+    /// are visited once per batch instead of once per key.
+    /// The values are returned in the order of the given keys.
     ///
     /// # Examples
     ///
     /// ```
-    /// # use lsm_tree::Error as TreeError;
-    /// # use lsm_tree::{AbstractTree, Config, Tree};
-    /// #
     /// # let folder = tempfile::tempdir()?;
-    /// let tree = Config::new(folder).open()?;
+    /// use lsm_tree::{AbstractTree, Config, Tree};
     ///
-    /// tree.insert("1", "abc", 0);
-    /// tree.insert("3", "abc", 1);
-    /// tree.insert("5", "abc", 2);
+    /// let tree = Config::new(folder).open()?;
+    /// tree.insert("a", "abc", 0);
+    /// tree.insert("c", "def", 1);
     ///
-    /// let (key, _) = tree.first_key_value(None, None)?.expect("item should exist");
-    /// assert_eq!(&*key, "1".as_bytes());
+    /// let items = tree.get_many(["c", "b", "a"], None)?;
+    /// assert_eq!(
+    ///     vec![Some("def".as_bytes().into()), None, Some("abc".as_bytes().into())],
+    ///     items,
+    /// );
     /// #
-    /// # Ok::<(), TreeError>(())
+    /// # Ok::<(), lsm_tree::Error>(())
     /// ```
     ///
     /// # Errors
     ///
     /// Will return `Err` if an IO error occurs.
-    fn first_key_value(
+    fn get_many<K: AsRef<[u8]>, I: IntoIterator<Item = K>>(
         &self,
+        keys: I,
         seqno: Option<SeqNo>,
-        index: Option<Arc<Memtable>>,
-    ) -> crate::Result<Option<KvPair>> {
-        self.iter(seqno, index).next().transpose()
-    }
+    ) -> crate::Result<Vec<Option<UserValue>>>;
 
-    /// Returns the last key-value pair in the tree.
-    /// The key in this pair is the maximum key in the tree.
+    /// Retrieves an item from the tree, reading the writes of the overlay on top of the tree.
     ///
     /// # Examples
     ///
     /// ```
-    /// # use lsm_tree::Error as TreeError;
-    /// # use lsm_tree::{AbstractTree, Config, Tree};
-    /// #
     /// # let folder = tempfile::tempdir()?;
-    /// # let tree = Config::new(folder).open()?;
-    /// #
-    /// tree.insert("1", "abc", 0);
-    /// tree.insert("3", "abc", 1);
-    /// tree.insert("5", "abc", 2);
+    /// use lsm_tree::{AbstractTree, Config, ReadOverlay};
     ///
-    /// let (key, _) = tree.last_key_value(None, None)?.expect("item should exist");
-    /// assert_eq!(&*key, "5".as_bytes());
+    /// let tree = Config::new(folder).open()?;
+    /// tree.insert("a", "my_value", 0);
+    ///
+    /// let overlay = ReadOverlay::default();
+    /// overlay.insert("a", "my_new_value");
+    ///
+    /// let item = tree.get_with_overlay("a", None, &overlay)?;
+    /// assert_eq!(Some("my_new_value".as_bytes().into()), item);
     /// #
-    /// # Ok::<(), TreeError>(())
+    /// # Ok::<(), lsm_tree::Error>(())
     /// ```
     ///
     /// # Errors
     ///
     /// Will return `Err` if an IO error occurs.
-    fn last_key_value(
+    fn get_with_overlay<K: AsRef<[u8]>>(
         &self,
+        key: K,
         seqno: Option<SeqNo>,
-        index: Option<Arc<Memtable>>,
-    ) -> crate::Result<Option<KvPair>> {
-        self.iter(seqno, index).next_back().transpose()
+        overlay: &ReadOverlay,
+    ) -> crate::Result<Option<UserValue>> {
+        if let Some(item) = overlay.get(&key) {
+            return Ok(item);
+        }
+
+        self.get(key, seqno)
     }
 
-    /// Returns an iterator that scans through the entire tree.
+    /// Returns an iterator over a range of items, reading the writes
+    /// of the overlay on top of the tree.
     ///
-    /// Avoid using this function, or limit it as otherwise it may scan a lot of items.
+    /// The overlay is read when the iterator is created, so later writes
+    /// to the overlay are not visible to the iterator.
     ///
     /// # Examples
     ///
     /// ```
     /// # let folder = tempfile::tempdir()?;
-    /// use lsm_tree::{AbstractTree, Config, Tree};
+    /// use lsm_tree::{AbstractTree, Config, ReadOverlay};
     ///
     /// let tree = Config::new(folder).open()?;
-    ///
     /// tree.insert("a", "abc", 0);
     /// tree.insert("f", "abc", 1);
-    /// tree.insert("g", "abc", 2);
-    /// assert_eq!(3, tree.iter(None, None).count());
+    ///
+    /// let overlay = ReadOverlay::default();
+    /// overlay.insert("b", "abc");
+    /// overlay.remove("f");
+    ///
+    /// assert_eq!(2, tree.range_with_overlay("a"..="f", None, &overlay).count());
     /// #
     /// # Ok::<(), lsm_tree::Error>(())
     /// ```
-    fn iter(
+    fn range_with_overlay<K: AsRef<[u8]>, R: RangeBounds<K>>(
         &self,
+        range: R,
         seqno: Option<SeqNo>,
-        index: Option<Arc<Memtable>>,
+        overlay: &ReadOverlay,
     ) -> Box<dyn DoubleEndedIterator<Item = crate::Result<KvPair>> + 'static> {
-        self.range::<&[u8], _>(.., seqno, index)
+        let bounds = crate::range::range_bounds_to_owned(&range);
+        let overlay = overlay.collect_range(&bounds);
+
+        Box::new(OverlayIter::new(self.range(bounds, seqno, None), overlay))
     }
 
-    /// Returns an iterator that scans through the entire tree, returning keys only.
+    /// Returns an iterator over a prefixed set of items, reading the writes
+    /// of the overlay on top of the tree.
     ///
-    /// Avoid using this function, or limit it as otherwise it may scan a lot of items.
+    /// See [`AbstractTree::range_with_overlay`].
     ///
     /// # Examples
     ///
     /// ```
     /// # let folder = tempfile::tempdir()?;
-    /// use lsm_tree::{AbstractTree, Config, Tree};
+    /// use lsm_tree::{AbstractTree, Config, ReadOverlay};
     ///
     /// let tree = Config::new(folder).open()?;
+    /// tree.insert("ab", "abc", 0);
     ///
-    /// tree.insert("a", "abc", 0);
-    /// tree.insert("f", "abc", 1);
-    /// tree.insert("g", "abc", 2);
-    /// assert_eq!(3, tree.keys(None, None).count());
+    /// let overlay = ReadOverlay::default();
+    /// overlay.insert("a", "abc");
+    /// overlay.insert("abc", "abc");
+    ///
+    /// assert_eq!(2, tree.prefix_with_overlay("ab", None, &overlay).count());
     /// #
     /// # Ok::<(), lsm_tree::Error>(())
     /// ```
-    fn keys(
+    fn prefix_with_overlay<K: AsRef<[u8]>>(
         &self,
+        prefix: K,
         seqno: Option<SeqNo>,
-        index: Option<Arc<Memtable>>,
-    ) -> Box<dyn DoubleEndedIterator<Item = crate::Result<UserKey>> + 'static>;
+        overlay: &ReadOverlay,
+    ) -> Box<dyn DoubleEndedIterator<Item = crate::Result<KvPair>> + 'static> {
+        let bounds = crate::range::prefix_to_range(prefix.as_ref());
+        let overlay = overlay.collect_range(&bounds);
 
-    /// Returns an iterator that scans through the entire tree, returning values only.
+        Box::new(OverlayIter::new(self.range(bounds, seqno, None), overlay))
+    }
+
+    /// Opens a read-only point-in-time snapshot of the tree
     ///
-    /// Avoid using this function, or limit it as otherwise it may scan a lot of items.
+    /// Dropping the snapshot will close the snapshot
     ///
     /// # Examples
     ///
@@ -322,47 +1354,65 @@ pub trait AbstractTree {
     /// let tree = Config::new(folder).open()?;
     ///
     /// tree.insert("a", "abc", 0);
-    /// tree.insert("f", "abc", 1);
-    /// tree.insert("g", "abc", 2);
-    /// assert_eq!(3, tree.values(None, None).count());
+    ///
+    /// let snapshot = tree.snapshot(1);
+    /// assert_eq!(snapshot.len()?, tree.len(None, None)?);
+    ///
+    /// tree.insert("b", "abc", 1);
+    ///
+    /// assert_eq!(2, tree.len(None, None)?);
+    /// assert_eq!(1, snapshot.len()?);
+    ///
+    /// assert!(snapshot.contains_key("a")?);
+    /// assert!(!snapshot.contains_key("b")?);
     /// #
     /// # Ok::<(), lsm_tree::Error>(())
     /// ```
-    fn values(
-        &self,
-        seqno: Option<SeqNo>,
-        index: Option<Arc<Memtable>>,
-    ) -> Box<dyn DoubleEndedIterator<Item = crate::Result<UserValue>> + 'static>;
+    fn snapshot(&self, seqno: SeqNo) -> Snapshot;
 
-    /// Returns an iterator over a range of items.
+    /// Writes a consistent, openable copy of the tree into a new folder.
     ///
-    /// Avoid using full or unbounded ranges as they may scan a lot of items (unless limited).
+    /// The checkpoint contains every write that was visible when it was started.
+    /// Segment and blob files are hard linked (or copied, if they cannot be linked),
+    /// and memtable data is flushed into the checkpoint only, so the tree itself is not flushed.
+    ///
+    /// Writers are not blocked while the checkpoint is written,
+    /// only flushes and compactions have to wait.
     ///
     /// # Examples
     ///
     /// ```
     /// # let folder = tempfile::tempdir()?;
-    /// use lsm_tree::{AbstractTree, Config, Tree};
+    /// # let checkpoint_folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config};
     ///
     /// let tree = Config::new(folder).open()?;
-    ///
     /// tree.insert("a", "abc", 0);
-    /// tree.insert("f", "abc", 1);
-    /// tree.insert("g", "abc", 2);
-    /// assert_eq!(2, tree.range("a"..="f", None, None).into_iter().count());
+    /// tree.flush_active_memtable(0)?;
+    /// tree.insert("b", "abc", 1);
+    ///
+    /// tree.checkpoint(&checkpoint_folder)?;
+    ///
+    /// let checkpoint = Config::new(&checkpoint_folder).open()?;
+    /// assert_eq!(2, checkpoint.len(None, None)?);
     /// #
     /// # Ok::<(), lsm_tree::Error>(())
     /// ```
-    fn range<K: AsRef<[u8]>, R: RangeBounds<K>>(
-        &self,
-        range: R,
-        seqno: Option<SeqNo>,
-        index: Option<Arc<Memtable>>,
-    ) -> Box<dyn DoubleEndedIterator<Item = crate::Result<KvPair>> + 'static>;
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs, or the folder already contains a tree.
+    fn checkpoint<P: AsRef<Path>>(&self, path: P) -> crate::Result<()> {
+        // NOTE: Writes that happen while the checkpoint is written get higher seqnos,
+        // so they are not part of it
+        let seqno = self.get_highest_seqno().map_or(0, |seqno| seqno + 1);
+        self.snapshot(seqno).export(path)
+    }
 
-    /// Returns an iterator over a prefixed set of items.
+    /// Returns a seekable cursor over the items of the tree.
     ///
-    /// Avoid using an empty prefix as it may scan a lot of items (unless limited).
+    /// The cursor can be repositioned without setting up a new
+    /// range iterator by hand, which makes skip-scans (e.g. index joins) easy.
     ///
     /// # Examples
     ///
@@ -373,100 +1423,147 @@ pub trait AbstractTree {
     /// let tree = Config::new(folder).open()?;
     ///
     /// tree.insert("a", "abc", 0);
-    /// tree.insert("ab", "abc", 1);
-    /// tree.insert("abc", "abc", 2);
-    /// assert_eq!(2, tree.prefix("ab", None, None).count());
+    /// tree.insert("c", "abc", 1);
+    /// tree.insert("e", "abc", 2);
+    ///
+    /// let mut cursor = tree.cursor(None);
+    ///
+    /// let (key, _) = cursor.seek("b")?.unwrap();
+    /// assert_eq!(b"c", &*key);
+    ///
+    /// let (key, _) = cursor.next()?.unwrap();
+    /// assert_eq!(b"e", &*key);
+    ///
+    /// let (key, _) = cursor.seek_for_prev("b")?.unwrap();
+    /// assert_eq!(b"a", &*key);
+    /// assert!(cursor.prev()?.is_none());
     /// #
     /// # Ok::<(), lsm_tree::Error>(())
     /// ```
-    fn prefix<K: AsRef<[u8]>>(
-        &self,
-        prefix: K,
-        seqno: Option<SeqNo>,
-        index: Option<Arc<Memtable>>,
-    ) -> Box<dyn DoubleEndedIterator<Item = crate::Result<KvPair>> + 'static>;
+    fn cursor(&self, seqno: Option<SeqNo>) -> Cursor;
 
-    /// Returns the size of a value if it exists.
+    /// Returns the scheduler of the background compaction workers,
+    /// if enabled using [`Config::background_compaction`].
     ///
     /// # Examples
     ///
     /// ```
     /// # let folder = tempfile::tempdir()?;
-    /// use lsm_tree::{AbstractTree, Config, Tree};
+    /// use lsm_tree::{compaction::Leveled, AbstractTree, Config};
+    /// use std::sync::Arc;
     ///
-    /// let tree = Config::new(folder).open()?;
-    /// tree.insert("a", "my_value", 0);
+    /// let tree = Config::new(folder)
+    ///     .background_compaction(Arc::new(Leveled::default()), 1)
+    ///     .open()?;
     ///
-    /// let size = tree.size_of("a", None)?.unwrap_or_default();
-    /// assert_eq!("my_value".len() as u32, size);
+    /// for seqno in 0..8 {
+    ///     tree.insert("a", seqno.to_string(), seqno);
+    ///     tree.flush_active_memtable(0)?;
+    /// }
     ///
-    /// let size = tree.size_of("b", None)?.unwrap_or_default();
-    /// assert_eq!(0, size);
+    /// let scheduler = tree.compaction_scheduler().unwrap();
+    /// scheduler.wait_for_idle();
+    /// assert!(tree.first_level_segment_count() < 8);
     /// #
     /// # Ok::<(), lsm_tree::Error>(())
     /// ```
+    fn compaction_scheduler(&self) -> Option<&CompactionScheduler>;
+
+    /// Opens a snapshot of this partition with a given sequence number
+    #[must_use]
+    fn snapshot_at(&self, seqno: SeqNo) -> Snapshot {
+        self.snapshot(seqno)
+    }
+
+    /// Returns the seqno watermark of the tree at the given time (as duration since the unix epoch).
     ///
-    /// # Errors
+    /// Watermarks are sampled (using the configured [`crate::Clock`]) whenever
+    /// memtables are flushed, and survive restarts. The result is the watermark
+    /// of the last flush at or before the given time, so writes that were
+    /// not flushed by then are not included.
     ///
-    /// Will return `Err` if an IO error occurs.
-    fn size_of<K: AsRef<[u8]>>(&self, key: K, seqno: Option<SeqNo>) -> crate::Result<Option<u32>>;
+    /// Returns `None` if there was no flush at or before the given time.
+    fn seqno_at_time(&self, time: Duration) -> Option<SeqNo>;
 
-    /// Retrieves an item from the tree.
+    /// Opens a snapshot of the tree as of the given time (as duration since the unix epoch).
+    ///
+    /// See [`AbstractTree::seqno_at_time`] for how precise the snapshot is.
     ///
     /// # Examples
     ///
     /// ```
     /// # let folder = tempfile::tempdir()?;
-    /// use lsm_tree::{AbstractTree, Config, Tree};
+    /// use lsm_tree::{AbstractTree, Config, ManualClock};
+    /// use std::{sync::Arc, time::Duration};
     ///
-    /// let tree = Config::new(folder).open()?;
-    /// tree.insert("a", "my_value", 0);
+    /// let clock = Arc::new(ManualClock::new(Duration::from_secs(1_000)));
+    /// let tree = Config::new(folder).clock(clock.clone()).open()?;
     ///
-    /// let item = tree.get("a", None)?;
-    /// assert_eq!(Some("my_value".as_bytes().into()), item);
+    /// tree.insert("a", "old", 0);
+    /// tree.flush_active_memtable(0)?;
+    ///
+    /// clock.advance(Duration::from_secs(60));
+    /// tree.insert("a", "new", 1);
+    /// tree.flush_active_memtable(0)?;
+    ///
+    /// let snapshot = tree.snapshot_at_time(Duration::from_secs(1_030)).expect("should exist");
+    /// assert_eq!(Some("old".as_bytes().into()), snapshot.get("a")?);
+    ///
+    /// assert!(tree.snapshot_at_time(Duration::from_secs(999)).is_none());
     /// #
     /// # Ok::<(), lsm_tree::Error>(())
     /// ```
+    fn snapshot_at_time(&self, time: Duration) -> Option<Snapshot> {
+        self.seqno_at_time(time).map(|seqno| self.snapshot(seqno))
+    }
+
+    /// Registers a snapshot at the given seqno, so blob garbage collection
+    /// keeps the blobs it may read.
     ///
-    /// # Errors
+    /// Snapshots opened using [`AbstractTree::snapshot`] register themselves
+    /// (and deregister when dropped), so this is only needed when reading
+    /// with raw seqnos.
+    fn register_snapshot(&self, seqno: SeqNo);
+
+    /// Deregisters a snapshot, see [`AbstractTree::register_snapshot`].
     ///
-    /// Will return `Err` if an IO error occurs.
-    fn get<K: AsRef<[u8]>>(&self, key: K, seqno: Option<SeqNo>)
-        -> crate::Result<Option<UserValue>>;
+    /// Returns `false` if there is no snapshot registered at the given seqno.
+    fn deregister_snapshot(&self, seqno: SeqNo) -> bool;
 
-    /// Opens a read-only point-in-time snapshot of the tree
+    /// Returns the lowest seqno of all open and named snapshots.
+    fn lowest_snapshot_seqno(&self) -> Option<SeqNo>;
+
+    /// Returns a seqno threshold for garbage collection, which can be passed
+    /// to flushes, compactions and blob GC.
     ///
-    /// Dropping the snapshot will close the snapshot
+    /// Flushes and compactions keep the versions that open and named snapshots read,
+    /// so all other versions below the threshold can be evicted. If there are no snapshots,
+    /// every version that is shadowed by a newer one can be evicted.
+    ///
+    /// Only snapshots the tree knows about are considered, so readers that use
+    /// raw seqnos need to use [`AbstractTree::register_snapshot`].
     ///
     /// # Examples
     ///
     /// ```
     /// # let folder = tempfile::tempdir()?;
-    /// use lsm_tree::{AbstractTree, Config, Tree};
+    /// use lsm_tree::{AbstractTree, Config, SeqNo};
     ///
     /// let tree = Config::new(folder).open()?;
+    /// assert_eq!(SeqNo::MAX, tree.gc_watermark());
     ///
-    /// tree.insert("a", "abc", 0);
-    ///
+    /// tree.insert("a", "old", 0);
     /// let snapshot = tree.snapshot(1);
-    /// assert_eq!(snapshot.len()?, tree.len(None, None)?);
-    ///
-    /// tree.insert("b", "abc", 1);
-    ///
-    /// assert_eq!(2, tree.len(None, None)?);
-    /// assert_eq!(1, snapshot.len()?);
+    /// assert_eq!(1, tree.gc_watermark());
     ///
-    /// assert!(snapshot.contains_key("a")?);
-    /// assert!(!snapshot.contains_key("b")?);
+    /// tree.insert("a", "new", 1);
+    /// tree.flush_active_memtable(tree.gc_watermark())?;
+    /// assert_eq!(Some("old".as_bytes().into()), snapshot.get("a")?);
     /// #
     /// # Ok::<(), lsm_tree::Error>(())
     /// ```
-    fn snapshot(&self, seqno: SeqNo) -> Snapshot;
-
-    /// Opens a snapshot of this partition with a given sequence number
-    #[must_use]
-    fn snapshot_at(&self, seqno: SeqNo) -> Snapshot {
-        self.snapshot(seqno)
+    fn gc_watermark(&self) -> SeqNo {
+        self.lowest_snapshot_seqno().unwrap_or(SeqNo::MAX)
     }
 
     /// Returns `true` if the tree contains the specified key.
@@ -583,4 +1680,92 @@ pub trait AbstractTree {
     ///
     /// Will return `Err` if an IO error occurs.
     fn remove_weak<K: Into<UserKey>>(&self, key: K, seqno: SeqNo) -> (u32, u32);
+
+    /// Removes all items in the key range `[start, end)` from the tree.
+    ///
+    /// Instead of writing a tombstone for every key, a single range tombstone
+    /// is written, which hides all older versions of the keys in the range.
+    /// The covered items are dropped in compactions, once no snapshot can read them anymore.
+    ///
+    /// If the range is empty, nothing is written.
+    ///
+    /// Returns the added tombstone's size and new size of the memtable.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// # use lsm_tree::{AbstractTree, Config, Tree};
+    /// #
+    /// # let tree = Config::new(folder).open()?;
+    /// tree.insert("a", "abc", 0);
+    /// tree.insert("b", "abc", 1);
+    /// tree.insert("c", "abc", 2);
+    ///
+    /// tree.remove_range("a".."c", 3);
+    ///
+    /// assert_eq!(None, tree.get("a", None)?);
+    /// assert_eq!(None, tree.get("b", None)?);
+    /// assert!(tree.contains_key("c", None)?);
+    ///
+    /// // NOTE: Snapshots older than the tombstone still see the items
+    /// assert!(tree.contains_key("a", Some(3))?);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    fn remove_range<K: Into<UserKey>>(&self, range: std::ops::Range<K>, seqno: SeqNo)
+        -> (u32, u32);
+
+    /// Inserts a key-value pair into the tree, like [`AbstractTree::insert`],
+    /// but returns an error instead of panicking if the key or value is invalid.
+    ///
+    /// Returns the added item's size and new size of the memtable.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, Tree};
+    ///
+    /// let tree = Config::new(folder).open()?;
+    /// tree.try_insert("a", "abc", 0)?;
+    ///
+    /// assert!(matches!(
+    ///     tree.try_insert("", "abc", 1),
+    ///     Err(lsm_tree::Error::InvalidInput(_)),
+    /// ));
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the key is empty or longer than 65535 bytes,
+    /// or the value is longer than 2^32 bytes.
+    fn try_insert<K: Into<UserKey>, V: Into<UserValue>>(
+        &self,
+        key: K,
+        value: V,
+        seqno: SeqNo,
+    ) -> crate::Result<(u32, u32)>;
+
+    /// Removes an item from the tree, like [`AbstractTree::remove`],
+    /// but returns an error instead of panicking if the key is invalid.
+    ///
+    /// Returns the added item's size and new size of the memtable.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the key is empty or longer than 65535 bytes.
+    fn try_remove<K: Into<UserKey>>(&self, key: K, seqno: SeqNo) -> crate::Result<(u32, u32)>;
+
+    /// Removes an item from the tree, like [`AbstractTree::remove_weak`],
+    /// but returns an error instead of panicking if the key is invalid.
+    ///
+    /// Returns the added item's size and new size of the memtable.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the key is empty or longer than 65535 bytes.
+    fn try_remove_weak<K: Into<UserKey>>(&self, key: K, seqno: SeqNo) -> crate::Result<(u32, u32)>;
 }
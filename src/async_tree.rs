@@ -0,0 +1,245 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::{
+    compaction::CompactionStrategy, AbstractTree, Executor, KvPair, Segment, SeqNo, UserKey,
+    UserValue,
+};
+use std::{
+    future::Future,
+    ops::Bound,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Waker},
+};
+
+// TODO: Bound::map: 1.77
+fn bound_into<K: Into<UserKey>>(bound: Bound<K>) -> Bound<UserKey> {
+    match bound {
+        Bound::Included(key) => Bound::Included(key.into()),
+        Bound::Excluded(key) => Bound::Excluded(key.into()),
+        Bound::Unbounded => Bound::Unbounded,
+    }
+}
+
+struct TaskState<R> {
+    result: Option<R>,
+    waker: Option<Waker>,
+
+    /// Set once the task has finished (or panicked)
+    finished: bool,
+}
+
+/// Completes a [`Task`] - if the task panics, the completer is dropped
+/// without a result, so the future does not wait forever
+struct Completer<R>(Arc<Mutex<TaskState<R>>>);
+
+impl<R> Completer<R> {
+    fn complete(self, result: R) {
+        self.0.lock().expect("lock is poisoned").result = Some(result);
+    }
+}
+
+impl<R> Drop for Completer<R> {
+    fn drop(&mut self) {
+        let mut state = self.0.lock().expect("lock is poisoned");
+        state.finished = true;
+
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// Future of an operation that runs on an [`Executor`]
+///
+/// The operation is started immediately, even if the future is never polled.
+///
+/// # Panics
+///
+/// Polling panics if the operation panicked.
+#[must_use = "the operation runs anyway, but its result is lost"]
+pub struct Task<R>(Arc<Mutex<TaskState<R>>>);
+
+impl<R> Future for Task<R> {
+    type Output = R;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.0.lock().expect("lock is poisoned");
+
+        if let Some(result) = state.result.take() {
+            return Poll::Ready(result);
+        }
+
+        assert!(!state.finished, "async tree operation panicked");
+
+        state.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+/// Async wrapper around a tree
+///
+/// Every operation runs on the configured [`Executor`], so I/O never blocks
+/// the async runtime. Operations return a [`Task`], which resolves to the
+/// same result as the corresponding blocking call.
+///
+/// Cloning an `AsyncTree` is cheap, clones refer to the same tree.
+///
+/// # Examples
+///
+/// ```
+/// # let folder = tempfile::tempdir()?;
+/// use lsm_tree::{AsyncTree, Config, ThreadExecutor};
+/// use std::sync::Arc;
+///
+/// let tree = AsyncTree::new(Config::new(folder).open()?, Arc::new(ThreadExecutor));
+///
+/// # let _ = async move {
+/// tree.insert("a", "abc", 0).await;
+/// tree.flush_active_memtable(0).await?;
+///
+/// assert_eq!(Some("abc".as_bytes().into()), tree.get("a", None).await?);
+/// # Ok::<(), lsm_tree::Error>(())
+/// # };
+/// #
+/// # Ok::<(), lsm_tree::Error>(())
+/// ```
+#[derive(Clone)]
+pub struct AsyncTree<T: AbstractTree + Clone + Send + Sync + 'static> {
+    tree: T,
+    executor: Arc<dyn Executor>,
+}
+
+impl<T: AbstractTree + Clone + Send + Sync + 'static> AsyncTree<T> {
+    /// Wraps a tree, running its operations on the given executor.
+    pub fn new(tree: T, executor: Arc<dyn Executor>) -> Self {
+        Self { tree, executor }
+    }
+
+    /// Returns the wrapped tree, for blocking access.
+    #[must_use]
+    pub fn inner(&self) -> &T {
+        &self.tree
+    }
+
+    /// Runs a closure with the tree on the executor.
+    pub fn spawn<R, F>(&self, f: F) -> Task<R>
+    where
+        R: Send + 'static,
+        F: FnOnce(&T) -> R + Send + 'static,
+    {
+        let state = Arc::new(Mutex::new(TaskState {
+            result: None,
+            waker: None,
+            finished: false,
+        }));
+
+        let completer = Completer(state.clone());
+        let tree = self.tree.clone();
+
+        self.executor.spawn_blocking(Box::new(move || {
+            completer.complete(f(&tree));
+        }));
+
+        Task(state)
+    }
+
+    /// Retrieves an item from the tree, see [`AbstractTree::get`].
+    pub fn get<K: Into<UserKey>>(
+        &self,
+        key: K,
+        seqno: Option<SeqNo>,
+    ) -> Task<crate::Result<Option<UserValue>>> {
+        let key = key.into();
+        self.spawn(move |tree| tree.get(key, seqno))
+    }
+
+    /// Returns `true` if the tree contains the specified key,
+    /// see [`AbstractTree::contains_key`].
+    pub fn contains_key<K: Into<UserKey>>(
+        &self,
+        key: K,
+        seqno: Option<SeqNo>,
+    ) -> Task<crate::Result<bool>> {
+        let key = key.into();
+        self.spawn(move |tree| tree.contains_key(key, seqno))
+    }
+
+    /// Inserts a key-value pair into the tree, see [`AbstractTree::insert`].
+    pub fn insert<K: Into<UserKey>, V: Into<UserValue>>(
+        &self,
+        key: K,
+        value: V,
+        seqno: SeqNo,
+    ) -> Task<(u32, u32)> {
+        let key = key.into();
+        let value = value.into();
+        self.spawn(move |tree| tree.insert(key, value, seqno))
+    }
+
+    /// Removes an item from the tree, see [`AbstractTree::remove`].
+    pub fn remove<K: Into<UserKey>>(&self, key: K, seqno: SeqNo) -> Task<(u32, u32)> {
+        let key = key.into();
+        self.spawn(move |tree| tree.remove(key, seqno))
+    }
+
+    /// Collects all items in the given key range, see [`AbstractTree::range`].
+    pub fn range<K: Into<UserKey>>(
+        &self,
+        start: Bound<K>,
+        end: Bound<K>,
+        seqno: Option<SeqNo>,
+    ) -> Task<crate::Result<Vec<KvPair>>> {
+        let start = bound_into(start);
+        let end = bound_into(end);
+        self.spawn(move |tree| tree.range((start, end), seqno, None).collect())
+    }
+
+    /// Scans the entire tree, returning the amount of items,
+    /// see [`AbstractTree::len`].
+    pub fn len(&self, seqno: Option<SeqNo>) -> Task<crate::Result<usize>> {
+        self.spawn(move |tree| tree.len(seqno, None))
+    }
+
+    /// Flushes the active memtable to a disk segment.
+    ///
+    /// Resolves to `None` if the active memtable was empty.
+    pub fn flush_active_memtable(
+        &self,
+        seqno_threshold: SeqNo,
+    ) -> Task<crate::Result<Option<Segment>>> {
+        self.spawn(move |tree| {
+            let Some((segment_id, memtable)) = tree.rotate_memtable() else {
+                return Ok(None);
+            };
+
+            let Some(segment) = tree.flush_memtable(segment_id, &memtable, seqno_threshold)? else {
+                return Ok(None);
+            };
+            tree.register_segments(std::slice::from_ref(&segment))?;
+
+            Ok(Some(segment))
+        })
+    }
+
+    /// Performs one compaction run, see [`AbstractTree::compact`].
+    pub fn compact(
+        &self,
+        strategy: Arc<dyn CompactionStrategy + Send + Sync>,
+        seqno_threshold: SeqNo,
+    ) -> Task<crate::Result<()>> {
+        self.spawn(move |tree| tree.compact(strategy, seqno_threshold))
+    }
+
+    /// Performs major compaction, merging all segments into segments of `target_size`,
+    /// see [`AbstractTree::major_compact`].
+    pub fn major_compact(
+        &self,
+        target_size: u64,
+        seqno_threshold: SeqNo,
+    ) -> Task<crate::Result<()>> {
+        self.spawn(move |tree| tree.major_compact(target_size, seqno_threshold))
+    }
+}
@@ -0,0 +1,251 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::{
+    coding::Encode,
+    file::{
+        fsync_directory, rewrite_atomic, LEVELS_MANIFEST_FILE, MANIFEST_FILE, SEGMENTS_FOLDER,
+        SEQNO_TIME_MAP_FILE,
+    },
+    level_manifest::{level::Level, LevelManifest},
+    HashSet, SegmentId, Tree, TreeType,
+};
+use std::{fs::File, path::Path};
+
+/// Result of [`BackupEngine::create`]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BackupReport {
+    /// Amount of segments in the backup
+    pub segment_count: usize,
+
+    /// Amount of segments that were copied, because they were not backed up before
+    pub copied_segments: usize,
+
+    /// Amount of segment bytes that were copied
+    pub copied_bytes: u64,
+
+    /// Amount of segments that were removed from the backup,
+    /// because they are not part of the tree anymore
+    pub removed_segments: usize,
+}
+
+/// Incremental backups of a tree
+///
+/// A backup folder mirrors the on-disk layout of the tree: the manifests
+/// of the latest backup, and the segments they reference.
+///
+/// Because segments are immutable, a segment that is already part of the backup
+/// is never copied again, so each backup only copies segments that were
+/// written since the last backup (by flushes or compactions).
+///
+/// Only flushed data is backed up, so memtables need to be flushed before
+/// creating a backup (or recovered from the journal after restoring).
+///
+/// # Examples
+///
+/// ```
+/// # let folder = tempfile::tempdir()?;
+/// # let backup_folder = tempfile::tempdir()?;
+/// # let restore_folder = tempfile::tempdir()?;
+/// use lsm_tree::{AbstractTree, BackupEngine, Config};
+///
+/// let tree = Config::new(folder).open()?;
+/// tree.insert("a", "abc", 0);
+/// tree.flush_active_memtable(0)?;
+///
+/// let report = BackupEngine::create(&tree, &backup_folder)?;
+/// assert_eq!(1, report.copied_segments);
+///
+/// tree.insert("b", "abc", 1);
+/// tree.flush_active_memtable(0)?;
+///
+/// let report = BackupEngine::create(&tree, &backup_folder)?;
+/// assert_eq!(2, report.segment_count);
+/// assert_eq!(1, report.copied_segments);
+///
+/// BackupEngine::restore(&backup_folder, &restore_folder)?;
+///
+/// let restored = Config::new(&restore_folder).open()?;
+/// assert_eq!(2, restored.len(None, None)?);
+/// #
+/// # Ok::<(), lsm_tree::Error>(())
+/// ```
+pub struct BackupEngine;
+
+impl BackupEngine {
+    /// Backs up the flushed data of a tree into the given folder.
+    ///
+    /// If the folder already contains a backup of the tree, only segments
+    /// that are not part of it yet are copied, and segments that were
+    /// compacted away since are removed.
+    ///
+    /// The folder should only be used for backups of a single tree.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs, or the tree is a blob tree index.
+    pub fn create<P: AsRef<Path>>(tree: &Tree, dest: P) -> crate::Result<BackupReport> {
+        let dest = dest.as_ref();
+
+        if tree.config.tree_type == TreeType::Blob {
+            return Err(crate::Error::InvalidInput("blob trees cannot be backed up"));
+        }
+
+        log::debug!("Backing up tree {} into {}", tree.id, dest.display());
+
+        let src_folder = tree.config.path.join(SEGMENTS_FOLDER);
+        let dest_folder = dest.join(SEGMENTS_FOLDER);
+        std::fs::create_dir_all(&dest_folder)?;
+
+        // IMPORTANT: The view keeps its segments alive, so their files
+        // are not deleted by compactions while they are copied
+        let level_view = tree.level_view.load();
+
+        let mut report = BackupReport::default();
+        let mut segment_ids = HashSet::default();
+
+        for segment in level_view.levels.iter().flat_map(|level| &level.segments) {
+            let segment_id = segment.id();
+            segment_ids.insert(segment_id);
+
+            let dest_path = dest_folder.join(segment_id.to_string());
+
+            if dest_path.try_exists()? {
+                continue;
+            }
+
+            report.copied_bytes +=
+                copy_atomic(&src_folder.join(segment_id.to_string()), &dest_path)?;
+            report.copied_segments += 1;
+        }
+
+        report.segment_count = segment_ids.len();
+
+        fsync_directory(&dest_folder)?;
+
+        let seqno_time_path = tree.config.path.join(SEQNO_TIME_MAP_FILE);
+        if seqno_time_path.try_exists()? {
+            rewrite_atomic(
+                dest.join(SEQNO_TIME_MAP_FILE),
+                &std::fs::read(seqno_time_path)?,
+            )?;
+        }
+
+        rewrite_atomic(
+            dest.join(MANIFEST_FILE),
+            &std::fs::read(tree.config.path.join(MANIFEST_FILE))?,
+        )?;
+
+        let levels = level_view
+            .levels
+            .iter()
+            .map(|level| Level {
+                segments: level.segments.clone(),
+                is_disjoint: level.is_disjoint,
+            })
+            .collect::<Vec<_>>();
+
+        // IMPORTANT: The level manifest is written last, so the backup
+        // only references segments once they are fully copied
+        rewrite_atomic(dest.join(LEVELS_MANIFEST_FILE), &levels.encode_into_vec())?;
+        fsync_directory(dest)?;
+
+        drop(level_view);
+
+        // NOTE: Segments that are not referenced anymore are only removed
+        // after the new level manifest is persisted
+        for entry in std::fs::read_dir(&dest_folder)? {
+            let entry = entry?;
+
+            let is_referenced = entry
+                .file_name()
+                .to_str()
+                .and_then(|name| name.parse::<SegmentId>().ok())
+                .is_some_and(|segment_id| segment_ids.contains(&segment_id));
+
+            if !is_referenced {
+                std::fs::remove_file(entry.path())?;
+                report.removed_segments += 1;
+            }
+        }
+
+        log::debug!("Backed up tree {}: {report:?}", tree.id);
+
+        Ok(report)
+    }
+
+    /// Restores a backup into a new tree folder, which can then be opened
+    /// using [`crate::Config::open`].
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs, the source folder does not
+    /// contain a backup, or the destination folder already contains a tree.
+    pub fn restore<P: AsRef<Path>, Q: AsRef<Path>>(src: P, path: Q) -> crate::Result<()> {
+        let (src, path) = (src.as_ref(), path.as_ref());
+
+        if !src.join(LEVELS_MANIFEST_FILE).try_exists()? {
+            return Err(crate::Error::InvalidInput(
+                "folder does not contain a backup",
+            ));
+        }
+
+        if path.join(MANIFEST_FILE).try_exists()? {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                "restore folder already contains a tree",
+            )
+            .into());
+        }
+
+        log::debug!("Restoring backup {} into {}", src.display(), path.display());
+
+        let (segment_ids, _) = LevelManifest::recover_ids(src.join(LEVELS_MANIFEST_FILE))?;
+
+        let dest_folder = path.join(SEGMENTS_FOLDER);
+        std::fs::create_dir_all(&dest_folder)?;
+
+        for segment_id in segment_ids.keys() {
+            copy_atomic(
+                &src.join(SEGMENTS_FOLDER).join(segment_id.to_string()),
+                &dest_folder.join(segment_id.to_string()),
+            )?;
+        }
+
+        fsync_directory(&dest_folder)?;
+
+        for file in [SEQNO_TIME_MAP_FILE, LEVELS_MANIFEST_FILE] {
+            let file_path = src.join(file);
+
+            if file_path.try_exists()? {
+                rewrite_atomic(path.join(file), &std::fs::read(file_path)?)?;
+            }
+        }
+
+        // IMPORTANT: The tree manifest is written last, because
+        // a folder without it is not recovered as a tree
+        rewrite_atomic(
+            path.join(MANIFEST_FILE),
+            &std::fs::read(src.join(MANIFEST_FILE))?,
+        )?;
+        fsync_directory(path)?;
+
+        Ok(())
+    }
+}
+
+/// Copies a file, so the destination is either missing or complete,
+/// and returns the amount of bytes copied.
+fn copy_atomic(src: &Path, dest: &Path) -> crate::Result<u64> {
+    // NOTE: Destinations are always inside a folder
+    #[allow(clippy::expect_used)]
+    let folder = dest.parent().expect("should have a parent");
+
+    let mut temp_file = tempfile::NamedTempFile::new_in(folder)?;
+    let bytes = std::io::copy(&mut File::open(src)?, &mut temp_file)?;
+    temp_file.as_file().sync_all()?;
+    temp_file.persist(dest).map_err(std::io::Error::from)?;
+
+    Ok(bytes)
+}
@@ -2,21 +2,42 @@
 // This source code is licensed under both the Apache 2.0 and MIT License
 // (found in the LICENSE-* files in the repository)
 
-use crate::CompressionType;
+use crate::{transform::BlockTransform, CompressionType};
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use std::sync::Arc;
 use value_log::Compressor;
 
-#[derive(Copy, Clone, Debug)]
-pub struct MyCompressor(pub(crate) CompressionType);
+#[derive(Clone)]
+pub struct MyCompressor {
+    pub(crate) compression: CompressionType,
+
+    /// Block transform that is applied after compression
+    ///
+    /// If set, every blob is prefixed with the ID of the key it was written with.
+    pub(crate) transform: Option<Arc<dyn BlockTransform>>,
+}
 
 impl Default for MyCompressor {
     fn default() -> Self {
-        Self(CompressionType::None)
+        Self {
+            compression: CompressionType::None,
+            transform: None,
+        }
     }
 }
 
-impl Compressor for MyCompressor {
-    fn compress(&self, bytes: &[u8]) -> value_log::Result<Vec<u8>> {
-        Ok(match self.0 {
+impl std::fmt::Debug for MyCompressor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MyCompressor")
+            .field("compression", &self.compression)
+            .field("transform", &self.transform.is_some())
+            .finish()
+    }
+}
+
+impl MyCompressor {
+    fn compress_raw(&self, bytes: &[u8]) -> value_log::Result<Vec<u8>> {
+        Ok(match self.compression {
             CompressionType::None => bytes.into(),
 
             #[cfg(feature = "lz4")]
@@ -24,11 +45,18 @@ impl Compressor for MyCompressor {
 
             #[cfg(feature = "miniz")]
             CompressionType::Miniz(lvl) => miniz_oxide::deflate::compress_to_vec(bytes, lvl),
+
+            CompressionType::Custom(tag) => crate::codec::get(tag)
+                .and_then(|codec| codec.compress(bytes))
+                .map_err(|e| {
+                    log::error!("Failed to compress blob using custom codec {tag}: {e:?}");
+                    value_log::Error::Compress
+                })?,
         })
     }
 
-    fn decompress(&self, bytes: &[u8]) -> value_log::Result<Vec<u8>> {
-        match self.0 {
+    fn decompress_raw(&self, bytes: &[u8]) -> value_log::Result<Vec<u8>> {
+        match self.compression {
             CompressionType::None => Ok(bytes.into()),
 
             #[cfg(feature = "lz4")]
@@ -39,6 +67,52 @@ impl Compressor for MyCompressor {
             #[cfg(feature = "miniz")]
             CompressionType::Miniz(_) => miniz_oxide::inflate::decompress_to_vec(bytes)
                 .map_err(|_| value_log::Error::Decompress),
+
+            CompressionType::Custom(tag) => crate::codec::get(tag)
+                .and_then(|codec| codec.decompress(bytes))
+                .map_err(|e| {
+                    log::error!("Failed to decompress blob using custom codec {tag}: {e:?}");
+                    value_log::Error::Decompress
+                }),
         }
     }
 }
+
+impl Compressor for MyCompressor {
+    fn compress(&self, bytes: &[u8]) -> value_log::Result<Vec<u8>> {
+        let bytes = self.compress_raw(bytes)?;
+
+        let Some(transform) = &self.transform else {
+            return Ok(bytes);
+        };
+
+        let key_id = transform.current_key_id();
+
+        let encoded = transform.encode(key_id, &bytes).map_err(|e| {
+            log::error!("Failed to transform blob: {e:?}");
+            value_log::Error::Compress
+        })?;
+
+        let mut v = Vec::with_capacity(std::mem::size_of::<u32>() + encoded.len());
+        v.write_u32::<BigEndian>(key_id)?;
+        v.extend_from_slice(&encoded);
+
+        Ok(v)
+    }
+
+    fn decompress(&self, bytes: &[u8]) -> value_log::Result<Vec<u8>> {
+        let Some(transform) = &self.transform else {
+            return self.decompress_raw(bytes);
+        };
+
+        let mut reader = bytes;
+        let key_id = reader.read_u32::<BigEndian>()?;
+
+        let decoded = transform.decode(key_id, reader).map_err(|e| {
+            log::error!("Failed to reverse blob transform (key_id={key_id}): {e:?}");
+            value_log::Error::Decompress
+        })?;
+
+        self.decompress_raw(&decoded)
+    }
+}
@@ -6,26 +6,36 @@ mod compression;
 mod gc;
 pub mod index;
 pub mod value;
+mod verify;
 
 use crate::{
     coding::{Decode, Encode},
     compaction::stream::CompactionStream,
     file::BLOBS_FOLDER,
     r#abstract::{AbstractTree, RangeItem},
+    range::{prefix_to_range, range_bounds_to_owned},
+    statistics::TimedIter,
     tree::inner::MemtableId,
     value::InternalValue,
-    Config, KvPair, Memtable, Segment, SegmentId, SeqNo, Snapshot, UserKey, UserValue,
+    Config, Health, KeyRange, KvPair, MemoryUsage, Memtable, PendingWork, ScrubReport, Segment,
+    SegmentId, SeqNo, SlowOperationKind, Snapshot, Temperature, UserKey, UserValue,
 };
-use compression::MyCompressor;
+pub(crate) use compression::MyCompressor;
 use gc::{reader::GcReader, writer::GcWriter};
 use index::IndexTree;
 use std::{
     io::Cursor,
     ops::{RangeBounds, RangeFull},
-    sync::{atomic::AtomicUsize, Arc},
+    path::Path,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize},
+        Arc, Mutex,
+    },
 };
 use value::MaybeInlineValue;
-use value_log::ValueLog;
+use value_log::{ValueHandle, ValueLog};
+
+pub use verify::ConsistencyReport;
 
 fn resolve_value_handle(vlog: &ValueLog<MyCompressor>, item: RangeItem) -> RangeItem {
     use MaybeInlineValue::{Indirect, Inline};
@@ -41,8 +51,15 @@ fn resolve_value_handle(vlog: &ValueLog<MyCompressor>, item: RangeItem) -> Range
                     match vlog.get(&vhandle) {
                         Ok(Some(bytes)) => Ok((key, bytes)),
                         Err(e) => Err(e.into()),
-                        _ => {
-                            panic!("value handle ({:?} => {vhandle:?}) did not match any blob - this is a bug", String::from_utf8_lossy(&key))
+                        Ok(None) => {
+                            log::error!(
+                                "value handle ({:?} => {vhandle:?}) did not match any blob",
+                                String::from_utf8_lossy(&key)
+                            );
+                            Err(crate::Error::DanglingValueHandle {
+                                key,
+                                handle: vhandle,
+                            })
                         }
                     }
                 }
@@ -52,6 +69,14 @@ fn resolve_value_handle(vlog: &ValueLog<MyCompressor>, item: RangeItem) -> Range
     }
 }
 
+/// Seqno the staleness of blob files was last computed at,
+/// and the snapshots that were accounted for
+#[derive(Clone)]
+struct GcScan {
+    seqno: SeqNo,
+    snapshot_seqnos: Vec<SeqNo>,
+}
+
 /// A key-value-separated log-structured merge tree
 ///
 /// This tree is a composite structure, consisting of an
@@ -72,25 +97,406 @@ pub struct BlobTree {
     // TODO: maybe replace this with a nonce system
     #[doc(hidden)]
     pub pending_segments: Arc<AtomicUsize>,
+
+    /// Last GC stats scan (or relocation)
+    last_gc_scan: Arc<Mutex<Option<GcScan>>>,
+
+    /// Highest eviction seqno of flushes and compactions,
+    /// used as GC watermark by automatic blob GC
+    gc_watermark: Arc<AtomicU64>,
+
+    /// Held while automatic blob GC is running
+    gc_lock: Arc<Mutex<()>>,
 }
 
 impl BlobTree {
+    /// Writes a memtable to a new disk segment, separating large values into blob files.
+    ///
+    /// If `fsync` is false, the index segment file needs to be synced before it is registered.
+    fn write_memtable(
+        &self,
+        segment_id: SegmentId,
+        memtable: &Arc<Memtable>,
+        eviction_seqno: SeqNo,
+        fsync: bool,
+    ) -> crate::Result<Option<Segment>> {
+        use crate::{
+            file::SEGMENTS_FOLDER,
+            segment::writer::{Options, Writer as SegmentWriter},
+        };
+        use value::MaybeInlineValue;
+
+        self.index.check_journal_persisted(memtable)?;
+
+        let start = std::time::Instant::now();
+
+        self.gc_watermark
+            .fetch_max(eviction_seqno, std::sync::atomic::Ordering::AcqRel);
+
+        let lsm_segment_folder = self.index.config.path.join(SEGMENTS_FOLDER);
+
+        log::debug!("flushing memtable & performing key-value separation");
+        log::debug!("=> to LSM segments in {:?}", lsm_segment_folder);
+        log::debug!("=> to blob segment at {:?}", self.blobs.path);
+
+        let mut segment_writer = SegmentWriter::new(Options {
+            segment_id,
+            data_block_size: self.index.config.data_block_size,
+            index_block_size: self.index.config.index_block_size,
+            folder: lsm_segment_folder,
+        })?
+        .use_compression(self.index.config.compression)
+        .use_bloom_layout(self.index.config.bloom_layout)
+        .use_block_size_policy(self.index.config.block_size_policy)
+        .use_transform(self.index.config.current_transform())
+        .use_clock(self.index.config.get_clock())
+        .use_paranoid_checks(self.index.config.paranoid_checks)
+        .use_temperature(Some(Temperature::Hot))
+        .use_fsync(fsync);
+
+        segment_writer = segment_writer.use_bloom_policy(self.index.config.cap_bloom_policy(
+            crate::segment::writer::BloomConstructionPolicy::FpRate(0.0001),
+            0,
+        ));
+
+        let mut blob_writer = self.blobs.get_writer()?;
+        let mut blob_bytes_written = 0;
+
+        let iter = memtable.iter().map(Ok);
+        let compaction_filter = CompactionStream::new(iter, eviction_seqno)
+            .with_snapshots(self.index.pinned_snapshot_seqnos());
+
+        for item in compaction_filter {
+            let item = item?;
+
+            if item.is_tombstone() {
+                // NOTE: Still need to add tombstone to index tree
+                // But no blob to blob writer
+
+                // TODO: Slice::empty
+                segment_writer.write(InternalValue::new(item.key, vec![]))?;
+                continue;
+            }
+
+            let mut cursor = Cursor::new(item.value);
+
+            let value = MaybeInlineValue::decode_from(&mut cursor)?;
+            let value = match value {
+                MaybeInlineValue::Inline(value) => value,
+                indirection @ MaybeInlineValue::Indirect { .. } => {
+                    // NOTE: This is a previous indirection, just write it to index tree
+                    // without writing the blob again
+
+                    let mut serialized_indirection = vec![];
+                    indirection.encode_into(&mut serialized_indirection)?;
+
+                    segment_writer
+                        .write(InternalValue::new(item.key.clone(), serialized_indirection))?;
+
+                    continue;
+                }
+            };
+
+            // NOTE: Values are 32-bit max
+            #[allow(clippy::cast_possible_truncation)]
+            let value_size = value.len() as u32;
+
+            if value_size >= self.index.config.blob_file_separation_threshold {
+                let vhandle = blob_writer.get_next_value_handle();
+
+                let indirection = MaybeInlineValue::Indirect {
+                    vhandle,
+                    size: value_size,
+                };
+                let mut serialized_indirection = vec![];
+                indirection.encode_into(&mut serialized_indirection)?;
+
+                segment_writer
+                    .write(InternalValue::new(item.key.clone(), serialized_indirection))?;
+
+                blob_bytes_written += u64::from(blob_writer.write(&item.key.user_key, value)?);
+            } else {
+                let direct = MaybeInlineValue::Inline(value);
+                let serialized_direct = direct.encode_into_vec();
+                segment_writer.write(InternalValue::new(item.key, serialized_direct))?;
+            }
+        }
+
+        for tombstone in memtable.range_tombstones() {
+            segment_writer.write_range_tombstone(tombstone);
+        }
+
+        let _memtable_lock = self.lock_active_memtable();
+
+        log::trace!("Register blob writer into value log");
+        self.blobs.register_writer(blob_writer)?;
+
+        fail_point!(crate::failpoint::points::BLOB_AFTER_REGISTER);
+
+        log::trace!("Creating LSM-tree segment {segment_id}");
+        let segment = self.index.consume_writer(segment_id, segment_writer)?;
+
+        self.index
+            .record_flush(memtable, segment.as_ref(), blob_bytes_written, start);
+
+        // TODO: this can probably solved in a nicer way
+        if segment.is_some() {
+            // IMPORTANT: Increment the pending count
+            // so there cannot be a GC scan now, until the segment is registered
+            self.pending_segments
+                .fetch_add(1, std::sync::atomic::Ordering::Release);
+        }
+
+        Ok(segment)
+    }
+
+    /// Reads a key, resolving its value handle using the value log.
+    fn get_value(&self, key: &[u8], seqno: Option<SeqNo>) -> crate::Result<Option<UserValue>> {
+        use value::MaybeInlineValue::{Indirect, Inline};
+
+        let Some(value) = self.index.get_vhandle(key, seqno)? else {
+            return Ok(None);
+        };
+
+        let value = match value {
+            Inline(bytes) => bytes,
+            Indirect { vhandle, .. } => {
+                // Resolve indirection using value log
+                let Some(bytes) = self.blobs.get(&vhandle)? else {
+                    log::error!("value handle ({key:?} => {vhandle:?}) did not match any blob");
+                    return Err(crate::Error::DanglingValueHandle {
+                        key: key.into(),
+                        handle: vhandle,
+                    });
+                };
+                bytes
+            }
+        };
+
+        Ok(Some(value))
+    }
+
     pub(crate) fn open(config: Config) -> crate::Result<Self> {
+        // NOTE: Merge operands are not separated, but their base value may be,
+        // which value log GC would not see
+        if config.merge_operator.is_some() {
+            return Err(crate::Error::InvalidInput(
+                "merge operators are not supported by blob trees",
+            ));
+        }
+
         let path = &config.path;
 
         let vlog_path = path.join(BLOBS_FOLDER);
         let vlog_cfg = value_log::Config::<MyCompressor>::default()
             .blob_cache(config.blob_cache.clone())
             .segment_size_bytes(config.blob_file_target_size)
-            .compression(MyCompressor(config.blob_compression));
+            .compression(MyCompressor {
+                compression: config.blob_compression,
+                transform: config.block_transform.clone(),
+            });
 
         let index: IndexTree = config.open()?.into();
 
-        Ok(Self {
+        let tree = Self {
             index,
             blobs: ValueLog::open(vlog_path, vlog_cfg)?,
             pending_segments: Arc::new(AtomicUsize::new(0)),
-        })
+            last_gc_scan: Arc::default(),
+            gc_watermark: Arc::default(),
+            gc_lock: Arc::default(),
+        };
+
+        if let Some(period) = tree.index.config.scrub_period {
+            crate::scrub::spawn(&tree.index, Some(tree.blobs.clone()), period);
+        }
+
+        if let Some(scheduler) = &tree.index.compaction_scheduler {
+            // IMPORTANT: Only clone the blob tree's parts besides the index,
+            // so the workers do not keep the index tree alive
+            let blobs = tree.blobs.clone();
+            let pending_segments = tree.pending_segments.clone();
+            let last_gc_scan = tree.last_gc_scan.clone();
+            let gc_watermark = tree.gc_watermark.clone();
+            let gc_lock = tree.gc_lock.clone();
+
+            crate::compaction::scheduler::spawn(&tree.index, scheduler, move |index| {
+                crate::AnyTree::Blob(Self {
+                    index: index.into(),
+                    blobs: blobs.clone(),
+                    pending_segments: pending_segments.clone(),
+                    last_gc_scan: last_gc_scan.clone(),
+                    gc_watermark: gc_watermark.clone(),
+                    gc_lock: gc_lock.clone(),
+                })
+            });
+        }
+
+        Ok(tree)
+    }
+
+    /// Writes the tree, as seen by a snapshot at the given seqno, into a new folder.
+    pub(crate) fn export_snapshot<P: AsRef<Path>>(
+        &self,
+        path: P,
+        seqno: SeqNo,
+    ) -> crate::Result<()> {
+        use crate::file::hard_link_folder;
+
+        let config = self.index.export_config(path)?;
+
+        // IMPORTANT: Lock memtable to prevent flushes and blob GC from changing the tree
+        let active_memtable = self.index.read_lock_active_memtable();
+
+        while self
+            .pending_segments
+            .load(std::sync::atomic::Ordering::Acquire)
+            > 0
+        {
+            // IMPORTANT: Busy wait until all segments in-flight are committed
+            // to the tree
+        }
+
+        let sealed_memtables = self
+            .index
+            .sealed_memtables
+            .read()
+            .expect("lock is poisoned");
+
+        let level_view = self.index.level_view.load();
+
+        // NOTE: The value log never modifies files in place, so all of it can be hard linked
+        //
+        // Blobs that are not visible to the snapshot become stale in the exported tree
+        hard_link_folder(
+            self.index.config.path.join(BLOBS_FOLDER),
+            config.path.join(BLOBS_FOLDER),
+        )?;
+
+        let dest = config.open_as_blob_tree()?;
+        self.index
+            .export_segments(&dest.index, &level_view, seqno)?;
+        crate::Tree::export_memtables(&dest.index, &active_memtable, &sealed_memtables, seqno);
+
+        drop(sealed_memtables);
+        drop(active_memtable);
+
+        dest.flush_active_memtable(0)?;
+
+        Ok(())
+    }
+
+    /// Checks that the index tree and the value log are consistent.
+    ///
+    /// Every version in the index that points into the value log is resolved,
+    /// and every blob in the value log is checked for a referencing version.
+    /// This reads all blobs, so it may take a while for large trees.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub fn verify_references(&self) -> crate::Result<ConsistencyReport> {
+        use MaybeInlineValue::Indirect;
+
+        // IMPORTANT: Lock memtable to prevent flushes from changing the segments
+        let active_memtable = self.index.read_lock_active_memtable();
+
+        while self
+            .pending_segments
+            .load(std::sync::atomic::Ordering::Acquire)
+            > 0
+        {
+            // IMPORTANT: Busy wait until all segments in-flight are committed
+            // to the tree
+        }
+
+        let sealed_memtables = self
+            .index
+            .sealed_memtables
+            .read()
+            .expect("lock is poisoned");
+
+        let level_view = self.index.level_view.load();
+
+        // NOTE: Look at all versions, not only the latest ones,
+        // because snapshots may still read older versions
+        let items = active_memtable
+            .iter()
+            .map(Ok)
+            .chain(
+                sealed_memtables
+                    .iter()
+                    .flat_map(|(_, memtable)| memtable.iter().map(Ok)),
+            )
+            .chain(level_view.iter().flat_map(Segment::iter));
+
+        let mut report = ConsistencyReport::default();
+
+        // Number of references to a key in a blob file
+        let mut references = crate::HashMap::<(SegmentId, UserKey), usize>::default();
+
+        for item in items {
+            let item = item?;
+
+            if item.is_tombstone() {
+                continue;
+            }
+
+            let mut cursor = Cursor::new(item.value);
+
+            let Indirect { vhandle, .. } = MaybeInlineValue::decode_from(&mut cursor)? else {
+                continue;
+            };
+
+            let is_resolvable = match self.blobs.get(&vhandle) {
+                Ok(value) => value.is_some(),
+
+                // NOTE: A handle pointing to some garbage offset
+                // cannot be decoded or runs into the end of the file
+                Err(value_log::Error::Decode(_) | value_log::Error::Decompress) => false,
+                Err(value_log::Error::Io(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+                    false
+                }
+
+                Err(e) => return Err(e.into()),
+            };
+
+            if is_resolvable {
+                *references
+                    .entry((vhandle.segment_id, item.key.user_key))
+                    .or_default() += 1;
+            } else {
+                log::error!(
+                    "value handle ({:?}:{} => {vhandle:?}) did not match any blob",
+                    String::from_utf8_lossy(&item.key.user_key),
+                    item.key.seqno,
+                );
+
+                report
+                    .dangling_handles
+                    .push((item.key.user_key, item.key.seqno, vhandle));
+            }
+        }
+
+        // NOTE: Scan every blob file on its own, the value log's merge reader
+        // would skip older blobs of the same key
+        for blob_file in self.blobs.manifest.list_segments() {
+            for blob in blob_file.scan()? {
+                let (key, _, _) = blob?;
+
+                match references.get_mut(&(blob_file.id, key.clone())) {
+                    Some(count) if *count > 0 => *count -= 1,
+                    _ => report.unreferenced_blobs.push((blob_file.id, key)),
+                }
+            }
+        }
+
+        // NOTE: Only allow flushes again after scanning the value log,
+        // otherwise newly flushed blobs would appear as unreferenced
+        drop(sealed_memtables);
+        drop(active_memtable);
+
+        Ok(report)
     }
 
     /// Scans the index tree, collecting statistics about
@@ -125,13 +531,22 @@ impl BlobTree {
             // to the tree
         }
 
-        let iter = self
-            .index
-            .create_internal_range::<&[u8], RangeFull>(&.., Some(seqno), None);
+        // NOTE: Blobs that are referenced by snapshots are still alive
+        let snapshot_seqnos = self.index.pinned_snapshot_seqnos();
+
+        let iter = std::iter::once(seqno)
+            .chain(snapshot_seqnos.iter().copied())
+            .flat_map(|seqno| {
+                self.index
+                    .create_internal_range::<&[u8], RangeFull>(&.., Some(seqno), None)
+            });
 
         // Stores the max seqno of every blob file
         let mut seqno_map = crate::HashMap::<SegmentId, SeqNo>::default();
 
+        // NOTE: A version can be visible to multiple snapshots, but may only be counted once
+        let mut seen = crate::HashSet::<ValueHandle>::default();
+
         let result = self
             .blobs
             .scan_for_stats(iter.filter_map(|kv| {
@@ -150,6 +565,10 @@ impl BlobTree {
 
                 match value {
                     Indirect { vhandle, size } => {
+                        if !seen.insert(vhandle.clone()) {
+                            return None;
+                        }
+
                         seqno_map
                             .entry(vhandle.segment_id)
                             .and_modify(|x| *x = (*x).max(kv.key.seqno))
@@ -183,25 +602,130 @@ impl BlobTree {
             }
         }
 
+        *self.last_gc_scan.lock().expect("lock is poisoned") = Some(GcScan {
+            seqno,
+            snapshot_seqnos,
+        });
+
         result
     }
 
+    /// Returns `true` if the staleness of blob files is still accurate, because
+    /// no snapshot was opened after the last GC stats scan below its seqno.
+    ///
+    /// Such a snapshot may read blobs that the scan counted as stale.
+    fn is_gc_scan_current(&self) -> bool {
+        let Some(scan) = self.last_gc_scan.lock().expect("lock is poisoned").clone() else {
+            return true;
+        };
+
+        self.index
+            .pinned_snapshot_seqnos()
+            .into_iter()
+            .all(|seqno| seqno >= scan.seqno || scan.snapshot_seqnos.binary_search(&seqno).is_ok())
+    }
+
+    /// Drops all stale blob files, unless a snapshot may still read them.
+    fn drop_stale_blob_files(&self) -> crate::Result<u64> {
+        if !self.is_gc_scan_current() {
+            log::debug!("Snapshot was opened after GC stats scan, not dropping stale blob files");
+            return Ok(0);
+        }
+
+        self.blobs.drop_stale_segments().map_err(Into::into)
+    }
+
+    /// Relocates the blobs of the blob files picked by the strategy, and drops stale blob files.
+    ///
+    /// Blob files are relocated one at a time, so the memtable is only write locked
+    /// while a single blob file is rewritten. The rewrite is throttled by the
+    /// rate limiter of the config, see [`Config::rate_limiter`].
     pub fn apply_gc_strategy(
         &self,
         strategy: &impl value_log::GcStrategy<MyCompressor>,
         seqno: SeqNo,
     ) -> crate::Result<u64> {
-        // IMPORTANT: Write lock memtable to avoid read skew
-        let memtable_lock = self.index.lock_active_memtable();
+        let blob_file_ids = strategy.pick(&self.blobs);
 
-        self.blobs.apply_gc_strategy(
-            strategy,
-            &GcReader::new(&self.index, &memtable_lock),
-            GcWriter::new(seqno, &memtable_lock),
-        )?;
+        for blob_file_id in blob_file_ids {
+            let Some(blob_file) = self.blobs.manifest.get_segment(blob_file_id) else {
+                continue;
+            };
 
-        // NOTE: We still have the memtable lock, can't use gc_drop_stale because recursive locking
-        self.blobs.drop_stale_segments().map_err(Into::into)
+            // NOTE: Wait before locking the memtable, so writes are not blocked while throttled
+            if let Some(rate_limiter) = &self.index.config.rate_limiter {
+                rate_limiter.request(blob_file.meta.compressed_bytes);
+            }
+
+            // IMPORTANT: Write lock memtable to avoid read skew
+            let memtable_lock = self.index.lock_active_memtable();
+
+            // IMPORTANT: Relocation only keeps the latest version of every key,
+            // so it would lose blobs that are only referenced by snapshots
+            if !self.index.pinned_snapshot_seqnos().is_empty() {
+                log::debug!("Snapshots are open, skipping blob relocation");
+                break;
+            }
+
+            self.blobs.rollover(
+                &[blob_file_id],
+                &GcReader::new(&self.index, &memtable_lock),
+                GcWriter::new(seqno, &memtable_lock),
+            )?;
+
+            // NOTE: Relocated blobs are only referenced by versions at `seqno`,
+            // a snapshot below it (opened while relocating) still reads the old blob files
+            *self.last_gc_scan.lock().expect("lock is poisoned") = Some(GcScan {
+                seqno,
+                snapshot_seqnos: Vec::new(),
+            });
+        }
+
+        self.gc_drop_stale()
+    }
+
+    /// Runs blob GC if the space amplification of the value log
+    /// exceeds the target, see [`Config::blob_space_amp_target`].
+    fn maybe_run_gc(&self) -> crate::Result<()> {
+        let (Some(target), Some(seqno)) = (
+            self.index.config.blob_space_amp_target,
+            &self.index.config.blob_gc_seqno,
+        ) else {
+            return Ok(());
+        };
+
+        // NOTE: The stats scan waits for in-flight segments, instead
+        // the flush that registers them will run GC afterwards
+        if self
+            .pending_segments
+            .load(std::sync::atomic::Ordering::Acquire)
+            > 0
+        {
+            return Ok(());
+        }
+
+        // NOTE: If another flush or compaction is already running GC, there is nothing to do
+        let Ok(_lock) = self.gc_lock.try_lock() else {
+            return Ok(());
+        };
+
+        let gc_watermark = self.gc_watermark.load(std::sync::atomic::Ordering::Acquire);
+        self.gc_scan_stats(seqno.get(), gc_watermark)?;
+
+        let space_amp = self.blobs.space_amp();
+
+        if space_amp <= target {
+            // NOTE: Blob files may have become fully stale without exceeding the target
+            self.gc_drop_stale()?;
+            return Ok(());
+        }
+
+        log::debug!("Value log space amp {space_amp} exceeds target {target}, running blob GC");
+
+        let strategy = value_log::SpaceAmpStrategy::new(target);
+        self.apply_gc_strategy(&strategy, seqno.next())?;
+
+        Ok(())
     }
 
     /// Drops all stale blob segment files
@@ -210,11 +734,14 @@ impl BlobTree {
         // IMPORTANT: Write lock memtable to avoid read skew
         let _lock = self.index.lock_active_memtable();
 
-        self.blobs.drop_stale_segments().map_err(Into::into)
+        self.drop_stale_blob_files()
     }
 
     #[doc(hidden)]
     pub fn flush_active_memtable(&self, eviction_seqno: SeqNo) -> crate::Result<Option<Segment>> {
+        self.index
+            .check_journal_persisted(&self.index.read_lock_active_memtable())?;
+
         let Some((segment_id, yanked_memtable)) = self.index.rotate_memtable() else {
             return Ok(None);
         };
@@ -230,6 +757,10 @@ impl BlobTree {
 }
 
 impl AbstractTree for BlobTree {
+    fn memory_usage(&self) -> MemoryUsage {
+        self.index.memory_usage()
+    }
+
     fn blob_file_count(&self) -> usize {
         self.blobs.segment_count()
     }
@@ -256,15 +787,22 @@ impl AbstractTree for BlobTree {
         self.index.sealed_memtable_count()
     }
 
+    fn sealed_memtables_size(&self) -> u64 {
+        self.index.sealed_memtables_size()
+    }
+
     fn is_first_level_disjoint(&self) -> bool {
         self.index.is_first_level_disjoint()
     }
 
-    #[doc(hidden)]
-    fn verify(&self) -> crate::Result<usize> {
-        let index_tree_sum = self.index.verify()?;
-        let vlog_sum = self.blobs.verify()?;
-        Ok(index_tree_sum + vlog_sum)
+    fn verify(&self) -> crate::Result<crate::VerifyReport> {
+        let mut report = self.index.verify()?;
+        report.corrupt_blob_count = self.blobs.verify()?;
+        Ok(report)
+    }
+
+    fn scrub(&self) -> crate::Result<ScrubReport> {
+        crate::scrub::scrub(&self.index, Some(&self.blobs))
     }
 
     fn keys(
@@ -275,6 +813,25 @@ impl AbstractTree for BlobTree {
         self.index.keys(seqno, index)
     }
 
+    fn keys_range<K: AsRef<[u8]>, R: RangeBounds<K>>(
+        &self,
+        range: R,
+        seqno: Option<SeqNo>,
+        index: Option<Arc<Memtable>>,
+    ) -> Box<dyn DoubleEndedIterator<Item = crate::Result<UserKey>> + 'static> {
+        // NOTE: The index tree contains all keys, so the value log is not needed
+        self.index.keys_range(range, seqno, index)
+    }
+
+    fn keys_prefix<K: AsRef<[u8]>>(
+        &self,
+        prefix: K,
+        seqno: Option<SeqNo>,
+        index: Option<Arc<Memtable>>,
+    ) -> Box<dyn DoubleEndedIterator<Item = crate::Result<UserKey>> + 'static> {
+        self.index.keys_prefix(prefix, seqno, index)
+    }
+
     fn values(
         &self,
         seqno: Option<SeqNo>,
@@ -289,107 +846,57 @@ impl AbstractTree for BlobTree {
         memtable: &Arc<Memtable>,
         eviction_seqno: SeqNo,
     ) -> crate::Result<Option<Segment>> {
-        use crate::{
-            file::SEGMENTS_FOLDER,
-            segment::writer::{Options, Writer as SegmentWriter},
-        };
-        use value::MaybeInlineValue;
-
-        let lsm_segment_folder = self.index.config.path.join(SEGMENTS_FOLDER);
-
-        log::debug!("flushing memtable & performing key-value separation");
-        log::debug!("=> to LSM segments in {:?}", lsm_segment_folder);
-        log::debug!("=> to blob segment at {:?}", self.blobs.path);
-
-        let mut segment_writer = SegmentWriter::new(Options {
-            segment_id,
-            data_block_size: self.index.config.data_block_size,
-            index_block_size: self.index.config.index_block_size,
-            folder: lsm_segment_folder,
-        })?
-        .use_compression(self.index.config.compression);
-
-        segment_writer = segment_writer.use_bloom_policy(
-            crate::segment::writer::BloomConstructionPolicy::FpRate(0.0001),
-        );
-
-        let mut blob_writer = self.blobs.get_writer()?;
-
-        let iter = memtable.iter().map(Ok);
-        let compaction_filter = CompactionStream::new(iter, eviction_seqno);
+        let result = self.write_memtable(segment_id, memtable, eviction_seqno, true);
+        self.index.record_background_error(result)
+    }
 
-        for item in compaction_filter {
-            let item = item?;
+    fn flush_memtables(
+        &self,
+        memtables: &[(SegmentId, Arc<Memtable>)],
+        eviction_seqno: SeqNo,
+    ) -> crate::Result<Vec<Segment>> {
+        let mut segments = Vec::with_capacity(memtables.len());
 
-            if item.is_tombstone() {
-                // NOTE: Still need to add tombstone to index tree
-                // But no blob to blob writer
+        // NOTE: Blob files are still synced by the value log one by one,
+        // only the index segments are synced in a batch
+        for (segment_id, memtable) in memtables {
+            let result = self.write_memtable(*segment_id, memtable, eviction_seqno, false);
 
-                // TODO: Slice::empty
-                segment_writer.write(InternalValue::new(item.key, vec![]))?;
-                continue;
+            if let Some(segment) = self.index.record_background_error(result)? {
+                segments.push(segment);
             }
+        }
 
-            let mut cursor = Cursor::new(item.value);
-
-            let value = MaybeInlineValue::decode_from(&mut cursor)?;
-            let value = match value {
-                MaybeInlineValue::Inline(value) => value,
-                indirection @ MaybeInlineValue::Indirect { .. } => {
-                    // NOTE: This is a previous indirection, just write it to index tree
-                    // without writing the blob again
-
-                    let mut serialized_indirection = vec![];
-                    indirection.encode_into(&mut serialized_indirection)?;
-
-                    segment_writer
-                        .write(InternalValue::new(item.key.clone(), serialized_indirection))?;
-
-                    continue;
-                }
-            };
-
-            // NOTE: Values are 32-bit max
-            #[allow(clippy::cast_possible_truncation)]
-            let value_size = value.len() as u32;
-
-            if value_size >= self.index.config.blob_file_separation_threshold {
-                let vhandle = blob_writer.get_next_value_handle();
-
-                let indirection = MaybeInlineValue::Indirect {
-                    vhandle,
-                    size: value_size,
-                };
-                let mut serialized_indirection = vec![];
-                indirection.encode_into(&mut serialized_indirection)?;
+        let result = self.index.sync_segment_files(&segments);
+        self.index.record_background_error(result)?;
 
-                segment_writer
-                    .write(InternalValue::new(item.key.clone(), serialized_indirection))?;
+        self.register_segments(&segments)?;
 
-                blob_writer.write(&item.key.user_key, value)?;
-            } else {
-                let direct = MaybeInlineValue::Inline(value);
-                let serialized_direct = direct.encode_into_vec();
-                segment_writer.write(InternalValue::new(item.key, serialized_direct))?;
-            }
-        }
+        Ok(segments)
+    }
 
-        let _memtable_lock = self.lock_active_memtable();
+    fn flush_memtables_coalesced(
+        &self,
+        memtables: &[(MemtableId, Arc<Memtable>)],
+        eviction_seqno: SeqNo,
+    ) -> crate::Result<Option<Segment>> {
+        let Some((segment_id, memtable)) = crate::tree::coalesce_memtables(memtables) else {
+            return Ok(None);
+        };
 
-        log::trace!("Register blob writer into value log");
-        self.blobs.register_writer(blob_writer)?;
+        let segment = self.flush_memtable(segment_id, &memtable, eviction_seqno)?;
 
-        log::trace!("Creating LSM-tree segment {segment_id}");
-        let segment = self.index.consume_writer(segment_id, segment_writer)?;
+        let memtable_ids = memtables.iter().map(|(id, _)| *id).collect::<Vec<_>>();
+        self.index
+            .register_segments_replacing(segment.as_slice(), &memtable_ids)?;
 
-        // TODO: this can probably solved in a nicer way
         if segment.is_some() {
-            // IMPORTANT: Increment the pending count
-            // so there cannot be a GC scan now, until the segment is registered
             self.pending_segments
-                .fetch_add(1, std::sync::atomic::Ordering::Release);
+                .fetch_sub(1, std::sync::atomic::Ordering::Release);
         }
 
+        self.index.record_background_error(self.maybe_run_gc())?;
+
         Ok(segment)
     }
 
@@ -408,7 +915,7 @@ impl AbstractTree for BlobTree {
         self.pending_segments
             .fetch_sub(segments.len(), std::sync::atomic::Ordering::Release);
 
-        Ok(())
+        self.index.record_background_error(self.maybe_run_gc())
     }
 
     fn lock_active_memtable(&self) -> std::sync::RwLockWriteGuard<'_, Memtable> {
@@ -423,12 +930,70 @@ impl AbstractTree for BlobTree {
         self.index.add_sealed_memtable(id, memtable);
     }
 
+    fn create_named_snapshot(&self, name: &str, seqno: SeqNo) -> crate::Result<Snapshot> {
+        self.index.create_named_snapshot(name, seqno)?;
+        Ok(self.snapshot(seqno))
+    }
+
+    fn named_snapshot(&self, name: &str) -> Option<Snapshot> {
+        self.index
+            .get_named_snapshot_seqno(name)
+            .map(|seqno| self.snapshot(seqno))
+    }
+
+    fn release_named_snapshot(&self, name: &str) -> crate::Result<bool> {
+        self.index.release_named_snapshot(name)
+    }
+
+    fn list_named_snapshots(&self) -> Vec<(String, SeqNo)> {
+        self.index.list_named_snapshots()
+    }
+
+    fn mark_journal_persisted(&self, seqno: SeqNo) {
+        self.index.mark_journal_persisted(seqno);
+    }
+
     fn compact(
         &self,
         strategy: Arc<dyn crate::compaction::CompactionStrategy>,
         seqno_threshold: SeqNo,
     ) -> crate::Result<()> {
-        self.index.compact(strategy, seqno_threshold)
+        self.index.compact(strategy, seqno_threshold)?;
+
+        self.gc_watermark
+            .fetch_max(seqno_threshold, std::sync::atomic::Ordering::AcqRel);
+
+        self.index.record_background_error(self.maybe_run_gc())
+    }
+
+    fn clear(&self) -> crate::Result<()> {
+        self.index.clear()?;
+
+        // IMPORTANT: Write lock memtable to avoid read skew
+        let _lock = self.index.lock_active_memtable();
+
+        // NOTE: The index is empty, so scanning it marks every blob file as stale
+        self.blobs.scan_for_stats(std::iter::empty())?;
+        self.blobs.drop_stale_segments()?;
+
+        Ok(())
+    }
+
+    fn drop_prefix<K: AsRef<[u8]>>(&self, prefix: K, seqno: SeqNo) -> crate::Result<usize> {
+        // NOTE: Blob files that are not referenced anymore become stale in the next GC scan
+        self.index.drop_prefix(prefix, seqno)
+    }
+
+    fn pending_work(&self, strategy: &dyn crate::compaction::CompactionStrategy) -> PendingWork {
+        self.index.pending_work(strategy)
+    }
+
+    fn health(&self) -> Health {
+        Health {
+            blob_stale_bytes: self.blobs.manifest.stale_bytes(),
+            blob_stale_ratio: self.blobs.manifest.stale_ratio(),
+            ..self.index.health()
+        }
     }
 
     fn get_next_segment_id(&self) -> SegmentId {
@@ -459,6 +1024,22 @@ impl AbstractTree for BlobTree {
         self.index.segment_count()
     }
 
+    fn estimated_unique_keys(&self) -> crate::Result<u64> {
+        self.index.estimated_unique_keys()
+    }
+
+    fn estimated_unique_keys_in_range(&self, key_range: &KeyRange) -> crate::Result<u64> {
+        self.index.estimated_unique_keys_in_range(key_range)
+    }
+
+    fn overlapping_segment_ids(&self, key_range: &KeyRange) -> Vec<Vec<SegmentId>> {
+        self.index.overlapping_segment_ids(key_range)
+    }
+
+    fn segment_access_stats(&self) -> Vec<crate::SegmentAccessStats> {
+        self.index.segment_access_stats()
+    }
+
     fn first_level_segment_count(&self) -> usize {
         self.index.first_level_segment_count()
     }
@@ -484,6 +1065,28 @@ impl AbstractTree for BlobTree {
         self.index.disk_space() + self.blobs.manifest.disk_space_used()
     }
 
+    #[allow(clippy::cast_precision_loss)]
+    fn space_amp(&self) -> f32 {
+        let index_bytes = self.index.disk_space() as f32;
+        let blob_bytes = self.blobs.manifest.disk_space_used() as f32;
+
+        // NOTE: Both estimates are 0.0 if empty, which means there is no live data
+        let live_bytes = [
+            (index_bytes, self.index.space_amp()),
+            (blob_bytes, self.blobs.space_amp()),
+        ]
+        .into_iter()
+        .filter(|(_, amp)| *amp > 0.0)
+        .map(|(bytes, amp)| bytes / amp)
+        .sum::<f32>();
+
+        if live_bytes == 0.0 {
+            return 0.0;
+        }
+
+        (index_bytes + blob_bytes) / live_bytes
+    }
+
     fn get_highest_memtable_seqno(&self) -> Option<SeqNo> {
         self.index.get_highest_memtable_seqno()
     }
@@ -492,12 +1095,40 @@ impl AbstractTree for BlobTree {
         self.index.get_highest_persisted_seqno()
     }
 
+    fn wait_for_persisted(&self, seqno: SeqNo, timeout: Option<std::time::Duration>) -> bool {
+        self.index.wait_for_persisted(seqno, timeout)
+    }
+
     fn snapshot(&self, seqno: SeqNo) -> Snapshot {
         use crate::AnyTree::Blob;
 
         Snapshot::new(Blob(self.clone()), seqno)
     }
 
+    fn cursor(&self, seqno: Option<SeqNo>) -> crate::Cursor {
+        crate::Cursor::new(crate::AnyTree::Blob(self.clone()), seqno)
+    }
+
+    fn compaction_scheduler(&self) -> Option<&crate::compaction::CompactionScheduler> {
+        self.index.compaction_scheduler()
+    }
+
+    fn seqno_at_time(&self, time: std::time::Duration) -> Option<SeqNo> {
+        self.index.seqno_at_time(time)
+    }
+
+    fn register_snapshot(&self, seqno: SeqNo) {
+        self.index.register_snapshot(seqno);
+    }
+
+    fn deregister_snapshot(&self, seqno: SeqNo) -> bool {
+        self.index.deregister_snapshot(seqno)
+    }
+
+    fn lowest_snapshot_seqno(&self) -> Option<SeqNo> {
+        self.index.lowest_snapshot_seqno()
+    }
+
     fn range<K: AsRef<[u8]>, R: RangeBounds<K>>(
         &self,
         range: R,
@@ -505,11 +1136,20 @@ impl AbstractTree for BlobTree {
         index: Option<Arc<Memtable>>,
     ) -> Box<dyn DoubleEndedIterator<Item = crate::Result<KvPair>> + 'static> {
         let vlog = self.blobs.clone();
+        let bounds = range_bounds_to_owned(&range);
+
         Box::new(
-            self.index
-                .0
-                .create_range(&range, seqno, index)
-                .map(move |item| resolve_value_handle(&vlog, item)),
+            self.index.slow_scan_log(
+                TimedIter::new(
+                    self.index
+                        .0
+                        .create_range(&bounds, seqno, index)
+                        .map(move |item| resolve_value_handle(&vlog, item)),
+                    self.index.config.statistics.clone(),
+                ),
+                bounds,
+                seqno,
+            ),
         )
     }
 
@@ -520,11 +1160,20 @@ impl AbstractTree for BlobTree {
         index: Option<Arc<Memtable>>,
     ) -> Box<dyn DoubleEndedIterator<Item = crate::Result<KvPair>> + 'static> {
         let vlog = self.blobs.clone();
+        let bounds = prefix_to_range(prefix.as_ref());
+
         Box::new(
-            self.index
-                .0
-                .create_prefix(prefix, seqno, index)
-                .map(move |item| resolve_value_handle(&vlog, item)),
+            self.index.slow_scan_log(
+                TimedIter::new(
+                    self.index
+                        .0
+                        .create_range(&bounds, seqno, index)
+                        .map(move |item| resolve_value_handle(&vlog, item)),
+                    self.index.config.statistics.clone(),
+                ),
+                bounds,
+                seqno,
+            ),
         )
     }
 
@@ -551,26 +1200,94 @@ impl AbstractTree for BlobTree {
         key: K,
         seqno: Option<SeqNo>,
     ) -> crate::Result<Option<crate::UserValue>> {
-        use value::MaybeInlineValue::{Indirect, Inline};
-
         let key = key.as_ref();
+        let start = std::time::Instant::now();
 
-        let Some(value) = self.index.get_vhandle(key, seqno)? else {
-            return Ok(None);
-        };
+        let (value, segments_read) = self.index.trace_reads(|| self.get_value(key, seqno));
+        let value = value?;
 
-        match value {
-            Inline(bytes) => Ok(Some(bytes)),
-            Indirect { vhandle, .. } => {
-                // Resolve indirection using value log
-                match self.blobs.get(&vhandle)? {
-                    Some(bytes) => Ok(Some(bytes)),
-                    None => {
-                        panic!("value handle ({key:?} => {vhandle:?}) did not match any blob - this is a bug")
+        self.index.config.statistics.record_get(start);
+        self.index.check_slow_get(
+            || SlowOperationKind::Get { key: key.into() },
+            seqno,
+            start,
+            segments_read,
+        );
+
+        Ok(value)
+    }
+
+    fn get_many<K: AsRef<[u8]>, I: IntoIterator<Item = K>>(
+        &self,
+        keys: I,
+        seqno: Option<SeqNo>,
+    ) -> crate::Result<Vec<Option<crate::UserValue>>> {
+        use value::MaybeInlineValue::{Indirect, Inline};
+
+        let start = std::time::Instant::now();
+
+        let (entries, segments_read) = self
+            .index
+            .trace_reads(|| self.index.get_internal_entries(keys, seqno));
+        let entries = entries?;
+
+        let mut values = Vec::with_capacity(entries.len());
+        let mut indirections = vec![];
+
+        for (idx, entry) in entries.into_iter().enumerate() {
+            let value = match entry {
+                Some(entry) => match MaybeInlineValue::from_slice(&entry.value)? {
+                    Inline(bytes) => Some(bytes),
+                    Indirect { vhandle, .. } => {
+                        indirections.push((vhandle, idx, entry.key.user_key));
+                        None
                     }
+                },
+                None => None,
+            };
+
+            values.push(value);
+        }
+
+        // NOTE: Read the blobs in file order, so neighbouring blobs are read
+        // sequentially, and blobs that are referenced multiple times are only read once
+        indirections.sort_by_key(|(vhandle, _, _)| (vhandle.segment_id, vhandle.offset));
+
+        let mut last_blob: Option<(value_log::ValueHandle, crate::UserValue)> = None;
+
+        for (vhandle, idx, key) in indirections {
+            let bytes = match &last_blob {
+                Some((last_vhandle, bytes)) if *last_vhandle == vhandle => bytes.clone(),
+                _ => {
+                    let Some(bytes) = self.blobs.get(&vhandle)? else {
+                        log::error!("value handle ({key:?} => {vhandle:?}) did not match any blob");
+                        return Err(crate::Error::DanglingValueHandle {
+                            key,
+                            handle: vhandle,
+                        });
+                    };
+
+                    last_blob = Some((vhandle, bytes.clone()));
+                    bytes
                 }
+            };
+
+            if let Some(slot) = values.get_mut(idx) {
+                *slot = Some(bytes);
             }
         }
+
+        self.index.config.statistics.record_get(start);
+        self.index.check_slow_get(
+            || SlowOperationKind::GetMany {
+                key_count: values.len(),
+            },
+            seqno,
+            start,
+            segments_read,
+        );
+
+        Ok(values)
     }
 
     fn remove<K: Into<UserKey>>(&self, key: K, seqno: SeqNo) -> (u32, u32) {
@@ -580,4 +1297,37 @@ impl AbstractTree for BlobTree {
     fn remove_weak<K: Into<UserKey>>(&self, key: K, seqno: SeqNo) -> (u32, u32) {
         self.index.remove_weak(key, seqno)
     }
+
+    fn remove_range<K: Into<UserKey>>(
+        &self,
+        range: std::ops::Range<K>,
+        seqno: SeqNo,
+    ) -> (u32, u32) {
+        self.index.remove_range(range, seqno)
+    }
+
+    fn try_insert<K: Into<UserKey>, V: Into<UserValue>>(
+        &self,
+        key: K,
+        value: V,
+        seqno: SeqNo,
+    ) -> crate::Result<(u32, u32)> {
+        use value::MaybeInlineValue;
+
+        // NOTE: See insert
+        let item = MaybeInlineValue::Inline(value.into());
+
+        let mut value = vec![];
+        item.encode_into(&mut value)?;
+
+        self.index.try_insert(key, value, seqno)
+    }
+
+    fn try_remove<K: Into<UserKey>>(&self, key: K, seqno: SeqNo) -> crate::Result<(u32, u32)> {
+        self.index.try_remove(key, seqno)
+    }
+
+    fn try_remove_weak<K: Into<UserKey>>(&self, key: K, seqno: SeqNo) -> crate::Result<(u32, u32)> {
+        self.index.try_remove_weak(key, seqno)
+    }
 }
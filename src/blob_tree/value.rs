@@ -42,27 +42,21 @@ const TAG_INLINE: u8 = 0;
 const TAG_INDIRECT: u8 = 1;
 
 impl MaybeInlineValue {
+    /// Decodes the value without copying inlined values.
     pub fn from_slice(bytes: &Slice) -> Result<Self, DecodeError> {
-        let tag = *bytes.first().expect("vhandle bytes should not be empty");
+        let mut reader = &**bytes;
+        let tag = reader.read_u8()?;
 
         match tag {
             TAG_INLINE => {
-                // TODO: 3.0.0 because the length field is varint encoded
-                // TODO: we need to get the amount of bytes the integer needs
-                // TODO: maybe not use varint encoding here... (breaking)
-                let len_size = {
-                    let mut reader = bytes.get(1..).expect("see above");
-                    let len = reader.read_u32_varint()?;
-                    match len {
-                        0..=0x7F => 1,                  // Fits in 7 bits
-                        0x80..=0x3FFF => 2,             // Fits in 14 bits
-                        0x4000..=0x001F_FFFF => 3,      // Fits in 21 bits
-                        0x0020_0000..=0x0FFF_FFFF => 4, // Fits in 28 bits
-                        _ => 5,                         // Fits in 35 bits
-                    }
-                };
-                let slice = bytes.slice((1 + len_size)..);
-                Ok(Self::Inline(slice))
+                let len = reader.read_u32_varint()? as usize;
+
+                if reader.len() < len {
+                    return Err(DecodeError::InvalidLength("MaybeInlineValue"));
+                }
+
+                let start = bytes.len() - reader.len();
+                Ok(Self::Inline(bytes.slice(start..(start + len))))
             }
             TAG_INDIRECT => {
                 let mut reader = &**bytes;
@@ -114,3 +108,38 @@ impl Decode for MaybeInlineValue {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn maybe_inline_value_from_slice() -> Result<(), DecodeError> {
+        let value = MaybeInlineValue::Inline(Slice::from("abc".repeat(100)));
+        let bytes = Slice::from(value.encode_into_vec());
+
+        let MaybeInlineValue::Inline(decoded) = MaybeInlineValue::from_slice(&bytes)? else {
+            panic!("should be inline");
+        };
+        assert_eq!(&*decoded, "abc".repeat(100).as_bytes());
+
+        Ok(())
+    }
+
+    #[test]
+    fn maybe_inline_value_from_slice_malformed() {
+        assert!(MaybeInlineValue::from_slice(&Slice::from(vec![])).is_err());
+
+        // NOTE: Claims 10 bytes, but only has 2
+        assert!(matches!(
+            MaybeInlineValue::from_slice(&Slice::from(vec![TAG_INLINE, 10, 1, 2])),
+            Err(DecodeError::InvalidLength("MaybeInlineValue"))
+        ));
+
+        assert!(matches!(
+            MaybeInlineValue::from_slice(&Slice::from(vec![7])),
+            Err(DecodeError::InvalidTag(("MaybeInlineValue", 7)))
+        ));
+    }
+}
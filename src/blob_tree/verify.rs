@@ -0,0 +1,29 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::{SegmentId, SeqNo, UserKey};
+use value_log::ValueHandle;
+
+/// Result of [`crate::BlobTree::verify_references`]
+#[derive(Debug, Default)]
+pub struct ConsistencyReport {
+    /// Index entries whose value handle could not be resolved
+    /// in the value log, as (key, seqno, handle)
+    pub dangling_handles: Vec<(UserKey, SeqNo, ValueHandle)>,
+
+    /// Blobs that are not referenced by any index entry, as (blob file ID, key)
+    ///
+    /// Blobs of overwritten or deleted keys stay in the value log until
+    /// garbage collection rewrites their blob file, so unreferenced blobs
+    /// alone do not indicate corruption.
+    pub unreferenced_blobs: Vec<(SegmentId, UserKey)>,
+}
+
+impl ConsistencyReport {
+    /// Returns `true` if every index entry could be resolved.
+    #[must_use]
+    pub fn is_consistent(&self) -> bool {
+        self.dangling_handles.is_empty()
+    }
+}
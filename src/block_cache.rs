@@ -6,8 +6,8 @@ use crate::either::Either::{self, Left, Right};
 use crate::segment::id::GlobalSegmentId;
 use crate::segment::value_block::BlockOffset;
 use crate::segment::{block_index::IndexBlock, value_block::ValueBlock};
-use quick_cache::Weighter;
 use quick_cache::{sync::Cache, Equivalent};
+use quick_cache::{Lifecycle, Weighter};
 use std::sync::Arc;
 
 type Item = Either<Arc<ValueBlock>, Arc<IndexBlock>>;
@@ -40,6 +40,52 @@ impl Weighter<CacheKey, Item> for BlockWeighter {
     }
 }
 
+/// Kind of a cached block
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum BlockType {
+    /// Data block, containing key-value pairs
+    Data,
+
+    /// Index block, pointing to data blocks
+    Index,
+}
+
+/// Receives events about blocks being evicted from a [`BlockCache`]
+///
+/// Can be used to feed a secondary cache, or to diagnose which
+/// segments are being displaced from the cache.
+pub trait BlockEvictionObserver: Send + Sync {
+    /// Called after a block was evicted from the cache to make room for other blocks.
+    ///
+    /// `weight` is the amount of bytes the block occupied in the cache.
+    ///
+    /// This is called while the cache is being modified, so it should
+    /// return quickly and must not access the block cache itself.
+    fn on_block_evicted(&self, segment_id: GlobalSegmentId, block_type: BlockType, weight: u64);
+}
+
+#[derive(Clone, Default)]
+struct BlockLifecycle(Option<Arc<dyn BlockEvictionObserver>>);
+
+impl Lifecycle<CacheKey, Item> for BlockLifecycle {
+    type RequestState = ();
+
+    fn begin_request(&self) -> Self::RequestState {}
+
+    fn on_evict(&self, (): &mut Self::RequestState, key: CacheKey, block: Item) {
+        if let Some(observer) = &self.0 {
+            let weight = BlockWeighter.weight(&key, &block);
+
+            let block_type = match block {
+                Either::Left(_) => BlockType::Data,
+                Either::Right(_) => BlockType::Index,
+            };
+
+            observer.on_block_evicted(key.0, block_type, weight);
+        }
+    }
+}
+
 /// Block cache, in which blocks are cached in-memory
 /// after being retrieved from disk
 ///
@@ -67,7 +113,7 @@ impl Weighter<CacheKey, Item> for BlockWeighter {
 pub struct BlockCache {
     // NOTE: rustc_hash performed best: https://fjall-rs.github.io/post/fjall-2-1
     /// Concurrent cache implementation
-    data: Cache<CacheKey, Item, BlockWeighter, rustc_hash::FxBuildHasher>,
+    data: Cache<CacheKey, Item, BlockWeighter, rustc_hash::FxBuildHasher, BlockLifecycle>,
 
     /// Capacity in bytes
     capacity: u64,
@@ -77,15 +123,17 @@ impl BlockCache {
     /// Creates a new block cache with roughly `n` bytes of capacity.
     #[must_use]
     pub fn with_capacity_bytes(bytes: u64) -> Self {
-        use quick_cache::sync::DefaultLifecycle;
+        Self::with_lifecycle(bytes, BlockLifecycle::default())
+    }
 
+    fn with_lifecycle(bytes: u64, lifecycle: BlockLifecycle) -> Self {
         #[allow(clippy::default_trait_access)]
         let quick_cache = Cache::with(
             1_000_000,
             bytes,
             BlockWeighter,
             Default::default(),
-            DefaultLifecycle::default(),
+            lifecycle,
         );
 
         Self {
@@ -94,6 +142,36 @@ impl BlockCache {
         }
     }
 
+    /// Registers an observer that is notified about evicted blocks.
+    ///
+    /// Blocks that are already cached are dropped, so this should be
+    /// called right after creating the cache.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use lsm_tree::{BlockCache, BlockEvictionObserver, BlockType, GlobalSegmentId};
+    /// # use std::sync::{atomic::{AtomicU64, Ordering}, Arc};
+    /// #
+    /// #[derive(Default)]
+    /// struct EvictedBytes(AtomicU64);
+    ///
+    /// impl BlockEvictionObserver for EvictedBytes {
+    ///     fn on_block_evicted(&self, _: GlobalSegmentId, _: BlockType, weight: u64) {
+    ///         self.0.fetch_add(weight, Ordering::Relaxed);
+    ///     }
+    /// }
+    ///
+    /// let evicted = Arc::new(EvictedBytes::default());
+    /// let block_cache = BlockCache::with_capacity_bytes(1_000_000).eviction_observer(evicted.clone());
+    /// ```
+    #[must_use]
+    pub fn eviction_observer(self, observer: Arc<dyn BlockEvictionObserver>) -> Self {
+        let cache = Self::with_lifecycle(self.capacity, BlockLifecycle(Some(observer)));
+        cache.set_limit(self.limit());
+        cache
+    }
+
     /// Returns the amount of cached bytes.
     #[must_use]
     pub fn size(&self) -> u64 {
@@ -106,6 +184,22 @@ impl BlockCache {
         self.capacity
     }
 
+    /// Returns the amount of bytes the cache may currently hold.
+    ///
+    /// This is the capacity, unless the cache has been shrunk using [`BlockCache::set_limit`].
+    #[must_use]
+    pub fn limit(&self) -> u64 {
+        self.data.capacity()
+    }
+
+    /// Limits the cache to `bytes`, evicting blocks if it currently holds more.
+    ///
+    /// The limit is capped at the capacity the cache was created with,
+    /// so it can be raised again up to that point.
+    pub fn set_limit(&self, bytes: u64) {
+        self.data.set_capacity(bytes.min(self.capacity));
+    }
+
     /// Returns the number of cached blocks.
     #[must_use]
     pub fn len(&self) -> usize {
@@ -3,6 +3,7 @@
 // (found in the LICENSE-* files in the repository)
 
 mod bit_array;
+mod xor;
 
 use crate::{
     coding::{Decode, DecodeError, Encode, EncodeError},
@@ -12,9 +13,172 @@ use bit_array::BitArray;
 use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
 use std::io::{Read, Write};
 
+pub use xor::XorFilter;
+
 /// Two hashes that are used for double hashing
 pub type CompositeHash = (u64, u64);
 
+/// Filter type tag of xor filters (bloom filters use their [`BloomLayout`] as tag)
+const FILTER_TYPE_XOR: u8 = 2;
+
+/// Kind of filter that is written for a segment
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum FilterType {
+    /// Bloom filter
+    #[default]
+    Bloom,
+
+    /// Xor filter, which is ~25% smaller than a bloom filter with
+    /// the same false positive rate, but needs the full key set up front
+    ///
+    /// Its false positive rate is fixed at ~0.4%.
+    Xor,
+}
+
+/// Approximate membership filter
+#[enum_dispatch::enum_dispatch]
+#[allow(clippy::len_without_is_empty)]
+pub trait Filter {
+    /// Returns `true` if the hash may be contained.
+    ///
+    /// Will never have a false negative.
+    fn contains_hash(&self, hash: CompositeHash) -> bool;
+
+    /// Size of the filter in bytes.
+    fn len(&self) -> usize;
+}
+
+impl Filter for BloomFilter {
+    fn contains_hash(&self, hash: CompositeHash) -> bool {
+        Self::contains_hash(self, hash)
+    }
+
+    fn len(&self) -> usize {
+        Self::len(self)
+    }
+}
+
+impl Filter for XorFilter {
+    fn contains_hash(&self, hash: CompositeHash) -> bool {
+        Self::contains_hash(self, hash)
+    }
+
+    fn len(&self) -> usize {
+        Self::len(self)
+    }
+}
+
+/// A segment filter of any type, see [`FilterType`]
+#[enum_dispatch::enum_dispatch(Filter)]
+#[derive(Debug, Eq, PartialEq)]
+#[allow(clippy::module_name_repetitions)]
+pub enum AnyFilter {
+    /// Bloom filter
+    Bloom(BloomFilter),
+
+    /// Xor filter
+    Xor(XorFilter),
+}
+
+impl AnyFilter {
+    /// Returns the expected false positive rate of a filter that contains `n` items.
+    #[must_use]
+    pub fn expected_fp_rate(&self, n: usize) -> f32 {
+        match self {
+            Self::Bloom(filter) => filter.expected_fp_rate(n),
+            Self::Xor(_) => XorFilter::FP_RATE,
+        }
+    }
+}
+
+impl Encode for AnyFilter {
+    fn encode_into<W: Write>(&self, writer: &mut W) -> Result<(), EncodeError> {
+        match self {
+            Self::Bloom(filter) => filter.encode_into(writer),
+            Self::Xor(filter) => filter.encode_into(writer),
+        }
+    }
+}
+
+impl Decode for AnyFilter {
+    fn decode_from<R: Read>(reader: &mut R) -> Result<Self, DecodeError> {
+        let filter_type = read_filter_header(reader)?;
+
+        if filter_type == FILTER_TYPE_XOR {
+            return Ok(Self::Xor(XorFilter::decode_body(reader)?));
+        }
+
+        let layout = BloomLayout::try_from(filter_type)
+            .map_err(|()| DecodeError::InvalidTag(("Filter", filter_type)))?;
+
+        Ok(Self::Bloom(BloomFilter::decode_body(reader, layout)?))
+    }
+}
+
+/// Checks the magic bytes & hash type, and returns the filter type tag.
+fn read_filter_header<R: Read>(reader: &mut R) -> Result<u8, DecodeError> {
+    // Check header
+    let mut magic = [0u8; MAGIC_BYTES.len()];
+    reader.read_exact(&mut magic)?;
+
+    if magic != MAGIC_BYTES {
+        return Err(DecodeError::InvalidHeader("BloomFilter"));
+    }
+
+    // NOTE: Filter type
+    let filter_type = reader.read_u8()?;
+
+    // NOTE: Hash type (unused)
+    let hash_type = reader.read_u8()?;
+
+    if hash_type != 0 {
+        return Err(DecodeError::InvalidHeader("BloomFilter"));
+    }
+
+    Ok(filter_type)
+}
+
+/// Size of a block in a blocked bloom filter, in bits (one 64-byte cache line)
+const BLOCK_BITS: usize = 512;
+
+/// Memory layout of a bloom filter
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum BloomLayout {
+    /// Hash probes are spread over the whole filter
+    #[default]
+    Standard,
+
+    /// Each key only probes bits inside a single cache line,
+    /// so a lookup incurs at most one cache miss
+    ///
+    /// Has a slightly higher false positive rate than
+    /// a standard filter of the same size.
+    Blocked,
+}
+
+impl From<BloomLayout> for u8 {
+    fn from(value: BloomLayout) -> Self {
+        match value {
+            BloomLayout::Standard => 0,
+            BloomLayout::Blocked => 1,
+        }
+    }
+}
+
+impl TryFrom<u8> for BloomLayout {
+    type Error = ();
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Standard),
+            1 => Ok(Self::Blocked),
+            _ => Err(()),
+        }
+    }
+}
+
 /// A standard bloom filter
 ///
 /// Allows buffering the key hashes before actual filter construction
@@ -34,6 +198,9 @@ pub struct BloomFilter {
 
     /// Number of hash functions
     k: usize,
+
+    /// Memory layout
+    layout: BloomLayout,
 }
 
 impl Encode for BloomFilter {
@@ -41,8 +208,8 @@ impl Encode for BloomFilter {
         // Write header
         writer.write_all(&MAGIC_BYTES)?;
 
-        // NOTE: Filter type (unused)
-        writer.write_u8(0)?;
+        // NOTE: Filter type
+        writer.write_u8(self.layout.into())?;
 
         // NOTE: Hash type (unused)
         writer.write_u8(0)?;
@@ -57,29 +224,32 @@ impl Encode for BloomFilter {
 
 impl Decode for BloomFilter {
     fn decode_from<R: Read>(reader: &mut R) -> Result<Self, DecodeError> {
-        // Check header
-        let mut magic = [0u8; MAGIC_BYTES.len()];
-        reader.read_exact(&mut magic)?;
+        let filter_type = read_filter_header(reader)?;
 
-        if magic != MAGIC_BYTES {
-            return Err(DecodeError::InvalidHeader("BloomFilter"));
-        }
+        let layout = BloomLayout::try_from(filter_type)
+            .map_err(|()| DecodeError::InvalidTag(("BloomFilter", filter_type)))?;
 
-        // NOTE: Filter type (unused)
-        let filter_type = reader.read_u8()?;
-        assert_eq!(0, filter_type, "Invalid filter type");
-
-        // NOTE: Hash type (unused)
-        let hash_type = reader.read_u8()?;
-        assert_eq!(0, hash_type, "Invalid bloom hash type");
+        Self::decode_body(reader, layout)
+    }
+}
 
+impl BloomFilter {
+    /// Decodes the filter after the header has already been read.
+    fn decode_body<R: Read>(reader: &mut R, layout: BloomLayout) -> Result<Self, DecodeError> {
         let m = reader.read_u64::<BigEndian>()? as usize;
         let k = reader.read_u64::<BigEndian>()? as usize;
 
-        let mut bytes = vec![0; m / 8];
-        reader.read_exact(&mut bytes)?;
+        let len = (m / 8) as u64;
 
-        Ok(Self::from_raw(m, k, bytes.into_boxed_slice()))
+        // NOTE: The length is read from disk, so only allocate what is actually there
+        let mut bytes = vec![];
+        reader.take(len).read_to_end(&mut bytes)?;
+
+        if bytes.len() as u64 != len {
+            return Err(DecodeError::InvalidLength("BloomFilter"));
+        }
+
+        Ok(Self::from_raw(m, k, layout, bytes.into_boxed_slice()))
     }
 }
 
@@ -91,12 +261,69 @@ impl BloomFilter {
         self.inner.bytes().len()
     }
 
-    fn from_raw(m: usize, k: usize, bytes: Box<[u8]>) -> Self {
+    fn from_raw(m: usize, k: usize, layout: BloomLayout, bytes: Box<[u8]>) -> Self {
         Self {
             inner: BitArray::from_bytes(bytes),
             m,
             k,
+            layout,
+        }
+    }
+
+    /// Returns the memory layout of the filter.
+    #[must_use]
+    pub fn layout(&self) -> BloomLayout {
+        self.layout
+    }
+
+    /// Changes the memory layout of an empty filter.
+    ///
+    /// A blocked filter rounds its size up to the next cache line.
+    #[must_use]
+    pub fn with_layout(self, layout: BloomLayout) -> Self {
+        let m = match layout {
+            BloomLayout::Standard => self.m,
+            BloomLayout::Blocked => self.m.div_ceil(BLOCK_BITS).max(1) * BLOCK_BITS,
+        };
+
+        Self {
+            inner: BitArray::with_capacity(m / 8),
+            m,
+            k: self.k,
+            layout,
+        }
+    }
+
+    /// Returns the bit offset of the block the hash maps to, and
+    /// the two hashes used for double hashing inside the block.
+    fn block_probe(&self, (h1, h2): CompositeHash) -> (u64, CompositeHash) {
+        let block_count = (self.m / BLOCK_BITS) as u64;
+        let block_offset = (h1 % block_count) * BLOCK_BITS as u64;
+        (block_offset, (h2, h1.rotate_left(32)))
+    }
+
+    /// Returns the bits per key needed to reach the false positive rate `fpr`.
+    #[must_use]
+    pub fn bits_per_key_for_fp_rate(fpr: f32) -> f32 {
+        use std::f32::consts::LN_2;
+
+        -fpr.max(0.000_001).ln() / LN_2.powi(2)
+    }
+
+    /// Returns the expected false positive rate after inserting `n` items.
+    ///
+    /// For blocked filters, the actual rate is slightly higher.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn expected_fp_rate(&self, n: usize) -> f32 {
+        if self.m == 0 {
+            return 1.0;
         }
+
+        let k = self.k as f32;
+        let fill = -k * n as f32 / self.m as f32;
+
+        (1.0 - fill.exp()).powf(k)
     }
 
     /// Constructs a bloom filter that can hold `n` items
@@ -118,6 +345,7 @@ impl BloomFilter {
             inner: BitArray::with_capacity(m / 8),
             m,
             k,
+            layout: BloomLayout::Standard,
         }
     }
 
@@ -144,6 +372,7 @@ impl BloomFilter {
             inner: BitArray::with_capacity(bytes),
             m: bytes * 8,
             k,
+            layout: BloomLayout::Standard,
         }
     }
 
@@ -165,10 +394,16 @@ impl BloomFilter {
     /// Will never have a false negative.
     #[must_use]
     pub fn contains_hash(&self, hash: CompositeHash) -> bool {
-        let (mut h1, mut h2) = hash;
+        let (offset, m, (mut h1, mut h2)) = match self.layout {
+            BloomLayout::Standard => (0, self.m as u64, hash),
+            BloomLayout::Blocked => {
+                let (offset, hash) = self.block_probe(hash);
+                (offset, BLOCK_BITS as u64, hash)
+            }
+        };
 
         for i in 0..(self.k as u64) {
-            let idx = h1 % (self.m as u64);
+            let idx = offset + h1 % m;
 
             // NOTE: should be in bounds because of modulo
             #[allow(clippy::expect_used)]
@@ -192,9 +427,17 @@ impl BloomFilter {
     }
 
     /// Adds the key to the filter.
-    pub fn set_with_hash(&mut self, (mut h1, mut h2): CompositeHash) {
+    pub fn set_with_hash(&mut self, hash: CompositeHash) {
+        let (offset, m, (mut h1, mut h2)) = match self.layout {
+            BloomLayout::Standard => (0, self.m as u64, hash),
+            BloomLayout::Blocked => {
+                let (offset, hash) = self.block_probe(hash);
+                (offset, BLOCK_BITS as u64, hash)
+            }
+        };
+
         for i in 0..(self.k as u64) {
-            let idx = h1 % (self.m as u64);
+            let idx = offset + h1 % m;
 
             self.enable_bit(idx as usize);
 
@@ -273,6 +516,134 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn bloom_decode_invalid_hash_type() -> crate::Result<()> {
+        let mut bytes = vec![];
+        BloomFilter::with_fp_rate(10, 0.01).encode_into(&mut bytes)?;
+
+        // NOTE: Hash type is the byte after the magic bytes and filter type
+        *bytes.get_mut(MAGIC_BYTES.len() + 1).expect("should exist") = 7;
+
+        assert!(matches!(
+            AnyFilter::decode_from(&mut &bytes[..]),
+            Err(DecodeError::InvalidHeader(_)),
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn bloom_decode_truncated() -> crate::Result<()> {
+        let mut bytes = vec![];
+        BloomFilter::with_fp_rate(10, 0.01).encode_into(&mut bytes)?;
+        bytes.truncate(bytes.len() - 1);
+
+        assert!(matches!(
+            AnyFilter::decode_from(&mut &bytes[..]),
+            Err(DecodeError::InvalidLength(_)),
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn xor_decode_invalid_length() -> crate::Result<()> {
+        let hashes = (0..100u64)
+            .map(|x| BloomFilter::get_hash(&x.to_be_bytes()))
+            .collect::<Vec<_>>();
+
+        let mut bytes = vec![];
+        XorFilter::from_hashes(&hashes)
+            .expect("should build")
+            .encode_into(&mut bytes)?;
+
+        // NOTE: Block length follows the header and seed
+        let block_length_offset = MAGIC_BYTES.len() + 2 + 8;
+        bytes
+            .get_mut(block_length_offset..block_length_offset + 4)
+            .expect("should exist")
+            .copy_from_slice(&u32::MAX.to_be_bytes());
+
+        assert!(matches!(
+            AnyFilter::decode_from(&mut &bytes[..]),
+            Err(DecodeError::InvalidLength(_)),
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn bloom_blocked_serde_round_trip() -> crate::Result<()> {
+        let mut filter = BloomFilter::with_fp_rate(10, 0.0001).with_layout(BloomLayout::Blocked);
+
+        for key in [b"item0", b"item1", b"item2"] {
+            filter.set_with_hash(BloomFilter::get_hash(key));
+        }
+
+        let mut bytes = vec![];
+        filter.encode_into(&mut bytes)?;
+
+        let filter_copy = BloomFilter::decode_from(&mut &bytes[..])?;
+        assert_eq!(filter, filter_copy);
+        assert_eq!(BloomLayout::Blocked, filter_copy.layout());
+
+        for key in [b"item0", b"item1", b"item2"] {
+            assert!(filter_copy.contains(key));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn bloom_blocked_size() {
+        let filter = BloomFilter::with_bpk(1, 10).with_layout(BloomLayout::Blocked);
+        assert_eq!(64, filter.len());
+
+        let filter = BloomFilter::with_bpk(1_000, 10).with_layout(BloomLayout::Blocked);
+        assert_eq!(1_280, filter.len());
+    }
+
+    #[test]
+    fn bloom_blocked_fpr() {
+        let item_count = 100_000;
+
+        let mut filter = BloomFilter::with_bpk(item_count, 10).with_layout(BloomLayout::Blocked);
+
+        for key in (0..item_count).map(|_| nanoid::nanoid!()) {
+            let key = key.as_bytes();
+
+            filter.set_with_hash(BloomFilter::get_hash(key));
+            assert!(filter.contains(key));
+        }
+
+        let mut false_positives = 0;
+
+        for key in (0..item_count).map(|_| nanoid::nanoid!()) {
+            let key = key.as_bytes();
+
+            if filter.contains(key) {
+                false_positives += 1;
+            }
+        }
+
+        #[allow(clippy::cast_precision_loss)]
+        let fpr = false_positives as f32 / item_count as f32;
+        assert!(fpr < 0.02);
+    }
+
+    #[test]
+    fn bloom_expected_fp_rate() {
+        let filter = BloomFilter::with_fp_rate(10_000, 0.01);
+        let fpr = filter.expected_fp_rate(10_000);
+        assert!((0.008..0.012).contains(&fpr), "{fpr}");
+
+        // NOTE: A half-full filter has far less false positives
+        assert!(filter.expected_fp_rate(5_000) < 0.001);
+
+        let bpk = BloomFilter::bits_per_key_for_fp_rate(0.01);
+        assert!((9.5..9.7).contains(&bpk), "{bpk}");
+    }
+
     #[test]
     fn bloom_calculate_m() {
         assert_eq!(9_592, BloomFilter::calculate_m(1_000, 0.01));
@@ -0,0 +1,312 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use super::{CompositeHash, FILTER_TYPE_XOR};
+use crate::{
+    coding::{DecodeError, Encode, EncodeError},
+    file::MAGIC_BYTES,
+};
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use std::io::{Read, Write};
+
+/// Maximum amount of seeds that are tried before giving up construction
+///
+/// Construction fails with a probability of ~3% per seed, so
+/// this should never be reached in practice.
+const MAX_CONSTRUCTION_ATTEMPTS: u64 = 1_000;
+
+/// Amount of seeds that are tried before the filter capacity is increased
+const ATTEMPTS_PER_CAPACITY: u64 = 10;
+
+fn murmur64(mut h: u64) -> u64 {
+    h ^= h >> 33;
+    h = h.wrapping_mul(0xff51_afd7_ed55_8ccd);
+    h ^= h >> 33;
+    h = h.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+    h ^= h >> 33;
+    h
+}
+
+/// Maps a 32-bit value into `0..n` without a modulo operation
+#[allow(clippy::cast_possible_truncation)]
+fn reduce(hash: u32, n: u32) -> u32 {
+    ((u64::from(hash) * u64::from(n)) >> 32) as u32
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn fingerprint(hash: u64) -> u8 {
+    (hash ^ (hash >> 32)) as u8
+}
+
+/// An 8-bit xor filter
+///
+/// Needs the whole key set up front, so it can only be used when
+/// the keys are static (e.g. when writing a segment), but uses ~25% less space than
+/// a bloom filter with the same false positive rate (~0.4%).
+///
+/// See <https://arxiv.org/abs/1912.08258>.
+#[derive(Debug, Eq, PartialEq)]
+#[allow(clippy::module_name_repetitions)]
+pub struct XorFilter {
+    seed: u64,
+    block_length: u32,
+    fingerprints: Box<[u8]>,
+}
+
+impl XorFilter {
+    /// False positive rate of 8-bit fingerprints
+    pub const FP_RATE: f32 = 1.0 / 256.0;
+}
+
+impl Encode for XorFilter {
+    fn encode_into<W: Write>(&self, writer: &mut W) -> Result<(), EncodeError> {
+        // Write header
+        writer.write_all(&MAGIC_BYTES)?;
+
+        // NOTE: Filter type
+        writer.write_u8(FILTER_TYPE_XOR)?;
+
+        // NOTE: Hash type (unused)
+        writer.write_u8(0)?;
+
+        writer.write_u64::<BigEndian>(self.seed)?;
+        writer.write_u32::<BigEndian>(self.block_length)?;
+        writer.write_all(&self.fingerprints)?;
+
+        Ok(())
+    }
+}
+
+impl XorFilter {
+    /// Decodes the filter after the header has already been read.
+    pub(crate) fn decode_body<R: Read>(reader: &mut R) -> Result<Self, DecodeError> {
+        let seed = reader.read_u64::<BigEndian>()?;
+        let block_length = reader.read_u32::<BigEndian>()?;
+
+        let len = u64::from(block_length) * 3;
+
+        // NOTE: The length is read from disk, so only allocate what is actually there
+        let mut fingerprints = vec![];
+        reader.take(len).read_to_end(&mut fingerprints)?;
+
+        if fingerprints.len() as u64 != len {
+            return Err(DecodeError::InvalidLength("XorFilter"));
+        }
+
+        Ok(Self {
+            seed,
+            block_length,
+            fingerprints: fingerprints.into_boxed_slice(),
+        })
+    }
+
+    /// Size of xor filter in bytes.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.fingerprints.len()
+    }
+
+    /// Returns `true` if the filter holds no fingerprints.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.fingerprints.is_empty()
+    }
+
+    // NOTE: Truncation is intended, each position uses 32 bits of the hash
+    #[allow(clippy::cast_possible_truncation)]
+    fn positions(&self, hash: u64) -> [usize; 3] {
+        let block_length = self.block_length;
+
+        [
+            reduce(hash as u32, block_length) as usize,
+            (reduce(hash.rotate_left(21) as u32, block_length) + block_length) as usize,
+            (reduce(hash.rotate_left(42) as u32, block_length) + 2 * block_length) as usize,
+        ]
+    }
+
+    /// Builds a filter from the given key hashes.
+    ///
+    /// Duplicate hashes are ignored.
+    ///
+    /// Returns `None` if no working seed can be found, which should never happen.
+    #[must_use]
+    pub fn from_hashes(hashes: &[CompositeHash]) -> Option<Self> {
+        let mut keys = hashes.iter().map(|(h1, _)| *h1).collect::<Vec<_>>();
+        keys.sort_unstable();
+        keys.dedup();
+
+        // NOTE: The filter needs 1.23 slots per key, plus some constant slack
+        let mut capacity = 32 + (keys.len() * 123).div_ceil(100);
+
+        for seed in 0..MAX_CONSTRUCTION_ATTEMPTS {
+            // NOTE: If a key set keeps failing, give the peeling more room
+            if seed > 0 && seed % ATTEMPTS_PER_CAPACITY == 0 {
+                capacity += capacity / 10;
+            }
+
+            if let Some(filter) = Self::try_build(&keys, capacity, seed) {
+                return Some(filter);
+            }
+        }
+
+        log::warn!(
+            "Could not construct xor filter for {} keys after {MAX_CONSTRUCTION_ATTEMPTS} attempts",
+            keys.len(),
+        );
+
+        None
+    }
+
+    /// Tries to build a filter for the given (deduplicated) keys using a single seed.
+    #[allow(clippy::indexing_slicing)]
+    fn try_build(keys: &[u64], capacity: usize, seed: u64) -> Option<Self> {
+        let capacity = capacity.div_ceil(3) * 3;
+
+        // NOTE: Truncation is OK, because segments cannot hold 2^32 keys
+        #[allow(clippy::cast_possible_truncation)]
+        let block_length = (capacity / 3) as u32;
+
+        let mut filter = Self {
+            seed,
+            block_length,
+            fingerprints: vec![0; capacity].into_boxed_slice(),
+        };
+
+        let mut counts = vec![0u32; capacity];
+        let mut xor_hashes = vec![0u64; capacity];
+        let mut stack = Vec::with_capacity(keys.len());
+
+        // NOTE: Positions are always < capacity because of `reduce`
+        for &key in keys {
+            let hash = murmur64(key.wrapping_add(seed));
+
+            for idx in filter.positions(hash) {
+                counts[idx] += 1;
+                xor_hashes[idx] ^= hash;
+            }
+        }
+
+        let mut queue = (0..capacity)
+            .filter(|&idx| counts[idx] == 1)
+            .collect::<Vec<_>>();
+
+        // NOTE: Peel off all slots that are only used by a single key
+        while let Some(idx) = queue.pop() {
+            if counts[idx] != 1 {
+                continue;
+            }
+
+            let hash = xor_hashes[idx];
+            stack.push((hash, idx));
+
+            for other in filter.positions(hash) {
+                counts[other] -= 1;
+                xor_hashes[other] ^= hash;
+
+                if counts[other] == 1 {
+                    queue.push(other);
+                }
+            }
+        }
+
+        if stack.len() != keys.len() {
+            return None;
+        }
+
+        for &(hash, idx) in stack.iter().rev() {
+            let [a, b, c] = filter.positions(hash);
+
+            filter.fingerprints[idx] = fingerprint(hash)
+                ^ filter.fingerprints[a]
+                ^ filter.fingerprints[b]
+                ^ filter.fingerprints[c];
+        }
+
+        Some(filter)
+    }
+
+    /// Returns `true` if the hash may be contained.
+    ///
+    /// Will never have a false negative.
+    #[must_use]
+    pub fn contains_hash(&self, (h1, _): CompositeHash) -> bool {
+        let hash = murmur64(h1.wrapping_add(self.seed));
+        let [a, b, c] = self.positions(hash);
+
+        let get = |idx: usize| self.fingerprints.get(idx).copied().unwrap_or_default();
+
+        fingerprint(hash) == get(a) ^ get(b) ^ get(c)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bloom::{AnyFilter, BloomFilter, Filter};
+    use crate::coding::Decode;
+    use test_log::test;
+
+    #[test]
+    fn xor_filter_basic() {
+        let hashes = (0..1_000u64)
+            .map(|x| BloomFilter::get_hash(&x.to_be_bytes()))
+            .collect::<Vec<_>>();
+
+        let filter = XorFilter::from_hashes(&hashes).expect("should build");
+
+        for hash in &hashes {
+            assert!(filter.contains_hash(*hash));
+        }
+    }
+
+    #[test]
+    fn xor_filter_duplicates() {
+        let hash = BloomFilter::get_hash(b"a");
+        let filter = XorFilter::from_hashes(&[hash, hash, hash]).expect("should build");
+        assert!(filter.contains_hash(hash));
+    }
+
+    #[test]
+    fn xor_filter_fpr() {
+        let item_count = 100_000;
+
+        let hashes = (0..item_count)
+            .map(|_| BloomFilter::get_hash(nanoid::nanoid!().as_bytes()))
+            .collect::<Vec<_>>();
+
+        let filter = XorFilter::from_hashes(&hashes).expect("should build");
+
+        let false_positives = (0..item_count)
+            .filter(|_| filter.contains_hash(BloomFilter::get_hash(nanoid::nanoid!().as_bytes())))
+            .count();
+
+        #[allow(clippy::cast_precision_loss)]
+        let fpr = false_positives as f32 / item_count as f32;
+        assert!(fpr < 0.01);
+
+        // NOTE: ~9.84 bits per key
+        assert!(filter.len() < item_count * 10 / 8);
+    }
+
+    #[test]
+    fn xor_filter_serde_round_trip() -> crate::Result<()> {
+        let hashes = (0..100u64)
+            .map(|x| BloomFilter::get_hash(&x.to_be_bytes()))
+            .collect::<Vec<_>>();
+
+        let filter = XorFilter::from_hashes(&hashes).expect("should build");
+
+        let mut bytes = vec![];
+        filter.encode_into(&mut bytes)?;
+
+        let filter_copy = AnyFilter::decode_from(&mut &bytes[..])?;
+        assert_eq!(filter.len(), filter_copy.len());
+
+        for hash in &hashes {
+            assert!(filter_copy.contains_hash(*hash));
+        }
+
+        Ok(())
+    }
+}
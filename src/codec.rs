@@ -0,0 +1,107 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, RwLock},
+};
+
+/// Registered custom codecs, by tag
+static CODECS: RwLock<BTreeMap<u8, Arc<dyn CompressionCodec>>> = RwLock::new(BTreeMap::new());
+
+/// Custom compression codec, used by [`crate::CompressionType::Custom`]
+///
+/// Codecs are identified by a tag, which is stored in every block and in
+/// the segment metadata, so the tag of a codec must never change once data
+/// has been written with it.
+///
+/// Codecs need to be registered using [`register_compression_codec`] before
+/// a tree that uses them is opened.
+///
+/// # Examples
+///
+/// ```
+/// # let folder = tempfile::tempdir()?;
+/// use lsm_tree::{register_compression_codec, AbstractTree, CompressionCodec, CompressionType, Config};
+/// use std::sync::Arc;
+///
+/// /// Toy run-length encoding
+/// struct Rle;
+///
+/// impl CompressionCodec for Rle {
+///     fn compress(&self, data: &[u8]) -> lsm_tree::Result<Vec<u8>> {
+///         let mut out = vec![];
+///         let mut iter = data.iter().peekable();
+///
+///         while let Some(&byte) = iter.next() {
+///             let mut len = 1u8;
+///             while len < u8::MAX && iter.next_if_eq(&&byte).is_some() {
+///                 len += 1;
+///             }
+///             out.extend([len, byte]);
+///         }
+///
+///         Ok(out)
+///     }
+///
+///     fn decompress(&self, data: &[u8]) -> lsm_tree::Result<Vec<u8>> {
+///         Ok(data
+///             .chunks(2)
+///             .flat_map(|run| std::iter::repeat(run[1]).take(run[0].into()))
+///             .collect())
+///     }
+/// }
+///
+/// register_compression_codec(200, Arc::new(Rle));
+///
+/// let tree = Config::new(folder)
+///     .compression(CompressionType::Custom(200))
+///     .open()?;
+///
+/// tree.insert("a", "aaaaaaaaaaaaaaaa", 0);
+/// tree.flush_active_memtable(0)?;
+///
+/// assert_eq!(&*tree.get("a", None)?.unwrap(), b"aaaaaaaaaaaaaaaa");
+/// #
+/// # Ok::<(), lsm_tree::Error>(())
+/// ```
+pub trait CompressionCodec: Send + Sync {
+    /// Compresses data.
+    ///
+    /// # Errors
+    ///
+    /// May return `Err` if the data cannot be compressed.
+    fn compress(&self, data: &[u8]) -> crate::Result<Vec<u8>>;
+
+    /// Reverses [`CompressionCodec::compress`].
+    ///
+    /// # Errors
+    ///
+    /// May return `Err` if the data is corrupted.
+    fn decompress(&self, data: &[u8]) -> crate::Result<Vec<u8>>;
+}
+
+/// Registers a custom compression codec for the given tag,
+/// replacing the codec that was previously registered for it, if any.
+///
+/// Codecs are registered process-wide, so they are available to all trees.
+///
+/// # Panics
+///
+/// Panics if the codec registry lock is poisoned.
+pub fn register_compression_codec(tag: u8, codec: Arc<dyn CompressionCodec>) {
+    CODECS.write().expect("lock is poisoned").insert(tag, codec);
+}
+
+/// Returns the custom compression codec that is registered for the given tag.
+///
+/// Returns `Err` if no codec is registered for the tag.
+pub fn get(tag: u8) -> crate::Result<Arc<dyn CompressionCodec>> {
+    CODECS
+        .read()
+        .expect("lock is poisoned")
+        .get(&tag)
+        .cloned()
+        .ok_or(crate::Error::UnknownCompressionCodec(tag))
+}
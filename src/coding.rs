@@ -46,6 +46,9 @@ pub enum DecodeError {
     InvalidTrailer,
 
     InvalidHeader(&'static str),
+
+    /// A length field points beyond the end of the data
+    InvalidLength(&'static str),
 }
 
 impl std::fmt::Display for DecodeError {
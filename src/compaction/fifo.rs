@@ -3,7 +3,7 @@
 // (found in the LICENSE-* files in the repository)
 
 use super::{Choice, CompactionStrategy};
-use crate::{config::Config, level_manifest::LevelManifest, time::unix_timestamp, HashSet};
+use crate::{config::Config, level_manifest::LevelManifest, HashSet};
 
 /// FIFO-style compaction
 ///
@@ -11,7 +11,7 @@ use crate::{config::Config, level_manifest::LevelManifest, time::unix_timestamp,
 /// when the threshold is reached.
 ///
 /// Will also merge segments if the amount of segments in level 0 grows too much, which
-/// could cause write stalls.
+/// could cause write stalls, see [`Strategy::l0_merge_threshold`].
 ///
 /// Additionally, a (lazy) TTL can be configured to drop old segments.
 ///
@@ -29,13 +29,40 @@ pub struct Strategy {
 
     /// TTL in seconds, will be disabled if 0 or None
     pub ttl_seconds: Option<u64>,
+
+    /// Amount of segments in level 0 above which segments are merged
+    ///
+    /// Default = 20
+    pub l0_merge_threshold: usize,
 }
 
 impl Strategy {
     /// Configures a new `Fifo` compaction strategy
     #[must_use]
     pub fn new(limit: u64, ttl_seconds: Option<u64>) -> Self {
-        Self { limit, ttl_seconds }
+        Self {
+            limit,
+            ttl_seconds,
+            l0_merge_threshold: 20,
+        }
+    }
+
+    /// Sets the amount of segments in level 0 above which segments are merged.
+    ///
+    /// The run of adjacent (by age) segments with the smallest total size is merged,
+    /// so the oldest data can still be dropped in small steps.
+    ///
+    /// The TTL of a merged segment starts when it is written, so merging
+    /// delays dropping the merged data.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is 0.
+    #[must_use]
+    pub fn l0_merge_threshold(mut self, n: usize) -> Self {
+        assert!(n > 0, "L0 merge threshold should be at least 1");
+        self.l0_merge_threshold = n;
+        self
     }
 }
 
@@ -55,10 +82,10 @@ impl CompactionStrategy for Strategy {
 
         if let Some(ttl_seconds) = self.ttl_seconds {
             if ttl_seconds > 0 {
-                let now = unix_timestamp().as_micros();
+                let now = config.get_clock().now().as_micros();
 
                 for segment in resolved_view.iter().flat_map(|lvl| &lvl.segments) {
-                    let lifetime_us = now - segment.metadata.created_at;
+                    let lifetime_us = now.saturating_sub(segment.metadata.created_at);
                     let lifetime_sec = lifetime_us / 1000 / 1000;
 
                     if lifetime_sec > ttl_seconds.into() {
@@ -97,11 +124,13 @@ impl CompactionStrategy for Strategy {
         }
 
         if segment_ids_to_delete.is_empty() {
-            // NOTE: Only try to merge segments if they are not disjoint
-            // to improve read performance
-            // But ideally FIFO is only used for monotonic workloads
-            // so there's nothing we need to do
-            if first_level.is_disjoint {
+            if first_level.len() > self.l0_merge_threshold {
+                super::maintenance::choose_l0_merge(levels, self.l0_merge_threshold)
+            } else if first_level.is_disjoint {
+                // NOTE: Only try to merge segments if they are not disjoint
+                // to improve read performance
+                // But ideally FIFO is only used for monotonic workloads
+                // so there's nothing we need to do
                 Choice::DoNothing
             } else {
                 super::maintenance::Strategy.choose(levels, config)
@@ -131,10 +160,9 @@ mod tests {
             value_block::BlockOffset,
             Segment, SegmentInner,
         },
-        time::unix_timestamp,
-        HashSet,
+        HashSet, ManualClock,
     };
-    use std::sync::Arc;
+    use std::{sync::Arc, time::Duration};
     use test_log::test;
 
     #[allow(clippy::expect_used)]
@@ -177,10 +205,20 @@ mod tests {
                 range_tombstone_count: 0,
                 uncompressed_size: 0,
                 seqnos: (0, created_at as u64),
+                temperature: None,
+                filter_fp_rate_ppb: None,
+                global_seqno: None,
             },
             block_cache,
-
-            bloom_filter: Some(crate::bloom::BloomFilter::with_fp_rate(1, 0.1)),
+            statistics: Arc::default(),
+
+            bloom_filter: Some(crate::bloom::BloomFilter::with_fp_rate(1, 0.1).into()).into(),
+            key_sketch: None.into(),
+            key_sketch_ptr: BlockOffset(0),
+            range_tombstones: crate::range_tombstone::RangeTombstoneIndex::default().into(),
+            transform: None,
+            deleted_path: std::sync::OnceLock::new(),
+            access: crate::segment::access_stats::AccessStats::default(),
         }
         .into()
     }
@@ -193,12 +231,37 @@ mod tests {
         let mut levels = LevelManifest::create_new(4, tempdir.path().join(LEVELS_MANIFEST_FILE))?;
 
         levels.add(fixture_segment(1, 1));
-        levels.add(fixture_segment(2, unix_timestamp().as_micros()));
+        levels.add(fixture_segment(2, 4_000_000_000));
 
-        assert_eq!(
-            compactor.choose(&levels, &Config::default()),
-            Choice::Drop(set![1])
-        );
+        let clock = Arc::new(ManualClock::new(Duration::from_secs(6_000)));
+        let config = Config::default().clock(clock.clone());
+
+        assert_eq!(compactor.choose(&levels, &config), Choice::Drop(set![1]));
+
+        // NOTE: TTL is exclusive
+        clock.set(Duration::from_secs(9_000));
+        assert_eq!(compactor.choose(&levels, &config), Choice::Drop(set![1]));
+
+        clock.set(Duration::from_secs(9_001));
+        assert_eq!(compactor.choose(&levels, &config), Choice::Drop(set![1, 2]));
+
+        Ok(())
+    }
+
+    #[test]
+    fn fifo_ttl_deeper_levels() -> crate::Result<()> {
+        let tempdir = tempfile::tempdir()?;
+        let compactor = Strategy::new(u64::MAX, Some(5_000));
+
+        let mut levels = LevelManifest::create_new(4, tempdir.path().join(LEVELS_MANIFEST_FILE))?;
+
+        levels.add(fixture_segment(1, 4_000_000_000));
+        levels.insert_into_level(3, fixture_segment(2, 1));
+
+        let clock = Arc::new(ManualClock::new(Duration::from_secs(6_000)));
+        let config = Config::default().clock(clock);
+
+        assert_eq!(compactor.choose(&levels, &config), Choice::Drop(set![2]));
 
         Ok(())
     }
@@ -270,4 +333,59 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn fifo_l0_merge() -> crate::Result<()> {
+        let tempdir = tempfile::tempdir()?;
+        let compactor = Strategy::new(u64::MAX, None).l0_merge_threshold(3);
+
+        let mut levels = LevelManifest::create_new(4, tempdir.path().join(LEVELS_MANIFEST_FILE))?;
+
+        for id in 1..=3 {
+            levels.add(fixture_segment(id, id.into()));
+        }
+        assert_eq!(
+            compactor.choose(&levels, &Config::default()),
+            Choice::DoNothing
+        );
+
+        levels.add(fixture_segment(4, 4));
+        levels.add(fixture_segment(5, 5));
+
+        // NOTE: 3 segments are merged into 1, so 3 segments are left
+        assert_eq!(
+            compactor.choose(&levels, &Config::default()),
+            Choice::Merge(crate::compaction::Input {
+                dest_level: 0,
+                segment_ids: set![1, 2, 3],
+                target_size: u64::MAX,
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn fifo_l0_merge_maintenance_fallback() -> crate::Result<()> {
+        let tempdir = tempfile::tempdir()?;
+        let compactor = Strategy::new(u64::MAX, None).l0_merge_threshold(30);
+
+        let mut levels = LevelManifest::create_new(4, tempdir.path().join(LEVELS_MANIFEST_FILE))?;
+
+        for id in 1..=21 {
+            levels.add(fixture_segment(id, id.into()));
+        }
+
+        // NOTE: The segments overlap, so maintenance still keeps L0 in check
+        assert_eq!(
+            compactor.choose(&levels, &Config::default()),
+            Choice::Merge(crate::compaction::Input {
+                dest_level: 0,
+                segment_ids: set![1, 2],
+                target_size: u64::MAX,
+            })
+        );
+
+        Ok(())
+    }
 }
@@ -174,6 +174,16 @@ pub struct Strategy {
     /// A level target size is: max_memtable_size * level_ratio.pow(#level + 1).
     #[allow(clippy::doc_markdown)]
     pub level_ratio: u8,
+
+    /// When set, a segment in L1+ that served at least this many point reads
+    /// (see [`Segment::read_count`]) is merged into the overlapping segments
+    /// of the next level, if no other compaction is needed.
+    ///
+    /// This reduces the read amplification of hot key ranges that are spread
+    /// across many levels, while leaving cold key ranges alone.
+    ///
+    /// Default = None
+    pub hot_range_read_threshold: Option<u64>,
 }
 
 impl Default for Strategy {
@@ -182,6 +192,7 @@ impl Default for Strategy {
             l0_threshold: 4,
             target_size:/* 64 Mib */ 64 * 1_024 * 1_024,
             level_ratio: 10,
+            hot_range_read_threshold: None,
         }
     }
 }
@@ -206,6 +217,67 @@ impl Strategy {
     fn level_base_size(&self) -> u64 {
         self.target_size as u64 * self.l0_threshold as u64
     }
+
+    /// Picks the hottest segment in L1+ that overlaps segments in the next level,
+    /// and merges it into them.
+    fn pick_hot_compaction(&self, levels: &LevelManifest, read_threshold: u64) -> Option<Choice> {
+        let view = &levels.levels;
+        let hidden_set = levels.hidden_set();
+
+        let mut hottest: Option<(u64, u8, HashSet<SegmentId>)> = None;
+
+        for (curr_level_index, level) in view.iter().enumerate().skip(1) {
+            let Some(next_level) = view.get(curr_level_index + 1) else {
+                break;
+            };
+
+            for segment in level.iter() {
+                let read_count = segment.read_count();
+
+                if read_count < read_threshold
+                    || hottest
+                        .as_ref()
+                        .is_some_and(|(hottest, _, _)| read_count <= *hottest)
+                    || hidden_set.is_hidden(segment.id())
+                {
+                    continue;
+                }
+
+                let overlapping = next_level
+                    .overlapping_segments(&segment.metadata.key_range)
+                    .map(Segment::id)
+                    .collect::<Vec<_>>();
+
+                // NOTE: The key range is not fragmented, so there is nothing to gain
+                if overlapping.is_empty() {
+                    continue;
+                }
+
+                // NOTE: Keep compactions with 25 or less segments
+                // to make compactions not too large
+                if overlapping.len() >= 25 || hidden_set.is_blocked(overlapping.iter().copied()) {
+                    continue;
+                }
+
+                let mut segment_ids: HashSet<_> = overlapping.into_iter().collect();
+                segment_ids.insert(segment.id());
+
+                // NOTE: Level count is 255 max
+                #[allow(clippy::cast_possible_truncation)]
+                let dest_level = (curr_level_index + 1) as u8;
+
+                hottest = Some((read_count, dest_level, segment_ids));
+            }
+        }
+
+        hottest.map(|(_, dest_level, segment_ids)| {
+            Choice::Merge(CompactionInput {
+                segment_ids,
+                dest_level,
+                target_size: u64::from(self.target_size),
+            })
+        })
+    }
 }
 
 impl CompactionStrategy for Strategy {
@@ -214,7 +286,7 @@ impl CompactionStrategy for Strategy {
     }
 
     #[allow(clippy::too_many_lines)]
-    fn choose(&self, levels: &LevelManifest, _: &Config) -> Choice {
+    fn choose(&self, levels: &LevelManifest, config: &Config) -> Choice {
         let view = &levels.levels;
 
         // TODO: look at L1+, if not disjoint
@@ -324,11 +396,23 @@ impl CompactionStrategy for Strategy {
                         };
                     }
 
-                    return Choice::Merge(CompactionInput {
+                    let choice = CompactionInput {
                         dest_level: 1,
                         segment_ids: first_level.list_ids(),
                         target_size: ((self.target_size as f32) * 1.1) as u64,
+                    };
+
+                    // NOTE: Nothing overlaps, so segments that are large enough can be
+                    // moved into L1 as they are (trivial move), instead of rewriting them,
+                    // which is what sequential-insert workloads would otherwise do all the time
+                    let is_large_enough = first_level.iter().all(|segment| {
+                        segment.metadata.file_size >= u64::from(self.target_size) / 2
                     });
+
+                    if is_large_enough {
+                        return Choice::Move(choice);
+                    }
+                    return Choice::Merge(choice);
                 }
 
                 if first_level_size < self.target_size.into() {
@@ -375,6 +459,18 @@ impl CompactionStrategy for Strategy {
             }
         }
 
+        // Hot range compactions
+        if let Some(read_threshold) = self.hot_range_read_threshold {
+            if let Some(choice) = self.pick_hot_compaction(levels, read_threshold) {
+                return choice;
+            }
+        }
+
+        // Age-based compactions
+        if let Some(choice) = super::maintenance::choose_aged_segment(levels, config) {
+            return choice;
+        }
+
         Choice::DoNothing
     }
 }
@@ -389,6 +485,7 @@ mod tests {
         key_range::KeyRange,
         level_manifest::LevelManifest,
         segment::{
+            access_stats::SegmentAccessStats,
             block_index::{two_level_index::TwoLevelBlockIndex, BlockIndexImpl},
             file_offsets::FileOffsets,
             meta::{Metadata, SegmentId},
@@ -453,10 +550,20 @@ mod tests {
                 range_tombstone_count: 0,
                 uncompressed_size: 0,
                 seqnos: (0, 0),
+                temperature: None,
+                filter_fp_rate_ppb: None,
+                global_seqno: None,
             },
             block_cache,
-
-            bloom_filter: Some(crate::bloom::BloomFilter::with_fp_rate(1, 0.1)),
+            statistics: Arc::default(),
+
+            bloom_filter: Some(crate::bloom::BloomFilter::with_fp_rate(1, 0.1).into()).into(),
+            key_sketch: None.into(),
+            key_sketch_ptr: BlockOffset(0),
+            range_tombstones: crate::range_tombstone::RangeTombstoneIndex::default().into(),
+            transform: None,
+            deleted_path: std::sync::OnceLock::new(),
+            access: crate::segment::access_stats::AccessStats::default(),
         }
         .into()
     }
@@ -612,6 +719,53 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    #[allow(
+        clippy::cast_sign_loss,
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation
+    )]
+    fn leveled_disjoint_trivial_move() -> crate::Result<()> {
+        let tempdir = tempfile::tempdir()?;
+        let compactor = Strategy {
+            target_size: 64 * 1_024 * 1_024,
+            ..Default::default()
+        };
+
+        #[rustfmt::skip]
+        let levels = build_levels(tempdir.path(), vec![
+            vec![(5, "e", "e", 64), (6, "f", "f", 64), (7, "g", "g", 64), (8, "h", "h", 64)],
+            vec![(1, "a", "a", 64), (2, "b", "b", 64), (3, "c", "c", 64), (4, "d", "d", 64)],
+            vec![],
+            vec![],
+        ])?;
+
+        assert_eq!(
+            compactor.choose(&levels, &Config::default()),
+            Choice::Move(CompactionInput {
+                dest_level: 1,
+                segment_ids: [5, 6, 7, 8].into_iter().collect::<HashSet<_>>(),
+                target_size: ((compactor.target_size as f32) * 1.1) as u64,
+            })
+        );
+
+        // NOTE: Small segments are merged, so L1 does not fill up with tiny segments
+        #[rustfmt::skip]
+        let levels = build_levels(tempdir.path(), vec![
+            vec![(5, "e", "e", 64), (6, "f", "f", 64), (7, "g", "g", 64), (8, "h", "h", 1)],
+            vec![(1, "a", "a", 64), (2, "b", "b", 64), (3, "c", "c", 64), (4, "d", "d", 64)],
+            vec![],
+            vec![],
+        ])?;
+
+        assert!(matches!(
+            compactor.choose(&levels, &Config::default()),
+            Choice::Merge(_)
+        ));
+
+        Ok(())
+    }
+
     #[test]
     fn leveled_more_than_min_with_overlap() -> crate::Result<()> {
         let tempdir = tempfile::tempdir()?;
@@ -674,4 +828,69 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn leveled_hot_range() -> crate::Result<()> {
+        let tempdir = tempfile::tempdir()?;
+        let compactor = Strategy {
+            target_size: 64 * 1_024 * 1_024,
+            hot_range_read_threshold: Some(100),
+            ..Default::default()
+        };
+
+        #[rustfmt::skip]
+        let mut levels = build_levels(tempdir.path(), vec![
+            vec![],
+            vec![(1, "a", "c", 1), (2, "h", "k", 1), (3, "x", "z", 1)],
+            vec![(4, "a", "b", 1), (5, "b", "d", 1), (6, "i", "j", 1), (7, "m", "z", 1)],
+            vec![],
+        ])?;
+
+        assert_eq!(
+            compactor.choose(&levels, &Config::default()),
+            Choice::DoNothing
+        );
+
+        let record_reads = |levels: &LevelManifest, id: SegmentId, reads: u64| {
+            for level in &levels.levels {
+                for segment in level.iter().filter(|x| x.id() == id) {
+                    segment.access.restore(&SegmentAccessStats {
+                        reads,
+                        ..Default::default()
+                    });
+                }
+            }
+        };
+
+        record_reads(&levels, 1, 150);
+        record_reads(&levels, 2, 200);
+        record_reads(&levels, 3, 50);
+
+        assert_eq!(
+            compactor.choose(&levels, &Config::default()),
+            Choice::Merge(CompactionInput {
+                dest_level: 2,
+                segment_ids: [2, 6].into_iter().collect::<HashSet<_>>(),
+                target_size: 64 * 1_024 * 1_024
+            })
+        );
+
+        levels.hide_segments(std::iter::once(6));
+
+        assert_eq!(
+            compactor.choose(&levels, &Config::default()),
+            Choice::Merge(CompactionInput {
+                dest_level: 2,
+                segment_ids: [1, 4, 5].into_iter().collect::<HashSet<_>>(),
+                target_size: 64 * 1_024 * 1_024
+            })
+        );
+
+        assert_eq!(
+            Strategy::default().choose(&levels, &Config::default()),
+            Choice::DoNothing
+        );
+
+        Ok(())
+    }
 }
@@ -42,39 +42,83 @@ pub fn choose_least_effort_compaction(segments: &[Segment], n: usize) -> HashSet
     window.iter().map(Segment::id).collect()
 }
 
-impl CompactionStrategy for Strategy {
-    fn get_name(&self) -> &'static str {
-        "MaintenanceStrategy"
-    }
+/// Merges the run of adjacent (by age) L0 segments with the smallest total size,
+/// if L0 has more than `segment_cap` segments.
+pub fn choose_l0_merge(levels: &LevelManifest, segment_cap: usize) -> Choice {
+    let resolved_view = levels.resolved_view();
 
-    fn choose(&self, levels: &LevelManifest, _: &Config) -> Choice {
-        let resolved_view = levels.resolved_view();
+    // NOTE: First level always exists, trivial
+    #[allow(clippy::expect_used)]
+    let first_level = resolved_view.first().expect("L0 should always exist");
+
+    if first_level.len() > segment_cap {
+        // NOTE: +1 because two will merge into one
+        // So if we have 18 segments, and merge two, we'll have 17, not 16
+        let segments_to_merge = first_level.len() - segment_cap + 1;
+
+        // NOTE: Sort the level by oldest to newest
+        // levels are sorted from newest to oldest, so we can just reverse
+        let mut first_level = first_level.clone();
+        first_level.sort_by_seqno();
+        first_level.segments.reverse();
+
+        let segment_ids = choose_least_effort_compaction(&first_level, segments_to_merge);
+
+        Choice::Merge(super::Input {
+            dest_level: 0,
+            segment_ids,
+            target_size: u64::MAX,
+        })
+    } else {
+        Choice::DoNothing
+    }
+}
 
-        // NOTE: First level always exists, trivial
-        #[allow(clippy::expect_used)]
-        let first_level = resolved_view.first().expect("L0 should always exist");
+/// Picks the oldest segment that is older than [`Config::max_segment_age`],
+/// and rewrites it into its own level.
+///
+/// Rewriting a single segment in place never changes which version
+/// of a key is seen first, so it is safe in any level.
+pub fn choose_aged_segment(levels: &LevelManifest, config: &Config) -> Option<Choice> {
+    let max_age = config.max_segment_age?;
+    let now = config.get_clock().now().as_micros();
+    let max_age = max_age.as_micros();
+
+    let hidden_set = levels.hidden_set();
+
+    let (dest_level, segment) = levels
+        .levels
+        .iter()
+        .enumerate()
+        .flat_map(|(idx, level)| level.iter().map(move |segment| (idx, segment)))
+        .filter(|(_, segment)| !hidden_set.is_hidden(segment.id()))
+        .filter(|(_, segment)| now.saturating_sub(segment.metadata.created_at) >= max_age)
+        .min_by_key(|(_, segment)| segment.metadata.created_at)?;
+
+    log::debug!(
+        "Rewriting segment {} in L{dest_level}, because it is older than {:?}",
+        segment.id(),
+        config.max_segment_age,
+    );
 
-        if first_level.len() > L0_SEGMENT_CAP {
-            // NOTE: +1 because two will merge into one
-            // So if we have 18 segments, and merge two, we'll have 17, not 16
-            let segments_to_merge = first_level.len() - L0_SEGMENT_CAP + 1;
+    // NOTE: Level count is 255 max
+    #[allow(clippy::cast_possible_truncation)]
+    let dest_level = dest_level as u8;
 
-            // NOTE: Sort the level by oldest to newest
-            // levels are sorted from newest to oldest, so we can just reverse
-            let mut first_level = first_level.clone();
-            first_level.sort_by_seqno();
-            first_level.segments.reverse();
+    Some(Choice::Merge(super::Input {
+        segment_ids: std::iter::once(segment.id()).collect(),
+        dest_level,
+        target_size: u64::MAX,
+    }))
+}
 
-            let segment_ids = choose_least_effort_compaction(&first_level, segments_to_merge);
+impl CompactionStrategy for Strategy {
+    fn get_name(&self) -> &'static str {
+        "MaintenanceStrategy"
+    }
 
-            Choice::Merge(super::Input {
-                dest_level: 0,
-                segment_ids,
-                target_size: u64::MAX,
-            })
-        } else {
-            Choice::DoNothing
-        }
+    fn choose(&self, levels: &LevelManifest, _: &Config) -> Choice {
+        choose_l0_merge(levels, L0_SEGMENT_CAP)
     }
 }
 
@@ -139,10 +183,20 @@ mod tests {
                 range_tombstone_count: 0,
                 uncompressed_size: 0,
                 seqnos: (0, created_at as u64),
+                temperature: None,
+                filter_fp_rate_ppb: None,
+                global_seqno: None,
             },
             block_cache,
-
-            bloom_filter: Some(crate::bloom::BloomFilter::with_fp_rate(1, 0.1)),
+            statistics: Arc::default(),
+
+            bloom_filter: Some(crate::bloom::BloomFilter::with_fp_rate(1, 0.1).into()).into(),
+            key_sketch: None.into(),
+            key_sketch_ptr: BlockOffset(0),
+            range_tombstones: crate::range_tombstone::RangeTombstoneIndex::default().into(),
+            transform: None,
+            deleted_path: std::sync::OnceLock::new(),
+            access: crate::segment::access_stats::AccessStats::default(),
         }
         .into()
     }
@@ -7,19 +7,18 @@ use crate::{config::Config, level_manifest::LevelManifest, Segment};
 
 /// Major compaction
 ///
-/// Compacts all segments into the last level.
+/// Compacts all segments into the last level, see [`crate::AbstractTree::major_compact`]
 pub struct Strategy {
     target_size: u64,
 }
 
 impl Strategy {
-    /// Configures a new `SizeTiered` compaction strategy.
+    /// Configures a new `Major` compaction strategy.
     ///
     /// # Panics
     ///
     /// Panics, if `target_size` is below 1024 bytes.
     #[must_use]
-    #[allow(dead_code)]
     pub fn new(target_size: u64) -> Self {
         assert!(target_size >= 1_024);
         Self { target_size }
@@ -9,12 +9,15 @@ pub(crate) mod leveled;
 pub(crate) mod maintenance;
 pub(crate) mod major;
 pub(crate) mod pulldown;
+pub(crate) mod scheduler;
 pub(crate) mod stream;
 pub(crate) mod tiered;
 pub(crate) mod worker;
 
 pub use fifo::Strategy as Fifo;
 pub use leveled::Strategy as Leveled;
+pub use major::Strategy as Major;
+pub use scheduler::CompactionScheduler;
 pub use tiered::Strategy as SizeTiered;
 
 use crate::{config::Config, level_manifest::LevelManifest, segment::meta::SegmentId, HashSet};
@@ -30,6 +33,7 @@ pub use pulldown::Strategy as PullDown;
 /// The compaction strategy chooses which segments to compact and how.
 /// That information is given to the compactor.
 #[derive(Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub struct Input {
     /// Segments to compact
     pub segment_ids: HashSet<SegmentId>,
@@ -46,6 +50,7 @@ pub struct Input {
 
 /// Describes what to do (compact or not)
 #[derive(Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub enum Choice {
     /// Just do nothing.
     DoNothing,
@@ -0,0 +1,261 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use super::{Choice, CompactionStrategy};
+use crate::{segment::meta::SegmentId, AbstractTree, AnyTree, HashSet, Tree};
+use std::sync::{Arc, Condvar, Mutex};
+
+#[derive(Default)]
+struct State {
+    /// Set when the levels changed, so a worker should look for compactions
+    triggered: bool,
+
+    /// Number of workers that are currently compacting
+    running: usize,
+
+    /// Set by the application, see [`CompactionScheduler::pause`]
+    paused: bool,
+
+    /// Number of live [`PauseGuard`]s
+    pause_count: usize,
+
+    stopped: bool,
+}
+
+impl State {
+    fn is_paused(&self) -> bool {
+        self.paused || self.pause_count > 0
+    }
+}
+
+/// Keeps background compactions paused until dropped,
+/// see [`CompactionScheduler::pause_guard`]
+pub struct PauseGuard<'a>(&'a CompactionScheduler);
+
+impl Drop for PauseGuard<'_> {
+    fn drop(&mut self) {
+        {
+            let mut state = self.0.state.lock().expect("lock is poisoned");
+            state.pause_count -= 1;
+            state.triggered = true;
+        }
+        self.0.cond.notify_all();
+    }
+}
+
+/// Runs the compaction strategy of the tree in the background,
+/// see [`crate::Config::background_compaction`]
+///
+/// Workers are woken up whenever flushed segments are registered,
+/// and compact as long as the strategy finds something to do.
+#[derive(Default)]
+#[allow(clippy::module_name_repetitions)]
+pub struct CompactionScheduler {
+    state: Mutex<State>,
+    cond: Condvar,
+}
+
+impl CompactionScheduler {
+    /// Wakes up an idle worker, so it checks for pending compactions.
+    pub(crate) fn notify(&self) {
+        self.state.lock().expect("lock is poisoned").triggered = true;
+        self.cond.notify_all();
+    }
+
+    /// Stops all workers, once their current compaction is done.
+    pub(crate) fn stop(&self) {
+        self.state.lock().expect("lock is poisoned").stopped = true;
+        self.cond.notify_all();
+    }
+
+    /// Pauses background compactions.
+    ///
+    /// Compactions that are already running are finished,
+    /// but no new compaction is started until [`CompactionScheduler::resume`] is called.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the scheduler lock is poisoned.
+    pub fn pause(&self) {
+        self.state.lock().expect("lock is poisoned").paused = true;
+        self.cond.notify_all();
+    }
+
+    /// Resumes background compactions after [`CompactionScheduler::pause`],
+    /// and checks for compactions that became pending in the meantime.
+    ///
+    /// Compactions stay paused while the tree pauses them itself, e.g. during a major compaction.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the scheduler lock is poisoned.
+    pub fn resume(&self) {
+        {
+            let mut state = self.state.lock().expect("lock is poisoned");
+            state.paused = false;
+            state.triggered = true;
+        }
+        self.cond.notify_all();
+    }
+
+    /// Pauses background compactions until the returned guard is dropped.
+    ///
+    /// Unlike [`CompactionScheduler::pause`], guards are counted, so compactions
+    /// are only resumed once all guards are dropped (and the application
+    /// did not pause the scheduler itself).
+    pub(crate) fn pause_guard(&self) -> PauseGuard<'_> {
+        self.state.lock().expect("lock is poisoned").pause_count += 1;
+        self.cond.notify_all();
+        PauseGuard(self)
+    }
+
+    /// Returns `true` if background compactions are paused.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the scheduler lock is poisoned.
+    #[must_use]
+    pub fn is_paused(&self) -> bool {
+        self.state.lock().expect("lock is poisoned").is_paused()
+    }
+
+    /// Blocks until no compaction is running or pending.
+    ///
+    /// While paused, this only waits for the running compactions to finish.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the scheduler lock is poisoned.
+    #[allow(clippy::significant_drop_tightening)]
+    pub fn wait_for_idle(&self) {
+        let mut state = self.state.lock().expect("lock is poisoned");
+
+        while state.running > 0 || (state.triggered && !state.is_paused() && !state.stopped) {
+            state = self.cond.wait(state).expect("lock is poisoned");
+        }
+    }
+
+    /// Blocks until a worker should look for compactions.
+    ///
+    /// Returns `false` if the worker should stop.
+    fn wait_for_trigger(&self) -> bool {
+        let mut state = self.state.lock().expect("lock is poisoned");
+
+        loop {
+            if state.stopped {
+                return false;
+            }
+
+            if state.triggered && !state.is_paused() {
+                state.triggered = false;
+                state.running += 1;
+                return true;
+            }
+
+            state = self.cond.wait(state).expect("lock is poisoned");
+        }
+    }
+
+    fn finish_run(&self) {
+        self.state.lock().expect("lock is poisoned").running -= 1;
+        self.cond.notify_all();
+    }
+}
+
+fn segment_ids(tree: &Tree) -> HashSet<SegmentId> {
+    tree.levels
+        .read()
+        .expect("lock is poisoned")
+        .iter()
+        .map(crate::Segment::id)
+        .collect()
+}
+
+/// Compacts as long as the strategy finds something to do.
+fn run(
+    tree: &AnyTree,
+    index: &Tree,
+    strategy: &Arc<dyn CompactionStrategy + Send + Sync>,
+    scheduler: &CompactionScheduler,
+) {
+    loop {
+        if scheduler.is_paused() || index.stop_signal.is_stopped() {
+            return;
+        }
+
+        if tree.pending_work(strategy.as_ref()).next_compaction == Choice::DoNothing {
+            return;
+        }
+
+        let before = segment_ids(index);
+
+        if let Err(e) = tree.compact(strategy.clone(), tree.gc_watermark()) {
+            log::error!("Background compaction failed: {e:?}");
+            return;
+        }
+
+        // NOTE: The compaction may have been declined because another worker
+        // is compacting some of its segments, so stop instead of retrying in a loop
+        if segment_ids(index) == before {
+            return;
+        }
+
+        // NOTE: Let an idle worker look for compactions that can run in parallel
+        scheduler.notify();
+    }
+}
+
+/// Spawns the background compaction workers of a tree.
+///
+/// `make_tree` turns the index tree back into the tree the strategy runs on,
+/// so blob trees can run their blob GC after compacting.
+///
+/// The workers only hold a weak reference to the tree while they are idle,
+/// and stop once the tree is dropped.
+pub fn spawn<F: Fn(Tree) -> AnyTree + Clone + Send + 'static>(
+    tree: &Tree,
+    scheduler: &Arc<CompactionScheduler>,
+    make_tree: F,
+) {
+    let Some(strategy) = tree.config.compaction_strategy.clone() else {
+        return;
+    };
+
+    log::debug!(
+        "Starting {} background compaction worker(s) using {}",
+        tree.config.compaction_threads,
+        strategy.get_name(),
+    );
+
+    for _ in 0..tree.config.compaction_threads {
+        let weak = Arc::downgrade(&tree.0);
+        let scheduler = scheduler.clone();
+        let strategy = strategy.clone();
+        let make_tree = make_tree.clone();
+
+        tree.config.executor().spawn_blocking(Box::new(move || {
+            while scheduler.wait_for_trigger() {
+                let tree = weak.upgrade().map(Tree);
+
+                if let Some(index) = &tree {
+                    run(&make_tree(index.clone()), index, &strategy, &scheduler);
+                }
+
+                // NOTE: Drop the tree before going back to sleep,
+                // so the worker does not keep it alive
+                let is_dropped = tree.is_none();
+                drop(tree);
+
+                scheduler.finish_run();
+
+                if is_dropped {
+                    return;
+                }
+            }
+        }));
+    }
+
+    // NOTE: Check for compactions left over from before the tree was reopened
+    scheduler.notify();
+}
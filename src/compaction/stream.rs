@@ -2,8 +2,12 @@
 // This source code is licensed under both the Apache 2.0 and MIT License
 // (found in the LICENSE-* files in the repository)
 
-use crate::{InternalValue, SeqNo, UserKey, ValueType};
-use std::iter::Peekable;
+use crate::{
+    merge_operator::{fold_versions, MergeOperator},
+    range_tombstone::{RangeTombstone, RangeTombstoneIndex},
+    InternalValue, SeqNo, UserKey, ValueType,
+};
+use std::{collections::VecDeque, iter::Peekable, sync::Arc};
 
 /// Consumes a stream of KVs and emits a new stream according to GC and tombstone rules
 ///
@@ -12,6 +16,27 @@ use std::iter::Peekable;
 pub struct CompactionStream<I: Iterator<Item = crate::Result<InternalValue>>> {
     inner: Peekable<I>,
     gc_seqno_threshold: SeqNo,
+
+    /// Seqnos of snapshots that need to keep seeing their versions,
+    /// regardless of the GC threshold
+    snapshot_seqnos: Vec<SeqNo>,
+
+    /// Folds merge operands into the newest version of their key
+    merge_operator: Option<Arc<dyn MergeOperator>>,
+
+    /// Whether the stream contains the oldest versions of its keys,
+    /// so merge operands without base value can be folded as well
+    is_bottommost: bool,
+
+    /// Range tombstones that may delete versions of the stream
+    range_tombstones: RangeTombstoneIndex,
+
+    /// Versions of a merge chain that could not be folded
+    pending: VecDeque<InternalValue>,
+
+    /// Base version of a merge chain that could not be folded,
+    /// which is emitted after the pending versions
+    deferred: Option<InternalValue>,
 }
 
 impl<I: Iterator<Item = crate::Result<InternalValue>>> CompactionStream<I> {
@@ -23,9 +48,101 @@ impl<I: Iterator<Item = crate::Result<InternalValue>>> CompactionStream<I> {
         Self {
             inner: iter,
             gc_seqno_threshold,
+            snapshot_seqnos: Vec::new(),
+            merge_operator: None,
+            is_bottommost: false,
+            range_tombstones: RangeTombstoneIndex::default(),
+            pending: VecDeque::new(),
+            deferred: None,
         }
     }
 
+    /// Folds merge operands using the given operator.
+    ///
+    /// If `is_bottommost` is set, there are no older versions of the keys
+    /// outside of the stream, so operands without base value are folded as well.
+    #[must_use]
+    pub fn with_merge_operator(
+        mut self,
+        merge_operator: Option<Arc<dyn MergeOperator>>,
+        is_bottommost: bool,
+    ) -> Self {
+        self.merge_operator = merge_operator;
+        self.is_bottommost = is_bottommost;
+        self
+    }
+
+    /// Does not fold merge operands onto versions that the given range tombstones delete.
+    #[must_use]
+    pub fn with_range_tombstones(mut self, range_tombstones: Vec<RangeTombstone>) -> Self {
+        self.range_tombstones = RangeTombstoneIndex::new(range_tombstones);
+        self
+    }
+
+    /// Consumes the older versions of the head's key, until the base value of the merge.
+    fn collect_merge_operands(&mut self, head: InternalValue) -> crate::Result<Vec<InternalValue>> {
+        let key = head.key.user_key.clone();
+        let mut versions = vec![head];
+
+        while let Some(next) = self.inner.peek() {
+            let Ok(next) = next else {
+                // NOTE: We just asserted, the peeked value is an error
+                #[allow(clippy::expect_used)]
+                return Err(self
+                    .inner
+                    .next()
+                    .expect("should exist")
+                    .expect_err("should be error"));
+            };
+
+            if next.key.user_key != key {
+                break;
+            }
+
+            let is_base = next.key.value_type != ValueType::Merge;
+
+            // NOTE: We know the next value is not empty, because we just peeked it
+            #[allow(clippy::expect_used)]
+            versions.push(self.inner.next().expect("should not be empty")?);
+
+            if is_base {
+                break;
+            }
+        }
+
+        Ok(versions)
+    }
+
+    /// Returns `true` if the merge chain can be replaced by a single value.
+    fn can_fold(&self, versions: &[InternalValue]) -> bool {
+        let (Some(head), Some(oldest)) = (versions.first(), versions.last()) else {
+            return false;
+        };
+
+        let has_base = oldest.key.value_type != ValueType::Merge;
+
+        // NOTE: The older versions are replaced, so no reader may see them anymore
+        let is_expired = versions.get(1).map_or(true, |newest| {
+            newest.key.seqno < self.gc_seqno_threshold
+                && !is_pinned(&self.snapshot_seqnos, oldest.key.seqno, head.key.seqno)
+        });
+
+        let is_range_deleted = versions.iter().any(|version| {
+            self.range_tombstones
+                .deletes(&version.key.user_key, version.key.seqno)
+        });
+
+        (has_base || self.is_bottommost) && is_expired && !is_range_deleted
+    }
+
+    /// Keeps the versions that snapshots at the given seqnos read.
+    #[must_use]
+    pub fn with_snapshots(mut self, mut snapshot_seqnos: Vec<SeqNo>) -> Self {
+        snapshot_seqnos.sort_unstable();
+        self.snapshot_seqnos = snapshot_seqnos;
+        self
+    }
+
     fn drain_key_min(&mut self, key: &UserKey) -> crate::Result<()> {
         loop {
             let Some(next) = self.inner.peek() else {
@@ -54,14 +171,60 @@ impl<I: Iterator<Item = crate::Result<InternalValue>>> CompactionStream<I> {
     }
 }
 
+/// Returns `true` if a snapshot reads the older version instead of the newer one.
+///
+/// The snapshot seqnos need to be sorted.
+pub fn is_pinned(snapshot_seqnos: &[SeqNo], older: SeqNo, newer: SeqNo) -> bool {
+    // NOTE: A snapshot reads versions with a seqno lower than its own,
+    // so find the lowest snapshot that can read the older version
+    let idx = snapshot_seqnos.partition_point(|&snapshot| snapshot <= older);
+
+    snapshot_seqnos
+        .get(idx)
+        .is_some_and(|&snapshot| snapshot <= newer)
+}
+
 impl<I: Iterator<Item = crate::Result<InternalValue>>> Iterator for CompactionStream<I> {
     type Item = crate::Result<InternalValue>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        loop {
-            let head = fail_iter!(self.inner.next()?);
+        if let Some(version) = self.pending.pop_front() {
+            return Some(Ok(version));
+        }
+
+        'outer: loop {
+            let mut head = match self.deferred.take() {
+                Some(head) => head,
+                None => fail_iter!(self.inner.next()?),
+            };
+
+            // NOTE: Merge operands need their older versions, so they are never dropped below them
+            if head.key.value_type == ValueType::Merge {
+                let mut versions = fail_iter!(self.collect_merge_operands(head));
+
+                if let Some(merge_operator) = self
+                    .merge_operator
+                    .as_ref()
+                    .filter(|_| self.can_fold(&versions))
+                {
+                    head = fail_iter!(fold_versions(&**merge_operator, &versions));
+                } else {
+                    // NOTE: The base version is processed as usual, so its older versions may be dropped
+                    // NOTE: The chain starts with the head, so it is not empty
+                    #[allow(clippy::expect_used)]
+                    let base = versions.pop().expect("should have head");
+
+                    if versions.is_empty() {
+                        head = base;
+                    } else {
+                        self.deferred = Some(base);
+                        self.pending.extend(versions);
+                        return self.pending.pop_front().map(Ok);
+                    }
+                }
+            }
 
-            if let Some(peeked) = self.inner.peek() {
+            while let Some(peeked) = self.inner.peek() {
                 let Ok(peeked) = peeked else {
                     // NOTE: We just asserted, the peeked value is an error
                     #[allow(clippy::expect_used)]
@@ -77,20 +240,36 @@ impl<I: Iterator<Item = crate::Result<InternalValue>>> Iterator for CompactionSt
                     return Some(Ok(head));
                 }
 
-                if peeked.key.seqno < self.gc_seqno_threshold {
+                if peeked.key.seqno < self.gc_seqno_threshold
+                    && !is_pinned(&self.snapshot_seqnos, peeked.key.seqno, head.key.seqno)
+                {
                     // NOTE: If next item is an actual value, and current value is weak tombstone,
                     // drop the tombstone
-                    let drop_weak_tombstone = peeked.key.value_type == ValueType::Value
-                        && head.key.value_type == ValueType::WeakTombstone;
-
-                    // NOTE: Next item is expired,
-                    // so the tail of this user key is entirely expired, so drain it all
-                    fail_iter!(self.drain_key_min(&head.key.user_key));
+                    let drop_weak_tombstone = matches!(
+                        peeked.key.value_type,
+                        ValueType::Value | ValueType::ValueWithExpiry
+                    ) && head.key.value_type == ValueType::WeakTombstone;
+
+                    if self.snapshot_seqnos.is_empty() {
+                        // NOTE: Next item is expired,
+                        // so the tail of this user key is entirely expired, so drain it all
+                        fail_iter!(self.drain_key_min(&head.key.user_key));
+                    } else {
+                        // NOTE: Older versions may still be read by a snapshot,
+                        // so only skip the next version
+                        fail_iter!(self.inner.next()?);
+
+                        if !drop_weak_tombstone {
+                            continue;
+                        }
+                    }
 
                     if drop_weak_tombstone {
-                        continue;
+                        continue 'outer;
                     }
                 }
+
+                break;
             }
 
             return Some(Ok(head));
@@ -120,6 +299,7 @@ mod tests {
                     "V" => ValueType::Value,
                     "T" => ValueType::Tombstone,
                     "W" => ValueType::WeakTombstone,
+                    "M" => ValueType::Merge,
                     _ => panic!("Unknown value type"),
                 };
 
@@ -156,6 +336,63 @@ mod tests {
         iter_closed!(iter);
     }
 
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn compaction_stream_snapshot_keeps_version() -> crate::Result<()> {
+        #[rustfmt::skip]
+        let vec = stream![
+          "a", "new", "V",
+          "a", "mid", "V",
+          "a", "old", "V",
+        ];
+
+        let iter = vec.iter().cloned().map(Ok);
+        let mut iter = CompactionStream::new(iter, SeqNo::MAX).with_snapshots(vec![999]);
+
+        assert_eq!(
+            InternalValue::from_components(*b"a", *b"new", 999, ValueType::Value),
+            iter.next().unwrap()?,
+        );
+        assert_eq!(
+            InternalValue::from_components(*b"a", *b"mid", 998, ValueType::Value),
+            iter.next().unwrap()?,
+        );
+        iter_closed!(iter);
+
+        Ok(())
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn compaction_stream_snapshot_skips_unread_version() -> crate::Result<()> {
+        #[rustfmt::skip]
+        let vec = stream![
+          "a", "new", "V",
+          "a", "mid", "V",
+          "a", "old", "V",
+          "b", "new", "V",
+        ];
+
+        let iter = vec.iter().cloned().map(Ok);
+        let mut iter = CompactionStream::new(iter, SeqNo::MAX).with_snapshots(vec![998]);
+
+        assert_eq!(
+            InternalValue::from_components(*b"a", *b"new", 999, ValueType::Value),
+            iter.next().unwrap()?,
+        );
+        assert_eq!(
+            InternalValue::from_components(*b"a", *b"old", 997, ValueType::Value),
+            iter.next().unwrap()?,
+        );
+        assert_eq!(
+            InternalValue::from_components(*b"b", *b"new", 999, ValueType::Value),
+            iter.next().unwrap()?,
+        );
+        iter_closed!(iter);
+
+        Ok(())
+    }
+
     /// GC should not evict tombstones, unless they are covered up
     #[test]
     #[allow(clippy::unwrap_used)]
@@ -429,4 +666,96 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn compaction_stream_merge() -> crate::Result<()> {
+        #[rustfmt::skip]
+        let vec = stream![
+          "a", "c", "M",
+          "a", "b", "M",
+          "a", "a", "V",
+          "a", "old", "V",
+          "b", "y", "M",
+          "b", "x", "M",
+        ];
+
+        let iter = vec.iter().cloned().map(Ok);
+        let mut iter = CompactionStream::new(iter, SeqNo::MAX)
+            .with_merge_operator(Some(Arc::new(crate::merge_operator::Concat)), false);
+
+        assert_eq!(
+            InternalValue::from_components(*b"a", *b"abc", 999, ValueType::Value),
+            iter.next().unwrap()?,
+        );
+
+        // NOTE: There may be older versions in other segments, so the operands are kept
+        assert_eq!(
+            InternalValue::from_components(*b"b", *b"y", 999, ValueType::Merge),
+            iter.next().unwrap()?,
+        );
+        assert_eq!(
+            InternalValue::from_components(*b"b", *b"x", 998, ValueType::Merge),
+            iter.next().unwrap()?,
+        );
+        iter_closed!(iter);
+
+        Ok(())
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn compaction_stream_merge_bottommost() -> crate::Result<()> {
+        #[rustfmt::skip]
+        let vec = stream![
+          "b", "y", "M",
+          "b", "x", "M",
+        ];
+
+        let iter = vec.iter().cloned().map(Ok);
+        let mut iter = CompactionStream::new(iter, SeqNo::MAX)
+            .with_merge_operator(Some(Arc::new(crate::merge_operator::Concat)), true);
+
+        assert_eq!(
+            InternalValue::from_components(*b"b", *b"xy", 999, ValueType::Value),
+            iter.next().unwrap()?,
+        );
+        iter_closed!(iter);
+
+        Ok(())
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn compaction_stream_merge_snapshot() -> crate::Result<()> {
+        #[rustfmt::skip]
+        let vec = stream![
+          "a", "c", "M",
+          "a", "b", "M",
+          "a", "a", "V",
+          "a", "old", "V",
+        ];
+
+        // NOTE: The snapshot reads the second operand, so the chain is kept
+        let iter = vec.iter().cloned().map(Ok);
+        let mut iter = CompactionStream::new(iter, SeqNo::MAX)
+            .with_snapshots(vec![999])
+            .with_merge_operator(Some(Arc::new(crate::merge_operator::Concat)), false);
+
+        assert_eq!(
+            InternalValue::from_components(*b"a", *b"c", 999, ValueType::Merge),
+            iter.next().unwrap()?,
+        );
+        assert_eq!(
+            InternalValue::from_components(*b"a", *b"b", 998, ValueType::Merge),
+            iter.next().unwrap()?,
+        );
+        assert_eq!(
+            InternalValue::from_components(*b"a", *b"a", 997, ValueType::Value),
+            iter.next().unwrap()?,
+        );
+        iter_closed!(iter);
+
+        Ok(())
+    }
 }
@@ -122,7 +122,12 @@ impl CompactionStrategy for Strategy {
         // NOTE: Reduce L0 segments if needed
         // this is probably an edge case if the `base_size` does not line up with
         // the `max_memtable_size` AT ALL
-        super::maintenance::Strategy.choose(levels, config)
+        match super::maintenance::Strategy.choose(levels, config) {
+            Choice::DoNothing => {
+                super::maintenance::choose_aged_segment(levels, config).unwrap_or(Choice::DoNothing)
+            }
+            choice => choice,
+        }
     }
 }
 
@@ -189,10 +194,20 @@ mod tests {
                 range_tombstone_count: 0,
                 uncompressed_size: size_mib * 1_024 * 1_024,
                 seqnos: (0, max_seqno),
+                temperature: None,
+                filter_fp_rate_ppb: None,
+                global_seqno: None,
             },
             block_cache,
-
-            bloom_filter: Some(BloomFilter::with_fp_rate(1, 0.1)),
+            statistics: Arc::default(),
+
+            bloom_filter: Some(BloomFilter::with_fp_rate(1, 0.1).into()).into(),
+            key_sketch: None.into(),
+            key_sketch_ptr: BlockOffset(0),
+            range_tombstones: crate::range_tombstone::RangeTombstoneIndex::default().into(),
+            transform: None,
+            deleted_path: std::sync::OnceLock::new(),
+            access: crate::segment::access_stats::AccessStats::default(),
         }
         .into()
     }
@@ -4,11 +4,16 @@
 
 use super::{CompactionStrategy, Input as CompactionPayload};
 use crate::{
-    compaction::{stream::CompactionStream, Choice},
+    compaction::{
+        stream::{is_pinned, CompactionStream},
+        Choice,
+    },
     file::SEGMENTS_FOLDER,
+    key_range::KeyRange,
     level_manifest::LevelManifest,
     level_scanner::LevelScanner,
     merge::Merger,
+    range_tombstone::RangeTombstoneIndex,
     segment::{
         block_index::{
             full_index::FullBlockIndex, two_level_index::TwoLevelBlockIndex, BlockIndexImpl,
@@ -16,16 +21,19 @@ use crate::{
         id::GlobalSegmentId,
         multi_writer::MultiWriter,
         scanner::CompactionReader,
+        trailer::SegmentFileTrailer,
+        value_block::CachePolicy,
         Segment, SegmentInner,
     },
     stop_signal::StopSignal,
     tree::inner::{SealedMemtables, TreeId},
-    Config, SegmentId, SeqNo,
+    AbstractTree, Config, InternalValue, RangeTombstone, SegmentId, SeqNo, Temperature, UserKey,
 };
 use std::{
+    ops::Bound,
     path::Path,
     sync::{atomic::AtomicU64, Arc, RwLock, RwLockWriteGuard},
-    time::Instant,
+    time::{Duration, Instant},
 };
 
 /// Compaction options
@@ -52,6 +60,19 @@ pub struct Options {
 
     /// Evicts items that are older than this seqno (MVCC GC).
     pub eviction_seqno: u64,
+
+    /// Seqnos of named and open snapshots, whose versions need to be kept.
+    pub snapshot_seqnos: Vec<SeqNo>,
+
+    /// Data below this seqno is older than the cold data age.
+    pub cold_seqno: Option<SeqNo>,
+
+    /// Tombstones in segments created after this time (in µs) may be
+    /// within the tombstone grace period.
+    pub tombstone_grace_cutoff: Option<u128>,
+
+    /// Tombstones below this seqno are older than the tombstone grace period.
+    pub tombstone_grace_seqno: Option<SeqNo>,
 }
 
 impl Options {
@@ -65,6 +86,19 @@ impl Options {
             stop_signal: tree.stop_signal.clone(),
             strategy,
             eviction_seqno: 0,
+            snapshot_seqnos: tree.pinned_snapshot_seqnos(),
+            cold_seqno: tree.config.cold_data_age.and_then(|age| {
+                let now = tree.config.get_clock().now();
+                tree.seqno_at_time(now.saturating_sub(age))
+            }),
+            tombstone_grace_cutoff: tree.config.tombstone_grace_period.map(|period| {
+                let now = tree.config.get_clock().now();
+                now.saturating_sub(period).as_micros()
+            }),
+            tombstone_grace_seqno: tree.config.tombstone_grace_period.and_then(|period| {
+                let now = tree.config.get_clock().now();
+                tree.seqno_at_time(now.saturating_sub(period))
+            }),
         }
     }
 }
@@ -107,6 +141,8 @@ fn create_compaction_stream<'a, P: AsRef<Path>>(
     levels: &LevelManifest,
     to_compact: &[SegmentId],
     eviction_seqno: SeqNo,
+    snapshot_seqnos: Vec<SeqNo>,
+    now: Duration,
 ) -> crate::Result<Option<CompactionStream<Merger<CompactionReader<'a>>>>> {
     let mut readers: Vec<CompactionReader<'_>> = vec![];
     let mut found = 0;
@@ -156,13 +192,26 @@ fn create_compaction_stream<'a, P: AsRef<Path>>(
         }
     }
 
+    let readers = readers
+        .into_iter()
+        .map(|reader| expire_items(reader, now))
+        .collect();
+
     Ok(if found == to_compact.len() {
-        Some(CompactionStream::new(Merger::new(readers), eviction_seqno))
+        Some(
+            CompactionStream::new(Merger::new(readers), eviction_seqno)
+                .with_snapshots(snapshot_seqnos),
+        )
     } else {
         None
     })
 }
 
+/// Turns expired values into tombstones, so they are dropped like deleted items.
+fn expire_items(reader: CompactionReader<'_>, now: Duration) -> CompactionReader<'_> {
+    Box::new(reader.map(move |item| item.map(|item| crate::ttl::expire(item, now))))
+}
+
 fn move_segments(
     mut levels: RwLockWriteGuard<'_, LevelManifest>,
     opts: &Options,
@@ -191,6 +240,80 @@ fn move_segments(
     })
 }
 
+/// Validates the output of a compaction before it is registered,
+/// see [`Config::paranoid_checks`]
+fn check_compaction_result(
+    levels: &LevelManifest,
+    payload: &CompactionPayload,
+    input_seqnos: Option<(SeqNo, SeqNo)>,
+    created_segments: &[Segment],
+) -> crate::Result<()> {
+    if let Some((lo, hi)) = input_seqnos {
+        for segment in created_segments {
+            let (min, max) = segment.metadata.seqnos;
+
+            if min < lo || max > hi {
+                log::error!(
+                    "Compaction output segment {} has seqnos {min}..={max}, but input only had {lo}..={hi}",
+                    segment.id(),
+                );
+                return Err(crate::Error::InvariantViolation(
+                    "compaction produced seqnos outside of its input",
+                ));
+            }
+        }
+    }
+
+    let ranges = created_segments
+        .iter()
+        .map(|segment| &segment.metadata.key_range)
+        .collect::<Vec<_>>();
+
+    if !KeyRange::is_disjoint(&ranges) {
+        log::error!("Compaction output segments overlap: {ranges:?}");
+        return Err(crate::Error::InvariantViolation(
+            "compaction produced overlapping segments",
+        ));
+    }
+
+    if payload.dest_level == 0 {
+        return Ok(());
+    }
+
+    let Some(dest_level) = levels.levels.get(payload.dest_level as usize) else {
+        return Ok(());
+    };
+
+    // NOTE: Tiered compaction stacks runs on top of each other, so only
+    // merges into a disjoint level need to keep it disjoint
+    let merges_into_level = dest_level
+        .iter()
+        .any(|segment| payload.segment_ids.contains(&segment.id()));
+
+    if !dest_level.is_disjoint || !merges_into_level {
+        return Ok(());
+    }
+
+    let ranges = dest_level
+        .iter()
+        .filter(|segment| !payload.segment_ids.contains(&segment.id()))
+        .chain(created_segments)
+        .map(|segment| &segment.metadata.key_range)
+        .collect::<Vec<_>>();
+
+    if !KeyRange::is_disjoint(&ranges) {
+        log::error!(
+            "Compaction output overlaps with L{}: {ranges:?}",
+            payload.dest_level,
+        );
+        return Err(crate::Error::InvariantViolation(
+            "compaction produced overlapping segments in a disjoint level",
+        ));
+    }
+
+    Ok(())
+}
+
 #[allow(clippy::too_many_lines)]
 fn merge_segments(
     mut levels: RwLockWriteGuard<'_, LevelManifest>,
@@ -212,12 +335,15 @@ fn merge_segments(
     }
 
     let segments_base_folder = opts.config.path.join(SEGMENTS_FOLDER);
+    let now = opts.config.get_clock().now();
 
     let Some(merge_iter) = create_compaction_stream(
         &segments_base_folder,
         &levels,
         &payload.segment_ids.iter().copied().collect::<Vec<_>>(),
         opts.eviction_seqno,
+        opts.snapshot_seqnos.clone(),
+        now,
     )?
     else {
         log::warn!(
@@ -228,6 +354,76 @@ fn merge_segments(
 
     let last_level = levels.last_level_index();
 
+    let bytes_read = levels
+        .iter()
+        .filter(|segment| payload.segment_ids.contains(&segment.id()))
+        .map(|segment| segment.metadata.file_size)
+        .sum::<u64>();
+
+    let input_seqnos = levels
+        .iter()
+        .filter(|segment| payload.segment_ids.contains(&segment.id()))
+        .map(|segment| segment.metadata.seqnos)
+        .reduce(|(lo, hi), (min, max)| (lo.min(min), hi.max(max)));
+
+    // NOTE: Segments only contain data written before they were created
+    let inputs_past_tombstone_grace = opts.tombstone_grace_cutoff.map_or(true, |cutoff| {
+        levels
+            .iter()
+            .filter(|segment| payload.segment_ids.contains(&segment.id()))
+            .all(|segment| segment.metadata.created_at < cutoff)
+    });
+
+    // NOTE: Range tombstones of the input segments are carried into the output,
+    // flagged whether some segment outside of the compaction may contain keys they delete
+    let mut range_tombstones = vec![];
+
+    for segment in levels
+        .iter()
+        .filter(|segment| payload.segment_ids.contains(&segment.id()))
+    {
+        for tombstone in segment.range_tombstones()? {
+            let overlaps_other_segments = levels
+                .iter()
+                .filter(|other| !payload.segment_ids.contains(&other.id()))
+                .any(|other| {
+                    let (min, max) = &*other.metadata.key_range;
+                    tombstone.overlaps(min, max)
+                });
+
+            range_tombstones.push((tombstone.clone(), overlaps_other_segments));
+        }
+    }
+
+    // NOTE: Sorted like a range tombstone index, see `write_output`
+    range_tombstones.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    // NOTE: Large compactions are split into disjoint key ranges, which are compacted in parallel
+    //
+    // Range tombstones may span multiple key ranges, so those compactions are not split
+    let sub_compactions = if range_tombstones.is_empty() {
+        let inputs = levels
+            .iter()
+            .filter(|segment| payload.segment_ids.contains(&segment.id()))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        sub_compaction_ranges(&inputs, payload.target_size, opts.config.max_subcompactions)
+            .into_iter()
+            .map(|range| {
+                let segments = inputs
+                    .iter()
+                    .filter(|segment| segment.metadata.key_range.overlaps_with_bounds(&range))
+                    .cloned()
+                    .collect();
+
+                (range, segments)
+            })
+            .collect::<Vec<_>>()
+    } else {
+        vec![]
+    };
+
     levels.hide_segments(payload.segment_ids.iter().copied());
 
     // IMPORTANT: Free lock so the compaction (which may go on for a while)
@@ -238,74 +434,60 @@ fn merge_segments(
     // That way we don't resurrect data beneath the tombstone
     let is_last_level = payload.dest_level == last_level;
 
-    let start = Instant::now();
+    let mut snapshot_seqnos = opts.snapshot_seqnos.clone();
+    snapshot_seqnos.sort_unstable();
 
-    let Ok(segment_writer) = MultiWriter::new(
-        opts.segment_id_generator.clone(),
-        payload.target_size,
-        crate::segment::writer::Options {
-            folder: segments_base_folder.clone(),
-            segment_id: 0, // TODO: this is never used in MultiWriter
-            data_block_size: opts.config.data_block_size,
-            index_block_size: opts.config.index_block_size,
-        },
-    ) else {
-        log::error!("Compaction failed");
-
-        // IMPORTANT: Show the segments again, because compaction failed
-        opts.levels
-            .write()
-            .expect("lock is poisoned")
-            .show_segments(payload.segment_ids.iter().copied());
+    let is_cold_data = opts
+        .cold_seqno
+        .zip(input_seqnos)
+        .is_some_and(|(cold_seqno, (_, max))| max < cold_seqno);
 
-        return Ok(());
+    let temperature = if is_cold_data {
+        Temperature::Cold
+    } else {
+        Temperature::for_level(payload.dest_level, opts.config.level_count)
     };
 
-    let mut segment_writer = segment_writer.use_compression(opts.config.compression);
-
-    {
-        use crate::segment::writer::BloomConstructionPolicy;
-
-        if opts.config.bloom_bits_per_key >= 0 {
-            // NOTE: Apply some MONKEY to have very high FPR on small levels
-            // because it's cheap
-            //
-            // See https://nivdayan.github.io/monkeykeyvaluestore.pdf
-            let bloom_policy = match payload.dest_level {
-                0 => BloomConstructionPolicy::FpRate(0.00001),
-                1 => BloomConstructionPolicy::FpRate(0.0005),
-                _ => BloomConstructionPolicy::BitsPerKey(
-                    opts.config.bloom_bits_per_key.unsigned_abs(),
-                ),
-            };
+    // NOTE: Sub-compactions run as executor tasks, so the parameters are owned
+    let params = Arc::new(OutputParams {
+        config: opts.config.clone(),
+        segment_id_generator: opts.segment_id_generator.clone(),
+        stop_signal: opts.stop_signal.clone(),
+        target_size: payload.target_size,
+        dest_level: payload.dest_level,
+        temperature,
+        is_last_level,
+        inputs_past_tombstone_grace,
+        eviction_seqno: opts.eviction_seqno,
+        tombstone_grace_seqno: opts.tombstone_grace_seqno,
+        snapshot_seqnos,
+        now,
+    });
 
-            segment_writer = segment_writer.use_bloom_policy(bloom_policy);
-        } else {
-            segment_writer =
-                segment_writer.use_bloom_policy(BloomConstructionPolicy::BitsPerKey(0));
-        }
-    }
+    let start = Instant::now();
 
-    for (idx, item) in merge_iter.enumerate() {
-        let Ok(item) = item else {
-            log::error!("Compaction failed");
+    let result = if sub_compactions.is_empty() {
+        let merge_iter = merge_iter
+            .with_merge_operator(opts.config.merge_operator.clone(), is_last_level)
+            .with_range_tombstones(range_tombstones.iter().map(|(t, _)| t.clone()).collect());
 
-            // IMPORTANT: Show the segments again, because compaction failed
-            opts.levels
-                .write()
-                .expect("lock is poisoned")
-                .show_segments(payload.segment_ids.iter().copied());
+        write_output(&params, merge_iter, &range_tombstones)
+    } else {
+        drop(merge_iter);
 
-            return Ok(());
-        };
+        log::debug!(
+            "Splitting compaction into {} sub-compactions",
+            sub_compactions.len()
+        );
 
-        // IMPORTANT: We can only drop tombstones when writing into last level
-        if is_last_level && item.is_tombstone() {
-            continue;
-        }
+        run_sub_compactions(&params, sub_compactions)
+    };
 
-        if segment_writer.write(item).is_err() {
-            log::error!("Compaction failed");
+    let writer_results = match result {
+        Ok(Some(writer_results)) => writer_results,
+        Ok(None) => return Ok(()),
+        Err(e) => {
+            log::error!("Compaction failed: {e:?}");
 
             // IMPORTANT: Show the segments again, because compaction failed
             opts.levels
@@ -313,25 +495,13 @@ fn merge_segments(
                 .expect("lock is poisoned")
                 .show_segments(payload.segment_ids.iter().copied());
 
-            return Ok(());
-        };
+            // NOTE: Invariant violations should not go unnoticed
+            if matches!(e, crate::Error::InvariantViolation(_)) {
+                return Err(e);
+            }
 
-        if idx % 100_000 == 0 && opts.stop_signal.is_stopped() {
-            log::debug!("compactor: stopping amidst compaction because of stop signal");
             return Ok(());
         }
-    }
-
-    let Ok(writer_results) = segment_writer.finish() else {
-        log::error!("Compaction failed");
-
-        // IMPORTANT: Show the segments again, because compaction failed
-        opts.levels
-            .write()
-            .expect("lock is poisoned")
-            .show_segments(payload.segment_ids.iter().copied());
-
-        return Ok(());
     };
 
     log::debug!(
@@ -352,6 +522,7 @@ fn merge_segments(
                         &segment_file_path,
                         &trailer.metadata,
                         &trailer.offsets,
+                        trailer.transform.as_ref(),
                     )?;
                     BlockIndexImpl::Full(block_index)
                 }
@@ -366,6 +537,7 @@ fn merge_segments(
                         (opts.tree_id, segment_id).into(),
                         opts.config.descriptor_table.clone(),
                         opts.config.block_cache.clone(),
+                        trailer.transform.clone(),
                     )?;
                     BlockIndexImpl::TwoLevel(block_index)
                 }
@@ -377,6 +549,7 @@ fn merge_segments(
 
                 descriptor_table: opts.config.descriptor_table.clone(),
                 block_cache: opts.config.block_cache.clone(),
+                statistics: opts.config.statistics.clone(),
 
                 metadata: trailer.metadata,
                 offsets: trailer.offsets,
@@ -384,7 +557,29 @@ fn merge_segments(
                 #[allow(clippy::needless_borrows_for_generic_args)]
                 block_index,
 
-                bloom_filter: Segment::load_bloom(&segment_file_path, trailer.offsets.bloom_ptr)?,
+                bloom_filter: Segment::load_bloom(
+                    &segment_file_path,
+                    trailer.offsets.bloom_ptr,
+                    trailer.transform.as_ref(),
+                )?
+                .into(),
+
+                key_sketch: Segment::load_key_sketch(
+                    &segment_file_path,
+                    trailer.key_sketch_ptr,
+                    trailer.transform.as_ref(),
+                )?
+                .into(),
+                key_sketch_ptr: trailer.key_sketch_ptr,
+                range_tombstones: Segment::load_range_tombstones(
+                    &segment_file_path,
+                    trailer.offsets.range_tombstones_ptr,
+                    trailer.transform.as_ref(),
+                )?
+                .into(),
+                transform: trailer.transform,
+                deleted_path: std::sync::OnceLock::new(),
+                access: crate::segment::access_stats::AccessStats::default(),
             }
             .into())
         })
@@ -410,6 +605,37 @@ fn merge_segments(
     log::trace!("compactor: acquiring sealed memtables write lock");
     let sealed_memtables_guard = opts.sealed_memtables.write().expect("lock is poisoned");
 
+    // NOTE: If the tree was cleared in the meantime, the input segments are gone,
+    // and the compacted segments would resurrect cleared data
+    if !payload
+        .segment_ids
+        .iter()
+        .all(|segment_id| levels.iter().any(|segment| segment.id() == *segment_id))
+    {
+        log::debug!("Input segments were removed while compacting, discarding compaction result");
+
+        for segment in &created_segments {
+            segment.mark_as_deleted(segments_base_folder.join(segment.id().to_string()));
+        }
+
+        levels.show_segments(payload.segment_ids.iter().copied());
+        return Ok(());
+    }
+
+    if opts.config.paranoid_checks {
+        if let Err(e) = check_compaction_result(&levels, payload, input_seqnos, &created_segments) {
+            // IMPORTANT: Show the segments again, because compaction failed
+            levels.show_segments(payload.segment_ids.iter().copied());
+            return Err(e);
+        }
+    }
+
+    let old_segments = levels
+        .iter()
+        .filter(|segment| payload.segment_ids.contains(&segment.id()))
+        .cloned()
+        .collect::<Vec<_>>();
+
     let swap_result = levels.atomic_swap(|recipe| {
         for segment in created_segments.iter().cloned() {
             log::trace!("Persisting segment {}", segment.id());
@@ -435,6 +661,35 @@ fn merge_segments(
         return Err(e);
     }
 
+    opts.config.statistics.record_compaction(
+        bytes_read,
+        created_segments
+            .iter()
+            .map(|segment| segment.metadata.file_size)
+            .sum(),
+    );
+
+    if let Some(threshold) = opts.config.slow_operation_threshold {
+        let elapsed = start.elapsed();
+
+        if elapsed >= threshold {
+            crate::slow_operation::report(
+                &opts.config,
+                crate::SlowOperation {
+                    tree_id: opts.tree_id,
+                    kind: crate::SlowOperationKind::Compaction {
+                        strategy: opts.strategy.get_name(),
+                        dest_level: payload.dest_level,
+                        output_segments: created_segments.iter().map(Segment::id).collect(),
+                    },
+                    seqno: None,
+                    elapsed,
+                    segments_read: payload.segment_ids.iter().copied().collect(),
+                },
+            );
+        }
+    }
+
     for segment in &created_segments {
         let segment_file_path = segments_base_folder.join(segment.id().to_string());
 
@@ -449,13 +704,12 @@ fn merge_segments(
     // NOTE: If the application were to crash >here< it's fine
     // The segments are not referenced anymore, and will be
     // cleaned up upon recovery
-    for segment_id in &payload.segment_ids {
-        let segment_file_path = segments_base_folder.join(segment_id.to_string());
-        log::trace!("Removing old segment at {segment_file_path:?}");
-
-        if let Err(e) = std::fs::remove_file(segment_file_path) {
-            log::error!("Failed to cleanup file of deleted segment: {e:?}");
-        }
+    //
+    // Readers may still hold a level view containing the old segments,
+    // so their files are only removed once the last reference is gone
+    for segment in &old_segments {
+        let segment_file_path = segments_base_folder.join(segment.id().to_string());
+        segment.mark_as_deleted(segment_file_path);
     }
 
     // NOTE: Unlock level manifest before clearing old file descriptors
@@ -472,18 +726,320 @@ fn merge_segments(
         "Closing file handles for old segment files: {:?}",
         payload.segment_ids
     );
-
-    for segment_id in &payload.segment_ids {
-        opts.config
-            .descriptor_table
-            .remove((opts.tree_id, *segment_id).into());
-    }
+    drop(old_segments);
 
     log::trace!("Compaction successful");
 
     Ok(())
 }
 
+/// Parameters that are shared by all sub-compactions of a merge
+struct OutputParams {
+    config: Config,
+    segment_id_generator: Arc<AtomicU64>,
+    stop_signal: StopSignal,
+    target_size: u64,
+    dest_level: u8,
+    temperature: Temperature,
+    is_last_level: bool,
+    inputs_past_tombstone_grace: bool,
+    eviction_seqno: SeqNo,
+    tombstone_grace_seqno: Option<SeqNo>,
+
+    /// Sorted seqnos of snapshots
+    snapshot_seqnos: Vec<SeqNo>,
+
+    /// Point in time that values expire at
+    now: Duration,
+}
+
+/// Key range of a sub-compaction, and the input segments that overlap it
+type SubCompaction = ((Bound<UserKey>, Bound<UserKey>), Vec<Segment>);
+
+/// Splits the input of a compaction into up to `max_subcompactions` disjoint key ranges.
+///
+/// The start keys of the input segments are used as boundaries, and the input
+/// is only split so far that every sub-compaction writes about one segment or more.
+///
+/// Returns an empty vector if the compaction should not be split.
+fn sub_compaction_ranges(
+    inputs: &[Segment],
+    target_size: u64,
+    max_subcompactions: usize,
+) -> Vec<(Bound<UserKey>, Bound<UserKey>)> {
+    let bytes = inputs
+        .iter()
+        .map(|segment| segment.metadata.file_size)
+        .sum::<u64>();
+
+    let count = usize::try_from(bytes / target_size.max(1))
+        .unwrap_or(usize::MAX)
+        .min(max_subcompactions);
+
+    let mut boundaries = inputs
+        .iter()
+        .map(|segment| segment.metadata.key_range.min().clone())
+        .collect::<Vec<_>>();
+    boundaries.sort();
+    boundaries.dedup();
+
+    // NOTE: The lowest start key does not split anything
+    let candidates = boundaries.get(1..).unwrap_or_default();
+    let count = count.min(candidates.len() + 1);
+
+    if count <= 1 {
+        return vec![];
+    }
+
+    let mut boundaries = (1..count)
+        .filter_map(|idx| candidates.get(idx * candidates.len() / count))
+        .cloned()
+        .collect::<Vec<_>>();
+    boundaries.dedup();
+
+    let mut ranges = Vec::with_capacity(boundaries.len() + 1);
+    let mut lo = Bound::Unbounded;
+
+    for boundary in boundaries {
+        ranges.push((lo, Bound::Excluded(boundary.clone())));
+        lo = Bound::Included(boundary);
+    }
+    ranges.push((lo, Bound::Unbounded));
+
+    ranges
+}
+
+/// Runs the sub-compactions on the executor of the tree,
+/// using at most `max_subcompactions` tasks at once.
+///
+/// Returns the output segments of all sub-compactions in key order,
+/// or `None` if any of them was stopped.
+fn run_sub_compactions(
+    params: &Arc<OutputParams>,
+    sub_compactions: Vec<SubCompaction>,
+) -> crate::Result<Option<Vec<SegmentFileTrailer>>> {
+    let jobs = sub_compactions
+        .into_iter()
+        .map(|(range, segments)| {
+            let params = params.clone();
+
+            move || {
+                // NOTE: Do not pollute the block cache with compaction reads
+                let readers = segments
+                    .iter()
+                    .map(|segment| {
+                        expire_items(
+                            Box::new(segment.range(range.clone()).cache_policy(CachePolicy::Read)),
+                            params.now,
+                        )
+                    })
+                    .collect::<Vec<_>>();
+
+                let merge_iter = CompactionStream::new(Merger::new(readers), params.eviction_seqno)
+                    .with_snapshots(params.snapshot_seqnos.clone())
+                    .with_merge_operator(
+                        params.config.merge_operator.clone(),
+                        params.is_last_level,
+                    );
+
+                write_output(&params, merge_iter, &[])
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let results = crate::executor::run_bounded(
+        &*params.config.executor(),
+        jobs,
+        params.config.max_subcompactions,
+    );
+
+    let mut writer_results = vec![];
+
+    for result in results {
+        let Some(trailers) = result? else {
+            return Ok(None);
+        };
+        writer_results.extend(trailers);
+    }
+
+    Ok(Some(writer_results))
+}
+
+/// Writes the output of a (sub-)compaction into new segment files.
+///
+/// The range tombstones need to be sorted.
+///
+/// Returns `None` if the compaction was stopped.
+fn write_output<I: Iterator<Item = crate::Result<InternalValue>>>(
+    params: &OutputParams,
+    merge_iter: I,
+    range_tombstones: &[(RangeTombstone, bool)],
+) -> crate::Result<Option<Vec<SegmentFileTrailer>>> {
+    let segments_base_folder = params.config.path.join(SEGMENTS_FOLDER);
+
+    let mut segment_writer = MultiWriter::new(
+        params.segment_id_generator.clone(),
+        params.target_size,
+        crate::segment::writer::Options {
+            folder: segments_base_folder,
+            segment_id: 0, // TODO: this is never used in MultiWriter
+            data_block_size: params.config.data_block_size,
+            index_block_size: params.config.index_block_size,
+        },
+    )?
+    .use_compression(params.config.compression)
+    .use_bloom_layout(params.config.bloom_layout)
+    .use_filter_type(params.config.compaction_filter_type)
+    .use_block_size_policy(params.config.block_size_policy)
+    .use_transform(params.config.current_transform())
+    .use_clock(params.config.get_clock())
+    .use_temperature(Some(params.temperature))
+    .use_paranoid_checks(params.config.paranoid_checks)
+    .use_partition_prefix_len(params.config.partition_prefix_len);
+
+    segment_writer = segment_writer.use_bloom_policy(params.config.bloom_policy(params.dest_level));
+
+    let mut merge_iter = merge_iter.enumerate().peekable();
+
+    // NOTE: Range tombstones are split at the segment boundaries, so every output segment
+    // only holds the part of a tombstone that covers its own key range
+    //
+    // A part that still deletes an item in its segment needs to be kept
+    let mut is_range_tombstone_needed = vec![false; range_tombstones.len()];
+
+    // NOTE: The tombstones are already sorted, so their positions in the index match
+    let range_tombstone_index =
+        RangeTombstoneIndex::new(range_tombstones.iter().map(|(t, _)| t.clone()).collect());
+    let mut needed_by_item = vec![];
+    let mut segment_start: Option<UserKey> = None;
+
+    while let Some((idx, item)) = merge_iter.next() {
+        let item = item?;
+
+        // IMPORTANT: We can only drop tombstones when writing into last level
+        //
+        // If an older version of the key survived (because a snapshot still needs it),
+        // the tombstone needs to be kept, otherwise the older version would be resurrected
+        //
+        // Tombstones within the grace period are kept as well
+        if params.is_last_level
+            && item.is_tombstone()
+            && (params.inputs_past_tombstone_grace
+                || params
+                    .tombstone_grace_seqno
+                    .is_some_and(|grace_seqno| item.key.seqno < grace_seqno))
+            && !matches!(
+                merge_iter.peek(),
+                Some((_, Ok(next))) if next.key.user_key == item.key.user_key
+            )
+        {
+            continue;
+        }
+
+        // NOTE: Items deleted by a range tombstone can be dropped,
+        // unless a snapshot that does not see the tombstone still reads them
+        let mut is_range_deleted = false;
+        needed_by_item.clear();
+
+        for (idx, tombstone) in range_tombstone_index.covering_positions(&item.key.user_key) {
+            if item.key.seqno >= tombstone.seqno {
+                continue;
+            }
+
+            if tombstone.seqno < params.eviction_seqno
+                && !is_pinned(&params.snapshot_seqnos, item.key.seqno, tombstone.seqno)
+            {
+                is_range_deleted = true;
+                break;
+            }
+
+            needed_by_item.push(idx);
+        }
+
+        if is_range_deleted {
+            continue;
+        }
+
+        if !range_tombstones.is_empty() && segment_writer.should_rotate(&item.key.user_key) {
+            let segment_end = item.key.user_key.clone();
+
+            write_range_tombstones(
+                params,
+                &mut segment_writer,
+                range_tombstones,
+                &mut is_range_tombstone_needed,
+                segment_start.as_ref(),
+                Some(&segment_end),
+            );
+
+            segment_start = Some(segment_end);
+        }
+
+        // NOTE: The item is written into the new segment, if the writer rotates
+        for &idx in &needed_by_item {
+            if let Some(needed) = is_range_tombstone_needed.get_mut(idx) {
+                *needed = true;
+            }
+        }
+
+        segment_writer.write(item)?;
+
+        if idx % 100_000 == 0 && params.stop_signal.is_stopped() {
+            log::debug!("compactor: stopping amidst compaction because of stop signal");
+            return Ok(None);
+        }
+    }
+
+    write_range_tombstones(
+        params,
+        &mut segment_writer,
+        range_tombstones,
+        &mut is_range_tombstone_needed,
+        segment_start.as_ref(),
+        None,
+    );
+
+    segment_writer.finish().map(Some)
+}
+
+/// Writes the parts of the range tombstones that cover `[segment_start, segment_end)`
+/// into the current output segment, and resets which tombstones are needed.
+fn write_range_tombstones(
+    params: &OutputParams,
+    segment_writer: &mut MultiWriter,
+    range_tombstones: &[(RangeTombstone, bool)],
+    is_range_tombstone_needed: &mut [bool],
+    segment_start: Option<&UserKey>,
+    segment_end: Option<&UserKey>,
+) {
+    for ((tombstone, overlaps_other_segments), needed) in
+        range_tombstones.iter().zip(is_range_tombstone_needed)
+    {
+        // IMPORTANT: Range tombstones can only be dropped when writing into the last level,
+        // and no other segment can contain data beneath the tombstone
+        //
+        // Like point tombstones, they are kept within the grace period
+        let can_drop = params.is_last_level
+            && !overlaps_other_segments
+            && !*needed
+            && tombstone.seqno < params.eviction_seqno
+            && (params.inputs_past_tombstone_grace
+                || params
+                    .tombstone_grace_seqno
+                    .is_some_and(|grace_seqno| tombstone.seqno < grace_seqno));
+
+        *needed = false;
+
+        if can_drop {
+            continue;
+        }
+
+        if let Some(tombstone) = tombstone.clip(segment_start, segment_end) {
+            segment_writer.write_range_tombstone(tombstone);
+        }
+    }
+}
+
 fn drop_segments(
     mut levels: RwLockWriteGuard<'_, LevelManifest>,
     opts: &Options,
@@ -504,6 +1060,12 @@ fn drop_segments(
     log::trace!("Acquiring sealed memtables write lock");
     let memtable_lock = opts.sealed_memtables.write().expect("lock is poisoned");
 
+    let old_segments = levels
+        .iter()
+        .filter(|segment| segment_ids.contains(&segment.global_id()))
+        .cloned()
+        .collect::<Vec<_>>();
+
     // IMPORTANT: Write the segment with the removed segments first
     // Otherwise the folder is deleted, but the segment is still referenced!
     levels.atomic_swap(|recipe| {
@@ -523,20 +1085,11 @@ fn drop_segments(
     // NOTE: If the application were to crash >here< it's fine
     // The segments are not referenced anymore, and will be
     // cleaned up upon recovery
-    for key in segment_ids {
-        let segment_id = key.segment_id();
-
-        let segment_file_path = segments_base_folder.join(segment_id.to_string());
-        log::trace!("Removing old segment at {segment_file_path:?}");
-
-        if let Err(e) = std::fs::remove_file(segment_file_path) {
-            log::error!("Failed to cleanup file of deleted segment: {e:?}");
-        }
-    }
-
-    for key in segment_ids {
-        log::trace!("Closing file handles for segment data file");
-        opts.config.descriptor_table.remove(*key);
+    //
+    // The files are removed once the last reader lets go of the segments
+    for segment in old_segments {
+        let segment_file_path = segments_base_folder.join(segment.id().to_string());
+        segment.mark_as_deleted(segment_file_path);
     }
 
     log::trace!("Dropped {} segments", segment_ids.len());
@@ -0,0 +1,105 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use std::cmp::Ordering;
+
+/// Keys longer than this are compared using `memcmp`,
+/// which uses wide vector loads for long inputs
+const WORD_COMPARE_THRESHOLD: usize = 64;
+
+fn load_u64(bytes: &[u8]) -> u64 {
+    let mut buf = [0; 8];
+    buf.copy_from_slice(bytes);
+    u64::from_be_bytes(buf)
+}
+
+/// Compares two keys lexicographically
+///
+/// Short keys (which are the common case) are compared 8 bytes at a time,
+/// by loading them as big-endian words, which avoids the `memcmp` call overhead
+/// and compiles down to a few branch-light instructions.
+#[must_use]
+#[inline]
+pub fn compare_keys(a: &[u8], b: &[u8]) -> Ordering {
+    let len = a.len().min(b.len());
+
+    if len > WORD_COMPARE_THRESHOLD {
+        return a.cmp(b);
+    }
+
+    for (x, y) in a.chunks_exact(8).zip(b.chunks_exact(8)) {
+        let (x, y) = (load_u64(x), load_u64(y));
+
+        if x != y {
+            return x.cmp(&y);
+        }
+    }
+
+    let offset = len - len % 8;
+
+    let a = a.get(offset..).unwrap_or_default();
+    let b = b.get(offset..).unwrap_or_default();
+    a.cmp(b)
+}
+
+/// Returns `true` if `a` is less than `b`.
+#[must_use]
+#[inline]
+pub fn key_lt(a: &[u8], b: &[u8]) -> bool {
+    compare_keys(a, b) == Ordering::Less
+}
+
+/// Returns `true` if `a` is less than or equal to `b`.
+#[must_use]
+#[inline]
+pub fn key_le(a: &[u8], b: &[u8]) -> bool {
+    compare_keys(a, b) != Ordering::Greater
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    fn generate_keys() -> Vec<Vec<u8>> {
+        let mut keys = vec![vec![]];
+
+        for len in [1, 7, 8, 9, 15, 16, 17, 64, 65, 100] {
+            for fill in [0u8, 1, 127, 128, 255] {
+                let mut key = vec![fill; len];
+                keys.push(key.clone());
+
+                for idx in [0, len / 2, len - 1] {
+                    if let Some(byte) = key.get_mut(idx) {
+                        *byte = byte.wrapping_add(1);
+                    }
+                    keys.push(key.clone());
+                }
+            }
+        }
+
+        keys
+    }
+
+    #[test]
+    fn compare_keys_matches_slice_ord() {
+        let keys = generate_keys();
+
+        for a in &keys {
+            for b in &keys {
+                assert_eq!(a.cmp(b), compare_keys(a, b), "{a:?} vs {b:?}");
+                assert_eq!(a < b, key_lt(a, b));
+                assert_eq!(a <= b, key_le(a, b));
+            }
+        }
+    }
+
+    #[test]
+    fn compare_keys_prefix() {
+        assert_eq!(Ordering::Less, compare_keys(b"abcdefgh", b"abcdefghi"));
+        assert_eq!(Ordering::Greater, compare_keys(b"abcdefghi", b"abcdefgh"));
+        assert_eq!(Ordering::Equal, compare_keys(b"", b""));
+        assert_eq!(Ordering::Less, compare_keys(b"", b"a"));
+    }
+}
@@ -3,19 +3,29 @@
 // (found in the LICENSE-* files in the repository)
 
 use crate::{
+    bloom::{BloomLayout, FilterType},
+    compaction::CompactionStrategy,
     descriptor_table::FileDescriptorTable,
     path::absolute_path,
-    segment::meta::{CompressionType, TableType},
-    BlobTree, BlockCache, Tree,
+    segment::{
+        meta::{CompressionType, TableType},
+        writer::BloomConstructionPolicy,
+    },
+    transform::KeyedTransform,
+    AnyTree, BlobTree, BlockCache, BlockSizePolicy, BlockTransform, Clock, Executor,
+    JournalObserver, MergeOperator, QuarantineObserver, RateLimiter, SeqNo, SequenceNumberCounter,
+    SlowOperationObserver, Snapshot, Statistics, ThreadExecutor, Tree, WriteBufferManager,
 };
 use std::{
     path::{Path, PathBuf},
     sync::Arc,
+    time::Duration,
 };
 use value_log::BlobCache;
 
 /// LSM-tree type
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub enum TreeType {
     /// Standard LSM-tree, see [`Tree`]
     Standard,
@@ -48,7 +58,14 @@ impl TryFrom<u8> for TreeType {
 const DEFAULT_FILE_FOLDER: &str = ".lsm.data";
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 /// Tree configuration builder
+///
+/// With the `serde` feature, the tuning options can be (de)serialized.
+/// Caches, the descriptor table and the statistics collector are runtime objects,
+/// and are not serialized; deserialized configs use fresh default instances.
+#[allow(clippy::struct_excessive_bools)]
 pub struct Config {
     /// Folder path
     #[doc(hidden)]
@@ -66,11 +83,15 @@ pub struct Config {
 
     /// Table type (unused)
     #[allow(unused)]
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub(crate) table_type: TableType,
 
     /// Block size of data blocks
     pub data_block_size: u32,
 
+    /// Decides when data blocks are full
+    pub block_size_policy: BlockSizePolicy,
+
     /// Block size of index blocks
     pub index_block_size: u32,
 
@@ -83,12 +104,34 @@ pub struct Config {
     #[doc(hidden)]
     pub bloom_bits_per_key: i8,
 
+    /// Target false positive rate of bloom filters in levels that are not L0 or L1
+    #[doc(hidden)]
+    pub bloom_fp_rate: Option<f32>,
+
+    /// Maximum bits per key of bloom filters, per level
+    #[doc(hidden)]
+    pub bloom_max_bits_per_key: Vec<u8>,
+
+    /// Bloom filter memory layout
+    #[doc(hidden)]
+    pub bloom_layout: BloomLayout,
+
+    /// Filter type of segments written by compactions
+    #[doc(hidden)]
+    pub compaction_filter_type: FilterType,
+
+    /// Maximum number of key ranges a compaction is split into
+    #[doc(hidden)]
+    pub max_subcompactions: usize,
+
     /// Block cache to use
     #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub block_cache: Arc<BlockCache>,
 
     /// Blob cache to use
     #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub blob_cache: Arc<BlobCache>,
 
     /// Blob file (value log segment) target size in bytes
@@ -99,9 +142,150 @@ pub struct Config {
     #[doc(hidden)]
     pub blob_file_separation_threshold: u32,
 
+    /// Value log space amplification above which blob GC runs automatically
+    #[doc(hidden)]
+    pub blob_space_amp_target: Option<f32>,
+
+    /// Seqno generator used to write the index entries of relocated blobs
+    #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub blob_gc_seqno: Option<SequenceNumberCounter>,
+
+    /// Limits the throughput of background I/O
+    #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub rate_limiter: Option<Arc<RateLimiter>>,
+
+    /// Tracks the memtable size of multiple trees
+    #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub write_buffer_manager: Option<Arc<WriteBufferManager>>,
+
     /// Descriptor table to use
     #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub descriptor_table: Arc<FileDescriptorTable>,
+
+    /// Statistics collector
+    #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub statistics: Arc<Statistics>,
+
+    /// Operations taking longer than this are logged as warnings
+    #[doc(hidden)]
+    pub slow_operation_threshold: Option<Duration>,
+
+    /// Receives reports about slow operations
+    #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub slow_operation_observer: Option<Arc<dyn SlowOperationObserver>>,
+
+    /// L0 segment count at which the tree reports a write slowdown
+    #[doc(hidden)]
+    pub l0_slowdown_threshold: usize,
+
+    /// L0 segment count at which the tree reports a write stop
+    #[doc(hidden)]
+    pub l0_stop_threshold: usize,
+
+    /// Sealed memtable count at which the tree reports a write slowdown
+    #[doc(hidden)]
+    pub sealed_memtable_slowdown_threshold: usize,
+
+    /// Sealed memtable count at which the tree reports a write stop
+    #[doc(hidden)]
+    pub sealed_memtable_stop_threshold: usize,
+
+    /// Data older than this is written into cold segments by compactions
+    #[doc(hidden)]
+    pub cold_data_age: Option<Duration>,
+
+    /// Minimum time tombstones are kept for, even in the last level
+    #[doc(hidden)]
+    pub tombstone_grace_period: Option<Duration>,
+
+    /// Segments older than this are rewritten by compactions
+    #[doc(hidden)]
+    pub max_segment_age: Option<Duration>,
+
+    /// Length of the key prefix that compacted segments never span multiple values of
+    #[doc(hidden)]
+    pub partition_prefix_len: Option<usize>,
+
+    /// Maximum amount of segments that are read from concurrently in a point read
+    #[doc(hidden)]
+    pub point_read_fanout: usize,
+
+    /// Amount of data blocks to read ahead in sequential scans
+    #[doc(hidden)]
+    pub scan_prefetch_blocks: usize,
+
+    /// Amount of levels whose block indexes are loaded when opening the tree
+    #[doc(hidden)]
+    pub index_prefetch_levels: u8,
+
+    /// Whether prefetched block indexes are pinned in memory
+    #[doc(hidden)]
+    pub pin_prefetched_indexes: bool,
+
+    /// Amount of threads that load segments when opening the tree
+    #[doc(hidden)]
+    pub open_threads: usize,
+
+    /// Whether to defer loading block indexes and filters until a segment is first read
+    #[doc(hidden)]
+    pub lazy_segment_open: bool,
+
+    /// Whether to validate internal invariants while writing segments
+    #[doc(hidden)]
+    pub paranoid_checks: bool,
+
+    /// Transform (e.g. encryption) that is applied to blocks before writing them
+    #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub block_transform: Option<Arc<dyn BlockTransform>>,
+
+    /// Hooks for an external write-ahead journal
+    #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub journal_observer: Option<Arc<dyn JournalObserver>>,
+
+    /// Folds merge operands into values
+    #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub merge_operator: Option<Arc<dyn MergeOperator>>,
+
+    /// Whether to quarantine corrupt segments instead of failing
+    #[doc(hidden)]
+    pub quarantine_corrupt_segments: bool,
+
+    /// Receives events about quarantined segments
+    #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub quarantine_observer: Option<Arc<dyn QuarantineObserver>>,
+
+    /// Period in which the background scrubber verifies every file of the tree
+    #[doc(hidden)]
+    pub scrub_period: Option<Duration>,
+
+    /// Executor for background work (e.g. read-ahead)
+    #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub spawn_hook: Option<Arc<dyn Executor>>,
+
+    /// Source of the current time
+    #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub clock: Option<Arc<dyn Clock>>,
+
+    /// Strategy that background compaction workers run
+    #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub compaction_strategy: Option<Arc<dyn CompactionStrategy + Send + Sync>>,
+
+    /// Number of background compaction workers
+    #[doc(hidden)]
+    pub compaction_threads: usize,
 }
 
 impl Default for Config {
@@ -112,6 +296,7 @@ impl Default for Config {
 
             block_cache: Arc::new(BlockCache::with_capacity_bytes(/* 16 MiB */ 16 * 1_024 * 1_024)),
             data_block_size: /* 4 KiB */ 4_096,
+            block_size_policy: BlockSizePolicy::Fixed,
             index_block_size: /* 4 KiB */ 4_096,
             level_count: 7,
             tree_type: TreeType::Standard,
@@ -119,10 +304,52 @@ impl Default for Config {
             compression: CompressionType::None,
             blob_compression: CompressionType::None,
             bloom_bits_per_key: 10,
+            bloom_fp_rate: None,
+            bloom_max_bits_per_key: Vec::new(),
+            bloom_layout: BloomLayout::Standard,
+            compaction_filter_type: FilterType::Bloom,
+            max_subcompactions: 1,
 
             blob_cache: Arc::new(BlobCache::with_capacity_bytes(/* 16 MiB */ 16 * 1_024 * 1_024)),
             blob_file_target_size: /* 64 MiB */ 64 * 1_024 * 1_024,
             blob_file_separation_threshold: /* 4 KiB */ 4 * 1_024,
+            blob_space_amp_target: None,
+            blob_gc_seqno: None,
+            rate_limiter: None,
+            write_buffer_manager: None,
+
+            statistics: Arc::default(),
+            slow_operation_threshold: None,
+            slow_operation_observer: None,
+
+            l0_slowdown_threshold: 20,
+            l0_stop_threshold: 36,
+            sealed_memtable_slowdown_threshold: 4,
+            sealed_memtable_stop_threshold: 8,
+
+            cold_data_age: None,
+            tombstone_grace_period: None,
+            max_segment_age: None,
+            partition_prefix_len: None,
+
+            point_read_fanout: 1,
+            scan_prefetch_blocks: 0,
+            index_prefetch_levels: 0,
+            pin_prefetched_indexes: false,
+            open_threads: 1,
+            lazy_segment_open: false,
+            paranoid_checks: false,
+
+            block_transform: None,
+            journal_observer: None,
+            merge_operator: None,
+            quarantine_corrupt_segments: false,
+            quarantine_observer: None,
+            scrub_period: None,
+            spawn_hook: None,
+            clock: None,
+            compaction_strategy: None,
+            compaction_threads: 1,
         }
     }
 }
@@ -154,6 +381,124 @@ impl Config {
         self
     }
 
+    /// Sizes the bloom filters of levels that are not L0 or L1 for the given
+    /// false positive rate, instead of using a fixed amount of bits per key.
+    ///
+    /// Each filter is sized from the actual amount of keys in its segment.
+    /// The expected false positive rate of a segment's filter is recorded in the
+    /// segment, see [`crate::Segment::filter_fp_rate`].
+    ///
+    /// Has no effect if bloom filters are disabled, see [`Config::bloom_bits_per_key`].
+    ///
+    /// Defaults to `None`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the rate is not in (0, 1).
+    #[must_use]
+    pub fn bloom_fp_rate(mut self, fp_rate: f32) -> Self {
+        assert!(fp_rate > 0.0 && fp_rate < 1.0, "invalid bloom fp rate");
+
+        self.bloom_fp_rate = Some(fp_rate);
+        self
+    }
+
+    /// Limits the memory used by bloom filters, per level.
+    ///
+    /// Bloom filters in level `idx` use at most `max_bits_per_key[idx]` bits
+    /// per key, even if that means not reaching their target false positive
+    /// rate. Levels beyond the given list are not limited, a limit of 0
+    /// disables bloom filters in that level.
+    ///
+    /// Defaults to no limits.
+    #[must_use]
+    pub fn bloom_max_bits_per_key(mut self, max_bits_per_key: Vec<u8>) -> Self {
+        self.bloom_max_bits_per_key = max_bits_per_key;
+        self
+    }
+
+    /// Returns the bloom filter policy for segments written into the given level.
+    pub(crate) fn bloom_policy(&self, level: u8) -> BloomConstructionPolicy {
+        if self.bloom_bits_per_key < 0 {
+            return BloomConstructionPolicy::BitsPerKey(0);
+        }
+
+        // NOTE: Apply some MONKEY to have very high FPR on small levels
+        // because it's cheap
+        //
+        // See https://nivdayan.github.io/monkeykeyvaluestore.pdf
+        let policy = match (level, self.bloom_fp_rate) {
+            (0, _) => BloomConstructionPolicy::FpRate(0.00001),
+            (1, _) => BloomConstructionPolicy::FpRate(0.0005),
+            (_, Some(fp_rate)) => BloomConstructionPolicy::FpRate(fp_rate),
+            (_, None) => {
+                BloomConstructionPolicy::BitsPerKey(self.bloom_bits_per_key.unsigned_abs())
+            }
+        };
+
+        self.cap_bloom_policy(policy, level)
+    }
+
+    /// Applies the bloom filter memory limit of the given level.
+    pub(crate) fn cap_bloom_policy(
+        &self,
+        policy: BloomConstructionPolicy,
+        level: u8,
+    ) -> BloomConstructionPolicy {
+        match self.bloom_max_bits_per_key.get(usize::from(level)) {
+            Some(&max_bits_per_key) => policy.capped(max_bits_per_key),
+            None => policy,
+        }
+    }
+
+    /// Sets the memory layout of newly written bloom filters.
+    ///
+    /// A [`BloomLayout::Blocked`] filter needs at most one cache miss per lookup,
+    /// at the cost of a slightly higher false positive rate.
+    ///
+    /// Existing segments keep the layout they were written with.
+    ///
+    /// Defaults to [`BloomLayout::Standard`].
+    #[must_use]
+    pub fn bloom_layout(mut self, layout: BloomLayout) -> Self {
+        self.bloom_layout = layout;
+        self
+    }
+
+    /// Sets the kind of filter that is written for segments created by compactions.
+    ///
+    /// Flushed segments always use bloom filters.
+    /// [`FilterType::Xor`] saves about 25% of filter space, but has a fixed
+    /// false positive rate of ~0.4% and ignores the bits per key setting.
+    ///
+    /// Defaults to [`FilterType::Bloom`].
+    #[must_use]
+    pub fn compaction_filter_type(mut self, filter_type: FilterType) -> Self {
+        self.compaction_filter_type = filter_type;
+        self
+    }
+
+    /// Sets the maximum number of sub-compactions a compaction is split into.
+    ///
+    /// Large compactions (e.g. L0 -> L1) are split into disjoint key ranges,
+    /// using the key ranges of the input segments as boundaries, which are
+    /// compacted in parallel. A compaction is only split as far as every
+    /// sub-compaction writes about one target-sized segment or more.
+    ///
+    /// Compactions that contain range tombstones are not split.
+    ///
+    /// Defaults to 1 (no sub-compactions).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is zero.
+    #[must_use]
+    pub fn max_subcompactions(mut self, n: usize) -> Self {
+        assert!(n > 0, "max subcompactions should be greater than 0");
+        self.max_subcompactions = n;
+        self
+    }
+
     /// Sets the compression method.
     ///
     /// Using some compression is recommended.
@@ -216,6 +561,34 @@ impl Config {
         self
     }
 
+    /// Sets the policy that decides when a data block is full.
+    ///
+    /// Defaults to [`BlockSizePolicy::Fixed`], which uses the data block size.
+    ///
+    /// For workloads with mixed value sizes, [`BlockSizePolicy::Adaptive`] keeps
+    /// blocks of tiny values small, while still grouping larger values.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an adaptive policy has a target of 0 items, or a byte cap
+    /// smaller than 1 KiB or larger than 512 KiB.
+    #[must_use]
+    pub fn block_size_policy(mut self, policy: BlockSizePolicy) -> Self {
+        if let BlockSizePolicy::Adaptive {
+            target_items,
+            max_bytes,
+        } = policy
+        {
+            assert!(target_items > 0);
+            assert!(max_bytes >= 1_024);
+            assert!(max_bytes <= 512 * 1_024);
+        }
+
+        self.block_size_policy = policy;
+
+        self
+    }
+
     /// Sets the index block size.
     ///
     /// Defaults to 4 KiB (4096 bytes).
@@ -296,6 +669,460 @@ impl Config {
         self
     }
 
+    /// Sets the value log space amplification target.
+    ///
+    /// After a flush or compaction, the blob tree scans the index for stale blobs,
+    /// and if the space amplification of the value log exceeds the target, relocates
+    /// blobs of the most fragmented blob files and drops the stale blob files.
+    ///
+    /// Relocated blobs are written into the index with a seqno taken from
+    /// the given counter, which needs to be the tree's seqno generator.
+    ///
+    /// By default, blob GC needs to be run manually.
+    ///
+    /// This option has no effect when not used for opening a blob tree.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the target is less than 1.0.
+    #[must_use]
+    pub fn blob_space_amp_target(mut self, target: f32, seqno: SequenceNumberCounter) -> Self {
+        assert!(
+            target >= 1.0,
+            "space amplification target should be at least 1.0"
+        );
+
+        self.blob_space_amp_target = Some(target);
+        self.blob_gc_seqno = Some(seqno);
+        self
+    }
+
+    /// Sets the rate limiter for background I/O.
+    ///
+    /// Currently, this throttles blob GC, which rewrites blob files.
+    ///
+    /// The rate limiter can be shared between trees to limit their combined I/O.
+    ///
+    /// By default, background I/O is not throttled.
+    #[must_use]
+    pub fn rate_limiter(mut self, rate_limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    /// Registers the tree with a write buffer manager when it is opened,
+    /// so its memtables count towards the manager's limit.
+    ///
+    /// The manager should be shared between trees to limit their combined memtable size.
+    #[must_use]
+    pub fn write_buffer_manager(mut self, manager: Arc<WriteBufferManager>) -> Self {
+        self.write_buffer_manager = Some(manager);
+        self
+    }
+
+    /// Sets the statistics collector.
+    ///
+    /// You can share a [`Statistics`] object between multiple trees
+    /// to aggregate their metrics.
+    ///
+    /// Defaults to a new statistics object *per tree*.
+    #[must_use]
+    pub fn statistics(mut self, statistics: Arc<Statistics>) -> Self {
+        self.statistics = statistics;
+        self
+    }
+
+    /// Sets the slow operation threshold.
+    ///
+    /// Point reads, scans, flushes and compactions that take at least
+    /// `threshold` are logged as a warning, including the disk segments they read,
+    /// and reported to the [`Config::slow_operation_observer`].
+    ///
+    /// Defaults to `None` (disabled).
+    #[must_use]
+    pub fn slow_operation_threshold(mut self, threshold: Duration) -> Self {
+        self.slow_operation_threshold = Some(threshold);
+        self
+    }
+
+    /// Sets an observer that receives a structured report of every slow operation,
+    /// see [`Config::slow_operation_threshold`].
+    ///
+    /// Defaults to none.
+    #[must_use]
+    pub fn slow_operation_observer(mut self, observer: Arc<dyn SlowOperationObserver>) -> Self {
+        self.slow_operation_observer = Some(observer);
+        self
+    }
+
+    /// Sets the L0 segment count at which [`crate::AbstractTree::write_stall_state`]
+    /// reports a write slowdown.
+    ///
+    /// Defaults to 20.
+    #[must_use]
+    pub fn l0_slowdown_threshold(mut self, n: usize) -> Self {
+        self.l0_slowdown_threshold = n;
+        self
+    }
+
+    /// Sets the L0 segment count at which [`crate::AbstractTree::write_stall_state`]
+    /// reports a write stop.
+    ///
+    /// Defaults to 36.
+    #[must_use]
+    pub fn l0_stop_threshold(mut self, n: usize) -> Self {
+        self.l0_stop_threshold = n;
+        self
+    }
+
+    /// Sets the amount of sealed memtables (waiting to be flushed) at which
+    /// [`crate::AbstractTree::write_stall_state`] reports a write slowdown.
+    ///
+    /// Defaults to 4.
+    #[must_use]
+    pub fn sealed_memtable_slowdown_threshold(mut self, n: usize) -> Self {
+        self.sealed_memtable_slowdown_threshold = n;
+        self
+    }
+
+    /// Sets the amount of sealed memtables (waiting to be flushed) at which
+    /// [`crate::AbstractTree::write_stall_state`] reports a write stop.
+    ///
+    /// Defaults to 8.
+    #[must_use]
+    pub fn sealed_memtable_stop_threshold(mut self, n: usize) -> Self {
+        self.sealed_memtable_stop_threshold = n;
+        self
+    }
+
+    /// Sets the age after which data is considered cold.
+    ///
+    /// Compactions tag their output segments as [`crate::Temperature::Cold`]
+    /// if all of their data was written at least `age` ago, no matter the level.
+    /// The age of data is derived from the seqno to time mapping,
+    /// see [`crate::AbstractTree::seqno_at_time`].
+    ///
+    /// Defaults to `None`, in which case only the level decides the temperature.
+    #[must_use]
+    pub fn cold_data_age(mut self, age: Duration) -> Self {
+        self.cold_data_age = Some(age);
+        self
+    }
+
+    /// Sets the minimum time tombstones are kept for.
+    ///
+    /// Compactions into the last level usually drop tombstones, because there is
+    /// no older data left that they could shadow. With a grace period, tombstones
+    /// are only dropped once they are at least `period` old, so slow consumers of
+    /// a change feed or lagging replicas still get to see the deletion.
+    ///
+    /// A tombstone is known to be old enough if all segments of the compaction
+    /// were created before the grace period, or if the seqno to time mapping
+    /// (see [`crate::AbstractTree::seqno_at_time`]) shows it was written before.
+    ///
+    /// Defaults to `None`, in which case tombstones are dropped as soon as possible.
+    #[must_use]
+    pub fn tombstone_grace_period(mut self, period: Duration) -> Self {
+        self.tombstone_grace_period = Some(period);
+        self
+    }
+
+    /// Sets the maximum age of segments.
+    ///
+    /// If the [`crate::compaction::Leveled`] or [`crate::compaction::SizeTiered`]
+    /// strategy has nothing else to do, it rewrites the oldest segment that was
+    /// created at least `age` ago, so compaction filters, expired values
+    /// (see [`crate::Tree::insert_with_ttl`]) and tombstones are eventually
+    /// applied to all data, even to key ranges that are not written anymore.
+    ///
+    /// Defaults to `None`, in which case segments are only compacted
+    /// when the strategy selects them by size.
+    #[must_use]
+    pub fn max_segment_age(mut self, age: Duration) -> Self {
+        self.max_segment_age = Some(age);
+        self
+    }
+
+    /// Partitions the segments written by compactions, so a segment never
+    /// contains keys with different prefixes of `len` bytes (e.g. a tenant ID).
+    ///
+    /// Then all data of a prefix is stored in segments of its own, which
+    /// [`crate::AbstractTree::drop_prefix`] can drop as a whole, instead of
+    /// writing tombstones.
+    ///
+    /// Segments written by memtable flushes may still span multiple
+    /// prefixes, until they are compacted.
+    ///
+    /// Defaults to `None` (no partitioning).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `len` is 0.
+    #[must_use]
+    pub fn partition_prefix_len(mut self, len: usize) -> Self {
+        assert!(len > 0);
+
+        self.partition_prefix_len = Some(len);
+        self
+    }
+
+    /// Sets the maximum amount of segments whose blocks are read concurrently
+    /// in a point read.
+    ///
+    /// Bloom filters are still probed serially; the data blocks of up to `n`
+    /// candidate segments are then loaded in parallel, which reduces cold-cache
+    /// point read latency for trees with many (overlapping) segments,
+    /// at the cost of possibly reading versions that are shadowed by newer ones.
+    ///
+    /// The reads run on the [`Config::spawn_hook`] executor, which should be a pool.
+    /// Without a spawn hook, point reads stay serial, because spawning a thread
+    /// per read would cost more than it saves.
+    ///
+    /// Defaults to 1 (serial reads).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is 0.
+    #[must_use]
+    pub fn point_read_fanout(mut self, n: usize) -> Self {
+        assert!(n > 0);
+
+        self.point_read_fanout = n;
+        self
+    }
+
+    /// Sets the amount of data blocks that are read ahead
+    /// once a range or prefix scan reads a segment sequentially.
+    ///
+    /// Prefetched blocks are loaded into the block cache by a background thread,
+    /// which hides disk latency for long scans.
+    ///
+    /// Defaults to 0 (disabled).
+    #[must_use]
+    pub fn scan_prefetch_blocks(mut self, n: usize) -> Self {
+        self.scan_prefetch_blocks = n;
+        self
+    }
+
+    /// If enabled, flushes and compactions validate internal invariants
+    /// and fail with [`crate::Error::InvariantViolation`] instead of
+    /// persisting a broken tree:
+    ///
+    /// - items are written in ascending key order, with strictly descending
+    ///   seqnos per key
+    /// - compactions do not produce seqnos outside of their input
+    /// - compactions produce disjoint segments, and keep a disjoint
+    ///   destination level disjoint when merging into it
+    ///
+    /// This costs some CPU time, so it is meant for testing and staging.
+    ///
+    /// Defaults to false.
+    #[must_use]
+    pub fn paranoid_checks(mut self, enabled: bool) -> Self {
+        self.paranoid_checks = enabled;
+        self
+    }
+
+    /// Sets a transform (e.g. encryption) that is applied to segment blocks
+    /// and blobs before they are written to disk, see [`BlockTransform`].
+    ///
+    /// Segments that were written without a transform stay readable.
+    /// For blob trees, the transform needs to be set when the tree is created,
+    /// because blob files do not record whether they were transformed.
+    ///
+    /// Defaults to none.
+    #[must_use]
+    pub fn block_transform(mut self, transform: Arc<dyn BlockTransform>) -> Self {
+        self.block_transform = Some(transform);
+        self
+    }
+
+    /// Sets hooks that keep an external write-ahead journal in sync with the tree,
+    /// see [`JournalObserver`].
+    ///
+    /// Defaults to none.
+    #[must_use]
+    pub fn journal_observer(mut self, observer: Arc<dyn JournalObserver>) -> Self {
+        self.journal_observer = Some(observer);
+        self
+    }
+
+    /// Sets the operator that folds merge operands (written using [`Tree::merge`])
+    /// into values, see [`MergeOperator`].
+    ///
+    /// The operator is not persisted, so it needs to be set every time the tree is opened.
+    /// Merge operands are not supported by blob trees.
+    ///
+    /// Defaults to none.
+    #[must_use]
+    pub fn merge_operator(mut self, operator: Arc<dyn MergeOperator>) -> Self {
+        self.merge_operator = Some(operator);
+        self
+    }
+
+    /// Loads the block indexes of segments in the first `levels` levels
+    /// when opening the tree, so the first point reads into a segment do
+    /// not need to read index blocks from disk.
+    ///
+    /// If `pin` is set, the indexes are kept in memory for the lifetime of
+    /// the segments, like the indexes of L0 and L1, otherwise they are
+    /// loaded into the block cache and may be evicted again.
+    ///
+    /// This increases the time it takes to open the tree.
+    ///
+    /// Defaults to 0 levels.
+    #[must_use]
+    pub fn prefetch_index_levels(mut self, levels: u8, pin: bool) -> Self {
+        self.index_prefetch_levels = levels;
+        self.pin_prefetched_indexes = pin;
+        self
+    }
+
+    /// Sets the amount of threads that load segments when opening the tree.
+    ///
+    /// Loading a segment reads its block index and filter (and its trailer, unless
+    /// it is cached in the level manifest), so opening a large tree on high-latency
+    /// storage scales with the amount of concurrent reads.
+    ///
+    /// Defaults to 1 (serial loading).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is 0.
+    #[must_use]
+    pub fn open_threads(mut self, n: usize) -> Self {
+        assert!(n > 0);
+
+        self.open_threads = n;
+        self
+    }
+
+    /// If enabled, opening the tree does not load the block index and filter
+    /// of every segment; they are loaded when the segment is first read instead.
+    ///
+    /// This makes opening a tree with many segments fast, at the cost of a
+    /// slower first read per segment. Segments of levels that are prefetched
+    /// (see [`Config::prefetch_index_levels`]) are still loaded at open.
+    ///
+    /// Defaults to `false`.
+    #[must_use]
+    pub fn lazy_segment_open(mut self, enabled: bool) -> Self {
+        self.lazy_segment_open = enabled;
+        self
+    }
+
+    /// If enabled, segments that turn out to be corrupt (e.g. a checksum mismatch)
+    /// when opening the tree or during a point read are quarantined instead of
+    /// failing the operation:
+    ///
+    /// The segment is removed from the tree and its file is moved into the
+    /// `quarantine` folder, so it can be inspected or salvaged later.
+    /// The tree keeps serving the remaining data, so reads may return older
+    /// versions of keys, or miss keys entirely.
+    ///
+    /// Only use this if availability is more important than consistency.
+    ///
+    /// Defaults to false.
+    #[must_use]
+    pub fn quarantine_corrupt_segments(mut self, enabled: bool) -> Self {
+        self.quarantine_corrupt_segments = enabled;
+        self
+    }
+
+    /// Sets an observer that is notified when a segment is quarantined,
+    /// see [`Config::quarantine_corrupt_segments`].
+    ///
+    /// Defaults to none.
+    #[must_use]
+    pub fn quarantine_observer(mut self, observer: Arc<dyn QuarantineObserver>) -> Self {
+        self.quarantine_observer = Some(observer);
+        self
+    }
+
+    /// Enables the background scrubber, which verifies the checksums of all
+    /// segments (and blob files) of the tree, spread out over `period`.
+    ///
+    /// Bit rot in rarely read data would otherwise only be detected once a
+    /// compaction reads it, which may be months later. Corrupt segments are
+    /// quarantined if [`Config::quarantine_corrupt_segments`] is enabled, and reported
+    /// to the [`Config::quarantine_observer`] either way.
+    ///
+    /// The scrubber runs on the [`Config::spawn_hook`] executor, and reads files
+    /// at the rate of the [`Config::rate_limiter`], if one is set.
+    /// A single pass can be run using [`crate::AbstractTree::scrub`].
+    ///
+    /// Defaults to `None` (disabled).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the period is zero.
+    #[must_use]
+    pub fn scrub_period(mut self, period: Duration) -> Self {
+        assert!(!period.is_zero(), "scrub period should be greater than 0");
+        self.scrub_period = Some(period);
+        self
+    }
+
+    /// Enables background compaction, which runs the given strategy on `threads` workers.
+    ///
+    /// The workers are woken up whenever a flush registers new segments, and compact
+    /// as long as the strategy chooses to, so [`crate::AbstractTree::compact`]
+    /// does not need to be called manually. Compactions evict versions up to
+    /// [`crate::AbstractTree::gc_watermark`].
+    ///
+    /// Workers run on the [`Config::spawn_hook`] executor, and can be controlled using
+    /// the [`crate::compaction::CompactionScheduler`], see [`crate::AbstractTree::compaction_scheduler`].
+    ///
+    /// Defaults to `None` (disabled).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `threads` is zero.
+    #[must_use]
+    pub fn background_compaction(
+        mut self,
+        strategy: Arc<dyn CompactionStrategy + Send + Sync>,
+        threads: usize,
+    ) -> Self {
+        assert!(
+            threads > 0,
+            "compaction thread count should be greater than 0"
+        );
+        self.compaction_strategy = Some(strategy);
+        self.compaction_threads = threads;
+        self
+    }
+
+    /// Sets the executor that runs any background work the tree starts
+    /// (e.g. read-ahead of sequential scans, see [`Config::scan_prefetch_blocks`]).
+    ///
+    /// This allows routing background work onto your own thread pool or runtime,
+    /// and controlling when it stops: Tasks that are dropped without
+    /// running are treated as cancelled.
+    ///
+    /// Defaults to spawning a new OS thread per task, see [`ThreadExecutor`].
+    #[must_use]
+    pub fn spawn_hook(mut self, executor: Arc<dyn Executor>) -> Self {
+        self.spawn_hook = Some(executor);
+        self
+    }
+
+    /// Sets the clock that is used for segment creation times and TTLs.
+    ///
+    /// Use a [`crate::ManualClock`] to make time-based behaviour
+    /// (e.g. FIFO compaction TTLs) deterministic in tests.
+    ///
+    /// The clock does not affect diagnostics, like [`crate::Segment::age`],
+    /// segment access times and latency statistics, which always use the system time.
+    /// There is no simulated filesystem, all files are written to `path`.
+    ///
+    /// Defaults to the system time, see [`crate::SystemClock`].
+    #[must_use]
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
     #[must_use]
     #[doc(hidden)]
     pub fn descriptor_table(mut self, descriptor_table: Arc<FileDescriptorTable>) -> Self {
@@ -321,4 +1148,82 @@ impl Config {
         self.tree_type = TreeType::Blob;
         BlobTree::open(self)
     }
+
+    /// Opens a read-only view of an existing tree, as of `seqno`.
+    ///
+    /// Like [`AbstractTree::snapshot`](crate::AbstractTree::snapshot), the view only
+    /// sees items with a seqno lower than `seqno`, and skips segments that were
+    /// written after that point. The tree type is restored from the tree's manifest.
+    ///
+    /// Background compactions and scrubbing are not started, so versions that are still
+    /// present on disk stay readable. Versions that were already evicted by compactions
+    /// cannot be recovered.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config};
+    ///
+    /// {
+    ///     let tree = Config::new(&folder).open()?;
+    ///     tree.insert("a", "old", 0);
+    ///     tree.flush_active_memtable(0)?;
+    ///
+    ///     tree.insert("a", "new", 1);
+    ///     tree.flush_active_memtable(0)?;
+    /// }
+    ///
+    /// let view = Config::new(&folder).open_at(1)?;
+    /// assert_eq!(Some("old".as_bytes().into()), view.get("a")?);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the tree does not exist, or an IO error occurs.
+    pub fn open_at(mut self, seqno: SeqNo) -> crate::Result<Snapshot> {
+        use crate::{coding::Decode, file::MANIFEST_FILE, manifest::Manifest, AbstractTree};
+
+        let manifest_path = self.path.join(MANIFEST_FILE);
+
+        if !manifest_path.try_exists()? {
+            return Err(crate::Error::InvalidInput("tree does not exist"));
+        }
+
+        let manifest =
+            Manifest::decode_from(&mut std::io::Cursor::new(std::fs::read(manifest_path)?))?;
+
+        // NOTE: The view is read-only, so nothing should rewrite the segments behind it
+        self.compaction_strategy = None;
+        self.scrub_period = None;
+        self.tree_type = manifest.tree_type;
+
+        let tree = match manifest.tree_type {
+            TreeType::Standard => AnyTree::Standard(Tree::open(self)?),
+            TreeType::Blob => AnyTree::Blob(BlobTree::open(self)?),
+        };
+
+        Ok(tree.snapshot(seqno))
+    }
+
+    /// Returns the block transform bound to its current key, for writing new files.
+    pub(crate) fn current_transform(&self) -> Option<KeyedTransform> {
+        self.block_transform.clone().map(KeyedTransform::current)
+    }
+
+    /// Returns the clock.
+    pub(crate) fn get_clock(&self) -> Arc<dyn Clock> {
+        self.clock
+            .clone()
+            .unwrap_or_else(|| Arc::new(crate::SystemClock))
+    }
+
+    /// Returns the executor for background work.
+    pub(crate) fn executor(&self) -> Arc<dyn Executor> {
+        self.spawn_hook
+            .clone()
+            .unwrap_or_else(|| Arc::new(ThreadExecutor))
+    }
 }
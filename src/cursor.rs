@@ -0,0 +1,208 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::{AbstractTree, AnyTree, KvPair, SeqNo, Snapshot, UserKey};
+use std::ops::Bound::{self, Excluded, Included, Unbounded};
+
+type BoxedIter = Box<dyn DoubleEndedIterator<Item = crate::Result<KvPair>> + 'static>;
+
+/// Amount of items a seek steps over in the current iterator,
+/// before setting up a new iterator at the target key instead
+///
+/// See `max_sequential_skip_in_iterations` in `RocksDB`.
+const MAX_SEQUENTIAL_SKIPS: usize = 8;
+
+/// Seekable cursor over the items of a tree, see [`crate::AbstractTree::cursor`]
+///
+/// The cursor is positioned at an item (or at none, initially and after moving past
+/// either end), and can be repositioned with [`Cursor::seek`] and [`Cursor::seek_for_prev`].
+///
+/// Moving the cursor reuses the merged iterator of the current direction,
+/// and so does seeking to a key shortly ahead of the cursor, in the same direction.
+/// Only seeking further, or changing the direction, sets up a new iterator.
+///
+/// The cursor reads at a fixed seqno, which is pinned like a [`Snapshot`] while the cursor is open.
+/// If no seqno is given, the cursor sees the writes that were visible when it was created.
+pub struct Cursor {
+    snapshot: Snapshot,
+
+    /// Item the cursor is positioned at
+    current: Option<KvPair>,
+
+    /// Iterator over the items after the current one
+    ///
+    /// Dropped once it is exhausted, so the cursor starts over from the first item.
+    forward: Option<BoxedIter>,
+
+    /// Iterator over the items before the current one
+    ///
+    /// Dropped once it is exhausted, so the cursor starts over from the last item.
+    backward: Option<BoxedIter>,
+}
+
+impl Cursor {
+    pub(crate) fn new(tree: AnyTree, seqno: Option<SeqNo>) -> Self {
+        let seqno = seqno.unwrap_or_else(|| tree.get_highest_seqno().map_or(0, |seqno| seqno + 1));
+
+        Self {
+            snapshot: Snapshot::new(tree, seqno),
+            current: None,
+            forward: None,
+            backward: None,
+        }
+    }
+
+    fn range(&self, lo: Bound<UserKey>, hi: Bound<UserKey>) -> BoxedIter {
+        Box::new(self.snapshot.range::<UserKey, _>((lo, hi)))
+    }
+
+    /// Steps over up to [`MAX_SEQUENTIAL_SKIPS`] items of the iterator, until `is_target` matches.
+    ///
+    /// Returns `None` if the target was not reached.
+    fn skip_to(
+        iter: &mut BoxedIter,
+        is_target: &dyn Fn(&[u8]) -> bool,
+        backwards: bool,
+    ) -> Option<crate::Result<Option<KvPair>>> {
+        for _ in 0..MAX_SEQUENTIAL_SKIPS {
+            let item = if backwards {
+                iter.next_back()
+            } else {
+                iter.next()
+            };
+
+            match item {
+                Some(Ok(item)) if is_target(&item.0) => return Some(Ok(Some(item))),
+                Some(Ok(_)) => {}
+                Some(Err(e)) => return Some(Err(e)),
+                None => return Some(Ok(None)),
+            }
+        }
+
+        None
+    }
+
+    /// Returns the item the cursor is positioned at.
+    #[must_use]
+    pub fn current(&self) -> Option<&KvPair> {
+        self.current.as_ref()
+    }
+
+    /// Positions the cursor at the first item with a key greater than or equal to the given key,
+    /// and returns it.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub fn seek<K: AsRef<[u8]>>(&mut self, key: K) -> crate::Result<Option<KvPair>> {
+        let key = key.as_ref();
+
+        // NOTE: If the key is shortly ahead of the cursor, step to it in the current iterator
+        if let (Some((current, _)), Some(mut iter)) = (&self.current, self.forward.take()) {
+            if &**current < key {
+                if let Some(item) = Self::skip_to(&mut iter, &|k| k >= key, false) {
+                    let item = item?;
+
+                    self.forward = item.is_some().then_some(iter);
+                    self.current.clone_from(&item);
+
+                    return Ok(item);
+                }
+            }
+        }
+
+        let mut iter = self.range(Included(key.into()), Unbounded);
+        let item = iter.next().transpose()?;
+
+        self.forward = item.is_some().then_some(iter);
+        self.backward = None;
+        self.current.clone_from(&item);
+
+        Ok(item)
+    }
+
+    /// Positions the cursor at the last item with a key less than or equal to the given key,
+    /// and returns it.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub fn seek_for_prev<K: AsRef<[u8]>>(&mut self, key: K) -> crate::Result<Option<KvPair>> {
+        let key = key.as_ref();
+
+        // NOTE: If the key is shortly behind the cursor, step to it in the current iterator
+        if let (Some((current, _)), Some(mut iter)) = (&self.current, self.backward.take()) {
+            if &**current > key {
+                if let Some(item) = Self::skip_to(&mut iter, &|k| k <= key, true) {
+                    let item = item?;
+
+                    self.backward = item.is_some().then_some(iter);
+                    self.current.clone_from(&item);
+
+                    return Ok(item);
+                }
+            }
+        }
+
+        let mut iter = self.range(Unbounded, Included(key.into()));
+        let item = iter.next_back().transpose()?;
+
+        self.forward = None;
+        self.backward = item.is_some().then_some(iter);
+        self.current.clone_from(&item);
+
+        Ok(item)
+    }
+
+    /// Moves the cursor to the next item, and returns it.
+    ///
+    /// If the cursor is not positioned at an item, it moves to the first item.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> crate::Result<Option<KvPair>> {
+        let mut iter = self.forward.take().unwrap_or_else(|| {
+            let lo = match &self.current {
+                Some((key, _)) => Excluded(key.clone()),
+                None => Unbounded,
+            };
+            self.range(lo, Unbounded)
+        });
+
+        let item = iter.next().transpose()?;
+
+        self.forward = item.is_some().then_some(iter);
+        self.backward = None;
+        self.current.clone_from(&item);
+
+        Ok(item)
+    }
+
+    /// Moves the cursor to the previous item, and returns it.
+    ///
+    /// If the cursor is not positioned at an item, it moves to the last item.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub fn prev(&mut self) -> crate::Result<Option<KvPair>> {
+        let mut iter = self.backward.take().unwrap_or_else(|| {
+            let hi = match &self.current {
+                Some((key, _)) => Excluded(key.clone()),
+                None => Unbounded,
+            };
+            self.range(Unbounded, hi)
+        });
+
+        let item = iter.next_back().transpose()?;
+
+        self.forward = None;
+        self.backward = item.is_some().then_some(iter);
+        self.current.clone_from(&item);
+
+        Ok(item)
+    }
+}
@@ -0,0 +1,69 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use std::collections::VecDeque;
+
+/// Ring of items that is swept by a clock hand
+///
+/// The hand is always at the front of the ring;
+/// advancing the hand moves the front item to the back.
+#[derive(Default)]
+pub struct ClockRing<T: Clone + Eq + PartialEq>(VecDeque<T>);
+
+impl<T: Clone + Eq + PartialEq> ClockRing<T> {
+    #[must_use]
+    pub fn with_capacity(n: usize) -> Self {
+        Self(VecDeque::with_capacity(n))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    /// Inserts an item right behind the hand, so it is visited last.
+    pub fn insert(&mut self, item: T) {
+        self.0.push_back(item);
+    }
+
+    pub fn remove(&mut self, item: &T) {
+        self.0.retain(|x| x != item);
+    }
+
+    /// Returns the item under the hand, and advances the hand.
+    pub fn advance(&mut self) -> Option<T> {
+        let front = self.0.pop_front()?;
+        self.0.push_back(front.clone());
+        Some(front)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn clock_ring_advance() {
+        let mut ring = ClockRing::with_capacity(3);
+        ring.insert(1);
+        ring.insert(2);
+        ring.insert(3);
+
+        assert_eq!(Some(1), ring.advance());
+        assert_eq!(Some(2), ring.advance());
+
+        ring.remove(&3);
+        assert_eq!(2, ring.len());
+
+        assert_eq!(Some(1), ring.advance());
+        assert_eq!(Some(2), ring.advance());
+
+        ring.clear();
+        assert_eq!(None, ring.advance());
+    }
+}
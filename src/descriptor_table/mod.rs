@@ -2,20 +2,22 @@
 // This source code is licensed under both the Apache 2.0 and MIT License
 // (found in the LICENSE-* files in the repository)
 
-mod lru;
+mod clock;
 
 use crate::{segment::id::GlobalSegmentId, HashMap};
-use lru::LruList;
+use clock::ClockRing;
 use std::{
     fs::File,
-    io::BufReader,
     path::PathBuf,
     sync::{
-        atomic::{AtomicBool, AtomicUsize},
-        Arc, Mutex, RwLock, RwLockWriteGuard,
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex, RwLock,
     },
 };
 
+/// Amount of shards the table is split into, to reduce lock contention
+const SHARD_COUNT: usize = 16;
+
 pub struct FileGuard(Arc<FileDescriptorWrapper>);
 
 impl std::ops::Deref for FileGuard {
@@ -28,38 +30,52 @@ impl std::ops::Deref for FileGuard {
 
 impl Drop for FileGuard {
     fn drop(&mut self) {
-        self.0
-            .is_used
-            .store(false, std::sync::atomic::Ordering::Release);
+        self.0.is_used.store(false, Ordering::Release);
     }
 }
 
 pub struct FileDescriptorWrapper {
-    pub file: Mutex<BufReader<File>>,
+    /// Only used for positional reads, so it can be shared without locking
+    pub file: Arc<File>,
     is_used: AtomicBool,
 }
 
 pub struct FileHandle {
     descriptors: RwLock<Vec<Arc<FileDescriptorWrapper>>>,
     path: PathBuf,
-}
 
-// TODO: FileDescriptorTable should wrap Arc<Inner>
-// TODO: table should probably use a concurrent hashmap
-
-pub struct FileDescriptorTableInner {
-    table: HashMap<GlobalSegmentId, FileHandle>,
-    lru: Mutex<LruList<GlobalSegmentId>>,
-    size: AtomicUsize,
+    /// Reference bit used by CLOCK eviction, set on every access
+    referenced: AtomicBool,
 }
 
+type Shard = RwLock<HashMap<GlobalSegmentId, Arc<FileHandle>>>;
+
 /// The descriptor table caches file descriptors to avoid `fopen()` calls
 ///
+/// Segments are spread over multiple shards, so accesses to different segments
+/// do not contend on a single lock. Accessing a segment whose files are already open only
+/// takes read locks.
+///
+/// If the amount of open file descriptors exceeds the configured limit,
+/// the file descriptors of segments that have not been accessed recently are closed,
+/// using the CLOCK (second chance) algorithm.
+///
 /// See `TableCache` in `RocksDB`.
 #[doc(alias("table cache"))]
 #[allow(clippy::module_name_repetitions)]
 pub struct FileDescriptorTable {
-    inner: RwLock<FileDescriptorTableInner>,
+    shards: Box<[Shard]>,
+
+    /// Eviction order
+    clock: Mutex<ClockRing<GlobalSegmentId>>,
+
+    /// Amount of open file descriptors
+    size: AtomicUsize,
+
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+
     concurrency: usize,
     limit: usize,
 }
@@ -67,21 +83,32 @@ pub struct FileDescriptorTable {
 impl FileDescriptorTable {
     /// Closes all file descriptors
     pub fn clear(&self) {
-        let mut lock = self.inner.write().expect("lock is poisoned");
-        lock.table.clear();
+        for shard in &*self.shards {
+            shard.write().expect("lock is poisoned").clear();
+        }
+
+        self.clock.lock().expect("lock is poisoned").clear();
+        self.size.store(0, Ordering::Release);
     }
 
     #[must_use]
     pub fn new(limit: usize, concurrency: usize) -> Self {
-        Self {
-            inner: RwLock::new(FileDescriptorTableInner {
-                table: HashMap::with_capacity_and_hasher(
-                    100,
+        let shards = (0..SHARD_COUNT)
+            .map(|_| {
+                RwLock::new(HashMap::with_capacity_and_hasher(
+                    16,
                     xxhash_rust::xxh3::Xxh3Builder::new(),
-                ),
-                lru: Mutex::new(LruList::with_capacity(100)),
-                size: AtomicUsize::default(),
-            }),
+                ))
+            })
+            .collect();
+
+        Self {
+            shards,
+            clock: Mutex::new(ClockRing::with_capacity(100)),
+            size: AtomicUsize::default(),
+            hits: AtomicU64::default(),
+            misses: AtomicU64::default(),
+            evictions: AtomicU64::default(),
             concurrency,
             limit,
         }
@@ -89,7 +116,10 @@ impl FileDescriptorTable {
 
     /// Number of segments
     pub fn len(&self) -> usize {
-        self.inner.read().expect("lock is poisoned").table.len()
+        self.shards
+            .iter()
+            .map(|shard| shard.read().expect("lock is poisoned").len())
+            .sum()
     }
 
     #[must_use]
@@ -97,128 +127,198 @@ impl FileDescriptorTable {
         self.len() == 0
     }
 
+    /// Number of open file descriptors
     pub fn size(&self) -> usize {
-        self.inner
+        self.size.load(Ordering::Acquire)
+    }
+
+    /// Number of accesses that found the segment's files already open
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// Number of accesses that needed to open the segment's files
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    /// Number of times the file descriptors of a segment were closed to stay under the limit
+    pub fn evictions(&self) -> u64 {
+        self.evictions.load(Ordering::Relaxed)
+    }
+
+    // NOTE: Truncation is fine, we only need to spread the IDs over the shards
+    #[allow(clippy::cast_possible_truncation)]
+    fn shard(&self, id: &GlobalSegmentId) -> &Shard {
+        let hash = id.tree_id().wrapping_mul(0x9E37_79B9_7F4A_7C15) ^ id.segment_id();
+        let idx = (hash % self.shards.len() as u64) as usize;
+        self.shards.get(idx).expect("shard should exist")
+    }
+
+    fn get_handle(&self, id: &GlobalSegmentId) -> Option<Arc<FileHandle>> {
+        self.shard(id)
             .read()
             .expect("lock is poisoned")
-            .size
-            .load(std::sync::atomic::Ordering::Acquire)
+            .get(id)
+            .cloned()
     }
 
-    // TODO: on access, adjust hotness of ID -> lock contention though
-    pub fn access(&self, id: &GlobalSegmentId) -> crate::Result<Option<FileGuard>> {
-        let lock = self.inner.read().expect("lock is poisoned");
+    /// Claims an unused file descriptor, waiting for one to become free if needed.
+    fn claim(fd_array: &[Arc<FileDescriptorWrapper>]) -> FileGuard {
+        loop {
+            for fd in fd_array {
+                if fd
+                    .is_used
+                    .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+                    .is_ok()
+                {
+                    return FileGuard(fd.clone());
+                }
+            }
 
-        let Some(item) = lock.table.get(id) else {
+            std::thread::yield_now();
+        }
+    }
+
+    pub fn access(&self, id: &GlobalSegmentId) -> crate::Result<Option<FileGuard>> {
+        let Some(handle) = self.get_handle(id) else {
             return Ok(None);
         };
 
-        let fd_array = item.descriptors.read().expect("lock is poisoned");
+        handle.referenced.store(true, Ordering::Relaxed);
 
-        if fd_array.is_empty() {
-            drop(fd_array);
-            drop(lock);
+        {
+            let fd_array = handle.descriptors.read().expect("lock is poisoned");
+
+            if !fd_array.is_empty() {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                return Ok(Some(Self::claim(&fd_array)));
+            }
+        }
 
-            let lock = self.inner.write().expect("lock is poisoned");
-            let mut lru = lock.lru.lock().expect("lock is poisoned");
-            lru.refresh(*id);
+        let fd = {
+            let mut fd_array = handle.descriptors.write().expect("lock is poisoned");
 
-            let fd = {
-                let item = lock.table.get(id).expect("should exist");
-                let mut fd_lock = item.descriptors.write().expect("lock is poisoned");
+            // NOTE: Another thread may have opened the file in the meantime
+            if !fd_array.is_empty() {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                return Ok(Some(Self::claim(&fd_array)));
+            }
 
-                for _ in 0..(self.concurrency - 1) {
-                    let fd = Arc::new(FileDescriptorWrapper {
-                        file: Mutex::new(BufReader::new(File::open(&item.path)?)),
-                        is_used: AtomicBool::default(),
-                    });
-                    fd_lock.push(fd.clone());
-                }
+            self.misses.fetch_add(1, Ordering::Relaxed);
 
+            for _ in 0..(self.concurrency - 1) {
                 let fd = Arc::new(FileDescriptorWrapper {
-                    file: Mutex::new(BufReader::new(File::open(&item.path)?)),
-                    is_used: AtomicBool::new(true),
+                    file: Arc::new(File::open(&handle.path)?),
+                    is_used: AtomicBool::default(),
                 });
-                fd_lock.push(fd.clone());
+                fd_array.push(fd);
+            }
 
-                fd
-            };
+            let fd = Arc::new(FileDescriptorWrapper {
+                file: Arc::new(File::open(&handle.path)?),
+                is_used: AtomicBool::new(true),
+            });
+            fd_array.push(fd.clone());
 
-            let mut size_now = lock
-                .size
-                .fetch_add(self.concurrency, std::sync::atomic::Ordering::AcqRel)
-                + self.concurrency;
+            fd
+        };
 
-            while size_now > self.limit {
-                if let Some(oldest) = lru.get_least_recently_used() {
-                    if &oldest != id {
-                        if let Some(item) = lock.table.get(&oldest) {
-                            let mut oldest_lock =
-                                item.descriptors.write().expect("lock is poisoned");
+        let size_now = self.size.fetch_add(self.concurrency, Ordering::AcqRel) + self.concurrency;
 
-                            lock.size
-                                .fetch_sub(oldest_lock.len(), std::sync::atomic::Ordering::Release);
-                            size_now -= oldest_lock.len();
+        if size_now > self.limit {
+            self.evict(id);
+        }
 
-                            oldest_lock.clear();
-                        };
-                    }
-                } else {
-                    break;
-                }
+        Ok(Some(FileGuard(fd)))
+    }
+
+    /// Closes file descriptors of segments that have not been
+    /// accessed recently, until the table is under its limit again.
+    fn evict(&self, current: &GlobalSegmentId) {
+        let mut clock = self.clock.lock().expect("lock is poisoned");
+
+        // NOTE: Each segment gets a second chance, so after two sweeps
+        // all reference bits are cleared
+        let mut budget = clock.len() * 2;
+
+        while self.size.load(Ordering::Acquire) > self.limit && budget > 0 {
+            budget -= 1;
+
+            let Some(candidate) = clock.advance() else {
+                break;
+            };
+
+            if &candidate == current {
+                continue;
             }
 
-            Ok(Some(FileGuard(fd)))
-        } else {
-            loop {
-                for shard in &*fd_array {
-                    if shard.is_used.compare_exchange(
-                        false,
-                        true,
-                        // TODO: could probably be not SeqCst
-                        std::sync::atomic::Ordering::SeqCst,
-                        std::sync::atomic::Ordering::SeqCst,
-                    ) == Ok(false)
-                    {
-                        return Ok(Some(FileGuard(shard.clone())));
-                    }
-                }
+            let Some(handle) = self.get_handle(&candidate) else {
+                clock.remove(&candidate);
+                continue;
+            };
+
+            if handle.referenced.swap(false, Ordering::Relaxed) {
+                continue;
             }
-        }
-    }
 
-    fn inner_insert(
-        mut lock: RwLockWriteGuard<'_, FileDescriptorTableInner>,
-        path: PathBuf,
-        id: GlobalSegmentId,
-    ) {
-        lock.table.insert(
-            id,
-            FileHandle {
-                descriptors: RwLock::new(vec![]),
-                path,
-            },
-        );
-
-        lock.lru.lock().expect("lock is poisoned").refresh(id);
+            let mut fd_array = handle.descriptors.write().expect("lock is poisoned");
+
+            if fd_array.is_empty() {
+                continue;
+            }
+
+            log::trace!("Closing file descriptors of segment {candidate:?}");
+
+            // NOTE: File descriptors that are currently in use are kept alive by their guard
+            self.size.fetch_sub(fd_array.len(), Ordering::Release);
+            fd_array.clear();
+
+            self.evictions.fetch_add(1, Ordering::Relaxed);
+        }
     }
 
     pub fn insert<P: Into<PathBuf>>(&self, path: P, id: GlobalSegmentId) {
-        let lock = self.inner.write().expect("lock is poisoned");
-        Self::inner_insert(lock, path.into(), id);
+        let handle = Arc::new(FileHandle {
+            descriptors: RwLock::new(vec![]),
+            path: path.into(),
+            referenced: AtomicBool::new(true),
+        });
+
+        let prev = self
+            .shard(&id)
+            .write()
+            .expect("lock is poisoned")
+            .insert(id, handle);
+
+        let mut clock = self.clock.lock().expect("lock is poisoned");
+
+        if let Some(prev) = prev {
+            self.size.fetch_sub(
+                prev.descriptors.read().expect("lock is poisoned").len(),
+                Ordering::Release,
+            );
+            clock.remove(&id);
+        }
+
+        clock.insert(id);
     }
 
     pub fn remove(&self, id: GlobalSegmentId) {
-        let mut lock = self.inner.write().expect("lock is poisoned");
+        let item = self
+            .shard(&id)
+            .write()
+            .expect("lock is poisoned")
+            .remove(&id);
 
-        if let Some(item) = lock.table.remove(&id) {
-            lock.size.fetch_sub(
+        if let Some(item) = item {
+            self.size.fetch_sub(
                 item.descriptors.read().expect("lock is poisoned").len(),
-                std::sync::atomic::Ordering::Release,
+                Ordering::Release,
             );
         }
 
-        lock.lru.lock().expect("lock is poisoned").remove(&id);
+        self.clock.lock().expect("lock is poisoned").remove(&id);
     }
 }
 
@@ -279,4 +379,69 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn descriptor_table_second_chance() -> crate::Result<()> {
+        let folder = tempfile::tempdir()?;
+        let path = folder.path();
+
+        let table = FileDescriptorTable::new(2, 1);
+
+        for id in 1..=3 {
+            File::create(path.join(id.to_string()))?;
+            table.insert(path.join(id.to_string()), (0, id).into());
+        }
+
+        drop(table.access(&(0, 1).into())?);
+        drop(table.access(&(0, 2).into())?);
+        assert_eq!(2, table.misses());
+
+        // NOTE: All reference bits are cleared, 1 is evicted
+        drop(table.access(&(0, 3).into())?);
+        assert_eq!(2, table.size());
+        assert_eq!(1, table.evictions());
+
+        // NOTE: 2 has lost its reference bit in the last sweep, but 3 has not
+        drop(table.access(&(0, 1).into())?);
+        assert_eq!(2, table.size());
+        assert_eq!(2, table.evictions());
+
+        drop(table.access(&(0, 3).into())?);
+        assert_eq!(1, table.hits());
+        assert_eq!(4, table.misses());
+
+        Ok(())
+    }
+
+    #[test]
+    fn descriptor_table_concurrent_access() -> crate::Result<()> {
+        let folder = tempfile::tempdir()?;
+        let path = folder.path();
+
+        let table = FileDescriptorTable::new(8, 2);
+
+        for id in 0..32 {
+            File::create(path.join(id.to_string()))?;
+            table.insert(path.join(id.to_string()), (0, id).into());
+        }
+
+        std::thread::scope(|scope| {
+            for t in 0..4 {
+                let table = &table;
+
+                scope.spawn(move || {
+                    for i in 0..1_000 {
+                        let id = (i * 7 + t) % 32;
+                        let guard = table.access(&(0, id).into()).expect("should open");
+                        assert!(guard.is_some());
+                    }
+                });
+            }
+        });
+
+        assert_eq!(32, table.len());
+        assert_eq!(4_000, table.hits() + table.misses());
+
+        Ok(())
+    }
 }
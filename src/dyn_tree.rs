@@ -0,0 +1,299 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::{
+    compaction::CompactionStrategy, config::TreeType, AbstractTree, Config, Health, KvPair,
+    Segment, SeqNo, Snapshot, UserKey, UserValue,
+};
+use std::{ops::Bound, sync::Arc};
+
+type BoxedIter = Box<dyn DoubleEndedIterator<Item = crate::Result<KvPair>> + 'static>;
+
+/// Object-safe tree API
+///
+/// [`AbstractTree`] has generic methods, so it cannot be used as a trait object.
+/// This trait covers the common operations with concrete argument types, and is
+/// implemented for every tree, so applications can hold an `Arc<dyn DynTree>`,
+/// no matter if the tree is a [`Tree`](crate::Tree) or a [`BlobTree`](crate::BlobTree).
+///
+/// The methods are named like their counterparts in [`AbstractTree`], so only one
+/// of the two traits should be imported in a module to avoid ambiguous method calls.
+///
+/// # Examples
+///
+/// ```
+/// # let folder = tempfile::tempdir()?;
+/// use lsm_tree::{Config, DynTree};
+/// use std::sync::Arc;
+///
+/// let tree: Arc<dyn DynTree> = Arc::new(Config::new(folder).open_as_blob_tree()?);
+///
+/// tree.insert("a".into(), "abc".into(), 0);
+/// tree.flush_active_memtable(0)?;
+///
+/// assert_eq!(Some("abc".as_bytes().into()), tree.get(b"a", None)?);
+/// #
+/// # Ok::<(), lsm_tree::Error>(())
+/// ```
+#[allow(clippy::module_name_repetitions)]
+pub trait DynTree: Send + Sync {
+    /// Retrieves an item from the tree, see [`AbstractTree::get`].
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn get(&self, key: &[u8], seqno: Option<SeqNo>) -> crate::Result<Option<UserValue>>;
+
+    /// Returns `true` if the tree contains the specified key, see [`AbstractTree::contains_key`].
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn contains_key(&self, key: &[u8], seqno: Option<SeqNo>) -> crate::Result<bool>;
+
+    /// Retrieves multiple items from the tree, see [`AbstractTree::get_many`].
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn get_many(
+        &self,
+        keys: &[&[u8]],
+        seqno: Option<SeqNo>,
+    ) -> crate::Result<Vec<Option<UserValue>>>;
+
+    /// Retrieves the size of a value, see [`AbstractTree::size_of`].
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn size_of(&self, key: &[u8], seqno: Option<SeqNo>) -> crate::Result<Option<u32>>;
+
+    /// Returns an iterator over the whole tree, see [`AbstractTree::iter`].
+    fn iter(&self, seqno: Option<SeqNo>) -> BoxedIter;
+
+    /// Returns an iterator over a range of items, see [`AbstractTree::range`].
+    fn range(&self, range: (Bound<UserKey>, Bound<UserKey>), seqno: Option<SeqNo>) -> BoxedIter;
+
+    /// Returns an iterator over a prefixed set of items, see [`AbstractTree::prefix`].
+    fn prefix(&self, prefix: &[u8], seqno: Option<SeqNo>) -> BoxedIter;
+
+    /// Returns the first key-value pair, see [`AbstractTree::first_key_value`].
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn first_key_value(&self, seqno: Option<SeqNo>) -> crate::Result<Option<KvPair>>;
+
+    /// Returns the last key-value pair, see [`AbstractTree::last_key_value`].
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn last_key_value(&self, seqno: Option<SeqNo>) -> crate::Result<Option<KvPair>>;
+
+    /// Scans the entire tree, returning the amount of items, see [`AbstractTree::len`].
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn len(&self, seqno: Option<SeqNo>) -> crate::Result<usize>;
+
+    /// Returns `true` if the tree is empty, see [`AbstractTree::is_empty`].
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn is_empty(&self, seqno: Option<SeqNo>) -> crate::Result<bool>;
+
+    /// Inserts a key-value pair, see [`AbstractTree::insert`].
+    fn insert(&self, key: UserKey, value: UserValue, seqno: SeqNo) -> (u32, u32);
+
+    /// Removes an item, see [`AbstractTree::remove`].
+    fn remove(&self, key: UserKey, seqno: SeqNo) -> (u32, u32);
+
+    /// Removes an item with a weak tombstone, see [`AbstractTree::remove_weak`].
+    fn remove_weak(&self, key: UserKey, seqno: SeqNo) -> (u32, u32);
+
+    /// Removes all items in a key range, see [`AbstractTree::remove_range`].
+    fn remove_range(&self, range: std::ops::Range<UserKey>, seqno: SeqNo) -> (u32, u32);
+
+    /// Opens a read-only point-in-time snapshot, see [`AbstractTree::snapshot`].
+    fn snapshot(&self, seqno: SeqNo) -> Snapshot;
+
+    /// Flushes the active memtable to a disk segment.
+    ///
+    /// Returns `None` if the active memtable was empty.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn flush_active_memtable(&self, eviction_seqno: SeqNo) -> crate::Result<Option<Segment>>;
+
+    /// Performs a compaction run, see [`AbstractTree::compact`].
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn compact(
+        &self,
+        strategy: Arc<dyn CompactionStrategy>,
+        seqno_threshold: SeqNo,
+    ) -> crate::Result<()>;
+
+    /// Performs major compaction, merging all segments into segments of `target_size`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn major_compact(&self, target_size: u64, seqno_threshold: SeqNo) -> crate::Result<()>;
+
+    /// Atomically removes all data from the tree, see [`AbstractTree::clear`].
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    fn clear(&self) -> crate::Result<()>;
+
+    /// Returns the tree type.
+    fn tree_type(&self) -> TreeType;
+
+    /// Returns the tree config.
+    fn tree_config(&self) -> &Config;
+
+    /// Returns a compact health report, see [`AbstractTree::health`].
+    fn health(&self) -> Health;
+
+    /// Returns the disk space used by the tree, see [`AbstractTree::disk_space`].
+    fn disk_space(&self) -> u64;
+
+    /// Returns the amount of disk segments.
+    fn segment_count(&self) -> usize;
+
+    /// Returns the highest sequence number.
+    fn get_highest_seqno(&self) -> Option<SeqNo>;
+}
+
+impl<T: AbstractTree + Send + Sync> DynTree for T {
+    fn get(&self, key: &[u8], seqno: Option<SeqNo>) -> crate::Result<Option<UserValue>> {
+        AbstractTree::get(self, key, seqno)
+    }
+
+    fn contains_key(&self, key: &[u8], seqno: Option<SeqNo>) -> crate::Result<bool> {
+        AbstractTree::contains_key(self, key, seqno)
+    }
+
+    fn get_many(
+        &self,
+        keys: &[&[u8]],
+        seqno: Option<SeqNo>,
+    ) -> crate::Result<Vec<Option<UserValue>>> {
+        AbstractTree::get_many(self, keys, seqno)
+    }
+
+    fn size_of(&self, key: &[u8], seqno: Option<SeqNo>) -> crate::Result<Option<u32>> {
+        AbstractTree::size_of(self, key, seqno)
+    }
+
+    fn iter(&self, seqno: Option<SeqNo>) -> BoxedIter {
+        AbstractTree::iter(self, seqno, None)
+    }
+
+    fn range(&self, range: (Bound<UserKey>, Bound<UserKey>), seqno: Option<SeqNo>) -> BoxedIter {
+        AbstractTree::range(self, range, seqno, None)
+    }
+
+    fn prefix(&self, prefix: &[u8], seqno: Option<SeqNo>) -> BoxedIter {
+        AbstractTree::prefix(self, prefix, seqno, None)
+    }
+
+    fn first_key_value(&self, seqno: Option<SeqNo>) -> crate::Result<Option<KvPair>> {
+        AbstractTree::first_key_value(self, seqno, None)
+    }
+
+    fn last_key_value(&self, seqno: Option<SeqNo>) -> crate::Result<Option<KvPair>> {
+        AbstractTree::last_key_value(self, seqno, None)
+    }
+
+    fn len(&self, seqno: Option<SeqNo>) -> crate::Result<usize> {
+        AbstractTree::len(self, seqno, None)
+    }
+
+    fn is_empty(&self, seqno: Option<SeqNo>) -> crate::Result<bool> {
+        AbstractTree::is_empty(self, seqno, None)
+    }
+
+    fn insert(&self, key: UserKey, value: UserValue, seqno: SeqNo) -> (u32, u32) {
+        AbstractTree::insert(self, key, value, seqno)
+    }
+
+    fn remove(&self, key: UserKey, seqno: SeqNo) -> (u32, u32) {
+        AbstractTree::remove(self, key, seqno)
+    }
+
+    fn remove_weak(&self, key: UserKey, seqno: SeqNo) -> (u32, u32) {
+        AbstractTree::remove_weak(self, key, seqno)
+    }
+
+    fn remove_range(&self, range: std::ops::Range<UserKey>, seqno: SeqNo) -> (u32, u32) {
+        AbstractTree::remove_range(self, range, seqno)
+    }
+
+    fn snapshot(&self, seqno: SeqNo) -> Snapshot {
+        AbstractTree::snapshot(self, seqno)
+    }
+
+    fn flush_active_memtable(&self, eviction_seqno: SeqNo) -> crate::Result<Option<Segment>> {
+        let Some((segment_id, memtable)) = self.rotate_memtable() else {
+            return Ok(None);
+        };
+
+        let Some(segment) = self.flush_memtable(segment_id, &memtable, eviction_seqno)? else {
+            return Ok(None);
+        };
+        self.register_segments(std::slice::from_ref(&segment))?;
+
+        Ok(Some(segment))
+    }
+
+    fn compact(
+        &self,
+        strategy: Arc<dyn CompactionStrategy>,
+        seqno_threshold: SeqNo,
+    ) -> crate::Result<()> {
+        AbstractTree::compact(self, strategy, seqno_threshold)
+    }
+
+    fn major_compact(&self, target_size: u64, seqno_threshold: SeqNo) -> crate::Result<()> {
+        AbstractTree::major_compact(self, target_size, seqno_threshold)
+    }
+
+    fn clear(&self) -> crate::Result<()> {
+        AbstractTree::clear(self)
+    }
+
+    fn tree_type(&self) -> TreeType {
+        AbstractTree::tree_type(self)
+    }
+
+    fn tree_config(&self) -> &Config {
+        AbstractTree::tree_config(self)
+    }
+
+    fn health(&self) -> Health {
+        AbstractTree::health(self)
+    }
+
+    fn disk_space(&self) -> u64 {
+        AbstractTree::disk_space(self)
+    }
+
+    fn segment_count(&self) -> usize {
+        AbstractTree::segment_count(self)
+    }
+
+    fn get_highest_seqno(&self) -> Option<SeqNo> {
+        AbstractTree::get_highest_seqno(self)
+    }
+}
@@ -5,9 +5,46 @@
 use crate::{
     coding::{DecodeError, EncodeError},
     version::Version,
-    Checksum, CompressionType,
+    Checksum, CompressionType, SegmentId, SeqNo, UserKey,
 };
 
+/// Category of an [`Error`], to decide how to handle it
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// An I/O operation failed, retrying may succeed
+    Io,
+
+    /// Data on disk is corrupt, e.g. a checksum mismatch or a missing blob
+    ///
+    /// The affected segment may be repaired or quarantined,
+    /// see [`crate::Config::quarantine_corrupt_segments`].
+    Corruption,
+
+    /// Data could not be deserialized
+    Decode,
+
+    /// The caller passed an invalid argument (or config), retrying will not succeed
+    InvalidArgument,
+
+    /// The operation cannot proceed yet (e.g. a flush waits for the journal),
+    /// retrying later may succeed
+    Busy,
+
+    /// The tree cannot be used anymore without operator intervention
+    /// (e.g. segments are missing, or an internal invariant was violated)
+    Unrecoverable,
+}
+
+impl ErrorKind {
+    fn of_io(error: &std::io::Error) -> Self {
+        match error.kind() {
+            std::io::ErrorKind::InvalidData | std::io::ErrorKind::UnexpectedEof => Self::Decode,
+            _ => Self::Io,
+        }
+    }
+}
+
 /// Represents errors that can occur in the LSM-tree
 #[derive(Debug)]
 #[non_exhaustive]
@@ -35,6 +72,97 @@ pub enum Error {
 
     /// Value log errors
     ValueLog(value_log::Error),
+
+    /// A file was written using a block transform with the given key ID,
+    /// but no block transform is configured
+    MissingBlockTransform(u32),
+
+    /// No custom compression codec is registered for the given tag
+    UnknownCompressionCodec(u8),
+
+    /// A write was rejected, because its key or value is invalid
+    /// (e.g. an empty key)
+    InvalidInput(&'static str),
+
+    /// An internal invariant was violated, see [`crate::Config::paranoid_checks`]
+    InvariantViolation(&'static str),
+
+    /// An index entry of a blob tree points to a blob that does not exist
+    /// in the value log, so the index and the value log are inconsistent
+    DanglingValueHandle {
+        /// Key of the index entry
+        key: UserKey,
+
+        /// Value handle that could not be resolved
+        handle: value_log::ValueHandle,
+    },
+
+    /// A memtable could not be flushed, because it contains a write (with the given seqno)
+    /// that is not persisted in the external journal yet
+    JournalNotPersisted(SeqNo),
+
+    /// A merge operand was read, but no merge operator is configured,
+    /// see [`crate::Config::merge_operator`]
+    MissingMergeOperator,
+
+    /// Data of a segment is corrupt
+    Corruption {
+        /// ID of the corrupt segment
+        segment_id: SegmentId,
+
+        /// Description of the underlying error
+        context: String,
+    },
+}
+
+impl Error {
+    /// Returns the category of the error.
+    #[must_use]
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Io(e) | Self::Encode(EncodeError::Io(e)) => ErrorKind::of_io(e),
+            Self::Decode(_) | Self::Decompress(_) => ErrorKind::Decode,
+            Self::InvalidChecksum(_)
+            | Self::DanglingValueHandle { .. }
+            | Self::Corruption { .. } => ErrorKind::Corruption,
+            Self::InvalidVersion(_) | Self::Unrecoverable | Self::InvariantViolation(_) => {
+                ErrorKind::Unrecoverable
+            }
+            Self::MissingBlockTransform(_)
+            | Self::UnknownCompressionCodec(_)
+            | Self::InvalidInput(_)
+            | Self::MissingMergeOperator => ErrorKind::InvalidArgument,
+            Self::JournalNotPersisted(_) => ErrorKind::Busy,
+            Self::ValueLog(e) => match e {
+                value_log::Error::Io(e) => ErrorKind::of_io(e),
+                value_log::Error::Encode(_) | value_log::Error::Compress => ErrorKind::Io,
+                value_log::Error::InvalidVersion(_) => ErrorKind::Unrecoverable,
+                _ => ErrorKind::Decode,
+            },
+        }
+    }
+
+    /// Returns `true` if the error was caused by data on disk that
+    /// could not be read back as written (e.g. a checksum mismatch).
+    #[must_use]
+    pub fn is_corruption(&self) -> bool {
+        matches!(self.kind(), ErrorKind::Corruption | ErrorKind::Decode)
+    }
+
+    /// Attributes an error that was caused by unreadable data to the segment it was read from.
+    ///
+    /// Other errors are returned as is.
+    #[must_use]
+    pub(crate) fn in_segment(self, segment_id: SegmentId) -> Self {
+        match self {
+            Self::Corruption { .. } => self,
+            e if e.is_corruption() => Self::Corruption {
+                segment_id,
+                context: e.to_string(),
+            },
+            e => e,
+        }
+    }
 }
 
 impl std::fmt::Display for Error {
@@ -71,3 +199,45 @@ impl From<value_log::Error> for Error {
 
 /// Tree result
 pub type Result<T> = std::result::Result<T, Error>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn error_kind() {
+        assert_eq!(
+            ErrorKind::Io,
+            Error::from(std::io::Error::from(std::io::ErrorKind::NotFound)).kind()
+        );
+        assert_eq!(
+            ErrorKind::Decode,
+            Error::from(std::io::Error::from(std::io::ErrorKind::UnexpectedEof)).kind()
+        );
+        assert_eq!(ErrorKind::InvalidArgument, Error::InvalidInput("").kind());
+        assert_eq!(ErrorKind::Busy, Error::JournalNotPersisted(0).kind());
+        assert_eq!(
+            ErrorKind::Unrecoverable,
+            Error::InvariantViolation("").kind()
+        );
+    }
+
+    #[test]
+    fn error_in_segment() {
+        let error = Error::Decompress(CompressionType::None).in_segment(5);
+        assert!(matches!(error, Error::Corruption { segment_id: 5, .. }));
+        assert!(error.is_corruption());
+
+        // NOTE: Errors keep the segment they were first attributed to
+        assert!(matches!(
+            error.in_segment(6),
+            Error::Corruption { segment_id: 5, .. }
+        ));
+
+        assert!(matches!(
+            Error::InvalidInput("").in_segment(5),
+            Error::InvalidInput(_)
+        ));
+    }
+}
@@ -0,0 +1,156 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+/// Blocking task that is handed to an [`Executor`]
+pub type BlockingTask = Box<dyn FnOnce() + Send + 'static>;
+
+/// Runs blocking work on behalf of the crate
+///
+/// Implement this to route blocking I/O onto your runtime or thread pool,
+/// e.g. using `tokio::task::spawn_blocking`, `smol::unblock`, or a custom thread pool.
+///
+/// Closures of the form `Fn(BlockingTask)` are executors as well.
+///
+/// Executors are used by [`crate::AsyncTree`] and for background work
+/// of the tree, see [`crate::Config::spawn_hook`].
+pub trait Executor: Send + Sync {
+    /// Runs the task to completion, without blocking the caller.
+    ///
+    /// Dropping the task without running it is allowed (e.g. when shutting down),
+    /// though for [`crate::AsyncTree`] this causes the operation to panic.
+    fn spawn_blocking(&self, task: BlockingTask);
+}
+
+impl<F: Fn(BlockingTask) + Send + Sync> Executor for F {
+    fn spawn_blocking(&self, task: BlockingTask) {
+        self(task);
+    }
+}
+
+/// Executor that runs every task on a new OS thread
+///
+/// Works without any runtime, but spawning a thread per operation
+/// is expensive, so prefer the blocking pool of your runtime.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ThreadExecutor;
+
+impl Executor for ThreadExecutor {
+    fn spawn_blocking(&self, task: BlockingTask) {
+        std::thread::spawn(task);
+    }
+}
+
+/// Runs the jobs using at most `limit` tasks on the executor, returning the results in order
+///
+/// The calling thread works through the jobs as well, so all jobs complete
+/// even if the executor is saturated, or drops tasks without running them.
+///
+/// A panicking job results in an I/O error instead of unwinding into the caller.
+pub fn run_bounded<T, F>(
+    executor: &dyn Executor,
+    jobs: Vec<F>,
+    limit: usize,
+) -> Vec<crate::Result<T>>
+where
+    T: Send + 'static,
+    F: FnOnce() -> crate::Result<T> + Send + 'static,
+{
+    use std::sync::{mpsc, Arc, Mutex};
+
+    type Queue<F> = Mutex<std::iter::Enumerate<std::vec::IntoIter<F>>>;
+
+    fn work<T, F: FnOnce() -> crate::Result<T>>(
+        queue: &Queue<F>,
+        tx: &mpsc::Sender<(usize, crate::Result<T>)>,
+    ) {
+        loop {
+            let Some((idx, job)) = queue.lock().expect("lock is poisoned").next() else {
+                return;
+            };
+
+            let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(job))
+                .unwrap_or_else(|_| Err(job_panicked()));
+
+            if tx.send((idx, result)).is_err() {
+                return;
+            }
+        }
+    }
+
+    let job_count = jobs.len();
+    let queue: Arc<Queue<F>> = Arc::new(Mutex::new(jobs.into_iter().enumerate()));
+    let (tx, rx) = mpsc::channel();
+
+    // NOTE: The current thread is one of the workers
+    for _ in 1..limit.min(job_count) {
+        let queue = queue.clone();
+        let tx = tx.clone();
+        executor.spawn_blocking(Box::new(move || work(&queue, &tx)));
+    }
+
+    work(&queue, &tx);
+    drop(tx);
+
+    let mut results = std::iter::repeat_with(|| None)
+        .take(job_count)
+        .collect::<Vec<_>>();
+
+    // NOTE: Do not wait for the other workers to exit, only for the jobs they took,
+    // a worker that is still queued in the executor will find the queue empty
+    for _ in 0..job_count {
+        let Ok((idx, result)) = rx.recv() else {
+            break;
+        };
+
+        if let Some(slot) = results.get_mut(idx) {
+            *slot = Some(result);
+        }
+    }
+
+    results
+        .into_iter()
+        .map(|result| result.unwrap_or_else(|| Err(job_panicked())))
+        .collect()
+}
+
+fn job_panicked() -> crate::Error {
+    crate::Error::Io(std::io::Error::other("background job panicked"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn run_bounded_in_order() {
+        let jobs = (0..10).map(|x| move || Ok(x)).collect::<Vec<_>>();
+
+        let results = run_bounded(&ThreadExecutor, jobs, 4)
+            .into_iter()
+            .collect::<crate::Result<Vec<_>>>()
+            .expect("should not fail");
+
+        assert_eq!((0..10).collect::<Vec<_>>(), results);
+    }
+
+    #[test]
+    fn run_bounded_dropping_executor() {
+        let executor = |_: BlockingTask| {};
+
+        let jobs = (0..10).map(|x| move || Ok(x)).collect::<Vec<_>>();
+
+        assert_eq!(10, run_bounded(&executor, jobs, 4).iter().flatten().count());
+    }
+
+    #[test]
+    fn run_bounded_panic() {
+        let jobs: Vec<Box<dyn FnOnce() -> crate::Result<u8> + Send>> =
+            vec![Box::new(|| Ok(0)), Box::new(|| panic!("oops"))];
+
+        let results = run_bounded(&ThreadExecutor, jobs, 2);
+        assert!(results.first().is_some_and(Result::is_ok));
+        assert!(matches!(results.get(1), Some(Err(crate::Error::Io(_)))));
+    }
+}
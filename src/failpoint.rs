@@ -0,0 +1,111 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+/// Evaluates a failpoint, returning early with an error if it is configured to fail
+///
+/// Compiles to nothing, unless the `failpoints` feature is enabled.
+macro_rules! fail_point {
+    ($name:expr) => {
+        #[cfg(feature = "failpoints")]
+        $crate::failpoint::eval($name)?;
+    };
+}
+
+#[cfg(feature = "failpoints")]
+pub use enabled::*;
+
+#[cfg(feature = "failpoints")]
+mod enabled {
+    use std::{
+        collections::BTreeMap,
+        sync::{Arc, Mutex},
+    };
+
+    /// Names of the failpoints
+    pub mod points {
+        /// A segment file (including its tombstones) is fully written, but not fsynced yet
+        pub const SEGMENT_BEFORE_FSYNC: &str = "segment::before_fsync";
+
+        /// New segments are written and synced, but the level manifest
+        /// does not reference them yet (flushes and compactions)
+        pub const MANIFEST_BEFORE_SWAP: &str = "manifest::before_swap";
+
+        /// Blob files of a blob tree flush are written and registered in the value log,
+        /// but the index segment that points to them is not written yet
+        pub const BLOB_AFTER_REGISTER: &str = "blob_tree::after_blob_register";
+    }
+
+    /// What happens when a failpoint is hit
+    #[derive(Clone)]
+    pub enum FailAction {
+        /// Returns an I/O error from the operation
+        Error,
+
+        /// Panics, aborting the operation half-way
+        Panic,
+
+        /// Calls the function and continues, e.g. to snapshot the tree folder
+        /// or to synchronize with the test
+        Call(Arc<dyn Fn() + Send + Sync>),
+    }
+
+    static FAILPOINTS: Mutex<BTreeMap<&'static str, FailAction>> = Mutex::new(BTreeMap::new());
+
+    /// Arms a failpoint, see [`points`] for the available failpoints.
+    ///
+    /// Failpoints are process-wide, so they affect all trees.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the failpoint registry lock is poisoned.
+    pub fn configure(name: &'static str, action: FailAction) {
+        FAILPOINTS
+            .lock()
+            .expect("lock is poisoned")
+            .insert(name, action);
+    }
+
+    /// Disarms a failpoint.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the failpoint registry lock is poisoned.
+    pub fn remove(name: &str) {
+        FAILPOINTS.lock().expect("lock is poisoned").remove(name);
+    }
+
+    /// Disarms all failpoints.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the failpoint registry lock is poisoned.
+    pub fn clear() {
+        FAILPOINTS.lock().expect("lock is poisoned").clear();
+    }
+
+    pub fn eval(name: &'static str) -> crate::Result<()> {
+        // NOTE: Clone, so the registry is not locked while running the action
+        let action = FAILPOINTS
+            .lock()
+            .expect("lock is poisoned")
+            .get(name)
+            .cloned();
+
+        match action {
+            None => Ok(()),
+            Some(FailAction::Error) => {
+                log::debug!("Failpoint {name} hit, returning error");
+
+                Err(crate::Error::Io(std::io::Error::other(format!(
+                    "failpoint {name}"
+                ))))
+            }
+            Some(FailAction::Panic) => panic!("failpoint {name}"),
+            Some(FailAction::Call(f)) => {
+                f();
+                Ok(())
+            }
+        }
+    }
+}
@@ -2,7 +2,7 @@
 // This source code is licensed under both the Apache 2.0 and MIT License
 // (found in the LICENSE-* files in the repository)
 
-use std::{io::Write, path::Path};
+use std::{fs::File, io::Write, path::Path};
 
 pub const MAGIC_BYTES: [u8; 4] = [b'L', b'S', b'M', 2];
 
@@ -10,6 +10,10 @@ pub const MANIFEST_FILE: &str = "manifest";
 pub const SEGMENTS_FOLDER: &str = "segments";
 pub const LEVELS_MANIFEST_FILE: &str = "levels";
 pub const BLOBS_FOLDER: &str = "blobs";
+pub const QUARANTINE_FOLDER: &str = "quarantine";
+pub const SEQNO_TIME_MAP_FILE: &str = "seqno_time";
+pub const ACCESS_STATS_FILE: &str = "access_stats";
+pub const KEYSPACES_FOLDER: &str = "keyspaces";
 
 /// Atomically rewrites a file
 pub fn rewrite_atomic<P: AsRef<Path>>(path: P, content: &[u8]) -> std::io::Result<()> {
@@ -33,6 +37,46 @@ pub fn rewrite_atomic<P: AsRef<Path>>(path: P, content: &[u8]) -> std::io::Resul
     Ok(())
 }
 
+/// Reads exactly `buf.len()` bytes from the given file offset
+///
+/// Does not move the file cursor, so the read is a single
+/// positional read (`pread`) instead of a seek followed by a read.
+#[cfg(unix)]
+pub fn read_exact_at(file: &File, buf: &mut [u8], offset: u64) -> std::io::Result<()> {
+    use std::os::unix::fs::FileExt;
+
+    file.read_exact_at(buf, offset)
+}
+
+/// Reads exactly `buf.len()` bytes from the given file offset
+#[cfg(target_os = "windows")]
+pub fn read_exact_at(file: &File, mut buf: &mut [u8], mut offset: u64) -> std::io::Result<()> {
+    use std::os::windows::fs::FileExt;
+
+    while !buf.is_empty() {
+        match file.seek_read(buf, offset) {
+            Ok(0) => return Err(std::io::ErrorKind::UnexpectedEof.into()),
+            Ok(n) => {
+                buf = std::mem::take(&mut buf).get_mut(n..).unwrap_or_default();
+                offset += n as u64;
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads exactly `buf.len()` bytes from the given file offset
+#[cfg(not(any(unix, target_os = "windows")))]
+pub fn read_exact_at(mut file: &File, buf: &mut [u8], offset: u64) -> std::io::Result<()> {
+    use std::io::{Read, Seek};
+
+    file.seek(std::io::SeekFrom::Start(offset))?;
+    file.read_exact(buf)
+}
+
 #[cfg(not(target_os = "windows"))]
 pub fn fsync_directory<P: AsRef<Path>>(path: P) -> std::io::Result<()> {
     let file = std::fs::File::open(path)?;
@@ -46,6 +90,46 @@ pub fn fsync_directory<P: AsRef<Path>>(path: P) -> std::io::Result<()> {
     Ok(())
 }
 
+/// Hard links a file, copying it if it cannot be linked
+/// (e.g. because the destination is on another file system)
+pub fn hard_link_or_copy<P: AsRef<Path>, Q: AsRef<Path>>(src: P, dest: Q) -> std::io::Result<()> {
+    let (src, dest) = (src.as_ref(), dest.as_ref());
+
+    if let Err(e) = std::fs::hard_link(src, dest) {
+        log::debug!("Could not hard link {}, copying it: {e:?}", src.display());
+        std::fs::copy(src, dest)?;
+    }
+
+    Ok(())
+}
+
+/// Hard links all files of a folder into another folder, recursively
+///
+/// Files are linked before descending into subfolders.
+pub fn hard_link_folder<P: AsRef<Path>, Q: AsRef<Path>>(src: P, dest: Q) -> std::io::Result<()> {
+    let (src, dest) = (src.as_ref(), dest.as_ref());
+
+    std::fs::create_dir_all(dest)?;
+
+    let mut folders = vec![];
+
+    for entry in std::fs::read_dir(src)? {
+        let entry = entry?;
+
+        if entry.file_type()?.is_dir() {
+            folders.push(entry.file_name());
+        } else {
+            hard_link_or_copy(entry.path(), dest.join(entry.file_name()))?;
+        }
+    }
+
+    for folder in folders {
+        hard_link_folder(src.join(&folder), dest.join(&folder))?;
+    }
+
+    fsync_directory(dest)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,88 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::Config;
+
+/// Write stall state, derived from the amount of L0 segments
+/// and sealed memtables, see [`crate::AbstractTree::write_stall_state`]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum StallState {
+    /// Flushes and compactions are keeping up
+    None,
+
+    /// L0 or the flush backlog has reached its slowdown threshold,
+    /// writes should be throttled
+    Slowdown,
+
+    /// L0 or the flush backlog has reached its stop threshold, writes should be
+    /// halted until flushes and compactions have caught up
+    Stop,
+}
+
+impl StallState {
+    pub(crate) fn new(
+        config: &Config,
+        l0_segment_count: usize,
+        sealed_memtable_count: usize,
+    ) -> Self {
+        if l0_segment_count >= config.l0_stop_threshold
+            || sealed_memtable_count >= config.sealed_memtable_stop_threshold
+        {
+            Self::Stop
+        } else if l0_segment_count >= config.l0_slowdown_threshold
+            || sealed_memtable_count >= config.sealed_memtable_slowdown_threshold
+        {
+            Self::Slowdown
+        } else {
+            Self::None
+        }
+    }
+}
+
+/// Compact health report of a tree
+///
+/// Meant to be polled cheaply (e.g. by a load balancer) to decide
+/// whether traffic should be shed from a node.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct Health {
+    /// Amount of segments in L0
+    pub l0_segment_count: usize,
+
+    /// L0 segment count at which writes should be slowed down
+    pub l0_slowdown_threshold: usize,
+
+    /// L0 segment count at which writes should be stopped
+    pub l0_stop_threshold: usize,
+
+    /// Write stall state
+    pub stall: StallState,
+
+    /// Amount of bytes that are stored above the last level,
+    /// and will need to be compacted (at least once more)
+    pub compaction_debt_bytes: u64,
+
+    /// Amount of sealed memtables waiting to be flushed
+    pub sealed_memtable_count: usize,
+
+    /// Amount of stale bytes in blob files (always 0 for standard trees)
+    pub blob_stale_bytes: u64,
+
+    /// Ratio of stale bytes in blob files (always 0.0 for standard trees)
+    pub blob_stale_ratio: f32,
+
+    /// Error of the last flush or compaction, if it failed
+    ///
+    /// Cleared once a flush or compaction succeeds again.
+    pub last_error: Option<String>,
+}
+
+impl Health {
+    /// Returns `true` if writes are not stalled and the last flush or compaction did not fail.
+    #[must_use]
+    pub fn is_healthy(&self) -> bool {
+        self.stall == StallState::None && self.last_error.is_none()
+    }
+}
@@ -0,0 +1,36 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::{tree::inner::MemtableId, SeqNo};
+
+/// Hooks for embedders that maintain their own write-ahead journal
+///
+/// The tree itself does not journal writes, so data in memtables is lost on a crash.
+/// An embedder that journals its writes can use these hooks (together with
+/// [`crate::AbstractTree::mark_journal_persisted`] and [`crate::AbstractTree::replay_journal`])
+/// to keep its journal and the tree in sync:
+///
+/// 1. When a memtable is sealed, start a new journal file, so the old journal
+///    file only contains data of the sealed memtable.
+/// 2. Once [`crate::AbstractTree::get_highest_persisted_seqno`] has caught up with
+///    the rotation watermark, the old journal file is no longer needed.
+/// 3. After a restart, replay the remaining journal files into the tree.
+pub trait JournalObserver: Send + Sync {
+    /// Called when the active memtable is sealed.
+    ///
+    /// `highest_seqno` is the highest seqno in the sealed memtable, all later writes
+    /// go into a new memtable.
+    ///
+    /// The memtable write lock is held during the call, so no write can slip in
+    /// between sealing and the callback, but writing to the tree from inside
+    /// the callback deadlocks.
+    fn on_memtable_rotated(&self, memtable_id: MemtableId, highest_seqno: SeqNo);
+
+    /// Called after flushed segments were registered in the tree.
+    ///
+    /// `persisted_seqno` is the new value of [`crate::AbstractTree::get_highest_persisted_seqno`],
+    /// so all writes up to it can be acknowledged as durable, or dropped from the journal.
+    #[allow(unused_variables)]
+    fn on_segments_persisted(&self, persisted_seqno: SeqNo) {}
+}
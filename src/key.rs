@@ -4,6 +4,7 @@
 
 use crate::{
     coding::{Decode, DecodeError, Encode, EncodeError},
+    compare::compare_keys,
     SeqNo, UserKey, ValueType,
 };
 use byteorder::{ReadBytesExt, WriteBytesExt};
@@ -33,6 +34,8 @@ impl std::fmt::Debug for InternalKey {
                 ValueType::Value => "V",
                 ValueType::Tombstone => "T",
                 ValueType::WeakTombstone => "W",
+                ValueType::Merge => "M",
+                ValueType::ValueWithExpiry => "E",
             },
         )
     }
@@ -101,7 +104,8 @@ impl PartialOrd for InternalKey {
 // Otherwise queries will not match expected behaviour
 impl Ord for InternalKey {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        (&self.user_key, Reverse(self.seqno)).cmp(&(&other.user_key, Reverse(other.seqno)))
+        compare_keys(&self.user_key, &other.user_key)
+            .then_with(|| Reverse(self.seqno).cmp(&Reverse(other.seqno)))
     }
 }
 
@@ -13,6 +13,25 @@ use std::{
 };
 
 /// A key range in the format of [min, max] (inclusive on both sides)
+///
+/// # Examples
+///
+/// ```
+/// use lsm_tree::KeyRange;
+///
+/// let range = KeyRange::new(("b".into(), "f".into()));
+/// let other = KeyRange::new(("d".into(), "k".into()));
+///
+/// assert!(range.overlaps_with_key_range(&other));
+/// assert_eq!(
+///     Some(KeyRange::new(("d".into(), "f".into()))),
+///     range.intersection(&other),
+/// );
+/// assert!(range.is_covered_by(&[
+///     KeyRange::new(("a".into(), "c".into())),
+///     KeyRange::new(("c".into(), "g".into())),
+/// ]));
+/// ```
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct KeyRange((UserKey, UserKey));
 
@@ -36,23 +55,79 @@ impl std::ops::Deref for KeyRange {
 }
 
 impl KeyRange {
+    /// Creates a key range from its (inclusive) min and max key.
+    #[must_use]
     pub fn new(range: (UserKey, UserKey)) -> Self {
         Self(range)
     }
 
+    /// Creates a key range that only contains the empty key.
+    #[must_use]
     pub fn empty() -> Self {
         Self((Slice::new(b""), Slice::new(b"")))
     }
 
-    fn min(&self) -> &UserKey {
+    /// Returns the lowest key of the range.
+    #[must_use]
+    pub fn min(&self) -> &UserKey {
         &self.0 .0
     }
 
-    fn max(&self) -> &UserKey {
+    /// Returns the highest key of the range.
+    #[must_use]
+    pub fn max(&self) -> &UserKey {
         &self.0 .1
     }
 
+    /// Returns the range of keys that are contained in both ranges,
+    /// or `None` if they do not overlap.
+    #[must_use]
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        if !self.overlaps_with_key_range(other) {
+            return None;
+        }
+
+        let min = self.min().max(other.min());
+        let max = self.max().min(other.max());
+
+        Some(Self((min.clone(), max.clone())))
+    }
+
+    /// Returns `true` if every key of this range is contained in at least one of the given ranges.
+    #[must_use]
+    pub fn is_covered_by(&self, ranges: &[Self]) -> bool {
+        let mut ranges = ranges
+            .iter()
+            .filter(|x| x.overlaps_with_key_range(self))
+            .collect::<Vec<_>>();
+
+        ranges.sort_by(|a, b| a.min().cmp(b.min()));
+
+        // NOTE: The lowest key that is not covered yet
+        let mut uncovered = self.min().to_vec();
+
+        for range in ranges {
+            if **range.min() > *uncovered {
+                return false;
+            }
+
+            if range.max() >= self.max() {
+                return true;
+            }
+
+            if **range.max() >= *uncovered {
+                // NOTE: The next key after max is max + 0x00, so an
+                // adjacent range may start right there and leave no gap
+                uncovered = range.max().to_vec();
+                uncovered.push(0);
+            }
+        }
+
+        false
+    }
+
     /// Returns `true` if the list of key ranges is disjoint
+    #[must_use]
     pub fn is_disjoint(ranges: &[&Self]) -> bool {
         for (idx, a) in ranges.iter().enumerate() {
             for b in ranges.iter().skip(idx + 1) {
@@ -66,6 +141,7 @@ impl KeyRange {
     }
 
     /// Returns `true` if the key falls within this key range.
+    #[must_use]
     pub fn contains_key<K: AsRef<[u8]>>(&self, key: K) -> bool {
         let key = key.as_ref();
         let (start, end) = &self.0;
@@ -73,6 +149,7 @@ impl KeyRange {
     }
 
     /// Returns `true` if the `other` is fully contained in this range.
+    #[must_use]
     pub fn contains_range(&self, other: &Self) -> bool {
         let (start1, end1) = &self.0;
         let (start2, end2) = &other.0;
@@ -80,52 +157,36 @@ impl KeyRange {
     }
 
     /// Returns `true` if the `other` overlaps at least partially with this range.
+    #[must_use]
     pub fn overlaps_with_key_range(&self, other: &Self) -> bool {
         let (start1, end1) = &self.0;
         let (start2, end2) = &other.0;
         end1 >= start2 && start1 <= end2
     }
 
+    /// Returns `true` if the range overlaps at least partially with the given bounds.
+    #[must_use]
     pub fn overlaps_with_bounds(&self, bounds: &(Bound<UserKey>, Bound<UserKey>)) -> bool {
         let (lo, hi) = bounds;
         let (my_lo, my_hi) = &self.0;
 
-        if *lo == Bound::Unbounded && *hi == Bound::Unbounded {
-            return true;
-        }
-
-        if *hi == Bound::Unbounded {
-            return match lo {
-                Bound::Included(key) => key <= my_hi,
-                Bound::Excluded(key) => key < my_hi,
-                Bound::Unbounded => panic!("Invalid key range check"),
-            };
-        }
-
-        if *lo == Bound::Unbounded {
-            return match hi {
-                Bound::Included(key) => key >= my_lo,
-                Bound::Excluded(key) => key > my_lo,
-                Bound::Unbounded => panic!("Invalid key range check"),
-            };
-        }
-
         let lo_included = match lo {
             Bound::Included(key) => key <= my_hi,
             Bound::Excluded(key) => key < my_hi,
-            Bound::Unbounded => panic!("Invalid key range check"),
+            Bound::Unbounded => true,
         };
 
         let hi_included = match hi {
             Bound::Included(key) => key >= my_lo,
             Bound::Excluded(key) => key > my_lo,
-            Bound::Unbounded => panic!("Invalid key range check"),
+            Bound::Unbounded => true,
         };
 
         lo_included && hi_included
     }
 
     /// Aggregates a key range.
+    #[must_use]
     pub fn aggregate<'a>(mut iter: impl Iterator<Item = &'a Self>) -> Self {
         let Some(first) = iter.next() else {
             return Self::empty();
@@ -178,6 +239,24 @@ impl Decode for KeyRange {
     }
 }
 
+// NOTE: Keys are (de)serialized as plain byte sequences,
+// so the format does not depend on the key's backing type
+#[cfg(feature = "serde")]
+impl serde::Serialize for KeyRange {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let (min, max) = &self.0;
+        (&**min, &**max).serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for KeyRange {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (min, max) = <(Vec<u8>, Vec<u8>)>::deserialize(deserializer)?;
+        Ok(Self::new((min.into(), max.into())))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -217,6 +296,40 @@ mod tests {
         assert_eq!([0, 0, 0, 0, 0, 0, 0, 10], &*max);
     }
 
+    #[test]
+    fn key_range_intersection() {
+        let a = string_key_range("b", "f");
+
+        assert_eq!(
+            Some(string_key_range("d", "f")),
+            a.intersection(&string_key_range("d", "k")),
+        );
+        assert_eq!(
+            Some(string_key_range("c", "d")),
+            a.intersection(&string_key_range("c", "d")),
+        );
+        assert_eq!(
+            Some(string_key_range("f", "f")),
+            a.intersection(&string_key_range("f", "g")),
+        );
+        assert_eq!(None, a.intersection(&string_key_range("g", "k")));
+    }
+
+    #[test]
+    fn key_range_is_covered_by() {
+        let a = string_key_range("b", "f");
+
+        assert!(a.is_covered_by(&[string_key_range("a", "z")]));
+        assert!(a.is_covered_by(&[string_key_range("d", "g"), string_key_range("a", "d")]));
+        assert!(!a.is_covered_by(&[]));
+        assert!(!a.is_covered_by(&[string_key_range("c", "z")]));
+        assert!(!a.is_covered_by(&[string_key_range("a", "e")]));
+
+        // NOTE: "c\0" is between "c" and "d"
+        assert!(!a.is_covered_by(&[string_key_range("a", "c"), string_key_range("d", "z")]));
+        assert!(a.is_covered_by(&[string_key_range("a", "c"), string_key_range("c\0", "z")]));
+    }
+
     mod is_disjoint {
         use super::*;
         use test_log::test;
@@ -0,0 +1,294 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::{
+    file::KEYSPACES_FOLDER, Config, InternalValue, SeqNo, Tree, UserKey, UserValue, ValueType,
+};
+use std::{
+    collections::BTreeMap,
+    path::PathBuf,
+    sync::{Arc, RwLock},
+};
+
+/// Maximum length of a keyspace name
+const MAX_NAME_LEN: usize = 255;
+
+fn validate_name(name: &str) -> crate::Result<()> {
+    if name.is_empty() || name.len() > MAX_NAME_LEN {
+        return Err(crate::Error::InvalidInput(
+            "keyspace names need to be 1 to 255 bytes in length",
+        ));
+    }
+
+    // NOTE: Names are used as folder names, so only allow characters that are safe on every platform
+    if !name
+        .bytes()
+        .all(|c| c.is_ascii_alphanumeric() || c == b'_' || c == b'-')
+    {
+        return Err(crate::Error::InvalidInput(
+            "keyspace names may only contain ASCII letters, digits, '_' and '-'",
+        ));
+    }
+
+    Ok(())
+}
+
+struct KeyspacesInner {
+    /// Config that keyspaces are derived from
+    ///
+    /// Contains the shared block cache, descriptor table and executor.
+    config: Config,
+
+    /// Open keyspaces
+    keyspaces: RwLock<BTreeMap<String, Tree>>,
+}
+
+/// Named keyspaces (column families) inside one folder
+///
+/// Every keyspace is a [`Tree`] with its own memtables, levels and config,
+/// but all keyspaces share the block cache, file descriptor table and
+/// background executor, so resource usage is controlled in one place.
+///
+/// Writes to multiple keyspaces can be applied atomically using a [`WriteBatch`].
+///
+/// Cloning `Keyspaces` is cheap, clones refer to the same keyspaces.
+///
+/// # Examples
+///
+/// ```
+/// # let folder = tempfile::tempdir()?;
+/// use lsm_tree::{AbstractTree, Config, Keyspaces};
+///
+/// let keyspaces = Keyspaces::open(Config::new(folder))?;
+/// let users = keyspaces.keyspace("users", |config| config)?;
+/// let emails = keyspaces.keyspace("emails", |config| config.level_count(4))?;
+///
+/// let mut batch = keyspaces.batch();
+/// batch.insert("users", "1", "alice");
+/// batch.insert("emails", "alice@example.com", "1");
+/// batch.commit(0)?;
+///
+/// assert!(users.contains_key("1", None)?);
+/// assert!(emails.contains_key("alice@example.com", None)?);
+/// #
+/// # Ok::<(), lsm_tree::Error>(())
+/// ```
+#[derive(Clone)]
+pub struct Keyspaces(Arc<KeyspacesInner>);
+
+impl Keyspaces {
+    /// Opens the keyspaces in the folder of the given config.
+    ///
+    /// The config is the template of every keyspace; its block cache,
+    /// descriptor table and executor (see [`Config::spawn_hook`]) are shared by all keyspaces.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub fn open(config: Config) -> crate::Result<Self> {
+        std::fs::create_dir_all(config.path.join(KEYSPACES_FOLDER))?;
+
+        Ok(Self(Arc::new(KeyspacesInner {
+            config,
+            keyspaces: RwLock::default(),
+        })))
+    }
+
+    fn keyspace_path(&self, name: &str) -> PathBuf {
+        self.0.config.path.join(KEYSPACES_FOLDER).join(name)
+    }
+
+    /// Opens a keyspace, creating it if it does not exist.
+    ///
+    /// The keyspace config is derived from the template config using `configure`,
+    /// but the shared resources are always kept. If the keyspace is already open,
+    /// its handle is returned and `configure` is not called.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the name is invalid, or an IO error occurs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the keyspace lock is poisoned.
+    #[allow(clippy::significant_drop_tightening)]
+    pub fn keyspace<F: FnOnce(Config) -> Config>(
+        &self,
+        name: &str,
+        configure: F,
+    ) -> crate::Result<Tree> {
+        validate_name(name)?;
+
+        // NOTE: Hold the lock while opening, so a keyspace is never opened twice
+        let mut keyspaces = self.0.keyspaces.write().expect("lock is poisoned");
+
+        if let Some(tree) = keyspaces.get(name) {
+            return Ok(tree.clone());
+        }
+
+        let template = &self.0.config;
+
+        let mut config = configure(template.clone());
+        config.path = self.keyspace_path(name);
+        config.block_cache.clone_from(&template.block_cache);
+        config
+            .descriptor_table
+            .clone_from(&template.descriptor_table);
+        config.spawn_hook.clone_from(&template.spawn_hook);
+
+        let tree = config.open()?;
+        keyspaces.insert(name.to_owned(), tree.clone());
+
+        Ok(tree)
+    }
+
+    /// Returns the names of all keyspaces on disk, in ascending order.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub fn list_keyspaces(&self) -> crate::Result<Vec<String>> {
+        let mut names = vec![];
+
+        for entry in std::fs::read_dir(self.0.config.path.join(KEYSPACES_FOLDER))? {
+            let entry = entry?;
+
+            // NOTE: Skip anything that is not a keyspace we could have created
+            let Ok(name) = entry.file_name().into_string() else {
+                continue;
+            };
+
+            if entry.file_type()?.is_dir() && validate_name(&name).is_ok() {
+                names.push(name);
+            }
+        }
+
+        names.sort();
+
+        Ok(names)
+    }
+
+    /// Returns `true` if a keyspace with the given name exists.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub fn contains_keyspace(&self, name: &str) -> crate::Result<bool> {
+        if validate_name(name).is_err() {
+            return Ok(false);
+        }
+
+        Ok(self.keyspace_path(name).try_exists()?)
+    }
+
+    /// Starts a batch of writes to the open keyspaces.
+    #[must_use]
+    pub fn batch(&self) -> WriteBatch {
+        WriteBatch {
+            keyspaces: self.clone(),
+            writes: Vec::new(),
+        }
+    }
+}
+
+/// Batch of writes across multiple keyspaces
+///
+/// All writes of a batch are applied with the same seqno, so a read at any seqno
+/// either sees all of them, or none of them.
+pub struct WriteBatch {
+    keyspaces: Keyspaces,
+    writes: Vec<(String, UserKey, UserValue, ValueType)>,
+}
+
+impl WriteBatch {
+    /// Adds an insert of a key-value pair into the given keyspace.
+    pub fn insert<K: Into<UserKey>, V: Into<UserValue>>(
+        &mut self,
+        keyspace: &str,
+        key: K,
+        value: V,
+    ) {
+        self.writes.push((
+            keyspace.to_owned(),
+            key.into(),
+            value.into(),
+            ValueType::Value,
+        ));
+    }
+
+    /// Adds a removal of a key from the given keyspace.
+    pub fn remove<K: Into<UserKey>>(&mut self, keyspace: &str, key: K) {
+        self.writes.push((
+            keyspace.to_owned(),
+            key.into(),
+            UserValue::empty(),
+            ValueType::Tombstone,
+        ));
+    }
+
+    /// Returns the amount of writes in the batch.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.writes.len()
+    }
+
+    /// Returns `true` if the batch contains no writes.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.writes.is_empty()
+    }
+
+    /// Applies all writes of the batch with the given seqno.
+    ///
+    /// The batch is validated before anything is written, so if it is rejected,
+    /// no keyspace is changed. As with single writes, the seqno should only
+    /// be made visible to readers after the batch is committed.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if a keyspace is not open, or a key or value is invalid.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the keyspace lock is poisoned.
+    pub fn commit(self, seqno: SeqNo) -> crate::Result<()> {
+        let keyspaces = self.keyspaces.0.keyspaces.read().expect("lock is poisoned");
+
+        let writes = self
+            .writes
+            .into_iter()
+            .map(|(name, key, value, value_type)| {
+                let tree = keyspaces
+                    .get(&name)
+                    .ok_or(crate::Error::InvalidInput("keyspace is not open"))?;
+
+                let value = InternalValue::try_from_components(key, value, seqno, value_type)?;
+
+                Ok((tree, value))
+            })
+            .collect::<crate::Result<Vec<_>>>()?;
+
+        for (tree, value) in writes {
+            let _ = tree.append_entry(value);
+        }
+
+        drop(keyspaces);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn keyspace_name_validation() {
+        assert!(validate_name("users_v2-a").is_ok());
+        assert!(validate_name("").is_err());
+        assert!(validate_name("../escape").is_err());
+        assert!(validate_name(&"a".repeat(MAX_NAME_LEN + 1)).is_err());
+    }
+}
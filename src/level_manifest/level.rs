@@ -297,10 +297,20 @@ mod tests {
                 range_tombstone_count: 0,
                 uncompressed_size: 0,
                 seqnos: (0, 0),
+                temperature: None,
+                filter_fp_rate_ppb: None,
+                global_seqno: None,
             },
             block_cache,
-
-            bloom_filter: Some(crate::bloom::BloomFilter::with_fp_rate(1, 0.1)),
+            statistics: Arc::default(),
+
+            bloom_filter: Some(crate::bloom::BloomFilter::with_fp_rate(1, 0.1).into()).into(),
+            key_sketch: None.into(),
+            key_sketch_ptr: BlockOffset(0),
+            range_tombstones: crate::range_tombstone::RangeTombstoneIndex::default().into(),
+            transform: None,
+            deleted_path: std::sync::OnceLock::new(),
+            access: crate::segment::access_stats::AccessStats::default(),
         }
         .into()
     }
@@ -4,12 +4,13 @@
 
 pub(crate) mod hidden_set;
 pub(crate) mod level;
+pub(crate) mod view;
 
 use crate::{
     coding::{DecodeError, Encode, EncodeError},
     file::{rewrite_atomic, MAGIC_BYTES},
     key_range::KeyRange,
-    segment::{meta::SegmentId, Segment},
+    segment::{meta::SegmentId, trailer::SegmentFileTrailer, Segment},
     HashMap, HashSet,
 };
 use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
@@ -20,9 +21,14 @@ use std::{
     path::{Path, PathBuf},
     sync::Arc,
 };
+use view::{LevelView, LevelViewCell};
 
 type Levels = Vec<Arc<Level>>;
 
+/// Cached trailers (and metadata) of segments, so opening
+/// a tree does not need to read every segment's trailer
+pub(crate) type MetadataCache = HashMap<SegmentId, SegmentFileTrailer>;
+
 /// Represents the levels of a log-structured merge tree
 pub struct LevelManifest {
     /// Path of level manifest file.
@@ -39,6 +45,9 @@ pub struct LevelManifest {
     hidden_set: HiddenSet,
 
     is_disjoint: bool,
+
+    /// Latest view of the levels, used by readers.
+    view: Arc<LevelViewCell>,
 }
 
 impl std::fmt::Display for LevelManifest {
@@ -120,12 +129,15 @@ impl LevelManifest {
 
         let levels = (0..level_count).map(|_| Arc::default()).collect::<Vec<_>>();
 
+        let view = Arc::new(LevelViewCell::new(LevelView::new(levels.clone(), true)));
+
         #[allow(unused_mut)]
         let mut manifest = Self {
             path: path.as_ref().to_path_buf(),
             levels,
             hidden_set: Default::default(),
             is_disjoint: true,
+            view,
         };
         Self::write_to_disk(path, &manifest.deep_clone())?;
 
@@ -146,7 +158,7 @@ impl LevelManifest {
 
     pub(crate) fn load_level_manifest<P: AsRef<Path>>(
         path: P,
-    ) -> crate::Result<Vec<Vec<SegmentId>>> {
+    ) -> crate::Result<(Vec<Vec<SegmentId>>, MetadataCache)> {
         let mut level_manifest = Cursor::new(std::fs::read(&path)?);
 
         // Check header
@@ -175,13 +187,30 @@ impl LevelManifest {
             levels.push(level);
         }
 
-        Ok(levels)
+        let mut cache = MetadataCache::default();
+
+        // NOTE: Level manifests written before metadata was cached end here
+        if level_manifest.position() < level_manifest.get_ref().len() as u64 {
+            let cached_count = level_manifest.read_u32::<BigEndian>()?;
+
+            for _ in 0..cached_count {
+                let id = level_manifest.read_u64::<BigEndian>()?;
+                let trailer = SegmentFileTrailer::decode_cached(&mut level_manifest)?;
+                cache.insert(id, trailer);
+            }
+        }
+
+        Ok((levels, cache))
     }
 
+    /// Returns the level index of every segment, and the cached segment trailers.
     pub(crate) fn recover_ids<P: AsRef<Path>>(
         path: P,
-    ) -> crate::Result<crate::HashMap<SegmentId, u8 /* Level index */>> {
-        let manifest = Self::load_level_manifest(path)?;
+    ) -> crate::Result<(
+        crate::HashMap<SegmentId, u8 /* Level index */>,
+        MetadataCache,
+    )> {
+        let (manifest, cache) = Self::load_level_manifest(path)?;
         let mut result = crate::HashMap::default();
 
         for (level_idx, segment_ids) in manifest.into_iter().enumerate() {
@@ -195,7 +224,7 @@ impl LevelManifest {
             }
         }
 
-        Ok(result)
+        Ok((result, cache))
     }
 
     fn resolve_levels(
@@ -208,7 +237,12 @@ impl LevelManifest {
             let mut created_level = Level::default();
 
             for id in level {
-                let segment = segments.get(&id).cloned().expect("should find segment");
+                // NOTE: Quarantined segments are missing, all others
+                // were checked to exist during recovery
+                let Some(segment) = segments.get(&id).cloned() else {
+                    log::warn!("Segment {id} is missing, dropping it from the level manifest");
+                    continue;
+                };
                 created_level.insert(segment);
             }
 
@@ -219,19 +253,23 @@ impl LevelManifest {
     }
 
     pub(crate) fn recover<P: AsRef<Path>>(path: P, segments: Vec<Segment>) -> crate::Result<Self> {
-        let level_manifest = Self::load_level_manifest(&path)?;
+        let (level_manifest, _) = Self::load_level_manifest(&path)?;
 
         let segments: HashMap<_, _> = segments.into_iter().map(|seg| (seg.id(), seg)).collect();
 
         let levels = Self::resolve_levels(level_manifest, &segments);
 
+        let view = Arc::new(LevelViewCell::new(LevelView::new(levels.clone(), false)));
+
         let mut manifest = Self {
             levels,
             hidden_set: HiddenSet::default(),
             path: path.as_ref().to_path_buf(),
             is_disjoint: false,
+            view,
         };
         manifest.set_disjoint_flag();
+        manifest.publish_view();
 
         Ok(manifest)
     }
@@ -278,10 +316,12 @@ impl LevelManifest {
 
         f(&mut working_copy);
 
+        fail_point!(crate::failpoint::points::MANIFEST_BEFORE_SWAP);
+
         Self::write_to_disk(&self.path, &working_copy)?;
         self.levels = working_copy.into_iter().map(Arc::new).collect();
-        self.update_metadata();
         self.set_disjoint_flag();
+        self.update_metadata();
 
         log::trace!("Swapped level manifest to:\n{self}");
 
@@ -296,10 +336,23 @@ impl LevelManifest {
 
     pub fn update_metadata(&mut self) {
         for level in &mut self.levels {
-            Arc::get_mut(level)
-                .expect("could not get mutable Arc - this is a bug")
-                .update_metadata();
+            // NOTE: The level may still be referenced by a published view,
+            // in which case it is copied
+            Arc::make_mut(level).update_metadata();
         }
+
+        self.publish_view();
+    }
+
+    /// Publishes the current levels to readers.
+    fn publish_view(&self) {
+        self.view
+            .publish(LevelView::new(self.levels.clone(), self.is_disjoint()));
+    }
+
+    /// Returns the cell that holds the latest [`LevelView`].
+    pub(crate) fn view_cell(&self) -> Arc<LevelViewCell> {
+        self.view.clone()
     }
 
     #[allow(unused)]
@@ -313,9 +366,9 @@ impl LevelManifest {
             .get_mut(index as usize)
             .expect("level should exist");
 
-        let level = Arc::get_mut(level).expect("only used in tests");
+        Arc::make_mut(level).insert(segment);
 
-        level.insert(segment);
+        self.publish_view();
     }
 
     #[must_use]
@@ -362,6 +415,37 @@ impl LevelManifest {
         self.iter().map(|s| s.metadata.file_size).sum()
     }
 
+    /// Estimates the space amplification, see [`crate::AbstractTree::space_amp`].
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn space_amp(&self) -> f32 {
+        let Some(live_bytes) = self
+            .levels
+            .iter()
+            .rev()
+            .map(|level| level.size())
+            .find(|&size| size > 0)
+        else {
+            return 0.0;
+        };
+
+        self.size() as f32 / live_bytes as f32
+    }
+
+    /// Returns the amount of bytes that are stored above the last non-empty level.
+    #[must_use]
+    pub fn compaction_debt(&self) -> u64 {
+        let last_level_size = self
+            .levels
+            .iter()
+            .rev()
+            .map(|level| level.size())
+            .find(|&size| size > 0)
+            .unwrap_or_default();
+
+        self.size() - last_level_size
+    }
+
     #[must_use]
     pub fn busy_levels(&self) -> HashSet<u8> {
         let mut output =
@@ -439,6 +523,32 @@ impl Encode for Vec<Level> {
             }
         }
 
+        // NOTE: The metadata of transformed segments is encoded,
+        // so it cannot be cached as plaintext
+        //
+        // The global seqno of ingested segments is only stored in their trailer
+        let cached = self
+            .iter()
+            .flat_map(|level| &level.segments)
+            .filter(|segment| {
+                segment.transform.is_none() && segment.metadata.global_seqno.is_none()
+            })
+            .collect::<Vec<_>>();
+
+        // NOTE: "Truncation" is OK, because there are never 4 billion segments in a tree
+        #[allow(clippy::cast_possible_truncation)]
+        writer.write_u32::<BigEndian>(cached.len() as u32)?;
+
+        for segment in cached {
+            writer.write_u64::<BigEndian>(segment.id())?;
+            SegmentFileTrailer::encode_cached(
+                &segment.metadata,
+                &segment.offsets,
+                segment.key_sketch_ptr,
+                writer,
+            )?;
+        }
+
         Ok(())
     }
 }
@@ -448,9 +558,14 @@ impl Encode for Vec<Level> {
 mod tests {
     use crate::{
         coding::Encode,
-        level_manifest::{hidden_set::HiddenSet, LevelManifest},
+        level_manifest::{
+            hidden_set::HiddenSet,
+            view::{LevelView, LevelViewCell},
+            LevelManifest,
+        },
         AbstractTree,
     };
+    use std::sync::Arc;
     use test_log::test;
 
     #[test]
@@ -502,6 +617,7 @@ mod tests {
             levels: Vec::default(),
             path: "a".into(),
             is_disjoint: false,
+            view: Arc::new(LevelViewCell::new(LevelView::new(Vec::default(), false))),
         };
 
         let bytes = manifest.deep_clone().encode_into_vec();
@@ -513,6 +629,9 @@ mod tests {
 
             // Count
             0,
+
+            // Cached metadata count
+            0, 0, 0, 0,
         ];
 
         assert_eq!(bytes, raw);
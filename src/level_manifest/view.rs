@@ -0,0 +1,130 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use super::level::Level;
+use crate::{range_tombstone::RangeTombstone, Segment, SeqNo};
+use std::sync::{Arc, RwLock};
+
+/// Immutable snapshot of the levels of a tree
+///
+/// Readers (point reads, range scans) work on a view instead of the
+/// level manifest, so they never take (or hold) the level manifest lock.
+/// Every change to the level manifest publishes a new view.
+pub struct LevelView {
+    #[doc(hidden)]
+    pub levels: Vec<Arc<Level>>,
+
+    is_disjoint: bool,
+
+    /// Segments that contain range tombstones
+    range_tombstone_segments: Vec<Segment>,
+}
+
+impl LevelView {
+    pub(crate) fn new(levels: Vec<Arc<Level>>, is_disjoint: bool) -> Self {
+        let range_tombstone_segments = levels
+            .iter()
+            .flat_map(|lvl| &lvl.segments)
+            .filter(|segment| segment.metadata.range_tombstone_count > 0)
+            .cloned()
+            .collect();
+
+        Self {
+            levels,
+            is_disjoint,
+            range_tombstone_segments,
+        }
+    }
+
+    /// Returns `true` if any segment contains range tombstones.
+    pub(crate) fn has_range_tombstones(&self) -> bool {
+        !self.range_tombstone_segments.is_empty()
+    }
+
+    /// Returns `true` if a range tombstone of a segment, which is visible to
+    /// a snapshot with the given seqno, deletes the given version of a key.
+    pub(crate) fn is_range_deleted(
+        &self,
+        key: &[u8],
+        item_seqno: SeqNo,
+        seqno: Option<SeqNo>,
+    ) -> crate::Result<bool> {
+        for segment in &self.range_tombstone_segments {
+            if segment.is_range_deleted(key, item_seqno, seqno)? {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// Collects the range tombstones of all segments that are visible
+    /// to a snapshot with the given seqno.
+    pub(crate) fn collect_range_tombstones(
+        &self,
+        seqno: Option<SeqNo>,
+        tombstones: &mut Vec<RangeTombstone>,
+    ) -> crate::Result<()> {
+        for segment in &self.range_tombstone_segments {
+            tombstones.extend(
+                segment
+                    .range_tombstones()?
+                    .iter()
+                    .filter(|t| t.is_visible(seqno))
+                    .cloned(),
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Returns `true` if all levels and their segments are disjoint to each other.
+    #[must_use]
+    pub fn is_disjoint(&self) -> bool {
+        self.is_disjoint
+    }
+
+    /// Returns the amount of segments, summed over all levels
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.levels.iter().map(|lvl| lvl.len()).sum()
+    }
+
+    /// Returns `true` if there are no segments
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Segment> + '_ {
+        self.levels.iter().flat_map(|x| &x.segments)
+    }
+}
+
+/// Holds the most recently published [`LevelView`]
+///
+/// Loading the view only clones an `Arc`, so the inner lock
+/// is only ever held for a few instructions.
+pub struct LevelViewCell(RwLock<Arc<LevelView>>);
+
+impl LevelViewCell {
+    pub(crate) fn new(view: LevelView) -> Self {
+        Self(RwLock::new(Arc::new(view)))
+    }
+
+    /// Returns the current view.
+    #[must_use]
+    pub fn load(&self) -> Arc<LevelView> {
+        self.0.read().expect("lock is poisoned").clone()
+    }
+
+    pub(crate) fn publish(&self, view: LevelView) {
+        let view = Arc::new(view);
+
+        // NOTE: Drop the old view after releasing the lock,
+        // which may close segments
+        let prev = std::mem::replace(&mut *self.0.write().expect("lock is poisoned"), view);
+        drop(prev);
+    }
+}
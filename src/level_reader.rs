@@ -4,7 +4,7 @@
 
 use crate::{
     level_manifest::level::Level,
-    segment::{range::Range, value_block::CachePolicy},
+    segment::{prefetch::ReadAhead, range::Range, value_block::CachePolicy},
     InternalValue, UserKey,
 };
 use std::{ops::Bound, sync::Arc};
@@ -17,6 +17,7 @@ pub struct LevelReader {
     lo_reader: Option<Range>,
     hi_reader: Option<Range>,
     cache_policy: CachePolicy,
+    read_ahead: Option<ReadAhead>,
 }
 
 impl LevelReader {
@@ -39,6 +40,7 @@ impl LevelReader {
                 lo_reader: None,
                 hi_reader: None,
                 cache_policy,
+                read_ahead: None,
             };
         };
 
@@ -74,8 +76,22 @@ impl LevelReader {
             lo_reader: Some(lo_reader),
             hi_reader,
             cache_policy,
+            read_ahead: None,
         }
     }
+
+    /// Sets the data blocks each segment reader reads ahead during sequential scans
+    #[must_use]
+    pub fn prefetch(mut self, read_ahead: Option<ReadAhead>) -> Self {
+        self.lo_reader = self
+            .lo_reader
+            .map(|reader| reader.prefetch(read_ahead.clone()));
+        self.hi_reader = self
+            .hi_reader
+            .map(|reader| reader.prefetch(read_ahead.clone()));
+        self.read_ahead = read_ahead;
+        self
+    }
 }
 
 impl Iterator for LevelReader {
@@ -98,7 +114,8 @@ impl Iterator for LevelReader {
                             .get(self.lo)
                             .expect("should exist")
                             .iter()
-                            .cache_policy(self.cache_policy),
+                            .cache_policy(self.cache_policy)
+                            .prefetch(self.read_ahead.clone()),
                     );
                 }
             } else if let Some(hi_reader) = &mut self.hi_reader {
@@ -131,7 +148,8 @@ impl DoubleEndedIterator for LevelReader {
                             .get(self.hi)
                             .expect("should exist")
                             .iter()
-                            .cache_policy(self.cache_policy),
+                            .cache_policy(self.cache_policy)
+                            .prefetch(self.read_ahead.clone()),
                     );
                 }
             } else if let Some(lo_reader) = &mut self.lo_reader {
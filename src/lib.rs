@@ -120,36 +120,54 @@ macro_rules! fail_iter {
     };
 }
 
+#[macro_use]
+mod failpoint;
+
 mod any_tree;
 
+mod async_tree;
+
 mod r#abstract;
 
+mod backup;
+
 #[doc(hidden)]
 pub mod blob_tree;
 
 mod block_cache;
 
+mod codec;
+
 #[doc(hidden)]
 pub mod bloom;
 
 #[doc(hidden)]
 pub mod coding;
 pub mod compaction;
+mod compare;
 mod config;
+mod cursor;
 
 #[doc(hidden)]
 pub mod descriptor_table;
 
+mod dyn_tree;
+
 mod either;
 mod error;
+mod executor;
 // mod export;
 
 #[doc(hidden)]
 pub mod file;
 
+mod health;
 mod key;
 mod key_range;
 
+mod journal;
+mod keyspace;
+
 #[doc(hidden)]
 pub mod level_manifest;
 
@@ -157,11 +175,16 @@ mod level_reader;
 mod level_scanner;
 
 mod manifest;
+mod memory_budget;
+mod memory_usage;
 mod memtable;
 
 #[doc(hidden)]
 pub mod merge;
 
+mod merge_operator;
+
+mod multi_range;
 mod multi_reader;
 
 #[doc(hidden)]
@@ -169,23 +192,44 @@ pub mod mvcc_stream;
 
 mod path;
 
+mod pending_work;
+mod quarantine;
+mod read_overlay;
+
+#[cfg(feature = "prometheus")]
+mod prometheus;
+
 #[doc(hidden)]
 pub mod range;
 
+mod range_tombstone;
+
+mod rate_limiter;
+
 #[doc(hidden)]
 pub mod segment;
 
+mod persist_watch;
+mod scan_cursor;
+mod scrub;
 mod seqno;
+mod seqno_time;
+mod slow_operation;
 mod snapshot;
+mod snapshot_tracker;
+mod statistics;
 mod windows;
 
 #[doc(hidden)]
 pub mod stop_signal;
 
 mod time;
+mod transform;
 mod tree;
+mod ttl;
 mod value;
 mod version;
+mod write_buffer_manager;
 
 /// KV-tuple, typically returned by an iterator
 pub type KvPair = (UserKey, UserValue);
@@ -199,18 +243,52 @@ pub use {
 };
 
 pub use {
-    block_cache::BlockCache,
+    async_tree::{AsyncTree, Task},
+    backup::{BackupEngine, BackupReport},
+    block_cache::{BlockCache, BlockEvictionObserver, BlockType},
+    bloom::{BloomLayout, FilterType},
+    codec::{register_compression_codec, CompressionCodec},
     coding::{DecodeError, EncodeError},
     config::{Config, TreeType},
-    error::{Error, Result},
+    cursor::Cursor,
+    dyn_tree::DynTree,
+    error::{Error, ErrorKind, Result},
+    executor::{BlockingTask, Executor, ThreadExecutor},
+    health::{Health, StallState},
+    journal::JournalObserver,
+    key_range::KeyRange,
+    keyspace::{Keyspaces, WriteBatch},
+    memory_budget::{MemoryBudget, TrackedMemory},
+    memory_usage::{LevelMemoryUsage, MemoryUsage},
     memtable::Memtable,
+    merge_operator::MergeOperator,
+    pending_work::PendingWork,
+    quarantine::QuarantineObserver,
     r#abstract::AbstractTree,
-    segment::{meta::CompressionType, Segment},
+    range_tombstone::RangeTombstone,
+    rate_limiter::RateLimiter,
+    read_overlay::ReadOverlay,
+    scan_cursor::{ScanCursor, ScanPage},
+    scrub::ScrubReport,
+    segment::{
+        access_stats::SegmentAccessStats,
+        dump::{DataBlockInfo, DumpItem, SegmentDump},
+        meta::{CompressionType, Temperature},
+        standalone::{SegmentFileReader, SegmentFileWriter},
+        verify::{SegmentVerifyReport, VerifyReport},
+        writer::BlockSizePolicy,
+        Segment,
+    },
     seqno::SequenceNumberCounter,
+    slow_operation::{SlowOperation, SlowOperationKind, SlowOperationObserver},
     snapshot::Snapshot,
+    statistics::{Histogram, HistogramSnapshot, Statistics, StatisticsSnapshot},
+    time::{Clock, ManualClock, SystemClock},
+    transform::BlockTransform,
     tree::Tree,
     value::{SeqNo, UserKey, UserValue, ValueType},
     version::Version,
+    write_buffer_manager::WriteBufferManager,
 };
 
 pub use any_tree::AnyTree;
@@ -219,6 +297,12 @@ pub use blob_tree::BlobTree;
 
 pub use value_log::{BlobCache, Slice};
 
+/// Fault injection for crash-consistency tests
+#[cfg(feature = "failpoints")]
+pub mod fail {
+    pub use crate::failpoint::{clear, configure, points, remove, FailAction};
+}
+
 /// Blob garbage collection utilities
 pub mod gc {
     pub use value_log::{
@@ -6,16 +6,23 @@ use crate::{
     coding::{Decode, DecodeError, Encode, EncodeError},
     file::MAGIC_BYTES,
     segment::meta::TableType,
-    TreeType, Version,
+    SeqNo, TreeType, Version,
 };
-use byteorder::{ReadBytesExt, WriteBytesExt};
-use std::io::Write;
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use std::{collections::BTreeMap, io::Write};
+
+/// Named snapshots, mapping each name to its seqno
+pub type NamedSnapshots = BTreeMap<String, SeqNo>;
 
 pub struct Manifest {
     pub(crate) version: Version,
     pub(crate) tree_type: TreeType,
     pub(crate) table_type: TableType,
     pub(crate) level_count: u8,
+
+    /// Named snapshots, which are appended to the manifest, so older
+    /// versions can still read it
+    pub(crate) named_snapshots: NamedSnapshots,
 }
 
 impl Encode for Manifest {
@@ -24,6 +31,22 @@ impl Encode for Manifest {
         writer.write_u8(self.tree_type.into())?;
         writer.write_u8(self.table_type.into())?;
         writer.write_u8(self.level_count)?;
+
+        // NOTE: Manifests without named snapshots are written as before
+        if !self.named_snapshots.is_empty() {
+            // NOTE: Truncation is okay, there are not 4 billion snapshots
+            #[allow(clippy::cast_possible_truncation)]
+            writer.write_u32::<BigEndian>(self.named_snapshots.len() as u32)?;
+
+            for (name, seqno) in &self.named_snapshots {
+                // NOTE: Name length is checked when creating the snapshot
+                #[allow(clippy::cast_possible_truncation)]
+                writer.write_u16::<BigEndian>(name.len() as u16)?;
+                writer.write_all(name.as_bytes())?;
+                writer.write_u64::<BigEndian>(*seqno)?;
+            }
+        }
+
         Ok(())
     }
 }
@@ -45,7 +68,29 @@ impl Decode for Manifest {
         let table_type = reader.read_u8()?;
         let level_count = reader.read_u8()?;
 
+        let mut named_snapshots = NamedSnapshots::new();
+
+        // NOTE: Older manifests end here
+        let snapshot_count = match reader.read_u32::<BigEndian>() {
+            Ok(count) => count,
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => 0,
+            Err(e) => return Err(e.into()),
+        };
+
+        for _ in 0..snapshot_count {
+            let name_len = reader.read_u16::<BigEndian>()?;
+
+            let mut name = vec![0; name_len.into()];
+            reader.read_exact(&mut name)?;
+            let name = String::from_utf8(name).map_err(|e| e.utf8_error())?;
+
+            let seqno = reader.read_u64::<BigEndian>()?;
+
+            named_snapshots.insert(name, seqno);
+        }
+
         Ok(Self {
+            named_snapshots,
             version,
             level_count,
             tree_type: tree_type
@@ -0,0 +1,155 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::{AbstractTree, SeqNo};
+
+/// Memory used by a tree, as tracked by a [`MemoryBudget`]
+///
+/// Caches are counted as a whole, so if they are shared between
+/// trees, their size is included in every tree's usage.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct TrackedMemory {
+    /// Approximate size of the active and sealed memtables
+    pub memtables: u64,
+
+    /// Bytes held by the block cache
+    pub block_cache: u64,
+
+    /// Bytes held by the blob cache
+    pub blob_cache: u64,
+
+    /// Heap memory used by bloom filters
+    pub bloom_filters: u64,
+
+    /// Heap memory used by block indexes
+    pub block_indexes: u64,
+}
+
+impl TrackedMemory {
+    /// Returns the total amount of tracked bytes.
+    #[must_use]
+    pub fn total(&self) -> u64 {
+        self.memtables
+            + self.block_cache
+            + self.blob_cache
+            + self.bloom_filters
+            + self.block_indexes
+    }
+}
+
+/// Keeps the memory used by a tree below a single limit
+///
+/// The block cache gets whatever memory is left after memtables, the blob cache,
+/// bloom filters and block indexes are accounted for, up to its configured capacity.
+/// If that is not enough to get below the limit, the active memtable is flushed.
+///
+/// The budget is not enforced on its own, [`MemoryBudget::enforce`] needs to be called
+/// periodically, e.g. after a batch of writes.
+///
+/// # Examples
+///
+/// ```
+/// # let folder = tempfile::tempdir()?;
+/// use lsm_tree::{AbstractTree, Config, MemoryBudget};
+///
+/// let tree = Config::new(folder).open()?;
+/// tree.insert("a", "abc", 0);
+///
+/// // This tree may use 64 MiB
+/// let budget = MemoryBudget::new(64 * 1_024 * 1_024);
+///
+/// let usage = budget.enforce(&tree, 0)?;
+/// assert!(usage.total() <= budget.limit());
+/// #
+/// # Ok::<(), lsm_tree::Error>(())
+/// ```
+pub struct MemoryBudget {
+    limit: u64,
+}
+
+impl MemoryBudget {
+    /// Creates a budget of `limit` bytes.
+    #[must_use]
+    pub fn new(limit: u64) -> Self {
+        Self { limit }
+    }
+
+    /// Returns the budget in bytes.
+    #[must_use]
+    pub fn limit(&self) -> u64 {
+        self.limit
+    }
+
+    /// Returns the memory currently used by the tree.
+    #[must_use]
+    pub fn usage<T: AbstractTree>(&self, tree: &T) -> TrackedMemory {
+        let config = tree.tree_config();
+        let segments = tree.memory_usage();
+
+        TrackedMemory {
+            memtables: u64::from(tree.active_memtable_size()) + tree.sealed_memtables_size(),
+            block_cache: config.block_cache.size(),
+            blob_cache: config.blob_cache.size(),
+            bloom_filters: segments.bloom_filter_size() as u64,
+            block_indexes: segments.block_index_size() as u64,
+        }
+    }
+
+    /// Brings the tree's memory usage below the budget, if possible.
+    ///
+    /// Shrinks the block cache, and flushes the active memtable if that is not enough.
+    /// If the tree uses less memory than the budget, the block cache is allowed to grow again.
+    ///
+    /// Sealed memtables are not flushed, because they are already queued up for flushing.
+    ///
+    /// Returns the memory usage after enforcing the budget.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs during the flush.
+    pub fn enforce<T: AbstractTree>(
+        &self,
+        tree: &T,
+        seqno_threshold: SeqNo,
+    ) -> crate::Result<TrackedMemory> {
+        let mut usage = self.usage(tree);
+
+        // NOTE: Memory that cannot be reclaimed by shrinking the block cache
+        let mut pinned = usage.total() - usage.block_cache;
+
+        if pinned > self.limit && tree.active_memtable_size() > 0 {
+            log::debug!(
+                "Memory budget of {}B exceeded ({}B without block cache), flushing active memtable",
+                self.limit,
+                pinned,
+            );
+
+            if let Some((segment_id, memtable)) = tree.rotate_memtable() {
+                if let Some(segment) =
+                    tree.flush_memtable(segment_id, &memtable, seqno_threshold)?
+                {
+                    tree.register_segments(&[segment])?;
+                }
+            }
+
+            usage = self.usage(tree);
+            pinned = usage.total() - usage.block_cache;
+        }
+
+        let block_cache = &tree.tree_config().block_cache;
+        block_cache.set_limit(self.limit.saturating_sub(pinned));
+        usage.block_cache = block_cache.size();
+
+        if usage.total() > self.limit {
+            log::warn!(
+                "Memory budget of {}B cannot be met, tree uses {}B",
+                self.limit,
+                usage.total(),
+            );
+        }
+
+        Ok(usage)
+    }
+}
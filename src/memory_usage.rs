@@ -0,0 +1,64 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::{level_manifest::LevelManifest, Segment};
+
+/// Memory retained by the disk segments of a single level
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct LevelMemoryUsage {
+    /// Amount of segments in the level
+    pub segment_count: usize,
+
+    /// Heap memory used by block indexes
+    pub block_index_size: usize,
+
+    /// Heap memory used by bloom filters
+    pub bloom_filter_size: usize,
+}
+
+/// Memory retained by the disk segments of a tree, per level
+///
+/// Does not include memtables or the block cache, which is shared
+/// between trees and can be queried through [`crate::BlockCache::size`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct MemoryUsage {
+    /// Memory usage of each level, starting at L0
+    pub levels: Vec<LevelMemoryUsage>,
+}
+
+impl MemoryUsage {
+    pub(crate) fn from_levels(manifest: &LevelManifest) -> Self {
+        let levels = manifest
+            .levels
+            .iter()
+            .map(|level| LevelMemoryUsage {
+                segment_count: level.len(),
+                block_index_size: level.iter().map(Segment::block_index_size).sum(),
+                bloom_filter_size: level.iter().map(Segment::bloom_filter_size).sum(),
+            })
+            .collect();
+
+        Self { levels }
+    }
+
+    /// Returns the heap memory used by all block indexes of the tree.
+    #[must_use]
+    pub fn block_index_size(&self) -> usize {
+        self.levels.iter().map(|x| x.block_index_size).sum()
+    }
+
+    /// Returns the heap memory used by all bloom filters of the tree.
+    #[must_use]
+    pub fn bloom_filter_size(&self) -> usize {
+        self.levels.iter().map(|x| x.bloom_filter_size).sum()
+    }
+
+    /// Returns the heap memory used by block indexes and bloom filters.
+    #[must_use]
+    pub fn total(&self) -> usize {
+        self.block_index_size() + self.bloom_filter_size()
+    }
+}
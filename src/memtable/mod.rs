@@ -3,21 +3,24 @@
 // (found in the LICENSE-* files in the repository)
 
 use crate::key::InternalKey;
+use crate::range_tombstone::RangeTombstone;
 use crate::segment::block::ItemSize;
 use crate::value::{InternalValue, SeqNo, UserValue, ValueType};
-use crossbeam_skiplist::SkipMap;
+use crossbeam_skiplist::{SkipMap, SkipSet};
 use std::ops::RangeBounds;
 use std::sync::atomic::{AtomicU32, AtomicU64};
 
 /// The memtable serves as an intermediary, ephemeral, sorted storage for new items
 ///
 /// When the Memtable exceeds some size, it should be flushed to a disk segment.
-#[derive(Default)]
 pub struct Memtable {
     /// The actual content, stored in a lock-free skiplist.
     #[doc(hidden)]
     pub items: SkipMap<InternalKey, UserValue>,
 
+    /// Range tombstones, which are written into the segment's range tombstone block
+    pub(crate) range_tombstones: SkipSet<RangeTombstone>,
+
     /// Approximate active memtable size.
     ///
     /// If this grows too large, a flush is triggered.
@@ -27,13 +30,33 @@ pub struct Memtable {
     ///
     /// This is used so that `get_highest_seqno` has O(1) complexity.
     pub(crate) highest_seqno: AtomicU64,
+
+    /// Lowest encountered sequence number.
+    ///
+    /// This is used to skip memtables that only contain items
+    /// that are too new for a snapshot.
+    pub(crate) lowest_seqno: AtomicU64,
+}
+
+impl Default for Memtable {
+    fn default() -> Self {
+        Self {
+            items: SkipMap::default(),
+            range_tombstones: SkipSet::default(),
+            approximate_size: AtomicU32::default(),
+            highest_seqno: AtomicU64::default(),
+            lowest_seqno: AtomicU64::new(SeqNo::MAX),
+        }
+    }
 }
 
 impl Memtable {
     /// Clears the memtable.
     pub fn clear(&mut self) {
         self.items.clear();
+        self.range_tombstones.clear();
         self.highest_seqno = AtomicU64::new(0);
+        self.lowest_seqno = AtomicU64::new(SeqNo::MAX);
         self.approximate_size
             .store(0, std::sync::atomic::Ordering::Release);
     }
@@ -62,8 +85,10 @@ impl Memtable {
     /// The item with the highest seqno will be returned, if `seqno` is None.
     #[doc(hidden)]
     pub fn get<K: AsRef<[u8]>>(&self, key: K, seqno: Option<SeqNo>) -> Option<InternalValue> {
-        if seqno == Some(0) {
-            return None;
+        if let Some(seqno) = seqno {
+            if !self.has_visible_items(seqno) {
+                return None;
+            }
         }
 
         let key = key.as_ref();
@@ -118,7 +143,57 @@ impl Memtable {
     /// Returns `true` if the memtable is empty.
     #[must_use]
     pub fn is_empty(&self) -> bool {
-        self.items.is_empty()
+        self.items.is_empty() && self.range_tombstones.is_empty()
+    }
+
+    /// Returns the range tombstones of the memtable, ordered by their start key.
+    pub fn range_tombstones(&self) -> impl Iterator<Item = RangeTombstone> + '_ {
+        self.range_tombstones
+            .iter()
+            .map(|entry| entry.value().clone())
+    }
+
+    /// Returns `true` if the memtable contains range tombstones.
+    pub(crate) fn has_range_tombstones(&self) -> bool {
+        !self.range_tombstones.is_empty()
+    }
+
+    /// Returns `true` if a range tombstone, which is visible to a snapshot
+    /// with the given seqno, deletes the given version of a key.
+    pub(crate) fn is_range_deleted(
+        &self,
+        key: &[u8],
+        item_seqno: SeqNo,
+        seqno: Option<SeqNo>,
+    ) -> bool {
+        self.range_tombstones
+            .iter()
+            .take_while(|entry| &*entry.value().start <= key)
+            .any(|entry| entry.value().is_visible(seqno) && entry.value().deletes(key, item_seqno))
+    }
+
+    /// Inserts a range tombstone into the memtable
+    #[doc(hidden)]
+    pub fn insert_range_tombstone(&self, tombstone: RangeTombstone) -> (u32, u32) {
+        // NOTE: We know keys are limited to 16-bit length
+        #[allow(clippy::cast_possible_truncation)]
+        let item_size =
+            (tombstone.start.len() + tombstone.end.len() + std::mem::size_of::<SeqNo>()) as u32;
+
+        let size_before = self
+            .approximate_size
+            .fetch_add(item_size, std::sync::atomic::Ordering::AcqRel);
+
+        self.lowest_seqno
+            .fetch_min(tombstone.seqno, std::sync::atomic::Ordering::AcqRel);
+
+        let seqno = tombstone.seqno;
+        self.range_tombstones.insert(tombstone);
+
+        self.highest_seqno
+            .fetch_max(seqno, std::sync::atomic::Ordering::AcqRel);
+
+        (item_size, size_before + item_size)
     }
 
     /// Inserts an item into the memtable
@@ -132,6 +207,11 @@ impl Memtable {
             .approximate_size
             .fetch_add(item_size, std::sync::atomic::Ordering::AcqRel);
 
+        // NOTE: Lower the seqno before inserting, so readers
+        // never skip a memtable that contains a visible item
+        self.lowest_seqno
+            .fetch_min(item.key.seqno, std::sync::atomic::Ordering::AcqRel);
+
         let key = InternalKey::new(item.key.user_key, item.key.seqno, item.key.value_type);
         self.items.insert(key, item.value);
 
@@ -141,6 +221,12 @@ impl Memtable {
         (item_size, size_before + item_size)
     }
 
+    /// Returns `true` if the memtable may contain items that are visible
+    /// to a snapshot with the given seqno.
+    pub(crate) fn has_visible_items(&self, seqno: SeqNo) -> bool {
+        self.lowest_seqno.load(std::sync::atomic::Ordering::Acquire) < seqno
+    }
+
     /// Returns the highest sequence number in the memtable.
     pub fn get_highest_seqno(&self) -> Option<SeqNo> {
         if self.is_empty() {
@@ -353,4 +439,49 @@ mod tests {
             memtable.get("abc", Some(50))
         );
     }
+
+    #[test]
+    fn memtable_range_tombstone() {
+        let memtable = Memtable::default();
+
+        memtable.insert_range_tombstone(RangeTombstone::new("b".into(), "d".into(), 5));
+        assert!(!memtable.is_empty());
+        assert_eq!(Some(5), memtable.get_highest_seqno());
+
+        assert!(!memtable.is_range_deleted(b"a", 0, None));
+        assert!(memtable.is_range_deleted(b"b", 0, None));
+        assert!(memtable.is_range_deleted(b"c", 4, None));
+        assert!(!memtable.is_range_deleted(b"c", 5, None));
+        assert!(!memtable.is_range_deleted(b"d", 0, None));
+
+        // NOTE: The tombstone is not visible to the snapshot
+        assert!(!memtable.is_range_deleted(b"c", 0, Some(5)));
+        assert!(memtable.is_range_deleted(b"c", 0, Some(6)));
+    }
+
+    #[test]
+    fn memtable_skip_invisible() {
+        let memtable = Memtable::default();
+        assert!(!memtable.has_visible_items(SeqNo::MAX));
+
+        memtable.insert(InternalValue::from_components(
+            "a",
+            "new",
+            10,
+            ValueType::Value,
+        ));
+        memtable.insert(InternalValue::from_components(
+            "b",
+            "new",
+            12,
+            ValueType::Value,
+        ));
+
+        assert!(!memtable.has_visible_items(10));
+        assert!(memtable.has_visible_items(11));
+
+        assert_eq!(None, memtable.get("a", Some(10)));
+        assert!(memtable.get("a", Some(11)).is_some());
+        assert_eq!(None, memtable.get("b", Some(11)));
+    }
 }
@@ -31,6 +31,10 @@ impl PartialOrd for HeapItem {
 }
 
 /// Merges multiple KV iterators
+///
+/// The head of every source is kept in an interval heap ordered by
+/// (user key, reverse seqno), so yielding an item costs O(log n) for n sources
+/// in both directions.
 pub struct Merger<I> {
     iterators: Vec<I>,
     heap: Heap<HeapItem>,
@@ -117,3 +121,53 @@ impl<I: DoubleEndedIterator<Item = IterItem>> DoubleEndedIterator for Merger<I>
         Some(Ok(max_item.1))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ValueType;
+    use test_log::test;
+
+    fn source(seqno: crate::SeqNo) -> std::vec::IntoIter<IterItem> {
+        (0..10u64)
+            .map(|key| {
+                Ok(InternalValue::from_components(
+                    key.to_be_bytes(),
+                    vec![],
+                    seqno,
+                    ValueType::Value,
+                ))
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    fn decode(item: IterItem) -> crate::Result<(u64, crate::SeqNo)> {
+        let item = item?;
+        let key = (*item.key.user_key).try_into().expect("should be u64");
+        Ok((u64::from_be_bytes(key), item.key.seqno))
+    }
+
+    #[test]
+    fn merge_many_sources() -> crate::Result<()> {
+        let source_count = 30;
+
+        // NOTE: Every source holds a version of every key, with a seqno equal to its index
+        let expected = (0..10u64)
+            .flat_map(|key| (0..source_count).rev().map(move |seqno| (key, seqno)))
+            .collect::<Vec<_>>();
+
+        let items = Merger::new((0..source_count).map(source).collect())
+            .map(decode)
+            .collect::<crate::Result<Vec<_>>>()?;
+        assert_eq!(expected, items);
+
+        let items = Merger::new((0..source_count).map(source).collect())
+            .rev()
+            .map(decode)
+            .collect::<crate::Result<Vec<_>>>()?;
+        assert_eq!(expected.into_iter().rev().collect::<Vec<_>>(), items);
+
+        Ok(())
+    }
+}
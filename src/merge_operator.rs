@@ -0,0 +1,135 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::{InternalValue, UserValue, ValueType};
+
+/// Combines merge operands into a value, see [`crate::Config::merge_operator`]
+///
+/// Merge operands are written using [`crate::Tree::merge`], and folded into
+/// the key's value lazily, when the key is read or compacted.
+///
+/// The operator needs to be deterministic, because the same operands
+/// may be folded multiple times (e.g. by reads and by a later compaction).
+/// Also, folding the operands in steps needs to give the same value as
+/// folding all of them at once, because compactions may fold only the
+/// older operands, if snapshots still read them.
+pub trait MergeOperator: Send + Sync {
+    /// Folds the operands into the base value.
+    ///
+    /// `base` is `None` if the key does not exist (or was deleted) before the
+    /// oldest operand. The operands are ordered from oldest to newest.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned to the reader of the key, or fails the compaction.
+    fn merge(
+        &self,
+        key: &[u8],
+        base: Option<&[u8]>,
+        operands: &[&[u8]],
+    ) -> crate::Result<UserValue>;
+}
+
+/// Folds the merge operands at the start of the versions into the newest version.
+///
+/// The versions are ordered from newest to oldest and need to belong to the same key;
+/// folding stops at the first version that is not a merge operand.
+///
+/// The result is a value with the seqno of the newest version.
+/// If the base value expires, the result does not inherit its expiry.
+pub fn fold_versions(
+    operator: &dyn MergeOperator,
+    versions: &[InternalValue],
+) -> crate::Result<InternalValue> {
+    // NOTE: Callers only fold versions if the newest version is a merge operand
+    #[allow(clippy::expect_used)]
+    let head = versions.first().expect("should have versions");
+
+    let mut operands = vec![];
+    let mut base = None;
+
+    for version in versions {
+        match version.key.value_type {
+            ValueType::Merge => operands.push(&*version.value),
+            ValueType::Value => {
+                base = Some(&*version.value);
+                break;
+            }
+            ValueType::ValueWithExpiry => {
+                base = Some(crate::ttl::user_value(&version.value));
+                break;
+            }
+            ValueType::Tombstone | ValueType::WeakTombstone => break,
+        }
+    }
+
+    operands.reverse();
+
+    let value = operator.merge(&head.key.user_key, base, &operands)?;
+
+    Ok(InternalValue::from_components(
+        head.key.user_key.clone(),
+        value,
+        head.key.seqno,
+        ValueType::Value,
+    ))
+}
+
+/// Appends the operands to the base value
+#[cfg(test)]
+pub struct Concat;
+
+#[cfg(test)]
+impl MergeOperator for Concat {
+    fn merge(&self, _: &[u8], base: Option<&[u8]>, operands: &[&[u8]]) -> crate::Result<UserValue> {
+        let mut value = base.unwrap_or_default().to_vec();
+
+        for operand in operands {
+            value.extend_from_slice(operand);
+        }
+
+        Ok(value.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn merge_operator_fold_versions() -> crate::Result<()> {
+        let versions = [
+            InternalValue::from_components("a", "c", 3, ValueType::Merge),
+            InternalValue::from_components("a", "b", 2, ValueType::Merge),
+            InternalValue::from_components("a", "a", 1, ValueType::Value),
+            InternalValue::from_components("a", "old", 0, ValueType::Value),
+        ];
+
+        let folded = fold_versions(&Concat, &versions)?;
+        assert_eq!(
+            InternalValue::from_components("a", "abc", 3, ValueType::Value),
+            folded
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn merge_operator_fold_versions_tombstone() -> crate::Result<()> {
+        let versions = [
+            InternalValue::from_components("a", "c", 3, ValueType::Merge),
+            InternalValue::new_tombstone("a", 2),
+            InternalValue::from_components("a", "a", 1, ValueType::Value),
+        ];
+
+        let folded = fold_versions(&Concat, &versions)?;
+        assert_eq!(
+            InternalValue::from_components("a", "c", 3, ValueType::Value),
+            folded
+        );
+
+        Ok(())
+    }
+}
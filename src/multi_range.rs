@@ -0,0 +1,127 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::UserKey;
+use std::{cmp::Ordering, ops::Bound};
+
+type OwnedBounds = (Bound<UserKey>, Bound<UserKey>);
+
+/// Maps a start bound to a sort key, `Unbounded` being the lowest.
+fn start_key(bound: &Bound<UserKey>) -> Option<(&UserKey, bool)> {
+    match bound {
+        Bound::Included(key) => Some((key, false)),
+        Bound::Excluded(key) => Some((key, true)),
+        Bound::Unbounded => None,
+    }
+}
+
+/// Orders end bounds, `Unbounded` being the highest.
+fn cmp_end(a: &Bound<UserKey>, b: &Bound<UserKey>) -> Ordering {
+    let end_key = |bound| match bound {
+        Bound::Included(key) => Some((key, true)),
+        Bound::Excluded(key) => Some((key, false)),
+        Bound::Unbounded => None,
+    };
+
+    match (end_key(a.as_ref()), end_key(b.as_ref())) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        (a, b) => b.is_some().cmp(&a.is_some()),
+    }
+}
+
+/// Returns `true` if range `a` ends before range `b` starts.
+fn ends_before(a: &OwnedBounds, b: &OwnedBounds) -> bool {
+    match (&a.1, &b.0) {
+        (Bound::Unbounded, _) | (_, Bound::Unbounded) => false,
+        (Bound::Included(end), Bound::Included(start)) => end < start,
+        (Bound::Included(end) | Bound::Excluded(end), Bound::Excluded(start))
+        | (Bound::Excluded(end), Bound::Included(start)) => end <= start,
+    }
+}
+
+/// Sorts the ranges by their start, and merges ranges that overlap,
+/// so every key is contained in at most one of the returned ranges.
+pub fn sort_and_merge(mut ranges: Vec<OwnedBounds>) -> Vec<OwnedBounds> {
+    ranges.sort_by(|a, b| start_key(&a.0).cmp(&start_key(&b.0)));
+
+    let mut merged: Vec<OwnedBounds> = Vec::with_capacity(ranges.len());
+
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if !ends_before(last, &range) => {
+                if cmp_end(&range.1, &last.1) == Ordering::Greater {
+                    last.1 = range.1;
+                }
+            }
+            _ => merged.push(range),
+        }
+    }
+
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    fn range(start: Bound<&str>, end: Bound<&str>) -> OwnedBounds {
+        // TODO: Bound::map: 1.77
+        let into_key = |bound: Bound<&str>| match bound {
+            Bound::Included(key) => Bound::Included(UserKey::from(key)),
+            Bound::Excluded(key) => Bound::Excluded(UserKey::from(key)),
+            Bound::Unbounded => Bound::Unbounded,
+        };
+
+        (into_key(start), into_key(end))
+    }
+
+    #[test]
+    fn multi_range_sorted_disjoint() {
+        let ranges = vec![
+            range(Bound::Included("b"), Bound::Excluded("d")),
+            range(Bound::Included("d"), Bound::Included("f")),
+            range(Bound::Excluded("f"), Bound::Included("h")),
+        ];
+
+        assert_eq!(ranges.clone(), sort_and_merge(ranges));
+    }
+
+    #[test]
+    fn multi_range_unsorted() {
+        let ranges = vec![
+            range(Bound::Included("h"), Bound::Included("h")),
+            range(Bound::Included("b"), Bound::Excluded("d")),
+            range(Bound::Unbounded, Bound::Excluded("a")),
+        ];
+
+        assert_eq!(
+            vec![
+                range(Bound::Unbounded, Bound::Excluded("a")),
+                range(Bound::Included("b"), Bound::Excluded("d")),
+                range(Bound::Included("h"), Bound::Included("h")),
+            ],
+            sort_and_merge(ranges),
+        );
+    }
+
+    #[test]
+    fn multi_range_overlapping() {
+        let ranges = vec![
+            range(Bound::Included("c"), Bound::Included("d")),
+            range(Bound::Included("a"), Bound::Included("c")),
+            range(Bound::Included("b"), Bound::Excluded("c")),
+            range(Bound::Included("x"), Bound::Unbounded),
+            range(Bound::Excluded("y"), Bound::Included("z")),
+        ];
+
+        assert_eq!(
+            vec![
+                range(Bound::Included("a"), Bound::Included("d")),
+                range(Bound::Included("x"), Bound::Unbounded),
+            ],
+            sort_and_merge(ranges),
+        );
+    }
+}
@@ -2,8 +2,12 @@
 // This source code is licensed under both the Apache 2.0 and MIT License
 // (found in the LICENSE-* files in the repository)
 
-use crate::{InternalValue, UserKey};
+use crate::{
+    merge_operator::{fold_versions, MergeOperator},
+    InternalValue, UserKey, ValueType,
+};
 use double_ended_peekable::{DoubleEndedPeekable, DoubleEndedPeekableExt};
+use std::sync::Arc;
 
 /// Consumes a stream of KVs and emits a new stream according to MVCC and tombstone rules
 ///
@@ -11,6 +15,12 @@ use double_ended_peekable::{DoubleEndedPeekable, DoubleEndedPeekableExt};
 #[allow(clippy::module_name_repetitions)]
 pub struct MvccStream<I: DoubleEndedIterator<Item = crate::Result<InternalValue>>> {
     inner: DoubleEndedPeekable<I>,
+
+    /// Folds merge operands into the newest version of their key
+    merge_operator: Option<Arc<dyn MergeOperator>>,
+
+    /// Versions of the key that is currently being read backwards
+    versions: Vec<InternalValue>,
 }
 
 impl<I: DoubleEndedIterator<Item = crate::Result<InternalValue>>> MvccStream<I> {
@@ -18,7 +28,76 @@ impl<I: DoubleEndedIterator<Item = crate::Result<InternalValue>>> MvccStream<I>
     #[must_use]
     pub fn new(iter: I) -> Self {
         let iter = iter.double_ended_peekable();
-        Self { inner: iter }
+
+        Self {
+            inner: iter,
+            merge_operator: None,
+            versions: Vec::new(),
+        }
+    }
+
+    /// Folds merge operands using the given operator.
+    ///
+    /// Without an operator, reading a merge operand returns an error.
+    #[must_use]
+    pub fn with_merge_operator(mut self, merge_operator: Option<Arc<dyn MergeOperator>>) -> Self {
+        self.merge_operator = merge_operator;
+        self
+    }
+
+    /// Folds the versions of a key, starting with a merge operand, into a value.
+    fn fold_merge(&self, versions: &[InternalValue]) -> crate::Result<InternalValue> {
+        let Some(merge_operator) = &self.merge_operator else {
+            return Err(crate::Error::MissingMergeOperator);
+        };
+
+        fold_versions(&**merge_operator, versions)
+    }
+
+    /// Consumes the older versions of the head's key, until the base value of the merge.
+    fn collect_merge_operands(&mut self, head: InternalValue) -> crate::Result<Vec<InternalValue>> {
+        let key = head.key.user_key.clone();
+        let mut versions = vec![head];
+
+        loop {
+            let Some(next) = self.inner.peek() else {
+                return Ok(versions);
+            };
+
+            let Ok(next) = next else {
+                // NOTE: We just asserted, the peeked value is an error
+                #[allow(clippy::expect_used)]
+                return Err(self
+                    .inner
+                    .next()
+                    .expect("should exist")
+                    .expect_err("should be error"));
+            };
+
+            if next.key.user_key != key {
+                return Ok(versions);
+            }
+
+            let is_base = next.key.value_type != ValueType::Merge;
+
+            // NOTE: We know the next value is not empty, because we just peeked it
+            #[allow(clippy::expect_used)]
+            versions.push(self.inner.next().expect("should not be empty")?);
+
+            if is_base {
+                return Ok(versions);
+            }
+        }
+    }
+
+    /// Folds the newest version of a key that was read backwards, if it is a merge operand.
+    fn resolve_back(&mut self, tail: InternalValue) -> crate::Result<InternalValue> {
+        if tail.key.value_type != ValueType::Merge {
+            return Ok(tail);
+        }
+
+        self.versions.reverse();
+        self.fold_merge(&self.versions)
     }
 
     fn drain_key_min(&mut self, key: &UserKey) -> crate::Result<()> {
@@ -53,7 +132,12 @@ impl<I: DoubleEndedIterator<Item = crate::Result<InternalValue>>> Iterator for M
     type Item = crate::Result<InternalValue>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let head = fail_iter!(self.inner.next()?);
+        let mut head = fail_iter!(self.inner.next()?);
+
+        if head.key.value_type == ValueType::Merge {
+            let versions = fail_iter!(self.collect_merge_operands(head));
+            head = fail_iter!(self.fold_merge(&versions));
+        }
 
         // As long as items are the same key, ignore them
         fail_iter!(self.drain_key_min(&head.key.user_key));
@@ -66,9 +150,17 @@ impl<I: DoubleEndedIterator<Item = crate::Result<InternalValue>>> DoubleEndedIte
     for MvccStream<I>
 {
     fn next_back(&mut self) -> Option<Self::Item> {
+        self.versions.clear();
+
         loop {
             let tail = fail_iter!(self.inner.next_back()?);
 
+            // NOTE: The newest version may be a merge operand,
+            // so keep the older versions around for folding
+            if self.merge_operator.is_some() {
+                self.versions.push(tail.clone());
+            }
+
             let prev = match self.inner.peek_back() {
                 Some(Ok(prev)) => prev,
                 Some(Err(_)) => {
@@ -81,12 +173,12 @@ impl<I: DoubleEndedIterator<Item = crate::Result<InternalValue>>> DoubleEndedIte
                         .expect_err("should be error")));
                 }
                 None => {
-                    return Some(Ok(tail));
+                    return Some(self.resolve_back(tail));
                 }
             };
 
             if prev.key.user_key < tail.key.user_key {
-                return Some(Ok(tail));
+                return Some(self.resolve_back(tail));
             }
         }
     }
@@ -111,6 +203,7 @@ mod tests {
                   "V" => ValueType::Value,
                   "T" => ValueType::Tombstone,
                   "W" => ValueType::WeakTombstone,
+                  "M" => ValueType::Merge,
                   _ => panic!("Unknown value type"),
               };
 
@@ -642,4 +735,77 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn mvcc_stream_merge() -> crate::Result<()> {
+        #[rustfmt::skip]
+        let vec = stream![
+          "a", "c", "M",
+          "a", "b", "M",
+          "a", "a", "V",
+          "a", "old", "V",
+          "b", "new", "M",
+          "b", "", "T",
+          "b", "old", "V",
+          "c", "y", "M",
+          "c", "x", "M",
+          "d", "d", "V",
+        ];
+
+        let iter = Box::new(vec.iter().cloned().map(Ok));
+
+        let mut iter = MvccStream::new(iter)
+            .with_merge_operator(Some(Arc::new(crate::merge_operator::Concat)));
+
+        assert_eq!(
+            InternalValue::from_components(*b"a", *b"abc", 999, ValueType::Value),
+            iter.next().unwrap()?,
+        );
+        assert_eq!(
+            InternalValue::from_components(*b"b", *b"new", 999, ValueType::Value),
+            iter.next().unwrap()?,
+        );
+        assert_eq!(
+            InternalValue::from_components(*b"c", *b"xy", 999, ValueType::Value),
+            iter.next().unwrap()?,
+        );
+        assert_eq!(
+            InternalValue::from_components(*b"d", *b"d", 999, ValueType::Value),
+            iter.next().unwrap()?,
+        );
+        iter_closed!(iter);
+
+        let iter = Box::new(vec.iter().cloned().map(Ok));
+        let iter = MvccStream::new(iter)
+            .with_merge_operator(Some(Arc::new(crate::merge_operator::Concat)));
+        let mut forwards = iter.flatten().collect::<Vec<_>>();
+        forwards.reverse();
+
+        let iter = Box::new(vec.iter().cloned().map(Ok));
+        let iter = MvccStream::new(iter)
+            .with_merge_operator(Some(Arc::new(crate::merge_operator::Concat)));
+        let backwards = iter.rev().flatten().collect::<Vec<_>>();
+
+        assert_eq!(forwards, backwards);
+
+        Ok(())
+    }
+
+    #[test]
+    fn mvcc_stream_merge_missing_operator() {
+        #[rustfmt::skip]
+        let vec = stream![
+          "a", "b", "M",
+          "a", "a", "V",
+        ];
+
+        let iter = Box::new(vec.iter().cloned().map(Ok));
+        let mut iter = MvccStream::new(iter);
+
+        assert!(matches!(
+            iter.next(),
+            Some(Err(crate::Error::MissingMergeOperator))
+        ));
+    }
 }
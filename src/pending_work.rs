@@ -0,0 +1,45 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::compaction::Choice;
+
+/// Snapshot of the background work a tree has not caught up with yet
+///
+/// Use it to decide whether the flush & compaction workers
+/// are keeping up with the write rate.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct PendingWork {
+    /// What the compaction strategy would schedule next
+    pub next_compaction: Choice,
+
+    /// Amount of segments that are currently being compacted
+    pub compacting_segment_count: usize,
+
+    /// Amount of sealed memtables waiting to be flushed
+    pub sealed_memtable_count: usize,
+
+    /// Approximate amount of bytes in sealed memtables waiting to be flushed
+    pub flush_backlog_bytes: u64,
+
+    /// Amount of segment bytes that would be deleted without compaction
+    /// (e.g. because of a FIFO size limit or TTL)
+    pub pending_drop_bytes: u64,
+}
+
+impl PendingWork {
+    /// Returns `true` if there is neither a flush nor compaction backlog.
+    #[must_use]
+    pub fn is_idle(&self) -> bool {
+        self.sealed_memtable_count == 0
+            && self.compacting_segment_count == 0
+            && self.next_compaction == Choice::DoNothing
+    }
+
+    /// Returns `true` if the compaction strategy wants to delete segments.
+    #[must_use]
+    pub fn has_pending_drops(&self) -> bool {
+        matches!(self.next_compaction, Choice::Drop(_))
+    }
+}
@@ -0,0 +1,60 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use std::{
+    sync::{Condvar, Mutex},
+    time::{Duration, Instant},
+};
+
+/// Wakes up threads that wait for writes to be persisted in segments
+#[derive(Default)]
+pub struct PersistWatch {
+    lock: Mutex<()>,
+    cond: Condvar,
+}
+
+impl PersistWatch {
+    /// Wakes up all waiting threads, so they re-check their condition.
+    ///
+    /// Needs to be called after the condition changed.
+    pub fn notify(&self) {
+        // NOTE: Acquire the lock, so a waiter cannot miss the notification
+        // between checking its condition and going to sleep
+        let _lock = self.lock.lock().expect("lock is poisoned");
+        self.cond.notify_all();
+    }
+
+    /// Blocks until `is_persisted` returns `true`, or the timeout expires.
+    ///
+    /// Returns `false` if the timeout expired.
+    pub fn wait_until(
+        &self,
+        mut is_persisted: impl FnMut() -> bool,
+        timeout: Option<Duration>,
+    ) -> bool {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+
+        let mut lock = self.lock.lock().expect("lock is poisoned");
+
+        loop {
+            if is_persisted() {
+                return true;
+            }
+
+            lock = match deadline {
+                Some(deadline) => {
+                    let Some(remaining) = deadline.checked_duration_since(Instant::now()) else {
+                        return false;
+                    };
+
+                    self.cond
+                        .wait_timeout(lock, remaining)
+                        .expect("lock is poisoned")
+                        .0
+                }
+                None => self.cond.wait(lock).expect("lock is poisoned"),
+            };
+        }
+    }
+}
@@ -0,0 +1,295 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::{statistics::Histogram, AbstractTree, Statistics, Tree, TreeId};
+use std::fmt::Write;
+
+const PREFIX: &str = "lsm_tree";
+
+fn write_metric(
+    out: &mut String,
+    name: &str,
+    kind: &str,
+    help: &str,
+    tree_id: TreeId,
+    value: impl std::fmt::Display,
+) {
+    // NOTE: Writing into a String cannot fail
+    let _ = writeln!(out, "# HELP {PREFIX}_{name} {help}");
+    let _ = writeln!(out, "# TYPE {PREFIX}_{name} {kind}");
+    let _ = writeln!(out, "{PREFIX}_{name}{{tree_id=\"{tree_id}\"}} {value}");
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn write_histogram(out: &mut String, name: &str, help: &str, tree_id: TreeId, hist: &Histogram) {
+    let _ = writeln!(out, "# HELP {PREFIX}_{name} {help}");
+    let _ = writeln!(out, "# TYPE {PREFIX}_{name} histogram");
+
+    let mut cumulative = 0;
+
+    // NOTE: Every bucket boundary is emitted, even if empty, because Prometheus
+    // expects the same set of buckets in every scrape
+    //
+    // The last bucket is unbounded, so it is emitted as +Inf below
+    for (upper_bound_us, count) in hist.buckets().filter(|(bound, _)| *bound < u64::MAX) {
+        cumulative += count;
+
+        let le = upper_bound_us as f64 / 1_000_000.0;
+        let _ = writeln!(
+            out,
+            "{PREFIX}_{name}_bucket{{tree_id=\"{tree_id}\",le=\"{le}\"}} {cumulative}"
+        );
+    }
+
+    let count = hist.count();
+    let _ = writeln!(
+        out,
+        "{PREFIX}_{name}_bucket{{tree_id=\"{tree_id}\",le=\"+Inf\"}} {count}"
+    );
+    let _ = writeln!(
+        out,
+        "{PREFIX}_{name}_sum{{tree_id=\"{tree_id}\"}} {}",
+        hist.sum().as_secs_f64()
+    );
+    let _ = writeln!(
+        out,
+        "{PREFIX}_{name}_count{{tree_id=\"{tree_id}\"}} {count}"
+    );
+}
+
+impl Statistics {
+    /// Renders the statistics in the Prometheus text exposition format,
+    /// labelling every sample with the given tree ID.
+    #[must_use]
+    pub fn to_prometheus(&self, tree_id: TreeId) -> String {
+        let mut out = String::new();
+
+        for (name, help, value) in [
+            ("flushes_total", "Memtable flushes", self.flush_count()),
+            (
+                "flush_read_bytes_total",
+                "Memtable bytes consumed by flushes",
+                self.flush_bytes_read(),
+            ),
+            (
+                "flush_written_bytes_total",
+                "Segment bytes written by flushes",
+                self.flush_bytes_written(),
+            ),
+            (
+                "compactions_total",
+                "Compactions that merged segments",
+                self.compaction_count(),
+            ),
+            (
+                "compaction_read_bytes_total",
+                "Segment bytes read by compactions",
+                self.compaction_bytes_read(),
+            ),
+            (
+                "compaction_written_bytes_total",
+                "Segment bytes written by compactions",
+                self.compaction_bytes_written(),
+            ),
+            (
+                "block_cache_hits_total",
+                "Data block reads served by the block cache",
+                self.block_cache_hits(),
+            ),
+            (
+                "block_cache_misses_total",
+                "Data block reads served from disk",
+                self.block_cache_misses(),
+            ),
+            (
+                "block_read_bytes_total",
+                "Data block bytes read from disk",
+                self.block_bytes_read(),
+            ),
+            (
+                "bloom_checked_total",
+                "Bloom filter queries",
+                self.bloom_checked(),
+            ),
+            (
+                "bloom_useful_total",
+                "Bloom filter queries that avoided a segment read",
+                self.bloom_useful(),
+            ),
+            (
+                "segments_probed_total",
+                "Segments probed by point reads",
+                self.segments_probed(),
+            ),
+        ] {
+            write_metric(&mut out, name, "counter", help, tree_id, value);
+        }
+
+        write_metric(
+            &mut out,
+            "write_amplification",
+            "gauge",
+            "Estimated write amplification",
+            tree_id,
+            self.write_amp(),
+        );
+        write_metric(
+            &mut out,
+            "read_amplification",
+            "gauge",
+            "Estimated read amplification",
+            tree_id,
+            self.read_amp(),
+        );
+
+        write_histogram(
+            &mut out,
+            "get_duration_seconds",
+            "Point read latency",
+            tree_id,
+            self.get_latency(),
+        );
+        write_histogram(
+            &mut out,
+            "scan_duration_seconds",
+            "Range & prefix scan latency",
+            tree_id,
+            self.scan_latency(),
+        );
+        write_histogram(
+            &mut out,
+            "stall_duration_seconds",
+            "Write stall duration",
+            tree_id,
+            self.stall_time(),
+        );
+
+        out
+    }
+}
+
+impl Tree {
+    /// Renders the tree's statistics and state gauges
+    /// in the Prometheus text exposition format.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config};
+    ///
+    /// let tree = Config::new(folder).open()?;
+    /// let metrics = tree.to_prometheus();
+    /// assert!(metrics.contains("lsm_tree_segments{"));
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    #[must_use]
+    pub fn to_prometheus(&self) -> String {
+        let mut out = self.config.statistics.to_prometheus(self.id);
+
+        write_metric(
+            &mut out,
+            "segments",
+            "gauge",
+            "Disk segments",
+            self.id,
+            self.segment_count(),
+        );
+        write_metric(
+            &mut out,
+            "first_level_segments",
+            "gauge",
+            "Disk segments in L0",
+            self.id,
+            self.first_level_segment_count(),
+        );
+        write_metric(
+            &mut out,
+            "sealed_memtables",
+            "gauge",
+            "Sealed memtables waiting to be flushed",
+            self.id,
+            self.sealed_memtable_count(),
+        );
+        write_metric(
+            &mut out,
+            "active_memtable_bytes",
+            "gauge",
+            "Approximate size of the active memtable",
+            self.id,
+            self.active_memtable_size(),
+        );
+        write_metric(
+            &mut out,
+            "disk_space_bytes",
+            "gauge",
+            "Disk space used by segments",
+            self.id,
+            self.disk_space(),
+        );
+        write_metric(
+            &mut out,
+            "space_amplification",
+            "gauge",
+            "Estimated space amplification",
+            self.id,
+            self.space_amp(),
+        );
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{statistics::Statistics, AbstractTree, Config};
+    use std::sync::Arc;
+    use test_log::test;
+
+    #[test]
+    fn prometheus_format() -> crate::Result<()> {
+        let folder = tempfile::tempdir()?;
+
+        let stats = Arc::new(Statistics::default());
+        let tree = Config::new(&folder).statistics(stats).open()?;
+        tree.insert("a", "a", 0);
+        tree.flush_active_memtable(0)?;
+        tree.get("a", None)?;
+
+        let metrics = tree.to_prometheus();
+        let tree_id = tree.id;
+
+        assert!(metrics.contains(&format!(
+            "lsm_tree_flushes_total{{tree_id=\"{tree_id}\"}} 1"
+        )));
+        assert!(metrics.contains(&format!("lsm_tree_segments{{tree_id=\"{tree_id}\"}} 1")));
+        assert!(metrics.contains(&format!(
+            "lsm_tree_get_duration_seconds_bucket{{tree_id=\"{tree_id}\",le=\"+Inf\"}} 1"
+        )));
+        assert!(metrics.contains(&format!(
+            "lsm_tree_get_duration_seconds_count{{tree_id=\"{tree_id}\"}} 1"
+        )));
+
+        // NOTE: Histograms always have the same buckets, even without samples
+        let bucket_count = |name: &str| {
+            metrics
+                .lines()
+                .filter(|line| line.starts_with(&format!("lsm_tree_{name}_bucket{{")))
+                .count()
+        };
+        assert_eq!(65, bucket_count("get_duration_seconds"));
+        assert_eq!(
+            bucket_count("get_duration_seconds"),
+            bucket_count("scan_duration_seconds")
+        );
+
+        for line in metrics.lines().filter(|line| !line.starts_with('#')) {
+            let (_, value) = line.rsplit_once(' ').expect("should have value");
+            assert!(value.parse::<f64>().is_ok(), "invalid sample: {line}");
+        }
+
+        Ok(())
+    }
+}
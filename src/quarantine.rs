@@ -0,0 +1,58 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::{
+    file::{fsync_directory, QUARANTINE_FOLDER, SEGMENTS_FOLDER},
+    Config, SegmentId,
+};
+use std::path::Path;
+
+/// Receives events about quarantined segments, see [`crate::Config::quarantine_corrupt_segments`]
+pub trait QuarantineObserver: Send + Sync {
+    /// Called after a corrupt segment was removed from the tree and moved
+    /// into the quarantine folder.
+    ///
+    /// The data of the segment is not visible anymore, so reads may return
+    /// older versions of its keys, or nothing at all.
+    fn on_segment_quarantined(&self, segment_id: SegmentId, error: &crate::Error);
+
+    /// Called when a scrub finds a corrupt segment that was not quarantined,
+    /// see [`crate::AbstractTree::scrub`].
+    #[allow(unused_variables)]
+    fn on_segment_corrupted(&self, segment_id: SegmentId, error: &crate::Error) {}
+
+    /// Called when a scrub finds a corrupt blob file.
+    ///
+    /// Blob files are never quarantined, because their blobs are referenced by the index tree.
+    #[allow(unused_variables)]
+    fn on_blob_file_corrupted(&self, blob_file_id: SegmentId, error: &crate::Error) {}
+}
+
+/// Moves a segment file into the quarantine folder of the tree
+pub fn move_segment_file(tree_path: &Path, segment_id: SegmentId) -> crate::Result<()> {
+    let quarantine_folder = tree_path.join(QUARANTINE_FOLDER);
+    std::fs::create_dir_all(&quarantine_folder)?;
+
+    let from = tree_path.join(SEGMENTS_FOLDER).join(segment_id.to_string());
+    let to = quarantine_folder.join(segment_id.to_string());
+
+    log::error!(
+        "Quarantining corrupt segment {} to {}",
+        from.display(),
+        to.display(),
+    );
+    std::fs::rename(from, to)?;
+
+    fsync_directory(&quarantine_folder)?;
+    fsync_directory(tree_path.join(SEGMENTS_FOLDER))?;
+
+    Ok(())
+}
+
+/// Notifies the configured observer (if any) about a quarantined segment
+pub fn notify(config: &Config, segment_id: SegmentId, error: &crate::Error) {
+    if let Some(observer) = &config.quarantine_observer {
+        observer.on_segment_quarantined(segment_id, error);
+    }
+}
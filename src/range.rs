@@ -4,26 +4,53 @@
 
 use crate::{
     key::InternalKey,
-    level_manifest::LevelManifest,
+    level_manifest::{level::Level, view::LevelView},
     level_reader::LevelReader,
     memtable::Memtable,
     merge::{BoxedIterator, Merger},
+    merge_operator::MergeOperator,
     multi_reader::MultiReader,
     mvcc_stream::MvccStream,
-    segment::value_block::CachePolicy,
+    range_tombstone::{RangeTombstone, RangeTombstoneIndex},
+    segment::{prefetch::ReadAhead, value_block::CachePolicy},
     tree::inner::SealedMemtables,
     value::{SeqNo, UserKey},
     InternalValue,
 };
 use guardian::ArcRwLockReadGuardian;
 use self_cell::self_cell;
-use std::{ops::Bound, sync::Arc};
+use std::{
+    ops::{Bound, RangeBounds},
+    sync::Arc,
+    time::Duration,
+};
 
 #[must_use]
 pub fn seqno_filter(item_seqno: SeqNo, seqno: SeqNo) -> bool {
     item_seqno < seqno
 }
 
+/// Converts generic range bounds into owned key bounds
+pub(crate) fn range_bounds_to_owned<K: AsRef<[u8]>, R: RangeBounds<K>>(
+    range: &R,
+) -> (Bound<UserKey>, Bound<UserKey>) {
+    use Bound::{Excluded, Included, Unbounded};
+
+    let lo = match range.start_bound() {
+        Included(x) => Included(x.as_ref().into()),
+        Excluded(x) => Excluded(x.as_ref().into()),
+        Unbounded => Unbounded,
+    };
+
+    let hi = match range.end_bound() {
+        Included(x) => Included(x.as_ref().into()),
+        Excluded(x) => Excluded(x.as_ref().into()),
+        Unbounded => Unbounded,
+    };
+
+    (lo, hi)
+}
+
 #[must_use]
 #[allow(clippy::module_name_repetitions)]
 pub fn prefix_to_range(prefix: &[u8]) -> (Bound<UserKey>, Bound<UserKey>) {
@@ -80,17 +107,63 @@ impl DoubleEndedIterator for TreeIter {
     }
 }
 
+/// Returns the level without the segments that only contain items
+/// that are newer than the snapshot seqno.
+///
+/// The level is only copied if there are such segments.
+fn snapshot_level(level: &Arc<Level>, seqno: Option<SeqNo>) -> Arc<Level> {
+    let Some(seqno) = seqno else {
+        return level.clone();
+    };
+
+    if level
+        .segments
+        .iter()
+        .all(|segment| segment.has_visible_items(seqno))
+    {
+        return level.clone();
+    }
+
+    Arc::new(Level {
+        segments: level
+            .segments
+            .iter()
+            .filter(|segment| segment.has_visible_items(seqno))
+            .cloned()
+            .collect(),
+        is_disjoint: level.is_disjoint,
+    })
+}
+
+/// Returns `true` if the range tombstone may delete keys inside the bounds.
+fn overlaps_bounds(tombstone: &RangeTombstone, bounds: &(Bound<UserKey>, Bound<UserKey>)) -> bool {
+    let above_lo = match &bounds.0 {
+        Bound::Included(key) | Bound::Excluded(key) => tombstone.end > *key,
+        Bound::Unbounded => true,
+    };
+
+    let below_hi = match &bounds.1 {
+        Bound::Included(key) => tombstone.start <= *key,
+        Bound::Excluded(key) => tombstone.start < *key,
+        Bound::Unbounded => true,
+    };
+
+    above_lo && below_hi
+}
+
 fn collect_disjoint_tree_with_range(
-    level_manifest: &LevelManifest,
+    level_view: &LevelView,
     bounds: &(Bound<UserKey>, Bound<UserKey>),
+    seqno: Option<SeqNo>,
+    read_ahead: Option<&ReadAhead>,
 ) -> MultiReader<LevelReader> {
-    debug_assert!(level_manifest.is_disjoint());
+    debug_assert!(level_view.is_disjoint());
 
-    let mut levels = level_manifest
+    let mut levels = level_view
         .levels
         .iter()
+        .map(|level| snapshot_level(level, seqno))
         .filter(|x| !x.is_empty())
-        .cloned()
         .collect::<Vec<_>>();
 
     // TODO: save key range per level, makes key range sorting easier
@@ -120,7 +193,7 @@ fn collect_disjoint_tree_with_range(
 
     let readers = levels
         .into_iter()
-        .map(|lvl| LevelReader::new(lvl, bounds, CachePolicy::Write))
+        .map(|lvl| LevelReader::new(lvl, bounds, CachePolicy::Write).prefetch(read_ahead.cloned()))
         .collect();
 
     MultiReader::new(readers)
@@ -133,7 +206,10 @@ impl TreeIter {
         guard: MemtableLockGuard,
         bounds: (Bound<UserKey>, Bound<UserKey>),
         seqno: Option<SeqNo>,
-        level_manifest: ArcRwLockReadGuardian<LevelManifest>,
+        level_view: Arc<LevelView>,
+        read_ahead: Option<&ReadAhead>,
+        merge_operator: Option<Arc<dyn MergeOperator>>,
+        now: Duration,
     ) -> Self {
         Self::new(guard, |lock| {
             let lo = match &bounds.0 {
@@ -184,8 +260,9 @@ impl TreeIter {
             let mut iters: Vec<BoxedIterator<'_>> = Vec::with_capacity(5);
 
             // NOTE: Optimize disjoint trees (e.g. timeseries) to only use a single MultiReader.
-            if level_manifest.is_disjoint() {
-                let reader = collect_disjoint_tree_with_range(&level_manifest, &bounds);
+            if level_view.is_disjoint() {
+                let reader =
+                    collect_disjoint_tree_with_range(&level_view, &bounds, seqno, read_ahead);
 
                 if let Some(seqno) = seqno {
                     iters.push(Box::new(reader.filter(move |item| match item {
@@ -196,11 +273,13 @@ impl TreeIter {
                     iters.push(Box::new(reader));
                 }
             } else {
-                for level in &level_manifest.levels {
+                for level in &level_view.levels {
                     if level.is_disjoint {
+                        let level = snapshot_level(level, seqno);
+
                         if !level.is_empty() {
-                            let reader =
-                                LevelReader::new(level.clone(), &bounds, CachePolicy::Write);
+                            let reader = LevelReader::new(level, &bounds, CachePolicy::Write)
+                                .prefetch(read_ahead.cloned());
 
                             if let Some(seqno) = seqno {
                                 iters.push(Box::new(reader.filter(move |item| match item {
@@ -213,8 +292,16 @@ impl TreeIter {
                         }
                     } else {
                         for segment in &level.segments {
+                            // NOTE: Skip segments that are newer than the snapshot
+                            if let Some(seqno) = seqno {
+                                if !segment.has_visible_items(seqno) {
+                                    continue;
+                                }
+                            }
+
                             if segment.check_key_range_overlap(&bounds) {
-                                let reader = segment.range(bounds.clone());
+                                let reader =
+                                    segment.range(bounds.clone()).prefetch(read_ahead.cloned());
 
                                 if let Some(seqno) = seqno {
                                     iters.push(Box::new(reader.filter(move |item| match item {
@@ -230,7 +317,29 @@ impl TreeIter {
                 }
             };
 
-            drop(level_manifest);
+            // NOTE: Collect the visible range tombstones,
+            // the merged stream is masked with them below
+            let mut range_tombstones = vec![];
+
+            if let Err(e) = level_view.collect_range_tombstones(seqno, &mut range_tombstones) {
+                iters.push(Box::new(std::iter::once(Err(e))));
+            }
+
+            for memtable in lock
+                .sealed
+                .iter()
+                .map(|(_, memtable)| &**memtable)
+                .chain(std::iter::once(&*lock.active))
+                .chain(lock.ephemeral.as_deref())
+            {
+                range_tombstones
+                    .extend(memtable.range_tombstones().filter(|t| t.is_visible(seqno)));
+            }
+
+            range_tombstones.retain(|t| overlaps_bounds(t, &bounds));
+            let range_tombstones = RangeTombstoneIndex::new(range_tombstones);
+
+            drop(level_view);
 
             // Sealed memtables
             for (_, memtable) in lock.sealed.iter() {
@@ -265,13 +374,26 @@ impl TreeIter {
                 iters.push(iter);
             }
 
-            let merged = Merger::new(iters);
-            let iter = MvccStream::new(merged);
-
-            Box::new(iter.filter(|x| match x {
-                Ok(value) => !value.key.is_tombstone(),
-                Err(_) => true,
-            }))
+            // NOTE: Mask versions before resolving MVCC,
+            // so merge operands are not folded onto range deleted versions
+            //
+            // Expired values are turned into tombstones, so they shadow older versions
+            let merged = Merger::new(iters)
+                .filter(move |x| match x {
+                    Ok(value) => !range_tombstones.deletes(&value.key.user_key, value.key.seqno),
+                    Err(_) => true,
+                })
+                .map(move |x| x.map(|value| crate::ttl::expire(value, now)));
+
+            let iter = MvccStream::new(merged).with_merge_operator(merge_operator);
+
+            Box::new(
+                iter.filter(|x| match x {
+                    Ok(value) => !value.key.is_tombstone(),
+                    Err(_) => true,
+                })
+                .map(|x| x.map(crate::ttl::strip)),
+            )
         })
     }
 }
@@ -0,0 +1,291 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::{
+    coding::{Decode, DecodeError, Encode, EncodeError},
+    SeqNo, Slice, UserKey,
+};
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use std::io::{Read, Write};
+
+/// Deletes all versions of the keys in `[start, end)` that are older than the tombstone
+///
+/// Range tombstones are written using [`crate::AbstractTree::remove_range`].
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RangeTombstone {
+    /// Start key (inclusive)
+    pub start: UserKey,
+
+    /// End key (exclusive)
+    pub end: UserKey,
+
+    /// Sequence number of the tombstone
+    pub seqno: SeqNo,
+}
+
+impl RangeTombstone {
+    /// Creates a range tombstone.
+    #[must_use]
+    pub fn new(start: UserKey, end: UserKey, seqno: SeqNo) -> Self {
+        Self { start, end, seqno }
+    }
+
+    /// Returns `true` if the key is inside the tombstone's key range.
+    #[must_use]
+    pub fn contains_key(&self, key: &[u8]) -> bool {
+        &*self.start <= key && key < &*self.end
+    }
+
+    /// Returns `true` if the tombstone is visible to a snapshot with the given seqno.
+    #[must_use]
+    pub fn is_visible(&self, seqno: Option<SeqNo>) -> bool {
+        seqno.map_or(true, |seqno| self.seqno < seqno)
+    }
+
+    /// Returns `true` if the tombstone deletes the given version of a key.
+    #[must_use]
+    pub fn deletes(&self, key: &[u8], item_seqno: SeqNo) -> bool {
+        item_seqno < self.seqno && self.contains_key(key)
+    }
+
+    /// Returns `true` if the tombstone's key range overlaps with the given key range.
+    ///
+    /// Both bounds of the given range are inclusive.
+    #[must_use]
+    pub fn overlaps(&self, min: &[u8], max: &[u8]) -> bool {
+        &*self.start <= max && min < &*self.end
+    }
+
+    /// Returns the part of the tombstone that lies in `[lo, hi)`, if any.
+    ///
+    /// Missing bounds are unbounded.
+    pub(crate) fn clip(&self, lo: Option<&UserKey>, hi: Option<&UserKey>) -> Option<Self> {
+        let start = match lo {
+            Some(lo) if lo > &self.start => lo.clone(),
+            _ => self.start.clone(),
+        };
+
+        let end = match hi {
+            Some(hi) if hi < &self.end => hi.clone(),
+            _ => self.end.clone(),
+        };
+
+        (start < end).then(|| Self::new(start, end, self.seqno))
+    }
+}
+
+/// Range tombstones, sorted by their start key, that can be looked up by key
+///
+/// Besides the tombstones, the largest end key of every prefix of the sorted tombstones
+/// is stored, so a lookup can stop as soon as no earlier tombstone can reach the key.
+#[derive(Clone, Debug, Default)]
+pub struct RangeTombstoneIndex {
+    tombstones: Vec<RangeTombstone>,
+
+    /// `max_ends[i]` is the largest end key of `tombstones[..=i]`
+    max_ends: Vec<UserKey>,
+}
+
+impl RangeTombstoneIndex {
+    /// Creates an index over the given range tombstones.
+    #[must_use]
+    pub fn new(mut tombstones: Vec<RangeTombstone>) -> Self {
+        tombstones.sort();
+
+        let mut max_ends: Vec<UserKey> = Vec::with_capacity(tombstones.len());
+
+        for tombstone in &tombstones {
+            let max_end = match max_ends.last() {
+                Some(prev) if prev >= &tombstone.end => prev.clone(),
+                _ => tombstone.end.clone(),
+            };
+            max_ends.push(max_end);
+        }
+
+        Self {
+            tombstones,
+            max_ends,
+        }
+    }
+
+    /// Returns the range tombstones, ordered by their start key.
+    #[must_use]
+    pub fn as_slice(&self) -> &[RangeTombstone] {
+        &self.tombstones
+    }
+
+    /// Returns the range tombstones whose key range contains the given key.
+    pub fn covering<'a>(&'a self, key: &'a [u8]) -> impl Iterator<Item = &'a RangeTombstone> + 'a {
+        self.covering_positions(key).map(|(_, t)| t)
+    }
+
+    /// Returns the range tombstones whose key range contains the given key,
+    /// together with their position in [`RangeTombstoneIndex::as_slice`].
+    pub(crate) fn covering_positions<'a>(
+        &'a self,
+        key: &'a [u8],
+    ) -> impl Iterator<Item = (usize, &'a RangeTombstone)> + 'a {
+        // NOTE: Only tombstones that start at or before the key can contain it
+        let end = self.tombstones.partition_point(|t| &*t.start <= key);
+
+        self.tombstones
+            .iter()
+            .zip(&self.max_ends)
+            .enumerate()
+            .take(end)
+            .rev()
+            .take_while(move |(_, (_, max_end))| key < &***max_end)
+            .map(|(idx, (t, _))| (idx, t))
+            .filter(move |(_, t)| t.contains_key(key))
+    }
+
+    /// Returns `true` if any of the tombstones deletes the given version of a key.
+    pub(crate) fn deletes(&self, key: &[u8], item_seqno: SeqNo) -> bool {
+        self.covering(key).any(|t| item_seqno < t.seqno)
+    }
+}
+
+impl Encode for RangeTombstone {
+    fn encode_into<W: Write>(&self, writer: &mut W) -> Result<(), EncodeError> {
+        // NOTE: Max key size = u16
+        #[allow(clippy::cast_possible_truncation)]
+        writer.write_u16::<BigEndian>(self.start.len() as u16)?;
+        writer.write_all(&self.start)?;
+
+        // NOTE: Max key size = u16
+        #[allow(clippy::cast_possible_truncation)]
+        writer.write_u16::<BigEndian>(self.end.len() as u16)?;
+        writer.write_all(&self.end)?;
+
+        writer.write_u64::<BigEndian>(self.seqno)?;
+
+        Ok(())
+    }
+}
+
+impl Decode for RangeTombstone {
+    fn decode_from<R: Read>(reader: &mut R) -> Result<Self, DecodeError> {
+        let start_len = reader.read_u16::<BigEndian>()?;
+        let start = Slice::from_reader(reader, start_len.into())?;
+
+        let end_len = reader.read_u16::<BigEndian>()?;
+        let end = Slice::from_reader(reader, end_len.into())?;
+
+        let seqno = reader.read_u64::<BigEndian>()?;
+
+        Ok(Self { start, end, seqno })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn range_tombstone_deletes() {
+        let t = RangeTombstone::new("b".into(), "d".into(), 5);
+
+        assert!(!t.deletes(b"a", 0));
+        assert!(t.deletes(b"b", 0));
+        assert!(t.deletes(b"c", 4));
+        assert!(!t.deletes(b"c", 5));
+        assert!(!t.deletes(b"d", 0));
+
+        assert!(!t.is_visible(Some(5)));
+        assert!(t.is_visible(Some(6)));
+        assert!(t.is_visible(None));
+    }
+
+    #[test]
+    fn range_tombstone_overlaps() {
+        let t = RangeTombstone::new("b".into(), "d".into(), 5);
+
+        assert!(t.overlaps(b"a", b"b"));
+        assert!(t.overlaps(b"c", b"z"));
+        assert!(!t.overlaps(b"d", b"z"));
+        assert!(!t.overlaps(b"0", b"a"));
+    }
+
+    #[test]
+    fn range_tombstone_clip() {
+        let t = RangeTombstone::new("b".into(), "d".into(), 5);
+
+        assert_eq!(Some(t.clone()), t.clip(None, None));
+        assert_eq!(
+            Some(t.clone()),
+            t.clip(Some(&"a".into()), Some(&"e".into()))
+        );
+        assert_eq!(
+            Some(RangeTombstone::new("c".into(), "d".into(), 5)),
+            t.clip(Some(&"c".into()), None),
+        );
+        assert_eq!(
+            Some(RangeTombstone::new("b".into(), "c".into(), 5)),
+            t.clip(None, Some(&"c".into())),
+        );
+        assert_eq!(None, t.clip(Some(&"d".into()), None));
+        assert_eq!(None, t.clip(None, Some(&"b".into())));
+    }
+
+    #[test]
+    fn range_tombstone_index_covering() {
+        let index = RangeTombstoneIndex::new(vec![
+            RangeTombstone::new("m".into(), "n".into(), 3),
+            RangeTombstone::new("a".into(), "z".into(), 1),
+            RangeTombstone::new("c".into(), "e".into(), 2),
+        ]);
+
+        assert_eq!(
+            b"a",
+            &*index.as_slice().first().expect("should exist").start
+        );
+
+        let seqnos = |key: &[u8]| index.covering(key).map(|t| t.seqno).collect::<Vec<_>>();
+
+        assert_eq!(vec![1], seqnos(b"b"));
+        assert_eq!(vec![2, 1], seqnos(b"d"));
+        assert_eq!(vec![3, 1], seqnos(b"m"));
+        assert_eq!(vec![1], seqnos(b"y"));
+        assert!(seqnos(b"z").is_empty());
+        assert!(seqnos(b"0").is_empty());
+
+        assert!(RangeTombstoneIndex::default()
+            .covering(b"a")
+            .next()
+            .is_none());
+
+        assert_eq!(
+            vec![1, 0],
+            index
+                .covering_positions(b"d")
+                .map(|(idx, _)| idx)
+                .collect::<Vec<_>>(),
+        );
+    }
+
+    #[test]
+    fn range_tombstone_index_deletes() {
+        let index = RangeTombstoneIndex::new(vec![
+            RangeTombstone::new("a".into(), "c".into(), 5),
+            RangeTombstone::new("b".into(), "d".into(), 3),
+        ]);
+
+        assert!(index.deletes(b"b", 4));
+        assert!(!index.deletes(b"b", 5));
+        assert!(index.deletes(b"c", 2));
+        assert!(!index.deletes(b"c", 3));
+        assert!(!index.deletes(b"d", 0));
+    }
+
+    #[test]
+    fn range_tombstone_roundtrip() -> crate::Result<()> {
+        let t = RangeTombstone::new("b".into(), "d".into(), 5);
+
+        let bytes = t.encode_into_vec();
+        assert_eq!(t, RangeTombstone::decode_from(&mut &bytes[..])?);
+
+        Ok(())
+    }
+}
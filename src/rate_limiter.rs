@@ -0,0 +1,163 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+struct State {
+    /// Bytes that can be written without waiting, negative if in debt
+    available: i128,
+
+    /// Time `available` was last refilled
+    refilled_at: Instant,
+}
+
+/// Limits the throughput of background I/O (e.g. blob GC)
+///
+/// A rate limiter can be shared between multiple trees to limit their combined
+/// throughput, see [`Config::rate_limiter`](crate::Config::rate_limiter).
+///
+/// # Examples
+///
+/// ```
+/// # let folder = tempfile::tempdir()?;
+/// use lsm_tree::{Config, RateLimiter};
+/// use std::sync::Arc;
+///
+/// let rate_limiter = Arc::new(RateLimiter::new(/* 16 MiB */ 16 * 1_024 * 1_024));
+///
+/// let tree = Config::new(folder)
+///     .rate_limiter(rate_limiter.clone())
+///     .open_as_blob_tree()?;
+/// #
+/// # Ok::<(), lsm_tree::Error>(())
+/// ```
+pub struct RateLimiter {
+    bytes_per_second: AtomicU64,
+    state: Mutex<State>,
+}
+
+impl RateLimiter {
+    /// Creates a rate limiter that allows the given amount of bytes per second.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the rate is 0.
+    #[must_use]
+    pub fn new(bytes_per_second: u64) -> Self {
+        assert!(bytes_per_second > 0, "rate should be greater than 0");
+
+        Self {
+            bytes_per_second: AtomicU64::new(bytes_per_second),
+            state: Mutex::new(State {
+                available: 0,
+                refilled_at: Instant::now(),
+            }),
+        }
+    }
+
+    /// Returns the allowed amount of bytes per second.
+    #[must_use]
+    pub fn bytes_per_second(&self) -> u64 {
+        self.bytes_per_second.load(Ordering::Relaxed)
+    }
+
+    /// Changes the allowed amount of bytes per second.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the rate is 0.
+    pub fn set_bytes_per_second(&self, bytes_per_second: u64) {
+        assert!(bytes_per_second > 0, "rate should be greater than 0");
+        self.bytes_per_second
+            .store(bytes_per_second, Ordering::Relaxed);
+    }
+
+    /// Reserves the given amount of bytes, returning how long the
+    /// caller needs to wait before performing the I/O.
+    fn reserve(&self, bytes: u64) -> Duration {
+        let rate = i128::from(self.bytes_per_second());
+
+        let available = {
+            let mut state = self.state.lock().expect("lock is poisoned");
+
+            let now = Instant::now();
+            let elapsed = now.duration_since(state.refilled_at);
+            state.refilled_at = now;
+
+            let refill = i128::try_from(elapsed.as_micros())
+                .unwrap_or(i128::MAX)
+                .saturating_mul(rate)
+                / 1_000_000;
+
+            // NOTE: At most a second worth of bytes can be saved up
+            state.available = state.available.saturating_add(refill).min(rate);
+            state.available -= i128::from(bytes);
+            state.available
+        };
+
+        if available >= 0 {
+            return Duration::ZERO;
+        }
+
+        let micros = available.saturating_neg().saturating_mul(1_000_000) / rate;
+        Duration::from_micros(u64::try_from(micros).unwrap_or(u64::MAX))
+    }
+
+    /// Blocks until the given amount of bytes may be read or written.
+    ///
+    /// Requests larger than the rate are allowed, but make
+    /// later requests wait until the debt is paid off.
+    pub fn request(&self, bytes: u64) {
+        let wait = self.reserve(bytes);
+
+        if !wait.is_zero() {
+            log::trace!("Rate limiter: waiting {wait:?} for {bytes}B");
+            std::thread::sleep(wait);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn rate_limiter_debt() {
+        let limiter = RateLimiter::new(1_000);
+
+        // NOTE: Nothing is saved up initially
+        let wait = limiter.reserve(500);
+        assert!(wait > Duration::from_millis(400));
+        assert!(wait <= Duration::from_millis(500));
+
+        // NOTE: The debt of the first request is added
+        let wait = limiter.reserve(1_000);
+        assert!(wait > Duration::from_millis(1_400));
+        assert!(wait <= Duration::from_millis(1_500));
+    }
+
+    #[test]
+    fn rate_limiter_burst_cap() {
+        let limiter = RateLimiter::new(1_000_000);
+
+        {
+            let mut state = limiter.state.lock().expect("lock is poisoned");
+            state.refilled_at = state
+                .refilled_at
+                .checked_sub(Duration::from_secs(10))
+                .expect("should not underflow");
+        }
+
+        // NOTE: Only a second worth of bytes is saved up
+        assert!(limiter.reserve(1_000_000).is_zero());
+        assert!(!limiter.reserve(1_000).is_zero());
+    }
+}
@@ -0,0 +1,290 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::{
+    key::InternalKey,
+    value::{InternalValue, SeqNo, UserKey, UserValue, ValueType},
+    KvPair, Memtable,
+};
+use double_ended_peekable::{DoubleEndedPeekable, DoubleEndedPeekableExt};
+use std::{
+    collections::VecDeque,
+    ops::Bound,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+/// Uncommitted writes that are merged on top of a tree when reading
+///
+/// This allows transaction layers to read their own writes, without
+/// writing them into the tree. Writes to the overlay always shadow the
+/// tree, no matter the snapshot seqno that is read at.
+///
+/// # Examples
+///
+/// ```
+/// # let folder = tempfile::tempdir()?;
+/// use lsm_tree::{AbstractTree, Config, ReadOverlay};
+///
+/// let tree = Config::new(folder).open()?;
+/// tree.insert("a", "committed", 0);
+/// tree.insert("b", "committed", 1);
+///
+/// let overlay = ReadOverlay::default();
+/// overlay.insert("a", "uncommitted");
+/// overlay.remove("b");
+///
+/// assert_eq!(b"uncommitted", &*tree.get_with_overlay("a", None, &overlay)?.unwrap());
+/// assert!(tree.get_with_overlay("b", None, &overlay)?.is_none());
+/// assert_eq!(1, tree.range_with_overlay::<&str, _>(.., None, &overlay).count());
+/// #
+/// # Ok::<(), lsm_tree::Error>(())
+/// ```
+#[derive(Default)]
+pub struct ReadOverlay {
+    memtable: Memtable,
+
+    /// Orders the writes to the overlay, so the latest write of a key wins
+    seqno: AtomicU64,
+}
+
+impl From<Memtable> for ReadOverlay {
+    fn from(memtable: Memtable) -> Self {
+        let seqno = memtable.get_highest_seqno().map_or(0, |seqno| seqno + 1);
+
+        Self {
+            memtable,
+            seqno: AtomicU64::new(seqno),
+        }
+    }
+}
+
+impl ReadOverlay {
+    fn next_seqno(&self) -> SeqNo {
+        self.seqno.fetch_add(1, Ordering::AcqRel)
+    }
+
+    /// Inserts a key-value pair into the overlay.
+    pub fn insert<K: Into<UserKey>, V: Into<UserValue>>(&self, key: K, value: V) {
+        self.memtable.insert(InternalValue::from_components(
+            key,
+            value,
+            self.next_seqno(),
+            ValueType::Value,
+        ));
+    }
+
+    /// Removes a key, hiding it from reads, even if it exists in the tree.
+    pub fn remove<K: Into<UserKey>>(&self, key: K) {
+        self.memtable
+            .insert(InternalValue::new_tombstone(key, self.next_seqno()));
+    }
+
+    /// Returns the amount of writes in the overlay.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.memtable.len()
+    }
+
+    /// Returns `true` if the overlay contains no writes.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.memtable.is_empty()
+    }
+
+    /// Returns the latest write of a key.
+    ///
+    /// Returns `Some(None)` if the key was removed in the overlay,
+    /// and `None` if the overlay does not contain the key.
+    #[must_use]
+    pub fn get<K: AsRef<[u8]>>(&self, key: K) -> Option<Option<UserValue>> {
+        self.memtable
+            .get(key, None)
+            .map(|item| (!item.is_tombstone()).then_some(item.value))
+    }
+
+    /// Returns the latest write of every key in the given range, in key order.
+    pub(crate) fn collect_range(
+        &self,
+        bounds: &(Bound<UserKey>, Bound<UserKey>),
+    ) -> VecDeque<InternalValue> {
+        // NOTE: See range.rs for how user key bounds map to internal key bounds
+        let lo = match &bounds.0 {
+            Bound::Included(key) => Bound::Included(InternalKey::new(
+                key.clone(),
+                SeqNo::MAX,
+                ValueType::Tombstone,
+            )),
+            Bound::Excluded(key) => {
+                Bound::Excluded(InternalKey::new(key.clone(), 0, ValueType::Tombstone))
+            }
+            Bound::Unbounded => Bound::Unbounded,
+        };
+
+        let hi = match &bounds.1 {
+            Bound::Included(key) => {
+                Bound::Included(InternalKey::new(key.clone(), 0, ValueType::Value))
+            }
+            Bound::Excluded(key) => {
+                Bound::Excluded(InternalKey::new(key.clone(), SeqNo::MAX, ValueType::Value))
+            }
+            Bound::Unbounded => Bound::Unbounded,
+        };
+
+        let mut items = VecDeque::<InternalValue>::new();
+
+        for item in self.memtable.range((lo, hi)) {
+            // NOTE: Versions are sorted by descending seqno, so the first one is the latest
+            if items
+                .back()
+                .is_some_and(|last| last.key.user_key == item.key.user_key)
+            {
+                continue;
+            }
+
+            items.push_back(item);
+        }
+
+        items
+    }
+}
+
+/// Merges the (latest) writes of an overlay on top of a tree iterator
+pub struct OverlayIter<I: DoubleEndedIterator<Item = crate::Result<KvPair>>> {
+    base: DoubleEndedPeekable<I>,
+    overlay: VecDeque<InternalValue>,
+}
+
+impl<I: DoubleEndedIterator<Item = crate::Result<KvPair>>> OverlayIter<I> {
+    pub fn new(base: I, overlay: VecDeque<InternalValue>) -> Self {
+        Self {
+            base: base.double_ended_peekable(),
+            overlay,
+        }
+    }
+}
+
+impl<I: DoubleEndedIterator<Item = crate::Result<KvPair>>> Iterator for OverlayIter<I> {
+    type Item = crate::Result<KvPair>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let Some(item) = self.overlay.front() else {
+                return self.base.next();
+            };
+
+            let order = match self.base.peek() {
+                Some(Ok((key, _))) => key.cmp(&item.key.user_key),
+                Some(Err(_)) => return self.base.next(),
+                None => std::cmp::Ordering::Greater,
+            };
+
+            match order {
+                std::cmp::Ordering::Less => return self.base.next(),
+                std::cmp::Ordering::Equal => {
+                    // NOTE: The overlay shadows the tree
+                    self.base.next();
+                }
+                std::cmp::Ordering::Greater => {}
+            }
+
+            let item = self.overlay.pop_front()?;
+
+            if !item.is_tombstone() {
+                return Some(Ok((item.key.user_key, item.value)));
+            }
+        }
+    }
+}
+
+impl<I: DoubleEndedIterator<Item = crate::Result<KvPair>>> DoubleEndedIterator for OverlayIter<I> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        loop {
+            let Some(item) = self.overlay.back() else {
+                return self.base.next_back();
+            };
+
+            let order = match self.base.peek_back() {
+                Some(Ok((key, _))) => key.cmp(&item.key.user_key),
+                Some(Err(_)) => return self.base.next_back(),
+                None => std::cmp::Ordering::Less,
+            };
+
+            match order {
+                std::cmp::Ordering::Greater => return self.base.next_back(),
+                std::cmp::Ordering::Equal => {
+                    // NOTE: The overlay shadows the tree
+                    self.base.next_back();
+                }
+                std::cmp::Ordering::Less => {}
+            }
+
+            let item = self.overlay.pop_back()?;
+
+            if !item.is_tombstone() {
+                return Some(Ok((item.key.user_key, item.value)));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    fn base(keys: &[&str]) -> impl DoubleEndedIterator<Item = crate::Result<KvPair>> {
+        keys.iter()
+            .map(|key| Ok((UserKey::from(*key), UserValue::from("base"))))
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    #[test]
+    fn read_overlay_latest_write_wins() {
+        let overlay = ReadOverlay::default();
+        assert_eq!(None, overlay.get("a"));
+
+        overlay.insert("a", "1");
+        overlay.insert("a", "2");
+        assert_eq!(Some(Some(UserValue::from("2"))), overlay.get("a"));
+
+        overlay.remove("a");
+        assert_eq!(Some(None), overlay.get("a"));
+
+        let items = overlay.collect_range(&(Bound::Unbounded, Bound::Unbounded));
+        assert_eq!(1, items.len());
+    }
+
+    #[test]
+    fn read_overlay_merge() -> crate::Result<()> {
+        let overlay = ReadOverlay::default();
+        overlay.insert("a", "overlay");
+        overlay.insert("c", "overlay");
+        overlay.remove("d");
+        overlay.insert("f", "overlay");
+
+        let bounds = (Bound::Unbounded, Bound::Unbounded);
+
+        let expected = vec![
+            (UserKey::from("a"), UserValue::from("overlay")),
+            (UserKey::from("b"), UserValue::from("base")),
+            (UserKey::from("c"), UserValue::from("overlay")),
+            (UserKey::from("e"), UserValue::from("base")),
+            (UserKey::from("f"), UserValue::from("overlay")),
+        ];
+
+        let forward = OverlayIter::new(base(&["b", "c", "d", "e"]), overlay.collect_range(&bounds))
+            .collect::<crate::Result<Vec<_>>>()?;
+        assert_eq!(expected, forward);
+
+        let mut backward =
+            OverlayIter::new(base(&["b", "c", "d", "e"]), overlay.collect_range(&bounds))
+                .rev()
+                .collect::<crate::Result<Vec<_>>>()?;
+        backward.reverse();
+        assert_eq!(expected, backward);
+
+        Ok(())
+    }
+}
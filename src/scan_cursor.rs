@@ -0,0 +1,174 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::{
+    coding::{Decode, DecodeError, Encode, EncodeError},
+    KvPair, SeqNo, Slice, UserKey,
+};
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use std::{
+    io::{Read, Write},
+    ops::Bound,
+};
+
+/// Position of a paginated scan, see [`crate::AbstractTree::scan_page`]
+///
+/// The cursor can be serialized using [`Encode`] and handed out as an
+/// opaque continuation token, for example to clients of a REST API.
+///
+/// All pages of a scan read the tree at the same seqno, so the scan sees a
+/// consistent view of the tree. Versions are only kept around while they
+/// are covered by a snapshot, so unless the seqno is pinned (e.g. by a named snapshot),
+/// compaction may drop the versions a resumed cursor reads. Keys that were overwritten
+/// since are then missing from later pages, see [`ScanPage::pinned`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ScanCursor {
+    /// Last key that was returned, if any
+    last_key: Option<UserKey>,
+
+    /// Read seqno of the scan
+    seqno: SeqNo,
+}
+
+impl ScanCursor {
+    /// Starts a new scan that reads the tree at the given seqno.
+    #[must_use]
+    pub fn new(seqno: SeqNo) -> Self {
+        Self {
+            last_key: None,
+            seqno,
+        }
+    }
+
+    /// Returns the read seqno of the scan.
+    #[must_use]
+    pub fn seqno(&self) -> SeqNo {
+        self.seqno
+    }
+
+    /// Returns the last key that was returned by the scan.
+    #[must_use]
+    pub fn last_key(&self) -> Option<&UserKey> {
+        self.last_key.as_ref()
+    }
+
+    /// Returns a cursor positioned after the given key.
+    pub(crate) fn advance(&self, last_key: UserKey) -> Self {
+        Self {
+            last_key: Some(last_key),
+            seqno: self.seqno,
+        }
+    }
+
+    /// Returns the start bound of the next page, given the start bound of the scanned range.
+    pub(crate) fn start_bound(&self, start: Bound<UserKey>) -> Bound<UserKey> {
+        let Some(last_key) = &self.last_key else {
+            return start;
+        };
+
+        match &start {
+            Bound::Included(key) | Bound::Excluded(key) if key > last_key => start,
+            _ => Bound::Excluded(last_key.clone()),
+        }
+    }
+}
+
+/// A page of a paginated scan, see [`crate::AbstractTree::scan_page`]
+#[derive(Clone, Debug)]
+pub struct ScanPage {
+    /// Items of the page
+    pub items: Vec<KvPair>,
+
+    /// Cursor to resume the scan, or `None` if the range is exhausted
+    pub next: Option<ScanCursor>,
+
+    /// `false` if the seqno of the cursor is below the GC watermark,
+    /// see [`crate::AbstractTree::gc_watermark`]
+    ///
+    /// Compaction may then have dropped versions the scan reads, so keys
+    /// that were overwritten after the seqno of the cursor may be missing.
+    pub pinned: bool,
+}
+
+impl Encode for ScanCursor {
+    fn encode_into<W: Write>(&self, writer: &mut W) -> Result<(), EncodeError> {
+        writer.write_u64::<BigEndian>(self.seqno)?;
+
+        match &self.last_key {
+            Some(key) => {
+                writer.write_u8(1)?;
+
+                // NOTE: Max key size = u16
+                #[allow(clippy::cast_possible_truncation)]
+                writer.write_u16::<BigEndian>(key.len() as u16)?;
+                writer.write_all(key)?;
+            }
+            None => {
+                writer.write_u8(0)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Decode for ScanCursor {
+    fn decode_from<R: Read>(reader: &mut R) -> Result<Self, DecodeError> {
+        let seqno = reader.read_u64::<BigEndian>()?;
+
+        let last_key = match reader.read_u8()? {
+            0 => None,
+            1 => {
+                let key_len = reader.read_u16::<BigEndian>()?;
+                Some(Slice::from_reader(reader, key_len.into())?)
+            }
+            tag => return Err(DecodeError::InvalidTag(("ScanCursor", tag))),
+        };
+
+        Ok(Self { last_key, seqno })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn scan_cursor_roundtrip() -> crate::Result<()> {
+        let cursor = ScanCursor::new(5);
+        let bytes = cursor.encode_into_vec();
+        assert_eq!(cursor, ScanCursor::decode_from(&mut &bytes[..])?);
+
+        let cursor = ScanCursor {
+            last_key: Some("abc".into()),
+            seqno: 7,
+        };
+        let bytes = cursor.encode_into_vec();
+        assert_eq!(cursor, ScanCursor::decode_from(&mut &bytes[..])?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn scan_cursor_start_bound() {
+        let cursor = ScanCursor {
+            last_key: Some("c".into()),
+            seqno: 0,
+        };
+
+        assert_eq!(
+            Bound::Excluded("c".into()),
+            cursor.start_bound(Bound::Unbounded)
+        );
+        assert_eq!(
+            Bound::Excluded("c".into()),
+            cursor.start_bound(Bound::Included("a".into()))
+        );
+        assert_eq!(
+            Bound::Included("d".into()),
+            cursor.start_bound(Bound::Included("d".into()))
+        );
+    }
+}
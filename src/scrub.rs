@@ -0,0 +1,295 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::{blob_tree::MyCompressor, stop_signal::StopSignal, Checksum, Config, SegmentId, Tree};
+use std::{sync::Arc, time::Duration};
+use value_log::ValueLog;
+
+type Blobs = ValueLog<MyCompressor>;
+
+/// Result of [`crate::AbstractTree::scrub`]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ScrubReport {
+    /// Amount of segments that were verified
+    pub segments_checked: usize,
+
+    /// Amount of blob files that were verified
+    pub blob_files_checked: usize,
+
+    /// Amount of bytes that were read
+    pub bytes_checked: u64,
+
+    /// Segments that turned out to be corrupt
+    pub corrupt_segments: Vec<SegmentId>,
+
+    /// Corrupt segments that were quarantined,
+    /// see [`crate::Config::quarantine_corrupt_segments`]
+    pub quarantined_segments: Vec<SegmentId>,
+
+    /// Blob files that turned out to be corrupt
+    pub corrupt_blob_files: Vec<SegmentId>,
+}
+
+impl ScrubReport {
+    /// Returns `true` if no corrupt file was found.
+    #[must_use]
+    pub fn is_clean(&self) -> bool {
+        self.corrupt_segments.is_empty() && self.corrupt_blob_files.is_empty()
+    }
+}
+
+/// File that is verified by a scrub
+#[derive(Copy, Clone, Debug)]
+enum Target {
+    Segment(SegmentId),
+    BlobFile(SegmentId),
+}
+
+fn list_targets(tree: &Tree, blobs: Option<&Blobs>) -> Vec<Target> {
+    let mut targets = tree
+        .level_view
+        .load()
+        .iter()
+        .map(|segment| Target::Segment(segment.id()))
+        .collect::<Vec<_>>();
+
+    if let Some(blobs) = blobs {
+        targets.extend(
+            blobs
+                .manifest
+                .list_segment_ids()
+                .into_iter()
+                .map(Target::BlobFile),
+        );
+    }
+
+    targets
+}
+
+fn scrub_target(
+    tree: &Tree,
+    blobs: Option<&Blobs>,
+    target: Target,
+    report: &mut ScrubReport,
+) -> crate::Result<()> {
+    match (target, blobs) {
+        (Target::Segment(segment_id), _) => scrub_segment(tree, segment_id, report),
+        (Target::BlobFile(blob_file_id), Some(blobs)) => {
+            scrub_blob_file(&tree.config, blobs, blob_file_id, report)
+        }
+        (Target::BlobFile(_), None) => Ok(()),
+    }
+}
+
+fn scrub_segment(
+    tree: &Tree,
+    segment_id: SegmentId,
+    report: &mut ScrubReport,
+) -> crate::Result<()> {
+    let Some(segment) = tree
+        .level_view
+        .load()
+        .iter()
+        .find(|segment| segment.id() == segment_id)
+        .cloned()
+    else {
+        // NOTE: The segment was compacted away in the meantime
+        return Ok(());
+    };
+
+    let file_size = segment.metadata.file_size;
+
+    if let Some(rate_limiter) = &tree.config.rate_limiter {
+        rate_limiter.request(file_size);
+    }
+
+    let error = match segment.verify() {
+        Ok(verify_report) if verify_report.is_ok() => None,
+        Ok(verify_report) => Some(crate::Error::Corruption {
+            segment_id,
+            context: format!("{} corrupt data blocks", verify_report.error_count()),
+        }),
+        Err(e) if e.is_corruption() => Some(e.in_segment(segment_id)),
+        Err(e) => return Err(e),
+    };
+    drop(segment);
+
+    report.segments_checked += 1;
+    report.bytes_checked += file_size;
+
+    let Some(error) = error else {
+        return Ok(());
+    };
+
+    log::error!("Scrub found corrupt segment {segment_id}: {error:?}");
+    report.corrupt_segments.push(segment_id);
+
+    if tree.config.quarantine_corrupt_segments && tree.quarantine_segment(segment_id, &error)? {
+        report.quarantined_segments.push(segment_id);
+    } else if let Some(observer) = &tree.config.quarantine_observer {
+        observer.on_segment_corrupted(segment_id, &error);
+    }
+
+    Ok(())
+}
+
+/// Reads through a blob file, returning the first corruption, if any.
+fn verify_blob_file(
+    blob_file: &value_log::Segment<MyCompressor>,
+) -> crate::Result<Option<crate::Error>> {
+    let as_corruption = |e: value_log::Error| {
+        let e = crate::Error::from(e);
+        if e.is_corruption() {
+            Ok(Some(e))
+        } else {
+            Err(e)
+        }
+    };
+
+    // NOTE: The reader does not decompress, so blobs are
+    // hashed exactly as they were written
+    let reader = match blob_file.scan() {
+        Ok(reader) => reader,
+        Err(e) => return as_corruption(e),
+    };
+
+    for item in reader {
+        let (key, value, expected_checksum) = match item {
+            Ok(item) => item,
+            Err(e) => return as_corruption(e),
+        };
+
+        let mut hasher = xxhash_rust::xxh3::Xxh3::new();
+        hasher.update(&key);
+        hasher.update(&value);
+        let got = hasher.digest();
+
+        if got != expected_checksum {
+            return Ok(Some(crate::Error::InvalidChecksum((
+                Checksum::from_raw(got),
+                Checksum::from_raw(expected_checksum),
+            ))));
+        }
+    }
+
+    Ok(None)
+}
+
+fn scrub_blob_file(
+    config: &Config,
+    blobs: &Blobs,
+    blob_file_id: SegmentId,
+    report: &mut ScrubReport,
+) -> crate::Result<()> {
+    let Some(blob_file) = blobs.manifest.get_segment(blob_file_id) else {
+        // NOTE: The blob file was garbage collected in the meantime
+        return Ok(());
+    };
+
+    let file_size = blob_file.meta.compressed_bytes;
+
+    if let Some(rate_limiter) = &config.rate_limiter {
+        rate_limiter.request(file_size);
+    }
+
+    let error = verify_blob_file(&blob_file)?;
+    drop(blob_file);
+
+    report.blob_files_checked += 1;
+    report.bytes_checked += file_size;
+
+    let Some(error) = error else {
+        return Ok(());
+    };
+
+    log::error!("Scrub found corrupt blob file {blob_file_id}: {error:?}");
+    report.corrupt_blob_files.push(blob_file_id);
+
+    if let Some(observer) = &config.quarantine_observer {
+        observer.on_blob_file_corrupted(blob_file_id, &error);
+    }
+
+    Ok(())
+}
+
+/// Verifies every segment (and blob file) of the tree once.
+pub fn scrub(tree: &Tree, blobs: Option<&Blobs>) -> crate::Result<ScrubReport> {
+    let mut report = ScrubReport::default();
+
+    for target in list_targets(tree, blobs) {
+        scrub_target(tree, blobs, target, &mut report)?;
+    }
+
+    Ok(report)
+}
+
+/// Sleeps for the given duration, returning `false` if the tree was dropped in the meantime.
+fn sleep(duration: Duration, stop_signal: &StopSignal) -> bool {
+    const TICK: Duration = Duration::from_millis(100);
+
+    let mut remaining = duration;
+
+    while !remaining.is_zero() {
+        if stop_signal.is_stopped() {
+            return false;
+        }
+
+        let tick = remaining.min(TICK);
+        std::thread::sleep(tick);
+        remaining -= tick;
+    }
+
+    !stop_signal.is_stopped()
+}
+
+/// Spawns the background scrubber, which verifies every file of the tree once per period.
+///
+/// The scrubber only holds a weak reference to the tree while it is idle,
+/// and stops once the tree is dropped.
+pub fn spawn(tree: &Tree, blobs: Option<Blobs>, period: Duration) {
+    let weak = Arc::downgrade(&tree.0);
+    let stop_signal = tree.stop_signal.clone();
+
+    tree.config.executor().spawn_blocking(Box::new(move || {
+        log::debug!("Starting scrubber with period {period:?}");
+
+        loop {
+            let Some(targets) = weak
+                .upgrade()
+                .map(|inner| list_targets(&Tree(inner), blobs.as_ref()))
+            else {
+                return;
+            };
+
+            if targets.is_empty() {
+                if !sleep(period, &stop_signal) {
+                    return;
+                }
+                continue;
+            }
+
+            // NOTE: Spread the files over the period, so the scrubber
+            // does not compete with foreground I/O in bursts
+            let pause = period / u32::try_from(targets.len()).unwrap_or(u32::MAX);
+
+            let mut report = ScrubReport::default();
+
+            for target in targets {
+                if !sleep(pause, &stop_signal) {
+                    return;
+                }
+
+                let Some(inner) = weak.upgrade() else {
+                    return;
+                };
+
+                if let Err(e) = scrub_target(&Tree(inner), blobs.as_ref(), target, &mut report) {
+                    log::error!("Scrubber could not verify {target:?}: {e:?}");
+                }
+            }
+
+            log::debug!("Scrub pass done: {report:?}");
+        }
+    }));
+}
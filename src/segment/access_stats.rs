@@ -0,0 +1,230 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use super::meta::SegmentId;
+use crate::{
+    coding::{Decode, DecodeError, Encode, EncodeError},
+    file::{rewrite_atomic, ACCESS_STATS_FILE},
+    time::unix_timestamp,
+};
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use std::{
+    io::{Read, Write},
+    path::Path,
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+/// In-memory access counters of a segment
+#[derive(Default)]
+pub struct AccessStats {
+    reads: AtomicU64,
+    gets_served: AtomicU64,
+    blocks_read: AtomicU64,
+    bloom_negatives: AtomicU64,
+
+    /// Time of the last read in microseconds since the unix epoch, 0 if never read
+    last_access: AtomicU64,
+}
+
+impl AccessStats {
+    /// Records a point read that hit the key range of the segment.
+    pub fn record_read(&self) {
+        self.reads.fetch_add(1, Ordering::Relaxed);
+
+        let now = u64::try_from(unix_timestamp().as_micros()).unwrap_or(u64::MAX);
+        self.last_access.fetch_max(now, Ordering::Relaxed);
+    }
+
+    /// Records a point read that was answered by the segment.
+    pub fn record_get_served(&self) {
+        self.gets_served.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records a data block that was read by a point read.
+    pub fn record_block_read(&self) {
+        self.blocks_read.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records a point read that was rejected by the bloom filter.
+    pub fn record_bloom_negative(&self) {
+        self.bloom_negatives.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Returns the amount of point reads that hit the key range of the segment.
+    pub fn reads(&self) -> u64 {
+        self.reads.load(Ordering::Relaxed)
+    }
+
+    /// Copies the counters.
+    pub fn snapshot(&self, segment_id: SegmentId) -> SegmentAccessStats {
+        let last_access = self.last_access.load(Ordering::Relaxed);
+
+        SegmentAccessStats {
+            segment_id,
+            reads: self.reads(),
+            gets_served: self.gets_served.load(Ordering::Relaxed),
+            blocks_read: self.blocks_read.load(Ordering::Relaxed),
+            bloom_negatives: self.bloom_negatives.load(Ordering::Relaxed),
+            last_access: (last_access > 0).then(|| Duration::from_micros(last_access)),
+        }
+    }
+
+    /// Adds persisted counters, e.g. after recovering the segment.
+    pub fn restore(&self, stats: &SegmentAccessStats) {
+        self.reads.fetch_add(stats.reads, Ordering::Relaxed);
+        self.gets_served
+            .fetch_add(stats.gets_served, Ordering::Relaxed);
+        self.blocks_read
+            .fetch_add(stats.blocks_read, Ordering::Relaxed);
+        self.bloom_negatives
+            .fetch_add(stats.bloom_negatives, Ordering::Relaxed);
+
+        if let Some(last_access) = stats.last_access {
+            let last_access = u64::try_from(last_access.as_micros()).unwrap_or(u64::MAX);
+            self.last_access.fetch_max(last_access, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Access counters of a segment
+///
+/// Counters are kept in memory, and are only persisted
+/// when requested, see [`AbstractTree::persist_access_stats`].
+///
+/// [`AbstractTree::persist_access_stats`]: crate::AbstractTree::persist_access_stats
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[allow(clippy::module_name_repetitions)]
+pub struct SegmentAccessStats {
+    /// Segment ID
+    pub segment_id: SegmentId,
+
+    /// Amount of point reads that hit the key range of the segment
+    pub reads: u64,
+
+    /// Amount of point reads that were answered by the segment
+    pub gets_served: u64,
+
+    /// Amount of data blocks that were read by point reads (from cache or disk)
+    pub blocks_read: u64,
+
+    /// Amount of point reads that were rejected by the bloom filter
+    pub bloom_negatives: u64,
+
+    /// Time of the last point read, as duration since the unix epoch
+    pub last_access: Option<Duration>,
+}
+
+impl Encode for SegmentAccessStats {
+    fn encode_into<W: Write>(&self, writer: &mut W) -> Result<(), EncodeError> {
+        writer.write_u64::<BigEndian>(self.segment_id)?;
+        writer.write_u64::<BigEndian>(self.reads)?;
+        writer.write_u64::<BigEndian>(self.gets_served)?;
+        writer.write_u64::<BigEndian>(self.blocks_read)?;
+        writer.write_u64::<BigEndian>(self.bloom_negatives)?;
+
+        let last_access = self
+            .last_access
+            .map_or(0, |x| u64::try_from(x.as_micros()).unwrap_or(u64::MAX));
+        writer.write_u64::<BigEndian>(last_access)?;
+
+        Ok(())
+    }
+}
+
+impl Decode for SegmentAccessStats {
+    fn decode_from<R: Read>(reader: &mut R) -> Result<Self, DecodeError> {
+        let segment_id = reader.read_u64::<BigEndian>()?;
+        let reads = reader.read_u64::<BigEndian>()?;
+        let gets_served = reader.read_u64::<BigEndian>()?;
+        let blocks_read = reader.read_u64::<BigEndian>()?;
+        let bloom_negatives = reader.read_u64::<BigEndian>()?;
+        let last_access = reader.read_u64::<BigEndian>()?;
+
+        Ok(Self {
+            segment_id,
+            reads,
+            gets_served,
+            blocks_read,
+            bloom_negatives,
+            last_access: (last_access > 0).then(|| Duration::from_micros(last_access)),
+        })
+    }
+}
+
+/// Loads the persisted access stats of a tree, returning nothing if none were persisted.
+pub fn load<P: AsRef<Path>>(tree_path: P) -> crate::Result<Vec<SegmentAccessStats>> {
+    let bytes = match std::fs::read(tree_path.as_ref().join(ACCESS_STATS_FILE)) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut reader = &bytes[..];
+    let len = reader.read_u32::<BigEndian>()?;
+
+    // NOTE: Every entry takes 48 bytes, so a corrupted
+    // length cannot cause a huge allocation
+    let mut stats = Vec::with_capacity((len as usize).min(bytes.len() / 48));
+
+    for _ in 0..len {
+        stats.push(SegmentAccessStats::decode_from(&mut reader)?);
+    }
+
+    Ok(stats)
+}
+
+/// Atomically writes the access stats into the tree folder.
+pub fn persist<P: AsRef<Path>>(tree_path: P, stats: &[SegmentAccessStats]) -> crate::Result<()> {
+    let mut bytes = vec![];
+
+    // NOTE: There are never 4 billion segments
+    #[allow(clippy::cast_possible_truncation)]
+    bytes.write_u32::<BigEndian>(stats.len() as u32)?;
+
+    for entry in stats {
+        entry.encode_into(&mut bytes)?;
+    }
+
+    rewrite_atomic(tree_path.as_ref().join(ACCESS_STATS_FILE), &bytes)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn access_stats_roundtrip() -> crate::Result<()> {
+        let folder = tempfile::tempdir()?;
+        assert!(load(&folder)?.is_empty());
+
+        let stats = AccessStats::default();
+        assert_eq!(None, stats.snapshot(1).last_access);
+
+        stats.record_read();
+        stats.record_read();
+        stats.record_bloom_negative();
+        stats.record_block_read();
+        stats.record_get_served();
+
+        let snapshot = stats.snapshot(1);
+        assert_eq!(2, snapshot.reads);
+        assert_eq!(1, snapshot.gets_served);
+        assert_eq!(1, snapshot.blocks_read);
+        assert_eq!(1, snapshot.bloom_negatives);
+        assert!(snapshot.last_access.is_some());
+
+        persist(&folder, &[snapshot.clone()])?;
+        assert_eq!(vec![snapshot.clone()], load(&folder)?);
+
+        let restored = AccessStats::default();
+        restored.restore(&snapshot);
+        assert_eq!(snapshot, restored.snapshot(1));
+
+        Ok(())
+    }
+}
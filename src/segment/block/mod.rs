@@ -4,13 +4,23 @@
 
 pub mod checksum;
 pub mod header;
+mod scratch;
 
 use super::{meta::CompressionType, value_block::BlockOffset};
-use crate::coding::{Decode, Encode};
+use crate::{
+    coding::{Decode, Encode},
+    file::read_exact_at,
+    transform::KeyedTransform,
+};
 use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
 use checksum::Checksum;
 use header::Header as BlockHeader;
-use std::io::{Cursor, Read};
+use scratch::ScratchBuffer;
+use std::{fs::File, io::Read};
+
+/// Maximum ratio of decompressed to compressed size of LZ4 data
+#[cfg(feature = "lz4")]
+const LZ4_MAX_RATIO: usize = 255;
 
 // TODO: better name
 pub trait ItemSize {
@@ -39,39 +49,97 @@ pub struct Block<T: Clone + Encode + Decode + ItemSize> {
 }
 
 impl<T: Clone + Encode + Decode + ItemSize> Block<T> {
-    pub fn from_reader<R: Read>(reader: &mut R) -> crate::Result<Self> {
+    pub fn from_reader<R: Read>(
+        reader: &mut R,
+        transform: Option<&KeyedTransform>,
+    ) -> crate::Result<Self> {
         // Read block header
         let header = BlockHeader::decode_from(reader)?;
         log::trace!("Got block header: {header:?}");
 
         // Read the (possibly compressed) data
-        let mut bytes = vec![0u8; header.data_length as usize];
-        reader.read_exact(&mut bytes)?;
+        ScratchBuffer::Read.with(header.data_length as usize, |bytes| {
+            reader.read_exact(bytes)?;
+            Self::from_raw_parts(header, bytes, transform)
+        })
+    }
 
-        // TODO: 3.0.0 when header.compressed is reliable
-        // can we preallocate a vector to stream the compression into?
-        // -> saves reallocation costs
-        let bytes = match header.compression {
-            super::meta::CompressionType::None => bytes,
+    /// Reverses the block transform (if any) of the block data that belongs to the given header,
+    /// and then decompresses & deserializes it.
+    fn from_raw_parts(
+        header: BlockHeader,
+        bytes: &[u8],
+        transform: Option<&KeyedTransform>,
+    ) -> crate::Result<Self> {
+        match transform {
+            Some(transform) => Self::decompress(header, &transform.decode(bytes)?),
+            None => Self::decompress(header, bytes),
+        }
+    }
+
+    /// Decompresses & deserializes the block data that belongs to the given header.
+    fn decompress(header: BlockHeader, bytes: &[u8]) -> crate::Result<Self> {
+        match header.compression {
+            super::meta::CompressionType::None => Self::decode_items(header, bytes),
 
             #[cfg(feature = "lz4")]
-            super::meta::CompressionType::Lz4 => lz4_flex::decompress_size_prepended(&bytes)
-                .map_err(|_| crate::Error::Decompress(header.compression))?,
+            super::meta::CompressionType::Lz4 => {
+                let compression = header.compression;
+
+                // NOTE: lz4_flex prepends the uncompressed size as little-endian u32
+                let size = bytes
+                    .get(..4)
+                    .and_then(|x| <[u8; 4]>::try_from(x).ok())
+                    .map(u32::from_le_bytes)
+                    .ok_or(crate::Error::Decompress(compression))?;
+
+                let compressed = bytes.get(4..).unwrap_or_default();
+
+                // NOTE: The size is read from disk, so do not trust it to allocate;
+                // LZ4 cannot compress better than 255:1
+                if size as usize > compressed.len().saturating_mul(LZ4_MAX_RATIO) {
+                    return Err(crate::Error::Decompress(compression));
+                }
+
+                ScratchBuffer::Decompression.with(size as usize, |buf| {
+                    let written = lz4_flex::decompress_into(compressed, buf)
+                        .map_err(|_| crate::Error::Decompress(compression))?;
+
+                    if written != buf.len() {
+                        return Err(crate::Error::Decompress(compression));
+                    }
+
+                    Self::decode_items(header, buf)
+                })
+            }
 
+            // TODO: decompress into scratch buffer
             #[cfg(feature = "miniz")]
             super::meta::CompressionType::Miniz(_) => {
-                miniz_oxide::inflate::decompress_to_vec(&bytes)
-                    .map_err(|_| crate::Error::Decompress(header.compression))?
+                let bytes = miniz_oxide::inflate::decompress_to_vec(bytes)
+                    .map_err(|_| crate::Error::Decompress(header.compression))?;
+
+                Self::decode_items(header, &bytes)
             }
-        };
-        let mut bytes = Cursor::new(bytes);
 
+            super::meta::CompressionType::Custom(tag) => {
+                let bytes = crate::codec::get(tag)?.decompress(bytes)?;
+                Self::decode_items(header, &bytes)
+            }
+        }
+    }
+
+    /// Deserializes the items of uncompressed block data.
+    fn decode_items(header: BlockHeader, mut bytes: &[u8]) -> crate::Result<Self> {
         // TODO: 3.0.0 varint?
         // Read number of items
         let item_count = bytes.read_u32::<BigEndian>()? as usize;
 
         // Deserialize each value
-        let mut items = Vec::with_capacity(item_count);
+        //
+        // NOTE: Every item takes at least 1 byte, so a corrupted
+        // item count cannot cause a huge allocation
+        let mut items = Vec::with_capacity(item_count.min(bytes.len()));
         for _ in 0..item_count {
             items.push(T::decode_from(&mut bytes)?);
         }
@@ -85,17 +153,64 @@ impl<T: Clone + Encode + Decode + ItemSize> Block<T> {
     pub fn from_file<R: std::io::Read + std::io::Seek>(
         reader: &mut R,
         offset: BlockOffset,
+        transform: Option<&KeyedTransform>,
     ) -> crate::Result<Self> {
         reader.seek(std::io::SeekFrom::Start(*offset))?;
-        Self::from_reader(reader)
+        Self::from_reader(reader, transform)
+    }
+
+    /// Reads a block at the given offset using positional reads,
+    /// leaving the file cursor untouched.
+    pub fn from_file_at(
+        file: &File,
+        offset: BlockOffset,
+        transform: Option<&KeyedTransform>,
+    ) -> crate::Result<Self> {
+        let mut header_bytes = [0u8; BlockHeader::serialized_len()];
+        read_exact_at(file, &mut header_bytes, *offset)?;
+
+        let header = BlockHeader::decode_from(&mut &header_bytes[..])?;
+        log::trace!("Got block header: {header:?}");
+
+        ScratchBuffer::Read.with(header.data_length as usize, |bytes| {
+            read_exact_at(file, bytes, *offset + BlockHeader::serialized_len() as u64)?;
+            Self::from_raw_parts(header, bytes, transform)
+        })
+    }
+
+    /// Reads the block at the given offset without decoding it, and checks
+    /// its data against the checksum in its header.
+    ///
+    /// Does not need the block transform, because the checksum covers the data as written.
+    pub fn checksum_matches_at(file: &File, offset: BlockOffset) -> crate::Result<bool> {
+        let mut header_bytes = [0u8; BlockHeader::serialized_len()];
+        read_exact_at(file, &mut header_bytes, *offset)?;
+
+        let header = BlockHeader::decode_from(&mut &header_bytes[..])?;
+
+        ScratchBuffer::Read.with(header.data_length as usize, |bytes| {
+            read_exact_at(file, bytes, *offset + BlockHeader::serialized_len() as u64)?;
+            Ok(header.checksum == Checksum::from_bytes(bytes))
+        })
     }
 
+    /// Serializes and compresses the items, and then applies the block transform (if any).
+    ///
+    /// The checksum covers the data as written, after the transform, so the integrity
+    /// of transformed (e.g. encrypted) blocks can be checked without decoding them.
     pub fn to_bytes_compressed(
         items: &[T],
         previous_block_offset: BlockOffset,
         compression: CompressionType,
+        transform: Option<&KeyedTransform>,
     ) -> crate::Result<(BlockHeader, Vec<u8>)> {
         let packed = Self::pack_items(items, compression)?;
+
+        let packed = match transform {
+            Some(transform) => transform.encode(&packed)?,
+            None => packed,
+        };
+
         let checksum = Checksum::from_bytes(&packed);
 
         let header = BlockHeader {
@@ -140,6 +255,8 @@ impl<T: Clone + Encode + Decode + ItemSize> Block<T> {
 
             #[cfg(feature = "miniz")]
             CompressionType::Miniz(level) => miniz_oxide::deflate::compress_to_vec(&buf, level),
+
+            CompressionType::Custom(tag) => crate::codec::get(tag)?.compress(&buf)?,
         })
     }
 }
@@ -151,7 +268,7 @@ mod tests {
         segment::value_block::ValueBlock,
         value::{InternalValue, ValueType},
     };
-    use std::io::Write;
+    use std::io::{Cursor, Write};
     use test_log::test;
 
     #[test]
@@ -167,7 +284,7 @@ mod tests {
         let mut serialized = Vec::new();
 
         let (header, data) =
-            ValueBlock::to_bytes_compressed(&items, BlockOffset(0), CompressionType::None)?;
+            ValueBlock::to_bytes_compressed(&items, BlockOffset(0), CompressionType::None, None)?;
 
         header.encode_into(&mut serialized)?;
         serialized.write_all(&data)?;
@@ -176,7 +293,7 @@ mod tests {
 
         // Deserialize from bytes
         let mut cursor = Cursor::new(serialized);
-        let block = ValueBlock::from_reader(&mut cursor)?;
+        let block = ValueBlock::from_reader(&mut cursor, None)?;
 
         assert_eq!(2, block.items.len());
         assert_eq!(block.items.first().cloned(), Some(item1));
@@ -187,6 +304,7 @@ mod tests {
                 &block.items,
                 block.header.previous_block_offset,
                 block.header.compression,
+                None,
             )?;
             Checksum::from_bytes(&data)
         };
@@ -195,6 +313,85 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn disk_block_deserialization_failure_item_count() -> crate::Result<()> {
+        // NOTE: Claims u32::MAX items, but only contains garbage
+        let data = [0xFF, 0xFF, 0xFF, 0xFF, 0, 1, 2];
+
+        let header = BlockHeader {
+            compression: CompressionType::None,
+            checksum: Checksum::from_raw(0),
+            previous_block_offset: BlockOffset(0),
+            data_length: data.len() as u32,
+            uncompressed_length: data.len() as u32,
+        };
+
+        let mut serialized = Vec::new();
+        header.encode_into(&mut serialized)?;
+        serialized.write_all(&data)?;
+
+        assert!(ValueBlock::from_reader(&mut Cursor::new(serialized), None).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "lz4")]
+    fn disk_block_deserialization_failure_lz4_size() -> crate::Result<()> {
+        let item =
+            InternalValue::from_components(vec![1, 2, 3], vec![4, 5, 6], 42, ValueType::Value);
+
+        let (mut header, data) =
+            ValueBlock::to_bytes_compressed(&[item], BlockOffset(0), CompressionType::Lz4, None)?;
+
+        let size = u32::from_le_bytes(data[..4].try_into().expect("should be 4 bytes"));
+
+        // NOTE: Claims a larger (and an absurdly large) decompressed size than there is
+        for bogus_size in [size + 1, u32::MAX] {
+            let mut data = data.clone();
+            data[..4].copy_from_slice(&bogus_size.to_le_bytes());
+            header.data_length = data.len() as u32;
+
+            let mut serialized = Vec::new();
+            header.encode_into(&mut serialized)?;
+            serialized.write_all(&data)?;
+
+            assert!(matches!(
+                ValueBlock::from_reader(&mut Cursor::new(serialized), None),
+                Err(crate::Error::Decompress(CompressionType::Lz4)),
+            ));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn disk_block_positional_read() -> crate::Result<()> {
+        let item1 =
+            InternalValue::from_components(vec![1, 2, 3], vec![4, 5, 6], 42, ValueType::Value);
+        let item2 =
+            InternalValue::from_components(vec![7, 8, 9], vec![10, 11, 12], 43, ValueType::Value);
+
+        let items = vec![item1.clone(), item2.clone()];
+
+        let (header, data) =
+            ValueBlock::to_bytes_compressed(&items, BlockOffset(0), CompressionType::None, None)?;
+
+        // NOTE: Write some padding in front of the block
+        let mut file = tempfile::tempfile()?;
+        file.write_all(&[0; 100])?;
+        header.encode_into(&mut file)?;
+        file.write_all(&data)?;
+
+        let block = ValueBlock::from_file_at(&file, BlockOffset(100), None)?;
+
+        assert_eq!(header, block.header);
+        assert_eq!(block.items.first().cloned(), Some(item1));
+        assert_eq!(block.items.get(1).cloned(), Some(item2));
+
+        Ok(())
+    }
+
     #[test]
     fn disk_block_deserialization_failure_checksum() -> crate::Result<()> {
         let item1 =
@@ -208,20 +405,21 @@ mod tests {
         let mut serialized = Vec::new();
 
         let (header, data) =
-            ValueBlock::to_bytes_compressed(&items, BlockOffset(0), CompressionType::None)?;
+            ValueBlock::to_bytes_compressed(&items, BlockOffset(0), CompressionType::None, None)?;
 
         header.encode_into(&mut serialized)?;
         serialized.write_all(&data)?;
 
         // Deserialize from bytes
         let mut cursor = Cursor::new(serialized);
-        let block = ValueBlock::from_reader(&mut cursor)?;
+        let block = ValueBlock::from_reader(&mut cursor, None)?;
 
         let checksum = {
             let (_, data) = ValueBlock::to_bytes_compressed(
                 &block.items,
                 block.header.previous_block_offset,
                 block.header.compression,
+                None,
             )?;
             Checksum::from_bytes(&data)
         };
@@ -0,0 +1,104 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use std::{cell::RefCell, thread::LocalKey};
+
+/// Scratch buffers that grew larger than this are released after use,
+/// so a single huge block does not pin memory for the lifetime of a thread
+const MAX_RETAINED_CAPACITY: usize = /* 1 MiB */ 1_024 * 1_024;
+
+thread_local! {
+    static READ_BUFFER: RefCell<Vec<u8>> = const { RefCell::new(Vec::new()) };
+    static DECOMPRESSION_BUFFER: RefCell<Vec<u8>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Kind of per-thread scratch buffer
+///
+/// Each kind is backed by its own buffer, so they can be nested.
+#[derive(Copy, Clone, Debug)]
+pub enum ScratchBuffer {
+    /// Holds the raw (possibly compressed) block data read from disk
+    Read,
+
+    /// Holds the decompressed block data
+    #[cfg_attr(not(feature = "lz4"), allow(dead_code))]
+    Decompression,
+}
+
+impl ScratchBuffer {
+    fn key(self) -> &'static LocalKey<RefCell<Vec<u8>>> {
+        match self {
+            Self::Read => &READ_BUFFER,
+            Self::Decompression => &DECOMPRESSION_BUFFER,
+        }
+    }
+
+    /// Runs `f` with a zeroed scratch buffer of `len` bytes.
+    ///
+    /// The buffer is reused across calls on the same thread,
+    /// which avoids an allocation per block read.
+    pub fn with<R>(self, len: usize, f: impl FnOnce(&mut [u8]) -> R) -> R {
+        self.key().with(|cell| {
+            let Ok(mut buf) = cell.try_borrow_mut() else {
+                // NOTE: Buffer is already in use further up the stack, fall back to allocating
+                return f(&mut vec![0; len]);
+            };
+
+            buf.clear();
+            buf.resize(len, 0);
+
+            let result = f(&mut buf);
+
+            if buf.capacity() > MAX_RETAINED_CAPACITY {
+                *buf = Vec::new();
+            }
+
+            result
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn scratch_buffer_reuse() {
+        let ptr = ScratchBuffer::Read.with(100, |buf| {
+            assert_eq!(100, buf.len());
+            buf.fill(1);
+            buf.as_ptr()
+        });
+
+        ScratchBuffer::Read.with(50, |buf| {
+            assert_eq!(50, buf.len());
+            assert!(buf.iter().all(|&x| x == 0));
+            assert_eq!(ptr, buf.as_ptr());
+        });
+    }
+
+    #[test]
+    fn scratch_buffer_nested() {
+        ScratchBuffer::Read.with(10, |outer| {
+            outer.fill(1);
+
+            ScratchBuffer::Read.with(10, |inner| {
+                assert_ne!(outer.as_ptr(), inner.as_ptr());
+                assert!(inner.iter().all(|&x| x == 0));
+            });
+
+            assert!(outer.iter().all(|&x| x == 1));
+        });
+    }
+
+    #[test]
+    fn scratch_buffer_release_large() {
+        ScratchBuffer::Decompression.with(MAX_RETAINED_CAPACITY + 1, |_| {});
+
+        DECOMPRESSION_BUFFER.with(|cell| {
+            assert_eq!(0, cell.borrow().capacity());
+        });
+    }
+}
@@ -1,7 +1,10 @@
 use super::{block_handle::KeyedBlockHandle, BlockIndex};
-use crate::segment::{
-    block_index::IndexBlock,
-    value_block::{BlockOffset, CachePolicy},
+use crate::{
+    coding::DecodeError,
+    segment::{
+        block_index::IndexBlock,
+        value_block::{BlockOffset, CachePolicy},
+    },
 };
 use std::{fs::File, io::Seek, path::Path};
 
@@ -25,6 +28,7 @@ impl FullBlockIndex {
         path: P,
         metadata: &crate::segment::meta::Metadata,
         offsets: &crate::segment::file_offsets::FileOffsets,
+        transform: Option<&crate::transform::KeyedTransform>,
     ) -> crate::Result<Self> {
         let path = path.as_ref();
         let cnt = metadata.index_block_count as usize;
@@ -37,21 +41,30 @@ impl FullBlockIndex {
         let mut file = File::open(path)?;
         file.seek(std::io::SeekFrom::Start(*offsets.index_block_ptr))?;
 
-        let mut block_handles = Vec::with_capacity(cnt);
+        let mut block_handles = Vec::new();
 
         for _ in 0..cnt {
-            let idx_block = IndexBlock::from_reader(&mut file)?.items;
+            let idx_block = IndexBlock::from_reader(&mut file, transform)?.items;
             // TODO: 1.80? IntoIter impl for Box<[T]>
             block_handles.extend(idx_block.into_vec());
         }
 
-        debug_assert!(!block_handles.is_empty());
+        // NOTE: Segments that only contain range tombstones have no data blocks
+        if block_handles.is_empty() && metadata.item_count > 0 {
+            return Err(crate::Error::Decode(DecodeError::InvalidHeader(
+                "FullBlockIndex",
+            )));
+        }
 
         Ok(Self(block_handles.into_boxed_slice()))
     }
 }
 
 impl BlockIndex for FullBlockIndex {
+    fn memory_usage(&self) -> usize {
+        super::handles_memory_usage(&self.0)
+    }
+
     fn get_lowest_block_containing_key(
         &self,
         key: &[u8],
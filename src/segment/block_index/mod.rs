@@ -10,14 +10,21 @@ pub mod writer;
 
 use super::{
     block::Block,
+    lazy::Lazy,
     value_block::{BlockOffset, CachePolicy},
 };
+use crate::compare::{key_le, key_lt};
 use block_handle::KeyedBlockHandle;
 use full_index::FullBlockIndex;
 use two_level_index::TwoLevelBlockIndex;
 
 pub type IndexBlock = Block<KeyedBlockHandle>;
 
+/// Returns the amount of heap memory used by a list of block handles
+fn handles_memory_usage(handles: &[KeyedBlockHandle]) -> usize {
+    std::mem::size_of_val(handles) + handles.iter().map(|x| x.end_key.len()).sum::<usize>()
+}
+
 #[allow(clippy::module_name_repetitions)]
 pub trait KeyedBlockIndex {
     /// Gets the lowest block handle that may contain the given item
@@ -44,7 +51,7 @@ impl KeyedBlockIndex for [KeyedBlockHandle] {
         key: &[u8],
         _: CachePolicy,
     ) -> crate::Result<Option<&KeyedBlockHandle>> {
-        let idx = self.partition_point(|x| &*x.end_key < key);
+        let idx = self.partition_point(|x| key_lt(&x.end_key, key));
         Ok(self.get(idx))
     }
 
@@ -53,7 +60,7 @@ impl KeyedBlockIndex for [KeyedBlockHandle] {
         key: &[u8],
         _: CachePolicy,
     ) -> crate::Result<Option<&KeyedBlockHandle>> {
-        let idx = self.partition_point(|x| &*x.end_key <= key);
+        let idx = self.partition_point(|x| key_le(&x.end_key, key));
 
         if idx == 0 {
             return Ok(self.first());
@@ -98,6 +105,10 @@ pub trait BlockIndex {
 
     /// Returns a handle to the last block
     fn get_last_block_handle(&self, cache_policy: CachePolicy) -> crate::Result<BlockOffset>;
+
+    /// Returns the amount of heap memory the index retains,
+    /// not counting index blocks that live in the block cache.
+    fn memory_usage(&self) -> usize;
 }
 
 /// The block index stores references to the positions of blocks on a file and their size
@@ -123,6 +134,54 @@ pub trait BlockIndex {
 pub enum BlockIndexImpl {
     Full(FullBlockIndex),
     TwoLevel(TwoLevelBlockIndex),
+    Lazy(LazyBlockIndex),
+}
+
+impl BlockIndexImpl {
+    /// Returns the loaded block index, loading it first if it is lazy.
+    pub fn resolve(&self) -> crate::Result<&Self> {
+        match self {
+            Self::Lazy(lazy) => lazy.0.get().map(|index| &**index),
+            _ => Ok(self),
+        }
+    }
+}
+
+/// Block index that is loaded from the segment file on first access,
+/// see [`crate::Config::lazy_segment_open`]
+pub struct LazyBlockIndex(pub Lazy<Box<BlockIndexImpl>>);
+
+impl BlockIndex for LazyBlockIndex {
+    fn get_lowest_block_containing_key(
+        &self,
+        key: &[u8],
+        cache_policy: CachePolicy,
+    ) -> crate::Result<Option<BlockOffset>> {
+        self.0
+            .get()?
+            .get_lowest_block_containing_key(key, cache_policy)
+    }
+
+    fn get_last_block_containing_key(
+        &self,
+        key: &[u8],
+        cache_policy: CachePolicy,
+    ) -> crate::Result<Option<BlockOffset>> {
+        self.0
+            .get()?
+            .get_last_block_containing_key(key, cache_policy)
+    }
+
+    fn get_last_block_handle(&self, cache_policy: CachePolicy) -> crate::Result<BlockOffset> {
+        self.0.get()?.get_last_block_handle(cache_policy)
+    }
+
+    fn memory_usage(&self) -> usize {
+        self.0
+            .get_loaded()
+            .map(|index| index.memory_usage())
+            .unwrap_or_default()
+    }
 }
 
 #[cfg(test)]
@@ -3,9 +3,12 @@
 // (found in the LICENSE-* files in the repository)
 
 use super::{block_handle::KeyedBlockHandle, KeyedBlockIndex};
-use crate::segment::{
-    block_index::IndexBlock,
-    value_block::{BlockOffset, CachePolicy},
+use crate::{
+    coding::DecodeError,
+    segment::{
+        block_index::IndexBlock,
+        value_block::{BlockOffset, CachePolicy},
+    },
 };
 use std::{fs::File, path::Path};
 
@@ -19,18 +22,25 @@ pub struct TopLevelIndex(Box<[KeyedBlockHandle]>);
 impl TopLevelIndex {
     pub fn from_file<P: AsRef<Path>>(
         path: P,
-        _: &crate::segment::meta::Metadata,
+        metadata: &crate::segment::meta::Metadata,
         tli_ptr: BlockOffset,
+        transform: Option<&crate::transform::KeyedTransform>,
     ) -> crate::Result<Self> {
         let path = path.as_ref();
 
         log::trace!("reading TLI from {path:?} at tli_ptr={tli_ptr}");
 
         let mut file = File::open(path)?;
-        let items = IndexBlock::from_file(&mut file, tli_ptr)?.items;
+        let items = IndexBlock::from_file(&mut file, tli_ptr, transform)?.items;
 
         log::trace!("loaded TLI ({path:?}): {items:?}");
-        debug_assert!(!items.is_empty());
+
+        // NOTE: Segments that only contain range tombstones have no data blocks
+        if items.is_empty() && metadata.item_count > 0 {
+            return Err(crate::Error::Decode(DecodeError::InvalidHeader(
+                "TopLevelIndex",
+            )));
+        }
 
         Ok(Self::from_boxed_slice(items))
     }
@@ -54,6 +64,12 @@ impl TopLevelIndex {
     pub fn iter(&self) -> impl Iterator<Item = &KeyedBlockHandle> {
         self.0.iter()
     }
+
+    /// Returns the amount of heap memory used by the index.
+    #[must_use]
+    pub fn memory_usage(&self) -> usize {
+        super::handles_memory_usage(&self.0)
+    }
 }
 
 impl KeyedBlockIndex for TopLevelIndex {
@@ -11,6 +11,7 @@ use crate::{
     block_cache::BlockCache,
     descriptor_table::FileDescriptorTable,
     segment::{meta::Metadata, value_block::BlockOffset},
+    transform::KeyedTransform,
 };
 use std::{path::Path, sync::Arc};
 
@@ -54,9 +55,16 @@ pub struct TwoLevelBlockIndex {
     /// To find a reference to a segment block, first the level-0 index needs to be checked,
     /// then the corresponding index block needs to be loaded, which contains the wanted disk block handle.
     index_block_fetcher: IndexBlockFetcher,
+
+    /// Block transform the segment was written with
+    transform: Option<KeyedTransform>,
 }
 
 impl BlockIndex for TwoLevelBlockIndex {
+    fn memory_usage(&self) -> usize {
+        self.top_level_index.memory_usage()
+    }
+
     fn get_lowest_block_containing_key(
         &self,
         key: &[u8],
@@ -177,22 +185,25 @@ impl TwoLevelBlockIndex {
                 .access(&self.segment_id)?
                 .expect("should acquire file handle");
 
-            let block = IndexBlock::from_file(
-                &mut *file_guard.file.lock().expect("lock is poisoned"),
-                offset,
-            )
-            .map_err(|e| {
-                log::error!(
-                    "Failed to load index block {:?}/{:?}: {e:?}",
-                    self.segment_id,
-                    offset
-                );
-                e
-            })?;
+            let block = IndexBlock::from_file_at(&file_guard.file, offset, self.transform.as_ref())
+                .map_err(|e| {
+                    log::error!(
+                        "Failed to load index block {:?}/{:?}: {e:?}",
+                        self.segment_id,
+                        offset
+                    );
+                    e
+                })?;
             // TODO: ^ inspect_err instead: 1.76
 
             drop(file_guard);
 
+            if block.items.is_empty() {
+                return Err(crate::Error::Decode(
+                    crate::coding::DecodeError::InvalidHeader("IndexBlock"),
+                ));
+            }
+
             let block = Arc::new(block);
 
             if cache_policy == CachePolicy::Write {
@@ -214,6 +225,7 @@ impl TwoLevelBlockIndex {
             segment_id,
             index_block_fetcher: index_block_index,
             top_level_index: TopLevelIndex::from_boxed_slice(Box::default()),
+            transform: None,
         }
     }
 
@@ -224,17 +236,20 @@ impl TwoLevelBlockIndex {
         segment_id: GlobalSegmentId,
         descriptor_table: Arc<FileDescriptorTable>,
         block_cache: Arc<BlockCache>,
+        transform: Option<KeyedTransform>,
     ) -> crate::Result<Self> {
         let file_path = path.as_ref();
         log::trace!("Reading block index from {file_path:?}");
 
-        let top_level_index = TopLevelIndex::from_file(file_path, metadata, tli_ptr)?;
+        let top_level_index =
+            TopLevelIndex::from_file(file_path, metadata, tli_ptr, transform.as_ref())?;
 
         Ok(Self {
             descriptor_table,
             segment_id,
             top_level_index,
             index_block_fetcher: IndexBlockFetcher(block_cache),
+            transform,
         })
     }
 }
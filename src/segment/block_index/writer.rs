@@ -8,6 +8,7 @@ use crate::{
     segment::{
         block::header::Header as BlockHeader, meta::CompressionType, value_block::BlockOffset,
     },
+    transform::KeyedTransform,
     value::UserKey,
 };
 use std::{
@@ -24,6 +25,7 @@ pub struct Writer {
 
     block_size: u32,
     compression: CompressionType,
+    transform: Option<KeyedTransform>,
 
     buffer_size: u32,
 
@@ -42,6 +44,7 @@ impl Writer {
             buffer_size: 0,
             block_size,
             compression: CompressionType::None,
+            transform: None,
             block_handles: Vec::new(),
             tli_pointers: Vec::new(),
             block_count: 0,
@@ -54,12 +57,19 @@ impl Writer {
         self
     }
 
+    #[must_use]
+    pub fn use_transform(mut self, transform: Option<KeyedTransform>) -> Self {
+        self.transform = transform;
+        self
+    }
+
     fn write_block(&mut self) -> crate::Result<()> {
         // Write to file
         let (header, data) = IndexBlock::to_bytes_compressed(
             &self.block_handles,
             self.prev_pos.0,
             self.compression,
+            self.transform.as_ref(),
         )?;
 
         header.encode_into(&mut self.write_buffer)?;
@@ -134,16 +144,20 @@ impl Writer {
         }
 
         // Write to file
-        let (header, data) =
-            IndexBlock::to_bytes_compressed(&self.tli_pointers, BlockOffset(0), self.compression)?;
+        let (header, data) = IndexBlock::to_bytes_compressed(
+            &self.tli_pointers,
+            BlockOffset(0),
+            self.compression,
+            self.transform.as_ref(),
+        )?;
 
         header.encode_into(block_file_writer)?;
         block_file_writer.write_all(&data)?;
 
         let bytes_written = BlockHeader::serialized_len() + data.len();
 
+        // NOTE: The segment writer fsyncs the file once it is finished
         block_file_writer.flush()?;
-        block_file_writer.get_mut().sync_all()?;
 
         log::trace!(
             "Written top level index, with {} pointers ({} bytes)",
@@ -0,0 +1,113 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use super::{meta::CompressionType, value_block::BlockOffset, value_block::ValueBlock};
+use crate::{transform::KeyedTransform, InternalValue};
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{BufReader, Seek},
+    path::Path,
+};
+
+/// Describes a data block of a segment
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct DataBlockInfo {
+    /// File offset of the block
+    pub offset: BlockOffset,
+
+    /// File offset of the previous block
+    pub previous_block_offset: BlockOffset,
+
+    /// Compression type used
+    pub compression: CompressionType,
+
+    /// Size of the block on disk (excluding the header)
+    pub compressed_size: u32,
+
+    /// Size of the block after decompression
+    pub uncompressed_size: u32,
+
+    /// Amount of items in the block
+    pub item_count: usize,
+}
+
+/// Item emitted by a [`SegmentDump`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DumpItem {
+    /// Start of a data block, followed by its entries
+    Block(DataBlockInfo),
+
+    /// Raw entry, including its seqno and value type
+    Entry(InternalValue),
+}
+
+/// Debug iterator over the raw contents of a single segment file
+///
+/// Yields every data block (in file order), each followed by its raw entries,
+/// including shadowed versions and tombstones.
+pub struct SegmentDump {
+    reader: BufReader<File>,
+
+    block_count: usize,
+    read_count: usize,
+
+    buffer: VecDeque<InternalValue>,
+
+    transform: Option<KeyedTransform>,
+}
+
+impl SegmentDump {
+    pub(crate) fn new<P: AsRef<Path>>(
+        path: P,
+        block_count: usize,
+        transform: Option<KeyedTransform>,
+    ) -> crate::Result<Self> {
+        let reader = BufReader::with_capacity(8 * 4_096, File::open(path)?);
+
+        Ok(Self {
+            reader,
+            block_count,
+            read_count: 0,
+            buffer: VecDeque::new(),
+            transform,
+        })
+    }
+}
+
+impl Iterator for SegmentDump {
+    type Item = crate::Result<DumpItem>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(item) = self.buffer.pop_front() {
+            return Some(Ok(DumpItem::Entry(item)));
+        }
+
+        if self.read_count >= self.block_count {
+            return None;
+        }
+
+        let offset = fail_iter!(self.reader.stream_position().map_err(crate::Error::from));
+        let block = fail_iter!(ValueBlock::from_reader(
+            &mut self.reader,
+            self.transform.as_ref()
+        ));
+
+        self.read_count += 1;
+
+        let info = DataBlockInfo {
+            offset: BlockOffset(offset),
+            previous_block_offset: block.header.previous_block_offset,
+            compression: block.header.compression,
+            compressed_size: block.header.data_length,
+            uncompressed_size: block.header.uncompressed_length,
+            item_count: block.items.len(),
+        };
+
+        self.buffer.extend(block.items.into_vec());
+
+        Some(Ok(DumpItem::Block(info)))
+    }
+}
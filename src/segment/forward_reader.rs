@@ -7,8 +7,8 @@ use super::{
     value_block_consumer::ValueBlockConsumer,
 };
 use crate::{
-    descriptor_table::FileDescriptorTable, segment::block::header::Header, value::InternalValue,
-    BlockCache, GlobalSegmentId,
+    descriptor_table::FileDescriptorTable, segment::block::header::Header, statistics::Statistics,
+    transform::KeyedTransform, value::InternalValue, BlockCache, GlobalSegmentId,
 };
 
 /// Segment forward reader specialized for point reads
@@ -17,6 +17,8 @@ pub struct ForwardReader<'a> {
 
     descriptor_table: &'a FileDescriptorTable,
     block_cache: &'a BlockCache,
+    statistics: &'a Statistics,
+    transform: Option<&'a KeyedTransform>,
 
     data_block_boundary: BlockOffset,
 
@@ -35,12 +37,16 @@ impl<'a> ForwardReader<'a> {
         descriptor_table: &'a FileDescriptorTable,
         segment_id: GlobalSegmentId,
         block_cache: &'a BlockCache,
+        statistics: &'a Statistics,
+        transform: Option<&'a KeyedTransform>,
         lo_block_offset: BlockOffset,
     ) -> Self {
         Self {
             descriptor_table,
             segment_id,
             block_cache,
+            statistics,
+            transform,
 
             data_block_boundary,
 
@@ -60,9 +66,11 @@ impl<'a> ForwardReader<'a> {
         let block = ValueBlock::load_by_block_handle(
             self.descriptor_table,
             self.block_cache,
+            self.statistics,
             self.segment_id,
             offset,
             self.cache_policy,
+            self.transform,
         )?;
 
         // Truncate as many items as possible
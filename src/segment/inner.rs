@@ -2,9 +2,19 @@
 // This source code is licensed under both the Apache 2.0 and MIT License
 // (found in the LICENSE-* files in the repository)
 
-use super::{block_index::BlockIndexImpl, file_offsets::FileOffsets, meta::Metadata};
-use crate::{block_cache::BlockCache, descriptor_table::FileDescriptorTable, tree::inner::TreeId};
-use std::sync::Arc;
+use super::{
+    access_stats::AccessStats, block_index::BlockIndexImpl, file_offsets::FileOffsets,
+    key_sketch::KeySketch, lazy::Lazy, meta::Metadata, value_block::BlockOffset,
+};
+use crate::{
+    block_cache::BlockCache, descriptor_table::FileDescriptorTable,
+    range_tombstone::RangeTombstoneIndex, statistics::Statistics, transform::KeyedTransform,
+    tree::inner::TreeId,
+};
+use std::{
+    path::PathBuf,
+    sync::{Arc, OnceLock},
+};
 
 pub struct Inner {
     pub(crate) tree_id: TreeId,
@@ -28,7 +38,49 @@ pub struct Inner {
     #[doc(hidden)]
     pub block_cache: Arc<BlockCache>,
 
+    /// Statistics of the tree the segment belongs to
+    pub(crate) statistics: Arc<Statistics>,
+
     /// Bloom filter
     #[doc(hidden)]
-    pub bloom_filter: Option<crate::bloom::BloomFilter>,
+    pub bloom_filter: Lazy<Option<crate::bloom::AnyFilter>>,
+
+    /// Sketch of the user keys, for distinct key estimation
+    pub(crate) key_sketch: Lazy<Option<KeySketch>>,
+
+    /// Position of the key sketch, or 0 if the segment has none
+    pub(crate) key_sketch_ptr: BlockOffset,
+
+    /// Range tombstones of the segment, ordered by their start key
+    pub(crate) range_tombstones: Lazy<RangeTombstoneIndex>,
+
+    /// Block transform the segment was written with
+    pub(crate) transform: Option<KeyedTransform>,
+
+    /// Set once the segment has been removed from the tree
+    ///
+    /// Readers may still hold a level view that references the segment,
+    /// so its file is only deleted when the last reference is dropped.
+    pub(crate) deleted_path: OnceLock<PathBuf>,
+
+    /// Access counters, see [`Segment::access_stats`]
+    ///
+    /// [`Segment::access_stats`]: super::Segment::access_stats
+    pub(crate) access: AccessStats,
+}
+
+impl Drop for Inner {
+    fn drop(&mut self) {
+        if let Some(path) = self.deleted_path.get() {
+            log::trace!("Removing old segment at {}", path.display());
+
+            if let Err(e) = std::fs::remove_file(path) {
+                log::error!("Failed to cleanup file of deleted segment: {e:?}");
+            }
+
+            log::trace!("Closing file handles for segment data file");
+            self.descriptor_table
+                .remove((self.tree_id, self.metadata.id).into());
+        }
+    }
 }
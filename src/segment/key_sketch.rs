@@ -0,0 +1,165 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::coding::{Decode, DecodeError, Encode, EncodeError};
+use byteorder::{ReadBytesExt, WriteBytesExt};
+use std::io::{Read, Write};
+
+/// Amount of bits of the hash that select the register
+const PRECISION: u8 = 10;
+
+/// Amount of registers (1 KiB), which results in a standard error of about 3%
+const REGISTER_COUNT: usize = 1 << PRECISION;
+
+/// `HyperLogLog` sketch of the user keys of a segment
+///
+/// Sketches of multiple segments can be merged to estimate the amount
+/// of distinct keys across segments, which the sum of
+/// [`crate::segment::meta::Metadata::key_count`] does not account for.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct KeySketch(Box<[u8]>);
+
+impl Default for KeySketch {
+    fn default() -> Self {
+        Self(vec![0; REGISTER_COUNT].into_boxed_slice())
+    }
+}
+
+impl KeySketch {
+    /// Adds a key to the sketch.
+    pub fn insert(&mut self, key: &[u8]) {
+        self.insert_hash(xxhash_rust::xxh3::xxh3_64(key));
+    }
+
+    fn insert_hash(&mut self, hash: u64) {
+        // NOTE: The register index is at most REGISTER_COUNT - 1
+        #[allow(clippy::cast_possible_truncation)]
+        let idx = (hash >> (64 - PRECISION)) as usize;
+
+        // NOTE: Set a guard bit, so the rank is bounded if the remaining bits are all zero
+        let rest = (hash << PRECISION) | (1 << (PRECISION - 1));
+
+        // NOTE: The rank is at most 64 - PRECISION + 1
+        #[allow(clippy::cast_possible_truncation)]
+        let rank = rest.leading_zeros() as u8 + 1;
+
+        if let Some(register) = self.0.get_mut(idx) {
+            *register = (*register).max(rank);
+        }
+    }
+
+    /// Merges another sketch into this one, so it estimates the union of both.
+    pub fn merge(&mut self, other: &Self) {
+        for (register, other) in self.0.iter_mut().zip(other.0.iter()) {
+            *register = (*register).max(*other);
+        }
+    }
+
+    /// Returns the estimated amount of distinct keys.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss, clippy::naive_bytecount)]
+    pub fn estimate(&self) -> u64 {
+        let m = REGISTER_COUNT as f64;
+        let alpha = 0.7213 / (1.0 + 1.079 / m);
+
+        let sum = self
+            .0
+            .iter()
+            .map(|&register| 2f64.powi(-i32::from(register)))
+            .sum::<f64>();
+
+        let raw = alpha * m * m / sum;
+
+        let zeros = self.0.iter().filter(|&&register| register == 0).count();
+
+        // NOTE: Use linear counting for small cardinalities,
+        // where the raw estimate is heavily biased
+        let estimate = if raw <= 2.5 * m && zeros > 0 {
+            m * (m / zeros as f64).ln()
+        } else {
+            raw
+        };
+
+        // NOTE: The estimate is always positive and finite
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let estimate = estimate.round() as u64;
+
+        estimate
+    }
+}
+
+impl Encode for KeySketch {
+    fn encode_into<W: Write>(&self, writer: &mut W) -> Result<(), EncodeError> {
+        writer.write_u8(PRECISION)?;
+        writer.write_all(&self.0)?;
+        Ok(())
+    }
+}
+
+impl Decode for KeySketch {
+    fn decode_from<R: Read>(reader: &mut R) -> Result<Self, DecodeError> {
+        let precision = reader.read_u8()?;
+
+        if precision != PRECISION {
+            return Err(DecodeError::InvalidTag(("KeySketchPrecision", precision)));
+        }
+
+        let mut registers = vec![0; REGISTER_COUNT];
+        reader.read_exact(&mut registers)?;
+
+        Ok(Self(registers.into_boxed_slice()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn key_sketch_estimate() {
+        let mut sketch = KeySketch::default();
+        assert_eq!(0, sketch.estimate());
+
+        for x in 0..10_000u64 {
+            sketch.insert(&x.to_be_bytes());
+
+            // NOTE: Duplicates are not counted
+            sketch.insert(&x.to_be_bytes());
+        }
+
+        let estimate = sketch.estimate();
+        assert!((9_000..=11_000).contains(&estimate), "{estimate}");
+    }
+
+    #[test]
+    fn key_sketch_merge() {
+        let mut a = KeySketch::default();
+        let mut b = KeySketch::default();
+
+        for x in 0..1_000u64 {
+            a.insert(&x.to_be_bytes());
+        }
+
+        for x in 500..1_500u64 {
+            b.insert(&x.to_be_bytes());
+        }
+
+        a.merge(&b);
+
+        let estimate = a.estimate();
+        assert!((1_400..=1_600).contains(&estimate), "{estimate}");
+    }
+
+    #[test]
+    fn key_sketch_roundtrip() -> crate::Result<()> {
+        let mut sketch = KeySketch::default();
+        sketch.insert(b"a");
+
+        let bytes = sketch.encode_into_vec();
+        assert_eq!(sketch, KeySketch::decode_from(&mut &bytes[..])?);
+
+        Ok(())
+    }
+}
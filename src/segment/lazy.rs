@@ -0,0 +1,95 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use std::sync::OnceLock;
+
+type Loader<T> = Box<dyn Fn() -> crate::Result<T> + Send + Sync>;
+
+/// Part of a segment (e.g. its filter) that is loaded from the segment file on first access
+pub enum Lazy<T> {
+    /// Value that was available up front
+    Loaded(T),
+
+    /// Value that is loaded on first access
+    Deferred { value: OnceLock<T>, load: Loader<T> },
+}
+
+impl<T> Lazy<T> {
+    /// Defers loading the value until it is first accessed.
+    pub fn new<F: Fn() -> crate::Result<T> + Send + Sync + 'static>(load: F) -> Self {
+        Self::Deferred {
+            value: OnceLock::new(),
+            load: Box::new(load),
+        }
+    }
+
+    /// Returns the value, loading it if needed.
+    ///
+    /// If loading fails, the next access tries again.
+    pub fn get(&self) -> crate::Result<&T> {
+        match self {
+            Self::Loaded(value) => Ok(value),
+            Self::Deferred { value, load } => {
+                if let Some(value) = value.get() {
+                    return Ok(value);
+                }
+
+                // NOTE: Concurrent first accesses may both load the value, only one of them is kept
+                let loaded = load()?;
+                Ok(value.get_or_init(|| loaded))
+            }
+        }
+    }
+
+    /// Returns the value, if it was already loaded.
+    pub fn get_loaded(&self) -> Option<&T> {
+        match self {
+            Self::Loaded(value) => Some(value),
+            Self::Deferred { value, .. } => value.get(),
+        }
+    }
+}
+
+impl<T> From<T> for Lazy<T> {
+    fn from(value: T) -> Self {
+        Self::Loaded(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+    use test_log::test;
+
+    #[test]
+    fn lazy_load_once() -> crate::Result<()> {
+        let loads = Arc::new(AtomicUsize::default());
+
+        let lazy = Lazy::new({
+            let loads = loads.clone();
+            move || {
+                // NOTE: Fail the first load
+                if loads.fetch_add(1, Ordering::Relaxed) == 0 {
+                    return Err(crate::Error::Unrecoverable);
+                }
+                Ok(5)
+            }
+        });
+        assert_eq!(None, lazy.get_loaded());
+
+        assert!(lazy.get().is_err());
+        assert_eq!(5, *lazy.get()?);
+        assert_eq!(5, *lazy.get()?);
+        assert_eq!(Some(&5), lazy.get_loaded());
+        assert_eq!(2, loads.load(Ordering::Relaxed));
+
+        assert_eq!(Some(&3), Lazy::from(3).get_loaded());
+
+        Ok(())
+    }
+}
@@ -8,6 +8,7 @@ use std::io::{Read, Write};
 
 /// Compression algorithm to use.
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 #[allow(clippy::module_name_repetitions)]
 pub enum CompressionType {
     /// No compression
@@ -33,6 +34,11 @@ pub enum CompressionType {
     /// - 10 may save even more space than 9, but the speed trade off may not be worth it
     #[cfg(feature = "miniz")]
     Miniz(u8),
+
+    /// Custom codec, identified by its tag
+    ///
+    /// The codec needs to be registered using [`crate::register_compression_codec`].
+    Custom(u8),
 }
 
 impl Encode for CompressionType {
@@ -56,6 +62,11 @@ impl Encode for CompressionType {
                 writer.write_u8(2)?;
                 writer.write_u8(*level)?;
             }
+
+            Self::Custom(tag) => {
+                writer.write_u8(3)?;
+                writer.write_u8(*tag)?;
+            }
         };
 
         Ok(())
@@ -67,25 +78,24 @@ impl Decode for CompressionType {
         let tag = reader.read_u8()?;
 
         match tag {
-            0 => {
-                assert_eq!(0, reader.read_u8()?, "Invalid compression");
-                Ok(Self::None)
-            }
+            0 => match reader.read_u8()? {
+                0 => Ok(Self::None),
+                _ => Err(DecodeError::InvalidHeader("CompressionType")),
+            },
 
             #[cfg(feature = "lz4")]
-            1 => {
-                assert_eq!(0, reader.read_u8()?, "Invalid compression");
-                Ok(Self::Lz4)
-            }
+            1 => match reader.read_u8()? {
+                0 => Ok(Self::Lz4),
+                _ => Err(DecodeError::InvalidHeader("CompressionType")),
+            },
 
             #[cfg(feature = "miniz")]
-            2 => {
-                let level = reader.read_u8()?;
-
-                assert!(level <= 10, "invalid miniz compression level");
+            2 => match reader.read_u8()? {
+                level @ 0..=10 => Ok(Self::Miniz(level)),
+                level => Err(DecodeError::InvalidTag(("MinizLevel", level))),
+            },
 
-                Ok(Self::Miniz(level))
-            }
+            3 => Ok(Self::Custom(reader.read_u8()?)),
 
             tag => Err(DecodeError::InvalidTag(("CompressionType", tag))),
         }
@@ -105,6 +115,8 @@ impl std::fmt::Display for CompressionType {
 
                 #[cfg(feature = "miniz")]
                 Self::Miniz(_) => "miniz",
+
+                Self::Custom(_) => "custom",
             }
         )
     }
@@ -121,6 +133,31 @@ mod tests {
         assert_eq!(2, serialized.len());
     }
 
+    #[test]
+    fn compression_serialize_custom() -> crate::Result<()> {
+        let serialized = CompressionType::Custom(42).encode_into_vec();
+        assert_eq!(2, serialized.len());
+
+        assert_eq!(
+            CompressionType::Custom(42),
+            CompressionType::decode_from(&mut &serialized[..])?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn compression_deserialize_invalid() {
+        assert!(matches!(
+            CompressionType::decode_from(&mut &[0, 1][..]),
+            Err(DecodeError::InvalidHeader("CompressionType"))
+        ));
+        assert!(matches!(
+            CompressionType::decode_from(&mut &[4, 0][..]),
+            Err(DecodeError::InvalidTag(("CompressionType", 4)))
+        ));
+    }
+
     #[cfg(feature = "lz4")]
     mod lz4 {
         use super::*;
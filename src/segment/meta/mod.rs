@@ -4,13 +4,13 @@
 
 mod compression;
 mod table_type;
+mod temperature;
 
 use super::writer::Writer;
 use crate::{
     coding::{Decode, DecodeError, Encode, EncodeError},
     file::MAGIC_BYTES,
     key_range::KeyRange,
-    time::unix_timestamp,
     value::SeqNo,
 };
 use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
@@ -18,11 +18,12 @@ use std::{
     io::{Cursor, Read, Write},
     path::Path,
 };
-pub use {compression::CompressionType, table_type::TableType};
+pub use {compression::CompressionType, table_type::TableType, temperature::Temperature};
 
 pub type SegmentId = u64;
 
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub struct Metadata {
     /// Segment ID
     pub id: SegmentId,
@@ -75,6 +76,26 @@ pub struct Metadata {
 
     /// Key range
     pub key_range: KeyRange,
+
+    /// Access temperature hint
+    ///
+    /// Stored in the segment file trailer instead of the metadata block,
+    /// so segments written before temperatures existed read as `None`.
+    pub temperature: Option<Temperature>,
+
+    /// Expected false positive rate of the filter, in parts per billion
+    ///
+    /// Stored in the segment file trailer, so segments written
+    /// before it was recorded read as `None`.
+    pub filter_fp_rate_ppb: Option<u32>,
+
+    /// Seqno of all items of an ingested segment
+    ///
+    /// Ingested segment files are written without knowing their seqno, so it
+    /// is applied when reading. Stored in the segment file trailer, together
+    /// with the segment ID, so a segment file can be adopted into a tree
+    /// without rewriting its blocks.
+    pub global_seqno: Option<SeqNo>,
 }
 
 impl Encode for Metadata {
@@ -176,6 +197,11 @@ impl Decode for Metadata {
             seqnos: (seqno_min, seqno_max),
 
             key_range,
+
+            // NOTE: Read from the trailer
+            temperature: None,
+            filter_fp_rate_ppb: None,
+            global_seqno: None,
         })
     }
 }
@@ -190,9 +216,9 @@ impl Metadata {
 
             // NOTE: Using seconds is not granular enough
             // But because millis already returns u128, might as well use micros :)
-            created_at: unix_timestamp().as_micros(),
+            created_at: writer.clock.now().as_micros(),
 
-            compression: CompressionType::None,
+            compression: writer.compression,
             table_type: TableType::Block,
 
             // NOTE: Truncation is OK - even with the smallest block size (1 KiB), 4 billion blocks would be 4 TB
@@ -226,12 +252,24 @@ impl Metadata {
                     .expect("should have written at least 1 item"),
             )),
 
-            seqnos: (writer.meta.lowest_seqno, writer.meta.highest_seqno),
+            seqnos: writer.global_seqno.map_or(
+                (writer.meta.lowest_seqno, writer.meta.highest_seqno),
+                |global_seqno| (global_seqno, global_seqno),
+            ),
 
             tombstone_count: writer.meta.tombstone_count as u64,
 
-            // TODO: #2 https://github.com/fjall-rs/lsm-tree/issues/2
-            range_tombstone_count: 0,
+            range_tombstone_count: writer.range_tombstones.len() as u64,
+
+            temperature: writer.temperature,
+
+            // NOTE: The rate is in [0, 1], so it always fits
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            filter_fp_rate_ppb: writer
+                .filter_fp_rate
+                .map(|fpr| ((fpr * 1_000_000_000.0).round() as u32).max(1)),
+
+            global_seqno: writer.global_seqno,
         })
     }
 
@@ -269,6 +307,9 @@ mod tests {
             range_tombstone_count: 0,
             uncompressed_size: 0,
             seqnos: (0, 5),
+            temperature: None,
+            filter_fp_rate_ppb: None,
+            global_seqno: None,
         };
 
         let bytes = metadata.encode_into_vec();
@@ -3,6 +3,7 @@
 // (found in the LICENSE-* files in the repository)
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub enum TableType {
     Block,
 }
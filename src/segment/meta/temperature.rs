@@ -0,0 +1,92 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+/// Hint of how frequently the data of a segment is expected to be accessed
+///
+/// Flushes tag segments as hot, compactions derive the temperature
+/// from the destination level and the age of the data, see [`crate::Config::cold_data_age`].
+///
+/// The temperature is stored in the segment file, so cache admission, file placement
+/// and read-ahead can differentiate between segments.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum Temperature {
+    /// Recently written data in the first levels
+    Hot,
+
+    /// Data in the middle levels
+    Warm,
+
+    /// Data in the last level, or data that is older than the cold data age
+    Cold,
+}
+
+impl Temperature {
+    /// Returns the temperature of segments in the given level.
+    #[must_use]
+    pub fn for_level(level: u8, level_count: u8) -> Self {
+        if level == 0 {
+            Self::Hot
+        } else if level + 1 >= level_count {
+            Self::Cold
+        } else if level == 1 {
+            Self::Hot
+        } else {
+            Self::Warm
+        }
+    }
+}
+
+impl From<Temperature> for u8 {
+    fn from(val: Temperature) -> Self {
+        match val {
+            Temperature::Hot => 1,
+            Temperature::Warm => 2,
+            Temperature::Cold => 3,
+        }
+    }
+}
+
+impl TryFrom<u8> for Temperature {
+    type Error = ();
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(Self::Hot),
+            2 => Ok(Self::Warm),
+            3 => Ok(Self::Cold),
+            _ => Err(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn temperature_for_level() {
+        assert_eq!(Temperature::Hot, Temperature::for_level(0, 7));
+        assert_eq!(Temperature::Hot, Temperature::for_level(1, 7));
+        assert_eq!(Temperature::Warm, Temperature::for_level(2, 7));
+        assert_eq!(Temperature::Warm, Temperature::for_level(5, 7));
+        assert_eq!(Temperature::Cold, Temperature::for_level(6, 7));
+
+        assert_eq!(Temperature::Hot, Temperature::for_level(0, 1));
+        assert_eq!(Temperature::Cold, Temperature::for_level(1, 2));
+    }
+
+    #[test]
+    fn temperature_roundtrip() {
+        for temperature in [Temperature::Hot, Temperature::Warm, Temperature::Cold] {
+            assert_eq!(
+                Ok(temperature),
+                Temperature::try_from(u8::from(temperature))
+            );
+        }
+
+        assert_eq!(Err(()), Temperature::try_from(0));
+    }
+}
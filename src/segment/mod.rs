@@ -2,27 +2,37 @@
 // This source code is licensed under both the Apache 2.0 and MIT License
 // (found in the LICENSE-* files in the repository)
 
+pub mod access_stats;
 pub mod block;
 pub mod block_index;
+pub mod dump;
 pub mod file_offsets;
 mod forward_reader;
 pub mod id;
 pub mod inner;
+pub mod key_sketch;
+pub mod lazy;
 pub mod meta;
 pub mod multi_writer;
+pub mod prefetch;
 pub mod range;
 pub mod reader;
 pub mod scanner;
+pub mod standalone;
 pub mod trailer;
 pub mod value_block;
 pub mod value_block_consumer;
+pub mod verify;
 pub mod writer;
 
 use crate::{
     block_cache::BlockCache,
-    bloom::{BloomFilter, CompositeHash},
+    bloom::{AnyFilter, CompositeHash, Filter},
     descriptor_table::FileDescriptorTable,
+    range_tombstone::{RangeTombstone, RangeTombstoneIndex},
+    statistics::Statistics,
     time::unix_timestamp,
+    transform::{BlockTransform, KeyedTransform},
     tree::inner::TreeId,
     value::{InternalValue, SeqNo, UserKey},
 };
@@ -30,10 +40,15 @@ use block_index::BlockIndexImpl;
 use forward_reader::ForwardReader;
 use id::GlobalSegmentId;
 use inner::Inner;
-use meta::SegmentId;
+use key_sketch::KeySketch;
+use meta::{SegmentId, Temperature};
 use range::Range;
 use scanner::Scanner;
-use std::{ops::Bound, path::Path, sync::Arc};
+use std::{
+    ops::Bound,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 #[allow(clippy::module_name_repetitions)]
 pub type SegmentInner = Inner;
@@ -79,7 +94,9 @@ impl Segment {
         self.metadata.item_count as f32 / self.metadata.key_count as f32
     }
 
-    /// Gets the segment age in nanoseconds.
+    /// Gets the segment age in nanoseconds, measured using the system clock.
+    ///
+    /// Compaction strategies use the tree's [`crate::Config::clock`] instead.
     #[must_use]
     pub fn age(&self) -> u128 {
         let now = unix_timestamp().as_nanos();
@@ -102,134 +119,357 @@ impl Segment {
         self.metadata.id
     }
 
-    pub(crate) fn verify(&self) -> crate::Result<usize> {
-        use block::checksum::Checksum;
-        use block_index::IndexBlock;
-        use value_block::ValueBlock;
+    /// Returns the access temperature hint of the segment.
+    ///
+    /// Returns `None` for segments that were written without a hint.
+    #[must_use]
+    pub fn temperature(&self) -> Option<Temperature> {
+        self.metadata.temperature
+    }
 
-        let mut data_block_count = 0;
-        let mut broken_count = 0;
+    /// Marks the segment as removed from the tree.
+    ///
+    /// The segment file is deleted once the last reference to the segment is dropped.
+    pub(crate) fn mark_as_deleted(&self, path: PathBuf) {
+        if self.deleted_path.set(path).is_err() {
+            log::warn!(
+                "Segment {:?} was already marked as deleted",
+                self.global_id()
+            );
+        }
+    }
+
+    /// Loads all index blocks of a partitioned block index into the block cache.
+    ///
+    /// Returns the amount of loaded index blocks.
+    pub(crate) fn prefetch_index_blocks(&self) -> crate::Result<usize> {
+        let BlockIndexImpl::TwoLevel(block_index) = self.block_index.resolve()? else {
+            return Ok(0);
+        };
+
+        let mut count = 0;
+
+        // NOTE: TODO: because of 1.74.0
+        #[allow(clippy::explicit_iter_loop)]
+        for handle in block_index.top_level_index.iter() {
+            block_index.load_index_block(handle.offset, value_block::CachePolicy::Write)?;
+            count += 1;
+        }
+
+        Ok(count)
+    }
+
+    /// Re-reads every block of the segment, and checks the block checksums,
+    /// the order of all items, and that the block index matches the data blocks.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config};
+    ///
+    /// let tree = Config::new(folder).open()?;
+    /// tree.insert("a", "abc", 0);
+    /// let segment = tree.flush_active_memtable(0)?.expect("should exist");
+    ///
+    /// let report = segment.verify()?;
+    /// assert_eq!(1, report.data_blocks_checked);
+    /// assert!(report.is_ok());
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the file lock is poisoned.
+    pub fn verify(&self) -> crate::Result<verify::SegmentVerifyReport> {
+        let mut report = verify::SegmentVerifyReport {
+            segment_id: self.id(),
+            ..Default::default()
+        };
 
         let guard = self
             .descriptor_table
             .access(&self.global_id())?
             .expect("should have gotten file");
 
-        let mut file = guard.file.lock().expect("lock is poisoned");
+        let file = &*guard.file;
 
-        // TODO: maybe move to BlockIndexImpl::verify
-        match &*self.block_index {
-            BlockIndexImpl::Full(block_index) => {
-                for handle in block_index.iter() {
-                    let value_block = match ValueBlock::from_file(&mut *file, handle.offset) {
-                        Ok(v) => v,
-                        Err(e) => {
-                            log::error!(
-                     "data block {handle:?} could not be loaded, it is probably corrupted: {e:?}"
-                 );
-                            broken_count += 1;
-                            data_block_count += 1;
-                            continue;
-                        }
-                    };
-
-                    let (_, data) = ValueBlock::to_bytes_compressed(
-                        &value_block.items,
-                        value_block.header.previous_block_offset,
-                        value_block.header.compression,
-                    )?;
-                    let actual_checksum = Checksum::from_bytes(&data);
-
-                    if value_block.header.checksum != actual_checksum {
-                        log::error!("{handle:?} is corrupted, invalid checksum value");
-                        broken_count += 1;
-                    }
+        let handles = Self::collect_block_handles(
+            &self.block_index,
+            file,
+            self.transform.as_ref(),
+            &mut report,
+        )?;
 
-                    data_block_count += 1;
+        let mut item_count = 0;
+        let mut last_key = None;
 
-                    if data_block_count % 1_000 == 0 {
-                        log::debug!("Checked {data_block_count} data blocks");
-                    }
-                }
+        for handle in &handles {
+            if let Some(count) = self.verify_data_block(file, handle, &mut last_key, &mut report) {
+                item_count += count;
             }
+
+            report.data_blocks_checked += 1;
+
+            if report.data_blocks_checked % 1_000 == 0 {
+                log::debug!("Checked {} data blocks", report.data_blocks_checked);
+            }
+        }
+
+        // NOTE: Items of corrupt blocks cannot be counted
+        let is_item_count_mismatch =
+            report.corrupt_blocks.is_empty() && item_count != self.metadata.item_count;
+
+        if handles.len() as u64 != u64::from(self.metadata.data_block_count)
+            || is_item_count_mismatch
+        {
+            log::error!(
+                "Not all data blocks were visited during verification of disk segment {:?}",
+                self.id(),
+            );
+            report.count_mismatch = true;
+        }
+
+        Ok(report)
+    }
+
+    /// Collects the handles of all data blocks, loading the block index if it is lazy.
+    ///
+    /// Index blocks that cannot be loaded are reported as corrupt.
+    fn collect_block_handles(
+        block_index: &BlockIndexImpl,
+        file: &std::fs::File,
+        transform: Option<&KeyedTransform>,
+        report: &mut verify::SegmentVerifyReport,
+    ) -> crate::Result<Vec<block_index::block_handle::KeyedBlockHandle>> {
+        use block_index::IndexBlock;
+
+        match block_index {
+            BlockIndexImpl::Full(block_index) => Ok(block_index.to_vec()),
             BlockIndexImpl::TwoLevel(block_index) => {
+                let mut handles = vec![];
+
                 // NOTE: TODO: because of 1.74.0
                 #[allow(clippy::explicit_iter_loop)]
                 for handle in block_index.top_level_index.iter() {
-                    let block = match IndexBlock::from_file(&mut *file, handle.offset) {
-                        Ok(v) => v,
+                    match IndexBlock::from_file_at(file, handle.offset, transform) {
+                        Ok(block) => handles.extend(block.items.iter().cloned()),
                         Err(e) => {
                             log::error!(
                  "index block {handle:?} could not be loaded, it is probably corrupted: {e:?}"
              );
-                            broken_count += 1;
-                            continue;
-                        }
-                    };
-
-                    for handle in &*block.items {
-                        let value_block = match ValueBlock::from_file(&mut *file, handle.offset) {
-                            Ok(v) => v,
-                            Err(e) => {
-                                log::error!(
-                     "data block {handle:?} could not be loaded, it is probably corrupted: {e:?}"
-                 );
-                                broken_count += 1;
-                                data_block_count += 1;
-                                continue;
-                            }
-                        };
-
-                        let (_, data) = ValueBlock::to_bytes_compressed(
-                            &value_block.items,
-                            value_block.header.previous_block_offset,
-                            value_block.header.compression,
-                        )?;
-                        let actual_checksum = Checksum::from_bytes(&data);
-
-                        if value_block.header.checksum != actual_checksum {
-                            log::error!("{handle:?} is corrupted, invalid checksum value");
-                            broken_count += 1;
+                            report.corrupt_blocks.push(handle.offset);
                         }
+                    }
+                }
+
+                Ok(handles)
+            }
+            BlockIndexImpl::Lazy(lazy) => {
+                Self::collect_block_handles(lazy.0.get()?, file, transform, report)
+            }
+        }
+    }
 
-                        data_block_count += 1;
+    /// Verifies a single data block, returning its item count, or `None` if it is corrupt.
+    fn verify_data_block(
+        &self,
+        file: &std::fs::File,
+        handle: &block_index::block_handle::KeyedBlockHandle,
+        last_key: &mut Option<crate::key::InternalKey>,
+        report: &mut verify::SegmentVerifyReport,
+    ) -> Option<u64> {
+        use value_block::ValueBlock;
 
-                        if data_block_count % 1_000 == 0 {
-                            log::debug!("Checked {data_block_count} data blocks");
-                        }
-                    }
+        // NOTE: The checksum covers the block as written, so check it before decoding,
+        // a corrupted transformed block may not even be decodable
+        let checksum_matches = ValueBlock::checksum_matches_at(file, handle.offset)
+            .map_err(|e| {
+                log::error!("data block {handle:?} could not be read: {e:?}");
+            })
+            .unwrap_or(false);
+
+        if !checksum_matches {
+            log::error!("{handle:?} is corrupted, invalid checksum value");
+            report.corrupt_blocks.push(handle.offset);
+            *last_key = None;
+            return None;
+        }
+
+        let value_block =
+            match ValueBlock::from_file_at(file, handle.offset, self.transform.as_ref()) {
+                Ok(v) => v,
+                Err(e) => {
+                    log::error!(
+                    "data block {handle:?} could not be loaded, it is probably corrupted: {e:?}"
+                );
+                    report.corrupt_blocks.push(handle.offset);
+                    *last_key = None;
+                    return None;
                 }
+            };
+
+        let mut is_sorted = true;
+
+        for item in &*value_block.items {
+            if last_key
+                .as_ref()
+                .is_some_and(|last_key| *last_key >= item.key)
+            {
+                is_sorted = false;
             }
+            *last_key = Some(item.key.clone());
         }
 
-        if data_block_count != self.metadata.data_block_count {
-            log::error!(
-                "Not all data blocks were visited during verification of disk segment {:?}",
-                self.id(),
-            );
-            broken_count += 1;
+        if !is_sorted {
+            log::error!("{handle:?} is corrupted, items are not sorted");
+            report.unordered_blocks.push(handle.offset);
+        }
+
+        let is_index_mismatch = value_block
+            .items
+            .last()
+            .map_or(true, |item| item.key.user_key != handle.end_key);
+
+        if is_index_mismatch {
+            log::error!("{handle:?} is corrupted, last key does not match the block index");
+            report.index_mismatches.push(handle.offset);
         }
 
-        Ok(broken_count)
+        Some(value_block.items.len() as u64)
     }
 
     pub(crate) fn load_bloom<P: AsRef<Path>>(
         path: P,
         ptr: value_block::BlockOffset,
-    ) -> crate::Result<Option<BloomFilter>> {
-        Ok(if *ptr > 0 {
-            use crate::coding::Decode;
-            use std::{
-                fs::File,
-                io::{Seek, SeekFrom},
-            };
+        transform: Option<&KeyedTransform>,
+    ) -> crate::Result<Option<AnyFilter>> {
+        Self::load_optional_block(path, ptr, transform)
+    }
+
+    pub(crate) fn load_key_sketch<P: AsRef<Path>>(
+        path: P,
+        ptr: value_block::BlockOffset,
+        transform: Option<&KeyedTransform>,
+    ) -> crate::Result<Option<KeySketch>> {
+        Self::load_optional_block(path, ptr, transform)
+    }
+
+    /// Loads a block that is not part of the block index (e.g. the bloom filter),
+    /// or returns `None` if the segment does not contain it.
+    ///
+    /// If the segment is transformed, the block is prefixed with its transformed length,
+    /// see [`writer::Writer`].
+    fn load_optional_block<T: crate::coding::Decode, P: AsRef<Path>>(
+        path: P,
+        ptr: value_block::BlockOffset,
+        transform: Option<&KeyedTransform>,
+    ) -> crate::Result<Option<T>> {
+        use byteorder::{BigEndian, ReadBytesExt};
+        use std::{
+            fs::File,
+            io::{BufReader, Read, Seek, SeekFrom},
+        };
+
+        if *ptr == 0 {
+            return Ok(None);
+        }
+
+        let mut reader = BufReader::new(File::open(path)?);
+        reader.seek(SeekFrom::Start(*ptr))?;
+
+        let Some(transform) = transform else {
+            return Ok(Some(T::decode_from(&mut reader)?));
+        };
+
+        let len = reader.read_u32::<BigEndian>()?;
+        let mut bytes = vec![0; len as usize];
+        reader.read_exact(&mut bytes)?;
+
+        let bytes = transform.decode(&bytes)?;
+        Ok(Some(T::decode_from(&mut &bytes[..])?))
+    }
+
+    pub(crate) fn load_range_tombstones<P: AsRef<Path>>(
+        path: P,
+        ptr: value_block::BlockOffset,
+        transform: Option<&KeyedTransform>,
+    ) -> crate::Result<RangeTombstoneIndex> {
+        use crate::coding::Decode;
+        use byteorder::{BigEndian, ReadBytesExt};
+        use std::{
+            fs::File,
+            io::{Read, Seek, SeekFrom},
+        };
+
+        if *ptr == 0 {
+            return Ok(RangeTombstoneIndex::default());
+        }
+
+        let mut reader = File::open(path)?;
+        reader.seek(SeekFrom::Start(*ptr))?;
+
+        let len = reader.read_u32::<BigEndian>()?;
+        let mut bytes = vec![0; len as usize];
+        reader.read_exact(&mut bytes)?;
+
+        if let Some(transform) = transform {
+            bytes = transform.decode(&bytes)?;
+        }
+
+        let mut reader = &bytes[..];
+        let count = reader.read_u32::<BigEndian>()?;
+
+        (0..count)
+            .map(|_| RangeTombstone::decode_from(&mut reader).map_err(Into::into))
+            .collect::<crate::Result<Vec<_>>>()
+            .map(RangeTombstoneIndex::new)
+    }
+
+    /// Returns the range tombstones of the segment, ordered by their start key.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the range tombstones could not be loaded.
+    pub fn range_tombstones(&self) -> crate::Result<&[RangeTombstone]> {
+        self.range_tombstones
+            .get()
+            .map(RangeTombstoneIndex::as_slice)
+    }
 
-            let mut reader = File::open(path)?;
-            reader.seek(SeekFrom::Start(*ptr))?;
-            Some(BloomFilter::decode_from(&mut reader)?)
-        } else {
-            None
-        })
+    /// Returns `true` if a range tombstone of the segment, which is visible to a
+    /// snapshot with the given seqno, deletes the given version of a key.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the range tombstones could not be loaded.
+    pub(crate) fn is_range_deleted(
+        &self,
+        key: &[u8],
+        item_seqno: SeqNo,
+        seqno: Option<SeqNo>,
+    ) -> crate::Result<bool> {
+        Ok(self
+            .range_tombstones
+            .get()?
+            .covering(key)
+            .any(|t| t.is_visible(seqno) && t.deletes(key, item_seqno)))
+    }
+
+    /// Returns the sketch of the segment's user keys.
+    ///
+    /// Returns `None` for segments that were written without a sketch.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the sketch could not be loaded.
+    pub fn key_sketch(&self) -> crate::Result<Option<&KeySketch>> {
+        self.key_sketch.get().map(Option::as_ref)
     }
 
     /// Tries to recover a segment from a file.
@@ -238,65 +478,175 @@ impl Segment {
         tree_id: TreeId,
         block_cache: Arc<BlockCache>,
         descriptor_table: Arc<FileDescriptorTable>,
+        statistics: Arc<Statistics>,
         use_full_block_index: bool,
+        block_transform: Option<&Arc<dyn BlockTransform>>,
     ) -> crate::Result<Self> {
-        use block_index::{full_index::FullBlockIndex, two_level_index::TwoLevelBlockIndex};
         use trailer::SegmentFileTrailer;
 
         let file_path = file_path.as_ref();
 
         log::debug!("Recovering segment from file {file_path:?}");
-        let trailer = SegmentFileTrailer::from_file(file_path)?;
+        let trailer = SegmentFileTrailer::from_file(file_path, block_transform)?;
 
-        assert_eq!(
-            0, *trailer.offsets.range_tombstones_ptr,
-            "Range tombstones not supported"
+        let segment = Self::recover_with_trailer(
+            file_path,
+            trailer,
+            tree_id,
+            block_cache,
+            descriptor_table,
+            statistics,
+            use_full_block_index,
         );
+        segment.load()?;
 
-        log::debug!(
-            "Creating block index, with tli_ptr={}",
-            trailer.offsets.tli_ptr
-        );
+        Ok(segment)
+    }
+
+    /// Loads the block index, filter, key sketch and range tombstones of the segment, if they are not loaded yet.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub(crate) fn load(&self) -> crate::Result<()> {
+        self.block_index.resolve()?;
+        self.bloom_filter.get()?;
+        self.key_sketch.get()?;
+        self.range_tombstones.get()?;
+        Ok(())
+    }
 
-        let block_index = if use_full_block_index {
-            let block_index =
-                FullBlockIndex::from_file(file_path, &trailer.metadata, &trailer.offsets)?;
-
-            BlockIndexImpl::Full(block_index)
-        } else {
-            let block_index = TwoLevelBlockIndex::from_file(
-                file_path,
-                &trailer.metadata,
-                trailer.offsets.tli_ptr,
-                (tree_id, trailer.metadata.id).into(),
-                descriptor_table.clone(),
-                block_cache.clone(),
-            )?;
-            BlockIndexImpl::TwoLevel(block_index)
+    /// Creates a segment from an already loaded (e.g. cached) trailer.
+    ///
+    /// The block index, filter, key sketch and range tombstones are only read from the
+    /// file on first access, see [`Segment::load`].
+    pub(crate) fn recover_with_trailer<P: AsRef<Path>>(
+        file_path: P,
+        trailer: trailer::SegmentFileTrailer,
+        tree_id: TreeId,
+        block_cache: Arc<BlockCache>,
+        descriptor_table: Arc<FileDescriptorTable>,
+        statistics: Arc<Statistics>,
+        use_full_block_index: bool,
+    ) -> Self {
+        use block_index::{
+            full_index::FullBlockIndex, two_level_index::TwoLevelBlockIndex, LazyBlockIndex,
         };
+        use lazy::Lazy;
+
+        let file_path = file_path.as_ref().to_path_buf();
+
+        let block_index = Lazy::new({
+            let file_path = file_path.clone();
+            let metadata = trailer.metadata.clone();
+            let offsets = trailer.offsets;
+            let descriptor_table = descriptor_table.clone();
+            let block_cache = block_cache.clone();
+            let transform = trailer.transform.clone();
+
+            move || {
+                log::debug!(
+                    "Creating block index of segment {}, with tli_ptr={}",
+                    metadata.id,
+                    offsets.tli_ptr
+                );
+
+                let block_index = if use_full_block_index {
+                    BlockIndexImpl::Full(FullBlockIndex::from_file(
+                        &file_path,
+                        &metadata,
+                        &offsets,
+                        transform.as_ref(),
+                    )?)
+                } else {
+                    BlockIndexImpl::TwoLevel(TwoLevelBlockIndex::from_file(
+                        &file_path,
+                        &metadata,
+                        offsets.tli_ptr,
+                        (tree_id, metadata.id).into(),
+                        descriptor_table.clone(),
+                        block_cache.clone(),
+                        transform.clone(),
+                    )?)
+                };
+
+                Ok(Box::new(block_index))
+            }
+        });
 
         let bloom_ptr = trailer.offsets.bloom_ptr;
+        let bloom_filter = Lazy::new({
+            let file_path = file_path.clone();
+            let transform = trailer.transform.clone();
+            move || Self::load_bloom(&file_path, bloom_ptr, transform.as_ref())
+        });
 
-        Ok(Self(Arc::new(Inner {
+        let key_sketch_ptr = trailer.key_sketch_ptr;
+        let key_sketch = Lazy::new({
+            let file_path = file_path.clone();
+            let transform = trailer.transform.clone();
+            move || Self::load_key_sketch(&file_path, key_sketch_ptr, transform.as_ref())
+        });
+
+        let range_tombstones_ptr = trailer.offsets.range_tombstones_ptr;
+        let range_tombstones = Lazy::new({
+            let transform = trailer.transform.clone();
+            move || {
+                Self::load_range_tombstones(&file_path, range_tombstones_ptr, transform.as_ref())
+            }
+        });
+
+        Self(Arc::new(Inner {
             tree_id,
 
             descriptor_table,
             metadata: trailer.metadata,
             offsets: trailer.offsets,
 
-            block_index: Arc::new(block_index),
+            block_index: Arc::new(BlockIndexImpl::Lazy(LazyBlockIndex(block_index))),
             block_cache,
+            statistics,
+
+            bloom_filter,
+            key_sketch,
+            key_sketch_ptr,
+            range_tombstones,
+            transform: trailer.transform,
+            deleted_path: std::sync::OnceLock::new(),
+            access: crate::segment::access_stats::AccessStats::default(),
+        }))
+    }
 
-            bloom_filter: Self::load_bloom(file_path, bloom_ptr)?,
-        })))
+    /// Gets the amount of heap memory retained by the block index.
+    ///
+    /// For partitioned (two-level) indexes, this only counts the top-level index,
+    /// because index blocks are stored in the block cache.
+    #[must_use]
+    pub fn block_index_size(&self) -> usize {
+        use block_index::BlockIndex;
+
+        self.block_index.memory_usage()
+    }
+
+    /// Returns the expected false positive rate of the segment's filter.
+    ///
+    /// Returns `None` if the segment has no filter, or was written
+    /// before the rate was recorded.
+    #[must_use]
+    pub fn filter_fp_rate(&self) -> Option<f32> {
+        #[allow(clippy::cast_precision_loss)]
+        self.metadata
+            .filter_fp_rate_ppb
+            .map(|ppb| ppb as f32 / 1_000_000_000.0)
     }
 
     #[must_use]
     /// Gets the bloom filter size
     pub fn bloom_filter_size(&self) -> usize {
         self.bloom_filter
-            .as_ref()
-            .map(super::bloom::BloomFilter::len)
+            .get_loaded()
+            .and_then(Option::as_ref)
+            .map(Filter::len)
             .unwrap_or_default()
     }
 
@@ -306,26 +656,86 @@ impl Segment {
         seqno: Option<SeqNo>,
         hash: CompositeHash,
     ) -> crate::Result<Option<InternalValue>> {
+        if !self.may_contain_key(&key, seqno, hash) {
+            return Ok(None);
+        }
+
+        self.point_read(key, seqno)
+    }
+
+    /// Returns `true` if the segment contains any item that is visible
+    /// to a snapshot with the given seqno.
+    ///
+    /// Segments that were written after the snapshot was taken can be skipped entirely.
+    #[must_use]
+    pub(crate) fn has_visible_items(&self, seqno: SeqNo) -> bool {
+        self.metadata.seqnos.0 < seqno
+    }
+
+    /// Returns `false` if the segment definitely does not contain a visible version of the key,
+    /// based on its seqno range, key range and bloom filter.
+    ///
+    /// Does not do any I/O.
+    pub(crate) fn may_contain_key<K: AsRef<[u8]>>(
+        &self,
+        key: K,
+        seqno: Option<SeqNo>,
+        hash: CompositeHash,
+    ) -> bool {
         if let Some(seqno) = seqno {
-            if self.metadata.seqnos.0 >= seqno {
-                return Ok(None);
+            if !self.has_visible_items(seqno) {
+                return false;
             }
         }
 
         if !self.metadata.key_range.contains_key(&key) {
-            return Ok(None);
+            return false;
         }
 
-        if let Some(bf) = &self.bloom_filter {
-            if !bf.contains_hash(hash) {
-                return Ok(None);
+        self.access.record_read();
+        self.statistics.record_segment_probe();
+
+        let bloom_filter = match self.bloom_filter.get() {
+            Ok(bloom_filter) => bloom_filter.as_ref(),
+            Err(e) => {
+                // NOTE: Without the filter, the segment has to be read
+                log::error!(
+                    "Could not load filter of segment {:?}: {e:?}",
+                    self.global_id(),
+                );
+                None
+            }
+        };
+
+        if let Some(bf) = bloom_filter {
+            let may_contain = bf.contains_hash(hash);
+            self.statistics.record_bloom_check(!may_contain);
+
+            if !may_contain {
+                self.access.record_bloom_negative();
+                return false;
             }
         }
 
-        self.point_read(key, seqno)
+        true
+    }
+
+    /// Looks up the key in the data blocks, skipping the seqno, key range & bloom filter checks.
+    pub(crate) fn point_read<K: AsRef<[u8]>>(
+        &self,
+        key: K,
+        seqno: Option<SeqNo>,
+    ) -> crate::Result<Option<InternalValue>> {
+        let item = self.read_data_blocks(key, seqno)?;
+
+        if item.is_some() {
+            self.access.record_get_served();
+        }
+
+        Ok(item)
     }
 
-    fn point_read<K: AsRef<[u8]>>(
+    fn read_data_blocks<K: AsRef<[u8]>>(
         &self,
         key: K,
         seqno: Option<SeqNo>,
@@ -336,6 +746,14 @@ impl Segment {
 
         let key = key.as_ref();
 
+        // NOTE: All items of an ingested segment have the same seqno, so
+        // the whole segment is either visible or not
+        if let Some(global_seqno) = self.metadata.global_seqno {
+            if seqno.is_some_and(|seqno| global_seqno >= seqno) {
+                return Ok(None);
+            }
+        }
+
         let Some(first_block_handle) = self
             .block_index
             .get_lowest_block_containing_key(key, CachePolicy::Write)?
@@ -346,21 +764,28 @@ impl Segment {
         let Some(block) = ValueBlock::load_by_block_handle(
             &self.descriptor_table,
             &self.block_cache,
+            &self.statistics,
             self.global_id(),
             first_block_handle,
             CachePolicy::Write,
+            self.transform.as_ref(),
         )?
         else {
             return Ok(None);
         };
 
-        if seqno.is_none() {
+        self.access.record_block_read();
+
+        if seqno.is_none() || self.metadata.global_seqno.is_some() {
             // NOTE: Fastpath for non-seqno reads (which are most common)
             // This avoids setting up a rather expensive block iterator
             // (see explanation for that below)
             // This only really works because sequence numbers are sorted
             // in descending order
-            return Ok(block.get_latest(key).cloned());
+            return Ok(block
+                .get_latest(key)
+                .cloned()
+                .map(|item| range::apply_global_seqno(item, self.metadata.global_seqno)));
         }
 
         let mut reader = ForwardReader::new(
@@ -368,6 +793,8 @@ impl Segment {
             &self.descriptor_table,
             self.global_id(),
             &self.block_cache,
+            &self.statistics,
+            self.transform.as_ref(),
             first_block_handle,
         );
         reader.lo_block_size = block.header.data_length.into();
@@ -439,7 +866,21 @@ impl Segment {
     pub fn scan<P: AsRef<Path>>(&self, base_folder: P) -> crate::Result<Scanner> {
         let segment_file_path = base_folder.as_ref().join(self.metadata.id.to_string());
         let block_count = self.metadata.data_block_count.try_into().expect("oops");
-        Scanner::new(segment_file_path, block_count)
+        Ok(
+            Scanner::new(segment_file_path, block_count, self.transform.clone())?
+                .use_global_seqno(self.metadata.global_seqno),
+        )
+    }
+
+    /// Returns a debug iterator over the raw blocks & entries of the segment file.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub fn dump<P: AsRef<Path>>(&self, base_folder: P) -> crate::Result<dump::SegmentDump> {
+        let segment_file_path = base_folder.as_ref().join(self.metadata.id.to_string());
+        let block_count = self.metadata.data_block_count.try_into().expect("oops");
+        dump::SegmentDump::new(segment_file_path, block_count, self.transform.clone())
     }
 
     /// Creates a ranged iterator over the `Segment`.
@@ -454,9 +895,27 @@ impl Segment {
             self.descriptor_table.clone(),
             self.global_id(),
             self.block_cache.clone(),
+            self.statistics.clone(),
             self.block_index.clone(),
             range,
         )
+        .use_transform(self.transform.clone())
+        .use_global_seqno(self.metadata.global_seqno)
+    }
+
+    /// Returns the amount of point reads that hit the key range of the segment,
+    /// including reads that were answered by its bloom filter.
+    ///
+    /// The counter is used to find hot key ranges.
+    #[must_use]
+    pub fn read_count(&self) -> u64 {
+        self.access.reads()
+    }
+
+    /// Returns the access counters of the segment.
+    #[must_use]
+    pub fn access_stats(&self) -> access_stats::SegmentAccessStats {
+        self.access.snapshot(self.id())
     }
 
     /// Returns the highest sequence number in the segment.
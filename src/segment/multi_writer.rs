@@ -3,10 +3,17 @@
 // (found in the LICENSE-* files in the repository)
 
 use super::{
+    meta::Temperature,
     trailer::SegmentFileTrailer,
-    writer::{BloomConstructionPolicy, Options, Writer},
+    writer::{BlockSizePolicy, BloomConstructionPolicy, Options, Writer},
+};
+use crate::{
+    bloom::{BloomLayout, FilterType},
+    range_tombstone::RangeTombstone,
+    transform::KeyedTransform,
+    value::InternalValue,
+    Clock, CompressionType, SystemClock, UserKey,
 };
-use crate::{value::InternalValue, CompressionType, UserKey};
 use std::sync::{atomic::AtomicU64, Arc};
 
 /// Like `Writer` but will rotate to a new segment, once a segment grows larger than `target_size`
@@ -32,9 +39,31 @@ pub struct MultiWriter {
 
     bloom_policy: BloomConstructionPolicy,
 
+    bloom_layout: BloomLayout,
+
+    filter_type: FilterType,
+
+    block_size_policy: BlockSizePolicy,
+
+    transform: Option<KeyedTransform>,
+
+    clock: Arc<dyn Clock>,
+
+    temperature: Option<Temperature>,
+
+    paranoid: bool,
+
+    /// If set, a new segment is started whenever the key prefix of this length changes
+    partition_prefix_len: Option<usize>,
+
     current_key: Option<UserKey>,
 }
 
+/// Returns the first `len` bytes of the key (or the whole key, if it is shorter).
+fn key_prefix(key: &[u8], len: usize) -> &[u8] {
+    key.get(..len).unwrap_or(key)
+}
+
 impl MultiWriter {
     /// Sets up a new `MultiWriter` at the given segments folder
     pub fn new(
@@ -64,6 +93,22 @@ impl MultiWriter {
 
             bloom_policy: BloomConstructionPolicy::default(),
 
+            bloom_layout: BloomLayout::default(),
+
+            filter_type: FilterType::default(),
+
+            block_size_policy: BlockSizePolicy::default(),
+
+            transform: None,
+
+            clock: Arc::new(SystemClock),
+
+            temperature: None,
+
+            paranoid: false,
+
+            partition_prefix_len: None,
+
             current_key: None,
         })
     }
@@ -82,6 +127,61 @@ impl MultiWriter {
         self
     }
 
+    #[must_use]
+    pub fn use_bloom_layout(mut self, bloom_layout: BloomLayout) -> Self {
+        self.bloom_layout = bloom_layout;
+        self.writer = self.writer.use_bloom_layout(bloom_layout);
+        self
+    }
+
+    #[must_use]
+    pub fn use_filter_type(mut self, filter_type: FilterType) -> Self {
+        self.filter_type = filter_type;
+        self.writer = self.writer.use_filter_type(filter_type);
+        self
+    }
+
+    #[must_use]
+    pub fn use_block_size_policy(mut self, block_size_policy: BlockSizePolicy) -> Self {
+        self.block_size_policy = block_size_policy;
+        self.writer = self.writer.use_block_size_policy(block_size_policy);
+        self
+    }
+
+    #[must_use]
+    pub fn use_transform(mut self, transform: Option<KeyedTransform>) -> Self {
+        self.transform.clone_from(&transform);
+        self.writer = self.writer.use_transform(transform);
+        self
+    }
+
+    #[must_use]
+    pub fn use_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock.clone();
+        self.writer = self.writer.use_clock(clock);
+        self
+    }
+
+    #[must_use]
+    pub fn use_paranoid_checks(mut self, enabled: bool) -> Self {
+        self.paranoid = enabled;
+        self.writer = self.writer.use_paranoid_checks(enabled);
+        self
+    }
+
+    #[must_use]
+    pub fn use_temperature(mut self, temperature: Option<Temperature>) -> Self {
+        self.temperature = temperature;
+        self.writer = self.writer.use_temperature(temperature);
+        self
+    }
+
+    #[must_use]
+    pub fn use_partition_prefix_len(mut self, len: Option<usize>) -> Self {
+        self.partition_prefix_len = len;
+        self
+    }
+
     fn get_next_segment_id(&mut self) -> u64 {
         self.current_segment_id = self
             .segment_id_generator
@@ -106,7 +206,15 @@ impl MultiWriter {
         })?
         .use_compression(self.compression);
 
-        new_writer = new_writer.use_bloom_policy(self.bloom_policy);
+        new_writer = new_writer
+            .use_bloom_policy(self.bloom_policy)
+            .use_bloom_layout(self.bloom_layout)
+            .use_filter_type(self.filter_type)
+            .use_block_size_policy(self.block_size_policy)
+            .use_transform(self.transform.clone())
+            .use_clock(self.clock.clone())
+            .use_temperature(self.temperature)
+            .use_paranoid_checks(self.paranoid);
 
         let mut old_writer = std::mem::replace(&mut self.writer, new_writer);
 
@@ -117,16 +225,35 @@ impl MultiWriter {
         Ok(())
     }
 
+    /// Returns `true` if writing the given key starts a new segment.
+    ///
+    /// Versions of the same key are never split across segments.
+    #[must_use]
+    pub fn should_rotate(&self, key: &UserKey) -> bool {
+        if self
+            .current_key
+            .as_ref()
+            .is_some_and(|current_key| current_key >= key)
+        {
+            return false;
+        }
+
+        let is_next_partition = match (self.partition_prefix_len, &self.current_key) {
+            (Some(len), Some(current_key)) => key_prefix(current_key, len) != key_prefix(key, len),
+            _ => false,
+        };
+
+        is_next_partition || *self.writer.meta.file_pos >= self.target_size
+    }
+
     /// Writes an item
     pub fn write(&mut self, item: InternalValue) -> crate::Result<()> {
-        let is_next_key = self.current_key.as_ref() < Some(&item.key.user_key);
+        if self.should_rotate(&item.key.user_key) {
+            self.rotate()?;
+        }
 
-        if is_next_key {
+        if self.current_key.as_ref() < Some(&item.key.user_key) {
             self.current_key = Some(item.key.user_key.clone());
-
-            if *self.writer.meta.file_pos >= self.target_size {
-                self.rotate()?;
-            }
         }
 
         self.writer.write(item)?;
@@ -134,6 +261,11 @@ impl MultiWriter {
         Ok(())
     }
 
+    /// Writes a range tombstone into the current segment.
+    pub fn write_range_tombstone(&mut self, tombstone: RangeTombstone) {
+        self.writer.write_range_tombstone(tombstone);
+    }
+
     /// Finishes the last segment, making sure all data is written durably
     ///
     /// Returns the metadata of created segments
@@ -198,4 +330,25 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn segment_multi_writer_partition_prefix() -> crate::Result<()> {
+        let folder = tempfile::tempdir()?;
+
+        let tree = Config::new(&folder).partition_prefix_len(2).open()?;
+
+        tree.insert("t1a", "a", 0);
+        tree.insert("t1b", "b", 0);
+        tree.insert("t2a", "a", 0);
+        tree.insert("t3", "a", 0);
+        tree.insert("t", "a", 0);
+        tree.flush_active_memtable(0)?;
+        assert_eq!(1, tree.segment_count());
+
+        tree.major_compact(u64::MAX, 0)?;
+        assert_eq!(4, tree.segment_count());
+        assert_eq!(5, tree.len(None, None)?);
+
+        Ok(())
+    }
 }
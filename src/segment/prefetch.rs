@@ -0,0 +1,184 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use super::{
+    block::header::Header,
+    value_block::{BlockOffset, CachePolicy, ValueBlock},
+};
+use crate::{
+    descriptor_table::FileDescriptorTable, statistics::Statistics, transform::KeyedTransform,
+    BlockCache, Executor, GlobalSegmentId,
+};
+use std::sync::{
+    mpsc::{self, Receiver, TryRecvError},
+    Arc,
+};
+
+/// Amount of consecutive forward block loads before the
+/// access pattern is considered sequential
+const SEQUENTIAL_TRIGGER: usize = 2;
+
+/// Read-ahead settings of a reader
+#[derive(Clone)]
+pub struct ReadAhead {
+    /// Amount of data blocks to read ahead
+    pub blocks: usize,
+
+    /// Executor that runs the read-ahead
+    pub executor: Arc<dyn Executor>,
+}
+
+/// Reads ahead data blocks of a segment in the background
+///
+/// Once a reader has loaded a couple of neighbouring blocks, the prefetcher
+/// loads the next `blocks` data blocks into the block cache using the executor,
+/// so the reader does not need to wait on disk I/O when it gets there.
+///
+/// Only a single read-ahead is in flight at any time.
+pub struct Prefetcher {
+    blocks: usize,
+    executor: Arc<dyn Executor>,
+    sequential_loads: usize,
+
+    /// Start of the last read-ahead window, a new read-ahead
+    /// is only issued after the reader has moved into it
+    window_start: BlockOffset,
+
+    /// End of the data that has been read ahead already
+    prefetched_until: BlockOffset,
+
+    /// Receives the end of the running read-ahead
+    pending: Option<Receiver<BlockOffset>>,
+}
+
+impl Prefetcher {
+    #[must_use]
+    pub fn new(read_ahead: ReadAhead) -> Self {
+        Self {
+            blocks: read_ahead.blocks,
+            executor: read_ahead.executor,
+            sequential_loads: 0,
+            window_start: BlockOffset(0),
+            prefetched_until: BlockOffset(0),
+            pending: None,
+        }
+    }
+
+    /// Returns `true` if a read-ahead is still running.
+    fn is_busy(&mut self) -> bool {
+        let Some(pending) = &self.pending else {
+            return false;
+        };
+
+        match pending.try_recv() {
+            Ok(offset) => {
+                self.prefetched_until = self.prefetched_until.max(offset);
+            }
+            Err(TryRecvError::Empty) => return true,
+            Err(TryRecvError::Disconnected) => {
+                // NOTE: The task panicked, or the executor dropped it (e.g. when shutting down)
+                log::warn!("Prefetch task did not complete, disabling read-ahead");
+                self.blocks = 0;
+            }
+        }
+
+        self.pending = None;
+
+        false
+    }
+
+    /// Registers a forward block load, and starts a read-ahead
+    /// if the access pattern is sequential.
+    ///
+    /// `current` is the block that was just loaded, `next` the block after it,
+    /// and `end` the (exclusive) end of the readable data blocks.
+    #[allow(clippy::too_many_arguments)]
+    pub fn on_block_load(
+        &mut self,
+        current: BlockOffset,
+        next: BlockOffset,
+        end: BlockOffset,
+        descriptor_table: &Arc<FileDescriptorTable>,
+        block_cache: &Arc<BlockCache>,
+        statistics: &Arc<Statistics>,
+        segment_id: GlobalSegmentId,
+        transform: Option<&KeyedTransform>,
+    ) {
+        self.sequential_loads += 1;
+
+        if self.blocks == 0 || self.sequential_loads < SEQUENTIAL_TRIGGER || self.is_busy() {
+            return;
+        }
+
+        // NOTE: Read-ahead may have been disabled by the last task
+        if self.blocks == 0 {
+            return;
+        }
+
+        if current < self.window_start {
+            // NOTE: The blocks ahead of us are already cached
+            return;
+        }
+
+        let start = next.max(self.prefetched_until);
+
+        if start >= end {
+            return;
+        }
+
+        self.window_start = start;
+
+        let blocks = self.blocks;
+        let descriptor_table = descriptor_table.clone();
+        let block_cache = block_cache.clone();
+        let statistics = statistics.clone();
+        let transform = transform.cloned();
+
+        log::trace!("prefetching {blocks} blocks of segment {segment_id:?} from {start:?}");
+
+        let (sender, receiver) = mpsc::sync_channel(1);
+        self.pending = Some(receiver);
+
+        self.executor.spawn_blocking(Box::new(move || {
+            let mut offset = start;
+
+            for _ in 0..blocks {
+                if offset >= end {
+                    break;
+                }
+
+                // NOTE: The segment may have been deleted in the meantime
+                if !matches!(descriptor_table.access(&segment_id), Ok(Some(_))) {
+                    break;
+                }
+
+                match ValueBlock::load_by_block_handle(
+                    &descriptor_table,
+                    &block_cache,
+                    &statistics,
+                    segment_id,
+                    offset,
+                    CachePolicy::Write,
+                    transform.as_ref(),
+                ) {
+                    Ok(Some(block)) => {
+                        offset = BlockOffset(
+                            *offset
+                                + Header::serialized_len() as u64
+                                + u64::from(block.header.data_length),
+                        );
+                    }
+                    Ok(None) => break,
+                    Err(e) => {
+                        log::debug!("Prefetch of {segment_id:?}/{offset:?} failed: {e:?}");
+                        break;
+                    }
+                }
+            }
+
+            // NOTE: The reader may be gone already
+            let _ = sender.send(offset);
+        }));
+    }
+}
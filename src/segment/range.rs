@@ -5,12 +5,16 @@
 use super::block_index::BlockIndex;
 use super::block_index::BlockIndexImpl;
 use super::id::GlobalSegmentId;
+use super::prefetch::ReadAhead;
 use super::reader::Reader;
 use super::value_block::BlockOffset;
 use super::value_block::CachePolicy;
 use crate::block_cache::BlockCache;
 use crate::descriptor_table::FileDescriptorTable;
+use crate::statistics::Statistics;
+use crate::transform::KeyedTransform;
 use crate::value::InternalValue;
+use crate::value::SeqNo;
 use crate::value::UserKey;
 use crate::Slice;
 use std::ops::Bound;
@@ -26,6 +30,9 @@ pub struct Range {
     pub(crate) range: (Bound<UserKey>, Bound<UserKey>),
 
     pub(crate) reader: Reader,
+
+    /// Seqno that replaces the seqnos of all items, see [`Range::use_global_seqno`]
+    global_seqno: Option<SeqNo>,
 }
 
 impl Range {
@@ -34,6 +41,7 @@ impl Range {
         descriptor_table: Arc<FileDescriptorTable>,
         segment_id: GlobalSegmentId,
         block_cache: Arc<BlockCache>,
+        statistics: Arc<Statistics>,
         block_index: Arc<BlockIndexImpl>,
         range: (Bound<UserKey>, Bound<UserKey>),
     ) -> Self {
@@ -42,6 +50,7 @@ impl Range {
             descriptor_table,
             segment_id,
             block_cache,
+            statistics,
             BlockOffset(0),
             None,
         );
@@ -54,9 +63,26 @@ impl Range {
 
             reader,
             range,
+
+            global_seqno: None,
         }
     }
 
+    /// Sets the global seqno of an ingested segment,
+    /// which replaces the seqnos of all items
+    #[must_use]
+    pub fn use_global_seqno(mut self, global_seqno: Option<SeqNo>) -> Self {
+        self.global_seqno = global_seqno;
+        self
+    }
+
+    /// Sets the block transform the segment was written with
+    #[must_use]
+    pub fn use_transform(mut self, transform: Option<KeyedTransform>) -> Self {
+        self.reader = self.reader.use_transform(transform);
+        self
+    }
+
     /// Sets the cache policy
     #[must_use]
     pub fn cache_policy(mut self, policy: CachePolicy) -> Self {
@@ -64,6 +90,13 @@ impl Range {
         self
     }
 
+    /// Sets the data blocks to read ahead during sequential scans
+    #[must_use]
+    pub fn prefetch(mut self, read_ahead: Option<ReadAhead>) -> Self {
+        self.reader = self.reader.prefetch(read_ahead);
+        self
+    }
+
     fn initialize_lo_bound(&mut self) -> crate::Result<()> {
         let start_key = match self.range.start_bound() {
             Bound::Unbounded => None,
@@ -122,10 +155,25 @@ impl Range {
     }
 }
 
+/// Replaces the seqno of an item read from an ingested segment.
+pub(crate) fn apply_global_seqno(
+    mut item: InternalValue,
+    global_seqno: Option<SeqNo>,
+) -> InternalValue {
+    if let Some(global_seqno) = global_seqno {
+        item.key.seqno = global_seqno;
+    }
+    item
+}
+
 impl Iterator for Range {
     type Item = crate::Result<InternalValue>;
 
     fn next(&mut self) -> Option<Self::Item> {
+        if !self.reader.has_data_blocks() {
+            return None;
+        }
+
         if !self.lo_initialized {
             if let Err(e) = self.initialize_lo_bound() {
                 return Some(Err(e));
@@ -169,7 +217,7 @@ impl Iterator for Range {
                         Bound::Unbounded => {}
                     }
 
-                    return Some(Ok(entry));
+                    return Some(Ok(apply_global_seqno(entry, self.global_seqno)));
                 }
                 Err(error) => return Some(Err(error)),
             };
@@ -179,6 +227,10 @@ impl Iterator for Range {
 
 impl DoubleEndedIterator for Range {
     fn next_back(&mut self) -> Option<Self::Item> {
+        if !self.reader.has_data_blocks() {
+            return None;
+        }
+
         if !self.hi_initialized {
             if let Err(e) = self.initialize_hi_bound() {
                 return Some(Err(e));
@@ -220,7 +272,7 @@ impl DoubleEndedIterator for Range {
                         Bound::Unbounded => {}
                     }
 
-                    return Some(Ok(entry));
+                    return Some(Ok(apply_global_seqno(entry, self.global_seqno)));
                 }
                 Err(error) => return Some(Err(error)),
             };
@@ -293,6 +345,7 @@ mod tests {
             (0, 0).into(),
             table.clone(),
             block_cache.clone(),
+            None,
         )?;
         let block_index = Arc::new(BlockIndexImpl::TwoLevel(block_index));
 
@@ -301,6 +354,7 @@ mod tests {
             table.clone(),
             (0, 0).into(),
             block_cache.clone(),
+            Arc::default(),
             block_index.clone(),
             (Bound::Unbounded, Bound::Unbounded),
         );
@@ -320,6 +374,7 @@ mod tests {
                 table.clone(),
                 (0, 0).into(),
                 block_cache.clone(),
+                Arc::default(),
                 block_index.clone(),
                 (Bound::Included(key.clone()), Bound::Unbounded),
             );
@@ -338,6 +393,7 @@ mod tests {
                 table.clone(),
                 (0, 0).into(),
                 block_cache.clone(),
+                Arc::default(),
                 block_index.clone(),
                 (Bound::Included(key), Bound::Unbounded),
             );
@@ -393,6 +449,7 @@ mod tests {
             (0, 0).into(),
             table.clone(),
             block_cache.clone(),
+            None,
         )?;
         let block_index = Arc::new(BlockIndexImpl::TwoLevel(block_index));
 
@@ -402,6 +459,7 @@ mod tests {
                 table.clone(),
                 (0, 0).into(),
                 block_cache.clone(),
+                Arc::default(),
                 block_index.clone(),
                 range_bounds_to_tuple(&..),
             );
@@ -416,6 +474,7 @@ mod tests {
                 table.clone(),
                 (0, 0).into(),
                 block_cache.clone(),
+                Arc::default(),
                 block_index.clone(),
                 range_bounds_to_tuple(&..),
             );
@@ -436,6 +495,7 @@ mod tests {
                 table.clone(),
                 (0, 0).into(),
                 block_cache.clone(),
+                Arc::default(),
                 block_index.clone(),
                 range_bounds_to_tuple::<UserKey>(&..end),
             );
@@ -454,6 +514,7 @@ mod tests {
                 table.clone(),
                 (0, 0).into(),
                 block_cache.clone(),
+                Arc::default(),
                 block_index.clone(),
                 range_bounds_to_tuple(&..end),
             );
@@ -474,6 +535,7 @@ mod tests {
                 table.clone(),
                 (0, 0).into(),
                 block_cache.clone(),
+                Arc::default(),
                 block_index.clone(),
                 range_bounds_to_tuple(&(start..)),
             );
@@ -493,6 +555,7 @@ mod tests {
                 table,
                 (0, 0).into(),
                 block_cache,
+                Arc::default(),
                 block_index,
                 range_bounds_to_tuple(&(start..end)),
             );
@@ -594,6 +657,7 @@ mod tests {
                 (0, 0).into(),
                 table.clone(),
                 block_cache.clone(),
+                None,
             )?;
             let block_index = Arc::new(BlockIndexImpl::TwoLevel(block_index));
 
@@ -619,6 +683,7 @@ mod tests {
                     table.clone(),
                     (0, 0).into(),
                     block_cache.clone(),
+                    Arc::default(),
                     block_index.clone(),
                     bounds_u64_to_bytes(&bounds),
                 );
@@ -639,6 +704,7 @@ mod tests {
                     table.clone(),
                     (0, 0).into(),
                     block_cache.clone(),
+                    Arc::default(),
                     block_index.clone(),
                     bounds_u64_to_bytes(&bounds),
                 );
@@ -698,6 +764,7 @@ mod tests {
             (0, 0).into(),
             table.clone(),
             block_cache.clone(),
+            None,
         )?;
         let block_index = Arc::new(BlockIndexImpl::TwoLevel(block_index));
 
@@ -712,6 +779,7 @@ mod tests {
                     table.clone(),
                     (0, 0).into(),
                     block_cache.clone(),
+                    Arc::default(),
                     block_index.clone(),
                     (
                         Included(Slice::from([start_char])),
@@ -730,6 +798,7 @@ mod tests {
                     table.clone(),
                     (0, 0).into(),
                     block_cache.clone(),
+                    Arc::default(),
                     block_index.clone(),
                     (
                         Included(Slice::from([start_char])),
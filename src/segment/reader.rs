@@ -3,12 +3,13 @@
 // (found in the LICENSE-* files in the repository)
 
 use super::{
+    prefetch::{Prefetcher, ReadAhead},
     value_block::{BlockOffset, CachePolicy, ValueBlock},
     value_block_consumer::ValueBlockConsumer,
 };
 use crate::{
-    descriptor_table::FileDescriptorTable, segment::block::header::Header, value::InternalValue,
-    BlockCache, GlobalSegmentId, UserKey,
+    descriptor_table::FileDescriptorTable, segment::block::header::Header, statistics::Statistics,
+    transform::KeyedTransform, value::InternalValue, BlockCache, GlobalSegmentId, UserKey,
 };
 use std::sync::Arc;
 
@@ -17,6 +18,7 @@ pub struct Reader {
 
     descriptor_table: Arc<FileDescriptorTable>,
     block_cache: Arc<BlockCache>,
+    statistics: Arc<Statistics>,
 
     data_block_boundary: BlockOffset,
 
@@ -34,6 +36,10 @@ pub struct Reader {
     end_key: Option<UserKey>,
 
     cache_policy: CachePolicy,
+
+    prefetcher: Option<Prefetcher>,
+
+    transform: Option<KeyedTransform>,
 }
 
 impl Reader {
@@ -43,6 +49,7 @@ impl Reader {
         descriptor_table: Arc<FileDescriptorTable>,
         segment_id: GlobalSegmentId,
         block_cache: Arc<BlockCache>,
+        statistics: Arc<Statistics>,
         lo_block_offset: BlockOffset,
         hi_block_offset: Option<BlockOffset>,
     ) -> Self {
@@ -52,6 +59,7 @@ impl Reader {
             descriptor_table,
             segment_id,
             block_cache,
+            statistics,
 
             lo_block_offset,
             lo_block_size: 0,
@@ -65,6 +73,10 @@ impl Reader {
 
             cache_policy: CachePolicy::Write,
 
+            prefetcher: None,
+
+            transform: None,
+
             start_key: None,
             end_key: None,
         }
@@ -87,6 +99,61 @@ impl Reader {
         self
     }
 
+    /// Reads ahead data blocks once the reader detects a sequential scan.
+    ///
+    /// Prefetched blocks are put into the block cache, so this has no effect if
+    /// the cache policy does not allow writing to the cache.
+    ///
+    /// `None` or 0 blocks = disabled
+    #[must_use]
+    pub fn prefetch(mut self, read_ahead: Option<ReadAhead>) -> Self {
+        self.prefetcher = read_ahead
+            .filter(|read_ahead| read_ahead.blocks > 0)
+            .map(Prefetcher::new);
+        self
+    }
+
+    /// Sets the block transform the segment was written with
+    #[must_use]
+    pub fn use_transform(mut self, transform: Option<KeyedTransform>) -> Self {
+        self.transform = transform;
+        self
+    }
+
+    /// Returns `false` if the segment has no data blocks,
+    /// which happens if it only contains range tombstones.
+    pub(crate) fn has_data_blocks(&self) -> bool {
+        self.data_block_boundary > BlockOffset(0)
+    }
+
+    fn on_forward_block_load(&mut self) {
+        if self.cache_policy != CachePolicy::Write {
+            return;
+        }
+
+        let Some(prefetcher) = &mut self.prefetcher else {
+            return;
+        };
+
+        let next_block_offset = BlockOffset(
+            *self.lo_block_offset + Header::serialized_len() as u64 + self.lo_block_size,
+        );
+
+        // NOTE: The hi block is loaded by the reader itself
+        let end = self.hi_block_offset.unwrap_or(self.data_block_boundary);
+
+        prefetcher.on_block_load(
+            self.lo_block_offset,
+            next_block_offset,
+            end,
+            &self.descriptor_table,
+            &self.block_cache,
+            &self.statistics,
+            self.segment_id,
+            self.transform.as_ref(),
+        );
+    }
+
     fn load_data_block(
         &self,
         offset: BlockOffset,
@@ -94,9 +161,11 @@ impl Reader {
         let block = ValueBlock::load_by_block_handle(
             &self.descriptor_table,
             &self.block_cache,
+            &self.statistics,
             self.segment_id,
             offset,
             self.cache_policy,
+            self.transform.as_ref(),
         )?;
 
         // TODO: we only need to truncate items from blocks that are not the first and last block
@@ -147,6 +216,10 @@ impl Iterator for Reader {
     type Item = crate::Result<InternalValue>;
 
     fn next(&mut self) -> Option<Self::Item> {
+        if !self.has_data_blocks() {
+            return None;
+        }
+
         if !self.lo_initialized {
             fail_iter!(self.initialize_lo());
         }
@@ -192,6 +265,8 @@ impl Iterator for Reader {
                 self.lo_block_size = size;
                 self.lo_block_offset = next_block_offset;
 
+                self.on_forward_block_load();
+
                 // We just loaded the block
                 self.lo_block_items.as_mut()?.next().map(Ok)
             }
@@ -204,6 +279,10 @@ impl Iterator for Reader {
 
 impl DoubleEndedIterator for Reader {
     fn next_back(&mut self) -> Option<Self::Item> {
+        if !self.has_data_blocks() {
+            return None;
+        }
+
         if !self.hi_initialized {
             fail_iter!(self.initialize_hi());
         }
@@ -1,5 +1,6 @@
+use super::range::apply_global_seqno;
 use super::value_block::ValueBlock;
-use crate::InternalValue;
+use crate::{transform::KeyedTransform, InternalValue, SeqNo};
 use std::{collections::VecDeque, fs::File, io::BufReader, path::Path};
 
 /// Segment reader that is optimized for consuming an entire segment
@@ -10,10 +11,18 @@ pub struct Scanner {
     read_count: usize,
 
     buffer: VecDeque<InternalValue>,
+
+    transform: Option<KeyedTransform>,
+
+    global_seqno: Option<SeqNo>,
 }
 
 impl Scanner {
-    pub fn new<P: AsRef<Path>>(path: P, block_count: usize) -> crate::Result<Self> {
+    pub fn new<P: AsRef<Path>>(
+        path: P,
+        block_count: usize,
+        transform: Option<KeyedTransform>,
+    ) -> crate::Result<Self> {
         // TODO: a larger buffer size may be better for HDD
         let reader = BufReader::with_capacity(8 * 4_096, File::open(path)?);
 
@@ -22,8 +31,18 @@ impl Scanner {
             block_count,
             read_count: 0,
             buffer: VecDeque::new(),
+            transform,
+            global_seqno: None,
         })
     }
+
+    /// Sets the global seqno of an ingested segment,
+    /// which replaces the seqnos of all items
+    #[must_use]
+    pub fn use_global_seqno(mut self, global_seqno: Option<SeqNo>) -> Self {
+        self.global_seqno = global_seqno;
+        self
+    }
 }
 
 impl Iterator for Scanner {
@@ -32,14 +51,14 @@ impl Iterator for Scanner {
     fn next(&mut self) -> Option<Self::Item> {
         loop {
             if let Some(item) = self.buffer.pop_front() {
-                return Some(Ok(item));
+                return Some(Ok(apply_global_seqno(item, self.global_seqno)));
             }
 
             if self.read_count >= self.block_count {
                 return None;
             }
 
-            let block = ValueBlock::from_reader(&mut self.reader);
+            let block = ValueBlock::from_reader(&mut self.reader, self.transform.as_ref());
             let block = fail_iter!(block);
 
             // TODO: 1.80? IntoIter impl for Box<[T]>
@@ -0,0 +1,217 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+//! Segment files that are built and read without a tree
+//!
+//! This allows building sorted data on one machine, shipping the file
+//! to another one, and bulk loading it there using [`crate::Tree::ingest_file`].
+
+use super::{
+    meta::CompressionType,
+    writer::{BloomConstructionPolicy, Options, Writer},
+    Segment,
+};
+use crate::{
+    bloom::BloomFilter, descriptor_table::FileDescriptorTable, path::absolute_path, BlockCache,
+    InternalValue, KeyRange, KvPair, UserKey, UserValue, ValueType,
+};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// Writes sorted key-value pairs into a standalone segment file
+///
+/// # Examples
+///
+/// ```
+/// # let folder = tempfile::tempdir()?;
+/// use lsm_tree::{SegmentFileReader, SegmentFileWriter};
+///
+/// let path = folder.path().join("data.sst");
+///
+/// let mut writer = SegmentFileWriter::new(&path)?;
+/// writer.write("a", "abc")?;
+/// writer.write("b", "def")?;
+/// writer.finish()?;
+///
+/// let reader = SegmentFileReader::open(&path)?;
+/// assert_eq!(2, reader.len());
+/// assert_eq!(Some("def".as_bytes().into()), reader.get("b")?);
+/// #
+/// # Ok::<(), lsm_tree::Error>(())
+/// ```
+pub struct SegmentFileWriter {
+    writer: Writer,
+    path: PathBuf,
+    last_key: Option<UserKey>,
+}
+
+impl SegmentFileWriter {
+    /// Creates a segment file at the given path, overwriting an existing file.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub fn new<P: AsRef<Path>>(path: P) -> crate::Result<Self> {
+        let path = absolute_path(path);
+        let folder = path.parent().map(Path::to_path_buf).unwrap_or_default();
+
+        let writer = Writer::with_path(
+            Options {
+                folder,
+                data_block_size: /* 4 KiB */ 4_096,
+                index_block_size: /* 4 KiB */ 4_096,
+                segment_id: 0,
+            },
+            path.clone(),
+        )?
+        .use_bloom_policy(BloomConstructionPolicy::default());
+
+        Ok(Self {
+            writer,
+            path,
+            last_key: None,
+        })
+    }
+
+    /// Sets the compression method.
+    ///
+    /// Default = None
+    #[must_use]
+    pub fn compression(mut self, compression: CompressionType) -> Self {
+        self.writer = self.writer.use_compression(compression);
+        self
+    }
+
+    /// Sets the bits per key of the bloom filter.
+    ///
+    /// Default = 10
+    #[must_use]
+    pub fn bloom_bits_per_key(mut self, bits: u8) -> Self {
+        self.writer = self
+            .writer
+            .use_bloom_policy(BloomConstructionPolicy::BitsPerKey(bits));
+        self
+    }
+
+    /// Writes a key-value pair.
+    ///
+    /// Keys need to be written in ascending order, and may only be written once.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs, the key or value is too large,
+    /// or the key is not greater than the previous key.
+    pub fn write<K: Into<UserKey>, V: Into<UserValue>>(
+        &mut self,
+        key: K,
+        value: V,
+    ) -> crate::Result<()> {
+        // NOTE: The seqno is replaced when the file is ingested
+        let item = InternalValue::try_from_components(key, value, 0, ValueType::Value)?;
+
+        if self
+            .last_key
+            .as_ref()
+            .is_some_and(|last_key| *last_key >= item.key.user_key)
+        {
+            return Err(crate::Error::InvalidInput(
+                "segment file keys need to be sorted and unique",
+            ));
+        }
+        self.last_key = Some(item.key.user_key.clone());
+
+        self.writer.write(item)
+    }
+
+    /// Finishes and fsyncs the segment file, and returns its path.
+    ///
+    /// Returns `None` (and deletes the file) if nothing was written.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub fn finish(mut self) -> crate::Result<Option<PathBuf>> {
+        Ok(self.writer.finish()?.map(|_| self.path))
+    }
+}
+
+/// Reads a standalone segment file, e.g. one that was written by [`SegmentFileWriter`]
+///
+/// The file is expected to contain a single version of each key, like the files written
+/// by [`SegmentFileWriter`]. Segment files of a tree may contain multiple versions
+/// of a key, and tombstones, which are not resolved.
+pub struct SegmentFileReader {
+    segment: Segment,
+}
+
+impl SegmentFileReader {
+    /// Opens a segment file.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs, or the file is not a valid segment file.
+    pub fn open<P: AsRef<Path>>(path: P) -> crate::Result<Self> {
+        let path = absolute_path(path);
+        let descriptor_table = Arc::new(FileDescriptorTable::new(1, 1));
+
+        let segment = Segment::recover(
+            &path,
+            0,
+            Arc::new(BlockCache::with_capacity_bytes(
+                /* 1 MiB */ 1_024 * 1_024,
+            )),
+            descriptor_table.clone(),
+            Arc::default(),
+            true,
+            None,
+        )?;
+
+        descriptor_table.insert(&path, segment.global_id());
+
+        Ok(Self { segment })
+    }
+
+    pub(crate) fn segment(&self) -> &Segment {
+        &self.segment
+    }
+
+    /// Returns the amount of items in the file.
+    #[must_use]
+    pub fn len(&self) -> u64 {
+        self.segment.metadata.item_count
+    }
+
+    /// Returns `true` if the file contains no items.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the key range of the file.
+    #[must_use]
+    pub fn key_range(&self) -> &KeyRange {
+        &self.segment.metadata.key_range
+    }
+
+    /// Retrieves the value of a key.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    pub fn get<K: AsRef<[u8]>>(&self, key: K) -> crate::Result<Option<UserValue>> {
+        let hash = BloomFilter::get_hash(key.as_ref());
+
+        Ok(self.segment.get(key, None, hash)?.map(|item| item.value))
+    }
+
+    /// Returns an iterator over all items of the file, in key order.
+    #[must_use]
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = crate::Result<KvPair>> {
+        self.segment
+            .iter()
+            .map(|item| item.map(|item| (item.key.user_key, item.value)))
+    }
+}
@@ -2,15 +2,23 @@
 // This source code is licensed under both the Apache 2.0 and MIT License
 // (found in the LICENSE-* files in the repository)
 
-use super::{file_offsets::FileOffsets, meta::Metadata};
+use super::{
+    file_offsets::FileOffsets,
+    meta::{Metadata, SegmentId, Temperature},
+    value_block::BlockOffset,
+};
 use crate::{
     coding::{Decode, DecodeError, Encode, EncodeError},
     file::MAGIC_BYTES,
+    transform::{BlockTransform, KeyedTransform},
+    SeqNo,
 };
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
 use std::{
     fs::File,
     io::{BufReader, Read, Seek, Write},
     path::Path,
+    sync::Arc,
 };
 
 pub const TRAILER_SIZE: usize = 256;
@@ -23,18 +31,86 @@ pub struct SegmentFileTrailer {
 
     #[doc(hidden)]
     pub offsets: FileOffsets,
+
+    /// Block transform the segment was written with, if any
+    ///
+    /// Only the key ID is stored in the trailer.
+    #[doc(hidden)]
+    pub transform: Option<KeyedTransform>,
+
+    /// Position of the key sketch, or 0 if the segment has none
+    #[doc(hidden)]
+    pub key_sketch_ptr: BlockOffset,
 }
 
 impl SegmentFileTrailer {
-    pub fn from_file<P: AsRef<Path>>(path: P) -> crate::Result<Self> {
+    /// Size of the key ID, including its tag byte
+    const KEY_ID_LEN: usize = 1 + std::mem::size_of::<u32>();
+
+    /// Position of the ingest info (segment ID & global seqno) inside the trailer,
+    /// after the offsets, key ID, temperature, key sketch pointer & filter FP rate
+    const INGEST_INFO_OFFSET: usize = FileOffsets::serialized_len()
+        + Self::KEY_ID_LEN
+        + std::mem::size_of::<u8>()
+        + std::mem::size_of::<u64>()
+        + std::mem::size_of::<u32>();
+
+    /// Size of the ingest info, including its tag byte
+    const INGEST_INFO_LEN: usize = 1 + 2 * std::mem::size_of::<u64>();
+
+    pub fn from_file<P: AsRef<Path>>(
+        path: P,
+        block_transform: Option<&Arc<dyn BlockTransform>>,
+    ) -> crate::Result<Self> {
         let file = File::open(path)?;
         let mut reader = BufReader::new(file);
-        reader.seek(std::io::SeekFrom::End(-(TRAILER_SIZE as i64)))?;
+        let trailer_ptr = reader.seek(std::io::SeekFrom::End(-(TRAILER_SIZE as i64)))?;
 
         // Parse pointers
         let offsets = FileOffsets::decode_from(&mut reader)?;
 
-        let remaining_padding = TRAILER_SIZE - FileOffsets::serialized_len() - MAGIC_BYTES.len();
+        // NOTE: Segments written before block transforms existed
+        // have zero padding here, which reads as "no key"
+        let key_id = match reader.read_u8()? {
+            0 => {
+                reader.read_u32::<BigEndian>()?;
+                None
+            }
+            1 => Some(reader.read_u32::<BigEndian>()?),
+            tag => {
+                return Err(crate::Error::Decode(DecodeError::InvalidTag((
+                    "SegmentTrailerKeyId",
+                    tag,
+                ))))
+            }
+        };
+
+        // NOTE: Segments written before temperatures existed
+        // have zero padding here, which reads as "unknown"
+        let temperature = reader.read_u8()?;
+        let temperature = match temperature {
+            0 => None,
+            tag => Some(
+                Temperature::try_from(tag)
+                    .map_err(|()| DecodeError::InvalidTag(("Temperature", tag)))?,
+            ),
+        };
+
+        // NOTE: Segments written before key sketches existed
+        // have zero padding here, which reads as "no sketch"
+        let key_sketch_ptr = BlockOffset(reader.read_u64::<BigEndian>()?);
+
+        // NOTE: Segments written before filter FP rates were recorded
+        // have zero padding here, which reads as "unknown"
+        let filter_fp_rate_ppb = match reader.read_u32::<BigEndian>()? {
+            0 => None,
+            ppb => Some(ppb),
+        };
+
+        let ingest_info = Self::decode_ingest_info(&mut reader)?;
+
+        let remaining_padding =
+            TRAILER_SIZE - Self::INGEST_INFO_OFFSET - Self::INGEST_INFO_LEN - MAGIC_BYTES.len();
         reader.seek_relative(remaining_padding as i64)?;
 
         // Check trailer magic
@@ -49,11 +125,145 @@ impl SegmentFileTrailer {
 
         log::trace!("Trailer offsets: {offsets:#?}");
 
+        let transform = KeyedTransform::resolve(block_transform, key_id)?;
+
         // Jump to metadata and parse
         reader.seek(std::io::SeekFrom::Start(*offsets.metadata_ptr))?;
-        let metadata = Metadata::decode_from(&mut reader)?;
 
-        Ok(Self { metadata, offsets })
+        let mut metadata = if let Some(transform) = &transform {
+            // NOTE: The transformed metadata spans until the trailer
+            let len = trailer_ptr
+                .checked_sub(*offsets.metadata_ptr)
+                .ok_or(crate::Error::Decode(DecodeError::InvalidTrailer))?;
+
+            #[allow(clippy::cast_possible_truncation)]
+            let mut bytes = vec![0; len as usize];
+            reader.read_exact(&mut bytes)?;
+
+            Metadata::decode_from(&mut &*transform.decode(&bytes)?)?
+        } else {
+            Metadata::decode_from(&mut reader)?
+        };
+        metadata.temperature = temperature;
+        metadata.filter_fp_rate_ppb = filter_fp_rate_ppb;
+
+        if let Some((segment_id, global_seqno)) = ingest_info {
+            metadata.id = segment_id;
+            metadata.global_seqno = Some(global_seqno);
+            metadata.seqnos = (global_seqno, global_seqno);
+        }
+
+        Ok(Self {
+            metadata,
+            offsets,
+            transform,
+            key_sketch_ptr,
+        })
+    }
+
+    /// Reads the segment ID and global seqno of an ingested segment.
+    fn decode_ingest_info<R: Read>(reader: &mut R) -> crate::Result<Option<(SegmentId, SeqNo)>> {
+        // NOTE: Segments that were not ingested (or written before ingestion
+        // was supported) have zero padding here
+        let tag = reader.read_u8()?;
+        let segment_id = reader.read_u64::<BigEndian>()?;
+        let global_seqno = reader.read_u64::<BigEndian>()?;
+
+        match tag {
+            0 => Ok(None),
+            1 => Ok(Some((segment_id, global_seqno))),
+            tag => Err(crate::Error::Decode(DecodeError::InvalidTag((
+                "SegmentTrailerIngestInfo",
+                tag,
+            )))),
+        }
+    }
+
+    fn encode_ingest_info<W: Write>(
+        writer: &mut W,
+        ingest_info: Option<(SegmentId, SeqNo)>,
+    ) -> std::io::Result<()> {
+        let (tag, (segment_id, global_seqno)) =
+            ingest_info.map_or((0, (0, 0)), |ingest_info| (1, ingest_info));
+
+        writer.write_u8(tag)?;
+        writer.write_u64::<BigEndian>(segment_id)?;
+        writer.write_u64::<BigEndian>(global_seqno)
+    }
+
+    /// Marks a segment file as ingested, by writing the segment ID it is
+    /// adopted as, and its global seqno, into the trailer.
+    ///
+    /// Only the (otherwise unused) trailer padding is overwritten,
+    /// so the data blocks are not rewritten.
+    pub fn write_ingest_info<P: AsRef<Path>>(
+        path: P,
+        segment_id: SegmentId,
+        global_seqno: SeqNo,
+    ) -> crate::Result<()> {
+        let mut bytes = Vec::with_capacity(Self::INGEST_INFO_LEN);
+        Self::encode_ingest_info(&mut bytes, Some((segment_id, global_seqno)))?;
+
+        // NOTE: Both values fit easily
+        #[allow(clippy::cast_possible_wrap)]
+        let offset = Self::INGEST_INFO_OFFSET as i64 - TRAILER_SIZE as i64;
+
+        let mut file = File::options().write(true).open(path)?;
+        file.seek(std::io::SeekFrom::End(offset))?;
+        file.write_all(&bytes)?;
+        file.sync_all()?;
+
+        Ok(())
+    }
+}
+
+impl SegmentFileTrailer {
+    /// Encodes the trailer fields and the metadata of a segment without padding,
+    /// so they can be cached in the level manifest.
+    ///
+    /// Only segments without a block transform can be cached,
+    /// because the metadata is written as plaintext.
+    pub fn encode_cached<W: Write>(
+        metadata: &Metadata,
+        offsets: &FileOffsets,
+        key_sketch_ptr: BlockOffset,
+        writer: &mut W,
+    ) -> Result<(), EncodeError> {
+        offsets.encode_into(writer)?;
+        writer.write_u64::<BigEndian>(*key_sketch_ptr)?;
+        writer.write_u8(metadata.temperature.map_or(0, u8::from))?;
+        writer.write_u32::<BigEndian>(metadata.filter_fp_rate_ppb.unwrap_or_default())?;
+        metadata.encode_into(writer)
+    }
+
+    /// Decodes a trailer that was cached using [`SegmentFileTrailer::encode_cached`].
+    pub fn decode_cached<R: Read>(reader: &mut R) -> Result<Self, DecodeError> {
+        let offsets = FileOffsets::decode_from(reader)?;
+        let key_sketch_ptr = BlockOffset(reader.read_u64::<BigEndian>()?);
+
+        let temperature = match reader.read_u8()? {
+            0 => None,
+            tag => Some(
+                Temperature::try_from(tag)
+                    .map_err(|()| DecodeError::InvalidTag(("Temperature", tag)))?,
+            ),
+        };
+
+        let filter_fp_rate_ppb = match reader.read_u32::<BigEndian>()? {
+            0 => None,
+            ppb => Some(ppb),
+        };
+
+        let mut metadata = Metadata::decode_from(reader)?;
+        metadata.temperature = temperature;
+        metadata.filter_fp_rate_ppb = filter_fp_rate_ppb;
+
+        Ok(Self {
+            metadata,
+            offsets,
+            transform: None,
+            key_sketch_ptr,
+        })
     }
 }
 
@@ -65,6 +275,24 @@ impl Encode for SegmentFileTrailer {
 
         self.offsets.encode_into(&mut v)?;
 
+        if let Some(transform) = &self.transform {
+            v.write_u8(1)?;
+            v.write_u32::<BigEndian>(transform.key_id())?;
+        } else {
+            v.write_u8(0)?;
+            v.write_u32::<BigEndian>(0)?;
+        }
+
+        v.write_u8(self.metadata.temperature.map_or(0, u8::from))?;
+        v.write_u64::<BigEndian>(*self.key_sketch_ptr)?;
+        v.write_u32::<BigEndian>(self.metadata.filter_fp_rate_ppb.unwrap_or_default())?;
+        Self::encode_ingest_info(
+            &mut v,
+            self.metadata
+                .global_seqno
+                .map(|global_seqno| (self.metadata.id, global_seqno)),
+        )?;
+
         // Pad with remaining bytes
         v.resize(TRAILER_SIZE - MAGIC_BYTES.len(), 0);
 
@@ -3,10 +3,14 @@
 // (found in the LICENSE-* files in the repository)
 
 use super::{block::Block, id::GlobalSegmentId};
-use crate::{descriptor_table::FileDescriptorTable, value::InternalValue, BlockCache};
+use crate::{
+    compare::key_lt, descriptor_table::FileDescriptorTable, statistics::Statistics,
+    transform::KeyedTransform, value::InternalValue, BlockCache,
+};
 use std::sync::Arc;
 
 #[derive(Copy, Clone, Default, Debug, std::hash::Hash, PartialEq, Eq, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub struct BlockOffset(pub u64);
 
 impl std::ops::Deref for BlockOffset {
@@ -54,7 +58,9 @@ pub type ValueBlock = Block<InternalValue>;
 impl ValueBlock {
     #[must_use]
     pub fn get_latest(&self, key: &[u8]) -> Option<&InternalValue> {
-        let idx = self.items.partition_point(|item| &*item.key.user_key < key);
+        let idx = self
+            .items
+            .partition_point(|item| key_lt(&item.key.user_key, key));
 
         self.items
             .get(idx)
@@ -64,13 +70,18 @@ impl ValueBlock {
     pub fn load_by_block_handle(
         descriptor_table: &FileDescriptorTable,
         block_cache: &BlockCache,
+        statistics: &Statistics,
         segment_id: GlobalSegmentId,
         offset: BlockOffset,
         cache_policy: CachePolicy,
+        transform: Option<&KeyedTransform>,
     ) -> crate::Result<Option<Arc<Self>>> {
+        crate::slow_operation::record_segment_read(segment_id.segment_id());
+
         Ok(
             if let Some(block) = block_cache.get_disk_block(segment_id, offset) {
                 // Cache hit: Copy from block
+                statistics.record_block_load(true, 0);
 
                 Some(block)
             } else {
@@ -87,18 +98,17 @@ impl ValueBlock {
                     .expect("should acquire file handle");
                 // TODO: ^ use inspect instead: 1.76
 
-                let block = Self::from_file(
-                    &mut *file_guard.file.lock().expect("lock is poisoned"),
-                    offset,
-                )
-                .map_err(|e| {
-                    log::error!("Failed to load value block {segment_id:?}/{offset:?}: {e:?}");
-                    e
-                })?;
+                let block =
+                    Self::from_file_at(&file_guard.file, offset, transform).map_err(|e| {
+                        log::error!("Failed to load value block {segment_id:?}/{offset:?}: {e:?}");
+                        e
+                    })?;
                 // TODO: ^ inspect_err instead: 1.76
 
                 drop(file_guard);
 
+                statistics.record_block_load(false, block.header.data_length.into());
+
                 let block = Arc::new(block);
 
                 if cache_policy == CachePolicy::Write {
@@ -3,7 +3,10 @@
 // (found in the LICENSE-* files in the repository)
 
 use super::value_block::ValueBlock;
-use crate::value::InternalValue;
+use crate::{
+    compare::{key_le, key_lt},
+    value::InternalValue,
+};
 use std::sync::Arc;
 
 pub struct ValueBlockConsumer {
@@ -25,13 +28,17 @@ impl ValueBlockConsumer {
         end_key: Option<&[u8]>,
     ) -> Self {
         let mut lo = start_key.as_ref().map_or(0, |key| {
-            inner.items.partition_point(|x| &*x.key.user_key < *key)
+            inner
+                .items
+                .partition_point(|x| key_lt(&x.key.user_key, key))
         });
 
         let hi = end_key.as_ref().map_or_else(
             || inner.items.len() - 1,
             |key| {
-                let idx = inner.items.partition_point(|x| &*x.key.user_key <= *key);
+                let idx = inner
+                    .items
+                    .partition_point(|x| key_le(&x.key.user_key, key));
 
                 if idx == 0 {
                     let first = inner
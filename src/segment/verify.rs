@@ -0,0 +1,82 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use super::{meta::SegmentId, value_block::BlockOffset};
+
+/// Result of [`crate::Segment::verify`]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SegmentVerifyReport {
+    /// ID of the verified segment
+    pub segment_id: SegmentId,
+
+    /// Amount of data blocks that were read
+    pub data_blocks_checked: usize,
+
+    /// Blocks that could not be read, or whose checksum does not match their content
+    pub corrupt_blocks: Vec<BlockOffset>,
+
+    /// Data blocks whose items are not sorted,
+    /// or that do not start after the last item of the previous block
+    pub unordered_blocks: Vec<BlockOffset>,
+
+    /// Data blocks whose last key does not match their block index entry
+    pub index_mismatches: Vec<BlockOffset>,
+
+    /// `true` if the block index does not reference as many
+    /// data blocks (or items) as the segment metadata states
+    pub count_mismatch: bool,
+}
+
+impl SegmentVerifyReport {
+    /// Returns the amount of problems that were found.
+    #[must_use]
+    pub fn error_count(&self) -> usize {
+        self.corrupt_blocks.len()
+            + self.unordered_blocks.len()
+            + self.index_mismatches.len()
+            + usize::from(self.count_mismatch)
+    }
+
+    /// Returns `true` if no problem was found.
+    #[must_use]
+    pub fn is_ok(&self) -> bool {
+        self.error_count() == 0
+    }
+}
+
+/// Result of [`crate::AbstractTree::verify`]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct VerifyReport {
+    /// Reports of all verified segments
+    pub segments: Vec<SegmentVerifyReport>,
+
+    /// Amount of blobs whose checksum does not match (blob trees only)
+    pub corrupt_blob_count: usize,
+}
+
+impl VerifyReport {
+    /// Returns the amount of problems that were found.
+    #[must_use]
+    pub fn error_count(&self) -> usize {
+        self.segments
+            .iter()
+            .map(SegmentVerifyReport::error_count)
+            .sum::<usize>()
+            + self.corrupt_blob_count
+    }
+
+    /// Returns `true` if no problem was found.
+    #[must_use]
+    pub fn is_ok(&self) -> bool {
+        self.error_count() == 0
+    }
+
+    /// Returns the IDs of all segments that have problems.
+    pub fn corrupt_segments(&self) -> impl Iterator<Item = SegmentId> + '_ {
+        self.segments
+            .iter()
+            .filter(|report| !report.is_ok())
+            .map(|report| report.segment_id)
+    }
+}
@@ -8,22 +8,28 @@ use super::{
     block::header::Header as BlockHeader,
     block_index::writer::Writer as IndexWriter,
     file_offsets::FileOffsets,
-    meta::{CompressionType, Metadata},
+    key_sketch::KeySketch,
+    meta::{CompressionType, Metadata, Temperature},
     trailer::SegmentFileTrailer,
     value_block::ValueBlock,
 };
 use crate::{
-    bloom::BloomFilter,
+    bloom::{AnyFilter, BloomFilter, BloomLayout, FilterType, XorFilter},
     coding::Encode,
     file::fsync_directory,
+    key::InternalKey,
+    range_tombstone::RangeTombstone,
     segment::{block::ItemSize, value_block::BlockOffset},
-    value::{InternalValue, UserKey},
-    SegmentId,
+    transform::KeyedTransform,
+    value::{InternalValue, SeqNo, UserKey},
+    Clock, SegmentId, SystemClock,
 };
+use byteorder::{BigEndian, WriteBytesExt};
 use std::{
     fs::File,
     io::{BufWriter, Seek, Write},
     path::PathBuf,
+    sync::Arc,
 };
 
 /// Serializes and compresses values into blocks and writes them to disk as segment
@@ -31,7 +37,7 @@ pub struct Writer {
     pub(crate) opts: Options,
 
     /// Compression to use
-    compression: CompressionType,
+    pub(crate) compression: CompressionType,
 
     /// Segment file
     segment_file_path: PathBuf,
@@ -55,12 +61,73 @@ pub struct Writer {
 
     bloom_policy: BloomConstructionPolicy,
 
+    bloom_layout: BloomLayout,
+
+    filter_type: FilterType,
+
+    block_size_policy: BlockSizePolicy,
+
+    /// Block transform (e.g. encryption) applied to blocks and metadata
+    transform: Option<KeyedTransform>,
+
+    /// Whether the segment file is fsynced when the writer is finished
+    fsync: bool,
+
+    /// Clock for the segment creation time
+    pub(crate) clock: Arc<dyn Clock>,
+
+    /// Access temperature hint that is stored in the segment
+    pub(crate) temperature: Option<Temperature>,
+
+    /// Global seqno of an ingested segment, see [`Metadata::global_seqno`]
+    pub(crate) global_seqno: Option<SeqNo>,
+
+    /// Whether to validate the order of written items
+    paranoid: bool,
+
+    /// Last written key, only tracked in paranoid mode
+    last_key: Option<InternalKey>,
+
+    /// Sketch of the written user keys
+    key_sketch: KeySketch,
+
+    /// Expected false positive rate of the written filter
+    pub(crate) filter_fp_rate: Option<f32>,
+
+    /// Range tombstones that are written into the range tombstone block
+    pub(crate) range_tombstones: Vec<RangeTombstone>,
+
     /// Hashes for bloom filter
     ///
     /// using enhanced double hashing, so we got two u64s
     bloom_hash_buffer: Vec<(u64, u64)>,
 }
 
+/// Decides when a data block is full and gets written to disk
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum BlockSizePolicy {
+    /// Blocks are written once they hold at least `data_block_size` bytes
+    #[default]
+    Fixed,
+
+    /// Blocks are written once they hold `target_items` items or `max_bytes` bytes,
+    /// whichever comes first
+    ///
+    /// Tiny values do not end up in blocks with hundreds of items, and
+    /// large values are still packed together instead of getting a block each.
+    ///
+    /// An item that would push a non-empty block over `max_bytes` starts a new block,
+    /// so huge values do not drag their neighbours into an oversized block.
+    Adaptive {
+        /// Amount of items after which a block is written
+        target_items: u32,
+
+        /// Maximum amount of raw bytes per block, unless a single item is larger
+        max_bytes: u32,
+    },
+}
+
 #[derive(Copy, Clone, Debug)]
 pub enum BloomConstructionPolicy {
     BitsPerKey(u8),
@@ -82,6 +149,21 @@ impl BloomConstructionPolicy {
         }
     }
 
+    /// Limits the policy to at most `max_bits_per_key` bits per key.
+    #[must_use]
+    pub fn capped(self, max_bits_per_key: u8) -> Self {
+        match self {
+            Self::BitsPerKey(bpk) => Self::BitsPerKey(bpk.min(max_bits_per_key)),
+            Self::FpRate(fpr) => {
+                if BloomFilter::bits_per_key_for_fp_rate(fpr) > f32::from(max_bits_per_key) {
+                    Self::BitsPerKey(max_bits_per_key)
+                } else {
+                    self
+                }
+            }
+        }
+    }
+
     #[must_use]
     pub fn is_active(&self) -> bool {
         match self {
@@ -102,7 +184,14 @@ impl Writer {
     /// Sets up a new `Writer` at the given folder
     pub fn new(opts: Options) -> crate::Result<Self> {
         let segment_file_path = opts.folder.join(opts.segment_id.to_string());
+        Self::with_path(opts, segment_file_path)
+    }
 
+    /// Sets up a new `Writer` that writes to the given file,
+    /// instead of naming the file after the segment ID.
+    ///
+    /// The file should be inside of `opts.folder`, which is fsynced after writing.
+    pub(crate) fn with_path(opts: Options, segment_file_path: PathBuf) -> crate::Result<Self> {
         let block_writer = File::create(&segment_file_path)?;
         let block_writer = BufWriter::with_capacity(u16::MAX.into(), block_writer);
 
@@ -130,6 +219,28 @@ impl Writer {
 
             bloom_policy: BloomConstructionPolicy::default(),
 
+            bloom_layout: BloomLayout::default(),
+
+            filter_type: FilterType::default(),
+
+            block_size_policy: BlockSizePolicy::default(),
+
+            transform: None,
+
+            fsync: true,
+
+            clock: Arc::new(SystemClock),
+
+            temperature: None,
+            global_seqno: None,
+
+            paranoid: false,
+            last_key: None,
+
+            key_sketch: KeySketch::default(),
+            filter_fp_rate: None,
+            range_tombstones: Vec::new(),
+
             bloom_hash_buffer: Vec::new(),
         })
     }
@@ -147,6 +258,68 @@ impl Writer {
         self
     }
 
+    #[must_use]
+    pub(crate) fn use_bloom_layout(mut self, bloom_layout: BloomLayout) -> Self {
+        self.bloom_layout = bloom_layout;
+        self
+    }
+
+    /// Sets the kind of filter that is written, as long as the bloom policy is active.
+    #[must_use]
+    pub(crate) fn use_filter_type(mut self, filter_type: FilterType) -> Self {
+        self.filter_type = filter_type;
+        self
+    }
+
+    #[must_use]
+    pub(crate) fn use_block_size_policy(mut self, block_size_policy: BlockSizePolicy) -> Self {
+        self.block_size_policy = block_size_policy;
+        self
+    }
+
+    #[must_use]
+    pub(crate) fn use_transform(mut self, transform: Option<KeyedTransform>) -> Self {
+        self.index_writer = self.index_writer.use_transform(transform.clone());
+        self.transform = transform;
+        self
+    }
+
+    #[must_use]
+    pub(crate) fn use_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Enables validation of the item order, see [`crate::Config::paranoid_checks`].
+    #[must_use]
+    pub(crate) fn use_paranoid_checks(mut self, enabled: bool) -> Self {
+        self.paranoid = enabled;
+        self
+    }
+
+    #[must_use]
+    pub(crate) fn use_temperature(mut self, temperature: Option<Temperature>) -> Self {
+        self.temperature = temperature;
+        self
+    }
+
+    /// Sets the global seqno that replaces the seqnos of all written items when reading.
+    #[must_use]
+    pub(crate) fn use_global_seqno(mut self, global_seqno: Option<SeqNo>) -> Self {
+        self.global_seqno = global_seqno;
+        self
+    }
+
+    /// Sets whether the segment file (and its folder) is fsynced when finishing the writer.
+    ///
+    /// If disabled, the caller is responsible for syncing the file
+    /// before the segment is registered into the tree.
+    #[must_use]
+    pub(crate) fn use_fsync(mut self, fsync: bool) -> Self {
+        self.fsync = fsync;
+        self
+    }
+
     /// Writes a compressed block to disk.
     ///
     /// This is triggered when a `Writer::write` causes the buffer to grow to the configured `block_size`.
@@ -157,8 +330,12 @@ impl Writer {
             return Ok(());
         };
 
-        let (header, data) =
-            ValueBlock::to_bytes_compressed(&self.chunk, self.prev_pos.0, self.compression)?;
+        let (header, data) = ValueBlock::to_bytes_compressed(
+            &self.chunk,
+            self.prev_pos.0,
+            self.compression,
+            self.transform.as_ref(),
+        )?;
 
         self.meta.uncompressed_size += u64::from(header.uncompressed_length);
 
@@ -211,6 +388,23 @@ impl Writer {
     /// sorted as described by the [`UserKey`], otherwise the block layout will
     /// be non-sense.
     pub fn write(&mut self, item: InternalValue) -> crate::Result<()> {
+        if self.paranoid {
+            if let Some(last_key) = &self.last_key {
+                if item.key <= *last_key {
+                    log::error!(
+                        "Segment {} received {:?} after {last_key:?}",
+                        self.opts.segment_id,
+                        item.key,
+                    );
+                    return Err(crate::Error::InvariantViolation(
+                        "segment items are not in ascending order",
+                    ));
+                }
+            }
+
+            self.last_key = Some(item.key.clone());
+        }
+
         if item.is_tombstone() {
             self.meta.tombstone_count += 1;
         }
@@ -218,6 +412,7 @@ impl Writer {
         // NOTE: Check if we visit a new key
         if Some(&item.key.user_key) != self.current_key.as_ref() {
             self.meta.key_count += 1;
+            self.key_sketch.insert(&item.key.user_key);
             self.current_key = Some(item.key.user_key.clone());
 
             // IMPORTANT: Do not buffer *every* item's key
@@ -235,10 +430,26 @@ impl Writer {
             self.meta.first_key = Some(item.key.user_key.clone());
         }
 
-        self.chunk_size += item.size();
+        let item_size = item.size();
+
+        if let BlockSizePolicy::Adaptive { max_bytes, .. } = self.block_size_policy {
+            if !self.chunk.is_empty() && self.chunk_size + item_size > max_bytes as usize {
+                self.spill_block()?;
+            }
+        }
+
+        self.chunk_size += item_size;
         self.chunk.push(item);
 
-        if self.chunk_size >= self.opts.data_block_size as usize {
+        let is_full = match self.block_size_policy {
+            BlockSizePolicy::Fixed => self.chunk_size >= self.opts.data_block_size as usize,
+            BlockSizePolicy::Adaptive {
+                target_items,
+                max_bytes,
+            } => self.chunk.len() >= target_items as usize || self.chunk_size >= max_bytes as usize,
+        };
+
+        if is_full {
             self.spill_block()?;
         }
 
@@ -248,6 +459,72 @@ impl Writer {
         Ok(())
     }
 
+    /// Writes a range tombstone.
+    ///
+    /// Range tombstones can be written in any order, and do not
+    /// need to be interleaved with the items.
+    pub fn write_range_tombstone(&mut self, tombstone: RangeTombstone) {
+        self.meta.lowest_seqno = self.meta.lowest_seqno.min(tombstone.seqno);
+        self.meta.highest_seqno = self.meta.highest_seqno.max(tombstone.seqno);
+        self.range_tombstones.push(tombstone);
+    }
+
+    /// Writes the range tombstone block, returning its position,
+    /// or 0 if there are no range tombstones.
+    /// Writes a block that is not part of the block index (e.g. the bloom filter).
+    ///
+    /// If a transform is set, the block is transformed and prefixed with its length,
+    /// because key hashes and sketches may leak information about the keys.
+    fn write_optional_block<T: Encode>(&mut self, block: &T) -> crate::Result<()> {
+        let Some(transform) = &self.transform else {
+            block.encode_into(&mut self.block_writer)?;
+            return Ok(());
+        };
+
+        let bytes = transform.encode(&block.encode_into_vec())?;
+
+        // NOTE: Truncation is OK, filters and sketches are much smaller than 4 GiB
+        #[allow(clippy::cast_possible_truncation)]
+        self.block_writer
+            .write_u32::<BigEndian>(bytes.len() as u32)?;
+        self.block_writer.write_all(&bytes)?;
+
+        Ok(())
+    }
+
+    fn write_range_tombstones(&mut self) -> crate::Result<BlockOffset> {
+        if self.range_tombstones.is_empty() {
+            return Ok(BlockOffset(0));
+        }
+
+        self.range_tombstones.sort();
+        self.range_tombstones.dedup();
+
+        let mut bytes = vec![];
+
+        // NOTE: Truncation is OK, there are not 4 billion range tombstones in a segment
+        #[allow(clippy::cast_possible_truncation)]
+        bytes.write_u32::<BigEndian>(self.range_tombstones.len() as u32)?;
+
+        for tombstone in &self.range_tombstones {
+            tombstone.encode_into(&mut bytes)?;
+        }
+
+        if let Some(transform) = &self.transform {
+            bytes = transform.encode(&bytes)?;
+        }
+
+        let ptr = BlockOffset(self.block_writer.stream_position()?);
+
+        // NOTE: Truncation is OK, see above
+        #[allow(clippy::cast_possible_truncation)]
+        self.block_writer
+            .write_u32::<BigEndian>(bytes.len() as u32)?;
+        self.block_writer.write_all(&bytes)?;
+
+        Ok(ptr)
+    }
+
     // TODO: should take mut self to avoid double finish
 
     /// Finishes the segment, making sure all data is written durably
@@ -256,8 +533,17 @@ impl Writer {
 
         // No items written! Just delete segment file and return nothing
         if self.meta.item_count == 0 {
-            std::fs::remove_file(&self.segment_file_path)?;
-            return Ok(None);
+            if self.range_tombstones.is_empty() {
+                std::fs::remove_file(&self.segment_file_path)?;
+                return Ok(None);
+            }
+
+            // NOTE: A segment that only contains range tombstones still needs a key range,
+            // use the lowest start key, so the segment does not overlap its neighbours
+            //
+            // Reads look up range tombstones regardless of the segment's key range
+            self.meta.first_key = self.range_tombstones.iter().map(|t| t.start.clone()).min();
+            self.meta.last_key.clone_from(&self.meta.first_key);
         }
 
         let index_block_ptr = BlockOffset(self.block_writer.stream_position()?);
@@ -277,34 +563,59 @@ impl Writer {
                 let bloom_ptr = self.block_writer.stream_position()?;
                 let n = self.bloom_hash_buffer.len();
 
-                log::trace!(
-                    "Constructing Bloom filter with {n} entries: {:?}",
-                    self.bloom_policy,
-                );
-
                 let start = std::time::Instant::now();
 
-                let mut filter = self.bloom_policy.build(n);
+                let xor_filter = match self.filter_type {
+                    FilterType::Bloom => None,
+                    FilterType::Xor => {
+                        log::trace!("Constructing xor filter with {n} entries");
 
-                for hash in std::mem::take(&mut self.bloom_hash_buffer) {
-                    filter.set_with_hash(hash);
-                }
+                        // NOTE: If construction fails, fall back to a bloom filter
+                        XorFilter::from_hashes(&self.bloom_hash_buffer)
+                    }
+                };
+
+                let filter: AnyFilter = if let Some(filter) = xor_filter {
+                    self.bloom_hash_buffer.clear();
+                    filter.into()
+                } else {
+                    log::trace!(
+                        "Constructing Bloom filter with {n} entries: {:?}",
+                        self.bloom_policy,
+                    );
+
+                    let mut filter = self.bloom_policy.build(n).with_layout(self.bloom_layout);
+
+                    for hash in std::mem::take(&mut self.bloom_hash_buffer) {
+                        filter.set_with_hash(hash);
+                    }
+
+                    filter.into()
+                };
 
-                log::trace!("Built Bloom filter in {:?}", start.elapsed());
+                log::trace!("Built filter in {:?}", start.elapsed());
 
-                filter.encode_into(&mut self.block_writer)?;
+                self.filter_fp_rate = Some(filter.expected_fp_rate(n));
+
+                self.write_optional_block(&filter)?;
 
                 BlockOffset(bloom_ptr)
             }
         };
         log::trace!("bloom_ptr={bloom_ptr}");
 
+        // Write key sketch
+        let key_sketch_ptr = BlockOffset(self.block_writer.stream_position()?);
+        let key_sketch = std::mem::take(&mut self.key_sketch);
+        self.write_optional_block(&key_sketch)?;
+        log::trace!("key_sketch_ptr={key_sketch_ptr}");
+
         // TODO: #46 https://github.com/fjall-rs/lsm-tree/issues/46 - Write range filter
         let rf_ptr = BlockOffset(0);
         log::trace!("rf_ptr={rf_ptr}");
 
-        // TODO: #2 https://github.com/fjall-rs/lsm-tree/issues/2 - Write range tombstones
-        let range_tombstones_ptr = BlockOffset(0);
+        // Write range tombstones
+        let range_tombstones_ptr = self.write_range_tombstones()?;
         log::trace!("range_tombstones_ptr={range_tombstones_ptr}");
 
         // TODO:
@@ -315,7 +626,13 @@ impl Writer {
         let metadata_ptr = BlockOffset(self.block_writer.stream_position()?);
 
         let metadata = Metadata::from_writer(self.opts.segment_id, self)?;
-        metadata.encode_into(&mut self.block_writer)?;
+
+        if let Some(transform) = &self.transform {
+            let bytes = transform.encode(&metadata.encode_into_vec())?;
+            self.block_writer.write_all(&bytes)?;
+        } else {
+            metadata.encode_into(&mut self.block_writer)?;
+        }
 
         // Bundle all the file offsets
         let offsets = FileOffsets {
@@ -329,15 +646,25 @@ impl Writer {
         };
 
         // Write trailer
-        let trailer = SegmentFileTrailer { metadata, offsets };
+        let trailer = SegmentFileTrailer {
+            metadata,
+            offsets,
+            transform: self.transform.clone(),
+            key_sketch_ptr,
+        };
         trailer.encode_into(&mut self.block_writer)?;
 
         // Finally, flush & fsync the blocks file
         self.block_writer.flush()?;
-        self.block_writer.get_mut().sync_all()?;
 
-        // IMPORTANT: fsync folder on Unix
-        fsync_directory(&self.opts.folder)?;
+        fail_point!(crate::failpoint::points::SEGMENT_BEFORE_FSYNC);
+
+        if self.fsync {
+            self.block_writer.get_mut().sync_all()?;
+
+            // IMPORTANT: fsync folder on Unix
+            fsync_directory(&self.opts.folder)?;
+        }
 
         log::debug!(
             "Written {} items in {} blocks into new segment file, written {} MB of data blocks",
@@ -362,6 +689,37 @@ mod tests {
     use std::sync::Arc;
     use test_log::test;
 
+    #[test]
+    fn segment_writer_paranoid_order() -> crate::Result<()> {
+        let folder = tempfile::tempdir()?;
+
+        let mut writer = Writer::new(Options {
+            folder: folder.path().to_path_buf(),
+            data_block_size: 4_096,
+            index_block_size: 4_096,
+            segment_id: 0,
+        })?
+        .use_paranoid_checks(true);
+
+        writer.write(InternalValue::from_components("a", "", 1, ValueType::Value))?;
+        writer.write(InternalValue::from_components("a", "", 0, ValueType::Value))?;
+        writer.write(InternalValue::from_components("b", "", 3, ValueType::Value))?;
+
+        // NOTE: Same seqno for the same key
+        assert!(matches!(
+            writer.write(InternalValue::from_components("b", "", 3, ValueType::Value)),
+            Err(crate::Error::InvariantViolation(_))
+        ));
+
+        // NOTE: Key goes backwards
+        assert!(matches!(
+            writer.write(InternalValue::from_components("a", "", 5, ValueType::Value)),
+            Err(crate::Error::InvariantViolation(_))
+        ));
+
+        Ok(())
+    }
+
     #[test]
     fn segment_writer_seqnos() -> crate::Result<()> {
         let folder = tempfile::tempdir()?.into_path();
@@ -491,6 +849,7 @@ mod tests {
                 &segment_file_path,
                 &trailer.metadata,
                 trailer.offsets.tli_ptr,
+                None,
             )?;
 
             assert_eq!(tli.len() as u32, trailer.metadata.index_block_count);
@@ -506,6 +865,7 @@ mod tests {
             table,
             (0, segment_id).into(),
             block_cache,
+            Arc::default(),
             BlockOffset(0),
             None,
         );
@@ -563,6 +923,7 @@ mod tests {
             table,
             (0, segment_id).into(),
             block_cache,
+            Arc::default(),
             BlockOffset(0),
             None,
         );
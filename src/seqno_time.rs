@@ -0,0 +1,165 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::{
+    coding::{Decode, DecodeError, Encode, EncodeError},
+    file::{rewrite_atomic, SEQNO_TIME_MAP_FILE},
+    SeqNo,
+};
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use std::{io::Write, path::Path, time::Duration};
+
+/// Maximum amount of samples that are kept
+///
+/// When exceeded, every other sample is dropped, so the
+/// resolution gets coarser the longer the tree is running.
+const MAX_SAMPLES: usize = 1_024;
+
+/// Converts a time to microseconds, saturating at `u64::MAX` (which is more than 500,000 years)
+fn to_micros(time: Duration) -> u64 {
+    u64::try_from(time.as_micros()).unwrap_or(u64::MAX)
+}
+
+/// Maps seqno watermarks to the wall-clock time they were sampled at
+///
+/// Samples are taken when flushed segments are registered, so a sample
+/// `(seqno, time)` means that all writes below `seqno` happened before `time`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SeqnoTimeMap(Vec<(SeqNo, u64)>);
+
+impl SeqnoTimeMap {
+    /// Loads the map of a tree, returning an empty map if none was written yet.
+    pub fn load<P: AsRef<Path>>(tree_path: P) -> crate::Result<Self> {
+        let path = tree_path.as_ref().join(SEQNO_TIME_MAP_FILE);
+
+        match std::fs::read(path) {
+            Ok(bytes) => Ok(Self::decode_from(&mut &bytes[..])?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Atomically writes the map into the tree folder.
+    pub fn persist<P: AsRef<Path>>(&self, tree_path: P) -> crate::Result<()> {
+        rewrite_atomic(
+            tree_path.as_ref().join(SEQNO_TIME_MAP_FILE),
+            &self.encode_into_vec(),
+        )?;
+        Ok(())
+    }
+
+    /// Adds a sample.
+    ///
+    /// Returns `false` if the sample does not advance the watermark,
+    /// in which case it is not added.
+    pub fn record(&mut self, seqno: SeqNo, time: Duration) -> bool {
+        if self.0.last().is_some_and(|&(last, _)| seqno <= last) {
+            return false;
+        }
+
+        let micros = to_micros(time);
+
+        // NOTE: Time may not go backwards, so the map stays sorted by time
+        let micros = self.0.last().map_or(micros, |&(_, last)| micros.max(last));
+
+        self.0.push((seqno, micros));
+
+        if self.0.len() > MAX_SAMPLES {
+            let newest = self.0.pop();
+            self.0 = self.0.iter().copied().step_by(2).chain(newest).collect();
+        }
+
+        true
+    }
+
+    /// Returns the highest seqno watermark sampled at or before the given time.
+    pub fn seqno_at(&self, time: Duration) -> Option<SeqNo> {
+        let micros = to_micros(time);
+
+        let idx = self.0.partition_point(|&(_, sampled)| sampled <= micros);
+
+        idx.checked_sub(1)
+            .and_then(|idx| self.0.get(idx))
+            .map(|&(seqno, _)| seqno)
+    }
+}
+
+impl Encode for SeqnoTimeMap {
+    fn encode_into<W: Write>(&self, writer: &mut W) -> Result<(), EncodeError> {
+        // NOTE: There are at most MAX_SAMPLES samples
+        #[allow(clippy::cast_possible_truncation)]
+        writer.write_u32::<BigEndian>(self.0.len() as u32)?;
+
+        for &(seqno, micros) in &self.0 {
+            writer.write_u64::<BigEndian>(seqno)?;
+            writer.write_u64::<BigEndian>(micros)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Decode for SeqnoTimeMap {
+    fn decode_from<R: std::io::Read>(reader: &mut R) -> Result<Self, DecodeError> {
+        let len = reader.read_u32::<BigEndian>()?;
+
+        let mut samples = Vec::with_capacity((len as usize).min(MAX_SAMPLES));
+
+        for _ in 0..len {
+            let seqno = reader.read_u64::<BigEndian>()?;
+            let micros = reader.read_u64::<BigEndian>()?;
+            samples.push((seqno, micros));
+        }
+
+        Ok(Self(samples))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn seqno_time_map_lookup() {
+        let mut map = SeqnoTimeMap::default();
+
+        assert!(map.record(10, Duration::from_secs(100)));
+        assert!(map.record(20, Duration::from_secs(200)));
+        assert!(!map.record(20, Duration::from_secs(300)));
+
+        assert_eq!(None, map.seqno_at(Duration::from_secs(99)));
+        assert_eq!(Some(10), map.seqno_at(Duration::from_secs(100)));
+        assert_eq!(Some(10), map.seqno_at(Duration::from_secs(199)));
+        assert_eq!(Some(20), map.seqno_at(Duration::from_secs(1_000)));
+    }
+
+    #[test]
+    fn seqno_time_map_thin_out() {
+        let mut map = SeqnoTimeMap::default();
+
+        for x in 1..=(MAX_SAMPLES as u64 + 1) {
+            map.record(x, Duration::from_secs(x));
+        }
+
+        assert_eq!(MAX_SAMPLES / 2 + 1, map.0.len());
+        assert_eq!(Some(1), map.seqno_at(Duration::from_secs(1)));
+        assert_eq!(
+            Some(MAX_SAMPLES as u64 + 1),
+            map.seqno_at(Duration::from_secs(u64::MAX))
+        );
+    }
+
+    #[test]
+    fn seqno_time_map_roundtrip() -> crate::Result<()> {
+        let mut map = SeqnoTimeMap::default();
+        map.record(5, Duration::from_secs(1));
+        map.record(7, Duration::from_secs(2));
+
+        let bytes = map.encode_into_vec();
+        assert_eq!(map, SeqnoTimeMap::decode_from(&mut &bytes[..])?);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,198 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::{Config, SegmentId, SeqNo, TreeId, UserKey};
+use std::{cell::RefCell, ops::Bound, time::Duration};
+
+/// What a slow operation did, see [`SlowOperation`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum SlowOperationKind {
+    /// Point read of a single key
+    Get {
+        /// Key that was read
+        key: UserKey,
+    },
+
+    /// Batched point read, see [`crate::AbstractTree::get_many`]
+    GetMany {
+        /// Amount of keys that were read
+        key_count: usize,
+    },
+
+    /// Range or prefix scan
+    Scan {
+        /// Bounds of the scan
+        range: (Bound<UserKey>, Bound<UserKey>),
+    },
+
+    /// Memtable flush
+    Flush {
+        /// Size of the flushed memtable in bytes
+        memtable_size: u64,
+
+        /// Segment that was written, if the memtable was not empty
+        segment: Option<SegmentId>,
+    },
+
+    /// Compaction
+    Compaction {
+        /// Name of the compaction strategy
+        strategy: &'static str,
+
+        /// Level the output segments were written into
+        dest_level: u8,
+
+        /// Segments that were written
+        output_segments: Vec<SegmentId>,
+    },
+}
+
+/// Report of an operation that took at least the configured
+/// slow operation threshold, see [`Config::slow_operation_threshold`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SlowOperation {
+    /// Tree the operation ran on
+    pub tree_id: TreeId,
+
+    /// What the operation did
+    pub kind: SlowOperationKind,
+
+    /// Snapshot seqno of reads
+    pub seqno: Option<SeqNo>,
+
+    /// Time the operation took
+    ///
+    /// For scans, this only includes the time spent advancing the iterator.
+    pub elapsed: Duration,
+
+    /// Disk segments the operation read data blocks from (sorted)
+    pub segments_read: Vec<SegmentId>,
+}
+
+impl std::fmt::Display for SlowOperation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let op = match &self.kind {
+            SlowOperationKind::Get { .. } => "get",
+            SlowOperationKind::GetMany { .. } => "get_many",
+            SlowOperationKind::Scan { .. } => "scan",
+            SlowOperationKind::Flush { .. } => "flush",
+            SlowOperationKind::Compaction { .. } => "compaction",
+        };
+
+        write!(
+            f,
+            "slow {op}: tree={} seqno={:?} elapsed={:?} segments_read={:?} {:?}",
+            self.tree_id, self.seqno, self.elapsed, self.segments_read, self.kind,
+        )
+    }
+}
+
+/// Receives reports about slow operations, see [`Config::slow_operation_threshold`]
+pub trait SlowOperationObserver: Send + Sync {
+    /// Called after an operation took at least the slow operation threshold.
+    ///
+    /// Called on the thread that ran the operation, so it should return quickly.
+    fn on_slow_operation(&self, operation: &SlowOperation);
+}
+
+/// Logs a slow operation and passes it to the configured observer (if any)
+pub fn report(config: &Config, mut operation: SlowOperation) {
+    operation.segments_read.sort_unstable();
+    operation.segments_read.dedup();
+
+    log::warn!("{operation}");
+
+    if let Some(observer) = &config.slow_operation_observer {
+        observer.on_slow_operation(&operation);
+    }
+}
+
+thread_local! {
+    /// Segments read by the traced operation running on this thread
+    static SEGMENT_READS: RefCell<Option<Vec<SegmentId>>> = const { RefCell::new(None) };
+}
+
+/// Marks that a data block of the given segment was read on this thread
+pub fn record_segment_read(segment_id: SegmentId) {
+    SEGMENT_READS.with(|reads| {
+        if let Some(reads) = &mut *reads.borrow_mut() {
+            reads.push(segment_id);
+        }
+    });
+}
+
+/// Restores the trace of an outer operation, even if the traced operation panics
+struct TraceGuard {
+    outer: Option<Vec<SegmentId>>,
+    restored: bool,
+}
+
+impl TraceGuard {
+    fn restore(&mut self) -> Vec<SegmentId> {
+        if self.restored {
+            return Vec::new();
+        }
+        self.restored = true;
+
+        let outer = self.outer.take();
+
+        SEGMENT_READS.with(|reads| {
+            let mut reads = reads.borrow_mut();
+            let inner = std::mem::replace(&mut *reads, outer).unwrap_or_default();
+
+            // NOTE: Nested operations are part of the outer operation as well
+            if let Some(outer) = &mut *reads {
+                outer.extend_from_slice(&inner);
+            }
+
+            inner
+        })
+    }
+}
+
+impl Drop for TraceGuard {
+    fn drop(&mut self) {
+        self.restore();
+    }
+}
+
+/// Runs `f`, returning the segments it read data blocks from on this thread.
+pub fn trace_segment_reads<T>(f: impl FnOnce() -> T) -> (T, Vec<SegmentId>) {
+    let mut guard = TraceGuard {
+        outer: SEGMENT_READS.with(|reads| reads.borrow_mut().replace(Vec::new())),
+        restored: false,
+    };
+
+    let result = f();
+    let segments = guard.restore();
+
+    (result, segments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn trace_segment_reads_nested() {
+        record_segment_read(9);
+
+        let (((), inner), outer) = trace_segment_reads(|| {
+            record_segment_read(1);
+
+            let inner = trace_segment_reads(|| {
+                record_segment_read(2);
+            });
+
+            record_segment_read(3);
+            inner
+        });
+
+        assert_eq!(vec![2], inner);
+        assert_eq!(vec![1, 2, 3], outer);
+        assert!(SEGMENT_READS.with(|reads| reads.borrow().is_none()));
+    }
+}
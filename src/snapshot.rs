@@ -6,7 +6,7 @@ use crate::{
     value::{SeqNo, UserKey, UserValue},
     AbstractTree, AnyTree, KvPair,
 };
-use std::ops::RangeBounds;
+use std::{ops::RangeBounds, path::Path};
 
 /// A snapshot captures a read-only point-in-time view of the tree at the time the snapshot was created
 ///
@@ -14,7 +14,6 @@ use std::ops::RangeBounds;
 /// keep the snapshot consistent. Thus, snapshots should only be kept around for as little as possible.
 ///
 /// Snapshots do not persist across restarts.
-#[derive(Clone)]
 pub struct Snapshot {
     tree: AnyTree,
 
@@ -22,10 +21,24 @@ pub struct Snapshot {
     pub seqno: SeqNo,
 }
 
+impl Clone for Snapshot {
+    fn clone(&self) -> Self {
+        Self::new(self.tree.clone(), self.seqno)
+    }
+}
+
+impl Drop for Snapshot {
+    fn drop(&mut self) {
+        log::trace!("Closing snapshot with seqno: {}", self.seqno);
+        self.tree.deregister_snapshot(self.seqno);
+    }
+}
+
 impl Snapshot {
     /// Creates a snapshot
     pub(crate) fn new(tree: AnyTree, seqno: SeqNo) -> Self {
         log::trace!("Opening snapshot with seqno: {seqno}");
+        tree.register_snapshot(seqno);
         Self { tree, seqno }
     }
 
@@ -390,4 +403,44 @@ impl Snapshot {
 
         Ok(count)
     }
+
+    /// Writes the snapshot into a new tree at the given folder.
+    ///
+    /// The exported tree keeps all versions that are visible to the snapshot,
+    /// and uses the same configuration as the tree (apart from its path).
+    /// Segments that only contain visible versions (and blob files) are hard linked,
+    /// only segments that contain newer versions are rewritten.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// # let export_folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, Tree};
+    ///
+    /// let tree = Config::new(folder).open()?;
+    /// tree.insert("a", "abc", 0);
+    /// tree.flush_active_memtable(0)?;
+    ///
+    /// let snapshot = tree.snapshot(1);
+    /// tree.insert("b", "abc", 1);
+    ///
+    /// snapshot.export(&export_folder)?;
+    ///
+    /// let exported = Config::new(&export_folder).open()?;
+    /// assert!(exported.contains_key("a", None)?);
+    /// assert!(!exported.contains_key("b", None)?);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs, or the folder already contains a tree.
+    pub fn export<P: AsRef<Path>>(&self, path: P) -> crate::Result<()> {
+        match &self.tree {
+            AnyTree::Standard(tree) => tree.export_snapshot(path, self.seqno),
+            AnyTree::Blob(tree) => tree.export_snapshot(path, self.seqno),
+        }
+    }
 }
@@ -0,0 +1,73 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::SeqNo;
+use std::{collections::BTreeMap, sync::Mutex};
+
+/// Keeps track of the seqnos of open snapshots
+///
+/// Multiple snapshots may be open at the same seqno,
+/// so every seqno is reference counted.
+#[derive(Default)]
+pub struct SnapshotTracker(Mutex<BTreeMap<SeqNo, usize>>);
+
+impl SnapshotTracker {
+    /// Registers a snapshot at the given seqno.
+    pub fn register(&self, seqno: SeqNo) {
+        let mut lock = self.0.lock().expect("lock is poisoned");
+        *lock.entry(seqno).or_default() += 1;
+    }
+
+    /// Deregisters a snapshot at the given seqno.
+    ///
+    /// Returns `false` if there is no snapshot at that seqno.
+    pub fn deregister(&self, seqno: SeqNo) -> bool {
+        let mut lock = self.0.lock().expect("lock is poisoned");
+
+        let Some(count) = lock.get_mut(&seqno) else {
+            return false;
+        };
+
+        *count -= 1;
+
+        if *count == 0 {
+            lock.remove(&seqno);
+        }
+
+        true
+    }
+
+    /// Returns the seqnos of all open snapshots, in ascending order.
+    pub fn seqnos(&self) -> Vec<SeqNo> {
+        self.0
+            .lock()
+            .expect("lock is poisoned")
+            .keys()
+            .copied()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn snapshot_tracker_ref_count() {
+        let tracker = SnapshotTracker::default();
+
+        tracker.register(5);
+        tracker.register(5);
+        tracker.register(3);
+        assert_eq!(vec![3, 5], tracker.seqnos());
+
+        assert!(tracker.deregister(5));
+        assert_eq!(vec![3, 5], tracker.seqnos());
+
+        assert!(tracker.deregister(5));
+        assert!(!tracker.deregister(5));
+        assert_eq!(vec![3], tracker.seqnos());
+    }
+}
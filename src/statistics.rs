@@ -0,0 +1,640 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use std::{
+    sync::atomic::{AtomicU64, Ordering::Relaxed},
+    time::{Duration, Instant},
+};
+
+/// Amount of histogram buckets
+///
+/// Bucket `i` holds samples in `[2^(i-1), 2^i)` microseconds,
+/// bucket 0 holds samples of 0 µs.
+const BUCKET_COUNT: usize = 65;
+
+/// Lock-free latency histogram with power-of-two microsecond buckets
+pub struct Histogram {
+    buckets: [AtomicU64; BUCKET_COUNT],
+    count: AtomicU64,
+    sum: AtomicU64,
+    max: AtomicU64,
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self {
+            buckets: std::array::from_fn(|_| AtomicU64::default()),
+            count: AtomicU64::default(),
+            sum: AtomicU64::default(),
+            max: AtomicU64::default(),
+        }
+    }
+}
+
+impl std::fmt::Debug for Histogram {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Histogram")
+            .field("count", &self.count())
+            .field("mean", &self.mean())
+            .field("p50", &self.percentile(0.5))
+            .field("p99", &self.percentile(0.99))
+            .field("max", &self.max())
+            .finish()
+    }
+}
+
+impl Histogram {
+    fn bucket_of(micros: u64) -> usize {
+        (u64::BITS - micros.leading_zeros()) as usize
+    }
+
+    /// Records a sample.
+    pub fn record(&self, duration: Duration) {
+        let micros = u64::try_from(duration.as_micros()).unwrap_or(u64::MAX);
+
+        if let Some(bucket) = self.buckets.get(Self::bucket_of(micros)) {
+            bucket.fetch_add(1, Relaxed);
+        }
+
+        self.count.fetch_add(1, Relaxed);
+        self.sum.fetch_add(micros, Relaxed);
+        self.max.fetch_max(micros, Relaxed);
+    }
+
+    /// Returns the amount of recorded samples.
+    #[must_use]
+    pub fn count(&self) -> u64 {
+        self.count.load(Relaxed)
+    }
+
+    /// Returns the sum of all recorded samples.
+    #[must_use]
+    pub fn sum(&self) -> Duration {
+        Duration::from_micros(self.sum.load(Relaxed))
+    }
+
+    /// Returns the largest recorded sample.
+    #[must_use]
+    pub fn max(&self) -> Duration {
+        Duration::from_micros(self.max.load(Relaxed))
+    }
+
+    /// Returns the average sample.
+    #[must_use]
+    pub fn mean(&self) -> Duration {
+        self.sum
+            .load(Relaxed)
+            .checked_div(self.count())
+            .map_or(Duration::ZERO, Duration::from_micros)
+    }
+
+    /// Returns the inclusive upper bound (in µs) and sample count of every bucket,
+    /// including empty ones, so the bucket boundaries never change.
+    #[allow(unused)]
+    pub(crate) fn buckets(&self) -> impl Iterator<Item = (u64, u64)> + '_ {
+        self.buckets
+            .iter()
+            .enumerate()
+            .map(|(idx, bucket)| (Self::bucket_upper_bound(idx), bucket.load(Relaxed)))
+    }
+
+    fn bucket_upper_bound(idx: usize) -> u64 {
+        // NOTE: Bucket `idx` ends at 2^idx - 1
+        match idx {
+            0 => 0,
+            idx => u64::MAX >> (BUCKET_COUNT - 1 - idx),
+        }
+    }
+
+    /// Returns an upper bound of the given percentile (0.0 - 1.0).
+    ///
+    /// Because samples are bucketed by powers of two, the result
+    /// may overestimate the real percentile by up to 2x.
+    #[must_use]
+    pub fn percentile(&self, p: f64) -> Duration {
+        let count = self.count();
+
+        if count == 0 {
+            return Duration::ZERO;
+        }
+
+        #[allow(
+            clippy::cast_possible_truncation,
+            clippy::cast_sign_loss,
+            clippy::cast_precision_loss
+        )]
+        let rank = ((count as f64) * p.clamp(0.0, 1.0)).ceil().max(1.0) as u64;
+
+        let mut seen = 0;
+
+        for (idx, bucket) in self.buckets.iter().enumerate() {
+            seen += bucket.load(Relaxed);
+
+            if seen >= rank {
+                let upper_bound = Self::bucket_upper_bound(idx);
+                return Duration::from_micros(upper_bound.min(self.max.load(Relaxed)));
+            }
+        }
+
+        self.max()
+    }
+
+    /// Returns a point-in-time copy of the histogram's summary.
+    #[must_use]
+    pub fn snapshot(&self) -> HistogramSnapshot {
+        let micros = |duration: Duration| u64::try_from(duration.as_micros()).unwrap_or(u64::MAX);
+
+        HistogramSnapshot {
+            count: self.count(),
+            sum_us: self.sum.load(Relaxed),
+            max_us: self.max.load(Relaxed),
+            mean_us: micros(self.mean()),
+            p50_us: micros(self.percentile(0.5)),
+            p90_us: micros(self.percentile(0.9)),
+            p99_us: micros(self.percentile(0.99)),
+        }
+    }
+}
+
+/// Summary of a [`Histogram`] at some point in time
+///
+/// All durations are in microseconds.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct HistogramSnapshot {
+    /// Amount of recorded samples
+    pub count: u64,
+
+    /// Sum of all recorded samples
+    pub sum_us: u64,
+
+    /// Largest recorded sample
+    pub max_us: u64,
+
+    /// Average sample
+    pub mean_us: u64,
+
+    /// Upper bound of the median
+    pub p50_us: u64,
+
+    /// Upper bound of the 90th percentile
+    pub p90_us: u64,
+
+    /// Upper bound of the 99th percentile
+    pub p99_us: u64,
+}
+
+/// Collects counters and latency histograms of a tree
+///
+/// Attach a [`Statistics`] object through [`crate::Config::statistics`];
+/// the same object can be shared between multiple trees to aggregate their metrics.
+///
+/// All counters are monotonically increasing and updated using relaxed atomics,
+/// so reading them is cheap, but not linearizable with respect to each other.
+///
+/// # Examples
+///
+/// ```
+/// # use lsm_tree::{AbstractTree, Config, Statistics};
+/// # use std::sync::Arc;
+/// #
+/// # let folder = tempfile::tempdir()?;
+/// let stats = Arc::new(Statistics::default());
+/// let tree = Config::new(folder).statistics(stats.clone()).open()?;
+///
+/// tree.insert("a", "abc", 0);
+/// tree.flush_active_memtable(0)?;
+/// assert!(tree.get("a", None)?.is_some());
+///
+/// assert_eq!(1, stats.flush_count());
+/// assert_eq!(1, stats.get_latency().count());
+/// #
+/// # Ok::<(), lsm_tree::Error>(())
+/// ```
+#[derive(Debug, Default)]
+pub struct Statistics {
+    pub(crate) flush_count: AtomicU64,
+    pub(crate) flush_bytes_read: AtomicU64,
+    pub(crate) flush_bytes_written: AtomicU64,
+
+    pub(crate) compaction_count: AtomicU64,
+    pub(crate) compaction_bytes_read: AtomicU64,
+    pub(crate) compaction_bytes_written: AtomicU64,
+
+    pub(crate) block_cache_hits: AtomicU64,
+    pub(crate) block_cache_misses: AtomicU64,
+    pub(crate) block_bytes_read: AtomicU64,
+
+    pub(crate) bloom_checked: AtomicU64,
+    pub(crate) bloom_useful: AtomicU64,
+
+    pub(crate) segments_probed: AtomicU64,
+
+    get_latency: Histogram,
+    scan_latency: Histogram,
+    stall_time: Histogram,
+}
+
+impl Statistics {
+    /// Returns the amount of memtable flushes.
+    #[must_use]
+    pub fn flush_count(&self) -> u64 {
+        self.flush_count.load(Relaxed)
+    }
+
+    /// Returns the amount of (approximate) memtable bytes consumed by flushes.
+    #[must_use]
+    pub fn flush_bytes_read(&self) -> u64 {
+        self.flush_bytes_read.load(Relaxed)
+    }
+
+    /// Returns the amount of bytes written to disk segments by flushes.
+    #[must_use]
+    pub fn flush_bytes_written(&self) -> u64 {
+        self.flush_bytes_written.load(Relaxed)
+    }
+
+    /// Returns the amount of compactions that merged segments.
+    #[must_use]
+    pub fn compaction_count(&self) -> u64 {
+        self.compaction_count.load(Relaxed)
+    }
+
+    /// Returns the amount of segment bytes read by compactions.
+    #[must_use]
+    pub fn compaction_bytes_read(&self) -> u64 {
+        self.compaction_bytes_read.load(Relaxed)
+    }
+
+    /// Returns the amount of segment bytes written by compactions.
+    #[must_use]
+    pub fn compaction_bytes_written(&self) -> u64 {
+        self.compaction_bytes_written.load(Relaxed)
+    }
+
+    /// Returns the amount of data block reads served by the block cache.
+    #[must_use]
+    pub fn block_cache_hits(&self) -> u64 {
+        self.block_cache_hits.load(Relaxed)
+    }
+
+    /// Returns the amount of data block reads that had to go to disk.
+    #[must_use]
+    pub fn block_cache_misses(&self) -> u64 {
+        self.block_cache_misses.load(Relaxed)
+    }
+
+    /// Returns the amount of (compressed) data block bytes read from disk.
+    #[must_use]
+    pub fn block_bytes_read(&self) -> u64 {
+        self.block_bytes_read.load(Relaxed)
+    }
+
+    /// Returns how often a bloom filter was queried.
+    #[must_use]
+    pub fn bloom_checked(&self) -> u64 {
+        self.bloom_checked.load(Relaxed)
+    }
+
+    /// Returns how often a bloom filter query avoided a segment read.
+    #[must_use]
+    pub fn bloom_useful(&self) -> u64 {
+        self.bloom_useful.load(Relaxed)
+    }
+
+    /// Returns how often a segment was probed by point reads.
+    ///
+    /// Segments that are skipped because of their key range are not counted.
+    #[must_use]
+    pub fn segments_probed(&self) -> u64 {
+        self.segments_probed.load(Relaxed)
+    }
+
+    /// Returns the running estimate of write amplification.
+    ///
+    /// Write amplification is the amount of bytes written to disk segments (by flushes and compactions)
+    /// divided by the amount of bytes ingested into the tree through flushes.
+    ///
+    /// Returns 0.0 if nothing was flushed yet.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn write_amp(&self) -> f64 {
+        let ingested = self.flush_bytes_written();
+
+        if ingested == 0 {
+            return 0.0;
+        }
+
+        (ingested + self.compaction_bytes_written()) as f64 / ingested as f64
+    }
+
+    /// Returns the running estimate of read amplification.
+    ///
+    /// Read amplification is the average amount of segments probed per point read.
+    ///
+    /// Returns 0.0 if there were no point reads yet.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn read_amp(&self) -> f64 {
+        let gets = self.get_latency.count();
+
+        if gets == 0 {
+            return 0.0;
+        }
+
+        self.segments_probed() as f64 / gets as f64
+    }
+
+    /// Returns the latency histogram of point reads.
+    #[must_use]
+    pub fn get_latency(&self) -> &Histogram {
+        &self.get_latency
+    }
+
+    /// Returns the latency histogram of range & prefix scans.
+    ///
+    /// Only the time spent advancing the iterator is measured, so time the caller
+    /// spends between items is not included. A scan is recorded once its iterator is dropped.
+    #[must_use]
+    pub fn scan_latency(&self) -> &Histogram {
+        &self.scan_latency
+    }
+
+    /// Returns the histogram of write stalls.
+    #[must_use]
+    pub fn stall_time(&self) -> &Histogram {
+        &self.stall_time
+    }
+
+    /// Records a write stall.
+    ///
+    /// The tree itself never blocks writers, so this is meant for
+    /// embedders that throttle writes based on the tree's state.
+    pub fn record_stall(&self, duration: Duration) {
+        self.stall_time.record(duration);
+    }
+
+    /// Returns a point-in-time copy of all counters and histograms.
+    ///
+    /// Counters are read one after another, so the snapshot
+    /// is not consistent under concurrent updates.
+    #[must_use]
+    pub fn snapshot(&self) -> StatisticsSnapshot {
+        StatisticsSnapshot {
+            flush_count: self.flush_count(),
+            flush_bytes_read: self.flush_bytes_read(),
+            flush_bytes_written: self.flush_bytes_written(),
+            compaction_count: self.compaction_count(),
+            compaction_bytes_read: self.compaction_bytes_read(),
+            compaction_bytes_written: self.compaction_bytes_written(),
+            block_cache_hits: self.block_cache_hits(),
+            block_cache_misses: self.block_cache_misses(),
+            block_bytes_read: self.block_bytes_read(),
+            bloom_checked: self.bloom_checked(),
+            bloom_useful: self.bloom_useful(),
+            segments_probed: self.segments_probed(),
+            write_amp: self.write_amp(),
+            read_amp: self.read_amp(),
+            get_latency: self.get_latency.snapshot(),
+            scan_latency: self.scan_latency.snapshot(),
+            stall_time: self.stall_time.snapshot(),
+        }
+    }
+
+    pub(crate) fn record_get(&self, start: Instant) {
+        self.get_latency.record(start.elapsed());
+    }
+
+    pub(crate) fn record_block_load(&self, cache_hit: bool, bytes: u64) {
+        if cache_hit {
+            self.block_cache_hits.fetch_add(1, Relaxed);
+        } else {
+            self.block_cache_misses.fetch_add(1, Relaxed);
+            self.block_bytes_read.fetch_add(bytes, Relaxed);
+        }
+    }
+
+    pub(crate) fn record_segment_probe(&self) {
+        self.segments_probed.fetch_add(1, Relaxed);
+    }
+
+    pub(crate) fn record_bloom_check(&self, useful: bool) {
+        self.bloom_checked.fetch_add(1, Relaxed);
+
+        if useful {
+            self.bloom_useful.fetch_add(1, Relaxed);
+        }
+    }
+
+    pub(crate) fn record_flush(&self, bytes_read: u64, bytes_written: u64) {
+        self.flush_count.fetch_add(1, Relaxed);
+        self.flush_bytes_read.fetch_add(bytes_read, Relaxed);
+        self.flush_bytes_written.fetch_add(bytes_written, Relaxed);
+    }
+
+    pub(crate) fn record_compaction(&self, bytes_read: u64, bytes_written: u64) {
+        self.compaction_count.fetch_add(1, Relaxed);
+        self.compaction_bytes_read.fetch_add(bytes_read, Relaxed);
+        self.compaction_bytes_written
+            .fetch_add(bytes_written, Relaxed);
+    }
+}
+
+/// Copy of all [`Statistics`] at some point in time
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct StatisticsSnapshot {
+    /// Amount of memtable flushes
+    pub flush_count: u64,
+
+    /// Amount of (approximate) memtable bytes consumed by flushes
+    pub flush_bytes_read: u64,
+
+    /// Amount of bytes written to disk segments by flushes
+    pub flush_bytes_written: u64,
+
+    /// Amount of compactions that merged segments
+    pub compaction_count: u64,
+
+    /// Amount of segment bytes read by compactions
+    pub compaction_bytes_read: u64,
+
+    /// Amount of segment bytes written by compactions
+    pub compaction_bytes_written: u64,
+
+    /// Amount of data block reads served by the block cache
+    pub block_cache_hits: u64,
+
+    /// Amount of data block reads that had to go to disk
+    pub block_cache_misses: u64,
+
+    /// Amount of (compressed) data block bytes read from disk
+    pub block_bytes_read: u64,
+
+    /// How often a bloom filter was queried
+    pub bloom_checked: u64,
+
+    /// How often a bloom filter query avoided a segment read
+    pub bloom_useful: u64,
+
+    /// How often a segment was probed by point reads
+    pub segments_probed: u64,
+
+    /// Running estimate of write amplification
+    pub write_amp: f64,
+
+    /// Running estimate of read amplification
+    pub read_amp: f64,
+
+    /// Latency of point reads
+    pub get_latency: HistogramSnapshot,
+
+    /// Latency of range & prefix scans
+    pub scan_latency: HistogramSnapshot,
+
+    /// Write stalls
+    pub stall_time: HistogramSnapshot,
+}
+
+/// Callback that is invoked with the scan duration
+/// and the segments the scan read, if a scan was slow
+type SlowScanCallback = Box<dyn FnOnce(Duration, Vec<crate::SegmentId>)>;
+
+/// State of a scan that reports itself if it was slow
+struct SlowScan {
+    threshold: Duration,
+    callback: SlowScanCallback,
+    segments_read: Vec<crate::SegmentId>,
+}
+
+/// Iterator adapter that records the scan latency when dropped
+///
+/// Only the time spent inside `next` and `next_back` is counted, so a scan
+/// that is consumed slowly by the application is not reported as slow.
+pub struct TimedIter<I> {
+    inner: I,
+    elapsed: Duration,
+    statistics: std::sync::Arc<Statistics>,
+    slow_scan: Option<SlowScan>,
+}
+
+impl<I> TimedIter<I> {
+    pub fn new(inner: I, statistics: std::sync::Arc<Statistics>) -> Self {
+        Self {
+            inner,
+            elapsed: Duration::ZERO,
+            statistics,
+            slow_scan: None,
+        }
+    }
+
+    /// Invokes `callback` on drop if the scan took at least `threshold`.
+    pub fn with_slow_log(mut self, threshold: Duration, callback: SlowScanCallback) -> Self {
+        self.slow_scan = Some(SlowScan {
+            threshold,
+            callback,
+            segments_read: Vec::new(),
+        });
+        self
+    }
+
+    fn timed<T>(&mut self, f: impl FnOnce(&mut I) -> T) -> T {
+        let start = Instant::now();
+
+        let result = if let Some(slow_scan) = &mut self.slow_scan {
+            let inner = &mut self.inner;
+            let (result, segments) = crate::slow_operation::trace_segment_reads(|| f(inner));
+
+            // NOTE: A long scan reads many blocks of the same segments
+            for segment_id in segments {
+                if !slow_scan.segments_read.contains(&segment_id) {
+                    slow_scan.segments_read.push(segment_id);
+                }
+            }
+
+            result
+        } else {
+            f(&mut self.inner)
+        };
+
+        self.elapsed += start.elapsed();
+        result
+    }
+}
+
+impl<I: Iterator> Iterator for TimedIter<I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.timed(Iterator::next)
+    }
+}
+
+impl<I: DoubleEndedIterator> DoubleEndedIterator for TimedIter<I> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.timed(DoubleEndedIterator::next_back)
+    }
+}
+
+impl<I> Drop for TimedIter<I> {
+    fn drop(&mut self) {
+        self.statistics.scan_latency.record(self.elapsed);
+
+        if let Some(slow_scan) = self.slow_scan.take() {
+            if self.elapsed >= slow_scan.threshold {
+                (slow_scan.callback)(self.elapsed, slow_scan.segments_read);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn histogram_percentiles() {
+        let histogram = Histogram::default();
+
+        for micros in 1..=100 {
+            histogram.record(Duration::from_micros(micros));
+        }
+
+        assert_eq!(100, histogram.count());
+        assert_eq!(Duration::from_micros(100), histogram.max());
+        assert_eq!(Duration::from_micros(50), histogram.mean());
+
+        assert!(histogram.percentile(0.5) >= Duration::from_micros(50));
+        assert!(histogram.percentile(0.5) <= Duration::from_micros(100));
+        assert_eq!(Duration::from_micros(100), histogram.percentile(1.0));
+    }
+
+    #[test]
+    fn histogram_empty() {
+        let histogram = Histogram::default();
+        assert_eq!(Duration::ZERO, histogram.mean());
+        assert_eq!(Duration::ZERO, histogram.percentile(0.99));
+    }
+
+    #[test]
+    fn statistics_snapshot() {
+        let stats = Statistics::default();
+        stats.record_flush(100, 50);
+        stats.record_block_load(true, 10);
+        stats.record_block_load(false, 10);
+        stats.record_stall(Duration::from_micros(100));
+
+        let snapshot = stats.snapshot();
+        assert_eq!(1, snapshot.flush_count);
+        assert_eq!(50, snapshot.flush_bytes_written);
+        assert_eq!(1, snapshot.block_cache_hits);
+        assert_eq!(1, snapshot.block_cache_misses);
+        assert_eq!(10, snapshot.block_bytes_read);
+        assert_eq!(0, snapshot.get_latency.count);
+        assert_eq!(1, snapshot.stall_time.count);
+        assert_eq!(100, snapshot.stall_time.max_us);
+        assert_eq!(100, snapshot.stall_time.p99_us);
+    }
+}
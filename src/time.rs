@@ -2,8 +2,13 @@
 // This source code is licensed under both the Apache 2.0 and MIT License
 // (found in the LICENSE-* files in the repository)
 
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
 /// Gets the unix timestamp as a duration
-pub fn unix_timestamp() -> std::time::Duration {
+pub fn unix_timestamp() -> Duration {
     let now = std::time::SystemTime::now();
 
     // NOTE: Expect is trivial
@@ -11,3 +16,80 @@ pub fn unix_timestamp() -> std::time::Duration {
     now.duration_since(std::time::SystemTime::UNIX_EPOCH)
         .expect("time went backwards")
 }
+
+/// Source of the current time, used for segment creation times and TTLs
+///
+/// Replace the [`SystemClock`] with a [`ManualClock`] to control time
+/// in tests and simulations, see [`crate::Config::clock`].
+///
+/// Only time can be injected: file I/O always goes through the real filesystem,
+/// so a simulation cannot reorder or drop fsyncs and renames.
+pub trait Clock: Send + Sync {
+    /// Returns the current time as duration since the unix epoch.
+    fn now(&self) -> Duration;
+}
+
+/// Clock that returns the system time
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        unix_timestamp()
+    }
+}
+
+/// Virtual clock that only moves when told to
+///
+/// # Examples
+///
+/// ```
+/// use lsm_tree::{Clock, ManualClock};
+/// use std::time::Duration;
+///
+/// let clock = ManualClock::new(Duration::from_secs(1_000));
+/// clock.advance(Duration::from_secs(5));
+/// assert_eq!(Duration::from_secs(1_005), clock.now());
+/// ```
+#[derive(Debug, Default)]
+pub struct ManualClock(AtomicU64);
+
+impl ManualClock {
+    /// Creates a clock that is stopped at the given unix time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the time does not fit into 64-bit microseconds.
+    #[must_use]
+    pub fn new(now: Duration) -> Self {
+        let clock = Self::default();
+        clock.set(now);
+        clock
+    }
+
+    /// Sets the current time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the time does not fit into 64-bit microseconds.
+    pub fn set(&self, now: Duration) {
+        let micros = u64::try_from(now.as_micros()).expect("time should fit into u64");
+        self.0.store(micros, Ordering::Release);
+    }
+
+    /// Moves the clock forward.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the time does not fit into 64-bit microseconds.
+    pub fn advance(&self, duration: Duration) {
+        let micros = u64::try_from(duration.as_micros()).expect("time should fit into u64");
+        self.0.fetch_add(micros, Ordering::AcqRel);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        Duration::from_micros(self.0.load(Ordering::Acquire))
+    }
+}
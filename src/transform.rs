@@ -0,0 +1,123 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use std::sync::Arc;
+
+/// Transforms data after compression, before it is written to disk,
+/// and reverses the transformation after reading it back
+///
+/// This is meant for encryption at rest: the transform is given the ID of the key
+/// to use, which is recorded per segment (and per blob), so keys can be rotated
+/// without rewriting old data - old segments keep being decoded using their key
+/// until compaction rewrites them with the current key.
+///
+/// Applies to the data and index blocks, bloom filters, key sketches, range tombstones
+/// and the metadata of segments, and to blobs of a [`crate::BlobTree`].
+/// The tree & level manifests only contain segment IDs and settings,
+/// so they are not transformed.
+///
+/// Block checksums cover the transformed data, so corruption is detected
+/// without decoding, see [`crate::AbstractTree::verify`].
+///
+/// # Examples
+///
+/// ```
+/// # let folder = tempfile::tempdir()?;
+/// use lsm_tree::{AbstractTree, BlockTransform, Config};
+/// use std::sync::Arc;
+///
+/// /// Toy cipher, use a real one (e.g. AES-GCM) instead
+/// struct Xor;
+///
+/// impl BlockTransform for Xor {
+///     fn current_key_id(&self) -> u32 {
+///         1
+///     }
+///
+///     fn encode(&self, key_id: u32, data: &[u8]) -> lsm_tree::Result<Vec<u8>> {
+///         Ok(data.iter().map(|byte| byte ^ key_id as u8).collect())
+///     }
+///
+///     fn decode(&self, key_id: u32, data: &[u8]) -> lsm_tree::Result<Vec<u8>> {
+///         self.encode(key_id, data)
+///     }
+/// }
+///
+/// let tree = Config::new(folder).block_transform(Arc::new(Xor)).open()?;
+/// tree.insert("a", "abc", 0);
+/// tree.flush_active_memtable(0)?;
+///
+/// assert_eq!(&*tree.get("a", None)?.unwrap(), b"abc");
+/// #
+/// # Ok::<(), lsm_tree::Error>(())
+/// ```
+pub trait BlockTransform: Send + Sync {
+    /// Returns the ID of the key that new data is written with.
+    fn current_key_id(&self) -> u32;
+
+    /// Transforms (e.g. encrypts) data using the given key.
+    ///
+    /// # Errors
+    ///
+    /// May return `Err` if the key is not available.
+    fn encode(&self, key_id: u32, data: &[u8]) -> crate::Result<Vec<u8>>;
+
+    /// Reverses [`BlockTransform::encode`].
+    ///
+    /// # Errors
+    ///
+    /// May return `Err` if the key is not available, or the data is corrupted.
+    fn decode(&self, key_id: u32, data: &[u8]) -> crate::Result<Vec<u8>>;
+}
+
+/// A block transform, bound to the key that a file was written with
+#[derive(Clone)]
+pub struct KeyedTransform {
+    transform: Arc<dyn BlockTransform>,
+    key_id: u32,
+}
+
+impl KeyedTransform {
+    /// Binds the transform to its current key, for writing new files.
+    pub fn current(transform: Arc<dyn BlockTransform>) -> Self {
+        let key_id = transform.current_key_id();
+        Self { transform, key_id }
+    }
+
+    /// Binds the transform to the key a file was written with.
+    ///
+    /// Returns `Err` if the file uses a key, but no transform is configured.
+    pub fn resolve(
+        transform: Option<&Arc<dyn BlockTransform>>,
+        key_id: Option<u32>,
+    ) -> crate::Result<Option<Self>> {
+        match (transform, key_id) {
+            (_, None) => Ok(None),
+            (Some(transform), Some(key_id)) => Ok(Some(Self {
+                transform: transform.clone(),
+                key_id,
+            })),
+            (None, Some(key_id)) => Err(crate::Error::MissingBlockTransform(key_id)),
+        }
+    }
+
+    /// Returns the ID of the bound key.
+    pub fn key_id(&self) -> u32 {
+        self.key_id
+    }
+
+    pub fn encode(&self, data: &[u8]) -> crate::Result<Vec<u8>> {
+        self.transform.encode(self.key_id, data)
+    }
+
+    pub fn decode(&self, data: &[u8]) -> crate::Result<Vec<u8>> {
+        self.transform.decode(self.key_id, data)
+    }
+}
+
+impl std::fmt::Debug for KeyedTransform {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "KeyedTransform(key_id={})", self.key_id)
+    }
+}
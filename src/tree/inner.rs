@@ -3,8 +3,18 @@
 // (found in the LICENSE-* files in the repository)
 
 use crate::{
-    config::Config, file::LEVELS_MANIFEST_FILE, level_manifest::LevelManifest, memtable::Memtable,
-    segment::meta::SegmentId, stop_signal::StopSignal,
+    compaction::CompactionScheduler,
+    config::Config,
+    file::LEVELS_MANIFEST_FILE,
+    level_manifest::{view::LevelViewCell, LevelManifest},
+    manifest::NamedSnapshots,
+    memtable::Memtable,
+    persist_watch::PersistWatch,
+    segment::meta::SegmentId,
+    seqno_time::SeqnoTimeMap,
+    snapshot_tracker::SnapshotTracker,
+    stop_signal::StopSignal,
+    SeqNo,
 };
 use std::sync::{atomic::AtomicU64, Arc, RwLock};
 
@@ -68,12 +78,44 @@ pub struct TreeInner {
     #[doc(hidden)]
     pub levels: Arc<RwLock<LevelManifest>>,
 
+    /// Latest view of the levels, which readers use instead of the level manifest
+    pub(crate) level_view: Arc<LevelViewCell>,
+
     /// Tree configuration
     pub config: Config,
 
     /// Compaction may take a while; setting the signal to `true`
     /// will interrupt the compaction and kill the worker.
     pub(crate) stop_signal: StopSignal,
+
+    /// Error of the last flush or compaction, if it failed
+    pub(crate) last_error: RwLock<Option<String>>,
+
+    /// Seqno up to which the external journal is persisted
+    ///
+    /// `SeqNo::MAX` if no journal is coordinated with the tree.
+    pub(crate) journal_persisted_seqno: AtomicU64,
+
+    /// Named snapshots, which are persisted in the manifest
+    pub(crate) named_snapshots: RwLock<NamedSnapshots>,
+
+    /// Seqno watermarks sampled with the time they were flushed at
+    pub(crate) seqno_time_map: RwLock<SeqnoTimeMap>,
+
+    /// Seqnos of open snapshots
+    pub(crate) open_snapshots: SnapshotTracker,
+
+    /// Notified whenever flushed segments are registered
+    pub(crate) persist_watch: PersistWatch,
+
+    /// Segment ID counter value at the last [`crate::AbstractTree::clear`]
+    ///
+    /// Segments with a lower ID contain cleared data, so they are
+    /// not registered anymore once their flush finishes.
+    pub(crate) clear_watermark: AtomicU64,
+
+    /// Background compaction workers, if enabled
+    pub(crate) compaction_scheduler: Option<Arc<CompactionScheduler>>,
 }
 
 impl TreeInner {
@@ -84,11 +126,20 @@ impl TreeInner {
         Ok(Self {
             id: get_next_tree_id(),
             segment_id_counter: Arc::new(AtomicU64::default()),
+            compaction_scheduler: config.compaction_strategy.as_ref().map(|_| Arc::default()),
             config,
             active_memtable: Arc::default(),
             sealed_memtables: Arc::default(),
+            level_view: levels.view_cell(),
             levels: Arc::new(RwLock::new(levels)),
             stop_signal: StopSignal::default(),
+            last_error: RwLock::default(),
+            journal_persisted_seqno: AtomicU64::new(SeqNo::MAX),
+            named_snapshots: RwLock::default(),
+            seqno_time_map: RwLock::default(),
+            open_snapshots: SnapshotTracker::default(),
+            persist_watch: PersistWatch::default(),
+            clear_watermark: AtomicU64::default(),
         })
     }
 
@@ -104,5 +155,9 @@ impl Drop for TreeInner {
 
         log::trace!("Sending stop signal to compactors");
         self.stop_signal.send();
+
+        if let Some(scheduler) = &self.compaction_scheduler {
+            scheduler.stop();
+        }
     }
 }
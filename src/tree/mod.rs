@@ -5,32 +5,61 @@
 pub mod inner;
 
 use crate::{
+    bloom::CompositeHash,
     coding::{Decode, Encode},
     compaction::{stream::CompactionStream, CompactionStrategy},
     config::Config,
-    descriptor_table::FileDescriptorTable,
-    level_manifest::LevelManifest,
-    manifest::Manifest,
+    executor::Executor,
+    level_manifest::{view::LevelView, LevelManifest},
+    manifest::{Manifest, NamedSnapshots},
     memtable::Memtable,
-    range::{prefix_to_range, MemtableLockGuard, TreeIter},
+    persist_watch::PersistWatch,
+    quarantine,
+    range::{prefix_to_range, range_bounds_to_owned, MemtableLockGuard, TreeIter},
+    range_tombstone::{RangeTombstone, RangeTombstoneIndex},
     segment::{
         block_index::{full_index::FullBlockIndex, BlockIndexImpl},
+        dump::SegmentDump,
         meta::TableType,
+        prefetch::ReadAhead,
+        trailer::SegmentFileTrailer,
         Segment, SegmentInner,
     },
+    seqno_time::SeqnoTimeMap,
+    snapshot_tracker::SnapshotTracker,
+    statistics::TimedIter,
     stop_signal::StopSignal,
     value::InternalValue,
     version::Version,
-    AbstractTree, BlockCache, KvPair, SegmentId, SeqNo, Snapshot, UserKey, UserValue, ValueType,
+    AbstractTree, Health, KeyRange, KvPair, MemoryUsage, PendingWork, SegmentId, SeqNo,
+    SlowOperation, SlowOperationKind, Snapshot, StallState, Temperature, UserKey, UserValue,
+    ValueType,
 };
 use inner::{MemtableId, SealedMemtables, TreeId, TreeInner};
 use std::{
     io::Cursor,
-    ops::RangeBounds,
-    path::Path,
-    sync::{atomic::AtomicU64, Arc, RwLock, RwLockReadGuard, RwLockWriteGuard},
+    ops::{Bound, RangeBounds},
+    path::{Path, PathBuf},
+    sync::{atomic::AtomicU64, Arc, Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard},
 };
 
+/// Maximum amount of segment files that are fsynced concurrently
+const MAX_SYNC_WORKERS: usize = 4;
+
+/// Segment that is recovered when opening the tree, as (ID, path, level index, cached trailer)
+type PendingSegment = (SegmentId, PathBuf, u8, Option<SegmentFileTrailer>);
+
+/// A key of a [`Tree::get_internal_entries`] batch
+struct PointLookup<'a> {
+    /// Position of the key in the batch
+    idx: usize,
+
+    key: &'a [u8],
+
+    /// Newest version of the key, which may be a tombstone
+    entry: Option<InternalValue>,
+}
+
 fn ignore_tombstone_value(item: InternalValue) -> Option<InternalValue> {
     if item.is_tombstone() {
         None
@@ -39,6 +68,34 @@ fn ignore_tombstone_value(item: InternalValue) -> Option<InternalValue> {
     }
 }
 
+/// Merges sealed memtables into a single memtable, which is flushed
+/// into a segment with the ID of the most recently sealed memtable.
+pub fn coalesce_memtables(
+    memtables: &[(MemtableId, Arc<Memtable>)],
+) -> Option<(MemtableId, Arc<Memtable>)> {
+    let (segment_id, last) = memtables.last()?;
+
+    if memtables.len() == 1 {
+        return Some((*segment_id, last.clone()));
+    }
+
+    // NOTE: Items are ordered by their key (and seqno) in the memtable,
+    // so versions of the same key end up in the right order
+    let coalesced = Memtable::default();
+
+    for (_, memtable) in memtables {
+        for item in memtable.iter() {
+            let _ = coalesced.insert(item);
+        }
+
+        for tombstone in memtable.range_tombstones() {
+            let _ = coalesced.insert_range_tombstone(tombstone);
+        }
+    }
+
+    Some((*segment_id, Arc::new(coalesced)))
+}
+
 /// A log-structured merge tree (LSM-tree/LSMT)
 #[derive(Clone)]
 pub struct Tree(#[doc(hidden)] pub Arc<TreeInner>);
@@ -65,6 +122,10 @@ impl AbstractTree for Tree {
             .sum()
     }
 
+    fn memory_usage(&self) -> MemoryUsage {
+        MemoryUsage::from_levels(&self.levels.read().expect("lock is poisoned"))
+    }
+
     fn sealed_memtable_count(&self) -> usize {
         self.sealed_memtables
             .read()
@@ -72,6 +133,15 @@ impl AbstractTree for Tree {
             .len()
     }
 
+    fn sealed_memtables_size(&self) -> u64 {
+        self.sealed_memtables
+            .read()
+            .expect("lock is poisoned")
+            .iter()
+            .map(|(_, memtable)| u64::from(memtable.size()))
+            .sum()
+    }
+
     fn is_first_level_disjoint(&self) -> bool {
         self.levels
             .read()
@@ -82,21 +152,25 @@ impl AbstractTree for Tree {
             .is_disjoint
     }
 
-    fn verify(&self) -> crate::Result<usize> {
+    fn verify(&self) -> crate::Result<crate::VerifyReport> {
         // NOTE: Lock memtable to prevent any tampering with disk segments
         let _lock = self.lock_active_memtable();
 
-        let mut sum = 0;
+        let level_view = self.level_view.load();
 
-        let level_manifest = self.levels.read().expect("lock is poisoned");
+        let segments = level_view
+            .iter()
+            .map(Segment::verify)
+            .collect::<crate::Result<Vec<_>>>()?;
 
-        for level in &level_manifest.levels {
-            for segment in &level.segments {
-                sum += segment.verify()?;
-            }
-        }
+        Ok(crate::VerifyReport {
+            segments,
+            corrupt_blob_count: 0,
+        })
+    }
 
-        Ok(sum)
+    fn scrub(&self) -> crate::Result<crate::ScrubReport> {
+        crate::scrub::scrub(self, None)
     }
 
     fn keys(
@@ -107,6 +181,34 @@ impl AbstractTree for Tree {
         Box::new(self.create_iter(seqno, index).map(|x| x.map(|(k, _)| k)))
     }
 
+    fn keys_range<K: AsRef<[u8]>, R: RangeBounds<K>>(
+        &self,
+        range: R,
+        seqno: Option<SeqNo>,
+        index: Option<Arc<Memtable>>,
+    ) -> Box<dyn DoubleEndedIterator<Item = crate::Result<UserKey>> + 'static> {
+        let bounds = range_bounds_to_owned(&range);
+
+        Box::new(
+            self.create_internal_range(&bounds, seqno, index)
+                .map(|x| x.map(|item| item.key.user_key)),
+        )
+    }
+
+    fn keys_prefix<K: AsRef<[u8]>>(
+        &self,
+        prefix: K,
+        seqno: Option<SeqNo>,
+        index: Option<Arc<Memtable>>,
+    ) -> Box<dyn DoubleEndedIterator<Item = crate::Result<UserKey>> + 'static> {
+        let bounds = prefix_to_range(prefix.as_ref());
+
+        Box::new(
+            self.create_internal_range(&bounds, seqno, index)
+                .map(|x| x.map(|item| item.key.user_key)),
+        )
+    }
+
     fn values(
         &self,
         seqno: Option<SeqNo>,
@@ -121,76 +223,53 @@ impl AbstractTree for Tree {
         memtable: &Arc<Memtable>,
         seqno_threshold: SeqNo,
     ) -> crate::Result<Option<Segment>> {
-        use crate::{
-            file::SEGMENTS_FOLDER,
-            segment::writer::{Options, Writer},
-        };
-
-        let start = std::time::Instant::now();
-
-        let folder = self.config.path.join(SEGMENTS_FOLDER);
-        log::debug!("writing segment to {folder:?}");
+        let result = self.write_memtable(segment_id, memtable, seqno_threshold, true);
+        self.record_background_error(result)
+    }
 
-        let mut segment_writer = Writer::new(Options {
-            segment_id,
-            folder,
-            data_block_size: self.config.data_block_size,
-            index_block_size: self.config.index_block_size,
-        })?
-        .use_compression(self.config.compression);
+    fn flush_memtables(
+        &self,
+        memtables: &[(SegmentId, Arc<Memtable>)],
+        seqno_threshold: SeqNo,
+    ) -> crate::Result<Vec<Segment>> {
+        let mut segments = Vec::with_capacity(memtables.len());
 
-        {
-            use crate::segment::writer::BloomConstructionPolicy;
+        for (segment_id, memtable) in memtables {
+            let result = self.write_memtable(*segment_id, memtable, seqno_threshold, false);
 
-            if self.config.bloom_bits_per_key >= 0 {
-                segment_writer =
-                    segment_writer.use_bloom_policy(BloomConstructionPolicy::FpRate(0.00001));
-            } else {
-                segment_writer =
-                    segment_writer.use_bloom_policy(BloomConstructionPolicy::BitsPerKey(0));
+            if let Some(segment) = self.record_background_error(result)? {
+                segments.push(segment);
             }
         }
 
-        let iter = memtable.iter().map(Ok);
-        let compaction_filter = CompactionStream::new(iter, seqno_threshold);
-
-        for item in compaction_filter {
-            segment_writer.write(item?)?;
-        }
-
-        let result = self.consume_writer(segment_id, segment_writer)?;
+        let result = self.sync_segment_files(&segments);
+        self.record_background_error(result)?;
 
-        log::debug!("Flushed memtable {segment_id:?} in {:?}", start.elapsed());
+        self.register_segments(&segments)?;
 
-        Ok(result)
+        Ok(segments)
     }
 
     fn register_segments(&self, segments: &[Segment]) -> crate::Result<()> {
-        // NOTE: Mind lock order L -> M -> S
-        log::trace!("Acquiring levels manifest write lock");
-        let mut original_levels = self.levels.write().expect("lock is poisoned");
-
-        // NOTE: Mind lock order L -> M -> S
-        log::trace!("Acquiring sealed memtables write lock");
-        let mut sealed_memtables = self.sealed_memtables.write().expect("lock is poisoned");
+        let memtable_ids = segments.iter().map(Segment::id).collect::<Vec<_>>();
+        self.register_segments_replacing(segments, &memtable_ids)
+    }
 
-        original_levels.atomic_swap(|recipe| {
-            for segment in segments.iter().cloned() {
-                recipe
-                    .first_mut()
-                    .expect("first level should exist")
-                    .insert(segment);
-            }
-        })?;
+    fn flush_memtables_coalesced(
+        &self,
+        memtables: &[(MemtableId, Arc<Memtable>)],
+        seqno_threshold: SeqNo,
+    ) -> crate::Result<Option<Segment>> {
+        let Some((segment_id, memtable)) = coalesce_memtables(memtables) else {
+            return Ok(None);
+        };
 
-        // eprintln!("{original_levels}");
+        let segment = self.flush_memtable(segment_id, &memtable, seqno_threshold)?;
 
-        for segment in segments {
-            log::trace!("releasing sealed memtable {}", segment.id());
-            sealed_memtables.remove(segment.id());
-        }
+        let memtable_ids = memtables.iter().map(|(id, _)| *id).collect::<Vec<_>>();
+        self.register_segments_replacing(segment.as_slice(), &memtable_ids)?;
 
-        Ok(())
+        Ok(segment)
     }
 
     fn lock_active_memtable(&self) -> RwLockWriteGuard<'_, Memtable> {
@@ -207,6 +286,61 @@ impl AbstractTree for Tree {
         memtable_lock.add(id, memtable);
     }
 
+    fn mark_journal_persisted(&self, seqno: SeqNo) {
+        self.journal_persisted_seqno
+            .store(seqno, std::sync::atomic::Ordering::Release);
+    }
+
+    fn create_named_snapshot(&self, name: &str, seqno: SeqNo) -> crate::Result<Snapshot> {
+        if u16::try_from(name.len()).is_err() {
+            return Err(crate::Error::InvalidInput(
+                "snapshot names can be 65535 bytes in length",
+            ));
+        }
+
+        let mut named_snapshots = self.named_snapshots.write().expect("lock is poisoned");
+
+        let mut updated = named_snapshots.clone();
+        updated.insert(name.to_owned(), seqno);
+        self.persist_named_snapshots(&updated)?;
+
+        *named_snapshots = updated;
+        drop(named_snapshots);
+
+        Ok(self.snapshot(seqno))
+    }
+
+    fn named_snapshot(&self, name: &str) -> Option<Snapshot> {
+        self.get_named_snapshot_seqno(name)
+            .map(|seqno| self.snapshot(seqno))
+    }
+
+    fn release_named_snapshot(&self, name: &str) -> crate::Result<bool> {
+        let mut named_snapshots = self.named_snapshots.write().expect("lock is poisoned");
+
+        if !named_snapshots.contains_key(name) {
+            return Ok(false);
+        }
+
+        let mut updated = named_snapshots.clone();
+        updated.remove(name);
+        self.persist_named_snapshots(&updated)?;
+
+        *named_snapshots = updated;
+        drop(named_snapshots);
+
+        Ok(true)
+    }
+
+    fn list_named_snapshots(&self) -> Vec<(String, SeqNo)> {
+        self.named_snapshots
+            .read()
+            .expect("lock is poisoned")
+            .iter()
+            .map(|(name, seqno)| (name.clone(), *seqno))
+            .collect()
+    }
+
     fn compact(
         &self,
         strategy: Arc<dyn CompactionStrategy>,
@@ -216,13 +350,147 @@ impl AbstractTree for Tree {
 
         let mut opts = Options::from_tree(self, strategy);
         opts.eviction_seqno = seqno_threshold;
-        do_compaction(&opts)?;
+        self.record_background_error(do_compaction(&opts))?;
 
         log::debug!("lsm-tree: compaction run over");
 
         Ok(())
     }
 
+    fn clear(&self) -> crate::Result<()> {
+        use crate::file::SEGMENTS_FOLDER;
+
+        // NOTE: Mind lock order L -> M -> S
+        log::trace!("clear: acquiring levels manifest write lock");
+        let mut levels = self.levels.write().expect("lock is poisoned");
+
+        log::trace!("clear: acquiring active memtable write lock");
+        let mut active_memtable = self.lock_active_memtable();
+
+        log::trace!("clear: acquiring sealed memtables write lock");
+        let mut sealed_memtables = self.lock_sealed_memtables();
+
+        let old_segments = levels.iter().cloned().collect::<Vec<_>>();
+
+        levels.atomic_swap(|recipe| {
+            for segment in &old_segments {
+                for level in recipe.iter_mut() {
+                    level.remove(segment.id());
+                }
+            }
+        })?;
+
+        // IMPORTANT: Memtables that are being flushed right now contain cleared data
+        self.clear_watermark.store(
+            self.segment_id_counter
+                .load(std::sync::atomic::Ordering::Acquire),
+            std::sync::atomic::Ordering::Release,
+        );
+
+        *active_memtable = Memtable::default();
+        *sealed_memtables = SealedMemtables::default();
+
+        drop(sealed_memtables);
+        drop(active_memtable);
+        drop(levels);
+
+        // NOTE: Readers may still hold a level view containing the old segments,
+        // so their files are only removed once the last reference is gone
+        let segments_base_folder = self.config.path.join(SEGMENTS_FOLDER);
+
+        for segment in &old_segments {
+            segment.mark_as_deleted(segments_base_folder.join(segment.id().to_string()));
+        }
+
+        log::debug!("Cleared tree, dropped {} segments", old_segments.len());
+
+        Ok(())
+    }
+
+    fn drop_prefix<K: AsRef<[u8]>>(&self, prefix: K, seqno: SeqNo) -> crate::Result<usize> {
+        let prefix = prefix.as_ref();
+
+        // NOTE: Items in memtables, or in segments that span other keys as well,
+        // are deleted by a single range tombstone
+        let end = if let Bound::Excluded(end) = prefix_to_range(prefix).1 {
+            Some(end)
+        } else {
+            // NOTE: The prefix has no upper bound (e.g. it is all 0xFF bytes),
+            // so the range tombstone ends right after the largest key of the prefix
+            self.prefix(prefix, None, None)
+                .next_back()
+                .transpose()?
+                .map(|(key, _)| {
+                    let mut end = key.to_vec();
+                    end.push(0);
+                    UserKey::from(end)
+                })
+        };
+
+        if let Some(end) = end {
+            self.remove_range(UserKey::from(prefix)..end, seqno);
+        }
+
+        let dropped_count = self.drop_prefix_segments(prefix, seqno)?;
+
+        log::debug!("Dropped {dropped_count} segments of prefix {prefix:?}");
+
+        Ok(dropped_count)
+    }
+
+    #[allow(clippy::significant_drop_tightening)]
+    fn pending_work(&self, strategy: &dyn CompactionStrategy) -> PendingWork {
+        use crate::compaction::Choice;
+
+        // NOTE: Mind lock order L -> M -> S
+        let levels = self.levels.read().expect("lock is poisoned");
+        let sealed = self.sealed_memtables.read().expect("lock is poisoned");
+
+        let next_compaction = strategy.choose(&levels, &self.config);
+
+        let pending_drop_bytes = match &next_compaction {
+            Choice::Drop(ids) => levels
+                .iter()
+                .filter(|segment| ids.contains(&segment.id()))
+                .map(|segment| segment.metadata.file_size)
+                .sum(),
+            _ => 0,
+        };
+
+        PendingWork {
+            compacting_segment_count: levels
+                .iter()
+                .filter(|segment| levels.hidden_set().is_hidden(segment.id()))
+                .count(),
+            sealed_memtable_count: sealed.len(),
+            flush_backlog_bytes: sealed.iter().map(|(_, mt)| u64::from(mt.size())).sum(),
+            pending_drop_bytes,
+            next_compaction,
+        }
+    }
+
+    fn health(&self) -> Health {
+        // NOTE: Mind lock order L -> M -> S
+        let levels = self.levels.read().expect("lock is poisoned");
+        let sealed_memtable_count = self.sealed_memtable_count();
+
+        let l0_segment_count = levels.first_level_segment_count();
+
+        let stall = StallState::new(&self.config, l0_segment_count, sealed_memtable_count);
+
+        Health {
+            l0_segment_count,
+            l0_slowdown_threshold: self.config.l0_slowdown_threshold,
+            l0_stop_threshold: self.config.l0_stop_threshold,
+            stall,
+            compaction_debt_bytes: levels.compaction_debt(),
+            sealed_memtable_count,
+            blob_stale_bytes: 0,
+            blob_stale_ratio: 0.0,
+            last_error: self.last_error.read().expect("lock is poisoned").clone(),
+        }
+    }
+
     fn get_next_segment_id(&self) -> SegmentId {
         self.0.get_next_segment_id()
     }
@@ -264,11 +532,50 @@ impl AbstractTree for Tree {
 
         log::trace!("rotate: added memtable id={tmp_memtable_id} to sealed memtables");
 
+        if let (Some(observer), Some(highest_seqno)) = (
+            &self.config.journal_observer,
+            yanked_memtable.get_highest_seqno(),
+        ) {
+            // NOTE: Still holding the memtable locks, so no write can slip in
+            observer.on_memtable_rotated(tmp_memtable_id, highest_seqno);
+        }
+
         Some((tmp_memtable_id, yanked_memtable))
     }
 
     fn segment_count(&self) -> usize {
-        self.levels.read().expect("lock is poisoned").len()
+        self.level_view.load().len()
+    }
+
+    fn estimated_unique_keys(&self) -> crate::Result<u64> {
+        self.estimate_unique_keys(None)
+    }
+
+    fn estimated_unique_keys_in_range(&self, key_range: &KeyRange) -> crate::Result<u64> {
+        self.estimate_unique_keys(Some(key_range))
+    }
+
+    fn overlapping_segment_ids(&self, key_range: &KeyRange) -> Vec<Vec<SegmentId>> {
+        self.level_view
+            .load()
+            .levels
+            .iter()
+            .map(|level| {
+                level
+                    .overlapping_segments(key_range)
+                    .map(Segment::id)
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn segment_access_stats(&self) -> Vec<crate::SegmentAccessStats> {
+        self.level_view
+            .load()
+            .levels
+            .iter()
+            .flat_map(|level| level.iter().map(Segment::access_stats))
+            .collect()
     }
 
     fn first_level_segment_count(&self) -> usize {
@@ -295,10 +602,14 @@ impl AbstractTree for Tree {
     }
 
     fn disk_space(&self) -> u64 {
-        let levels = self.levels.read().expect("lock is poisoned");
+        let levels = self.level_view.load();
         levels.iter().map(|x| x.metadata.file_size).sum()
     }
 
+    fn space_amp(&self) -> f32 {
+        self.levels.read().expect("lock is poisoned").space_amp()
+    }
+
     fn get_highest_memtable_seqno(&self) -> Option<SeqNo> {
         let active = self
             .active_memtable
@@ -319,25 +630,99 @@ impl AbstractTree for Tree {
     }
 
     fn get_highest_persisted_seqno(&self) -> Option<SeqNo> {
-        let levels = self.levels.read().expect("lock is poisoned");
+        let levels = self.level_view.load();
         levels
             .iter()
             .map(super::segment::Segment::get_highest_seqno)
             .max()
     }
 
+    fn wait_for_persisted(&self, seqno: SeqNo, timeout: Option<std::time::Duration>) -> bool {
+        self.persist_watch
+            .wait_until(|| self.is_persisted(seqno), timeout)
+    }
+
     fn snapshot(&self, seqno: SeqNo) -> Snapshot {
         use crate::AnyTree::Standard;
 
         Snapshot::new(Standard(self.clone()), seqno)
     }
 
+    fn cursor(&self, seqno: Option<SeqNo>) -> crate::Cursor {
+        crate::Cursor::new(crate::AnyTree::Standard(self.clone()), seqno)
+    }
+
+    fn compaction_scheduler(&self) -> Option<&crate::compaction::CompactionScheduler> {
+        self.0.compaction_scheduler.as_deref()
+    }
+
+    fn seqno_at_time(&self, time: std::time::Duration) -> Option<SeqNo> {
+        self.seqno_time_map
+            .read()
+            .expect("lock is poisoned")
+            .seqno_at(time)
+    }
+
+    fn register_snapshot(&self, seqno: SeqNo) {
+        self.open_snapshots.register(seqno);
+    }
+
+    fn deregister_snapshot(&self, seqno: SeqNo) -> bool {
+        self.open_snapshots.deregister(seqno)
+    }
+
+    fn lowest_snapshot_seqno(&self) -> Option<SeqNo> {
+        self.pinned_snapshot_seqnos().first().copied()
+    }
+
     fn get<K: AsRef<[u8]>>(
         &self,
         key: K,
         seqno: Option<SeqNo>,
     ) -> crate::Result<Option<UserValue>> {
-        Ok(self.get_internal_entry(key, seqno)?.map(|x| x.value))
+        let start = std::time::Instant::now();
+
+        let (item, segments_read) = self.trace_reads(|| self.get_internal_entry(&key, seqno));
+        let item = item?.map(|x| x.value);
+
+        self.config.statistics.record_get(start);
+        self.check_slow_get(
+            || SlowOperationKind::Get {
+                key: key.as_ref().into(),
+            },
+            seqno,
+            start,
+            segments_read,
+        );
+
+        Ok(item)
+    }
+
+    fn get_many<K: AsRef<[u8]>, I: IntoIterator<Item = K>>(
+        &self,
+        keys: I,
+        seqno: Option<SeqNo>,
+    ) -> crate::Result<Vec<Option<UserValue>>> {
+        let start = std::time::Instant::now();
+
+        let (items, segments_read) = self.trace_reads(|| self.get_internal_entries(keys, seqno));
+
+        let items = items?
+            .into_iter()
+            .map(|item| item.map(|x| x.value))
+            .collect::<Vec<_>>();
+
+        self.config.statistics.record_get(start);
+        self.check_slow_get(
+            || SlowOperationKind::GetMany {
+                key_count: items.len(),
+            },
+            seqno,
+            start,
+            segments_read,
+        );
+
+        Ok(items)
     }
 
     fn range<K: AsRef<[u8]>, R: RangeBounds<K>>(
@@ -346,7 +731,16 @@ impl AbstractTree for Tree {
         seqno: Option<SeqNo>,
         index: Option<Arc<Memtable>>,
     ) -> Box<dyn DoubleEndedIterator<Item = crate::Result<KvPair>> + 'static> {
-        Box::new(self.create_range(&range, seqno, index))
+        let bounds = range_bounds_to_owned(&range);
+
+        Box::new(self.slow_scan_log(
+            TimedIter::new(
+                self.create_range(&bounds, seqno, index),
+                self.config.statistics.clone(),
+            ),
+            bounds,
+            seqno,
+        ))
     }
 
     fn prefix<K: AsRef<[u8]>>(
@@ -355,7 +749,16 @@ impl AbstractTree for Tree {
         seqno: Option<SeqNo>,
         index: Option<Arc<Memtable>>,
     ) -> Box<dyn DoubleEndedIterator<Item = crate::Result<KvPair>> + 'static> {
-        Box::new(self.create_prefix(prefix, seqno, index))
+        let bounds = prefix_to_range(prefix.as_ref());
+
+        Box::new(self.slow_scan_log(
+            TimedIter::new(
+                self.create_range(&bounds, seqno, index),
+                self.config.statistics.clone(),
+            ),
+            bounds,
+            seqno,
+        ))
     }
 
     fn insert<K: Into<UserKey>, V: Into<UserValue>>(
@@ -377,60 +780,465 @@ impl AbstractTree for Tree {
         let value = InternalValue::new_weak_tombstone(key, seqno);
         self.append_entry(value)
     }
-}
 
-impl Tree {
-    /// Opens an LSM-tree in the given directory.
-    ///
-    /// Will recover previous state if the folder was previously
-    /// occupied by an LSM-tree, including the previous configuration.
-    /// If not, a new tree will be initialized with the given config.
-    ///
-    /// After recovering a previous state, use [`Tree::set_active_memtable`]
-    /// to fill the memtable with data from a write-ahead log for full durability.
-    ///
-    /// # Errors
-    ///
-    /// Returns error, if an IO error occurred.
-    pub(crate) fn open(config: Config) -> crate::Result<Self> {
-        use crate::file::MANIFEST_FILE;
+    fn remove_range<K: Into<UserKey>>(
+        &self,
+        range: std::ops::Range<K>,
+        seqno: SeqNo,
+    ) -> (u32, u32) {
+        let start = range.start.into();
+        let end = range.end.into();
 
-        log::debug!("Opening LSM-tree at {:?}", config.path);
+        let memtable_lock = self.active_memtable.read().expect("lock is poisoned");
 
-        // Check for old version
-        if config.path.join("version").try_exists()? {
-            return Err(crate::Error::InvalidVersion(Version::V1));
+        if start >= end {
+            return (0, memtable_lock.size());
         }
 
-        let tree = if config.path.join(MANIFEST_FILE).try_exists()? {
-            Self::recover(config)
-        } else {
-            Self::create_new(config)
-        }?;
+        memtable_lock.insert_range_tombstone(RangeTombstone::new(start, end, seqno))
+    }
 
-        Ok(tree)
+    fn try_insert<K: Into<UserKey>, V: Into<UserValue>>(
+        &self,
+        key: K,
+        value: V,
+        seqno: SeqNo,
+    ) -> crate::Result<(u32, u32)> {
+        let value = InternalValue::try_from_components(key, value, seqno, ValueType::Value)?;
+        Ok(self.append_entry(value))
     }
 
-    pub(crate) fn read_lock_active_memtable(&self) -> RwLockReadGuard<'_, Memtable> {
-        self.active_memtable.read().expect("lock is poisoned")
+    fn try_remove<K: Into<UserKey>>(&self, key: K, seqno: SeqNo) -> crate::Result<(u32, u32)> {
+        let value = InternalValue::try_from_components(key, [], seqno, ValueType::Tombstone)?;
+        Ok(self.append_entry(value))
+    }
+
+    fn try_remove_weak<K: Into<UserKey>>(&self, key: K, seqno: SeqNo) -> crate::Result<(u32, u32)> {
+        let value = InternalValue::try_from_components(key, [], seqno, ValueType::WeakTombstone)?;
+        Ok(self.append_entry(value))
+    }
+}
+
+impl Tree {
+    /// Returns `Err` if the memtable contains writes that are
+    /// not persisted in the external journal yet.
+    pub(crate) fn check_journal_persisted(&self, memtable: &Memtable) -> crate::Result<()> {
+        let persisted_seqno = self
+            .journal_persisted_seqno
+            .load(std::sync::atomic::Ordering::Acquire);
+
+        match memtable.get_highest_seqno() {
+            Some(seqno) if seqno > persisted_seqno => {
+                log::debug!(
+                    "Not flushing memtable, journal is only persisted up to seqno={persisted_seqno}"
+                );
+                Err(crate::Error::JournalNotPersisted(seqno))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Writes a memtable to a new disk segment.
+    ///
+    /// If `fsync` is false, the segment file needs to be synced
+    /// using [`Tree::sync_segment_files`] before it is registered.
+    pub(crate) fn write_memtable(
+        &self,
+        segment_id: SegmentId,
+        memtable: &Arc<Memtable>,
+        seqno_threshold: SeqNo,
+        fsync: bool,
+    ) -> crate::Result<Option<Segment>> {
+        self.check_journal_persisted(memtable)?;
+
+        let start = std::time::Instant::now();
+
+        let mut segment_writer = self
+            .create_segment_writer(segment_id)?
+            .use_temperature(Some(Temperature::Hot))
+            .use_fsync(fsync);
+
+        // NOTE: Expired values are written as tombstones
+        let now = self.config.get_clock().now();
+        let iter = memtable
+            .iter()
+            .map(|item| Ok(crate::ttl::expire(item, now)));
+        let compaction_filter = CompactionStream::new(iter, seqno_threshold)
+            .with_snapshots(self.pinned_snapshot_seqnos())
+            .with_merge_operator(self.config.merge_operator.clone(), false)
+            .with_range_tombstones(memtable.range_tombstones().collect());
+
+        for item in compaction_filter {
+            segment_writer.write(item?)?;
+        }
+
+        for tombstone in memtable.range_tombstones() {
+            segment_writer.write_range_tombstone(tombstone);
+        }
+
+        let result = self.consume_writer(segment_id, segment_writer)?;
+
+        self.record_flush(memtable, result.as_ref(), 0, start);
+
+        log::debug!("Flushed memtable {segment_id:?} in {:?}", start.elapsed());
+
+        Ok(result)
+    }
+
+    /// Records a finished flush in the statistics, and reports it if it was slow.
+    ///
+    /// `extra_bytes_written` are bytes that were written outside
+    /// of the segment, e.g. into blob files.
+    pub(crate) fn record_flush(
+        &self,
+        memtable: &Memtable,
+        segment: Option<&Segment>,
+        extra_bytes_written: u64,
+        start: std::time::Instant,
+    ) {
+        if let Some(threshold) = self.config.slow_operation_threshold {
+            let elapsed = start.elapsed();
+
+            if elapsed >= threshold {
+                crate::slow_operation::report(
+                    &self.config,
+                    SlowOperation {
+                        tree_id: self.id,
+                        kind: SlowOperationKind::Flush {
+                            memtable_size: memtable.size().into(),
+                            segment: segment.map(Segment::id),
+                        },
+                        seqno: None,
+                        elapsed,
+                        segments_read: Vec::new(),
+                    },
+                );
+            }
+        }
+
+        self.config.statistics.record_flush(
+            memtable.size().into(),
+            segment.map_or(0, |segment| segment.metadata.file_size) + extra_bytes_written,
+        );
+    }
+
+    /// Atomically registers flushed disk segments into the tree,
+    /// removing the given sealed memtables.
+    pub(crate) fn register_segments_replacing(
+        &self,
+        segments: &[Segment],
+        memtable_ids: &[MemtableId],
+    ) -> crate::Result<()> {
+        self.register_segments_into(segments, memtable_ids, |_, _| 0)
+    }
+
+    /// Returns the deepest level an ingested segment can be placed in.
+    ///
+    /// The ingested data is newer than all other data, so no level above
+    /// the target level (including itself) may contain an overlapping segment.
+    /// Levels that are being compacted are avoided as well, because the
+    /// compaction may write overlapping segments into them.
+    fn choose_ingest_level(levels: &LevelManifest, segment: &Segment) -> usize {
+        let busy_levels = levels.busy_levels();
+        let mut target = 0;
+
+        for (idx, level) in levels.levels.iter().enumerate() {
+            // NOTE: Level count is u8
+            #[allow(clippy::cast_possible_truncation)]
+            let is_busy = busy_levels.contains(&(idx as u8));
+
+            let overlaps = level.segments.iter().any(|other| {
+                other
+                    .metadata
+                    .key_range
+                    .overlaps_with_key_range(&segment.metadata.key_range)
+            });
+
+            if is_busy || overlaps {
+                break;
+            }
+
+            target = idx;
+        }
+
+        target
+    }
+
+    /// Atomically registers disk segments into the levels chosen by `choose_level`,
+    /// removing the given sealed memtables.
+    fn register_segments_into<F: Fn(&LevelManifest, &Segment) -> usize>(
+        &self,
+        segments: &[Segment],
+        memtable_ids: &[MemtableId],
+        choose_level: F,
+    ) -> crate::Result<()> {
+        // NOTE: Mind lock order L -> M -> S
+        log::trace!("Acquiring levels manifest write lock");
+        let mut original_levels = self.levels.write().expect("lock is poisoned");
+
+        // NOTE: Mind lock order L -> M -> S
+        log::trace!("Acquiring sealed memtables write lock");
+        let mut sealed_memtables = self.sealed_memtables.write().expect("lock is poisoned");
+
+        let segments = self.swap_in_segments(
+            &mut original_levels,
+            &mut sealed_memtables,
+            segments,
+            memtable_ids,
+            choose_level,
+        )?;
+
+        drop(sealed_memtables);
+        drop(original_levels);
+
+        self.on_segments_registered(&segments);
+
+        Ok(())
+    }
+
+    /// Inserts disk segments into the levels chosen by `choose_level`, and
+    /// removes the given sealed memtables, while the caller holds the locks.
+    ///
+    /// Returns the segments that were registered.
+    fn swap_in_segments<F: Fn(&LevelManifest, &Segment) -> usize>(
+        &self,
+        original_levels: &mut LevelManifest,
+        sealed_memtables: &mut SealedMemtables,
+        segments: &[Segment],
+        memtable_ids: &[MemtableId],
+        choose_level: F,
+    ) -> crate::Result<Vec<Segment>> {
+        // NOTE: Segments that were written before the tree was cleared are discarded
+        let clear_watermark = self
+            .clear_watermark
+            .load(std::sync::atomic::Ordering::Acquire);
+
+        let (segments, cleared_segments): (Vec<_>, Vec<_>) = segments
+            .iter()
+            .cloned()
+            .partition(|segment| segment.id() >= clear_watermark);
+
+        for segment in cleared_segments {
+            log::debug!("Discarding segment {} of cleared tree", segment.id());
+
+            segment.mark_as_deleted(
+                self.config
+                    .path
+                    .join(crate::file::SEGMENTS_FOLDER)
+                    .join(segment.id().to_string()),
+            );
+        }
+
+        let targets = segments
+            .iter()
+            .map(|segment| (choose_level(original_levels, segment), segment.clone()))
+            .collect::<Vec<_>>();
+
+        original_levels.atomic_swap(|recipe| {
+            for (level_idx, segment) in targets {
+                recipe
+                    .get_mut(level_idx)
+                    .expect("level should exist")
+                    .insert(segment);
+            }
+        })?;
+
+        for &memtable_id in memtable_ids {
+            log::trace!("releasing sealed memtable {memtable_id}");
+            sealed_memtables.remove(memtable_id);
+        }
+
+        Ok(segments)
+    }
+
+    /// Notifies waiters, the compaction scheduler and the journal observer
+    /// about newly registered segments.
+    fn on_segments_registered(&self, segments: &[Segment]) {
+        if let Some(seqno) = segments.iter().map(|x| x.metadata.seqnos.1).max() {
+            self.sample_seqno_time(seqno + 1);
+
+            self.persist_watch.notify();
+
+            if let Some(scheduler) = &self.compaction_scheduler {
+                scheduler.notify();
+            }
+
+            if let Some(observer) = &self.config.journal_observer {
+                if let Some(persisted_seqno) = self.get_highest_persisted_seqno() {
+                    observer.on_segments_persisted(persisted_seqno);
+                }
+            }
+        }
+    }
+
+    /// Merges the key sketches of all segments (overlapping the key range, if given),
+    /// and the keys of all memtables.
+    fn estimate_unique_keys(&self, key_range: Option<&KeyRange>) -> crate::Result<u64> {
+        use crate::segment::key_sketch::KeySketch;
+
+        let mut sketch = KeySketch::default();
+        let mut unsketched_key_count = 0;
+
+        let level_view = self.level_view.load();
+
+        for segment in level_view.iter().filter(|segment| {
+            key_range.map_or(true, |range| {
+                segment.metadata.key_range.overlaps_with_key_range(range)
+            })
+        }) {
+            if let Some(segment_sketch) = segment.key_sketch()? {
+                sketch.merge(segment_sketch);
+            } else {
+                unsketched_key_count += segment.metadata.key_count;
+            }
+        }
+
+        let mut insert_memtable = |memtable: &Memtable| {
+            for item in memtable.iter() {
+                if key_range.map_or(true, |range| range.contains_key(&item.key.user_key)) {
+                    sketch.insert(&item.key.user_key);
+                }
+            }
+        };
+
+        // NOTE: Mind lock order M -> S
+        let active = self.active_memtable.read().expect("lock is poisoned");
+        let sealed = self.sealed_memtables.read().expect("lock is poisoned");
+
+        insert_memtable(&active);
+
+        for (_, memtable) in sealed.iter() {
+            insert_memtable(memtable);
+        }
+
+        drop(sealed);
+        drop(active);
+
+        Ok(sketch.estimate() + unsketched_key_count)
+    }
+
+    /// Creates a writer for a new segment in the tree's segment folder.
+    fn create_segment_writer(
+        &self,
+        segment_id: SegmentId,
+    ) -> crate::Result<crate::segment::writer::Writer> {
+        use crate::{
+            file::SEGMENTS_FOLDER,
+            segment::writer::{Options, Writer},
+        };
+
+        let folder = self.config.path.join(SEGMENTS_FOLDER);
+        log::debug!("writing segment to {folder:?}");
+
+        let segment_writer = Writer::new(Options {
+            segment_id,
+            folder,
+            data_block_size: self.config.data_block_size,
+            index_block_size: self.config.index_block_size,
+        })?
+        .use_compression(self.config.compression)
+        .use_bloom_layout(self.config.bloom_layout)
+        .use_block_size_policy(self.config.block_size_policy)
+        .use_transform(self.config.current_transform())
+        .use_clock(self.config.get_clock())
+        .use_paranoid_checks(self.config.paranoid_checks)
+        .use_bloom_policy(self.config.bloom_policy(0));
+
+        Ok(segment_writer)
     }
 
-    // TODO: Expose as public function, however:
-    // TODO: Right now this is somewhat unsafe to expose as
-    // major compaction needs ALL segments, right now it just takes as many
-    // as it can, which may make the LSM inconsistent.
-    // TODO: There should also be a function to partially compact levels and individual segments
+    /// Fsyncs the files of the given segments concurrently (using at most
+    /// [`MAX_SYNC_WORKERS`] tasks on the executor), and then their folder once.
+    pub(crate) fn sync_segment_files(&self, segments: &[Segment]) -> crate::Result<()> {
+        use crate::file::{fsync_directory, SEGMENTS_FOLDER};
+
+        if segments.is_empty() {
+            return Ok(());
+        }
+
+        let folder = self.config.path.join(SEGMENTS_FOLDER);
+
+        let jobs = segments
+            .iter()
+            .map(|segment| {
+                let path = folder.join(segment.id().to_string());
+
+                move || -> crate::Result<()> {
+                    std::fs::OpenOptions::new()
+                        .write(true)
+                        .open(path)?
+                        .sync_all()?;
+
+                    Ok(())
+                }
+            })
+            .collect::<Vec<_>>();
+
+        // NOTE: A worker that panics is reported as an I/O error
+        for result in crate::executor::run_bounded(&*self.config.executor(), jobs, MAX_SYNC_WORKERS)
+        {
+            result?;
+        }
+
+        // IMPORTANT: fsync folder on Unix
+        fsync_directory(&folder)?;
+
+        log::debug!("Synced {} segment files", segments.len());
+
+        Ok(())
+    }
 
-    /// Performs major compaction, blocking the caller until it's done.
+    /// Opens an LSM-tree in the given directory.
+    ///
+    /// Will recover previous state if the folder was previously
+    /// occupied by an LSM-tree, including the previous configuration.
+    /// If not, a new tree will be initialized with the given config.
+    ///
+    /// After recovering a previous state, use [`Tree::set_active_memtable`]
+    /// to fill the memtable with data from a write-ahead log for full durability.
     ///
     /// # Errors
     ///
-    /// Will return `Err` if an IO error occurs.
-    #[doc(hidden)]
-    pub fn major_compact(&self, target_size: u64, seqno_threshold: SeqNo) -> crate::Result<()> {
-        log::info!("Starting major compaction");
-        let strategy = Arc::new(crate::compaction::major::Strategy::new(target_size));
-        self.compact(strategy, seqno_threshold)
+    /// Returns error, if an IO error occurred.
+    pub(crate) fn open(config: Config) -> crate::Result<Self> {
+        use crate::file::MANIFEST_FILE;
+
+        log::debug!("Opening LSM-tree at {:?}", config.path);
+
+        // Check for old version
+        if config.path.join("version").try_exists()? {
+            return Err(crate::Error::InvalidVersion(Version::V1));
+        }
+
+        let tree = if config.path.join(MANIFEST_FILE).try_exists()? {
+            Self::recover(config)
+        } else {
+            Self::create_new(config)
+        }?;
+
+        // NOTE: Blob trees spawn their own scrubber, which also verifies blob files
+        if let (Some(period), crate::TreeType::Standard) =
+            (tree.config.scrub_period, tree.config.tree_type)
+        {
+            crate::scrub::spawn(&tree, None, period);
+        }
+
+        // NOTE: Blob trees spawn their own compaction workers, which also run blob GC
+        if let (Some(scheduler), crate::TreeType::Standard) =
+            (&tree.compaction_scheduler, tree.config.tree_type)
+        {
+            crate::compaction::scheduler::spawn(&tree, scheduler, crate::AnyTree::Standard);
+        }
+
+        if let Some(manager) = &tree.config.write_buffer_manager {
+            manager.register(&tree);
+        }
+
+        Ok(tree)
+    }
+
+    pub(crate) fn read_lock_active_memtable(&self) -> RwLockReadGuard<'_, Memtable> {
+        self.active_memtable.read().expect("lock is poisoned")
     }
 
     pub(crate) fn consume_writer(
@@ -447,8 +1255,12 @@ impl Tree {
 
         log::debug!("Finalized segment write at {segment_folder:?}");
 
-        let block_index =
-            FullBlockIndex::from_file(&segment_file_path, &trailer.metadata, &trailer.offsets)?;
+        let block_index = FullBlockIndex::from_file(
+            &segment_file_path,
+            &trailer.metadata,
+            &trailer.offsets,
+            trailer.transform.as_ref(),
+        )?;
         let block_index = Arc::new(BlockIndexImpl::Full(block_index));
 
         let created_segment: Segment = SegmentInner {
@@ -460,8 +1272,31 @@ impl Tree {
             descriptor_table: self.config.descriptor_table.clone(),
             block_index,
             block_cache: self.config.block_cache.clone(),
-
-            bloom_filter: Segment::load_bloom(&segment_file_path, trailer.offsets.bloom_ptr)?,
+            statistics: self.config.statistics.clone(),
+
+            bloom_filter: Segment::load_bloom(
+                &segment_file_path,
+                trailer.offsets.bloom_ptr,
+                trailer.transform.as_ref(),
+            )?
+            .into(),
+
+            key_sketch: Segment::load_key_sketch(
+                &segment_file_path,
+                trailer.key_sketch_ptr,
+                trailer.transform.as_ref(),
+            )?
+            .into(),
+            key_sketch_ptr: trailer.key_sketch_ptr,
+            range_tombstones: Segment::load_range_tombstones(
+                &segment_file_path,
+                trailer.offsets.range_tombstones_ptr,
+                trailer.transform.as_ref(),
+            )?
+            .into(),
+            transform: trailer.transform,
+            deleted_path: std::sync::OnceLock::new(),
+            access: crate::segment::access_stats::AccessStats::default(),
         }
         .into();
 
@@ -488,6 +1323,8 @@ impl Tree {
     pub fn flush_active_memtable(&self, seqno_threshold: SeqNo) -> crate::Result<Option<Segment>> {
         log::debug!("Flushing active memtable");
 
+        self.check_journal_persisted(&self.read_lock_active_memtable())?;
+
         let Some((segment_id, yanked_memtable)) = self.rotate_memtable() else {
             return Ok(None);
         };
@@ -501,102 +1338,991 @@ impl Tree {
         Ok(Some(segment))
     }
 
-    /// Returns `true` if there are some segments that are being compacted.
-    #[doc(hidden)]
-    #[must_use]
-    pub fn is_compacting(&self) -> bool {
-        let levels = self.levels.read().expect("lock is poisoned");
-        levels.is_compacting()
-    }
-
-    /// Write-locks the sealed memtables for exclusive access
-    fn lock_sealed_memtables(&self) -> RwLockWriteGuard<'_, SealedMemtables> {
-        self.sealed_memtables.write().expect("lock is poisoned")
+    /// Bulk loads sorted key-value pairs into a new disk segment, bypassing the memtable.
+    ///
+    /// The global seqno is stored once in the segment metadata and applied to every
+    /// item when reading, and is recorded as the segment's seqno range. So the ingested
+    /// data is ordered relative to live writes like a single write batch at that seqno,
+    /// and is invisible to snapshots below it.
+    ///
+    /// The global seqno should be taken from the tree's seqno generator.
+    /// Writes with a lower seqno need to be flushed before ingesting.
+    ///
+    /// The segment is placed into the deepest level that has no overlapping
+    /// segments in it or any level above it, so ingesting into an empty key range
+    /// does not cause any compaction work.
+    ///
+    /// Returns `None` if the iterator was empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, SequenceNumberCounter};
+    ///
+    /// let tree = Config::new(folder).open()?;
+    /// let seqno = SequenceNumberCounter::default();
+    ///
+    /// tree.insert("a", "old", seqno.next());
+    /// tree.flush_active_memtable(0)?;
+    ///
+    /// let snapshot = tree.snapshot(seqno.get());
+    /// tree.ingest([("a", "new"), ("b", "new")], seqno.next())?;
+    ///
+    /// assert_eq!(b"new", &*tree.get("a", None)?.unwrap());
+    /// assert_eq!(b"old", &*snapshot.get("a")?.unwrap());
+    /// assert!(!snapshot.contains_key("b")?);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs, if the keys are not sorted and unique,
+    /// if the global seqno is not higher than the seqnos of all disk segments,
+    /// or if a memtable contains writes below the global seqno.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a lock is poisoned.
+    pub fn ingest<K, V, I>(&self, iter: I, global_seqno: SeqNo) -> crate::Result<Option<Segment>>
+    where
+        K: Into<UserKey>,
+        V: Into<UserValue>,
+        I: IntoIterator<Item = (K, V)>,
+    {
+        self.ingest_from(
+            iter.into_iter().map(|(k, v)| Ok((k.into(), v.into()))),
+            global_seqno,
+        )
     }
 
-    /// Used for [`BlobTree`] lookup
-    pub(crate) fn get_internal_entry_with_lock<K: AsRef<[u8]>>(
+    fn ingest_from<I: Iterator<Item = crate::Result<KvPair>>>(
         &self,
-        memtable_lock: &RwLockWriteGuard<'_, Memtable>,
-        key: K,
-        seqno: Option<SeqNo>,
-    ) -> crate::Result<Option<InternalValue>> {
-        if let Some(entry) = memtable_lock.get(&key, seqno) {
-            return Ok(ignore_tombstone_value(entry));
+        iter: I,
+        global_seqno: SeqNo,
+    ) -> crate::Result<Option<Segment>> {
+        self.check_ingest(global_seqno)?;
+
+        let segment_id = self.get_next_segment_id();
+        let segment_file_path = self
+            .config
+            .path
+            .join(crate::file::SEGMENTS_FOLDER)
+            .join(segment_id.to_string());
+
+        let mut segment_writer = self
+            .create_segment_writer(segment_id)?
+            .use_global_seqno(Some(global_seqno));
+
+        let result = (|| {
+            let mut last_key: Option<UserKey> = None;
+
+            for kv in iter {
+                let (key, value) = kv?;
+
+                // NOTE: The seqno is replaced by the global seqno when reading
+                let item = InternalValue::try_from_components(key, value, 0, ValueType::Value)?;
+
+                if last_key
+                    .as_ref()
+                    .is_some_and(|last_key| *last_key >= item.key.user_key)
+                {
+                    return Err(crate::Error::InvalidInput(
+                        "ingested keys need to be sorted and unique",
+                    ));
+                }
+                last_key = Some(item.key.user_key.clone());
+
+                segment_writer.write(item)?;
+            }
+
+            Ok(())
+        })();
+
+        if let Err(e) = result {
+            drop(segment_writer);
+            Self::remove_unregistered_segment(&segment_file_path);
+            return Err(e);
+        }
+
+        let Some(segment) = self.consume_writer(segment_id, segment_writer)? else {
+            return Ok(None);
         };
 
-        // Now look in sealed memtables
-        if let Some(entry) = self.get_internal_entry_from_sealed_memtables(&key, seqno) {
-            return Ok(ignore_tombstone_value(entry));
+        if let Err(e) = self.register_ingested_segment(&segment, global_seqno) {
+            self.config.descriptor_table.remove(segment.global_id());
+            drop(segment);
+            Self::remove_unregistered_segment(&segment_file_path);
+            return Err(e);
         }
 
-        self.get_internal_entry_from_segments(key, seqno)
-    }
+        log::debug!("Ingested segment {segment_id} with global seqno {global_seqno}");
+
+        Ok(Some(segment))
+    }
+
+    /// Adopts a segment file that was built by a [`SegmentFileWriter`](crate::SegmentFileWriter),
+    /// e.g. on another machine.
+    ///
+    /// The file is validated, and then moved into the tree (or copied, if it is on
+    /// another file system), without rewriting its blocks. Only the segment ID
+    /// and the global seqno are written into the file's trailer, see [`Tree::ingest`]
+    /// for how the global seqno is applied.
+    ///
+    /// Returns `None` if the file was empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// # let file_folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, SegmentFileWriter};
+    ///
+    /// let path = file_folder.path().join("data.sst");
+    ///
+    /// let mut writer = SegmentFileWriter::new(&path)?;
+    /// writer.write("a", "abc")?;
+    /// writer.write("b", "def")?;
+    /// writer.finish()?;
+    ///
+    /// let tree = Config::new(folder).open()?;
+    /// tree.ingest_file(&path, 0)?;
+    ///
+    /// assert_eq!(2, tree.len(None, None)?);
+    /// assert!(!path.try_exists()?);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs, the file is not a valid segment file
+    /// (or contains tombstones or multiple versions of a key), or for the same reasons
+    /// as [`Tree::ingest`]. If ingesting fails, the file is left in place.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a lock is poisoned.
+    pub fn ingest_file<P: AsRef<Path>>(
+        &self,
+        path: P,
+        global_seqno: SeqNo,
+    ) -> crate::Result<Option<Segment>> {
+        let path = path.as_ref();
+
+        {
+            let reader = crate::SegmentFileReader::open(path)?;
+            let segment = reader.segment();
+
+            if segment.transform.is_some()
+                || segment.metadata.tombstone_count > 0
+                || segment.metadata.range_tombstone_count > 0
+                || segment.metadata.item_count != segment.metadata.key_count
+            {
+                return Err(crate::Error::InvalidInput(
+                    "segment file needs to contain a single version of each key",
+                ));
+            }
+
+            if segment.metadata.item_count == 0 {
+                return Ok(None);
+            }
+
+            let report = segment.verify()?;
+
+            if !report.is_ok() {
+                log::error!("Refusing to ingest corrupt segment file: {report:?}");
+                return Err(crate::Error::InvalidInput("segment file is corrupted"));
+            }
+        }
+
+        self.check_ingest(global_seqno)?;
+
+        let segment_id = self.get_next_segment_id();
+        let segment_folder = self.config.path.join(crate::file::SEGMENTS_FOLDER);
+        let segment_file_path = segment_folder.join(segment_id.to_string());
+
+        // NOTE: Renaming fails across file systems, then the file has to be copied
+        let is_moved = std::fs::rename(path, &segment_file_path).is_ok();
+
+        if !is_moved {
+            std::fs::copy(path, &segment_file_path)?;
+        }
+
+        let result = (|| {
+            SegmentFileTrailer::write_ingest_info(&segment_file_path, segment_id, global_seqno)?;
+            crate::file::fsync_directory(&segment_folder)?;
+
+            let segment =
+                Self::recover_segment(&self.config, self.id, &segment_file_path, 0, None)?;
+
+            if let Err(e) = self.register_ingested_segment(&segment, global_seqno) {
+                self.config.descriptor_table.remove(segment.global_id());
+                return Err(e);
+            }
+
+            Ok(segment)
+        })();
+
+        match result {
+            Ok(segment) => {
+                if !is_moved {
+                    std::fs::remove_file(path)?;
+                }
+
+                log::debug!(
+                    "Ingested segment file {} as segment {segment_id} with global seqno {global_seqno}",
+                    path.display(),
+                );
+
+                Ok(Some(segment))
+            }
+            Err(e) => {
+                // NOTE: Give the file back, so ingesting can be retried
+                if is_moved {
+                    if let Err(e) = std::fs::rename(&segment_file_path, path) {
+                        log::warn!(
+                            "Failed to move segment file {} back to {}: {e:?}",
+                            segment_file_path.display(),
+                            path.display(),
+                        );
+                    }
+                } else {
+                    Self::remove_unregistered_segment(&segment_file_path);
+                }
+
+                Err(e)
+            }
+        }
+    }
+
+    /// Returns `Err` if a segment with the given global seqno cannot be ingested.
+    fn check_ingest_locked(
+        levels: &LevelManifest,
+        active_memtable: &Memtable,
+        sealed_memtables: &SealedMemtables,
+        global_seqno: SeqNo,
+    ) -> crate::Result<()> {
+        // NOTE: Point reads return the first version they find, going from memtables
+        // to the newest segments, so the ingested segment needs to be the newest source
+        if levels
+            .iter()
+            .map(Segment::get_highest_seqno)
+            .max()
+            .is_some_and(|seqno| seqno >= global_seqno)
+        {
+            return Err(crate::Error::InvalidInput(
+                "global seqno needs to be higher than the seqnos of all segments",
+            ));
+        }
+
+        let has_older_writes = active_memtable.has_visible_items(global_seqno)
+            || sealed_memtables
+                .iter()
+                .any(|(_, memtable)| memtable.has_visible_items(global_seqno));
+
+        if has_older_writes {
+            return Err(crate::Error::InvalidInput(
+                "memtables need to be flushed before ingesting",
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Checks up front if a segment with the given global seqno can be ingested,
+    /// so no segment is written in vain.
+    ///
+    /// The check is repeated when registering the segment.
+    fn check_ingest(&self, global_seqno: SeqNo) -> crate::Result<()> {
+        // NOTE: Mind lock order L -> M -> S
+        let levels = self.levels.read().expect("lock is poisoned");
+        let active_memtable = self.active_memtable.read().expect("lock is poisoned");
+        let sealed_memtables = self.sealed_memtables.read().expect("lock is poisoned");
+
+        Self::check_ingest_locked(&levels, &active_memtable, &sealed_memtables, global_seqno)
+    }
+
+    /// Registers an ingested segment into the deepest free level.
+    ///
+    /// The ingest checks are done while holding all locks (including the
+    /// active memtable write lock), so no write can slip in between.
+    fn register_ingested_segment(
+        &self,
+        segment: &Segment,
+        global_seqno: SeqNo,
+    ) -> crate::Result<()> {
+        // NOTE: Mind lock order L -> M -> S
+        let mut levels = self.levels.write().expect("lock is poisoned");
+        let active_memtable = self.lock_active_memtable();
+        let mut sealed_memtables = self.lock_sealed_memtables();
+
+        Self::check_ingest_locked(&levels, &active_memtable, &sealed_memtables, global_seqno)?;
+
+        let segments = self.swap_in_segments(
+            &mut levels,
+            &mut sealed_memtables,
+            std::slice::from_ref(segment),
+            &[],
+            Self::choose_ingest_level,
+        )?;
+
+        drop(sealed_memtables);
+        drop(active_memtable);
+        drop(levels);
+
+        self.on_segments_registered(&segments);
+
+        Ok(())
+    }
+
+    /// Deletes a segment file that was never registered.
+    fn remove_unregistered_segment(path: &Path) {
+        if let Err(e) = std::fs::remove_file(path) {
+            log::warn!(
+                "Failed to delete unfinished segment {}: {e:?}",
+                path.display(),
+            );
+        }
+    }
+
+    /// Returns a debug iterator over the raw contents of a disk segment,
+    /// see [`SegmentDump`].
+    ///
+    /// Returns `None` if the segment does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, DumpItem};
+    ///
+    /// let tree = Config::new(folder).open()?;
+    /// tree.insert("a", "old", 0);
+    /// tree.insert("a", "new", 1);
+    /// let segment = tree.flush_active_memtable(0)?.expect("should flush");
+    ///
+    /// let entries = tree
+    ///     .dump_segment(segment.id())?
+    ///     .expect("segment should exist")
+    ///     .filter(|item| matches!(item, Ok(DumpItem::Entry(_))))
+    ///     .count();
+    ///
+    /// // Both versions are still stored in the segment
+    /// assert_eq!(2, entries);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an IO error occurs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a lock is poisoned.
+    pub fn dump_segment(&self, segment_id: SegmentId) -> crate::Result<Option<SegmentDump>> {
+        use crate::file::SEGMENTS_FOLDER;
+
+        let segment = self
+            .levels
+            .read()
+            .expect("lock is poisoned")
+            .iter()
+            .find(|segment| segment.id() == segment_id)
+            .cloned();
+
+        segment
+            .map(|segment| segment.dump(self.config.path.join(SEGMENTS_FOLDER)))
+            .transpose()
+    }
+
+    /// Remembers the error of a failed flush or compaction, so it can be reported by [`AbstractTree::health`].
+    ///
+    /// The error is cleared once a flush or compaction succeeds again.
+    pub(crate) fn record_background_error<T>(&self, result: crate::Result<T>) -> crate::Result<T> {
+        *self.last_error.write().expect("lock is poisoned") =
+            result.as_ref().err().map(ToString::to_string);
+
+        result
+    }
+
+    /// Runs a read, tracing the disk segments it reads if slow operations are reported.
+    pub(crate) fn trace_reads<T>(&self, f: impl FnOnce() -> T) -> (T, Vec<SegmentId>) {
+        if self.config.slow_operation_threshold.is_some() {
+            crate::slow_operation::trace_segment_reads(f)
+        } else {
+            (f(), Vec::new())
+        }
+    }
+
+    /// Reports a point read if it took at least the configured slow operation threshold.
+    pub(crate) fn check_slow_get(
+        &self,
+        kind: impl FnOnce() -> SlowOperationKind,
+        seqno: Option<SeqNo>,
+        start: std::time::Instant,
+        segments_read: Vec<SegmentId>,
+    ) {
+        let Some(threshold) = self.config.slow_operation_threshold else {
+            return;
+        };
+
+        let elapsed = start.elapsed();
+
+        if elapsed < threshold {
+            return;
+        }
+
+        crate::slow_operation::report(
+            &self.config,
+            SlowOperation {
+                tree_id: self.id,
+                kind: kind(),
+                seqno,
+                elapsed,
+                segments_read,
+            },
+        );
+    }
+
+    /// Attaches slow operation reporting to a scan, if a threshold is configured.
+    pub(crate) fn slow_scan_log<I>(
+        &self,
+        iter: TimedIter<I>,
+        bounds: (Bound<UserKey>, Bound<UserKey>),
+        seqno: Option<SeqNo>,
+    ) -> TimedIter<I> {
+        let Some(threshold) = self.config.slow_operation_threshold else {
+            return iter;
+        };
+
+        let tree = self.clone();
+
+        iter.with_slow_log(
+            threshold,
+            Box::new(move |elapsed, segments_read| {
+                crate::slow_operation::report(
+                    &tree.config,
+                    SlowOperation {
+                        tree_id: tree.id,
+                        kind: SlowOperationKind::Scan { range: bounds },
+                        seqno,
+                        elapsed,
+                        segments_read,
+                    },
+                );
+            }),
+        )
+    }
+
+    /// Returns `true` if there are some segments that are being compacted.
+    #[doc(hidden)]
+    #[must_use]
+    pub fn is_compacting(&self) -> bool {
+        let levels = self.levels.read().expect("lock is poisoned");
+        levels.is_compacting()
+    }
+
+    /// Write-locks the sealed memtables for exclusive access
+    fn lock_sealed_memtables(&self) -> RwLockWriteGuard<'_, SealedMemtables> {
+        self.sealed_memtables.write().expect("lock is poisoned")
+    }
+
+    /// Used for [`BlobTree`] lookup
+    pub(crate) fn get_internal_entry_with_lock<K: AsRef<[u8]>>(
+        &self,
+        memtable_lock: &RwLockWriteGuard<'_, Memtable>,
+        key: K,
+        seqno: Option<SeqNo>,
+    ) -> crate::Result<Option<InternalValue>> {
+        if let Some(entry) = memtable_lock.get(&key, seqno) {
+            return self.hide_range_deleted(memtable_lock, ignore_tombstone_value(entry), seqno);
+        };
+
+        // Now look in sealed memtables
+        if let Some(entry) = self.get_internal_entry_from_sealed_memtables(&key, seqno) {
+            return self.hide_range_deleted(memtable_lock, ignore_tombstone_value(entry), seqno);
+        }
+
+        // NOTE: Cannot quarantine while holding the memtable lock, see lock order
+        let entry = self.get_internal_entry_from_segments(key, seqno, false)?;
+        self.hide_range_deleted(memtable_lock, entry, seqno)
+    }
+
+    /// Returns `None` if the entry is deleted by a range tombstone
+    /// that is visible to a snapshot with the given seqno.
+    fn hide_range_deleted(
+        &self,
+        active_memtable: &Memtable,
+        entry: Option<InternalValue>,
+        seqno: Option<SeqNo>,
+    ) -> crate::Result<Option<InternalValue>> {
+        let Some(entry) = entry else {
+            return Ok(None);
+        };
+
+        let key = &entry.key.user_key;
+        let item_seqno = entry.key.seqno;
+
+        // NOTE: Range tombstones only move from the active memtable
+        // to the sealed memtables to the segments, so look them up in that order
+        if active_memtable.is_range_deleted(key, item_seqno, seqno) {
+            return Ok(None);
+        }
+
+        // NOTE: Mind lock order M -> S
+        let sealed = self.sealed_memtables.read().expect("lock is poisoned");
+
+        // IMPORTANT: Load the view while holding the sealed memtables lock,
+        // so range tombstones that are being flushed are not missed
+        let level_view = self.level_view.load();
+
+        // NOTE: Fast path, most trees do not contain any range tombstones
+        if !level_view.has_range_tombstones()
+            && !sealed
+                .iter()
+                .any(|(_, memtable)| memtable.has_range_tombstones())
+        {
+            return Ok(Some(entry));
+        }
+
+        if sealed
+            .iter()
+            .any(|(_, memtable)| memtable.is_range_deleted(key, item_seqno, seqno))
+        {
+            return Ok(None);
+        }
+
+        drop(sealed);
+
+        if level_view.is_range_deleted(key, item_seqno, seqno)? {
+            return Ok(None);
+        }
+
+        Ok(Some(entry))
+    }
+
+    fn get_internal_entry_from_sealed_memtables<K: AsRef<[u8]>>(
+        &self,
+        key: K,
+        seqno: Option<SeqNo>,
+    ) -> Option<InternalValue> {
+        let memtable_lock = self.sealed_memtables.read().expect("lock is poisoned");
+
+        for (_, memtable) in memtable_lock.iter().rev() {
+            if let Some(entry) = memtable.get(&key, seqno) {
+                return Some(entry);
+            }
+        }
+
+        None
+    }
+
+    /// Reads a key from a segment, quarantining the segment if it is corrupt,
+    /// see [`Config::quarantine_corrupt_segments`]
+    fn get_from_segment<K: AsRef<[u8]>>(
+        &self,
+        segment: &Segment,
+        key: K,
+        seqno: Option<SeqNo>,
+        key_hash: CompositeHash,
+        allow_quarantine: bool,
+    ) -> crate::Result<Option<InternalValue>> {
+        match segment.get(key, seqno, key_hash) {
+            Err(e)
+                if allow_quarantine
+                    && self.config.quarantine_corrupt_segments
+                    && e.is_corruption() =>
+            {
+                if self.quarantine_segment(segment.id(), &e)? {
+                    Ok(None)
+                } else {
+                    Err(e.in_segment(segment.id()))
+                }
+            }
+            result => result.map_err(|e| e.in_segment(segment.id())),
+        }
+    }
+
+    /// Removes all segments that only contain keys with the given prefix,
+    /// which are deleted by a range tombstone with the given seqno.
+    ///
+    /// Segments that are currently being compacted are skipped, as well as
+    /// segments with range tombstones, which may delete keys outside of the prefix.
+    /// Segments that contain items which are not deleted by the range tombstone,
+    /// or are still visible to a pinned snapshot, are skipped as well.
+    fn drop_prefix_segments(&self, prefix: &[u8], seqno: SeqNo) -> crate::Result<usize> {
+        use crate::file::SEGMENTS_FOLDER;
+
+        // NOTE: Mind lock order L -> M -> S
+        let mut levels = self.levels.write().expect("lock is poisoned");
+
+        // IMPORTANT: Write lock memtable, otherwise segments may get deleted while a range read is happening
+        let memtable_lock = self.sealed_memtables.write().expect("lock is poisoned");
+
+        // NOTE: Snapshots at or below the seqno of the range tombstone
+        // do not see it, so they still read the items of the prefix
+        let pinned_snapshot_seqnos = self.pinned_snapshot_seqnos();
+
+        let dropped_segments = levels
+            .iter()
+            .filter(|segment| !levels.hidden_set().is_hidden(segment.id()))
+            .filter(|segment| segment.metadata.range_tombstone_count == 0)
+            .filter(|segment| {
+                let (lo, hi) = segment.metadata.seqnos;
+
+                hi < seqno
+                    && !pinned_snapshot_seqnos
+                        .iter()
+                        .any(|&snapshot| lo < snapshot && snapshot <= seqno)
+            })
+            .filter(|segment| {
+                let key_range = &segment.metadata.key_range;
+                key_range.min().starts_with(prefix) && key_range.max().starts_with(prefix)
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+
+        if dropped_segments.is_empty() {
+            return Ok(0);
+        }
+
+        levels.atomic_swap(|recipe| {
+            for segment in &dropped_segments {
+                for level in recipe.iter_mut() {
+                    level.remove(segment.id());
+                }
+            }
+        })?;
+
+        drop(memtable_lock);
+        drop(levels);
+
+        // NOTE: The files are removed once the last reader lets go of the segments
+        let segments_base_folder = self.config.path.join(SEGMENTS_FOLDER);
+
+        for segment in &dropped_segments {
+            segment.mark_as_deleted(segments_base_folder.join(segment.id().to_string()));
+        }
+
+        Ok(dropped_segments.len())
+    }
+
+    /// Removes a corrupt segment from the tree and moves its file into the quarantine folder.
+    ///
+    /// Returns `false` if the segment could not be quarantined, because it is
+    /// currently being compacted.
+    pub(crate) fn quarantine_segment(
+        &self,
+        segment_id: SegmentId,
+        error: &crate::Error,
+    ) -> crate::Result<bool> {
+        let mut levels = self.levels.write().expect("lock is poisoned");
+
+        if levels.hidden_set().is_hidden(segment_id) {
+            return Ok(false);
+        }
+
+        if !levels.iter().any(|segment| segment.id() == segment_id) {
+            // NOTE: Another reader was faster
+            return Ok(true);
+        }
+
+        levels.atomic_swap(|recipe| {
+            for level in recipe.iter_mut() {
+                level.remove(segment_id);
+            }
+        })?;
+
+        drop(levels);
+
+        quarantine::move_segment_file(&self.config.path, segment_id)?;
+        quarantine::notify(&self.config, segment_id, error);
+
+        Ok(true)
+    }
+
+    fn get_internal_entry_from_segments<K: AsRef<[u8]>>(
+        &self,
+        key: K,
+        seqno: Option<SeqNo>,
+        allow_quarantine: bool,
+    ) -> crate::Result<Option<InternalValue>> {
+        // NOTE: Create key hash for hash sharing
+        // https://fjall-rs.github.io/post/bloom-filter-hash-sharing/
+        let key_hash = crate::bloom::BloomFilter::get_hash(key.as_ref());
+
+        let level_view = self.level_view.load();
+
+        // NOTE: The default executor spawns a thread per task, which costs more than
+        // the fan-out saves, so only fan out on a configured executor
+        if let (Some(executor), 2..) = (&self.config.spawn_hook, self.config.point_read_fanout) {
+            return Self::fan_out_point_read(
+                &**executor,
+                &level_view,
+                key.as_ref(),
+                seqno,
+                key_hash,
+                self.config.point_read_fanout,
+            );
+        }
+
+        for level in &level_view.levels {
+            // NOTE: Based on benchmarking, binary search is only worth it with ~4 segments
+            if level.len() >= 4 {
+                if let Some(level) = level.as_disjoint() {
+                    // TODO: unit test in disjoint level:
+                    // [a:5, a:4] [a:3, b:5]
+                    // ^
+                    // snapshot read a:3!!!
+
+                    if let Some(segment) = level.get_segment_containing_key(&key) {
+                        let maybe_item = self.get_from_segment(
+                            &segment,
+                            &key,
+                            seqno,
+                            key_hash,
+                            allow_quarantine,
+                        )?;
+
+                        if let Some(item) = maybe_item {
+                            return Ok(ignore_tombstone_value(item));
+                        }
+                    }
+
+                    // NOTE: Go to next level
+                    continue;
+                }
+            }
+
+            // NOTE: Fallback to linear search
+            for segment in &level.segments {
+                let maybe_item =
+                    self.get_from_segment(segment, &key, seqno, key_hash, allow_quarantine)?;
+
+                if let Some(item) = maybe_item {
+                    return Ok(ignore_tombstone_value(item));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Probes the bloom filters of the segments serially (newest first), reading
+    /// the candidate segments in batches of `fanout` concurrently on the executor.
+    ///
+    /// The first candidate (newest first) that contains the key wins, so
+    /// segments older than the batch of the hit are never probed.
+    fn fan_out_point_read(
+        executor: &dyn Executor,
+        level_view: &LevelView,
+        key: &[u8],
+        seqno: Option<SeqNo>,
+        key_hash: CompositeHash,
+        fanout: usize,
+    ) -> crate::Result<Option<InternalValue>> {
+        let mut candidates = level_view
+            .levels
+            .iter()
+            .flat_map(|level| {
+                // NOTE: Disjoint levels only have a single candidate
+                let (candidate, segments) = match level.as_disjoint() {
+                    Some(disjoint) if level.len() >= 4 => {
+                        (disjoint.get_segment_containing_key(key), &[][..])
+                    }
+                    _ => (None, &level.segments[..]),
+                };
+
+                candidate.into_iter().chain(segments.iter().cloned())
+            })
+            .filter(|segment| segment.may_contain_key(key, seqno, key_hash));
+
+        loop {
+            let batch = candidates.by_ref().take(fanout).collect::<Vec<_>>();
+
+            if batch.is_empty() {
+                return Ok(None);
+            }
+
+            // NOTE: A single candidate (e.g. in disjoint levels) is not worth a task
+            let results = if let [segment] = &batch[..] {
+                vec![segment.point_read(key, seqno)]
+            } else {
+                let owned_key: UserKey = key.into();
+
+                let jobs = batch
+                    .iter()
+                    .cloned()
+                    .map(|segment| {
+                        let key = owned_key.clone();
+                        move || segment.point_read(&key, seqno)
+                    })
+                    .collect::<Vec<_>>();
+
+                crate::executor::run_bounded(executor, jobs, fanout)
+            };
+
+            for (segment, result) in batch.iter().zip(results) {
+                // NOTE: The jobs may have run on other threads, so they were not traced
+                crate::slow_operation::record_segment_read(segment.id());
+
+                if let Some(item) = result.map_err(|e| e.in_segment(segment.id()))? {
+                    return Ok(ignore_tombstone_value(item));
+                }
+            }
+        }
+    }
+
+    #[doc(hidden)]
+    pub fn get_internal_entry<K: AsRef<[u8]>>(
+        &self,
+        key: K,
+        seqno: Option<SeqNo>,
+    ) -> crate::Result<Option<InternalValue>> {
+        let entry = self.get_newest_version(&key, seqno)?;
+
+        if entry
+            .as_ref()
+            .is_some_and(|entry| entry.key.value_type == ValueType::Merge)
+        {
+            // NOTE: Merge operands need to be folded with the older versions
+            // of the key, so read all of them, like a range read does
+            let range = key.as_ref()..=key.as_ref();
+            return self
+                .create_internal_range(&range, seqno, None)
+                .next()
+                .transpose();
+        }
+
+        let now = self.config.get_clock().now();
+        Ok(entry.and_then(|entry| crate::ttl::resolve(entry, now)))
+    }
+
+    /// Looks up a batch of keys, see [`AbstractTree::get_many`].
+    ///
+    /// The entries are returned in the order of the given keys.
+    #[doc(hidden)]
+    pub fn get_internal_entries<K: AsRef<[u8]>, I: IntoIterator<Item = K>>(
+        &self,
+        keys: I,
+        seqno: Option<SeqNo>,
+    ) -> crate::Result<Vec<Option<InternalValue>>> {
+        let keys = keys.into_iter().collect::<Vec<_>>();
+
+        // NOTE: Sorting the keys allows searching every segment only for the keys
+        // inside its key range, and neighbouring keys hit the same (cached) blocks
+        let mut lookups = keys
+            .iter()
+            .enumerate()
+            .map(|(idx, key)| PointLookup {
+                idx,
+                key: key.as_ref(),
+                entry: None,
+            })
+            .collect::<Vec<_>>();
+        lookups.sort_by(|a, b| a.key.cmp(b.key));
+
+        let mut range_tombstones = vec![];
+
+        // NOTE: Mind lock order M -> S
+        let active = self.active_memtable.read().expect("lock is poisoned");
+        let sealed = self.sealed_memtables.read().expect("lock is poisoned");
+
+        // IMPORTANT: Load the view while holding the sealed memtables lock,
+        // so data that is being flushed is not missed
+        let level_view = self.level_view.load();
+
+        for lookup in &mut lookups {
+            lookup.entry = active.get(lookup.key, seqno).or_else(|| {
+                sealed
+                    .iter()
+                    .rev()
+                    .find_map(|(_, memtable)| memtable.get(lookup.key, seqno))
+            });
+        }
+
+        for memtable in sealed
+            .iter()
+            .map(|(_, memtable)| &**memtable)
+            .chain(std::iter::once(&*active))
+        {
+            range_tombstones.extend(memtable.range_tombstones().filter(|t| t.is_visible(seqno)));
+        }
+
+        drop(sealed);
+        drop(active);
+
+        level_view.collect_range_tombstones(seqno, &mut range_tombstones)?;
+        let range_tombstones = RangeTombstoneIndex::new(range_tombstones);
+
+        // NOTE: Create key hashes once for hash sharing
+        let mut pending = lookups
+            .iter_mut()
+            .filter(|lookup| lookup.entry.is_none())
+            .map(|lookup| {
+                let key_hash = crate::bloom::BloomFilter::get_hash(lookup.key);
+                (lookup, key_hash)
+            })
+            .collect::<Vec<_>>();
+
+        for level in &level_view.levels {
+            if pending.is_empty() {
+                break;
+            }
+
+            for segment in &level.segments {
+                let (min, max) = &*segment.metadata.key_range;
 
-    fn get_internal_entry_from_sealed_memtables<K: AsRef<[u8]>>(
-        &self,
-        key: K,
-        seqno: Option<SeqNo>,
-    ) -> Option<InternalValue> {
-        let memtable_lock = self.sealed_memtables.read().expect("lock is poisoned");
+                let lo = pending.partition_point(|(lookup, _)| lookup.key < &**min);
+                let hi = pending.partition_point(|(lookup, _)| lookup.key <= &**max);
 
-        for (_, memtable) in memtable_lock.iter().rev() {
-            if let Some(entry) = memtable.get(&key, seqno) {
-                return Some(entry);
+                for (lookup, key_hash) in pending.get_mut(lo..hi).into_iter().flatten() {
+                    // NOTE: The first segment of the level that contains the key wins
+                    if lookup.entry.is_none() {
+                        lookup.entry =
+                            self.get_from_segment(segment, lookup.key, seqno, *key_hash, true)?;
+                    }
+                }
             }
-        }
-
-        None
-    }
 
-    fn get_internal_entry_from_segments<K: AsRef<[u8]>>(
-        &self,
-        key: K,
-        seqno: Option<SeqNo>,
-    ) -> crate::Result<Option<InternalValue>> {
-        // NOTE: Create key hash for hash sharing
-        // https://fjall-rs.github.io/post/bloom-filter-hash-sharing/
-        let key_hash = crate::bloom::BloomFilter::get_hash(key.as_ref());
+            pending.retain(|(lookup, _)| lookup.entry.is_none());
+        }
 
-        let level_manifest = self.levels.read().expect("lock is poisoned");
+        drop(pending);
 
-        for level in &level_manifest.levels {
-            // NOTE: Based on benchmarking, binary search is only worth it with ~4 segments
-            if level.len() >= 4 {
-                if let Some(level) = level.as_disjoint() {
-                    // TODO: unit test in disjoint level:
-                    // [a:5, a:4] [a:3, b:5]
-                    // ^
-                    // snapshot read a:3!!!
+        let mut entries = vec![None; keys.len()];
+        let now = self.config.get_clock().now();
 
-                    if let Some(segment) = level.get_segment_containing_key(&key) {
-                        let maybe_item = segment.get(&key, seqno, key_hash)?;
+        for lookup in lookups {
+            let Some(mut entry) = lookup.entry else {
+                continue;
+            };
 
-                        if let Some(item) = maybe_item {
-                            return Ok(ignore_tombstone_value(item));
-                        }
-                    }
+            if range_tombstones.deletes(&entry.key.user_key, entry.key.seqno) {
+                continue;
+            }
 
-                    // NOTE: Go to next level
+            // NOTE: Merge operands need to be folded with the older versions of the key
+            if entry.key.value_type == ValueType::Merge {
+                let Some(merged) = self.get_internal_entry(lookup.key, seqno)? else {
                     continue;
-                }
+                };
+                entry = merged;
             }
 
-            // NOTE: Fallback to linear search
-            for segment in &level.segments {
-                let maybe_item = segment.get(&key, seqno, key_hash)?;
-
-                if let Some(item) = maybe_item {
-                    return Ok(ignore_tombstone_value(item));
-                }
+            if let Some(slot) = entries.get_mut(lookup.idx) {
+                *slot = crate::ttl::resolve(entry, now);
             }
         }
 
-        Ok(None)
+        Ok(entries)
     }
 
-    #[doc(hidden)]
-    pub fn get_internal_entry<K: AsRef<[u8]>>(
+    /// Returns the newest version of a key, which may be a merge operand.
+    fn get_newest_version<K: AsRef<[u8]>>(
         &self,
         key: K,
         seqno: Option<SeqNo>,
@@ -606,18 +2332,28 @@ impl Tree {
         let memtable_lock = self.active_memtable.read().expect("lock is poisoned");
 
         if let Some(entry) = memtable_lock.get(&key, seqno) {
-            return Ok(ignore_tombstone_value(entry));
+            return self.hide_range_deleted(&memtable_lock, ignore_tombstone_value(entry), seqno);
         };
 
         drop(memtable_lock);
 
         // Now look in sealed memtables
         if let Some(entry) = self.get_internal_entry_from_sealed_memtables(&key, seqno) {
-            return Ok(ignore_tombstone_value(entry));
+            return self.hide_range_deleted(
+                &self.active_memtable.read().expect("lock is poisoned"),
+                ignore_tombstone_value(entry),
+                seqno,
+            );
         }
 
         // Now look in segments... this may involve disk I/O
-        self.get_internal_entry_from_segments(key, seqno)
+        let entry = self.get_internal_entry_from_segments(key, seqno, true)?;
+
+        self.hide_range_deleted(
+            &self.active_memtable.read().expect("lock is poisoned"),
+            entry,
+            seqno,
+        )
     }
 
     #[doc(hidden)]
@@ -637,32 +2373,25 @@ impl Tree {
         seqno: Option<SeqNo>,
         ephemeral: Option<Arc<Memtable>>,
     ) -> impl DoubleEndedIterator<Item = crate::Result<InternalValue>> + 'static {
-        use std::ops::Bound::{self, Excluded, Included, Unbounded};
-
-        let lo: Bound<UserKey> = match range.start_bound() {
-            Included(x) => Included(x.as_ref().into()),
-            Excluded(x) => Excluded(x.as_ref().into()),
-            Unbounded => Unbounded,
-        };
-
-        let hi: Bound<UserKey> = match range.end_bound() {
-            Included(x) => Included(x.as_ref().into()),
-            Excluded(x) => Excluded(x.as_ref().into()),
-            Unbounded => Unbounded,
-        };
-
-        let bounds: (Bound<UserKey>, Bound<UserKey>) = (lo, hi);
-
-        // NOTE: Mind lock order L -> M -> S
-        let level_manifest_lock =
-            guardian::ArcRwLockReadGuardian::take(self.levels.clone()).expect("lock is poisoned");
+        let bounds = range_bounds_to_owned(range);
 
+        // NOTE: Mind lock order M -> S
         let active = guardian::ArcRwLockReadGuardian::take(self.active_memtable.clone())
             .expect("lock is poisoned");
 
         let sealed = guardian::ArcRwLockReadGuardian::take(self.sealed_memtables.clone())
             .expect("lock is poisoned");
 
+        // IMPORTANT: Load the view after locking the sealed memtables:
+        // Registering flushed segments needs the sealed memtables write lock,
+        // so the view is consistent with the sealed memtables we see
+        let level_view = self.level_view.load();
+
+        let read_ahead = (self.config.scan_prefetch_blocks > 0).then(|| ReadAhead {
+            blocks: self.config.scan_prefetch_blocks,
+            executor: self.config.executor(),
+        });
+
         TreeIter::create_range(
             MemtableLockGuard {
                 active,
@@ -671,7 +2400,10 @@ impl Tree {
             },
             bounds,
             seqno,
-            level_manifest_lock,
+            level_view,
+            read_ahead.as_ref(),
+            self.config.merge_operator.clone(),
+            self.config.get_clock().now(),
         )
     }
 
@@ -700,6 +2432,395 @@ impl Tree {
         self.create_range(&range, seqno, ephemeral)
     }
 
+    /// Writes the tree, as seen by a snapshot at the given seqno, into a new folder.
+    pub(crate) fn export_snapshot<P: AsRef<Path>>(
+        &self,
+        path: P,
+        seqno: SeqNo,
+    ) -> crate::Result<()> {
+        let config = self.export_config(path)?;
+
+        // IMPORTANT: Lock memtables, so no flush can move data
+        // between the memtables and the level view while exporting
+        let active_memtable = self.read_lock_active_memtable();
+        let sealed_memtables = self.sealed_memtables.read().expect("lock is poisoned");
+        let level_view = self.level_view.load();
+
+        let dest = config.open()?;
+        self.export_segments(&dest, &level_view, seqno)?;
+        Self::export_memtables(&dest, &active_memtable, &sealed_memtables, seqno);
+
+        drop(sealed_memtables);
+        drop(active_memtable);
+
+        dest.flush_active_memtable(0)?;
+
+        Ok(())
+    }
+
+    /// Returns the config of a tree exported into the given folder.
+    pub(crate) fn export_config<P: AsRef<Path>>(&self, path: P) -> crate::Result<Config> {
+        use crate::file::MANIFEST_FILE;
+
+        let path = crate::path::absolute_path(path);
+
+        if path.join(MANIFEST_FILE).try_exists()? {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                "export folder already contains a tree",
+            )
+            .into());
+        }
+
+        let mut config = self.config.clone();
+        config.path = path;
+
+        // NOTE: The embedder's journal does not belong to the exported tree
+        config.journal_observer = None;
+
+        Ok(config)
+    }
+
+    /// Adds the segments of a level view to another tree, keeping their levels
+    /// and only keeping versions that are visible at the given seqno.
+    ///
+    /// Segments that only contain visible versions are hard linked,
+    /// the others are rewritten.
+    pub(crate) fn export_segments(
+        &self,
+        dest: &Self,
+        level_view: &LevelView,
+        seqno: SeqNo,
+    ) -> crate::Result<()> {
+        use crate::file::{fsync_directory, hard_link_or_copy, SEGMENTS_FOLDER};
+
+        let src_folder = self.config.path.join(SEGMENTS_FOLDER);
+        let dest_folder = dest.config.path.join(SEGMENTS_FOLDER);
+
+        let mut levels = Vec::with_capacity(level_view.levels.len());
+
+        for (level_idx, level) in level_view.levels.iter().enumerate() {
+            let mut segments = Vec::with_capacity(level.len());
+
+            for segment in &level.segments {
+                let segment_id = segment.id();
+
+                if segment.metadata.seqnos.1 < seqno {
+                    let src_path = src_folder.join(segment_id.to_string());
+                    let dest_path = dest_folder.join(segment_id.to_string());
+
+                    hard_link_or_copy(&src_path, &dest_path)?;
+
+                    let exported = Segment::recover(
+                        &dest_path,
+                        dest.id,
+                        dest.config.block_cache.clone(),
+                        dest.config.descriptor_table.clone(),
+                        dest.config.statistics.clone(),
+                        level_idx <= 1,
+                        dest.config.block_transform.as_ref(),
+                    )?;
+
+                    dest.config
+                        .descriptor_table
+                        .insert(&dest_path, exported.global_id());
+
+                    segments.push(exported);
+                } else {
+                    let mut segment_writer = dest
+                        .create_segment_writer(segment_id)?
+                        .use_temperature(segment.metadata.temperature);
+
+                    for item in segment.iter() {
+                        let item = item?;
+
+                        if item.key.seqno < seqno {
+                            segment_writer.write(item)?;
+                        }
+                    }
+
+                    if let Some(exported) = dest.consume_writer(segment_id, segment_writer)? {
+                        segments.push(exported);
+                    }
+                }
+            }
+
+            levels.push(segments);
+        }
+
+        fsync_directory(&dest_folder)?;
+
+        dest.levels
+            .write()
+            .expect("lock is poisoned")
+            .atomic_swap(|recipe| {
+                for (level, segments) in recipe.iter_mut().zip(levels) {
+                    for segment in segments {
+                        level.insert(segment);
+                    }
+                }
+            })?;
+
+        // NOTE: Segments flushed into the exported tree may not reuse IDs of exported segments
+        dest.segment_id_counter.fetch_max(
+            self.segment_id_counter
+                .load(std::sync::atomic::Ordering::Relaxed),
+            std::sync::atomic::Ordering::Relaxed,
+        );
+
+        Ok(())
+    }
+
+    /// Adds all memtable versions that are visible at the given seqno
+    /// to the active memtable of another tree.
+    pub(crate) fn export_memtables(
+        dest: &Self,
+        active_memtable: &Memtable,
+        sealed_memtables: &SealedMemtables,
+        seqno: SeqNo,
+    ) {
+        let items = active_memtable.iter().chain(
+            sealed_memtables
+                .iter()
+                .flat_map(|(_, memtable)| memtable.iter()),
+        );
+
+        let dest_memtable = dest.read_lock_active_memtable();
+
+        for item in items.filter(|item| item.key.seqno < seqno) {
+            dest_memtable.insert(item);
+        }
+    }
+
+    /// Records that all writes below the given seqno happened before now.
+    ///
+    /// The map only drives time-travel reads, so failing to persist it
+    /// does not fail the flush.
+    fn sample_seqno_time(&self, seqno: SeqNo) {
+        let mut seqno_time_map = self.seqno_time_map.write().expect("lock is poisoned");
+
+        if seqno_time_map.record(seqno, self.config.get_clock().now()) {
+            if let Err(e) = seqno_time_map.persist(&self.config.path) {
+                log::error!("Failed to persist seqno time map: {e:?}");
+            }
+        }
+    }
+
+    /// Returns the seqno of a named snapshot.
+    pub(crate) fn get_named_snapshot_seqno(&self, name: &str) -> Option<SeqNo> {
+        self.named_snapshots
+            .read()
+            .expect("lock is poisoned")
+            .get(name)
+            .copied()
+    }
+
+    /// Returns the seqnos of all named snapshots, in ascending order.
+    pub(crate) fn named_snapshot_seqnos(&self) -> Vec<SeqNo> {
+        let mut seqnos = self
+            .named_snapshots
+            .read()
+            .expect("lock is poisoned")
+            .values()
+            .copied()
+            .collect::<Vec<_>>();
+
+        seqnos.sort_unstable();
+        seqnos.dedup();
+        seqnos
+    }
+
+    /// Returns the seqnos of all named and open snapshots, in ascending order.
+    pub(crate) fn pinned_snapshot_seqnos(&self) -> Vec<SeqNo> {
+        let mut seqnos = self.named_snapshot_seqnos();
+        seqnos.extend(self.open_snapshots.seqnos());
+
+        seqnos.sort_unstable();
+        seqnos.dedup();
+        seqnos
+    }
+
+    /// Rewrites the manifest with the given named snapshots.
+    fn persist_named_snapshots(&self, named_snapshots: &NamedSnapshots) -> crate::Result<()> {
+        use crate::file::{rewrite_atomic, MANIFEST_FILE};
+
+        let manifest = Manifest {
+            version: Version::V2,
+            level_count: self.config.level_count,
+            tree_type: self.config.tree_type,
+            table_type: self.config.table_type,
+            named_snapshots: named_snapshots.clone(),
+        };
+
+        rewrite_atomic(
+            self.config.path.join(MANIFEST_FILE),
+            &manifest.encode_into_vec(),
+        )?;
+
+        Ok(())
+    }
+
+    /// Writes a merge operand for a key.
+    ///
+    /// The operand is folded into the key's value by the configured
+    /// [`crate::MergeOperator`] when the key is read, or when it is compacted,
+    /// so read-modify-write cycles (e.g. for counters) are avoided.
+    ///
+    /// Returns the added item's size and new size of the memtable.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config, MergeOperator, UserValue};
+    /// use std::sync::Arc;
+    ///
+    /// struct Append;
+    ///
+    /// impl MergeOperator for Append {
+    ///     fn merge(
+    ///         &self,
+    ///         _key: &[u8],
+    ///         base: Option<&[u8]>,
+    ///         operands: &[&[u8]],
+    ///     ) -> lsm_tree::Result<UserValue> {
+    ///         let mut value = base.unwrap_or_default().to_vec();
+    ///         for operand in operands {
+    ///             value.extend_from_slice(operand);
+    ///         }
+    ///         Ok(value.into())
+    ///     }
+    /// }
+    ///
+    /// let tree = Config::new(folder).merge_operator(Arc::new(Append)).open()?;
+    /// tree.insert("a", "abc", 0);
+    /// tree.merge("a", "def", 1);
+    ///
+    /// assert_eq!(Some("abcdef".as_bytes().into()), tree.get("a", None)?);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    pub fn merge<K: Into<UserKey>, V: Into<UserValue>>(
+        &self,
+        key: K,
+        operand: V,
+        seqno: SeqNo,
+    ) -> (u32, u32) {
+        let value = InternalValue::from_components(key, operand, seqno, ValueType::Merge);
+        self.append_entry(value)
+    }
+
+    /// Inserts a key-value pair that expires after the given duration.
+    ///
+    /// Once expired, the key is treated as deleted by reads,
+    /// and is dropped when it is compacted into the last level.
+    ///
+    /// The expiry is measured using the configured [`crate::Clock`].
+    ///
+    /// Returns the added item's size and new size of the memtable.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config};
+    /// use std::time::Duration;
+    ///
+    /// let tree = Config::new(folder).open()?;
+    /// tree.insert_with_ttl("a", "abc", 0, Duration::from_secs(60));
+    ///
+    /// assert_eq!(Some("abc".as_bytes().into()), tree.get("a", None)?);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    pub fn insert_with_ttl<K: Into<UserKey>, V: AsRef<[u8]>>(
+        &self,
+        key: K,
+        value: V,
+        seqno: SeqNo,
+        ttl: std::time::Duration,
+    ) -> (u32, u32) {
+        let expires_at = self.config.get_clock().now().saturating_add(ttl);
+        let value = crate::ttl::encode(value.as_ref(), expires_at);
+
+        let value = InternalValue::from_components(key, value, seqno, ValueType::ValueWithExpiry);
+        self.append_entry(value)
+    }
+
+    /// Applies a batch of inserts and removals with the same seqno.
+    ///
+    /// An item without a value removes its key. If a key occurs multiple times,
+    /// its last item wins.
+    ///
+    /// The items are validated before anything is written, and the active memtable
+    /// cannot be rotated while the batch is applied, so all items end up in the same
+    /// memtable. As with single writes, the seqno should only be made visible
+    /// to readers after the batch is applied.
+    ///
+    /// Returns the added items' size and new size of the memtable.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let folder = tempfile::tempdir()?;
+    /// use lsm_tree::{AbstractTree, Config};
+    ///
+    /// let tree = Config::new(folder).open()?;
+    /// tree.insert("a", "abc", 0);
+    ///
+    /// tree.apply_batch([("a", None), ("b", Some("def"))], 1)?;
+    ///
+    /// assert!(!tree.contains_key("a", None)?);
+    /// assert!(tree.contains_key("b", None)?);
+    /// assert!(tree.contains_key("a", Some(1))?);
+    /// #
+    /// # Ok::<(), lsm_tree::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if a key or value is invalid, in which case nothing is written.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the memtable lock is poisoned.
+    pub fn apply_batch<K, V, I>(&self, items: I, seqno: SeqNo) -> crate::Result<(u32, u32)>
+    where
+        K: Into<UserKey>,
+        V: Into<UserValue>,
+        I: IntoIterator<Item = (K, Option<V>)>,
+    {
+        let items = items
+            .into_iter()
+            .map(|(key, value)| match value {
+                Some(value) => {
+                    InternalValue::try_from_components(key, value, seqno, ValueType::Value)
+                }
+                None => InternalValue::try_from_components(
+                    key,
+                    UserValue::empty(),
+                    seqno,
+                    ValueType::Tombstone,
+                ),
+            })
+            .collect::<crate::Result<Vec<_>>>()?;
+
+        let memtable = self.active_memtable.read().expect("lock is poisoned");
+
+        let mut batch_size = 0;
+        let mut memtable_size = memtable.size();
+
+        for item in items {
+            let (item_size, size) = memtable.insert(item);
+            batch_size += item_size;
+            memtable_size = size;
+        }
+
+        drop(memtable);
+
+        Ok((batch_size, memtable_size))
+    }
+
     /// Adds an item to the active memtable.
     ///
     /// Returns the added item's size and new size of the memtable.
@@ -736,12 +2857,7 @@ impl Tree {
 
         let tree_id = get_next_tree_id();
 
-        let mut levels = Self::recover_levels(
-            &config.path,
-            tree_id,
-            &config.block_cache,
-            &config.descriptor_table,
-        )?;
+        let mut levels = Self::recover_levels(&config, tree_id)?;
         levels.update_metadata();
 
         let highest_segment_id = levels.iter().map(Segment::id).max().unwrap_or_default();
@@ -751,8 +2867,17 @@ impl Tree {
             segment_id_counter: Arc::new(AtomicU64::new(highest_segment_id + 1)),
             active_memtable: Arc::default(),
             sealed_memtables: Arc::default(),
+            level_view: levels.view_cell(),
             levels: Arc::new(RwLock::new(levels)),
             stop_signal: StopSignal::default(),
+            last_error: RwLock::default(),
+            journal_persisted_seqno: AtomicU64::new(SeqNo::MAX),
+            named_snapshots: RwLock::new(manifest.named_snapshots),
+            seqno_time_map: RwLock::new(SeqnoTimeMap::load(&config.path)?),
+            open_snapshots: SnapshotTracker::default(),
+            persist_watch: PersistWatch::default(),
+            clear_watermark: AtomicU64::default(),
+            compaction_scheduler: config.compaction_strategy.as_ref().map(|_| Arc::default()),
             config,
         };
 
@@ -783,6 +2908,7 @@ impl Tree {
             level_count: config.level_count,
             tree_type: config.tree_type,
             table_type: TableType::Block,
+            named_snapshots: NamedSnapshots::new(),
         }
         .encode_into(&mut file)?;
         file.sync_all()?;
@@ -796,24 +2922,126 @@ impl Tree {
     }
 
     /// Recovers the level manifest, loading all segments from disk.
-    fn recover_levels<P: AsRef<Path>>(
-        tree_path: P,
+    /// Recovers a segment and registers it in the descriptor table.
+    ///
+    /// If the trailer of the segment is cached in the level manifest,
+    /// it is not read from the segment file.
+    fn recover_segment(
+        config: &Config,
+        tree_id: TreeId,
+        segment_file_path: &Path,
+        level_idx: u8,
+        cached_trailer: Option<SegmentFileTrailer>,
+    ) -> crate::Result<Segment> {
+        let prefetch_index = level_idx < config.index_prefetch_levels;
+
+        let pin_index =
+            level_idx == 0 || level_idx == 1 || (prefetch_index && config.pin_prefetched_indexes);
+
+        let trailer = cached_trailer.map_or_else(
+            || SegmentFileTrailer::from_file(segment_file_path, config.block_transform.as_ref()),
+            Ok,
+        )?;
+
+        let segment = Segment::recover_with_trailer(
+            segment_file_path,
+            trailer,
+            tree_id,
+            config.block_cache.clone(),
+            config.descriptor_table.clone(),
+            config.statistics.clone(),
+            pin_index,
+        );
+
+        // NOTE: If lazy, the block index and filter are loaded on first read
+        if !config.lazy_segment_open {
+            segment.load()?;
+        }
+
+        config
+            .descriptor_table
+            .insert(segment_file_path, segment.global_id());
+
+        if prefetch_index && !pin_index {
+            let count = segment.prefetch_index_blocks().map_err(|e| {
+                config.descriptor_table.remove(segment.global_id());
+                e
+            })?;
+
+            log::trace!(
+                "Prefetched {count} index blocks of segment {:?}",
+                segment.global_id()
+            );
+        }
+
+        Ok(segment)
+    }
+
+    /// Recovers segments using up to [`Config::open_threads`] threads.
+    ///
+    /// The results are returned in the order of the input.
+    fn recover_segments(
+        config: &Config,
         tree_id: TreeId,
-        block_cache: &Arc<BlockCache>,
-        descriptor_table: &Arc<FileDescriptorTable>,
-    ) -> crate::Result<LevelManifest> {
+        pending: Vec<PendingSegment>,
+    ) -> Vec<(SegmentId, crate::Result<Segment>)> {
+        let recover = |(segment_id, path, level_idx, cached_trailer): PendingSegment| {
+            log::debug!("Recovering segment from {path:?}");
+            let result = Self::recover_segment(config, tree_id, &path, level_idx, cached_trailer);
+            (segment_id, result)
+        };
+
+        let threads = config.open_threads.min(pending.len());
+
+        if threads <= 1 {
+            return pending.into_iter().map(recover).collect();
+        }
+
+        let queue = Mutex::new(pending.into_iter().enumerate());
+
+        std::thread::scope(|scope| {
+            let workers = (0..threads)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut results = vec![];
+
+                        loop {
+                            let next = queue.lock().expect("lock is poisoned").next();
+
+                            let Some((idx, segment)) = next else {
+                                return results;
+                            };
+
+                            results.push((idx, recover(segment)));
+                        }
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            let mut results = workers
+                .into_iter()
+                .flat_map(|worker| worker.join().expect("segment recovery should not panic"))
+                .collect::<Vec<_>>();
+
+            results.sort_by_key(|(idx, _)| *idx);
+            results.into_iter().map(|(_, result)| result).collect()
+        })
+    }
+
+    fn recover_levels(config: &Config, tree_id: TreeId) -> crate::Result<LevelManifest> {
         use crate::{
             file::fsync_directory,
             file::{LEVELS_MANIFEST_FILE, SEGMENTS_FOLDER},
             SegmentId,
         };
 
-        let tree_path = tree_path.as_ref();
+        let tree_path = &config.path;
 
         let level_manifest_path = tree_path.join(LEVELS_MANIFEST_FILE);
         log::info!("Recovering manifest at {level_manifest_path:?}");
 
-        let segment_id_map = LevelManifest::recover_ids(&level_manifest_path)?;
+        let (segment_id_map, mut metadata_cache) =
+            LevelManifest::recover_ids(&level_manifest_path)?;
         let cnt = segment_id_map.len();
 
         log::debug!("Recovering {cnt} disk segments from {tree_path:?}");
@@ -824,7 +3052,7 @@ impl Tree {
             _ => 100,
         };
 
-        let mut segments = vec![];
+        let mut pending = vec![];
 
         let segment_base_folder = tree_path.join(SEGMENTS_FOLDER);
 
@@ -833,7 +3061,7 @@ impl Tree {
             fsync_directory(&segment_base_folder)?;
         }
 
-        for (idx, dirent) in std::fs::read_dir(&segment_base_folder)?.enumerate() {
+        for dirent in std::fs::read_dir(&segment_base_folder)? {
             let dirent = dirent?;
 
             let file_name = dirent.file_name();
@@ -856,37 +3084,46 @@ impl Tree {
                 continue;
             }
 
-            log::debug!("Recovering segment from {segment_file_path:?}");
-
             let segment_id = segment_file_name.parse::<SegmentId>().map_err(|e| {
                 log::error!("invalid segment file name {segment_file_name:?}: {e:?}");
                 crate::Error::Unrecoverable
             })?;
 
             if let Some(&level_idx) = segment_id_map.get(&segment_id) {
-                let segment = Segment::recover(
-                    &segment_file_path,
-                    tree_id,
-                    block_cache.clone(),
-                    descriptor_table.clone(),
-                    level_idx == 0 || level_idx == 1,
-                )?;
-
-                descriptor_table.insert(&segment_file_path, segment.global_id());
-
-                segments.push(segment);
-                log::debug!("Recovered segment from {segment_file_path:?}");
-
-                if idx % progress_mod == 0 {
-                    log::debug!("Recovered {idx}/{cnt} disk segments");
-                }
+                let cached_trailer = metadata_cache.remove(&segment_id);
+                pending.push((segment_id, segment_file_path, level_idx, cached_trailer));
             } else {
                 log::debug!("Deleting unfinished segment: {segment_file_path:?}",);
                 std::fs::remove_file(&segment_file_path)?;
             }
         }
 
-        if segments.len() < cnt {
+        let mut segments = vec![];
+        let mut quarantined = vec![];
+
+        for (idx, (segment_id, result)) in Self::recover_segments(config, tree_id, pending)
+            .into_iter()
+            .enumerate()
+        {
+            let segment = match result {
+                Ok(segment) => segment,
+                Err(e) if config.quarantine_corrupt_segments && e.is_corruption() => {
+                    quarantine::move_segment_file(tree_path, segment_id)?;
+                    quarantine::notify(config, segment_id, &e);
+                    quarantined.push(segment_id);
+                    continue;
+                }
+                Err(e) => return Err(e.in_segment(segment_id)),
+            };
+
+            segments.push(segment);
+
+            if idx % progress_mod == 0 {
+                log::debug!("Recovered {idx}/{cnt} disk segments");
+            }
+        }
+
+        if segments.len() + quarantined.len() < cnt {
             log::error!(
                 "Recovered less segments than expected: {:?}",
                 segment_id_map.keys(),
@@ -896,6 +3133,19 @@ impl Tree {
 
         log::debug!("Successfully recovered {} segments", segments.len());
 
-        LevelManifest::recover(&level_manifest_path, segments)
+        for stats in crate::segment::access_stats::load(tree_path)? {
+            if let Some(segment) = segments.iter().find(|x| x.id() == stats.segment_id) {
+                segment.access.restore(&stats);
+            }
+        }
+
+        let mut levels = LevelManifest::recover(&level_manifest_path, segments)?;
+
+        if !quarantined.is_empty() {
+            // NOTE: Persist the level manifest without the quarantined segments
+            levels.atomic_swap(|_| {})?;
+        }
+
+        Ok(levels)
     }
 }
@@ -0,0 +1,107 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+//! Expiring values, see [`crate::Tree::insert_with_ttl`]
+//!
+//! The value of an expiring item is prefixed with the point in time
+//! it expires at (in µs since the unix epoch, big endian).
+//!
+//! Expired items are turned into tombstones before the MVCC rules are applied,
+//! so they shadow older versions of their key like deletions do.
+
+use crate::{InternalValue, UserValue, ValueType};
+use std::time::Duration;
+
+const EXPIRY_LEN: usize = std::mem::size_of::<u64>();
+
+/// Prefixes the value with the point in time it expires at.
+pub fn encode(value: &[u8], expires_at: Duration) -> UserValue {
+    let expires_at = u64::try_from(expires_at.as_micros()).unwrap_or(u64::MAX);
+
+    let mut bytes = Vec::with_capacity(EXPIRY_LEN + value.len());
+    bytes.extend_from_slice(&expires_at.to_be_bytes());
+    bytes.extend_from_slice(value);
+    bytes.into()
+}
+
+/// Returns the point in time (in µs since the unix epoch) an expiring value expires at.
+fn expires_at(value: &[u8]) -> Option<u64> {
+    let bytes = value.get(..EXPIRY_LEN)?;
+    bytes.try_into().ok().map(u64::from_be_bytes)
+}
+
+/// Returns the user value of an expiring value.
+pub fn user_value(value: &[u8]) -> &[u8] {
+    value.get(EXPIRY_LEN..).unwrap_or_default()
+}
+
+/// Turns the item into a tombstone if it is an expiring value that has expired at `now`.
+pub fn expire(item: InternalValue, now: Duration) -> InternalValue {
+    if item.key.value_type != ValueType::ValueWithExpiry {
+        return item;
+    }
+
+    // NOTE: A value without expiry is malformed, so treat it as expired
+    let is_expired = expires_at(&item.value)
+        .map_or(true, |expires_at| u128::from(expires_at) <= now.as_micros());
+
+    if is_expired {
+        InternalValue::new_tombstone(item.key.user_key, item.key.seqno)
+    } else {
+        item
+    }
+}
+
+/// Turns an expiring value into a regular value, by removing its expiry.
+pub fn strip(mut item: InternalValue) -> InternalValue {
+    if item.key.value_type == ValueType::ValueWithExpiry {
+        item.value = item.value.slice(EXPIRY_LEN.min(item.value.len())..);
+        item.key.value_type = ValueType::Value;
+    }
+    item
+}
+
+/// Resolves the newest version of a key as seen at `now`.
+///
+/// Returns `None` if the item is a tombstone or has expired.
+pub fn resolve(item: InternalValue, now: Duration) -> Option<InternalValue> {
+    let item = expire(item, now);
+
+    if item.is_tombstone() {
+        None
+    } else {
+        Some(strip(item))
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn ttl_expire() {
+        let value = encode(b"abc", Duration::from_secs(10));
+        let item = InternalValue::from_components("a", value, 5, ValueType::ValueWithExpiry);
+
+        let resolved = resolve(item.clone(), Duration::from_secs(9)).expect("should exist");
+        assert_eq!(ValueType::Value, resolved.key.value_type);
+        assert_eq!(b"abc", &*resolved.value);
+
+        assert!(resolve(item.clone(), Duration::from_secs(10)).is_none());
+
+        let expired = expire(item, Duration::from_secs(11));
+        assert!(expired.is_tombstone());
+        assert_eq!(5, expired.key.seqno);
+        assert!(expired.value.is_empty());
+    }
+
+    #[test]
+    fn ttl_regular_value() {
+        let item = InternalValue::from_components("a", "abc", 5, ValueType::Value);
+        assert_eq!(item, expire(item.clone(), Duration::MAX));
+        assert_eq!(Some(item.clone()), resolve(item, Duration::MAX));
+    }
+}
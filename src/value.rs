@@ -40,6 +40,12 @@ pub enum ValueType {
 
     /// "Weak" deletion (a.k.a. `SingleDelete` in `RocksDB`)
     WeakTombstone,
+
+    /// Merge operand, see [`crate::MergeOperator`]
+    Merge,
+
+    /// Value that expires, see [`crate::Tree::insert_with_ttl`]
+    ValueWithExpiry,
 }
 
 impl TryFrom<u8> for ValueType {
@@ -50,6 +56,8 @@ impl TryFrom<u8> for ValueType {
             0 => Ok(Self::Value),
             1 => Ok(Self::Tombstone),
             2 => Ok(Self::WeakTombstone),
+            3 => Ok(Self::Merge),
+            4 => Ok(Self::ValueWithExpiry),
             _ => Err(()),
         }
     }
@@ -61,6 +69,8 @@ impl From<ValueType> for u8 {
             ValueType::Value => 0,
             ValueType::Tombstone => 1,
             ValueType::WeakTombstone => 2,
+            ValueType::Merge => 3,
+            ValueType::ValueWithExpiry => 4,
         }
     }
 }
@@ -111,6 +121,40 @@ impl InternalValue {
         Self::new(key, value)
     }
 
+    /// Creates a new [`Value`], returning an error instead of panicking
+    /// on invalid input.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the key length is empty or greater than 2^16, or the value length is greater than 2^32.
+    pub fn try_from_components<K: Into<UserKey>, V: Into<UserValue>>(
+        user_key: K,
+        value: V,
+        seqno: SeqNo,
+        value_type: ValueType,
+    ) -> crate::Result<Self> {
+        let user_key = user_key.into();
+        let value = value.into();
+
+        if user_key.is_empty() {
+            return Err(crate::Error::InvalidInput("key may not be empty"));
+        }
+
+        if u16::try_from(user_key.len()).is_err() {
+            return Err(crate::Error::InvalidInput(
+                "keys can be 65535 bytes in length",
+            ));
+        }
+
+        if u32::try_from(value.len()).is_err() {
+            return Err(crate::Error::InvalidInput(
+                "values can be 2^32 bytes in length",
+            ));
+        }
+
+        Ok(Self::from_components(user_key, value, seqno, value_type))
+    }
+
     /// Creates a new tombstone.
     ///
     /// # Panics
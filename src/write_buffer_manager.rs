@@ -0,0 +1,173 @@
+// Copyright (c) 2024-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::{
+    tree::inner::{TreeId, TreeInner},
+    Tree,
+};
+use std::sync::{Mutex, Weak};
+
+/// Keeps the memtables of multiple trees below a single write buffer size
+///
+/// Trees are registered when they are opened with
+/// [`Config::write_buffer_manager`](crate::Config::write_buffer_manager).
+/// The manager tracks the size of their active and sealed memtables,
+/// and reports which tree should be flushed once the limit is exceeded.
+///
+/// The manager does not flush trees on its own, [`WriteBufferManager::tree_to_flush`]
+/// needs to be checked periodically, e.g. after a batch of writes.
+///
+/// # Examples
+///
+/// ```
+/// # let folder = tempfile::tempdir()?;
+/// use lsm_tree::{AbstractTree, Config, WriteBufferManager};
+/// use std::sync::Arc;
+///
+/// let manager = Arc::new(WriteBufferManager::new(/* 64 MiB */ 64 * 1_024 * 1_024));
+///
+/// let a = Config::new(folder.path().join("a"))
+///     .write_buffer_manager(manager.clone())
+///     .open()?;
+///
+/// let b = Config::new(folder.path().join("b"))
+///     .write_buffer_manager(manager.clone())
+///     .open_as_blob_tree()?;
+///
+/// a.insert("a", "abc", 0);
+/// b.insert("a", "abc", 1);
+///
+/// assert!(!manager.is_exceeded());
+/// assert_eq!(None, manager.tree_to_flush());
+/// #
+/// # Ok::<(), lsm_tree::Error>(())
+/// ```
+pub struct WriteBufferManager {
+    limit: u64,
+
+    /// Registered trees, which are forgotten once they are dropped
+    trees: Mutex<Vec<Weak<TreeInner>>>,
+}
+
+impl WriteBufferManager {
+    /// Creates a write buffer manager that allows `limit` bytes of memtables in total.
+    #[must_use]
+    pub fn new(limit: u64) -> Self {
+        Self {
+            limit,
+            trees: Mutex::default(),
+        }
+    }
+
+    /// Returns the write buffer size limit in bytes.
+    #[must_use]
+    pub fn limit(&self) -> u64 {
+        self.limit
+    }
+
+    pub(crate) fn register(&self, tree: &Tree) {
+        let mut trees = self.trees.lock().expect("lock is poisoned");
+        trees.retain(|tree| tree.strong_count() > 0);
+        trees.push(std::sync::Arc::downgrade(&tree.0));
+    }
+
+    /// Calls `f` with every registered tree that is still open.
+    fn for_each_tree<F: FnMut(&TreeInner)>(&self, mut f: F) {
+        let trees = self.trees.lock().expect("lock is poisoned");
+
+        for tree in trees.iter().filter_map(Weak::upgrade) {
+            f(&tree);
+        }
+    }
+
+    /// Returns the amount of trees that are registered and still open.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the manager lock is poisoned.
+    #[must_use]
+    pub fn tree_count(&self) -> usize {
+        let mut count = 0;
+        self.for_each_tree(|_| count += 1);
+        count
+    }
+
+    /// Returns the size of the active and sealed memtables of all registered trees.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a lock is poisoned.
+    #[must_use]
+    pub fn usage(&self) -> u64 {
+        let mut usage = 0;
+
+        self.for_each_tree(|tree| {
+            let (active, sealed) = memtables_size(tree);
+            usage += active + sealed;
+        });
+
+        usage
+    }
+
+    /// Returns `true` if the memtables of all registered trees exceed the limit.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a lock is poisoned.
+    #[must_use]
+    pub fn is_exceeded(&self) -> bool {
+        self.usage() > self.limit
+    }
+
+    /// Returns the ID of the tree whose active memtable should be flushed next,
+    /// if the limit is exceeded.
+    ///
+    /// That is the tree with the largest active memtable, because flushing it frees
+    /// the most memory. Sealed memtables are not considered, because they are
+    /// already queued up for flushing.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a lock is poisoned.
+    #[must_use]
+    pub fn tree_to_flush(&self) -> Option<TreeId> {
+        let mut usage = 0;
+        let mut largest: Option<(u64, TreeId)> = None;
+
+        self.for_each_tree(|tree| {
+            let (active, sealed) = memtables_size(tree);
+            usage += active + sealed;
+
+            if active > 0 && largest.map_or(true, |(size, _)| active > size) {
+                largest = Some((active, tree.id));
+            }
+        });
+
+        if usage > self.limit {
+            largest.map(|(_, id)| id)
+        } else {
+            None
+        }
+    }
+}
+
+/// Returns the size of the active and sealed memtables of a tree.
+fn memtables_size(tree: &TreeInner) -> (u64, u64) {
+    let active = u64::from(
+        tree.active_memtable
+            .read()
+            .expect("lock is poisoned")
+            .size(),
+    );
+
+    let sealed = tree
+        .sealed_memtables
+        .read()
+        .expect("lock is poisoned")
+        .iter()
+        .map(|(_, memtable)| u64::from(memtable.size()))
+        .sum();
+
+    (active, sealed)
+}
@@ -0,0 +1,49 @@
+use lsm_tree::{AbstractTree, Config};
+use test_log::test;
+
+#[test]
+fn blob_tree_dangling_value_handle() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let big_value = b"neptune!".repeat(1_000);
+
+    let tree = Config::new(&folder).open_as_blob_tree()?;
+    tree.insert("big", &big_value, 0);
+    tree.insert("smol", "small value", 0);
+    tree.flush_active_memtable(0)?;
+
+    // NOTE: Simulate an inconsistency between index and value log
+    let blob_file_ids = tree.blobs.manifest.list_segment_ids();
+    tree.blobs.manifest.drop_segments(&blob_file_ids)?;
+
+    match tree.get("big", None) {
+        Err(lsm_tree::Error::DanglingValueHandle { key, .. }) => {
+            assert_eq!(b"big", &*key);
+        }
+        _ => panic!("should fail with dangling value handle"),
+    }
+
+    assert_eq!(
+        b"small value",
+        &*tree.get("smol", None)?.expect("should exist")
+    );
+
+    let mut iter = tree.range("big"..="smol", None, None);
+
+    match iter.next() {
+        Some(Err(e)) => {
+            assert_eq!(lsm_tree::ErrorKind::Corruption, e.kind());
+            assert!(matches!(e, lsm_tree::Error::DanglingValueHandle { .. }));
+        }
+        _ => panic!("should fail with dangling value handle"),
+    }
+    assert_eq!(b"smol", &*iter.next().expect("should exist")?.0);
+
+    assert!(tree
+        .prefix("b", None, None)
+        .next()
+        .expect("should exist")
+        .is_err());
+
+    Ok(())
+}
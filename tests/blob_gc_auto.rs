@@ -0,0 +1,68 @@
+use lsm_tree::{AbstractTree, Config, SequenceNumberCounter};
+use test_log::test;
+
+#[test]
+fn blob_gc_auto_space_amp_target() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let seqno = SequenceNumberCounter::default();
+
+    let tree = Config::new(&folder)
+        .blob_space_amp_target(1.5, seqno.clone())
+        .open_as_blob_tree()?;
+
+    let big_value = b"neptune!".repeat(128_000);
+    let new_big_value = b"winter!".repeat(128_000);
+
+    for key in ["a", "b", "c"] {
+        tree.insert(key, &big_value, seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+    assert_eq!(1, tree.blobs.segment_count());
+
+    tree.insert("a", &new_big_value, seqno.next());
+    tree.flush_active_memtable(seqno.get())?;
+
+    // NOTE: 4 blobs, 3 of them alive
+    assert_eq!(2, tree.blobs.segment_count());
+    assert!(tree.blobs.space_amp() <= 1.5);
+
+    tree.insert("b", &new_big_value, seqno.next());
+    tree.flush_active_memtable(seqno.get())?;
+
+    // NOTE: 5 blobs, 3 of them alive, so the first blob file
+    // was relocated, leaving only "c" in a new blob file
+    assert_eq!(3, tree.blobs.segment_count());
+    assert!(tree.blobs.space_amp() <= 1.5);
+
+    assert_eq!(&*tree.get("a", None)?.unwrap(), new_big_value);
+    assert_eq!(&*tree.get("b", None)?.unwrap(), new_big_value);
+    assert_eq!(&*tree.get("c", None)?.unwrap(), big_value);
+
+    // NOTE: Writes after GC shadow the relocated blobs
+    tree.insert("c", &new_big_value, seqno.next());
+    assert_eq!(&*tree.get("c", None)?.unwrap(), new_big_value);
+
+    Ok(())
+}
+
+#[test]
+fn blob_gc_auto_disabled() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open_as_blob_tree()?;
+
+    let seqno = SequenceNumberCounter::default();
+
+    let big_value = b"neptune!".repeat(128_000);
+
+    for _ in 0..3 {
+        tree.insert("a", &big_value, seqno.next());
+        tree.flush_active_memtable(seqno.get())?;
+    }
+
+    // NOTE: Without a target, blob GC needs to be run manually
+    assert_eq!(3, tree.blobs.segment_count());
+
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use lsm_tree::{AbstractTree, Config, RateLimiter, SequenceNumberCounter};
+use std::{sync::Arc, time::Duration};
+use test_log::test;
+
+#[test]
+fn blob_gc_rate_limit() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let rate_limiter = Arc::new(RateLimiter::new(/* 1 MiB */ 1_024 * 1_024));
+
+    let tree = Config::new(&folder)
+        .rate_limiter(rate_limiter.clone())
+        .open_as_blob_tree()?;
+
+    let seqno = SequenceNumberCounter::default();
+
+    let big_value = b"neptune!".repeat(16_000);
+    let new_big_value = b"winter!!".repeat(16_000);
+
+    tree.insert("a", &big_value, seqno.next());
+    tree.insert("b", &big_value, seqno.next());
+    tree.flush_active_memtable(0)?;
+
+    tree.insert("a", &new_big_value, seqno.next());
+    tree.flush_active_memtable(0)?;
+    assert_eq!(2, tree.blobs.segment_count());
+
+    tree.gc_scan_stats(seqno.get(), 1_000)?;
+
+    // NOTE: Rewriting the first blob file (256 KB) takes a quarter second
+    let start = std::time::Instant::now();
+    let strategy = value_log::SpaceAmpStrategy::new(1.0);
+    tree.apply_gc_strategy(&strategy, seqno.next())?;
+    assert!(start.elapsed() >= Duration::from_millis(200));
+
+    assert_eq!(2, tree.blobs.segment_count());
+    assert_eq!(&*tree.get("a", None)?.unwrap(), new_big_value);
+    assert_eq!(&*tree.get("b", None)?.unwrap(), big_value);
+
+    Ok(())
+}
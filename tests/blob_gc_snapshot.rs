@@ -0,0 +1,106 @@
+use lsm_tree::{AbstractTree, Config};
+use test_log::test;
+
+#[test]
+fn blob_gc_snapshot_pins_blobs() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open_as_blob_tree()?;
+
+    let old_value = "old".repeat(10_000);
+
+    tree.insert("a", &old_value, 0);
+    tree.flush_active_memtable(0)?;
+
+    let snapshot = tree.snapshot(1);
+
+    tree.insert("a", "new", 1);
+    tree.flush_active_memtable(0)?;
+
+    let report = tree.gc_scan_stats(2, 1_000)?;
+    assert_eq!(0, report.stale_blobs);
+
+    let strategy = lsm_tree::gc::StaleThresholdStrategy::new(0.0);
+    tree.apply_gc_strategy(&strategy, 2)?;
+    assert_eq!(1, tree.blob_file_count());
+
+    assert_eq!(
+        old_value.as_bytes(),
+        &*snapshot.get("a")?.expect("should exist")
+    );
+
+    drop(snapshot);
+
+    let report = tree.gc_scan_stats(2, 1_000)?;
+    assert_eq!(1, report.stale_blobs);
+
+    tree.gc_drop_stale()?;
+    assert_eq!(0, tree.blob_file_count());
+    assert_eq!(b"new", &*tree.get("a", None)?.expect("should exist"));
+
+    Ok(())
+}
+
+#[test]
+fn blob_gc_snapshot_opened_after_scan() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open_as_blob_tree()?;
+
+    let old_value = "old".repeat(10_000);
+
+    tree.insert("a", &old_value, 0);
+    tree.flush_active_memtable(0)?;
+
+    tree.insert("a", "new", 1);
+    tree.flush_active_memtable(0)?;
+
+    let report = tree.gc_scan_stats(2, 1_000)?;
+    assert_eq!(1, report.stale_blobs);
+
+    // NOTE: The scan did not know about the snapshot
+    let snapshot = tree.snapshot(1);
+
+    assert_eq!(0, tree.gc_drop_stale()?);
+    assert_eq!(1, tree.blob_file_count());
+
+    assert_eq!(
+        old_value.as_bytes(),
+        &*snapshot.get("a")?.expect("should exist")
+    );
+
+    // NOTE: A snapshot at the latest seqno can not read stale blobs
+    drop(snapshot);
+    let _snapshot = tree.snapshot(2);
+
+    tree.gc_drop_stale()?;
+    assert_eq!(0, tree.blob_file_count());
+
+    Ok(())
+}
+
+#[test]
+fn blob_gc_registered_snapshot() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open_as_blob_tree()?;
+
+    tree.insert("a", "old".repeat(10_000), 0);
+    tree.flush_active_memtable(0)?;
+
+    tree.insert("a", "new", 1);
+    tree.flush_active_memtable(0)?;
+
+    tree.register_snapshot(1);
+
+    let report = tree.gc_scan_stats(2, 1_000)?;
+    assert_eq!(0, report.stale_blobs);
+
+    assert!(tree.deregister_snapshot(1));
+    assert!(!tree.deregister_snapshot(1));
+
+    let report = tree.gc_scan_stats(2, 1_000)?;
+    assert_eq!(1, report.stale_blobs);
+
+    Ok(())
+}
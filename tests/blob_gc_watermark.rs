@@ -27,8 +27,9 @@ fn blob_gc_seqno_watermark() -> lsm_tree::Result<()> {
     assert_eq!(&*snapshot.get("a")?.unwrap(), b"neptune".repeat(10_000));
     assert_eq!(&*tree.get("a", None)?.unwrap(), b"neptune3".repeat(10_000));
 
+    // NOTE: The blob of the first version is still read by the open snapshot
     let report = tree.gc_scan_stats(seqno.get() + 1, 0)?;
-    assert_eq!(2, report.stale_blobs);
+    assert_eq!(1, report.stale_blobs);
 
     let strategy = value_log::SpaceAmpStrategy::new(1.0);
     tree.apply_gc_strategy(&strategy, 0)?;
@@ -38,7 +39,7 @@ fn blob_gc_seqno_watermark() -> lsm_tree::Result<()> {
     //
     // This would previously fail
     let report = tree.gc_scan_stats(seqno.get() + 1, 0)?;
-    assert_eq!(2, report.stale_blobs);
+    assert_eq!(1, report.stale_blobs);
 
     assert_eq!(&*snapshot.get("a")?.unwrap(), b"neptune".repeat(10_000));
     assert_eq!(&*tree.get("a", None)?.unwrap(), b"neptune3".repeat(10_000));
@@ -0,0 +1,94 @@
+use lsm_tree::{AbstractTree, Config};
+use std::path::Path;
+use test_log::test;
+
+fn copy_dir(from: &Path, to: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(to)?;
+
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let dest = to.join(entry.file_name());
+
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &dest)?;
+        } else {
+            std::fs::copy(entry.path(), dest)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn blob_verify_references() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open_as_blob_tree()?;
+
+    let big_value = "abc".repeat(10_000);
+
+    tree.insert("a", &big_value, 0);
+    tree.insert("b", &big_value, 1);
+    tree.insert("c", "small", 2);
+    tree.flush_active_memtable(0)?;
+
+    tree.insert("d", &big_value, 3);
+
+    let report = tree.verify_references()?;
+    assert!(report.is_consistent());
+    assert!(report.unreferenced_blobs.is_empty());
+
+    // NOTE: Old version of "a" is still referenced
+    tree.insert("a", &big_value, 4);
+    tree.flush_active_memtable(0)?;
+
+    let report = tree.verify_references()?;
+    assert!(report.is_consistent());
+    assert!(report.unreferenced_blobs.is_empty());
+
+    tree.index.major_compact(u64::MAX, 5)?;
+
+    let report = tree.verify_references()?;
+    assert!(report.is_consistent());
+    assert_eq!(1, report.unreferenced_blobs.len());
+    assert_eq!(b"a", &*report.unreferenced_blobs[0].1);
+
+    Ok(())
+}
+
+#[test]
+fn blob_verify_references_dangling() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let old_blobs = tempfile::tempdir()?;
+
+    let big_value = "abc".repeat(10_000);
+
+    {
+        let tree = Config::new(&folder).open_as_blob_tree()?;
+
+        tree.insert("a", &big_value, 0);
+        tree.flush_active_memtable(0)?;
+
+        copy_dir(&folder.path().join("blobs"), old_blobs.path())?;
+
+        tree.insert("b", &big_value, 1);
+        tree.flush_active_memtable(0)?;
+    }
+
+    // NOTE: Simulate a botched restore, where the index is newer than the value log
+    std::fs::remove_dir_all(folder.path().join("blobs"))?;
+    copy_dir(old_blobs.path(), &folder.path().join("blobs"))?;
+
+    let tree = Config::new(&folder).open_as_blob_tree()?;
+
+    let report = tree.verify_references()?;
+    assert!(!report.is_consistent());
+    assert!(report.unreferenced_blobs.is_empty());
+    assert_eq!(1, report.dangling_handles.len());
+
+    let (key, seqno, _) = &report.dangling_handles[0];
+    assert_eq!(b"b", &**key);
+    assert_eq!(1, *seqno);
+
+    Ok(())
+}
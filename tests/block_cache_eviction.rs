@@ -0,0 +1,66 @@
+use lsm_tree::{
+    AbstractTree, BlockCache, BlockEvictionObserver, BlockType, Config, GlobalSegmentId,
+};
+use std::sync::{Arc, Mutex};
+use test_log::test;
+
+const ITEM_COUNT: usize = 1_000;
+
+#[derive(Default)]
+struct EvictionLog(Mutex<Vec<(GlobalSegmentId, BlockType, u64)>>);
+
+impl BlockEvictionObserver for EvictionLog {
+    fn on_block_evicted(&self, segment_id: GlobalSegmentId, block_type: BlockType, weight: u64) {
+        self.0
+            .lock()
+            .expect("lock is poisoned")
+            .push((segment_id, block_type, weight));
+    }
+}
+
+#[test]
+fn block_cache_eviction_observer() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let observer = Arc::new(EvictionLog::default());
+    let block_cache =
+        Arc::new(BlockCache::with_capacity_bytes(16_000).eviction_observer(observer.clone()));
+
+    let tree = Config::new(&folder)
+        .data_block_size(1_024)
+        .block_cache(block_cache.clone())
+        .open()?;
+
+    for x in 0..ITEM_COUNT as u64 {
+        tree.insert(x.to_be_bytes(), "a".repeat(100), 0);
+    }
+    let segment = tree.flush_active_memtable(0)?.expect("should flush");
+
+    for x in 0..ITEM_COUNT as u64 {
+        assert!(tree.contains_key(x.to_be_bytes(), None)?);
+    }
+
+    let evicted = observer.0.lock().expect("lock is poisoned");
+    assert!(!evicted.is_empty());
+    assert!(block_cache.size() <= block_cache.capacity());
+
+    for (segment_id, _, weight) in evicted.iter() {
+        assert_eq!(segment.id(), segment_id.segment_id());
+        assert!(*weight > 0);
+    }
+    assert!(evicted
+        .iter()
+        .any(|(_, block_type, _)| *block_type == BlockType::Data));
+
+    Ok(())
+}
+
+#[test]
+fn block_cache_eviction_observer_keeps_limit() {
+    let block_cache = BlockCache::with_capacity_bytes(1_000);
+    block_cache.set_limit(500);
+
+    let block_cache = block_cache.eviction_observer(Arc::new(EvictionLog::default()));
+    assert_eq!(1_000, block_cache.capacity());
+    assert_eq!(500, block_cache.limit());
+}
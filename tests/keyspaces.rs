@@ -0,0 +1,118 @@
+use lsm_tree::{AbstractTree, Config, Keyspaces};
+use std::sync::Arc;
+use test_log::test;
+
+#[test]
+fn keyspaces_shared_resources() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let keyspaces = Keyspaces::open(Config::new(&folder))?;
+    let a = keyspaces.keyspace("a", |config| config)?;
+    let b = keyspaces.keyspace("b", |config| config.level_count(3))?;
+
+    assert!(Arc::ptr_eq(&a.config.block_cache, &b.config.block_cache));
+    assert!(Arc::ptr_eq(
+        &a.config.descriptor_table,
+        &b.config.descriptor_table
+    ));
+    assert_eq!(7, a.config.level_count);
+    assert_eq!(3, b.config.level_count);
+
+    // NOTE: Keyspaces have separate memtables and levels
+    a.insert("k", "a", 0);
+    a.flush_active_memtable(0)?;
+    b.insert("k", "b", 1);
+
+    assert_eq!(1, a.segment_count());
+    assert_eq!(0, b.segment_count());
+    assert_eq!(Some("a".as_bytes().into()), a.get("k", None)?);
+    assert_eq!(Some("b".as_bytes().into()), b.get("k", None)?);
+
+    Ok(())
+}
+
+#[test]
+fn keyspaces_recover() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    {
+        let keyspaces = Keyspaces::open(Config::new(&folder))?;
+        let users = keyspaces.keyspace("users", |config| config)?;
+        keyspaces.keyspace("emails", |config| config)?;
+
+        users.insert("1", "alice", 0);
+        users.flush_active_memtable(0)?;
+    }
+
+    let keyspaces = Keyspaces::open(Config::new(&folder))?;
+    assert_eq!(vec!["emails", "users"], keyspaces.list_keyspaces()?);
+    assert!(keyspaces.contains_keyspace("users")?);
+    assert!(!keyspaces.contains_keyspace("orders")?);
+
+    let users = keyspaces.keyspace("users", |config| config)?;
+    assert_eq!(Some("alice".as_bytes().into()), users.get("1", None)?);
+
+    Ok(())
+}
+
+#[test]
+fn keyspaces_invalid_name() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let keyspaces = Keyspaces::open(Config::new(&folder))?;
+
+    assert!(matches!(
+        keyspaces.keyspace("../other", |config| config),
+        Err(lsm_tree::Error::InvalidInput(_))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn keyspaces_batch_atomic() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let keyspaces = Keyspaces::open(Config::new(&folder))?;
+    let a = keyspaces.keyspace("a", |config| config)?;
+    let b = keyspaces.keyspace("b", |config| config)?;
+
+    a.insert("gone", "abc", 0);
+
+    let mut batch = keyspaces.batch();
+    batch.insert("a", "x", "1");
+    batch.remove("a", "gone");
+    batch.insert("b", "y", "2");
+    assert_eq!(3, batch.len());
+    batch.commit(1)?;
+
+    assert!(a.contains_key("x", None)?);
+    assert!(!a.contains_key("gone", None)?);
+    assert!(b.contains_key("y", None)?);
+
+    // NOTE: All writes share the seqno, so older reads see none of them
+    assert!(!a.contains_key("x", Some(1))?);
+    assert!(a.contains_key("gone", Some(1))?);
+    assert!(!b.contains_key("y", Some(1))?);
+
+    // NOTE: Rejected batches do not change anything
+    let mut batch = keyspaces.batch();
+    batch.insert("a", "z", "3");
+    batch.insert("b", "", "3");
+    assert!(matches!(
+        batch.commit(2),
+        Err(lsm_tree::Error::InvalidInput(_))
+    ));
+
+    let mut batch = keyspaces.batch();
+    batch.insert("a", "z", "3");
+    batch.insert("unknown", "z", "3");
+    assert!(matches!(
+        batch.commit(2),
+        Err(lsm_tree::Error::InvalidInput(_))
+    ));
+
+    assert!(!a.contains_key("z", None)?);
+
+    Ok(())
+}
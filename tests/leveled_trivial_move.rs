@@ -0,0 +1,48 @@
+use lsm_tree::{compaction::Leveled, AbstractTree, Config, SequenceNumberCounter};
+use std::sync::Arc;
+use test_log::test;
+
+#[test]
+fn leveled_trivial_move_sequential() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    let strategy = Arc::new(Leveled {
+        target_size: 4_000,
+        ..Default::default()
+    });
+
+    // NOTE: Sequential inserts create disjoint segments
+    for batch in 0..4u64 {
+        for x in 0..100u64 {
+            let key = (batch * 100 + x).to_be_bytes();
+            tree.insert(key, "a".repeat(100), seqno.next());
+        }
+        tree.flush_active_memtable(0)?;
+    }
+
+    let segment_ids = |tree: &lsm_tree::Tree| {
+        let mut ids = tree
+            .levels
+            .read()
+            .expect("lock is poisoned")
+            .iter()
+            .map(lsm_tree::Segment::id)
+            .collect::<Vec<_>>();
+        ids.sort_unstable();
+        ids
+    };
+
+    let before = segment_ids(&tree);
+    assert_eq!(4, tree.first_level_segment_count());
+
+    tree.compact(strategy, seqno.get())?;
+
+    // NOTE: The segments were moved into L1, not rewritten
+    assert_eq!(0, tree.first_level_segment_count());
+    assert_eq!(before, segment_ids(&tree));
+    assert_eq!(400, tree.len(None, None)?);
+
+    Ok(())
+}
@@ -1,4 +1,5 @@
-use lsm_tree::{AbstractTree, Config, SequenceNumberCounter};
+use lsm_tree::{compaction::Leveled, AbstractTree, Config, SequenceNumberCounter};
+use std::sync::Arc;
 use test_log::test;
 
 #[test]
@@ -53,3 +54,96 @@ fn tree_major_compaction() -> lsm_tree::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn blob_tree_major_compaction() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open_as_blob_tree()?;
+    let seqno = SequenceNumberCounter::default();
+
+    for _ in 0..5 {
+        tree.insert("a", "abc", seqno.next());
+        tree.insert("b", "abc", seqno.next());
+        tree.flush_active_memtable(0)?;
+    }
+    assert_eq!(10, tree.approximate_len());
+
+    tree.insert("a", "def", seqno.next());
+    tree.flush_active_memtable(0)?;
+
+    tree.major_compact(u64::MAX, seqno.get())?;
+    assert_eq!(1, tree.segment_count());
+    assert_eq!(2, tree.approximate_len());
+    assert_eq!(Some("def".as_bytes().into()), tree.get("a", None)?);
+    assert_eq!(Some("abc".as_bytes().into()), tree.get("b", None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_major_compaction_background() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder)
+        .background_compaction(Arc::new(Leveled::default()), 2)
+        .open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    for x in 0..20_u64 {
+        tree.insert(x.to_be_bytes(), "abc", seqno.next());
+        tree.flush_active_memtable(0)?;
+    }
+
+    tree.major_compact(u64::MAX, seqno.get())?;
+    assert_eq!(1, tree.segment_count());
+    assert_eq!(20, tree.len(None, None)?);
+
+    let scheduler = tree.compaction_scheduler().expect("should be enabled");
+    assert!(!scheduler.is_paused());
+
+    Ok(())
+}
+
+#[test]
+fn tree_major_compaction_concurrent() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder)
+        .background_compaction(Arc::new(Leveled::default()), 2)
+        .open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    for x in 0..20_u64 {
+        tree.insert(x.to_be_bytes(), "abc", seqno.next());
+        tree.flush_active_memtable(0)?;
+    }
+
+    let seqno_threshold = seqno.get();
+
+    std::thread::scope(|s| {
+        let handles = (0..4)
+            .map(|_| s.spawn(|| tree.major_compact(u64::MAX, seqno_threshold)))
+            .collect::<Vec<_>>();
+
+        for handle in handles {
+            handle.join().expect("should join")?;
+        }
+
+        Ok::<_, lsm_tree::Error>(())
+    })?;
+
+    assert_eq!(20, tree.len(None, None)?);
+
+    let scheduler = tree.compaction_scheduler().expect("should be enabled");
+    assert!(!scheduler.is_paused());
+
+    // NOTE: A pause of the application is kept
+    scheduler.pause();
+    tree.major_compact(u64::MAX, seqno_threshold)?;
+    assert!(scheduler.is_paused());
+    scheduler.resume();
+    assert!(!scheduler.is_paused());
+
+    Ok(())
+}
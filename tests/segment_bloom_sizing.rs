@@ -0,0 +1,82 @@
+use lsm_tree::{AbstractTree, Config};
+use test_log::test;
+
+const ITEM_COUNT: usize = 10_000;
+
+fn write_last_level(config: Config) -> lsm_tree::Result<lsm_tree::Tree> {
+    let tree = config.open()?;
+
+    for x in 0..ITEM_COUNT as u64 {
+        tree.insert(x.to_be_bytes(), "abc", 0);
+    }
+    tree.flush_active_memtable(0)?;
+    tree.major_compact(u64::MAX, 0)?;
+
+    Ok(tree)
+}
+
+fn segments(tree: &lsm_tree::Tree) -> Vec<lsm_tree::Segment> {
+    tree.levels
+        .read()
+        .expect("lock is poisoned")
+        .iter()
+        .cloned()
+        .collect()
+}
+
+#[test]
+fn segment_bloom_fp_rate() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = write_last_level(Config::new(&folder).bloom_fp_rate(0.001))?;
+
+    let segment = segments(&tree).pop().expect("should exist");
+    let fpr = segment.filter_fp_rate().expect("should have fp rate");
+    assert!((0.0008..0.0012).contains(&fpr), "{fpr}");
+
+    // NOTE: Roughly 14.4 bits per key
+    let bloom_size = segment.bloom_filter_size();
+    assert!(bloom_size > ITEM_COUNT * 14 / 8);
+    assert!(bloom_size < ITEM_COUNT * 15 / 8);
+
+    drop(tree);
+
+    // NOTE: The rate is persisted in the segment file
+    let tree = Config::new(&folder).open()?;
+    assert_eq!(
+        Some(fpr),
+        segments(&tree).pop().and_then(|x| x.filter_fp_rate())
+    );
+
+    Ok(())
+}
+
+#[test]
+fn segment_bloom_max_bits_per_key() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = write_last_level(
+        Config::new(&folder)
+            .bloom_fp_rate(0.000_001)
+            .bloom_max_bits_per_key(vec![16, 16, 4, 4, 4, 4, 4]),
+    )?;
+
+    let segment = segments(&tree).pop().expect("should exist");
+    assert!(segment.bloom_filter_size() <= ITEM_COUNT * 4 / 8 + 1);
+    assert!(segment.filter_fp_rate().expect("should have fp rate") > 0.1);
+
+    Ok(())
+}
+
+#[test]
+fn segment_bloom_disabled_by_cap() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = write_last_level(Config::new(&folder).bloom_max_bits_per_key(vec![0; 7]))?;
+
+    let segment = segments(&tree).pop().expect("should exist");
+    assert_eq!(0, segment.bloom_filter_size());
+    assert_eq!(None, segment.filter_fp_rate());
+
+    Ok(())
+}
@@ -0,0 +1,55 @@
+use lsm_tree::{AbstractTree, Config, DumpItem, ValueType};
+use test_log::test;
+
+#[test]
+fn segment_dump() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?.into_path();
+
+    let tree = Config::new(folder).data_block_size(1_024).open()?;
+
+    for key in 0u64..1_000 {
+        tree.insert(key.to_be_bytes(), "old", 0);
+    }
+    for key in 0u64..1_000 {
+        tree.remove(key.to_be_bytes(), 1);
+    }
+
+    let segment = tree.flush_active_memtable(0)?.expect("should flush");
+
+    let mut blocks = vec![];
+    let mut entries = vec![];
+
+    for item in tree
+        .dump_segment(segment.id())?
+        .expect("segment should exist")
+    {
+        match item? {
+            DumpItem::Block(info) => blocks.push(info),
+            DumpItem::Entry(entry) => entries.push(entry),
+        }
+    }
+
+    assert_eq!(segment.metadata.data_block_count as usize, blocks.len());
+    assert_eq!(2_000, entries.len());
+    assert_eq!(
+        entries.len(),
+        blocks.iter().map(|block| block.item_count).sum::<usize>()
+    );
+    assert_eq!(0, *blocks.first().expect("should exist").offset);
+    assert!(blocks.windows(2).all(|w| w[0].offset < w[1].offset));
+    assert!(blocks
+        .windows(2)
+        .all(|w| w[1].previous_block_offset == w[0].offset));
+
+    assert_eq!(
+        1_000,
+        entries
+            .iter()
+            .filter(|x| x.key.value_type == ValueType::Tombstone)
+            .count()
+    );
+
+    assert!(tree.dump_segment(segment.id() + 1)?.is_none());
+
+    Ok(())
+}
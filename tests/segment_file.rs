@@ -0,0 +1,143 @@
+use lsm_tree::{AbstractTree, Config, SegmentFileReader, SegmentFileWriter, SequenceNumberCounter};
+use test_log::test;
+
+const ITEM_COUNT: usize = 10_000;
+
+#[test]
+fn segment_file_write_read() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let path = folder.path().join("data.sst");
+
+    let mut writer = SegmentFileWriter::new(&path)?.bloom_bits_per_key(5);
+    for x in 0..ITEM_COUNT {
+        writer.write(format!("{x:05}"), x.to_string())?;
+    }
+    assert_eq!(Some(path.clone()), writer.finish()?);
+
+    let reader = SegmentFileReader::open(&path)?;
+    assert_eq!(ITEM_COUNT as u64, reader.len());
+    assert_eq!(b"00000", &**reader.key_range().min());
+    assert_eq!(b"09999", &**reader.key_range().max());
+
+    assert_eq!(Some("1234".as_bytes().into()), reader.get("01234")?);
+    assert_eq!(None, reader.get("x")?);
+
+    let items = reader.iter().collect::<lsm_tree::Result<Vec<_>>>()?;
+    assert_eq!(ITEM_COUNT, items.len());
+    assert_eq!(b"00000", &*items.first().expect("should exist").0);
+
+    let (key, value) = reader.iter().next_back().expect("should exist")?;
+    assert_eq!(b"09999", &*key);
+    assert_eq!(b"9999", &*value);
+
+    Ok(())
+}
+
+#[test]
+fn segment_file_invalid() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let path = folder.path().join("data.sst");
+
+    let mut writer = SegmentFileWriter::new(&path)?;
+    writer.write("b", "abc")?;
+    assert!(matches!(
+        writer.write("a", "abc"),
+        Err(lsm_tree::Error::InvalidInput(_))
+    ));
+    assert!(matches!(
+        writer.write("b", "abc"),
+        Err(lsm_tree::Error::InvalidInput(_))
+    ));
+
+    let writer = SegmentFileWriter::new(&path)?;
+    assert_eq!(None, writer.finish()?);
+    assert!(!path.try_exists()?);
+
+    std::fs::write(&path, "not a segment")?;
+    assert!(SegmentFileReader::open(&path).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn segment_file_ingest() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let path = folder.path().join("data.sst");
+
+    let mut writer = SegmentFileWriter::new(&path)?;
+    for x in 0..ITEM_COUNT {
+        writer.write(format!("{x:05}"), "ingested")?;
+    }
+    writer.finish()?;
+
+    let file_size = std::fs::metadata(&path)?.len();
+    let tree_path = folder.path().join("tree");
+
+    {
+        let tree = Config::new(&tree_path).open()?;
+        let seqno = SequenceNumberCounter::default();
+
+        tree.insert("00000", "old", seqno.next());
+        tree.flush_active_memtable(0)?;
+
+        let segment = tree
+            .ingest_file(&path, seqno.next())?
+            .expect("should exist");
+        assert_eq!((1, 1), segment.metadata.seqnos);
+        assert_eq!(Some(1), segment.metadata.global_seqno);
+
+        // NOTE: The file is adopted, not rewritten
+        assert!(!path.try_exists()?);
+        assert_eq!(
+            file_size,
+            std::fs::metadata(tree_path.join("segments").join(segment.id().to_string()))?.len()
+        );
+
+        assert_eq!(ITEM_COUNT, tree.len(None, None)?);
+        assert_eq!(Some("ingested".as_bytes().into()), tree.get("00000", None)?);
+        assert_eq!(Some("old".as_bytes().into()), tree.get("00000", Some(1))?);
+        assert_eq!(1, tree.len(Some(1), None)?);
+    }
+
+    {
+        // NOTE: The global seqno is persisted in the segment file
+        let tree = Config::new(&tree_path).open()?;
+        assert_eq!(ITEM_COUNT, tree.len(None, None)?);
+        assert_eq!(Some("ingested".as_bytes().into()), tree.get("00000", None)?);
+        assert_eq!(Some("old".as_bytes().into()), tree.get("00000", Some(1))?);
+
+        // NOTE: Compacting rewrites the items with their global seqno
+        tree.major_compact(u64::MAX, 0)?;
+        assert_eq!(1, tree.segment_count());
+        assert_eq!(Some("ingested".as_bytes().into()), tree.get("00000", None)?);
+        assert_eq!(Some("old".as_bytes().into()), tree.get("00000", Some(1))?);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn segment_file_ingest_rejected() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let path = folder.path().join("data.sst");
+
+    let mut writer = SegmentFileWriter::new(&path)?;
+    writer.write("a", "ingested")?;
+    writer.finish()?;
+
+    let tree = Config::new(folder.path().join("tree")).open()?;
+    tree.insert("a", "old", 5);
+    tree.flush_active_memtable(0)?;
+
+    assert!(matches!(
+        tree.ingest_file(&path, 5),
+        Err(lsm_tree::Error::InvalidInput(_))
+    ));
+
+    // NOTE: The file is left in place, so ingesting can be retried
+    assert!(path.try_exists()?);
+    tree.ingest_file(&path, 6)?;
+    assert_eq!(Some("ingested".as_bytes().into()), tree.get("a", None)?);
+
+    Ok(())
+}
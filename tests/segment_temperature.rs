@@ -0,0 +1,83 @@
+use lsm_tree::{AbstractTree, Config, ManualClock, Temperature};
+use std::{sync::Arc, time::Duration};
+use test_log::test;
+
+#[test]
+fn segment_temperature_by_level() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    {
+        let tree = Config::new(&folder).open()?;
+
+        tree.insert("a", "abc", 0);
+        let segment = tree.flush_active_memtable(0)?.expect("should flush");
+        assert_eq!(Some(Temperature::Hot), segment.temperature());
+
+        // NOTE: Major compaction writes into the last level
+        tree.major_compact(u64::MAX, 0)?;
+        assert!(tree
+            .levels
+            .read()
+            .expect("lock is poisoned")
+            .iter()
+            .all(|segment| segment.temperature() == Some(Temperature::Cold)));
+    }
+
+    // NOTE: The temperature is persisted in the segment file
+    let tree = Config::new(&folder).open()?;
+    assert!(tree
+        .levels
+        .read()
+        .expect("lock is poisoned")
+        .iter()
+        .all(|segment| segment.temperature() == Some(Temperature::Cold)));
+
+    Ok(())
+}
+
+#[test]
+fn segment_temperature_by_age() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let clock = Arc::new(ManualClock::new(Duration::from_secs(1_000)));
+
+    let tree = Config::new(&folder)
+        .clock(clock.clone())
+        .cold_data_age(Duration::from_secs(60))
+        .open()?;
+
+    let strategy = Arc::new(lsm_tree::compaction::PullDown(0, 1));
+
+    tree.insert("a", "abc", 0);
+    tree.flush_active_memtable(0)?;
+    tree.compact(strategy.clone(), 0)?;
+
+    assert_eq!(1, tree.segment_count());
+    assert!(tree
+        .levels
+        .read()
+        .expect("lock is poisoned")
+        .iter()
+        .all(|segment| segment.temperature() == Some(Temperature::Hot)));
+
+    // NOTE: Sample the time of the next flush, so the first write is known to be old
+    clock.advance(Duration::from_secs(120));
+    tree.insert("b", "abc", 1);
+    tree.flush_active_memtable(0)?;
+    clock.advance(Duration::from_secs(120));
+
+    tree.compact(Arc::new(lsm_tree::compaction::PullDown(1, 2)), 0)?;
+
+    let temperatures = tree
+        .levels
+        .read()
+        .expect("lock is poisoned")
+        .iter()
+        .map(lsm_tree::Segment::temperature)
+        .collect::<Vec<_>>();
+
+    assert!(temperatures.contains(&Some(Temperature::Cold)));
+    assert!(temperatures.contains(&Some(Temperature::Hot)));
+
+    Ok(())
+}
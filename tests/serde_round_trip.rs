@@ -0,0 +1,90 @@
+#![cfg(feature = "serde")]
+
+use lsm_tree::{AbstractTree, BlockSizePolicy, Config, FilterType, Statistics};
+use std::sync::Arc;
+use test_log::test;
+
+#[test]
+fn serde_config_round_trip() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let config = Config::new(&folder)
+        .data_block_size(16 * 1_024)
+        .block_size_policy(BlockSizePolicy::Adaptive {
+            target_items: 32,
+            max_bytes: 64 * 1_024,
+        })
+        .compaction_filter_type(FilterType::Xor)
+        .level_count(5);
+
+    let json = serde_json::to_string(&config).expect("should serialize");
+    let copy: Config = serde_json::from_str(&json).expect("should deserialize");
+
+    assert_eq!(config.path, copy.path);
+    assert_eq!(16 * 1_024, copy.data_block_size);
+    assert_eq!(config.block_size_policy, copy.block_size_policy);
+    assert_eq!(FilterType::Xor, copy.compaction_filter_type);
+    assert_eq!(5, copy.level_count);
+
+    // NOTE: Missing options fall back to their defaults
+    let partial: Config =
+        serde_json::from_str(r#"{ "data_block_size": 8192 }"#).expect("should deserialize");
+    assert_eq!(8_192, partial.data_block_size);
+    assert_eq!(Config::default().index_block_size, partial.index_block_size);
+
+    let tree = copy.open()?;
+    tree.insert("a", "abc", 0);
+
+    Ok(())
+}
+
+#[test]
+fn serde_introspection_round_trip() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let stats = Arc::new(Statistics::default());
+    let tree = Config::new(&folder).statistics(stats.clone()).open()?;
+
+    tree.insert("a", "abc", 0);
+    tree.flush_active_memtable(0)?;
+    assert!(tree.get("a", None)?.is_some());
+
+    let usage = tree.memory_usage();
+    let json = serde_json::to_string(&usage).expect("should serialize");
+    assert_eq!(
+        usage,
+        serde_json::from_str(&json).expect("should deserialize")
+    );
+
+    let health = tree.health();
+    let json = serde_json::to_string(&health).expect("should serialize");
+    assert_eq!(
+        health,
+        serde_json::from_str(&json).expect("should deserialize")
+    );
+
+    let metadata = tree
+        .levels
+        .read()
+        .expect("lock is poisoned")
+        .iter()
+        .next()
+        .expect("segment should exist")
+        .metadata
+        .clone();
+    let json = serde_json::to_string(&metadata).expect("should serialize");
+    assert_eq!(
+        metadata,
+        serde_json::from_str(&json).expect("should deserialize")
+    );
+
+    let snapshot = stats.snapshot();
+    assert_eq!(1, snapshot.get_latency.count);
+    let json = serde_json::to_string(&snapshot).expect("should serialize");
+    assert_eq!(
+        snapshot,
+        serde_json::from_str(&json).expect("should deserialize")
+    );
+
+    Ok(())
+}
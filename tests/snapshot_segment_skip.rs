@@ -0,0 +1,66 @@
+use lsm_tree::{AbstractTree, Config, Statistics};
+use std::sync::Arc;
+use test_log::test;
+
+fn block_loads(stats: &Statistics) -> u64 {
+    stats.block_cache_hits() + stats.block_cache_misses()
+}
+
+fn snapshot_segment_skip(key_offset: u64) -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let stats = Arc::new(Statistics::default());
+    let tree = Config::new(&folder).statistics(stats.clone()).open()?;
+
+    for x in 0..100u64 {
+        tree.insert(x.to_be_bytes(), "old", x);
+    }
+    tree.flush_active_memtable(0)?;
+
+    let snapshot_seqno = 100;
+
+    for batch in 1..=3u64 {
+        for x in 0..100u64 {
+            let key = (batch * key_offset + x).to_be_bytes();
+            tree.insert(key, "new", batch * 100 + x);
+        }
+        tree.flush_active_memtable(0)?;
+    }
+
+    assert_eq!(4, tree.segment_count());
+
+    let before = block_loads(&stats);
+    assert_eq!(100, tree.iter(Some(snapshot_seqno), None).count());
+    let snapshot_loads = block_loads(&stats) - before;
+
+    let before = block_loads(&stats);
+    let _ = tree.iter(None, None).count();
+    let full_loads = block_loads(&stats) - before;
+
+    assert!(snapshot_loads < full_loads);
+
+    for (_, value) in tree
+        .iter(Some(snapshot_seqno), None)
+        .collect::<lsm_tree::Result<Vec<_>>>()?
+    {
+        assert_eq!(&*value, b"old");
+    }
+
+    let probed = stats.segments_probed();
+    assert!(tree
+        .get(0u64.to_be_bytes(), Some(snapshot_seqno))?
+        .is_some());
+    assert_eq!(1, stats.segments_probed() - probed);
+
+    Ok(())
+}
+
+#[test]
+fn snapshot_segment_skip_overlapping() -> lsm_tree::Result<()> {
+    snapshot_segment_skip(0)
+}
+
+#[test]
+fn snapshot_segment_skip_disjoint() -> lsm_tree::Result<()> {
+    snapshot_segment_skip(1_000)
+}
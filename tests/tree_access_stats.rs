@@ -0,0 +1,60 @@
+use lsm_tree::{AbstractTree, Config};
+use test_log::test;
+
+#[test]
+fn tree_segment_access_stats() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    {
+        let tree = Config::new(&folder).open()?;
+
+        tree.insert("a", "abc", 0);
+        tree.insert("c", "abc", 1);
+        let segment = tree.flush_active_memtable(0)?.expect("should flush");
+
+        let stats = segment.access_stats();
+        assert_eq!(segment.id(), stats.segment_id);
+        assert_eq!(0, stats.reads);
+        assert_eq!(None, stats.last_access);
+
+        assert!(tree.contains_key("a", None)?);
+        assert!(!tree.contains_key("b", None)?);
+
+        // NOTE: Outside of the key range
+        assert!(!tree.contains_key("d", None)?);
+
+        let stats = tree.segment_access_stats();
+        assert_eq!(1, stats.len());
+
+        let stats = stats.first().expect("should exist");
+        assert_eq!(2, stats.reads);
+        assert_eq!(1, stats.gets_served);
+        assert_eq!(1, stats.blocks_read);
+        assert_eq!(1, stats.bloom_negatives);
+        assert!(stats.last_access.is_some());
+
+        tree.persist_access_stats()?;
+        assert!(tree.contains_key("a", None)?);
+    }
+
+    {
+        let tree = Config::new(&folder).open()?;
+
+        // NOTE: Reads after persisting the counters are lost
+        let stats = tree.segment_access_stats();
+        let stats = stats.first().expect("should exist");
+        assert_eq!(2, stats.reads);
+        assert_eq!(1, stats.gets_served);
+
+        tree.major_compact(u64::MAX, 0)?;
+        assert_eq!(
+            0,
+            tree.segment_access_stats()
+                .first()
+                .expect("should exist")
+                .reads
+        );
+    }
+
+    Ok(())
+}
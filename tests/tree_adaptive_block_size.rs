@@ -0,0 +1,90 @@
+use lsm_tree::{AbstractTree, BlockSizePolicy, Config, Tree};
+use test_log::test;
+
+fn data_block_count(tree: &Tree) -> u32 {
+    tree.levels
+        .read()
+        .expect("lock is poisoned")
+        .iter()
+        .map(|segment| segment.metadata.data_block_count)
+        .sum()
+}
+
+#[test]
+fn tree_adaptive_block_size_tiny_values() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder)
+        .block_size_policy(BlockSizePolicy::Adaptive {
+            target_items: 16,
+            max_bytes: 64 * 1_024,
+        })
+        .open()?;
+
+    for x in 0..1_600u64 {
+        tree.insert(x.to_be_bytes(), "a", 0);
+    }
+    tree.flush_active_memtable(0)?;
+
+    assert_eq!(100, data_block_count(&tree));
+
+    for x in 0..1_600u64 {
+        assert!(tree.contains_key(x.to_be_bytes(), None)?);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn tree_adaptive_block_size_large_values() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let value = "a".repeat(5_000);
+
+    let fixed = Config::new(folder.path().join("fixed")).open()?;
+    let adaptive = Config::new(folder.path().join("adaptive"))
+        .block_size_policy(BlockSizePolicy::Adaptive {
+            target_items: 16,
+            max_bytes: 64 * 1_024,
+        })
+        .open()?;
+
+    for tree in [&fixed, &adaptive] {
+        for x in 0..100u64 {
+            tree.insert(x.to_be_bytes(), &value, 0);
+        }
+        tree.flush_active_memtable(0)?;
+    }
+
+    // NOTE: Every value fills a 4 KiB block on its own
+    assert_eq!(100, data_block_count(&fixed));
+    assert!(data_block_count(&adaptive) <= 10);
+
+    assert_eq!(100, adaptive.iter(None, None).count());
+
+    Ok(())
+}
+
+#[test]
+fn tree_adaptive_block_size_huge_value_starts_new_block() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder)
+        .block_size_policy(BlockSizePolicy::Adaptive {
+            target_items: 16,
+            max_bytes: 4_096,
+        })
+        .open()?;
+
+    tree.insert("a", "small", 0);
+    tree.insert("b", "x".repeat(100_000), 0);
+    tree.insert("c", "small", 0);
+    tree.flush_active_memtable(0)?;
+
+    assert_eq!(3, data_block_count(&tree));
+
+    assert_eq!(Some(100_000), tree.size_of("b", None)?);
+    assert_eq!(&*tree.get("c", None)?.expect("should exist"), b"small");
+
+    Ok(())
+}
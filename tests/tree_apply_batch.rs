@@ -0,0 +1,51 @@
+use lsm_tree::{AbstractTree, Config};
+use test_log::test;
+
+#[test]
+fn tree_apply_batch() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+
+    tree.insert("a", "old", 0);
+    tree.insert("b", "old", 0);
+
+    let (batch_size, memtable_size) = tree.apply_batch(
+        [
+            ("a", Some("new")),
+            ("b", None),
+            ("c", Some("new")),
+            ("c", Some("newer")),
+        ],
+        1,
+    )?;
+    assert!(batch_size > 0);
+    assert_eq!(tree.active_memtable_size(), memtable_size);
+
+    assert_eq!(Some("new".as_bytes().into()), tree.get("a", None)?);
+    assert_eq!(None, tree.get("b", None)?);
+    assert_eq!(Some("newer".as_bytes().into()), tree.get("c", None)?);
+
+    // NOTE: Reads below the batch seqno do not see any of it
+    assert_eq!(Some("old".as_bytes().into()), tree.get("a", Some(1))?);
+    assert_eq!(Some("old".as_bytes().into()), tree.get("b", Some(1))?);
+    assert_eq!(None, tree.get("c", Some(1))?);
+
+    tree.flush_active_memtable(0)?;
+    assert_eq!(2, tree.len(None, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_apply_batch_invalid() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+
+    let result = tree.apply_batch([("a", Some("abc")), ("", Some("abc"))], 0);
+    assert!(matches!(result, Err(lsm_tree::Error::InvalidInput(_))));
+
+    assert!(tree.is_empty(None, None)?);
+    assert_eq!(0, tree.active_memtable_size());
+
+    Ok(())
+}
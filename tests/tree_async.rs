@@ -0,0 +1,154 @@
+use lsm_tree::{AbstractTree, AsyncTree, BlockingTask, Config, Executor, ThreadExecutor};
+use std::{
+    future::Future,
+    ops::Bound,
+    sync::{
+        atomic::{AtomicUsize, Ordering::Relaxed},
+        mpsc, Arc, Mutex,
+    },
+    task::{Context, Poll, Wake, Waker},
+    thread::Thread,
+};
+use test_log::test;
+
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Minimal executor-agnostic `block_on`
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = std::pin::pin!(future);
+
+    let waker = Waker::from(Arc::new(ThreadWaker(std::thread::current())));
+    let mut cx = Context::from_waker(&waker);
+
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => std::thread::park(),
+        }
+    }
+}
+
+/// Runs all tasks on a single background thread
+struct SingleThreadPool {
+    sender: Mutex<mpsc::Sender<BlockingTask>>,
+    task_count: AtomicUsize,
+}
+
+impl SingleThreadPool {
+    fn new() -> Self {
+        let (sender, receiver) = mpsc::channel::<BlockingTask>();
+
+        std::thread::spawn(move || {
+            while let Ok(task) = receiver.recv() {
+                task();
+            }
+        });
+
+        Self {
+            sender: Mutex::new(sender),
+            task_count: AtomicUsize::default(),
+        }
+    }
+}
+
+impl Executor for SingleThreadPool {
+    fn spawn_blocking(&self, task: BlockingTask) {
+        self.task_count.fetch_add(1, Relaxed);
+
+        self.sender
+            .lock()
+            .expect("lock is poisoned")
+            .send(task)
+            .expect("pool should be running");
+    }
+}
+
+#[test]
+fn tree_async_operations() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = AsyncTree::new(Config::new(&folder).open()?, Arc::new(ThreadExecutor));
+
+    block_on(async {
+        for x in 0..100u64 {
+            tree.insert(x.to_be_bytes(), x.to_string(), x).await;
+        }
+        tree.remove(0u64.to_be_bytes(), 100).await;
+
+        assert!(tree.flush_active_memtable(0).await?.is_some());
+        assert!(tree.flush_active_memtable(0).await?.is_none());
+
+        assert_eq!(99, tree.len(None).await?);
+        assert!(tree.contains_key(1u64.to_be_bytes(), None).await?);
+        assert!(!tree.contains_key(0u64.to_be_bytes(), None).await?);
+        assert_eq!(
+            Some("5".as_bytes().into()),
+            tree.get(5u64.to_be_bytes(), None).await?
+        );
+
+        let items = tree
+            .range(
+                Bound::Included(10u64.to_be_bytes()),
+                Bound::Excluded(20u64.to_be_bytes()),
+                None,
+            )
+            .await?;
+        assert_eq!(10, items.len());
+
+        for x in 100..200u64 {
+            tree.insert(x.to_be_bytes(), x.to_string(), x).await;
+        }
+        tree.flush_active_memtable(0).await?;
+        assert_eq!(2, tree.inner().segment_count());
+
+        tree.major_compact(u64::MAX, 1_000).await?;
+        assert_eq!(1, tree.inner().segment_count());
+        assert_eq!(199, tree.len(None).await?);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn tree_async_custom_executor() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let pool = Arc::new(SingleThreadPool::new());
+    let tree = AsyncTree::new(Config::new(&folder).open_as_blob_tree()?, pool.clone());
+
+    block_on(async {
+        tree.insert("a", "abc".repeat(10_000), 0).await;
+        tree.flush_active_memtable(0).await?;
+
+        assert_eq!(
+            Some("abc".repeat(10_000).as_bytes().into()),
+            tree.get("a", None).await?
+        );
+
+        let len = tree.spawn(|tree| tree.blob_file_count()).await;
+        assert_eq!(1, len);
+
+        Ok::<_, lsm_tree::Error>(())
+    })?;
+
+    assert_eq!(4, pool.task_count.load(Relaxed));
+
+    Ok(())
+}
+
+#[test]
+#[should_panic = "async tree operation panicked"]
+fn tree_async_panic() {
+    let folder = tempfile::tempdir().expect("should create folder");
+    let tree = Config::new(&folder).open().expect("should open");
+
+    let tree = AsyncTree::new(tree, Arc::new(ThreadExecutor));
+
+    block_on(tree.spawn(|_| panic!("oops")));
+}
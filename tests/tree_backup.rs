@@ -0,0 +1,103 @@
+use lsm_tree::{AbstractTree, BackupEngine, Config, SequenceNumberCounter};
+use test_log::test;
+
+#[test]
+fn tree_backup_incremental() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let backup_folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    for batch in 0..3_u64 {
+        for x in 0..10_u64 {
+            tree.insert((batch * 10 + x).to_be_bytes(), "abc", seqno.next());
+        }
+        tree.flush_active_memtable(0)?;
+    }
+
+    let report = BackupEngine::create(&tree, &backup_folder)?;
+    assert_eq!(3, report.segment_count);
+    assert_eq!(3, report.copied_segments);
+    assert!(report.copied_bytes > 0);
+    assert_eq!(0, report.removed_segments);
+
+    // NOTE: Nothing changed, so nothing is copied
+    let report = BackupEngine::create(&tree, &backup_folder)?;
+    assert_eq!(3, report.segment_count);
+    assert_eq!(0, report.copied_segments);
+    assert_eq!(0, report.copied_bytes);
+
+    tree.insert("a", "abc", seqno.next());
+    tree.flush_active_memtable(0)?;
+
+    let report = BackupEngine::create(&tree, &backup_folder)?;
+    assert_eq!(4, report.segment_count);
+    assert_eq!(1, report.copied_segments);
+
+    tree.major_compact(u64::MAX, seqno.get())?;
+
+    let report = BackupEngine::create(&tree, &backup_folder)?;
+    assert_eq!(1, report.segment_count);
+    assert_eq!(1, report.copied_segments);
+    assert_eq!(4, report.removed_segments);
+
+    Ok(())
+}
+
+#[test]
+fn tree_backup_restore() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let backup_folder = tempfile::tempdir()?;
+    let restore_folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    for x in 0..100_u64 {
+        tree.insert(x.to_be_bytes(), "abc", seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+    tree.remove(5_u64.to_be_bytes(), seqno.next());
+    tree.flush_active_memtable(0)?;
+
+    BackupEngine::create(&tree, &backup_folder)?;
+
+    // NOTE: Not part of the backup, because it is not flushed
+    tree.insert("a", "abc", seqno.next());
+
+    BackupEngine::restore(&backup_folder, &restore_folder)?;
+    assert!(BackupEngine::restore(&backup_folder, &restore_folder).is_err());
+
+    let restored = Config::new(&restore_folder).open()?;
+    assert_eq!(2, restored.segment_count());
+    assert_eq!(99, restored.len(None, None)?);
+    assert!(!restored.contains_key(5_u64.to_be_bytes(), None)?);
+    assert!(!restored.contains_key("a", None)?);
+
+    // NOTE: New segments of the restored tree may not overwrite restored segments
+    restored.insert("b", "abc", seqno.next());
+    restored.flush_active_memtable(0)?;
+    assert_eq!(100, restored.len(None, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_backup_invalid() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let backup_folder = tempfile::tempdir()?;
+
+    assert!(matches!(
+        BackupEngine::restore(&backup_folder, folder.path().join("tree")),
+        Err(lsm_tree::Error::InvalidInput(_)),
+    ));
+
+    let tree = Config::new(&folder).open_as_blob_tree()?;
+    assert!(matches!(
+        BackupEngine::create(&tree.index, &backup_folder),
+        Err(lsm_tree::Error::InvalidInput(_)),
+    ));
+
+    Ok(())
+}
@@ -0,0 +1,257 @@
+use lsm_tree::{AbstractTree, BlockTransform, Config};
+use std::{
+    path::Path,
+    sync::{
+        atomic::{AtomicU32, Ordering::Relaxed},
+        Arc,
+    },
+};
+use test_log::test;
+
+const ITEM_COUNT: usize = 1_000;
+
+const MARKER: &[u8] = b"PLAINTEXT_MARKER";
+
+/// XORs every byte with the key ID, keys below `min_key_id` are retired
+struct XorTransform {
+    current_key_id: AtomicU32,
+    min_key_id: AtomicU32,
+}
+
+impl XorTransform {
+    fn new(key_id: u32) -> Self {
+        Self {
+            current_key_id: AtomicU32::new(key_id),
+            min_key_id: AtomicU32::new(0),
+        }
+    }
+
+    fn apply(&self, key_id: u32, data: &[u8]) -> lsm_tree::Result<Vec<u8>> {
+        if key_id < self.min_key_id.load(Relaxed) {
+            return Err(lsm_tree::Error::MissingBlockTransform(key_id));
+        }
+
+        #[allow(clippy::cast_possible_truncation)]
+        let mask = 0xA5 ^ key_id as u8;
+
+        Ok(data.iter().map(|byte| byte ^ mask).collect())
+    }
+}
+
+impl BlockTransform for XorTransform {
+    fn current_key_id(&self) -> u32 {
+        self.current_key_id.load(Relaxed)
+    }
+
+    fn encode(&self, key_id: u32, data: &[u8]) -> lsm_tree::Result<Vec<u8>> {
+        self.apply(key_id, data)
+    }
+
+    fn decode(&self, key_id: u32, data: &[u8]) -> lsm_tree::Result<Vec<u8>> {
+        self.apply(key_id, data)
+    }
+}
+
+fn folder_contains(folder: &Path, needle: &[u8]) -> lsm_tree::Result<bool> {
+    for entry in std::fs::read_dir(folder)? {
+        let path = entry?.path();
+
+        if path.is_dir() {
+            if folder_contains(&path, needle)? {
+                return Ok(true);
+            }
+        } else if std::fs::read(&path)?
+            .windows(needle.len())
+            .any(|window| window == needle)
+        {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+#[test]
+fn tree_block_transform_no_plaintext() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let transform = Arc::new(XorTransform::new(1));
+
+    {
+        let tree = Config::new(&folder)
+            .block_transform(transform.clone())
+            .open()?;
+
+        for x in 0..ITEM_COUNT as u64 {
+            tree.insert(x.to_be_bytes(), MARKER, 0);
+        }
+        tree.flush_active_memtable(0)?;
+
+        assert!(!folder_contains(folder.path(), MARKER)?);
+        assert!(tree.verify()?.is_ok());
+    }
+
+    let tree = Config::new(&folder).block_transform(transform).open()?;
+
+    assert_eq!(ITEM_COUNT, tree.len(None, None)?);
+    assert_eq!(
+        &*tree.get(5u64.to_be_bytes(), None)?.expect("should exist"),
+        MARKER
+    );
+
+    Ok(())
+}
+
+#[test]
+fn tree_block_transform_missing() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    {
+        let tree = Config::new(&folder)
+            .block_transform(Arc::new(XorTransform::new(7)))
+            .open()?;
+
+        tree.insert("a", MARKER, 0);
+        tree.flush_active_memtable(0)?;
+    }
+
+    assert!(matches!(
+        Config::new(&folder).open(),
+        Err(lsm_tree::Error::MissingBlockTransform(7))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn tree_block_transform_key_rotation() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let transform = Arc::new(XorTransform::new(1));
+
+    {
+        let tree = Config::new(&folder)
+            .block_transform(transform.clone())
+            .open()?;
+
+        for x in 0..ITEM_COUNT as u64 {
+            tree.insert(x.to_be_bytes(), MARKER, 0);
+        }
+        tree.flush_active_memtable(0)?;
+
+        transform.current_key_id.store(2, Relaxed);
+
+        for x in ITEM_COUNT as u64..2 * ITEM_COUNT as u64 {
+            tree.insert(x.to_be_bytes(), MARKER, 1);
+        }
+        tree.flush_active_memtable(0)?;
+
+        // NOTE: Old segments are still readable using the old key
+        assert_eq!(2, tree.segment_count());
+        assert_eq!(2 * ITEM_COUNT, tree.len(None, None)?);
+
+        // NOTE: Compaction rewrites everything using the current key
+        tree.major_compact(u64::MAX, 2)?;
+        assert_eq!(1, tree.segment_count());
+    }
+
+    // NOTE: The old key can now be retired
+    transform.min_key_id.store(2, Relaxed);
+
+    let tree = Config::new(&folder).block_transform(transform).open()?;
+    assert_eq!(2 * ITEM_COUNT, tree.len(None, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_block_transform() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let transform = Arc::new(XorTransform::new(3));
+    let big_value = MARKER.repeat(1_000);
+
+    {
+        let tree = Config::new(&folder)
+            .block_transform(transform.clone())
+            .open_as_blob_tree()?;
+
+        for x in 0..ITEM_COUNT as u64 {
+            tree.insert(x.to_be_bytes(), &big_value, 0);
+        }
+        tree.insert("small", MARKER, 0);
+        tree.flush_active_memtable(0)?;
+
+        assert!(tree.blobs.segment_count() > 0);
+        assert!(!folder_contains(folder.path(), MARKER)?);
+    }
+
+    let tree = Config::new(&folder)
+        .block_transform(transform)
+        .open_as_blob_tree()?;
+
+    assert_eq!(
+        &*tree.get(5u64.to_be_bytes(), None)?.expect("should exist"),
+        &*big_value
+    );
+    assert_eq!(&*tree.get("small", None)?.expect("should exist"), MARKER);
+    assert_eq!(ITEM_COUNT + 1, tree.len(None, None)?);
+
+    Ok(())
+}
+
+/// Prepends a random nonce, so encoding the same data twice gives different results
+struct NonceTransform;
+
+impl BlockTransform for NonceTransform {
+    fn current_key_id(&self) -> u32 {
+        1
+    }
+
+    fn encode(&self, key_id: u32, data: &[u8]) -> lsm_tree::Result<Vec<u8>> {
+        let nonce = rand::random::<u8>();
+        let mut bytes = vec![nonce];
+        bytes.extend(XorTransform::new(key_id).apply(key_id ^ u32::from(nonce), data)?);
+        Ok(bytes)
+    }
+
+    fn decode(&self, key_id: u32, data: &[u8]) -> lsm_tree::Result<Vec<u8>> {
+        let (&nonce, data) = data.split_first().expect("should have nonce");
+        XorTransform::new(key_id).apply(key_id ^ u32::from(nonce), data)
+    }
+}
+
+#[test]
+fn tree_block_transform_verify_ciphertext() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder)
+        .block_transform(Arc::new(NonceTransform))
+        .open()?;
+
+    for x in 0..ITEM_COUNT as u64 {
+        tree.insert(x.to_be_bytes(), MARKER, 0);
+    }
+    tree.flush_active_memtable(0)?;
+
+    assert!(tree.verify()?.is_ok());
+    assert!(tree.contains_key(5u64.to_be_bytes(), None)?);
+    assert!(!tree.contains_key(b"missing", None)?);
+
+    // NOTE: Corrupt the ciphertext of the first data block
+    let segment_path = std::fs::read_dir(folder.path().join("segments"))?
+        .next()
+        .expect("should have segment")?
+        .path();
+
+    let mut bytes = std::fs::read(&segment_path)?;
+    *bytes.get_mut(100).expect("should exist") ^= 0xFF;
+    std::fs::write(&segment_path, bytes)?;
+
+    let tree = Config::new(&folder)
+        .block_transform(Arc::new(NonceTransform))
+        .open()?;
+    assert!(!tree.verify()?.is_ok());
+
+    Ok(())
+}
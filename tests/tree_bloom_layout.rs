@@ -0,0 +1,37 @@
+use lsm_tree::{AbstractTree, BloomLayout, Config};
+use test_log::test;
+
+#[test]
+fn tree_blocked_bloom_filter() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?.into_path();
+
+    let tree = Config::new(&folder)
+        .bloom_layout(BloomLayout::Blocked)
+        .open()?;
+
+    for key in 0u64..1_000 {
+        tree.insert(key.to_be_bytes(), "abc", 0);
+    }
+    tree.flush_active_memtable(0)?;
+
+    // NOTE: Blocked filters are rounded up to the next cache line
+    assert_eq!(0, tree.bloom_filter_size() % 64);
+
+    for key in 0u64..1_000 {
+        assert!(tree.contains_key(key.to_be_bytes(), None)?);
+    }
+    assert!(!tree.contains_key(1_000u64.to_be_bytes(), None)?);
+
+    tree.major_compact(u64::MAX, 0)?;
+    assert_eq!(0, tree.bloom_filter_size() % 64);
+
+    drop(tree);
+
+    // NOTE: The layout is read from the segment, not the config
+    let tree = Config::new(&folder).open()?;
+    for key in 0u64..1_000 {
+        assert!(tree.contains_key(key.to_be_bytes(), None)?);
+    }
+
+    Ok(())
+}
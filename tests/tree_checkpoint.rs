@@ -0,0 +1,70 @@
+use lsm_tree::{AbstractTree, Config, SequenceNumberCounter};
+use test_log::test;
+
+#[test]
+fn tree_checkpoint() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let checkpoint_folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    for x in 0..100_u64 {
+        tree.insert(x.to_be_bytes(), "old", seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+
+    for x in 0..10_u64 {
+        tree.insert(x.to_be_bytes(), "new", seqno.next());
+    }
+    tree.remove(99_u64.to_be_bytes(), seqno.next());
+
+    tree.checkpoint(&checkpoint_folder)?;
+
+    // NOTE: The tree is not flushed by the checkpoint
+    assert_eq!(1, tree.segment_count());
+
+    tree.insert("later", "abc", seqno.next());
+
+    let checkpoint = Config::new(&checkpoint_folder).open()?;
+    assert_eq!(99, checkpoint.len(None, None)?);
+    assert_eq!(
+        Some("new".as_bytes().into()),
+        checkpoint.get(0_u64.to_be_bytes(), None)?,
+    );
+    assert_eq!(
+        Some("old".as_bytes().into()),
+        checkpoint.get(50_u64.to_be_bytes(), None)?,
+    );
+    assert!(!checkpoint.contains_key(99_u64.to_be_bytes(), None)?);
+    assert!(!checkpoint.contains_key("later", None)?);
+
+    // NOTE: The checkpoint is independent of the tree
+    checkpoint.insert("a", "abc", seqno.next());
+    assert!(!tree.contains_key("a", None)?);
+
+    assert!(tree.checkpoint(&checkpoint_folder).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_checkpoint() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let checkpoint_folder = tempfile::tempdir()?;
+    let big_value = b"neptune!".repeat(128_000);
+
+    let tree = Config::new(&folder).open_as_blob_tree()?;
+    tree.insert("a", &big_value, 0);
+    tree.flush_active_memtable(0)?;
+    tree.insert("b", &big_value, 1);
+
+    tree.checkpoint(&checkpoint_folder)?;
+    drop(tree);
+
+    let checkpoint = Config::new(&checkpoint_folder).open_as_blob_tree()?;
+    assert_eq!(Some(big_value.clone().into()), checkpoint.get("a", None)?);
+    assert_eq!(Some(big_value.into()), checkpoint.get("b", None)?);
+
+    Ok(())
+}
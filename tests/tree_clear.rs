@@ -0,0 +1,99 @@
+use lsm_tree::{AbstractTree, Config};
+use test_log::test;
+
+const ITEM_COUNT: u64 = 100;
+
+fn segment_file_count(folder: &tempfile::TempDir) -> std::io::Result<usize> {
+    Ok(std::fs::read_dir(folder.path().join("segments"))?.count())
+}
+
+#[test]
+fn tree_clear() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    {
+        let tree = Config::new(&folder).open()?;
+
+        for x in 0..ITEM_COUNT {
+            tree.insert(x.to_be_bytes(), x.to_string(), x);
+
+            if x % 10 == 9 {
+                tree.flush_active_memtable(0)?;
+            }
+        }
+        tree.insert("a", "a", ITEM_COUNT);
+
+        assert_eq!(10, tree.segment_count());
+
+        tree.clear()?;
+
+        assert!(tree.is_empty(None, None)?);
+        assert_eq!(0, tree.segment_count());
+        assert_eq!(0, segment_file_count(&folder)?);
+
+        tree.insert("b", "b", ITEM_COUNT + 1);
+        tree.flush_active_memtable(0)?;
+        assert_eq!(1, tree.segment_count());
+    }
+
+    let tree = Config::new(&folder).open()?;
+    assert_eq!(1, tree.len(None, None)?);
+    assert_eq!(1, segment_file_count(&folder)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_clear_during_flush() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open()?;
+    tree.insert("a", "a", 0);
+
+    let (segment_id, memtable) = tree
+        .rotate_memtable()
+        .expect("memtable should not be empty");
+
+    tree.clear()?;
+
+    // NOTE: The flush started before clearing, so its segment is discarded
+    let segment = tree
+        .flush_memtable(segment_id, &memtable, 0)?
+        .expect("segment should be written");
+    tree.register_segments(&[segment])?;
+
+    assert_eq!(0, tree.segment_count());
+    assert!(tree.is_empty(None, None)?);
+    assert_eq!(0, segment_file_count(&folder)?);
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_clear() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let big_value = b"neptune!".repeat(1_000);
+
+    {
+        let tree = Config::new(&folder).open_as_blob_tree()?;
+
+        tree.insert("big", &big_value, 0);
+        tree.insert("smol", "small value", 0);
+        tree.flush_active_memtable(0)?;
+
+        assert_eq!(1, tree.blob_file_count());
+
+        tree.clear()?;
+
+        assert!(tree.is_empty(None, None)?);
+        assert_eq!(0, tree.segment_count());
+        assert_eq!(0, tree.blob_file_count());
+    }
+
+    let tree = Config::new(&folder).open_as_blob_tree()?;
+    assert!(tree.is_empty(None, None)?);
+    assert_eq!(0, tree.blob_file_count());
+
+    Ok(())
+}
@@ -0,0 +1,104 @@
+use lsm_tree::{compaction::Leveled, AbstractTree, Config, SequenceNumberCounter};
+use std::sync::Arc;
+use test_log::test;
+
+const ITEM_COUNT: u64 = 10;
+
+fn write_segments<T: AbstractTree>(
+    tree: &T,
+    flush: impl Fn(&T) -> lsm_tree::Result<Option<lsm_tree::Segment>>,
+    seqno: &SequenceNumberCounter,
+    count: u64,
+) -> lsm_tree::Result<()> {
+    for _ in 0..count {
+        for x in 0..ITEM_COUNT {
+            tree.insert(x.to_be_bytes(), "a".repeat(10_000), seqno.next());
+        }
+        flush(tree)?;
+    }
+    Ok(())
+}
+
+#[test]
+fn tree_compaction_scheduler() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder)
+        .background_compaction(Arc::new(Leveled::default()), 2)
+        .open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    write_segments(&tree, |tree| tree.flush_active_memtable(0), &seqno, 10)?;
+
+    let scheduler = tree.compaction_scheduler().expect("should be enabled");
+    scheduler.wait_for_idle();
+
+    assert!(tree.first_level_segment_count() < 4);
+    assert_eq!(ITEM_COUNT as usize, tree.len(None, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_compaction_scheduler_pause() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder)
+        .background_compaction(Arc::new(Leveled::default()), 1)
+        .open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    let scheduler = tree.compaction_scheduler().expect("should be enabled");
+    scheduler.wait_for_idle();
+    scheduler.pause();
+    assert!(scheduler.is_paused());
+
+    write_segments(&tree, |tree| tree.flush_active_memtable(0), &seqno, 8)?;
+
+    scheduler.wait_for_idle();
+    assert_eq!(8, tree.first_level_segment_count());
+
+    scheduler.resume();
+    scheduler.wait_for_idle();
+    assert!(tree.first_level_segment_count() < 4);
+
+    Ok(())
+}
+
+#[test]
+fn tree_compaction_scheduler_disabled() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    assert!(tree.compaction_scheduler().is_none());
+
+    write_segments(&tree, |tree| tree.flush_active_memtable(0), &seqno, 8)?;
+    assert_eq!(8, tree.first_level_segment_count());
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_compaction_scheduler() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    {
+        let tree = Config::new(&folder).open_as_blob_tree()?;
+        write_segments(&tree, |tree| tree.flush_active_memtable(0), &seqno, 8)?;
+        assert_eq!(8, tree.first_level_segment_count());
+    }
+
+    // NOTE: Segments left over from before reopening are compacted as well
+    let tree = Config::new(&folder)
+        .background_compaction(Arc::new(Leveled::default()), 1)
+        .open_as_blob_tree()?;
+
+    tree.compaction_scheduler()
+        .expect("should be enabled")
+        .wait_for_idle();
+
+    assert!(tree.first_level_segment_count() < 4);
+    assert_eq!(ITEM_COUNT as usize, tree.len(None, None)?);
+
+    Ok(())
+}
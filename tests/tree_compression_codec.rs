@@ -0,0 +1,134 @@
+use lsm_tree::{
+    register_compression_codec, AbstractTree, CompressionCodec, CompressionType, Config,
+};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering::Relaxed},
+    Arc,
+};
+use test_log::test;
+
+const ITEM_COUNT: usize = 1_000;
+
+/// Stores every byte as the difference to the previous byte
+#[derive(Default)]
+struct DeltaCodec {
+    compress_count: AtomicUsize,
+}
+
+impl CompressionCodec for DeltaCodec {
+    fn compress(&self, data: &[u8]) -> lsm_tree::Result<Vec<u8>> {
+        self.compress_count.fetch_add(1, Relaxed);
+
+        let mut prev = 0u8;
+
+        Ok(data
+            .iter()
+            .map(|&byte| {
+                let delta = byte.wrapping_sub(prev);
+                prev = byte;
+                delta
+            })
+            .collect())
+    }
+
+    fn decompress(&self, data: &[u8]) -> lsm_tree::Result<Vec<u8>> {
+        let mut prev = 0u8;
+
+        Ok(data
+            .iter()
+            .map(|&delta| {
+                prev = prev.wrapping_add(delta);
+                prev
+            })
+            .collect())
+    }
+}
+
+#[test]
+fn tree_compression_codec_custom() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let codec = Arc::new(DeltaCodec::default());
+    register_compression_codec(101, codec.clone());
+
+    {
+        let tree = Config::new(&folder)
+            .compression(CompressionType::Custom(101))
+            .open()?;
+
+        for x in 0..ITEM_COUNT as u64 {
+            tree.insert(x.to_be_bytes(), x.to_string(), 0);
+        }
+        tree.flush_active_memtable(0)?;
+
+        assert!(codec.compress_count.load(Relaxed) > 0);
+        assert!(tree.verify()?.is_ok());
+
+        let segment = tree
+            .levels
+            .read()
+            .expect("lock is poisoned")
+            .iter()
+            .next()
+            .cloned()
+            .expect("segment should exist");
+        assert_eq!(CompressionType::Custom(101), segment.metadata.compression);
+    }
+
+    let tree = Config::new(&folder).open()?;
+
+    assert_eq!(ITEM_COUNT, tree.len(None, None)?);
+    assert_eq!(
+        &*tree.get(5u64.to_be_bytes(), None)?.expect("should exist"),
+        b"5"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn tree_compression_codec_unknown() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder)
+        .compression(CompressionType::Custom(102))
+        .open()?;
+
+    tree.insert("a", "abc", 0);
+
+    assert!(matches!(
+        tree.flush_active_memtable(0),
+        Err(lsm_tree::Error::UnknownCompressionCodec(102))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_compression_codec_custom() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let codec = Arc::new(DeltaCodec::default());
+    register_compression_codec(103, codec.clone());
+
+    let big_value = "abc".repeat(10_000);
+
+    let tree = Config::new(&folder)
+        .compression(CompressionType::Custom(103))
+        .blob_compression(CompressionType::Custom(103))
+        .open_as_blob_tree()?;
+
+    tree.insert("big", &big_value, 0);
+    tree.insert("small", "abc", 0);
+    tree.flush_active_memtable(0)?;
+
+    assert!(codec.compress_count.load(Relaxed) >= 2);
+
+    assert_eq!(
+        &*tree.get("big", None)?.expect("should exist"),
+        big_value.as_bytes()
+    );
+    assert_eq!(&*tree.get("small", None)?.expect("should exist"), b"abc");
+
+    Ok(())
+}
@@ -0,0 +1,161 @@
+use lsm_tree::{AbstractTree, Config, SequenceNumberCounter};
+use test_log::test;
+
+const ITEM_COUNT: u64 = 100;
+
+fn key(x: u64) -> [u8; 8] {
+    x.to_be_bytes()
+}
+
+fn cursor_key(item: Option<lsm_tree::KvPair>) -> Option<u64> {
+    item.map(|(key, _)| u64::from_be_bytes((*key).try_into().expect("should be u64")))
+}
+
+#[test]
+fn tree_cursor() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    // NOTE: Only even keys
+    for x in (0..ITEM_COUNT).step_by(2) {
+        tree.insert(key(x), "abc", seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+
+    for x in (0..ITEM_COUNT).step_by(4) {
+        tree.insert(key(x), "new", seqno.next());
+    }
+
+    let mut cursor = tree.cursor(None);
+    assert!(cursor.current().is_none());
+
+    assert_eq!(Some(0), cursor_key(cursor.next()?));
+    assert_eq!(Some(2), cursor_key(cursor.next()?));
+    assert_eq!(Some(0), cursor_key(cursor.prev()?));
+    assert_eq!(None, cursor_key(cursor.prev()?));
+    assert!(cursor.current().is_none());
+
+    assert_eq!(Some(12), cursor_key(cursor.seek(key(11))?));
+    assert_eq!(Some(12), cursor_key(cursor.seek(key(12))?));
+    assert_eq!(Some(14), cursor_key(cursor.next()?));
+    assert_eq!(Some(12), cursor_key(cursor.prev()?));
+    assert_eq!(Some(10), cursor_key(cursor.prev()?));
+    assert_eq!(Some(12), cursor_key(cursor.next()?));
+
+    assert_eq!(Some(50), cursor_key(cursor.seek_for_prev(key(51))?));
+    assert_eq!(Some(48), cursor_key(cursor.prev()?));
+    assert_eq!(Some(50), cursor_key(cursor.next()?));
+
+    // NOTE: Seeking backwards works as well
+    assert_eq!(Some(4), cursor_key(cursor.seek(key(3))?));
+
+    assert_eq!(Some(98), cursor_key(cursor.seek(key(97))?));
+    assert_eq!(None, cursor_key(cursor.next()?));
+    assert_eq!(Some(98), cursor_key(cursor.prev()?));
+
+    assert_eq!(None, cursor_key(cursor.seek(key(ITEM_COUNT))?));
+    assert_eq!(None, cursor_key(cursor.seek_for_prev([])?));
+
+    Ok(())
+}
+
+#[test]
+fn tree_cursor_seqno() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open_as_blob_tree()?;
+    let seqno = SequenceNumberCounter::default();
+
+    for x in 0..ITEM_COUNT {
+        tree.insert(key(x), "a".repeat(10_000), seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+
+    let mut cursor = tree.cursor(Some(seqno.get()));
+
+    assert_eq!(Some(10), cursor_key(cursor.seek(key(10))?));
+
+    // NOTE: The cursor reads at a fixed seqno, so it does not see newer writes
+    tree.remove(key(11), seqno.next());
+    tree.insert(key(ITEM_COUNT), "abc", seqno.next());
+
+    assert_eq!(Some(11), cursor_key(cursor.next()?));
+    assert_eq!(None, cursor_key(cursor.seek(key(ITEM_COUNT))?));
+    assert_eq!(Some(99), cursor_key(cursor.prev()?));
+
+    let (_, value) = cursor.seek(key(50))?.expect("should exist");
+    assert_eq!(10_000, value.len());
+
+    Ok(())
+}
+
+#[test]
+fn tree_cursor_short_seeks() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    // NOTE: Only even keys
+    for x in (0..ITEM_COUNT).step_by(2) {
+        tree.insert(key(x), "abc", seqno.next());
+    }
+
+    let mut cursor = tree.cursor(None);
+
+    // NOTE: The cursor pins the seqno it was created at
+    tree.insert(key(25), "abc", seqno.next());
+    tree.remove(key(26), seqno.next());
+
+    assert_eq!(Some(20), cursor_key(cursor.seek(key(20))?));
+    assert_eq!(Some(24), cursor_key(cursor.seek(key(23))?));
+    assert_eq!(Some(26), cursor_key(cursor.seek(key(25))?));
+    assert_eq!(Some(26), cursor_key(cursor.seek(key(26))?));
+    assert_eq!(Some(90), cursor_key(cursor.seek(key(89))?));
+    assert_eq!(Some(92), cursor_key(cursor.next()?));
+
+    assert_eq!(Some(88), cursor_key(cursor.seek_for_prev(key(89))?));
+    assert_eq!(Some(84), cursor_key(cursor.seek_for_prev(key(85))?));
+    assert_eq!(Some(26), cursor_key(cursor.seek_for_prev(key(27))?));
+    assert_eq!(Some(24), cursor_key(cursor.prev()?));
+
+    assert_eq!(None, cursor_key(cursor.seek(key(ITEM_COUNT))?));
+    assert_eq!(Some(98), cursor_key(cursor.prev()?));
+
+    drop(cursor);
+
+    let mut cursor = tree.cursor(None);
+    assert_eq!(Some(25), cursor_key(cursor.seek(key(25))?));
+    assert_eq!(Some(28), cursor_key(cursor.next()?));
+
+    Ok(())
+}
+
+#[test]
+fn tree_cursor_wraps_around() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+
+    for x in 0..3 {
+        tree.insert(key(x), "abc", x);
+    }
+
+    let mut cursor = tree.cursor(None);
+
+    assert_eq!(Some(1), cursor_key(cursor.seek(key(1))?));
+    assert_eq!(Some(2), cursor_key(cursor.next()?));
+    assert_eq!(None, cursor_key(cursor.next()?));
+
+    // NOTE: After moving past the end, the cursor starts over
+    assert_eq!(Some(0), cursor_key(cursor.next()?));
+
+    assert_eq!(None, cursor_key(cursor.prev()?));
+    assert_eq!(Some(2), cursor_key(cursor.prev()?));
+
+    assert_eq!(None, cursor_key(cursor.seek(key(3))?));
+    assert_eq!(Some(0), cursor_key(cursor.next()?));
+
+    assert_eq!(None, cursor_key(cursor.seek_for_prev([])?));
+    assert_eq!(Some(2), cursor_key(cursor.prev()?));
+
+    Ok(())
+}
@@ -0,0 +1,153 @@
+use lsm_tree::{AbstractTree, Config, SequenceNumberCounter};
+use test_log::test;
+
+const TENANT_COUNT: u64 = 5;
+const ITEM_COUNT: u64 = 100;
+
+fn key(tenant: u64, x: u64) -> Vec<u8> {
+    let mut key = tenant.to_be_bytes().to_vec();
+    key.extend_from_slice(&x.to_be_bytes());
+    key
+}
+
+#[test]
+fn tree_drop_prefix() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    {
+        let tree = Config::new(&folder).partition_prefix_len(8).open()?;
+
+        for tenant in 0..TENANT_COUNT {
+            for x in 0..ITEM_COUNT {
+                tree.insert(key(tenant, x), "abc", seqno.next());
+            }
+        }
+        tree.flush_active_memtable(0)?;
+        assert_eq!(1, tree.segment_count());
+
+        tree.major_compact(u64::MAX, seqno.get())?;
+        assert_eq!(TENANT_COUNT as usize, tree.segment_count());
+
+        // NOTE: Not flushed yet, so needs a tombstone
+        tree.insert(key(1, ITEM_COUNT), "abc", seqno.next());
+
+        assert_eq!(1, tree.drop_prefix(1u64.to_be_bytes(), seqno.next())?);
+        assert_eq!(TENANT_COUNT as usize - 1, tree.segment_count());
+        assert_eq!(0, tree.prefix(1u64.to_be_bytes(), None, None).count());
+        assert_eq!(
+            ((TENANT_COUNT - 1) * ITEM_COUNT) as usize,
+            tree.len(None, None)?
+        );
+    }
+
+    let tree = Config::new(&folder).open()?;
+    assert_eq!(TENANT_COUNT as usize - 1, tree.segment_count());
+    assert_eq!(0, tree.prefix(1u64.to_be_bytes(), None, None).count());
+    assert_eq!(
+        ((TENANT_COUNT - 1) * ITEM_COUNT) as usize,
+        tree.len(None, None)?
+    );
+
+    Ok(())
+}
+
+#[test]
+fn tree_drop_prefix_spanning_segment() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    let tree = Config::new(&folder).open()?;
+
+    for tenant in 0..TENANT_COUNT {
+        for x in 0..ITEM_COUNT {
+            tree.insert(key(tenant, x), "abc", seqno.next());
+        }
+    }
+    tree.flush_active_memtable(0)?;
+
+    // NOTE: Without partitioning, the segment spans all tenants
+    assert_eq!(0, tree.drop_prefix(1u64.to_be_bytes(), seqno.next())?);
+    assert_eq!(1, tree.segment_count());
+    assert_eq!(0, tree.prefix(1u64.to_be_bytes(), None, None).count());
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_drop_prefix() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    let tree = Config::new(&folder)
+        .partition_prefix_len(8)
+        .open_as_blob_tree()?;
+
+    for tenant in 0..2 {
+        for x in 0..ITEM_COUNT {
+            tree.insert(key(tenant, x), "neptune".repeat(1_000), seqno.next());
+        }
+        tree.flush_active_memtable(0)?;
+    }
+    assert_eq!(2, tree.blob_file_count());
+
+    assert_eq!(1, tree.drop_prefix(1u64.to_be_bytes(), seqno.next())?);
+    assert_eq!(ITEM_COUNT as usize, tree.len(None, None)?);
+
+    tree.gc_scan_stats(seqno.get(), seqno.get())?;
+    tree.gc_drop_stale()?;
+    assert_eq!(1, tree.blob_file_count());
+
+    Ok(())
+}
+
+#[test]
+fn tree_drop_prefix_snapshot() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    let tree = Config::new(&folder).partition_prefix_len(8).open()?;
+
+    for tenant in 0..2 {
+        for x in 0..ITEM_COUNT {
+            tree.insert(key(tenant, x), "abc", seqno.next());
+        }
+        tree.flush_active_memtable(0)?;
+    }
+    assert_eq!(2, tree.segment_count());
+
+    // NOTE: The snapshot does not see the range tombstone, so the segment is kept
+    let snapshot = tree.snapshot(seqno.get());
+    assert_eq!(0, tree.drop_prefix(1u64.to_be_bytes(), seqno.next())?);
+    assert_eq!(2, tree.segment_count());
+
+    assert_eq!(0, tree.prefix(1u64.to_be_bytes(), None, None).count());
+    assert_eq!(ITEM_COUNT as usize, tree.len(None, None)?);
+    assert_eq!(2 * ITEM_COUNT as usize, snapshot.len()?);
+    drop(snapshot);
+
+    // NOTE: Once the snapshot is gone, the segment can be dropped
+    assert_eq!(1, tree.drop_prefix(1u64.to_be_bytes(), seqno.next())?);
+    assert_eq!(1, tree.segment_count());
+    assert_eq!(ITEM_COUNT as usize, tree.len(None, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_drop_prefix_unbounded() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    let tree = Config::new(&folder).open()?;
+
+    tree.insert([0xFE, 0], "abc", seqno.next());
+    tree.insert([0xFF], "abc", seqno.next());
+    tree.insert([0xFF, 0xFF, 0xFF], "abc", seqno.next());
+
+    assert_eq!(0, tree.drop_prefix([0xFF], seqno.next())?);
+    assert_eq!(1, tree.len(None, None)?);
+    assert!(tree.contains_key([0xFE, 0], None)?);
+
+    Ok(())
+}
@@ -0,0 +1,81 @@
+use lsm_tree::{AnyTree, Config, DynTree, TreeType, UserKey};
+use std::{ops::Bound, sync::Arc};
+use test_log::test;
+
+fn exercise(tree: &Arc<dyn DynTree>) -> lsm_tree::Result<()> {
+    for (idx, key) in ["a", "b", "c", "d"].into_iter().enumerate() {
+        tree.insert(key.into(), "neptune".repeat(1_000).into(), idx as u64);
+    }
+    tree.remove("b".into(), 4);
+
+    let snapshot = tree.snapshot(5);
+    tree.insert("e".into(), "abc".into(), 5);
+
+    assert_eq!(3, snapshot.len()?);
+    assert_eq!(4, tree.len(None)?);
+    assert!(tree.contains_key(b"a", None)?);
+    assert!(!tree.contains_key(b"b", None)?);
+    assert_eq!(Some(7_000), tree.size_of(b"c", None)?);
+
+    tree.flush_active_memtable(0)?;
+    assert_eq!(1, tree.segment_count());
+
+    let keys = tree
+        .range(
+            (Bound::Excluded("a".into()), Bound::Included("d".into())),
+            None,
+        )
+        .map(|item| item.map(|(key, _)| key))
+        .collect::<lsm_tree::Result<Vec<_>>>()?;
+    assert_eq!(vec![UserKey::from("c"), UserKey::from("d")], keys);
+
+    assert_eq!(1, tree.prefix(b"e", None).count());
+    assert_eq!(&*tree.last_key_value(None)?.unwrap().0, b"e");
+
+    tree.insert("f".into(), "abc".into(), 6);
+    tree.flush_active_memtable(0)?;
+    assert_eq!(2, tree.segment_count());
+
+    tree.major_compact(u64::MAX, 0)?;
+    assert_eq!(1, tree.segment_count());
+
+    assert_eq!(Some(6), tree.get_highest_seqno());
+    assert_eq!(&*tree.get(b"f", None)?.unwrap(), b"abc");
+    assert_eq!(3, snapshot.len()?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_dyn_standard() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree: Arc<dyn DynTree> = Arc::new(Config::new(&folder).open()?);
+    assert_eq!(TreeType::Standard, tree.tree_type());
+
+    exercise(&tree)
+}
+
+#[test]
+fn tree_dyn_blob() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree: Arc<dyn DynTree> = Arc::new(
+        Config::new(&folder)
+            .blob_file_separation_threshold(1)
+            .open_as_blob_tree()?,
+    );
+    assert_eq!(TreeType::Blob, tree.tree_type());
+
+    exercise(&tree)
+}
+
+#[test]
+fn tree_dyn_any_tree() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree: AnyTree = Config::new(&folder).open_as_blob_tree()?.into();
+    let tree: Arc<dyn DynTree> = Arc::new(tree);
+
+    exercise(&tree)
+}
@@ -0,0 +1,207 @@
+#![cfg(feature = "failpoints")]
+
+use lsm_tree::{
+    fail::{self, points, FailAction},
+    AbstractTree, Config,
+};
+use std::{
+    path::Path,
+    sync::{Arc, Mutex, MutexGuard},
+};
+use test_log::test;
+
+const ITEM_COUNT: usize = 100;
+
+/// Failpoints are process-wide, so tests need to run one at a time
+static SERIAL: Mutex<()> = Mutex::new(());
+
+fn serial() -> MutexGuard<'static, ()> {
+    let guard = SERIAL
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    fail::clear();
+    guard
+}
+
+fn copy_dir(from: &Path, to: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(to)?;
+
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let dest = to.join(entry.file_name());
+
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &dest)?;
+        } else {
+            std::fs::copy(entry.path(), dest)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn segment_file_count(path: &Path) -> std::io::Result<usize> {
+    Ok(std::fs::read_dir(path.join("segments"))?.count())
+}
+
+#[test]
+fn tree_failpoint_segment_before_fsync() -> lsm_tree::Result<()> {
+    let _guard = serial();
+    let folder = tempfile::tempdir()?;
+
+    {
+        let tree = Config::new(&folder).open()?;
+
+        for x in 0..ITEM_COUNT as u64 {
+            tree.insert(x.to_be_bytes(), "abc", x);
+        }
+        tree.remove(0u64.to_be_bytes(), ITEM_COUNT as u64);
+
+        fail::configure(points::SEGMENT_BEFORE_FSYNC, FailAction::Error);
+        assert!(matches!(
+            tree.flush_active_memtable(0),
+            Err(lsm_tree::Error::Io(_))
+        ));
+        fail::clear();
+
+        assert_eq!(0, tree.segment_count());
+        assert_eq!(1, segment_file_count(folder.path())?);
+    }
+
+    // NOTE: The unfinished segment is cleaned up
+    let tree = Config::new(&folder).open()?;
+    assert_eq!(0, tree.segment_count());
+    assert_eq!(0, segment_file_count(folder.path())?);
+    assert!(tree.is_empty(None, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_failpoint_manifest_before_swap() -> lsm_tree::Result<()> {
+    let _guard = serial();
+    let folder = tempfile::tempdir()?;
+
+    {
+        let tree = Config::new(&folder).open()?;
+
+        for x in 0..ITEM_COUNT as u64 {
+            tree.insert(x.to_be_bytes(), "abc", x);
+            tree.flush_active_memtable(0)?;
+        }
+        assert_eq!(ITEM_COUNT, tree.segment_count());
+
+        fail::configure(points::MANIFEST_BEFORE_SWAP, FailAction::Error);
+        assert!(tree.major_compact(u64::MAX, 0).is_err());
+        fail::clear();
+
+        // NOTE: The failed compaction did not change the tree
+        assert_eq!(ITEM_COUNT, tree.segment_count());
+        assert_eq!(ITEM_COUNT, tree.len(None, None)?);
+        assert_eq!(ITEM_COUNT + 1, segment_file_count(folder.path())?);
+    }
+
+    let tree = Config::new(&folder).open()?;
+    assert_eq!(ITEM_COUNT, tree.segment_count());
+    assert_eq!(ITEM_COUNT, segment_file_count(folder.path())?);
+    assert_eq!(ITEM_COUNT, tree.len(None, None)?);
+
+    tree.major_compact(u64::MAX, 0)?;
+    assert_eq!(1, tree.segment_count());
+
+    Ok(())
+}
+
+#[test]
+fn tree_failpoint_crash_snapshot() -> lsm_tree::Result<()> {
+    let _guard = serial();
+    let folder = tempfile::tempdir()?;
+    let crashed = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open()?;
+
+    tree.insert("a", "old", 0);
+    tree.flush_active_memtable(0)?;
+
+    // NOTE: Capture the folder as it would look after a crash
+    // between writing the segment and updating the manifest
+    fail::configure(points::MANIFEST_BEFORE_SWAP, {
+        let source = folder.path().to_path_buf();
+        let dest = crashed.path().join("tree");
+
+        FailAction::Call(Arc::new(move || {
+            copy_dir(&source, &dest).expect("should copy");
+        }))
+    });
+
+    tree.insert("a", "new", 1);
+    tree.flush_active_memtable(0)?;
+    fail::clear();
+
+    assert_eq!(&*tree.get("a", None)?.expect("should exist"), b"new");
+
+    let recovered = Config::new(crashed.path().join("tree")).open()?;
+    assert_eq!(1, recovered.segment_count());
+    assert_eq!(1, segment_file_count(&crashed.path().join("tree"))?);
+    assert_eq!(&*recovered.get("a", None)?.expect("should exist"), b"old");
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_failpoint_after_blob_register() -> lsm_tree::Result<()> {
+    let _guard = serial();
+    let folder = tempfile::tempdir()?;
+
+    let big_value = "abc".repeat(10_000);
+
+    {
+        let tree = Config::new(&folder).open_as_blob_tree()?;
+
+        tree.insert("a", &big_value, 0);
+        tree.flush_active_memtable(0)?;
+
+        tree.insert("b", &big_value, 1);
+
+        fail::configure(points::BLOB_AFTER_REGISTER, FailAction::Error);
+        assert!(tree.flush_active_memtable(0).is_err());
+        fail::clear();
+
+        assert_eq!(1, tree.index.segment_count());
+    }
+
+    let tree = Config::new(&folder).open_as_blob_tree()?;
+    assert_eq!(1, tree.len(None, None)?);
+    assert_eq!(
+        &*tree.get("a", None)?.expect("should exist"),
+        big_value.as_bytes()
+    );
+    assert!(!tree.contains_key("b", None)?);
+
+    Ok(())
+}
+
+#[test]
+#[should_panic = "failpoint segment::before_fsync"]
+fn tree_failpoint_panic() {
+    let _guard = serial();
+    let folder = tempfile::tempdir().expect("should create folder");
+
+    let tree = Config::new(&folder).open().expect("should open");
+    tree.insert("a", "abc", 0);
+
+    fail::configure(points::SEGMENT_BEFORE_FSYNC, FailAction::Panic);
+
+    // NOTE: Disarm while unwinding, so other tests are not affected
+    struct Disarm;
+
+    impl Drop for Disarm {
+        fn drop(&mut self) {
+            fail::clear();
+        }
+    }
+
+    let _disarm = Disarm;
+
+    let _ = tree.flush_active_memtable(0);
+}
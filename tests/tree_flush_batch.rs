@@ -0,0 +1,69 @@
+use lsm_tree::{AbstractTree, Config, SequenceNumberCounter};
+use test_log::test;
+
+fn fill_and_flush<T: AbstractTree>(tree: &T) -> lsm_tree::Result<()> {
+    let seqno = SequenceNumberCounter::default();
+
+    let mut memtables = vec![];
+
+    for batch in 0..4u64 {
+        for x in 0..100u64 {
+            let key = (batch * 100 + x).to_be_bytes();
+            tree.insert(key, "a".repeat(2_000), seqno.next());
+        }
+
+        memtables.push(tree.rotate_memtable().expect("should rotate"));
+    }
+
+    assert_eq!(4, tree.sealed_memtable_count());
+
+    let segments = tree.flush_memtables(&memtables, 0)?;
+    assert_eq!(4, segments.len());
+
+    assert_eq!(0, tree.sealed_memtable_count());
+    assert_eq!(4, tree.segment_count());
+    assert_eq!(400, tree.len(None, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_flush_batch() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    {
+        let tree = Config::new(&folder).open()?;
+        fill_and_flush(&tree)?;
+    }
+
+    {
+        let tree = Config::new(&folder).open()?;
+        assert_eq!(4, tree.segment_count());
+        assert_eq!(400, tree.len(None, None)?);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_flush_batch() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    {
+        let tree = Config::new(&folder)
+            .blob_file_separation_threshold(1_000)
+            .open_as_blob_tree()?;
+        fill_and_flush(&tree)?;
+        assert_eq!(4, tree.blob_file_count());
+    }
+
+    {
+        let tree = Config::new(&folder)
+            .blob_file_separation_threshold(1_000)
+            .open_as_blob_tree()?;
+        assert_eq!(4, tree.segment_count());
+        assert_eq!(400, tree.len(None, None)?);
+    }
+
+    Ok(())
+}
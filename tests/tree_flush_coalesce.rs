@@ -0,0 +1,97 @@
+use lsm_tree::{AbstractTree, Config};
+use test_log::test;
+
+#[test]
+fn tree_flush_coalesce() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open()?;
+
+    let mut memtables = vec![];
+
+    tree.insert("a", "old", 0);
+    tree.insert("b", "abc", 1);
+    memtables.extend(tree.rotate_memtable());
+
+    tree.insert("a", "new", 2);
+    tree.remove("b", 3);
+    memtables.extend(tree.rotate_memtable());
+
+    tree.insert("c", "abc", 4);
+    memtables.extend(tree.rotate_memtable());
+
+    assert_eq!(3, tree.sealed_memtable_count());
+
+    let segment = tree
+        .flush_memtables_coalesced(&memtables, 0)?
+        .expect("should flush");
+
+    assert_eq!(memtables.last().map(|(id, _)| *id), Some(segment.id()));
+    assert_eq!(1, tree.segment_count());
+    assert_eq!(0, tree.sealed_memtable_count());
+
+    assert_eq!(Some("new".as_bytes().into()), tree.get("a", None)?);
+    assert!(!tree.contains_key("b", None)?);
+    assert!(tree.contains_key("c", None)?);
+
+    // NOTE: Older versions are kept
+    assert_eq!(Some("old".as_bytes().into()), tree.get("a", Some(2))?);
+    assert!(tree.contains_key("b", Some(3))?);
+
+    drop(tree);
+
+    let tree = Config::new(&folder).open()?;
+    assert_eq!(1, tree.segment_count());
+    assert_eq!(2, tree.len(None, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_flush_coalesce_evicted() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open()?;
+    assert!(tree.flush_memtables_coalesced(&[], 0)?.is_none());
+
+    let mut memtables = vec![];
+
+    tree.insert("a", "abc", 0);
+    memtables.extend(tree.rotate_memtable());
+
+    tree.insert("a", "def", 1);
+    memtables.extend(tree.rotate_memtable());
+
+    tree.flush_memtables_coalesced(&memtables, 2)?;
+    assert_eq!(0, tree.sealed_memtable_count());
+    assert_eq!(1, tree.approximate_len());
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_flush_coalesce() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let big_value = "abc".repeat(10_000);
+
+    let tree = Config::new(&folder).open_as_blob_tree()?;
+
+    let mut memtables = vec![];
+
+    for seqno in 0..4 {
+        tree.insert(seqno.to_string(), &big_value, seqno);
+        memtables.extend(tree.rotate_memtable());
+    }
+
+    tree.flush_memtables_coalesced(&memtables, 0)?;
+
+    assert_eq!(1, tree.segment_count());
+    assert_eq!(1, tree.blob_file_count());
+    assert_eq!(4, tree.len(None, None)?);
+
+    // NOTE: GC scans do not wait for the coalesced segment forever
+    tree.gc_scan_stats(4, 0)?;
+
+    Ok(())
+}
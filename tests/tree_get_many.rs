@@ -0,0 +1,134 @@
+use lsm_tree::{AbstractTree, Config, SequenceNumberCounter};
+use test_log::test;
+
+const ITEM_COUNT: u64 = 100;
+
+fn key(x: u64) -> [u8; 8] {
+    x.to_be_bytes()
+}
+
+#[test]
+fn tree_get_many() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    // NOTE: Spread the versions over segments, sealed memtables and the active memtable
+    for x in 0..ITEM_COUNT {
+        tree.insert(key(x), "old", seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+
+    for x in (0..ITEM_COUNT).step_by(2) {
+        tree.insert(key(x), "new", seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+
+    let snapshot_seqno = seqno.get();
+
+    for x in (0..ITEM_COUNT).step_by(3) {
+        tree.insert(key(x), "sealed", seqno.next());
+    }
+    tree.rotate_memtable();
+
+    for x in (0..ITEM_COUNT).step_by(5) {
+        tree.remove(key(x), seqno.next());
+    }
+    tree.remove_range(key(90)..key(95), seqno.next());
+
+    let keys = (0..ITEM_COUNT + 10).rev().map(key).collect::<Vec<_>>();
+
+    let items = tree.get_many(&keys, None)?;
+    assert_eq!(keys.len(), items.len());
+
+    for (key, item) in keys.iter().zip(&items) {
+        assert_eq!(tree.get(key, None)?, *item);
+    }
+
+    let items = tree.get_many(&keys, Some(snapshot_seqno))?;
+
+    for (key, item) in keys.iter().zip(&items) {
+        assert_eq!(tree.get(key, Some(snapshot_seqno))?, *item);
+    }
+
+    // NOTE: Duplicate keys are fine
+    let items = tree.get_many([key(1), key(1), key(2)], None)?;
+    assert_eq!(
+        vec![
+            Some("old".as_bytes().into()),
+            Some("old".as_bytes().into()),
+            Some("new".as_bytes().into())
+        ],
+        items,
+    );
+
+    assert!(tree.get_many(Vec::<&[u8]>::new(), None)?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn tree_get_many_compacted() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    for batch in 0..4 {
+        for x in (batch..ITEM_COUNT).step_by(4) {
+            tree.insert(key(x), x.to_string(), seqno.next());
+        }
+        tree.flush_active_memtable(0)?;
+    }
+    tree.compact(
+        std::sync::Arc::new(lsm_tree::compaction::SizeTiered::new(2, 4)),
+        seqno.get(),
+    )?;
+
+    let keys = (0..ITEM_COUNT).map(key).collect::<Vec<_>>();
+    let items = tree.get_many(&keys, None)?;
+
+    for (x, item) in items.into_iter().enumerate() {
+        assert_eq!(Some(x.to_string().as_bytes().into()), item);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_get_many() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open_as_blob_tree()?;
+    let seqno = SequenceNumberCounter::default();
+
+    let big_value = "a".repeat(10_000);
+
+    for x in 0..ITEM_COUNT {
+        if x % 2 == 0 {
+            tree.insert(key(x), &big_value, seqno.next());
+        } else {
+            tree.insert(key(x), "small", seqno.next());
+        }
+    }
+    tree.flush_active_memtable(0)?;
+    tree.remove(key(4), seqno.next());
+
+    let keys = (0..ITEM_COUNT + 10).rev().map(key).collect::<Vec<_>>();
+    let items = tree.get_many(&keys, None)?;
+
+    for (key, item) in keys.iter().zip(&items) {
+        assert_eq!(tree.get(key, None)?, *item);
+    }
+
+    let items = tree.get_many([key(2), key(2), key(3), key(4)], None)?;
+    assert_eq!(
+        vec![
+            Some(big_value.as_bytes().into()),
+            Some(big_value.as_bytes().into()),
+            Some("small".as_bytes().into()),
+            None,
+        ],
+        items,
+    );
+
+    Ok(())
+}
@@ -0,0 +1,152 @@
+use lsm_tree::{AbstractTree, Config, StallState};
+use test_log::test;
+
+#[test]
+fn tree_health() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?.into_path();
+
+    let tree = Config::new(folder)
+        .l0_slowdown_threshold(2)
+        .l0_stop_threshold(3)
+        .open()?;
+
+    let health = tree.health();
+    assert!(health.is_healthy());
+    assert_eq!(0, health.l0_segment_count);
+    assert_eq!(0, health.compaction_debt_bytes);
+
+    for seqno in 0..2 {
+        tree.insert("a", "abc", seqno);
+        tree.flush_active_memtable(0)?;
+    }
+
+    let health = tree.health();
+    assert_eq!(2, health.l0_segment_count);
+    assert_eq!(StallState::Slowdown, health.stall);
+    assert!(!health.is_healthy());
+
+    tree.insert("a", "abc", 2);
+    tree.flush_active_memtable(0)?;
+    assert_eq!(StallState::Stop, tree.health().stall);
+
+    tree.major_compact(u64::MAX, 3)?;
+
+    let health = tree.health();
+    assert!(health.is_healthy());
+    assert_eq!(0, health.l0_segment_count);
+    assert_eq!(0, health.compaction_debt_bytes);
+
+    Ok(())
+}
+
+#[test]
+fn tree_write_stall_state() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder)
+        .l0_slowdown_threshold(2)
+        .l0_stop_threshold(4)
+        .sealed_memtable_slowdown_threshold(2)
+        .sealed_memtable_stop_threshold(3)
+        .open()?;
+
+    assert_eq!(StallState::None, tree.write_stall_state());
+
+    let mut memtables = vec![];
+
+    for seqno in 0..3 {
+        tree.insert("a", "abc", seqno);
+        memtables.extend(tree.rotate_memtable());
+
+        let expected = match memtables.len() {
+            1 => StallState::None,
+            2 => StallState::Slowdown,
+            _ => StallState::Stop,
+        };
+        assert_eq!(expected, tree.write_stall_state());
+        assert_eq!(expected, tree.health().stall);
+    }
+
+    // NOTE: Flushing clears the backlog, but fills up L0
+    tree.flush_memtables(&memtables, 0)?;
+    assert_eq!(3, tree.first_level_segment_count());
+    assert_eq!(StallState::Slowdown, tree.write_stall_state());
+
+    tree.major_compact(u64::MAX, 3)?;
+    assert_eq!(StallState::None, tree.write_stall_state());
+
+    Ok(())
+}
+
+#[test]
+fn tree_health_compaction_debt() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?.into_path();
+
+    let tree = Config::new(folder).open()?;
+
+    tree.insert("a", "abc", 0);
+    tree.flush_active_memtable(0)?;
+    tree.major_compact(u64::MAX, 1)?;
+    assert_eq!(0, tree.health().compaction_debt_bytes);
+
+    tree.insert("b", "abc", 1);
+    let segment = tree.flush_active_memtable(0)?.expect("should flush");
+    assert_eq!(
+        segment.metadata.file_size,
+        tree.health().compaction_debt_bytes,
+    );
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_health() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?.into_path();
+
+    let tree = Config::new(folder).open_as_blob_tree()?;
+
+    let big_value = "a".repeat(10_000);
+
+    tree.insert("a", &big_value, 0);
+    tree.flush_active_memtable(0)?;
+    assert_eq!(0, tree.health().blob_stale_bytes);
+
+    tree.insert("a", &big_value, 1);
+    tree.gc_scan_stats(2, 1_000)?;
+
+    let health = tree.health();
+    assert!(health.blob_stale_bytes > 0);
+    assert!(health.blob_stale_ratio > 0.0);
+    assert!(health.is_healthy());
+
+    Ok(())
+}
+
+#[test]
+fn tree_health_recovers_after_failed_flush() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let segments_folder = folder.path().join("segments");
+
+    let tree = Config::new(&folder).open()?;
+
+    // NOTE: Flushing cannot create the segment file without its folder
+    std::fs::remove_dir_all(&segments_folder)?;
+
+    tree.insert("a", "abc", 0);
+    assert!(tree.flush_active_memtable(0).is_err());
+
+    let health = tree.health();
+    assert!(health.last_error.is_some());
+    assert!(!health.is_healthy());
+
+    std::fs::create_dir_all(&segments_folder)?;
+
+    tree.insert("b", "abc", 1);
+    tree.flush_active_memtable(0)?;
+
+    let health = tree.health();
+    assert_eq!(None, health.last_error);
+    assert!(health.is_healthy());
+
+    Ok(())
+}
@@ -0,0 +1,81 @@
+use lsm_tree::{AbstractTree, BlockCache, Config};
+use std::sync::Arc;
+use test_log::test;
+
+const ITEM_COUNT: usize = 10_000;
+
+fn create_tree(folder: &std::path::Path) -> lsm_tree::Result<()> {
+    let tree = Config::new(folder).index_block_size(1_024).open()?;
+
+    for x in 0..ITEM_COUNT as u64 {
+        tree.insert(x.to_be_bytes(), "abc", x);
+    }
+    tree.flush_active_memtable(0)?;
+
+    // NOTE: Move the segment out of L0/L1, which are always pinned
+    tree.major_compact(u64::MAX, 0)?;
+    assert_eq!(0, tree.first_level_segment_count());
+
+    Ok(())
+}
+
+#[test]
+fn tree_index_prefetch_disabled() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    create_tree(folder.path())?;
+
+    let block_cache = Arc::new(BlockCache::with_capacity_bytes(10_000_000));
+    let _tree = Config::new(&folder)
+        .block_cache(block_cache.clone())
+        .open()?;
+
+    assert!(block_cache.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn tree_index_prefetch() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    create_tree(folder.path())?;
+
+    let block_cache = Arc::new(BlockCache::with_capacity_bytes(10_000_000));
+    let tree = Config::new(&folder)
+        .block_cache(block_cache.clone())
+        .prefetch_index_levels(7, false)
+        .open()?;
+
+    let index_block_count = block_cache.len();
+    assert!(index_block_count > 1);
+
+    // NOTE: Point reads only need to load data blocks
+    assert!(tree.contains_key(0u64.to_be_bytes(), None)?);
+    assert_eq!(index_block_count + 1, block_cache.len());
+
+    Ok(())
+}
+
+#[test]
+fn tree_index_prefetch_pinned() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    create_tree(folder.path())?;
+
+    let unpinned_size = {
+        let tree = Config::new(&folder).open()?;
+        tree.memory_usage().block_index_size()
+    };
+
+    let block_cache = Arc::new(BlockCache::with_capacity_bytes(10_000_000));
+    let tree = Config::new(&folder)
+        .block_cache(block_cache.clone())
+        .prefetch_index_levels(7, true)
+        .open()?;
+
+    assert!(block_cache.is_empty());
+    assert!(tree.memory_usage().block_index_size() > unpinned_size);
+
+    assert!(tree.contains_key(0u64.to_be_bytes(), None)?);
+    assert_eq!(1, block_cache.len());
+
+    Ok(())
+}
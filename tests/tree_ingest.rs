@@ -0,0 +1,182 @@
+use lsm_tree::{AbstractTree, Config, SequenceNumberCounter};
+use test_log::test;
+
+const ITEM_COUNT: usize = 1_000;
+
+#[test]
+fn tree_ingest_global_seqno() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let seqno = SequenceNumberCounter::default();
+
+    {
+        let tree = Config::new(&folder).open()?;
+
+        tree.insert("a", "old", seqno.next());
+        tree.insert("z", "old", seqno.next());
+        tree.flush_active_memtable(0)?;
+
+        let snapshot = tree.snapshot(seqno.get());
+
+        let global_seqno = seqno.next();
+        let segment = tree
+            .ingest(
+                (0..ITEM_COUNT).map(|x| (format!("a{x:04}"), "ingested")),
+                global_seqno,
+            )?
+            .expect("should exist");
+        assert_eq!((global_seqno, global_seqno), segment.metadata.seqnos);
+        assert_eq!(Some(global_seqno), segment.metadata.global_seqno);
+
+        // NOTE: Writes after ingesting shadow the ingested data
+        tree.insert("a0000", "new", seqno.next());
+
+        assert_eq!(ITEM_COUNT + 2, tree.len(None, None)?);
+        assert_eq!(2, snapshot.len()?);
+        assert_eq!(&*tree.get("a0000", None)?.unwrap(), b"new");
+        assert_eq!(&*tree.get("a0001", None)?.unwrap(), b"ingested");
+        assert_eq!(
+            &*tree.get("a0001", Some(global_seqno + 1))?.unwrap(),
+            b"ingested"
+        );
+        assert!(tree.get("a0001", Some(global_seqno))?.is_none());
+    }
+
+    {
+        // NOTE: The unflushed write is lost, but the ingested segment was persisted
+        let tree = Config::new(&folder).open()?;
+        assert_eq!(ITEM_COUNT + 2, tree.len(None, None)?);
+        assert_eq!(&*tree.get("a0000", None)?.unwrap(), b"ingested");
+
+        // NOTE: The global seqno was 2
+        assert!(tree.get("a0001", Some(2))?.is_none());
+        assert_eq!(2, tree.len(Some(2), None)?);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn tree_ingest_invalid() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open()?;
+
+    tree.insert("a", "old", 0);
+
+    // NOTE: The memtable contains a write below the global seqno
+    assert!(matches!(
+        tree.ingest([("b", "ingested")], 1),
+        Err(lsm_tree::Error::InvalidInput(_))
+    ));
+
+    tree.flush_active_memtable(0)?;
+
+    // NOTE: The segment contains a write above the global seqno
+    assert!(matches!(
+        tree.ingest([("b", "ingested")], 0),
+        Err(lsm_tree::Error::InvalidInput(_))
+    ));
+
+    assert!(matches!(
+        tree.ingest([("c", "ingested"), ("b", "ingested")], 1),
+        Err(lsm_tree::Error::InvalidInput(_))
+    ));
+
+    assert!(tree.ingest(Vec::<(&str, &str)>::new(), 1)?.is_none());
+
+    assert_eq!(1, tree.segment_count());
+    assert_eq!(1, tree.len(None, None)?);
+
+    // NOTE: The unfinished segments were deleted
+    assert_eq!(
+        1,
+        std::fs::read_dir(folder.path().join("segments"))?.count()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn tree_ingest_level() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    let level_of = |tree: &lsm_tree::Tree, segment: &lsm_tree::Segment| {
+        tree.levels
+            .read()
+            .expect("lock is poisoned")
+            .levels
+            .iter()
+            .position(|level| level.segments.iter().any(|x| x.id() == segment.id()))
+    };
+
+    // NOTE: Nothing overlaps, so the segment is placed into the last level
+    let first = tree
+        .ingest(
+            (0..ITEM_COUNT).map(|x| (format!("a{x:04}"), "first")),
+            seqno.next(),
+        )?
+        .expect("should exist");
+    assert_eq!(Some(6), level_of(&tree, &first));
+
+    // NOTE: The newer data needs to be above the data it overlaps
+    let second = tree
+        .ingest(
+            (500..ITEM_COUNT).map(|x| (format!("a{x:04}"), "second")),
+            seqno.next(),
+        )?
+        .expect("should exist");
+    assert_eq!(Some(5), level_of(&tree, &second));
+
+    let third = tree
+        .ingest(
+            (0..ITEM_COUNT).map(|x| (format!("b{x:04}"), "third")),
+            seqno.next(),
+        )?
+        .expect("should exist");
+    assert_eq!(Some(6), level_of(&tree, &third));
+
+    tree.insert("a0000", "new", seqno.next());
+    tree.flush_active_memtable(0)?;
+
+    let fourth = tree
+        .ingest([("c", "fourth")], seqno.next())?
+        .expect("should exist");
+    assert_eq!(Some(6), level_of(&tree, &fourth));
+
+    assert_eq!(&*tree.get("a0000", None)?.unwrap(), b"new");
+    assert_eq!(&*tree.get("a0001", None)?.unwrap(), b"first");
+    assert_eq!(&*tree.get("a0500", None)?.unwrap(), b"second");
+    assert_eq!(&*tree.get("b0500", None)?.unwrap(), b"third");
+    assert_eq!(ITEM_COUNT * 2 + 1, tree.len(None, None)?);
+
+    tree.major_compact(u64::MAX, seqno.get())?;
+    assert_eq!(&*tree.get("a0000", None)?.unwrap(), b"new");
+    assert_eq!(&*tree.get("a0500", None)?.unwrap(), b"second");
+    assert_eq!(ITEM_COUNT * 2 + 1, tree.len(None, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_ingest_keeps_sealed_memtables() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open()?;
+
+    // NOTE: Writes above the global seqno may stay in memtables
+    tree.insert("z", "sealed", 10);
+    tree.rotate_memtable().expect("should rotate");
+    assert_eq!(1, tree.sealed_memtable_count());
+
+    tree.ingest([("a", "ingested")], 5)?;
+
+    assert_eq!(1, tree.sealed_memtable_count());
+    assert_eq!(&*tree.get("z", None)?.unwrap(), b"sealed");
+    assert_eq!(&*tree.get("a", None)?.unwrap(), b"ingested");
+
+    Ok(())
+}
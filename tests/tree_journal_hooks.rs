@@ -0,0 +1,184 @@
+use lsm_tree::{AbstractTree, Config, InternalValue, JournalObserver, SeqNo, ValueType};
+use std::sync::{Arc, Mutex};
+use test_log::test;
+
+#[derive(Default)]
+struct RotationLog(Mutex<Vec<SeqNo>>);
+
+impl JournalObserver for RotationLog {
+    fn on_memtable_rotated(&self, _memtable_id: u64, highest_seqno: SeqNo) {
+        self.0.lock().expect("lock is poisoned").push(highest_seqno);
+    }
+}
+
+#[derive(Default)]
+struct PersistLog(Mutex<Vec<SeqNo>>);
+
+impl JournalObserver for PersistLog {
+    fn on_memtable_rotated(&self, _memtable_id: u64, _highest_seqno: SeqNo) {}
+
+    fn on_segments_persisted(&self, persisted_seqno: SeqNo) {
+        self.0
+            .lock()
+            .expect("lock is poisoned")
+            .push(persisted_seqno);
+    }
+}
+
+#[test]
+fn tree_journal_rotation_observer() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let observer = Arc::new(RotationLog::default());
+    let tree = Config::new(&folder)
+        .journal_observer(observer.clone())
+        .open()?;
+
+    assert!(tree.rotate_memtable().is_none());
+
+    tree.insert("a", "abc", 0);
+    tree.insert("b", "abc", 1);
+    tree.flush_active_memtable(0)?;
+
+    tree.insert("c", "abc", 2);
+    tree.flush_active_memtable(0)?;
+
+    assert_eq!(vec![1, 2], *observer.0.lock().expect("lock is poisoned"));
+
+    Ok(())
+}
+
+#[test]
+fn tree_journal_persist_observer() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let observer = Arc::new(PersistLog::default());
+    let tree = Config::new(&folder)
+        .journal_observer(observer.clone())
+        .open()?;
+
+    tree.insert("a", "abc", 0);
+    tree.insert("b", "abc", 1);
+    tree.flush_active_memtable(0)?;
+
+    tree.insert("c", "abc", 2);
+    tree.flush_active_memtable(0)?;
+
+    // NOTE: Compactions do not persist new writes
+    tree.major_compact(u64::MAX, 0)?;
+
+    assert_eq!(vec![1, 2], *observer.0.lock().expect("lock is poisoned"));
+
+    Ok(())
+}
+
+#[test]
+fn tree_journal_flush_guard() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open()?;
+    tree.mark_journal_persisted(0);
+
+    tree.insert("a", "abc", 0);
+    tree.insert("b", "abc", 1);
+
+    assert!(matches!(
+        tree.flush_active_memtable(0),
+        Err(lsm_tree::Error::JournalNotPersisted(1))
+    ));
+    assert_eq!(0, tree.segment_count());
+    assert_eq!(0, tree.sealed_memtable_count());
+
+    tree.mark_journal_persisted(1);
+    tree.flush_active_memtable(0)?;
+    assert_eq!(1, tree.segment_count());
+
+    Ok(())
+}
+
+#[test]
+fn tree_journal_replay() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let journal = vec![
+        InternalValue::from_components("a", "abc", 0, ValueType::Value),
+        InternalValue::from_components("b", "abc", 1, ValueType::Value),
+        InternalValue::from_components("c", "abc", 2, ValueType::Value),
+        InternalValue::new_tombstone("a", 3),
+    ];
+
+    {
+        let tree = Config::new(&folder).open()?;
+
+        for entry in &journal[..2] {
+            tree.insert(
+                entry.key.user_key.clone(),
+                entry.value.clone(),
+                entry.key.seqno,
+            );
+        }
+        tree.flush_active_memtable(0)?;
+
+        // NOTE: Unflushed data is lost
+        tree.insert("c", "abc", 2);
+    }
+
+    let tree = Config::new(&folder).open()?;
+    assert_eq!(2, tree.len(None, None)?);
+
+    assert_eq!(2, tree.replay_journal(journal.clone()));
+    assert_eq!(2, tree.len(None, None)?);
+    assert!(!tree.contains_key("a", None)?);
+    assert!(tree.contains_key("c", None)?);
+
+    tree.flush_active_memtable(0)?;
+    assert_eq!(0, tree.replay_journal(journal));
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_journal_hooks() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let observer = Arc::new(RotationLog::default());
+    let big_value = "abc".repeat(10_000);
+
+    {
+        let tree = Config::new(&folder)
+            .journal_observer(observer.clone())
+            .open_as_blob_tree()?;
+        tree.mark_journal_persisted(0);
+
+        tree.insert("a", &big_value, 0);
+        tree.insert("b", &big_value, 1);
+
+        assert!(matches!(
+            tree.flush_active_memtable(0),
+            Err(lsm_tree::Error::JournalNotPersisted(1))
+        ));
+        assert_eq!(0, tree.blob_file_count());
+
+        tree.mark_journal_persisted(1);
+        tree.flush_active_memtable(0)?;
+        assert_eq!(1, tree.blob_file_count());
+    }
+
+    assert_eq!(vec![1], *observer.0.lock().expect("lock is poisoned"));
+
+    let tree = Config::new(&folder).open_as_blob_tree()?;
+
+    let journal = vec![
+        InternalValue::from_components("a", big_value.clone(), 0, ValueType::Value),
+        InternalValue::from_components("c", big_value.clone(), 2, ValueType::Value),
+    ];
+    assert_eq!(1, tree.replay_journal(journal));
+
+    assert_eq!(
+        &*tree.get("c", None)?.expect("should exist"),
+        big_value.as_bytes()
+    );
+    assert_eq!(3, tree.len(None, None)?);
+
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use lsm_tree::{AbstractTree, Config, SequenceNumberCounter};
+use test_log::test;
+
+const ITEM_COUNT: u64 = 100;
+
+fn key(x: u64) -> [u8; 8] {
+    x.to_be_bytes()
+}
+
+#[test]
+fn tree_keys_range() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    for x in 0..ITEM_COUNT {
+        tree.insert(key(x), "abc", seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+    tree.remove(key(15), seqno.next());
+
+    let keys = tree
+        .keys_range(key(10)..key(20), None, None)
+        .collect::<lsm_tree::Result<Vec<_>>>()?;
+    let expected = (10..20)
+        .filter(|&x| x != 15)
+        .map(|x| lsm_tree::UserKey::from(key(x)))
+        .collect::<Vec<_>>();
+    assert_eq!(expected, keys);
+
+    assert_eq!(
+        Some(lsm_tree::UserKey::from(key(19))),
+        tree.keys_range(key(10)..key(20), None, None)
+            .next_back()
+            .transpose()?,
+    );
+
+    assert_eq!(
+        ITEM_COUNT as usize - 1,
+        tree.keys_prefix(&key(15)[..7], None, None).count()
+    );
+    assert_eq!(0, tree.keys_prefix(&[1u8][..], None, None).count());
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_keys_range() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open_as_blob_tree()?;
+    let seqno = SequenceNumberCounter::default();
+
+    let big_value = "a".repeat(10_000);
+
+    for x in 0..ITEM_COUNT {
+        tree.insert(key(x), &big_value, seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+    assert_eq!(1, tree.blob_file_count());
+
+    assert_eq!(10, tree.keys_range(key(10)..key(20), None, None).count());
+    assert_eq!(
+        ITEM_COUNT as usize,
+        tree.keys_prefix(&[0u8][..], None, None)
+            .collect::<lsm_tree::Result<Vec<_>>>()?
+            .len(),
+    );
+
+    Ok(())
+}
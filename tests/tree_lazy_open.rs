@@ -0,0 +1,71 @@
+use lsm_tree::{AbstractTree, Config};
+use test_log::test;
+
+const ITEM_COUNT: u64 = 100;
+
+#[test]
+fn tree_lazy_open() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    {
+        let tree = Config::new(&folder).open()?;
+
+        for x in 0..ITEM_COUNT {
+            tree.insert(x.to_be_bytes(), x.to_string(), 0);
+        }
+        tree.flush_active_memtable(0)?;
+    }
+
+    let tree = Config::new(&folder).lazy_segment_open(true).open()?;
+
+    let segment = tree
+        .levels
+        .read()
+        .expect("lock is poisoned")
+        .iter()
+        .next()
+        .cloned()
+        .expect("segment should exist");
+
+    assert_eq!(0, segment.block_index_size());
+    assert_eq!(0, segment.bloom_filter_size());
+
+    assert_eq!(
+        b"42",
+        &*tree.get(42u64.to_be_bytes(), None)?.expect("should exist")
+    );
+
+    assert!(segment.block_index_size() > 0);
+    assert!(segment.bloom_filter_size() > 0);
+
+    assert_eq!(ITEM_COUNT as usize, tree.len(None, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_lazy_open_disabled() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    {
+        let tree = Config::new(&folder).open()?;
+        tree.insert("a", "a", 0);
+        tree.flush_active_memtable(0)?;
+    }
+
+    let tree = Config::new(&folder).open()?;
+
+    let segment = tree
+        .levels
+        .read()
+        .expect("lock is poisoned")
+        .iter()
+        .next()
+        .cloned()
+        .expect("segment should exist");
+
+    assert!(segment.block_index_size() > 0);
+    assert!(segment.bloom_filter_size() > 0);
+
+    Ok(())
+}
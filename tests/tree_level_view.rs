@@ -0,0 +1,68 @@
+use lsm_tree::{AbstractTree, Config};
+use std::time::Duration;
+use test_log::test;
+
+#[test]
+fn tree_reads_do_not_take_levels_lock() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open()?;
+
+    for key in ["a", "b", "c"] {
+        tree.insert(key, key, 0);
+        tree.flush_active_memtable(0)?;
+    }
+
+    // NOTE: Simulate a long-running manifest update
+    let levels_lock = tree.levels.write().expect("lock is poisoned");
+
+    let (tx, rx) = std::sync::mpsc::channel();
+
+    {
+        let tree = tree.clone();
+
+        std::thread::spawn(move || {
+            let result = (|| -> lsm_tree::Result<_> {
+                let item = tree.get("b", None)?;
+                let count = tree.iter(None, None).count();
+                Ok((item, count, tree.segment_count()))
+            })();
+
+            tx.send(result).expect("should send");
+        });
+    }
+
+    let (item, count, segment_count) = rx
+        .recv_timeout(Duration::from_secs(10))
+        .expect("reads should not block on the levels lock")?;
+
+    assert_eq!(Some("b".as_bytes().into()), item);
+    assert_eq!(3, count);
+    assert_eq!(3, segment_count);
+
+    drop(levels_lock);
+
+    Ok(())
+}
+
+#[test]
+fn tree_level_view_sees_compaction() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open()?;
+
+    for key in ["a", "b", "c"] {
+        tree.insert(key, key, 0);
+        tree.flush_active_memtable(0)?;
+    }
+
+    assert_eq!(3, tree.segment_count());
+
+    tree.major_compact(u64::MAX, 0)?;
+    assert_eq!(1, tree.segment_count());
+
+    assert_eq!(3, tree.iter(None, None).count());
+    assert_eq!(Some("c".as_bytes().into()), tree.get("c", None)?);
+
+    Ok(())
+}
@@ -0,0 +1,61 @@
+use lsm_tree::{compaction::Fifo, AbstractTree, Config, ManualClock};
+use std::{sync::Arc, time::Duration};
+use test_log::test;
+
+#[test]
+fn tree_manual_clock_fifo_ttl() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let clock = Arc::new(ManualClock::new(Duration::from_secs(1_000)));
+    let tree = Config::new(&folder).clock(clock.clone()).open()?;
+
+    tree.insert("a", "abc", 0);
+    let segment = tree.flush_active_memtable(0)?.expect("should flush");
+    assert_eq!(1_000_000_000, segment.metadata.created_at);
+
+    clock.advance(Duration::from_secs(30));
+
+    tree.insert("b", "abc", 1);
+    tree.flush_active_memtable(0)?;
+
+    let strategy = Arc::new(Fifo::new(u64::MAX, Some(60)));
+
+    clock.advance(Duration::from_secs(60));
+    tree.compact(strategy.clone(), 0)?;
+    assert_eq!(1, tree.segment_count());
+    assert!(!tree.contains_key("a", None)?);
+    assert!(tree.contains_key("b", None)?);
+
+    clock.advance(Duration::from_secs(1));
+    tree.compact(strategy.clone(), 0)?;
+    assert_eq!(0, tree.segment_count());
+
+    Ok(())
+}
+
+#[test]
+fn tree_manual_clock_compaction() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let clock = Arc::new(ManualClock::new(Duration::from_secs(5)));
+    let tree = Config::new(&folder).clock(clock.clone()).open()?;
+
+    for x in 0..10u64 {
+        tree.insert(x.to_be_bytes(), "abc", x);
+        tree.flush_active_memtable(0)?;
+    }
+
+    clock.set(Duration::from_secs(10));
+    tree.major_compact(u64::MAX, 0)?;
+
+    let created_at = tree
+        .levels
+        .read()
+        .expect("lock is poisoned")
+        .iter()
+        .map(|segment| segment.metadata.created_at)
+        .collect::<Vec<_>>();
+    assert_eq!(vec![10_000_000], created_at);
+
+    Ok(())
+}
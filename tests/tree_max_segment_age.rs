@@ -0,0 +1,60 @@
+use lsm_tree::{
+    compaction::{CompactionStrategy, Leveled, SizeTiered},
+    AbstractTree, Config, ManualClock, SequenceNumberCounter,
+};
+use std::{sync::Arc, time::Duration};
+use test_log::test;
+
+fn max_segment_age(strategy: Arc<dyn CompactionStrategy + Send + Sync>) -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let clock = Arc::new(ManualClock::new(Duration::from_secs(1_000)));
+    let tree = Config::new(&folder)
+        .clock(clock.clone())
+        .max_segment_age(Duration::from_secs(60))
+        .open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    for x in 0..10_u64 {
+        tree.insert(x.to_be_bytes(), "abc", seqno.next());
+    }
+    for x in 10..20_u64 {
+        tree.insert_with_ttl(
+            x.to_be_bytes(),
+            "abc",
+            seqno.next(),
+            Duration::from_secs(30),
+        );
+    }
+    tree.flush_active_memtable(0)?;
+    tree.major_compact(u64::MAX, 0)?;
+    assert_eq!(1, tree.segment_count());
+    assert_eq!(20, tree.approximate_len());
+
+    clock.advance(Duration::from_secs(59));
+    tree.compact(strategy.clone(), seqno.get())?;
+    assert_eq!(20, tree.approximate_len());
+
+    // NOTE: The segment is old enough, so it is rewritten, dropping the expired values
+    clock.advance(Duration::from_secs(1));
+    tree.compact(strategy.clone(), seqno.get())?;
+    assert_eq!(1, tree.segment_count());
+    assert_eq!(10, tree.approximate_len());
+    assert_eq!(10, tree.len(None, None)?);
+
+    // NOTE: The rewritten segment is new again
+    tree.compact(strategy, seqno.get())?;
+    assert_eq!(10, tree.approximate_len());
+
+    Ok(())
+}
+
+#[test]
+fn tree_max_segment_age_leveled() -> lsm_tree::Result<()> {
+    max_segment_age(Arc::new(Leveled::default()))
+}
+
+#[test]
+fn tree_max_segment_age_tiered() -> lsm_tree::Result<()> {
+    max_segment_age(Arc::new(SizeTiered::default()))
+}
@@ -0,0 +1,68 @@
+use lsm_tree::{AbstractTree, BlockCache, Config, MemoryBudget};
+use std::sync::Arc;
+use test_log::test;
+
+const ITEM_COUNT: usize = 10_000;
+
+#[test]
+fn tree_memory_budget_shrinks_block_cache() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let block_cache = Arc::new(BlockCache::with_capacity_bytes(16 * 1_024 * 1_024));
+
+    let tree = Config::new(&folder)
+        .block_cache(block_cache.clone())
+        .open()?;
+
+    for x in 0..ITEM_COUNT as u64 {
+        tree.insert(x.to_be_bytes(), x.to_string().repeat(10), 0);
+    }
+    tree.flush_active_memtable(0)?;
+
+    for kv in tree.iter(None, None) {
+        kv?;
+    }
+
+    let cached = block_cache.size();
+    assert!(cached > 100_000);
+
+    let budget = MemoryBudget::new(cached / 2);
+    let usage = budget.enforce(&tree, 0)?;
+
+    assert!(usage.block_cache < cached);
+    assert!(block_cache.size() < cached);
+    assert!(block_cache.limit() < block_cache.capacity());
+    assert!(usage.total() <= budget.limit());
+
+    // NOTE: Memory is available again, so the block cache can grow back
+    MemoryBudget::new(u64::MAX).enforce(&tree, 0)?;
+    assert_eq!(block_cache.capacity(), block_cache.limit());
+
+    Ok(())
+}
+
+#[test]
+fn tree_memory_budget_flushes_memtable() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open()?;
+
+    for x in 0..ITEM_COUNT as u64 {
+        tree.insert(x.to_be_bytes(), x.to_string().repeat(10), 0);
+    }
+
+    let budget = MemoryBudget::new(100_000);
+
+    let usage = budget.usage(&tree);
+    assert!(usage.memtables > budget.limit());
+    assert_eq!(0, tree.segment_count());
+
+    let usage = budget.enforce(&tree, 0)?;
+    assert_eq!(0, usage.memtables);
+    assert_eq!(0, tree.active_memtable_size());
+    assert_eq!(0, tree.sealed_memtable_count());
+    assert_eq!(1, tree.segment_count());
+    assert_eq!(ITEM_COUNT, tree.len(None, None)?);
+
+    Ok(())
+}
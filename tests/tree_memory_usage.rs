@@ -0,0 +1,35 @@
+use lsm_tree::{AbstractTree, Config};
+use test_log::test;
+
+#[test]
+fn tree_memory_usage() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?.into_path();
+
+    let tree = Config::new(folder).open()?;
+    assert_eq!(0, tree.memory_usage().total());
+
+    for key in 0u64..1_000 {
+        tree.insert(key.to_be_bytes(), "abc", 0);
+    }
+    tree.flush_active_memtable(0)?;
+
+    let usage = tree.memory_usage();
+    assert_eq!(7, usage.levels.len());
+    assert_eq!(1, usage.levels[0].segment_count);
+    assert!(usage.levels[0].block_index_size > 0);
+    assert_eq!(tree.bloom_filter_size(), usage.bloom_filter_size());
+
+    let full_index_size = usage.block_index_size();
+
+    // NOTE: Major compaction moves data into the last level, which uses a two-level index
+    tree.major_compact(u64::MAX, 0)?;
+
+    let usage = tree.memory_usage();
+    assert_eq!(0, usage.levels[0].segment_count);
+    assert_eq!(1, usage.levels[6].segment_count);
+    assert!(usage.levels[6].block_index_size > 0);
+    assert!(usage.block_index_size() < full_index_size);
+    assert_eq!(tree.bloom_filter_size(), usage.bloom_filter_size());
+
+    Ok(())
+}
@@ -0,0 +1,201 @@
+use lsm_tree::{AbstractTree, Config, MergeOperator, SequenceNumberCounter, UserValue};
+use std::sync::Arc;
+use test_log::test;
+
+/// Adds up big-endian u64 counters
+struct Counter;
+
+impl MergeOperator for Counter {
+    fn merge(
+        &self,
+        _: &[u8],
+        base: Option<&[u8]>,
+        operands: &[&[u8]],
+    ) -> lsm_tree::Result<UserValue> {
+        let mut sum = base.map_or(0, decode);
+
+        for operand in operands {
+            sum += decode(operand);
+        }
+
+        Ok(sum.to_be_bytes().into())
+    }
+}
+
+fn decode(bytes: &[u8]) -> u64 {
+    u64::from_be_bytes(bytes.try_into().expect("should be u64"))
+}
+
+fn get_counter(tree: &lsm_tree::Tree, key: &str, seqno: Option<u64>) -> lsm_tree::Result<u64> {
+    Ok(tree.get(key, seqno)?.as_deref().map_or(0, decode))
+}
+
+#[test]
+fn tree_merge_operator_memtable() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder)
+        .merge_operator(Arc::new(Counter))
+        .open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    tree.insert("a", 10u64.to_be_bytes(), seqno.next());
+
+    for _ in 0..5 {
+        tree.merge("a", 1u64.to_be_bytes(), seqno.next());
+        tree.merge("b", 2u64.to_be_bytes(), seqno.next());
+    }
+
+    assert_eq!(15, get_counter(&tree, "a", None)?);
+    assert_eq!(10, get_counter(&tree, "b", None)?);
+    assert_eq!(12, get_counter(&tree, "a", Some(5))?);
+
+    let items = tree.get_many(["b", "a"], None)?;
+    assert_eq!(Some(10), items[0].as_deref().map(decode));
+    assert_eq!(Some(15), items[1].as_deref().map(decode));
+
+    let items = tree
+        .iter(None, None)
+        .collect::<lsm_tree::Result<Vec<_>>>()?;
+    assert_eq!(2, items.len());
+    assert_eq!(15, decode(&items[0].1));
+    assert_eq!(10, decode(&items[1].1));
+
+    let items = tree
+        .iter(None, None)
+        .rev()
+        .collect::<lsm_tree::Result<Vec<_>>>()?;
+    assert_eq!(10, decode(&items[0].1));
+    assert_eq!(15, decode(&items[1].1));
+
+    // NOTE: Operands after a tombstone start from scratch
+    tree.remove("a", seqno.next());
+    tree.merge("a", 3u64.to_be_bytes(), seqno.next());
+    assert_eq!(3, get_counter(&tree, "a", None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_merge_operator_flush_and_compaction() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    {
+        let tree = Config::new(&folder)
+            .merge_operator(Arc::new(Counter))
+            .open()?;
+
+        tree.insert("a", 10u64.to_be_bytes(), seqno.next());
+        tree.flush_active_memtable(0)?;
+
+        for _ in 0..3 {
+            tree.merge("a", 1u64.to_be_bytes(), seqno.next());
+            tree.merge("b", 1u64.to_be_bytes(), seqno.next());
+            tree.flush_active_memtable(0)?;
+        }
+        assert_eq!(4, tree.segment_count());
+
+        assert_eq!(13, get_counter(&tree, "a", None)?);
+        assert_eq!(3, get_counter(&tree, "b", None)?);
+    }
+
+    let tree = Config::new(&folder)
+        .merge_operator(Arc::new(Counter))
+        .open()?;
+    assert_eq!(13, get_counter(&tree, "a", None)?);
+    assert_eq!(3, get_counter(&tree, "b", None)?);
+
+    tree.major_compact(u64::MAX, seqno.get())?;
+    assert_eq!(1, tree.segment_count());
+    assert_eq!(13, get_counter(&tree, "a", None)?);
+    assert_eq!(3, get_counter(&tree, "b", None)?);
+
+    // NOTE: The operands are folded into a single version per key
+    let segment = tree
+        .levels
+        .read()
+        .expect("lock is poisoned")
+        .iter()
+        .next()
+        .cloned()
+        .expect("should exist");
+    assert_eq!(2, segment.metadata.item_count);
+
+    Ok(())
+}
+
+#[test]
+fn tree_merge_operator_snapshot() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder)
+        .merge_operator(Arc::new(Counter))
+        .open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    tree.insert("a", 10u64.to_be_bytes(), seqno.next());
+    tree.merge("a", 1u64.to_be_bytes(), seqno.next());
+    tree.flush_active_memtable(0)?;
+
+    let snapshot = tree.snapshot(seqno.get());
+
+    tree.merge("a", 1u64.to_be_bytes(), seqno.next());
+    tree.flush_active_memtable(0)?;
+
+    tree.major_compact(u64::MAX, seqno.get())?;
+    assert_eq!(12, get_counter(&tree, "a", None)?);
+    assert_eq!(Some(11), snapshot.get("a")?.as_deref().map(decode));
+
+    Ok(())
+}
+
+#[test]
+fn tree_merge_operator_range_tombstone() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder)
+        .merge_operator(Arc::new(Counter))
+        .open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    tree.insert("a", 10u64.to_be_bytes(), seqno.next());
+    tree.merge("a", 1u64.to_be_bytes(), seqno.next());
+    tree.remove_range("a".."b", seqno.next());
+    tree.merge("a", 1u64.to_be_bytes(), seqno.next());
+
+    assert_eq!(1, get_counter(&tree, "a", None)?);
+
+    tree.flush_active_memtable(0)?;
+    assert_eq!(1, get_counter(&tree, "a", None)?);
+
+    tree.major_compact(u64::MAX, seqno.get())?;
+    assert_eq!(1, get_counter(&tree, "a", None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_merge_operator_missing() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+
+    tree.insert("a", 10u64.to_be_bytes(), 0);
+    tree.merge("a", 1u64.to_be_bytes(), 1);
+
+    assert!(matches!(
+        tree.get("a", None),
+        Err(lsm_tree::Error::MissingMergeOperator)
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_merge_operator_unsupported() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    assert!(Config::new(&folder)
+        .merge_operator(Arc::new(Counter))
+        .open_as_blob_tree()
+        .is_err());
+
+    Ok(())
+}
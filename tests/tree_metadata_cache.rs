@@ -0,0 +1,60 @@
+use lsm_tree::{AbstractTree, Config};
+use std::io::{Seek, SeekFrom, Write};
+use test_log::test;
+
+const ITEM_COUNT: usize = 100;
+
+#[test]
+fn tree_metadata_cache_reopen() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let (segment_ids, metadata) = {
+        let tree = Config::new(&folder).open()?;
+
+        for batch in 0..3u64 {
+            for x in 0..ITEM_COUNT as u64 {
+                tree.insert(x.to_be_bytes(), batch.to_string(), batch);
+            }
+            tree.flush_active_memtable(0)?;
+        }
+
+        let levels = tree.levels.read().expect("lock is poisoned");
+        (
+            levels.iter().map(|x| x.id()).collect::<Vec<_>>(),
+            levels
+                .iter()
+                .map(|x| x.metadata.clone())
+                .collect::<Vec<_>>(),
+        )
+    };
+    assert_eq!(3, segment_ids.len());
+
+    // NOTE: Break the trailer magic of every segment, which is
+    // not noticed, because the trailers are cached in the level manifest
+    for segment_id in &segment_ids {
+        let path = folder.path().join("segments").join(segment_id.to_string());
+        let mut file = std::fs::OpenOptions::new().write(true).open(path)?;
+        file.seek(SeekFrom::End(-4))?;
+        file.write_all(b"xxxx")?;
+        file.sync_all()?;
+    }
+
+    let tree = Config::new(&folder).open()?;
+    assert_eq!(
+        metadata,
+        tree.levels
+            .read()
+            .expect("lock is poisoned")
+            .iter()
+            .map(|x| x.metadata.clone())
+            .collect::<Vec<_>>()
+    );
+
+    assert_eq!(ITEM_COUNT, tree.len(None, None)?);
+    assert_eq!(
+        b"2",
+        &*tree.get(0u64.to_be_bytes(), None)?.expect("should exist")
+    );
+
+    Ok(())
+}
@@ -0,0 +1,122 @@
+use lsm_tree::{AbstractTree, Config, UserKey};
+use std::ops::Bound;
+use test_log::test;
+
+const ITEM_COUNT: u64 = 1_000;
+
+#[test]
+fn tree_multi_range() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open()?;
+
+    for x in 0..ITEM_COUNT {
+        tree.insert(x.to_be_bytes(), "abc", x);
+
+        if x % 100 == 99 {
+            tree.flush_active_memtable(0)?;
+        }
+    }
+    tree.remove(501u64.to_be_bytes(), ITEM_COUNT);
+
+    let ranges = [(10u64, 20u64), (500, 505), (990, 2_000)]
+        .map(|(start, end)| start.to_be_bytes()..end.to_be_bytes());
+
+    let expected = [(10u64, 20u64), (500, 505), (990, 1_000)]
+        .into_iter()
+        .flat_map(|(start, end)| start..end)
+        .filter(|&x| x != 501)
+        .map(|x| UserKey::from(x.to_be_bytes()))
+        .collect::<Vec<_>>();
+
+    let keys = tree
+        .multi_range(ranges.clone(), None)
+        .map(|item| item.map(|(key, _)| key))
+        .collect::<lsm_tree::Result<Vec<_>>>()?;
+    assert_eq!(expected, keys);
+
+    let mut keys = tree
+        .multi_range(ranges.clone(), None)
+        .rev()
+        .map(|item| item.map(|(key, _)| key))
+        .collect::<lsm_tree::Result<Vec<_>>>()?;
+    keys.reverse();
+    assert_eq!(expected, keys);
+
+    // NOTE: The tombstone is not visible to the snapshot
+    assert_eq!(
+        expected.len() + 1,
+        tree.multi_range(ranges, Some(ITEM_COUNT)).count()
+    );
+
+    assert_eq!(
+        0,
+        tree.multi_range::<&[u8], (Bound<&[u8]>, Bound<&[u8]>), _>(vec![], None)
+            .count()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_multi_range() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let big_value = "abc".repeat(10_000);
+
+    let tree = Config::new(&folder).open_as_blob_tree()?;
+    tree.insert("a", &big_value, 0);
+    tree.insert("b", "small", 1);
+    tree.insert("c", &big_value, 2);
+    tree.flush_active_memtable(0)?;
+
+    let items = tree
+        .multi_range(["a"..="a", "c"..="z"], None)
+        .collect::<lsm_tree::Result<Vec<_>>>()?;
+    assert_eq!(2, items.len());
+
+    for (_, value) in items {
+        assert_eq!(big_value.as_bytes(), &*value);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn tree_multi_range_unsorted_overlapping() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+
+    for (idx, key) in ["a", "b", "c", "d", "e", "f"].into_iter().enumerate() {
+        tree.insert(key, "abc", idx as u64);
+    }
+
+    let keys = tree
+        .multi_range(["e"..="f", "a"..="b", "b"..="c"], None)
+        .map(|item| item.map(|(key, _)| key))
+        .collect::<lsm_tree::Result<Vec<_>>>()?;
+
+    assert_eq!(["a", "b", "c", "e", "f"].map(UserKey::from).to_vec(), keys);
+
+    Ok(())
+}
+
+#[test]
+fn tree_multi_range_pins_seqno() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+
+    tree.insert("a", "abc", 0);
+    tree.insert("c", "abc", 1);
+
+    let mut iter = tree.multi_range(["a"..="a", "c"..="d"], None);
+    assert_eq!(b"a", &*iter.next().expect("should exist")?.0);
+
+    // NOTE: Written after the iterator was created, so not visible
+    tree.insert("d", "abc", 2);
+
+    assert_eq!(b"c", &*iter.next().expect("should exist")?.0);
+    assert!(iter.next().is_none());
+
+    Ok(())
+}
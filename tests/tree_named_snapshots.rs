@@ -0,0 +1,101 @@
+use lsm_tree::{AbstractTree, Config};
+use test_log::test;
+
+#[test]
+fn tree_named_snapshot_recover() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    {
+        let tree = Config::new(&folder).open()?;
+
+        tree.insert("a", "old", 0);
+        tree.insert("b", "old", 1);
+        tree.flush_active_memtable(0)?;
+
+        tree.create_named_snapshot("backup", 2)?;
+        tree.create_named_snapshot("other", 0)?;
+        assert!(tree.release_named_snapshot("other")?);
+        assert!(!tree.release_named_snapshot("other")?);
+
+        tree.insert("a", "new", 2);
+        tree.remove("b", 3);
+        tree.flush_active_memtable(0)?;
+    }
+
+    let tree = Config::new(&folder).open()?;
+    assert_eq!(vec![("backup".to_owned(), 2)], tree.list_named_snapshots());
+
+    // NOTE: Compaction keeps the versions the snapshot needs
+    tree.major_compact(u64::MAX, 1_000)?;
+
+    let snapshot = tree.named_snapshot("backup").expect("should exist");
+    assert_eq!(Some("old".as_bytes().into()), snapshot.get("a")?);
+    assert_eq!(Some("old".as_bytes().into()), snapshot.get("b")?);
+    assert_eq!(Some("new".as_bytes().into()), tree.get("a", None)?);
+    assert!(!tree.contains_key("b", None)?);
+
+    // NOTE: The open snapshot would still keep the old versions
+    drop(snapshot);
+
+    assert!(tree.release_named_snapshot("backup")?);
+    assert!(tree.named_snapshot("backup").is_none());
+
+    tree.major_compact(u64::MAX, 1_000)?;
+    assert_eq!(1, tree.len(None, None)?);
+    assert_eq!(None, tree.get("a", Some(2))?);
+
+    drop(tree);
+
+    let tree = Config::new(&folder).open()?;
+    assert!(tree.list_named_snapshots().is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn tree_named_snapshot_name_too_long() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open()?;
+
+    assert!(matches!(
+        tree.create_named_snapshot(&"a".repeat(70_000), 0),
+        Err(lsm_tree::Error::InvalidInput(_))
+    ));
+    assert!(tree.list_named_snapshots().is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_named_snapshot() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let old_value = "old".repeat(10_000);
+    let new_value = "new".repeat(10_000);
+
+    {
+        let tree = Config::new(&folder).open_as_blob_tree()?;
+
+        tree.insert("a", &old_value, 0);
+        tree.flush_active_memtable(0)?;
+
+        tree.create_named_snapshot("backup", 1)?;
+
+        tree.insert("a", &new_value, 1);
+        tree.flush_active_memtable(1_000)?;
+    }
+
+    let tree = Config::new(&folder).open_as_blob_tree()?;
+
+    tree.index.major_compact(u64::MAX, 1_000)?;
+
+    let report = tree.gc_scan_stats(1_000, 1_000)?;
+    assert_eq!(0, report.stale_blobs);
+
+    let snapshot = tree.named_snapshot("backup").expect("should exist");
+    assert_eq!(Some(old_value.as_bytes().into()), snapshot.get("a")?);
+    assert_eq!(Some(new_value.as_bytes().into()), tree.get("a", None)?);
+
+    Ok(())
+}
@@ -0,0 +1,75 @@
+use lsm_tree::{AbstractTree, Config, SequenceNumberCounter};
+use test_log::test;
+
+#[test]
+fn tree_open_at() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    {
+        let tree = Config::new(&folder).open()?;
+
+        for key in ["a", "b", "c"] {
+            tree.insert(key, "old", seqno.next());
+        }
+        tree.flush_active_memtable(0)?;
+
+        tree.insert("a", "new", seqno.next());
+        tree.remove("b", seqno.next());
+        tree.flush_active_memtable(0)?;
+
+        tree.insert("d", "new", seqno.next());
+    }
+
+    let view = Config::new(&folder).open_at(3)?;
+    assert_eq!(Some("old".as_bytes().into()), view.get("a")?);
+    assert_eq!(Some("old".as_bytes().into()), view.get("b")?);
+    assert!(!view.contains_key("d")?);
+    assert_eq!(3, view.len()?);
+
+    let view = Config::new(&folder).open_at(5)?;
+    assert_eq!(Some("new".as_bytes().into()), view.get("a")?);
+    assert!(!view.contains_key("b")?);
+    assert_eq!(2, view.len()?);
+
+    let view = Config::new(&folder).open_at(0)?;
+    assert!(view.is_empty()?);
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_open_at() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let big_value = b"neptune!".repeat(128_000);
+
+    {
+        let tree = Config::new(&folder).open_as_blob_tree()?;
+        tree.insert("a", &big_value, 0);
+        tree.flush_active_memtable(0)?;
+
+        tree.insert("a", "small", 1);
+        tree.flush_active_memtable(0)?;
+    }
+
+    let view = Config::new(&folder).open_at(1)?;
+    assert_eq!(Some(big_value.into()), view.get("a")?);
+
+    let view = Config::new(&folder).open_at(2)?;
+    assert_eq!(Some("small".as_bytes().into()), view.get("a")?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_open_at_missing() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    assert!(matches!(
+        Config::new(folder.path().join("missing")).open_at(0),
+        Err(lsm_tree::Error::InvalidInput(_)),
+    ));
+    assert!(!folder.path().join("missing").try_exists()?);
+
+    Ok(())
+}
@@ -0,0 +1,88 @@
+use lsm_tree::{AbstractTree, Config, SegmentId};
+use test_log::test;
+
+const SEGMENT_COUNT: u64 = 20;
+const ITEM_COUNT: u64 = 100;
+
+fn create_tree(folder: &tempfile::TempDir) -> lsm_tree::Result<Vec<SegmentId>> {
+    let tree = Config::new(folder).open()?;
+
+    for batch in 0..SEGMENT_COUNT {
+        for x in 0..ITEM_COUNT {
+            tree.insert(x.to_be_bytes(), batch.to_string(), batch);
+        }
+        tree.flush_active_memtable(0)?;
+    }
+
+    let segment_ids = tree
+        .levels
+        .read()
+        .expect("lock is poisoned")
+        .iter()
+        .map(|x| x.id())
+        .collect();
+
+    Ok(segment_ids)
+}
+
+#[test]
+fn tree_open_threads() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let segment_ids = create_tree(&folder)?;
+
+    let tree = Config::new(&folder)
+        .open_threads(4)
+        .prefetch_index_levels(7, false)
+        .open()?;
+
+    assert_eq!(
+        segment_ids,
+        tree.levels
+            .read()
+            .expect("lock is poisoned")
+            .iter()
+            .map(|x| x.id())
+            .collect::<Vec<_>>()
+    );
+
+    assert_eq!(ITEM_COUNT as usize, tree.len(None, None)?);
+    assert_eq!(
+        (SEGMENT_COUNT - 1).to_string().as_bytes(),
+        &*tree.get(0u64.to_be_bytes(), None)?.expect("should exist")
+    );
+
+    Ok(())
+}
+
+#[test]
+fn tree_open_threads_corruption() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let segment_ids = create_tree(&folder)?;
+
+    let corrupt_id = segment_ids[SEGMENT_COUNT as usize / 2];
+
+    let segment_path = folder.path().join("segments").join(corrupt_id.to_string());
+    let file = std::fs::OpenOptions::new()
+        .write(true)
+        .open(&segment_path)?;
+    file.set_len(file.metadata()?.len() / 2)?;
+    file.sync_all()?;
+    drop(file);
+
+    match Config::new(&folder).open_threads(4).open() {
+        Err(lsm_tree::Error::Corruption { segment_id, .. }) => {
+            assert_eq!(corrupt_id, segment_id);
+        }
+        _ => panic!("should fail with corruption"),
+    }
+
+    let tree = Config::new(&folder)
+        .open_threads(4)
+        .quarantine_corrupt_segments(true)
+        .open()?;
+
+    assert_eq!(SEGMENT_COUNT as usize - 1, tree.segment_count());
+    assert_eq!(ITEM_COUNT as usize, tree.len(None, None)?);
+
+    Ok(())
+}
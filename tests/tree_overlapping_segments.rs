@@ -0,0 +1,35 @@
+use lsm_tree::{AbstractTree, Config, KeyRange};
+use test_log::test;
+
+#[test]
+fn tree_overlapping_segment_ids() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open()?;
+
+    for (idx, (min, max)) in [("a", "c"), ("d", "f"), ("g", "k")].into_iter().enumerate() {
+        tree.insert(min, "abc", idx as u64);
+        tree.insert(max, "abc", idx as u64);
+        tree.flush_active_memtable(0)?;
+    }
+    tree.major_compact(u64::MAX, 0)?;
+
+    tree.insert("b", "abc", 3);
+    tree.insert("e", "abc", 3);
+    let l0 = tree.flush_active_memtable(0)?.expect("should flush");
+
+    let range = KeyRange::new(("c".into(), "d".into()));
+    let overlapping = tree.overlapping_segment_ids(&range);
+
+    assert_eq!(tree.tree_config().level_count as usize, overlapping.len());
+    assert_eq!(vec![l0.id()], overlapping[0]);
+    assert_eq!(1, overlapping.iter().skip(1).flatten().count());
+
+    let range = KeyRange::new(("x".into(), "z".into()));
+    assert!(tree
+        .overlapping_segment_ids(&range)
+        .iter()
+        .all(Vec::is_empty));
+
+    Ok(())
+}
@@ -0,0 +1,91 @@
+use lsm_tree::{compaction::Leveled, AbstractTree, Config, SequenceNumberCounter};
+use std::sync::Arc;
+use test_log::test;
+
+const ITEM_COUNT: u64 = 1_000;
+
+fn fill(tree: &impl AbstractTree, seqno: &SequenceNumberCounter, value: &str) {
+    for x in 0..ITEM_COUNT {
+        tree.insert(x.to_be_bytes(), value, seqno.next());
+    }
+}
+
+#[test]
+fn tree_paranoid_checks_leveled() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).paranoid_checks(true).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    for _ in 0..4 {
+        fill(&tree, &seqno, "abc");
+        tree.remove(0u64.to_be_bytes(), seqno.next());
+        tree.flush_active_memtable(0)?;
+    }
+    assert_eq!(4, tree.segment_count());
+
+    let strategy = Arc::new(Leveled {
+        target_size: 1_024,
+        ..Default::default()
+    });
+
+    tree.compact(strategy.clone(), seqno.get())?;
+    assert!(tree.segment_count() > 1);
+    assert_eq!(0, tree.first_level_segment_count());
+
+    fill(&tree, &seqno, "def");
+    tree.flush_active_memtable(0)?;
+
+    for _ in 0..4 {
+        tree.compact(strategy.clone(), seqno.get())?;
+    }
+
+    tree.major_compact(u64::MAX, seqno.get())?;
+    assert_eq!(1, tree.segment_count());
+    assert_eq!(ITEM_COUNT as usize, tree.len(None, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_paranoid_checks_tiered() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).paranoid_checks(true).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    let strategy = Arc::new(lsm_tree::compaction::SizeTiered::new(1, 2));
+
+    for _ in 0..8 {
+        fill(&tree, &seqno, "abc");
+        tree.flush_active_memtable(0)?;
+        tree.compact(strategy.clone(), seqno.get())?;
+    }
+
+    assert_eq!(ITEM_COUNT as usize, tree.len(None, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_paranoid_checks() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder)
+        .paranoid_checks(true)
+        .open_as_blob_tree()?;
+
+    let big_value = "abc".repeat(10_000);
+
+    tree.insert("a", &big_value, 0);
+    tree.insert("b", "small", 1);
+    tree.flush_active_memtable(0)?;
+
+    tree.insert("a", &big_value, 2);
+    tree.flush_active_memtable(0)?;
+
+    tree.index.major_compact(u64::MAX, 3)?;
+    assert_eq!(2, tree.len(None, None)?);
+
+    Ok(())
+}
@@ -0,0 +1,52 @@
+use lsm_tree::{
+    compaction::{Choice, Fifo, Leveled},
+    AbstractTree, Config,
+};
+use test_log::test;
+
+#[test]
+fn tree_pending_work_flush_backlog() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?.into_path();
+
+    let tree = Config::new(folder).open()?;
+    let strategy = Leveled::default();
+
+    assert!(tree.pending_work(&strategy).is_idle());
+
+    tree.insert("a", "a", 0);
+    tree.insert("b", "b", 1);
+    let (_, memtable) = tree.rotate_memtable().expect("should rotate");
+
+    let pending = tree.pending_work(&strategy);
+    assert!(!pending.is_idle());
+    assert_eq!(1, pending.sealed_memtable_count);
+    assert_eq!(u64::from(memtable.size()), pending.flush_backlog_bytes);
+
+    Ok(())
+}
+
+#[test]
+fn tree_pending_work_fifo_drop() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?.into_path();
+
+    let tree = Config::new(folder).open()?;
+
+    for key in ["a", "b", "c"] {
+        tree.insert(key, key, 0);
+        tree.flush_active_memtable(0)?;
+    }
+
+    let strategy = Fifo::new(1, None);
+
+    let pending = tree.pending_work(&strategy);
+    assert!(pending.has_pending_drops());
+    assert_eq!(0, pending.sealed_memtable_count);
+    assert!(pending.pending_drop_bytes > 0);
+    assert!(matches!(pending.next_compaction, Choice::Drop(_)));
+
+    let pending = tree.pending_work(&Fifo::new(u64::MAX, None));
+    assert!(!pending.has_pending_drops());
+    assert_eq!(0, pending.pending_drop_bytes);
+
+    Ok(())
+}
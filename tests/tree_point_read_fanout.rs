@@ -0,0 +1,75 @@
+use lsm_tree::{AbstractTree, BlockingTask, Config};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering::Relaxed},
+    Arc,
+};
+use test_log::test;
+
+#[test]
+fn tree_point_read_fanout() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?.into_path();
+
+    let spawned = Arc::new(AtomicUsize::default());
+
+    let tree = Config::new(folder)
+        .point_read_fanout(4)
+        .spawn_hook(Arc::new({
+            let spawned = spawned.clone();
+
+            move |task: BlockingTask| {
+                spawned.fetch_add(1, Relaxed);
+                std::thread::spawn(task);
+            }
+        }))
+        .open()?;
+
+    let mut seqno = 0;
+
+    for round in 0..10u64 {
+        for key in ["a", "b", "c"] {
+            tree.insert(key, round.to_be_bytes(), seqno);
+            seqno += 1;
+        }
+
+        if round == 7 {
+            tree.remove("b", seqno);
+            seqno += 1;
+        }
+
+        tree.flush_active_memtable(0)?;
+    }
+
+    assert_eq!(10, tree.segment_count());
+
+    assert_eq!(Some(9u64.to_be_bytes().into()), tree.get("a", None)?,);
+    assert_eq!(Some(9u64.to_be_bytes().into()), tree.get("c", None)?,);
+    assert_eq!(None, tree.get("d", None)?);
+
+    // NOTE: Snapshot reads, b is deleted at seqno 24
+    assert_eq!(Some(7u64.to_be_bytes().into()), tree.get("b", Some(24))?,);
+    assert_eq!(None, tree.get("b", Some(25))?);
+    assert_eq!(Some(8u64.to_be_bytes().into()), tree.get("b", Some(27))?,);
+    assert_eq!(Some(0u64.to_be_bytes().into()), tree.get("a", Some(1))?,);
+
+    assert!(spawned.load(Relaxed) > 0);
+
+    Ok(())
+}
+
+#[test]
+fn tree_point_read_fanout_without_spawn_hook() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    // NOTE: Without a pooled executor, point reads stay serial
+    let tree = Config::new(&folder).point_read_fanout(4).open()?;
+
+    for seqno in 0..5 {
+        tree.insert("a", seqno.to_string(), seqno);
+        tree.flush_active_memtable(0)?;
+    }
+
+    assert_eq!(Some("4".as_bytes().into()), tree.get("a", None)?);
+    assert_eq!(Some("1".as_bytes().into()), tree.get("a", Some(2))?);
+
+    Ok(())
+}
@@ -0,0 +1,96 @@
+use lsm_tree::{AbstractTree, Config, UserKey};
+use test_log::test;
+
+const ITEM_COUNT: usize = 1_000;
+
+fn latest_under_prefix<T: AbstractTree>(
+    tree: &T,
+    prefix: &[u8],
+    n: usize,
+) -> lsm_tree::Result<Vec<UserKey>> {
+    tree.prefix(prefix, None, None)
+        .rev()
+        .take(n)
+        .map(|item| item.map(|(key, _)| key))
+        .collect()
+}
+
+fn key(prefix: &[u8], x: usize) -> Vec<u8> {
+    let mut key = prefix.to_vec();
+    key.extend_from_slice(&(x as u64).to_be_bytes());
+    key
+}
+
+#[test]
+fn tree_prefix_reverse_latest_n() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open()?;
+
+    // NOTE: The prefixes end with 0xFF, so their successor is
+    // shorter than the prefix itself
+    let prefixes: [&[u8]; 3] = [&[0, 255], &[1], &[1, 255, 255]];
+
+    let mut seqno = 0;
+
+    for x in 0..ITEM_COUNT {
+        for prefix in prefixes {
+            tree.insert(key(prefix, x), "abc", seqno);
+            seqno += 1;
+        }
+
+        if x % 100 == 99 {
+            tree.flush_active_memtable(0)?;
+        }
+    }
+
+    // NOTE: Some items stay in the active memtable
+    for x in ITEM_COUNT..ITEM_COUNT + 50 {
+        tree.insert(key(&[0, 255], x), "abc", seqno);
+        seqno += 1;
+    }
+
+    assert_eq!(
+        (ITEM_COUNT + 40..ITEM_COUNT + 50)
+            .rev()
+            .map(|x| UserKey::from(key(&[0, 255], x)))
+            .collect::<Vec<_>>(),
+        latest_under_prefix(&tree, &[0, 255], 10)?,
+    );
+
+    // NOTE: [1] also contains [1, 255, 255], which sorts last
+    assert_eq!(
+        (ITEM_COUNT - 10..ITEM_COUNT)
+            .rev()
+            .map(|x| UserKey::from(key(&[1, 255, 255], x)))
+            .collect::<Vec<_>>(),
+        latest_under_prefix(&tree, &[1], 10)?,
+    );
+
+    assert_eq!(
+        (ITEM_COUNT - 10..ITEM_COUNT)
+            .rev()
+            .map(|x| UserKey::from(key(&[1, 255, 255], x)))
+            .collect::<Vec<_>>(),
+        latest_under_prefix(&tree, &[1, 255, 255], 10)?,
+    );
+
+    tree.flush_active_memtable(0)?;
+    tree.major_compact(u64::MAX, 0)?;
+
+    assert_eq!(
+        (ITEM_COUNT + 40..ITEM_COUNT + 50)
+            .rev()
+            .map(|x| UserKey::from(key(&[0, 255], x)))
+            .collect::<Vec<_>>(),
+        latest_under_prefix(&tree, &[0, 255], 10)?,
+    );
+
+    assert_eq!(
+        ITEM_COUNT,
+        tree.prefix([1, 255, 255], None, None).rev().count()
+    );
+    assert!(latest_under_prefix(&tree, &[0, 254], 10)?.is_empty());
+
+    Ok(())
+}
@@ -0,0 +1,121 @@
+use lsm_tree::{AbstractTree, Config, QuarantineObserver, SegmentId};
+use std::{
+    io::{Seek, SeekFrom, Write},
+    path::Path,
+    sync::{Arc, Mutex},
+};
+use test_log::test;
+
+#[derive(Default)]
+struct QuarantineLog(Mutex<Vec<SegmentId>>);
+
+impl QuarantineObserver for QuarantineLog {
+    fn on_segment_quarantined(&self, segment_id: SegmentId, error: &lsm_tree::Error) {
+        assert!(error.is_corruption());
+        self.0.lock().expect("lock is poisoned").push(segment_id);
+    }
+}
+
+fn overwrite(path: &Path, offset: SeekFrom, bytes: &[u8]) -> std::io::Result<()> {
+    let mut file = std::fs::OpenOptions::new().write(true).open(path)?;
+    file.seek(offset)?;
+    file.write_all(bytes)?;
+    file.sync_all()
+}
+
+#[test]
+fn tree_quarantine_on_open() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let segment_id = {
+        let tree = Config::new(&folder).open()?;
+
+        tree.insert("a", "old", 0);
+        tree.flush_active_memtable(0)?;
+
+        tree.insert("a", "new", 1);
+        tree.insert("b", "abc", 2);
+        tree.flush_active_memtable(0)?.expect("should flush").id()
+    };
+
+    // NOTE: Cut off the index blocks (and trailer); the trailer itself
+    // is not read when opening, because it is cached in the level manifest
+    let segment_path = folder.path().join("segments").join(segment_id.to_string());
+    let file = std::fs::OpenOptions::new()
+        .write(true)
+        .open(&segment_path)?;
+    file.set_len(file.metadata()?.len() / 2)?;
+    file.sync_all()?;
+    drop(file);
+
+    match Config::new(&folder).open() {
+        Err(e @ lsm_tree::Error::Corruption { segment_id: id, .. }) => {
+            assert_eq!(segment_id, id);
+            assert_eq!(lsm_tree::ErrorKind::Corruption, e.kind());
+        }
+        _ => panic!("should fail with corruption"),
+    }
+
+    let observer = Arc::new(QuarantineLog::default());
+
+    {
+        let tree = Config::new(&folder)
+            .quarantine_corrupt_segments(true)
+            .quarantine_observer(observer.clone())
+            .open()?;
+
+        assert_eq!(1, tree.segment_count());
+        assert_eq!(&*tree.get("a", None)?.expect("should exist"), b"old");
+        assert!(!tree.contains_key("b", None)?);
+    }
+
+    assert_eq!(
+        vec![segment_id],
+        *observer.0.lock().expect("lock is poisoned")
+    );
+    assert!(!segment_path.try_exists()?);
+    assert!(folder
+        .path()
+        .join("quarantine")
+        .join(segment_id.to_string())
+        .try_exists()?);
+
+    // NOTE: The quarantined segment is not referenced anymore
+    let tree = Config::new(&folder).open()?;
+    assert_eq!(1, tree.segment_count());
+
+    Ok(())
+}
+
+#[test]
+fn tree_quarantine_on_read() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let observer = Arc::new(QuarantineLog::default());
+
+    let tree = Config::new(&folder)
+        .quarantine_corrupt_segments(true)
+        .quarantine_observer(observer.clone())
+        .open()?;
+
+    tree.insert("a", "old", 0);
+    tree.flush_active_memtable(0)?;
+
+    tree.insert("a", "new".repeat(100), 1);
+    let segment_id = tree.flush_active_memtable(0)?.expect("should flush").id();
+
+    // NOTE: Break the items of the first block
+    let segment_path = folder.path().join("segments").join(segment_id.to_string());
+    overwrite(&segment_path, SeekFrom::Start(30), &[0xFF; 8])?;
+
+    assert_eq!(&*tree.get("a", None)?.expect("should exist"), b"old");
+    assert_eq!(1, tree.segment_count());
+
+    assert_eq!(
+        vec![segment_id],
+        *observer.0.lock().expect("lock is poisoned")
+    );
+    assert!(!segment_path.try_exists()?);
+
+    Ok(())
+}
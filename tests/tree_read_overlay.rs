@@ -0,0 +1,101 @@
+use lsm_tree::{AbstractTree, Config, KvPair, ReadOverlay, UserKey, UserValue};
+use test_log::test;
+
+fn collect(iter: impl Iterator<Item = lsm_tree::Result<KvPair>>) -> lsm_tree::Result<Vec<KvPair>> {
+    iter.collect()
+}
+
+#[test]
+fn tree_read_overlay_snapshot() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open()?;
+
+    tree.insert("a", "old", 0);
+    tree.insert("b", "old", 1);
+    tree.flush_active_memtable(0)?;
+    tree.insert("c", "old", 2);
+
+    let overlay = ReadOverlay::default();
+    overlay.insert("a", "overlay");
+    overlay.remove("c");
+    overlay.insert("d", "overlay");
+
+    // NOTE: Committed after the snapshot, so not visible
+    tree.insert("b", "new", 3);
+    tree.insert("e", "new", 4);
+
+    let seqno = Some(3);
+
+    assert_eq!(
+        &*tree
+            .get_with_overlay("a", seqno, &overlay)?
+            .expect("should exist"),
+        b"overlay"
+    );
+    assert_eq!(
+        &*tree
+            .get_with_overlay("b", seqno, &overlay)?
+            .expect("should exist"),
+        b"old"
+    );
+    assert!(tree.get_with_overlay("c", seqno, &overlay)?.is_none());
+    assert!(tree.get_with_overlay("e", seqno, &overlay)?.is_none());
+
+    let keys: Vec<lsm_tree::UserKey> =
+        collect(tree.range_with_overlay::<&str, _>(.., seqno, &overlay))?
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect::<Vec<_>>();
+    assert_eq!(
+        vec![UserKey::from("a"), UserKey::from("b"), UserKey::from("d")],
+        keys
+    );
+
+    let items = collect(
+        tree.range_with_overlay::<&str, _>(.., seqno, &overlay)
+            .rev(),
+    )?;
+    assert_eq!(3, items.len());
+    assert_eq!(b"d", &*items.first().expect("should exist").0);
+
+    assert_eq!(1, tree.prefix_with_overlay("d", seqno, &overlay).count());
+    assert_eq!(0, tree.prefix_with_overlay("c", seqno, &overlay).count());
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_read_overlay() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let big_value = "abc".repeat(10_000);
+
+    let tree = Config::new(&folder).open_as_blob_tree()?;
+    tree.insert("a", &big_value, 0);
+    tree.insert("b", &big_value, 1);
+    tree.flush_active_memtable(0)?;
+
+    let overlay = ReadOverlay::default();
+    overlay.insert("b", "small");
+    overlay.insert("c", &big_value);
+
+    let items = collect(tree.range_with_overlay::<&str, _>(.., None, &overlay))?;
+    assert_eq!(
+        vec![
+            (UserKey::from("a"), UserValue::from(big_value.as_str())),
+            (UserKey::from("b"), UserValue::from("small")),
+            (UserKey::from("c"), UserValue::from(big_value.as_str())),
+        ],
+        items
+    );
+
+    assert_eq!(
+        &*tree
+            .get_with_overlay("a", None, &overlay)?
+            .expect("should exist"),
+        big_value.as_bytes()
+    );
+
+    Ok(())
+}
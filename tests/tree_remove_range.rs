@@ -0,0 +1,269 @@
+use lsm_tree::{AbstractTree, Config, SequenceNumberCounter};
+use test_log::test;
+
+const ITEM_COUNT: u64 = 100;
+
+fn key(x: u64) -> [u8; 8] {
+    x.to_be_bytes()
+}
+
+#[test]
+fn tree_remove_range_memtable() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    for x in 0..ITEM_COUNT {
+        tree.insert(key(x), "abc", seqno.next());
+    }
+
+    let snapshot_seqno = seqno.get();
+    tree.remove_range(key(10)..key(20), seqno.next());
+
+    // NOTE: Empty ranges are ignored
+    tree.remove_range(key(50)..key(50), seqno.next());
+
+    assert!(tree.contains_key(key(9), None)?);
+    assert!(!tree.contains_key(key(10), None)?);
+    assert!(!tree.contains_key(key(19), None)?);
+    assert!(tree.contains_key(key(20), None)?);
+    assert!(tree.contains_key(key(50), None)?);
+
+    assert_eq!(ITEM_COUNT as usize - 10, tree.len(None, None)?);
+    assert_eq!(
+        ITEM_COUNT as usize - 10,
+        tree.iter(None, None).rev().count()
+    );
+    assert_eq!(0, tree.range(key(10)..key(20), None, None).count());
+    assert_eq!(5, tree.range(key(5)..key(15), None, None).count());
+
+    // NOTE: The snapshot was taken before the range tombstone was written
+    assert!(tree.contains_key(key(10), Some(snapshot_seqno))?);
+    assert_eq!(ITEM_COUNT as usize, tree.len(Some(snapshot_seqno), None)?);
+
+    // NOTE: Newer writes are not deleted
+    tree.insert(key(15), "new", seqno.next());
+    assert!(tree.contains_key(key(15), None)?);
+    assert_eq!(ITEM_COUNT as usize - 9, tree.len(None, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_remove_range_flush() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    {
+        let tree = Config::new(&folder).open()?;
+
+        for x in 0..ITEM_COUNT {
+            tree.insert(key(x), "abc", seqno.next());
+        }
+        tree.flush_active_memtable(0)?;
+
+        // NOTE: The range tombstone is flushed into a segment of its own
+        tree.remove_range(key(10)..key(20), seqno.next());
+        tree.flush_active_memtable(0)?;
+        assert_eq!(2, tree.segment_count());
+
+        assert!(!tree.contains_key(key(10), None)?);
+        assert_eq!(ITEM_COUNT as usize - 10, tree.len(None, None)?);
+    }
+
+    let tree = Config::new(&folder).open()?;
+    assert_eq!(2, tree.segment_count());
+
+    assert!(tree.contains_key(key(9), None)?);
+    assert!(!tree.contains_key(key(10), None)?);
+    assert!(!tree.contains_key(key(19), None)?);
+    assert!(tree.contains_key(key(20), None)?);
+    assert_eq!(ITEM_COUNT as usize - 10, tree.len(None, None)?);
+    assert_eq!(0, tree.range(key(10)..key(20), None, None).count());
+
+    Ok(())
+}
+
+#[test]
+fn tree_remove_range_compaction() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    for x in 0..ITEM_COUNT {
+        tree.insert(key(x), "abc", seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+
+    tree.remove_range(key(10)..key(20), seqno.next());
+    tree.remove(key(50), seqno.next());
+    tree.flush_active_memtable(0)?;
+
+    // NOTE: The snapshot still reads the deleted items, so they are kept
+    let snapshot = tree.snapshot(seqno.get() - 2);
+    tree.major_compact(u64::MAX, seqno.get())?;
+    assert_eq!(1, tree.segment_count());
+    assert_eq!(ITEM_COUNT as usize, snapshot.len()?);
+    assert_eq!(ITEM_COUNT as usize - 11, tree.len(None, None)?);
+    drop(snapshot);
+
+    tree.major_compact(u64::MAX, seqno.get())?;
+    assert_eq!(1, tree.segment_count());
+    assert_eq!(ITEM_COUNT as usize - 11, tree.len(None, None)?);
+
+    // NOTE: The covered items and the range tombstone are gone
+    let segment = tree
+        .levels
+        .read()
+        .expect("lock is poisoned")
+        .iter()
+        .next()
+        .cloned()
+        .expect("should exist");
+    assert_eq!(ITEM_COUNT - 11, segment.metadata.item_count);
+    assert!(segment.range_tombstones()?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn tree_remove_range_compaction_keeps_tombstone() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    for x in 0..ITEM_COUNT {
+        tree.insert(key(x), "abc", seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+    tree.major_compact(u64::MAX, seqno.get())?;
+
+    tree.remove_range(key(10)..key(20), seqno.next());
+    tree.flush_active_memtable(0)?;
+
+    // NOTE: The tombstone is not compacted into the last level, so it is kept
+    tree.compact(
+        std::sync::Arc::new(lsm_tree::compaction::PullDown(0, 1)),
+        seqno.get(),
+    )?;
+    assert_eq!(ITEM_COUNT as usize - 10, tree.len(None, None)?);
+
+    let tree = Config::new(&folder).open()?;
+    assert_eq!(ITEM_COUNT as usize - 10, tree.len(None, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_remove_range() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open_as_blob_tree()?;
+    let seqno = SequenceNumberCounter::default();
+
+    let big_value = "a".repeat(10_000);
+
+    for x in 0..ITEM_COUNT {
+        tree.insert(key(x), &big_value, seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+
+    tree.remove_range(key(10)..key(20), seqno.next());
+    assert!(!tree.contains_key(key(10), None)?);
+    assert_eq!(ITEM_COUNT as usize - 10, tree.len(None, None)?);
+
+    tree.flush_active_memtable(0)?;
+    assert!(!tree.contains_key(key(10), None)?);
+    assert_eq!(None, tree.get(key(15), None)?);
+    assert_eq!(ITEM_COUNT as usize - 10, tree.len(None, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_remove_range_only_tombstones() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let seqno = SequenceNumberCounter::default();
+
+    {
+        let tree = Config::new(&folder).open()?;
+
+        for x in 0..ITEM_COUNT {
+            tree.insert(key(x), "abc", seqno.next());
+        }
+        tree.flush_active_memtable(0)?;
+        tree.compact(
+            std::sync::Arc::new(lsm_tree::compaction::PullDown(0, 6)),
+            seqno.get(),
+        )?;
+
+        // NOTE: The segment of the range tombstone does not contain any items
+        tree.remove_range(key(10)..key(20), seqno.next());
+        tree.flush_active_memtable(0)?;
+        tree.compact(
+            std::sync::Arc::new(lsm_tree::compaction::PullDown(0, 5)),
+            seqno.get(),
+        )?;
+        assert_eq!(2, tree.segment_count());
+        assert_eq!(ITEM_COUNT as usize - 10, tree.len(None, None)?);
+    }
+
+    let tree = Config::new(&folder).open()?;
+    assert_eq!(2, tree.segment_count());
+    assert!(!tree.contains_key(key(10), None)?);
+    assert_eq!(ITEM_COUNT as usize - 10, tree.len(None, None)?);
+    assert_eq!(
+        ITEM_COUNT as usize - 10,
+        tree.iter(None, None).rev().count()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn tree_remove_range_compaction_split() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    let value = "a".repeat(1_000);
+
+    for x in 0..ITEM_COUNT {
+        tree.insert(key(x), &value, seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+
+    tree.remove_range(key(10)..key(90), seqno.next());
+    tree.flush_active_memtable(0)?;
+
+    // NOTE: The snapshot keeps the deleted items (and thus the range tombstone) alive
+    let snapshot = tree.snapshot(seqno.get() - 1);
+    tree.major_compact(10_000, seqno.get())?;
+    assert!(tree.segment_count() > 2);
+
+    assert_eq!(ITEM_COUNT as usize, snapshot.len()?);
+    assert_eq!(ITEM_COUNT as usize - 80, tree.len(None, None)?);
+    assert!(!tree.contains_key(key(50), None)?);
+
+    // NOTE: Every segment only holds the part of the tombstone that covers its keys
+    let mut fragments = tree
+        .levels
+        .read()
+        .expect("lock is poisoned")
+        .iter()
+        .map(|segment| segment.range_tombstones().map(<[_]>::to_vec))
+        .collect::<lsm_tree::Result<Vec<_>>>()?
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    fragments.sort();
+
+    assert!(fragments.len() > 1);
+    assert_eq!(&key(10), &*fragments.first().expect("should exist").start);
+    assert_eq!(&key(90), &*fragments.last().expect("should exist").end);
+
+    for pair in fragments.windows(2) {
+        assert_eq!(pair[0].end, pair[1].start);
+    }
+
+    Ok(())
+}
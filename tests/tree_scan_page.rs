@@ -0,0 +1,182 @@
+use lsm_tree::{
+    coding::{Decode, Encode},
+    AbstractTree, Config, ScanCursor,
+};
+use test_log::test;
+
+const ITEM_COUNT: usize = 100;
+
+#[test]
+fn tree_scan_page() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open()?;
+
+    for x in 0..ITEM_COUNT as u64 {
+        tree.insert(x.to_be_bytes(), "abc", x);
+    }
+    tree.flush_active_memtable(0)?;
+
+    let mut cursor = ScanCursor::new(ITEM_COUNT as u64);
+    let mut keys = vec![];
+    let mut pages = 0;
+
+    loop {
+        let page = tree.scan_page::<&[u8], _>(.., &cursor, 7)?;
+        pages += 1;
+
+        keys.extend(page.items.into_iter().map(|(key, _)| key));
+
+        // NOTE: Newer writes are not visible to the scan
+        tree.insert(u64::MAX.to_be_bytes(), "abc", ITEM_COUNT as u64 + pages);
+
+        match page.next {
+            Some(next) => cursor = next,
+            None => break,
+        }
+    }
+
+    assert_eq!(ITEM_COUNT.div_ceil(7), pages as usize);
+    assert_eq!(
+        (0..ITEM_COUNT as u64)
+            .map(|x| x.to_be_bytes().into())
+            .collect::<Vec<lsm_tree::UserKey>>(),
+        keys,
+    );
+
+    Ok(())
+}
+
+#[test]
+fn tree_scan_page_range() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open()?;
+
+    for key in ["a", "b", "c", "d", "e"] {
+        tree.insert(key, "abc", 0);
+    }
+
+    let page = tree.scan_page("b".."e", &ScanCursor::new(1), 2)?;
+    assert_eq!(2, page.items.len());
+    assert_eq!(&*page.items[0].0, b"b");
+
+    let page = tree.scan_page("b".."e", &page.next.expect("should exist"), 2)?;
+    assert_eq!(1, page.items.len());
+    assert_eq!(&*page.items[0].0, b"d");
+    assert!(page.next.is_none());
+
+    // NOTE: An exact fit does not need an empty last page
+    let page = tree.scan_page("b".."d", &ScanCursor::new(1), 2)?;
+    assert_eq!(2, page.items.len());
+    assert!(page.next.is_none());
+
+    Ok(())
+}
+
+#[test]
+fn tree_scan_page_resume_after_reopen() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let token = {
+        let tree = Config::new(&folder).open()?;
+
+        for key in ["a", "b", "c", "d"] {
+            tree.insert(key, "abc", 0);
+        }
+        tree.flush_active_memtable(0)?;
+
+        let page = tree.scan_page::<&str, _>(.., &ScanCursor::new(1), 2)?;
+        page.next.expect("should exist").encode_into_vec()
+    };
+
+    let tree = Config::new(&folder).open()?;
+
+    let cursor = ScanCursor::decode_from(&mut &token[..])?;
+    let page = tree.scan_page::<&str, _>(.., &cursor, 2)?;
+
+    assert_eq!(
+        vec![b"c".as_slice(), b"d".as_slice()],
+        page.items.iter().map(|(key, _)| &**key).collect::<Vec<_>>(),
+    );
+    assert!(page.next.is_none());
+
+    Ok(())
+}
+
+#[test]
+#[should_panic = "page limit should be at least 1"]
+fn tree_scan_page_zero_limit() {
+    let folder = tempfile::tempdir().expect("should create folder");
+    let tree = Config::new(&folder).open().expect("should open");
+
+    let _ = tree.scan_page::<&str, _>(.., &ScanCursor::new(0), 0);
+}
+
+#[test]
+fn tree_scan_page_named_snapshot() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open()?;
+
+    for x in 0..ITEM_COUNT as u64 {
+        tree.insert(x.to_be_bytes(), "abc", x);
+    }
+    tree.flush_active_memtable(0)?;
+
+    let seqno = ITEM_COUNT as u64;
+    tree.create_named_snapshot("scan", seqno)?;
+
+    let page = tree.scan_page::<&[u8], _>(.., &ScanCursor::new(seqno), 10)?;
+    let cursor = page.next.expect("should have more").encode_into_vec();
+
+    // NOTE: The named snapshot keeps the old versions through compaction and reopening
+    for x in 0..ITEM_COUNT as u64 {
+        tree.insert(x.to_be_bytes(), "new", seqno + x);
+    }
+    tree.flush_active_memtable(0)?;
+    tree.major_compact(u64::MAX, 2 * seqno)?;
+    drop(tree);
+
+    let tree = Config::new(&folder).open()?;
+    let cursor = ScanCursor::decode_from(&mut &cursor[..])?;
+
+    let page = tree.scan_page::<&[u8], _>(.., &cursor, ITEM_COUNT)?;
+    assert!(page.pinned);
+    assert_eq!(ITEM_COUNT - 10, page.items.len());
+    assert!(page.items.iter().all(|(_, value)| &**value == b"abc"));
+
+    assert!(tree.release_named_snapshot("scan")?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_scan_page_unpinned() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open()?;
+
+    for key in ["a", "b", "c", "d"] {
+        tree.insert(key, "old", 0);
+    }
+    tree.flush_active_memtable(0)?;
+
+    let page = tree.scan_page::<&str, _>(.., &ScanCursor::new(1), 2)?;
+    assert!(!page.pinned);
+    let cursor = page.next.expect("should have more");
+
+    // NOTE: Without a snapshot, compaction drops the versions the cursor reads
+    tree.insert("c", "new", 1);
+    tree.flush_active_memtable(0)?;
+    tree.major_compact(u64::MAX, tree.gc_watermark())?;
+
+    let page = tree.scan_page::<&str, _>(.., &cursor, 2)?;
+    assert!(!page.pinned);
+    assert_eq!(
+        vec![b"d".as_slice()],
+        page.items.iter().map(|(key, _)| &**key).collect::<Vec<_>>(),
+    );
+
+    Ok(())
+}
@@ -0,0 +1,63 @@
+use lsm_tree::{AbstractTree, BlockCache, Config, Statistics};
+use std::{sync::Arc, time::Duration};
+use test_log::test;
+
+const ITEM_COUNT: usize = 5_000;
+
+fn scan(config: Config, prefetch_blocks: usize) -> lsm_tree::Result<(Vec<u64>, u64)> {
+    let stats = Arc::new(Statistics::default());
+
+    let tree = config
+        .block_cache(Arc::new(BlockCache::with_capacity_bytes(
+            64 * 1_024 * 1_024,
+        )))
+        .statistics(stats.clone())
+        .scan_prefetch_blocks(prefetch_blocks)
+        .open()?;
+
+    let mut iter = tree.iter(None, None);
+    let mut keys = vec![];
+
+    for kv in iter.by_ref().take(100) {
+        let (key, _) = kv?;
+        keys.push(u64::from_be_bytes(
+            (*key).try_into().expect("should be u64"),
+        ));
+    }
+
+    // NOTE: Give the read-ahead some time to finish
+    std::thread::sleep(Duration::from_millis(250));
+
+    for kv in iter {
+        let (key, _) = kv?;
+        keys.push(u64::from_be_bytes(
+            (*key).try_into().expect("should be u64"),
+        ));
+    }
+
+    Ok((keys, stats.block_cache_hits()))
+}
+
+#[test]
+fn tree_scan_prefetch() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    {
+        let tree = Config::new(&folder).data_block_size(1_024).open()?;
+
+        for x in 0..ITEM_COUNT as u64 {
+            tree.insert(x.to_be_bytes(), x.to_string().repeat(10), 0);
+        }
+        tree.flush_active_memtable(0)?;
+    }
+
+    let (keys, hits_without_prefetch) = scan(Config::new(&folder), 0)?;
+    assert_eq!((0..ITEM_COUNT as u64).collect::<Vec<_>>(), keys);
+
+    let (keys, hits_with_prefetch) = scan(Config::new(&folder), 8)?;
+    assert_eq!((0..ITEM_COUNT as u64).collect::<Vec<_>>(), keys);
+
+    assert!(hits_with_prefetch > hits_without_prefetch);
+
+    Ok(())
+}
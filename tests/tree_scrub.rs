@@ -0,0 +1,213 @@
+use lsm_tree::{AbstractTree, Config, QuarantineObserver, SegmentId};
+use std::{
+    io::{Seek, SeekFrom, Write},
+    path::Path,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use test_log::test;
+
+#[derive(Default)]
+struct CorruptionLog {
+    quarantined: Mutex<Vec<SegmentId>>,
+    corrupted: Mutex<Vec<SegmentId>>,
+    corrupted_blob_files: Mutex<Vec<SegmentId>>,
+}
+
+impl QuarantineObserver for CorruptionLog {
+    fn on_segment_quarantined(&self, segment_id: SegmentId, error: &lsm_tree::Error) {
+        assert!(error.is_corruption());
+        self.quarantined
+            .lock()
+            .expect("lock is poisoned")
+            .push(segment_id);
+    }
+
+    fn on_segment_corrupted(&self, segment_id: SegmentId, error: &lsm_tree::Error) {
+        assert!(error.is_corruption());
+        self.corrupted
+            .lock()
+            .expect("lock is poisoned")
+            .push(segment_id);
+    }
+
+    fn on_blob_file_corrupted(&self, blob_file_id: SegmentId, error: &lsm_tree::Error) {
+        assert!(error.is_corruption());
+        self.corrupted_blob_files
+            .lock()
+            .expect("lock is poisoned")
+            .push(blob_file_id);
+    }
+}
+
+fn overwrite(path: &Path, offset: u64, bytes: &[u8]) -> std::io::Result<()> {
+    let mut file = std::fs::OpenOptions::new().write(true).open(path)?;
+    file.seek(SeekFrom::Start(offset))?;
+    file.write_all(bytes)?;
+    file.sync_all()
+}
+
+fn fill(tree: &dyn lsm_tree::DynTree, value_size: usize) -> lsm_tree::Result<SegmentId> {
+    lsm_tree::DynTree::insert(tree, "a".into(), "x".repeat(value_size).into(), 0);
+    lsm_tree::DynTree::insert(tree, "b".into(), "x".repeat(value_size).into(), 0);
+    Ok(lsm_tree::DynTree::flush_active_memtable(tree, 0)?
+        .expect("should flush")
+        .id())
+}
+
+#[test]
+fn tree_scrub_report() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let observer = Arc::new(CorruptionLog::default());
+
+    let tree = Config::new(&folder)
+        .quarantine_observer(observer.clone())
+        .open()?;
+
+    fill(&tree, 100)?;
+    let segment_id = fill(&tree, 1_000)?;
+
+    let report = tree.scrub()?;
+    assert_eq!(2, report.segments_checked);
+    assert!(report.bytes_checked > 2_000);
+    assert!(report.is_clean());
+
+    // NOTE: Flip bytes inside of the first value
+    let segment_path = folder.path().join("segments").join(segment_id.to_string());
+    overwrite(&segment_path, 200, b"yyyy")?;
+
+    let report = tree.scrub()?;
+    assert_eq!(2, report.segments_checked);
+    assert_eq!(vec![segment_id], report.corrupt_segments);
+    assert!(report.quarantined_segments.is_empty());
+    assert!(!report.is_clean());
+
+    // NOTE: Without quarantine, the corrupt segment is only reported
+    assert_eq!(
+        vec![segment_id],
+        *observer.corrupted.lock().expect("lock is poisoned")
+    );
+    assert!(observer
+        .quarantined
+        .lock()
+        .expect("lock is poisoned")
+        .is_empty());
+    assert_eq!(2, tree.segment_count());
+    assert!(segment_path.try_exists()?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_scrub_quarantine() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let observer = Arc::new(CorruptionLog::default());
+
+    let tree = Config::new(&folder)
+        .quarantine_corrupt_segments(true)
+        .quarantine_observer(observer.clone())
+        .open()?;
+
+    let segment_id = fill(&tree, 1_000)?;
+
+    let segment_path = folder.path().join("segments").join(segment_id.to_string());
+    overwrite(&segment_path, 200, b"yyyy")?;
+
+    let report = tree.scrub()?;
+    assert_eq!(vec![segment_id], report.corrupt_segments);
+    assert_eq!(vec![segment_id], report.quarantined_segments);
+
+    assert_eq!(
+        vec![segment_id],
+        *observer.quarantined.lock().expect("lock is poisoned")
+    );
+    assert_eq!(0, tree.segment_count());
+    assert!(!segment_path.try_exists()?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_scrub_blob_file() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let observer = Arc::new(CorruptionLog::default());
+
+    let tree = Config::new(&folder)
+        .quarantine_observer(observer.clone())
+        .open_as_blob_tree()?;
+
+    fill(&tree, 10_000)?;
+
+    let report = tree.scrub()?;
+    assert_eq!(1, report.segments_checked);
+    assert_eq!(1, report.blob_files_checked);
+    assert!(report.is_clean());
+
+    let blob_file_id = *tree
+        .blobs
+        .manifest
+        .list_segment_ids()
+        .first()
+        .expect("should exist");
+
+    let blob_file_path = folder
+        .path()
+        .join("blobs")
+        .join("segments")
+        .join(blob_file_id.to_string());
+    overwrite(&blob_file_path, 1_000, b"yyyy")?;
+
+    let report = tree.scrub()?;
+    assert_eq!(vec![blob_file_id], report.corrupt_blob_files);
+    assert!(report.corrupt_segments.is_empty());
+
+    assert_eq!(
+        vec![blob_file_id],
+        *observer
+            .corrupted_blob_files
+            .lock()
+            .expect("lock is poisoned")
+    );
+
+    Ok(())
+}
+
+#[test]
+fn tree_scrub_background() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let observer = Arc::new(CorruptionLog::default());
+
+    let segment_id = {
+        let tree = Config::new(&folder).open()?;
+        fill(&tree, 1_000)?
+    };
+
+    let segment_path = folder.path().join("segments").join(segment_id.to_string());
+    overwrite(&segment_path, 200, b"yyyy")?;
+
+    let tree = Config::new(&folder)
+        .quarantine_corrupt_segments(true)
+        .quarantine_observer(observer.clone())
+        .scrub_period(Duration::from_millis(50))
+        .open()?;
+
+    let start = Instant::now();
+
+    while observer
+        .quarantined
+        .lock()
+        .expect("lock is poisoned")
+        .is_empty()
+    {
+        assert!(
+            start.elapsed() < Duration::from_secs(10),
+            "scrubber should quarantine the segment"
+        );
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    assert_eq!(0, tree.segment_count());
+    assert!(!segment_path.try_exists()?);
+
+    Ok(())
+}
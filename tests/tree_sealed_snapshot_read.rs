@@ -0,0 +1,63 @@
+use lsm_tree::{AbstractTree, BlockingTask, Config};
+use std::sync::Arc;
+use test_log::test;
+
+const MEMTABLE_COUNT: u64 = 10;
+
+#[test]
+fn tree_sealed_memtable_snapshot_read() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open()?;
+
+    tree.insert("a", "0", 0);
+    tree.flush_active_memtable(0)?;
+
+    for seqno in 1..=MEMTABLE_COUNT {
+        tree.insert("a", seqno.to_string(), seqno);
+        tree.rotate_memtable();
+    }
+
+    tree.insert("a", "active", MEMTABLE_COUNT + 1);
+
+    assert_eq!(MEMTABLE_COUNT as usize, tree.sealed_memtable_count());
+
+    assert_eq!(&*tree.get("a", None)?.expect("should exist"), b"active");
+
+    for seqno in 0..=MEMTABLE_COUNT {
+        assert_eq!(
+            &*tree.get("a", Some(seqno + 1))?.expect("should exist"),
+            seqno.to_string().as_bytes(),
+        );
+    }
+
+    assert!(tree.get("a", Some(0))?.is_none());
+
+    Ok(())
+}
+
+#[test]
+fn tree_fanout_point_read_stops_at_newest() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder)
+        .point_read_fanout(2)
+        .spawn_hook(Arc::new(|task: BlockingTask| {
+            std::thread::spawn(task);
+        }))
+        .open()?;
+
+    for seqno in 0..MEMTABLE_COUNT {
+        tree.insert("a", seqno.to_string(), seqno);
+        tree.flush_active_memtable(0)?;
+    }
+
+    assert_eq!(
+        &*tree.get("a", None)?.expect("should exist"),
+        (MEMTABLE_COUNT - 1).to_string().as_bytes(),
+    );
+    assert_eq!(&*tree.get("a", Some(4))?.expect("should exist"), b"3");
+    assert!(tree.get("b", None)?.is_none());
+
+    Ok(())
+}
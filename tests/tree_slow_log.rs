@@ -0,0 +1,148 @@
+use lsm_tree::{AbstractTree, Config, SlowOperation, SlowOperationKind, SlowOperationObserver};
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use test_log::test;
+
+#[test]
+fn tree_slow_log() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?.into_path();
+
+    // NOTE: Every operation is "slow", so every logging path is exercised
+    let tree = Config::new(folder)
+        .slow_operation_threshold(Duration::ZERO)
+        .open()?;
+
+    for key in ["a", "b", "c"] {
+        tree.insert(key, key, 0);
+        tree.flush_active_memtable(0)?;
+    }
+
+    assert!(tree.get("a", None)?.is_some());
+    assert!(tree.get("a", Some(1))?.is_some());
+    assert_eq!(2, tree.range("a"..="b", None, None).count());
+    assert_eq!(1, tree.prefix("c", None, None).count());
+
+    tree.major_compact(u64::MAX, 0)?;
+    assert_eq!(3, tree.len(None, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_slow_log() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?.into_path();
+
+    let tree = Config::new(folder)
+        .slow_operation_threshold(Duration::ZERO)
+        .open_as_blob_tree()?;
+
+    tree.insert("a", "a".repeat(10_000), 0);
+    tree.flush_active_memtable(0)?;
+
+    assert!(tree.get("a", None)?.is_some());
+    assert!(tree.get("b", None)?.is_none());
+    assert_eq!(1, tree.range("a"..="b", None, None).count());
+    assert_eq!(1, tree.prefix("a", None, None).count());
+
+    Ok(())
+}
+
+#[derive(Default)]
+struct Reports(Mutex<Vec<SlowOperation>>);
+
+impl SlowOperationObserver for Reports {
+    fn on_slow_operation(&self, operation: &SlowOperation) {
+        self.0
+            .lock()
+            .expect("lock is poisoned")
+            .push(operation.clone());
+    }
+}
+
+impl Reports {
+    fn take(&self) -> Vec<SlowOperation> {
+        std::mem::take(&mut *self.0.lock().expect("lock is poisoned"))
+    }
+}
+
+#[test]
+fn tree_slow_log_observer() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let reports = Arc::new(Reports::default());
+
+    let tree = Config::new(&folder)
+        .slow_operation_threshold(Duration::ZERO)
+        .slow_operation_observer(reports.clone())
+        .open()?;
+
+    tree.insert("a", "a", 0);
+    tree.insert("z", "z", 0);
+    tree.flush_active_memtable(0)?;
+
+    tree.insert("b", "b", 1);
+    tree.insert("y", "y", 1);
+    tree.flush_active_memtable(0)?;
+    reports.take();
+
+    // NOTE: Both segments overlap the key, but their bloom filters rule it out
+    assert!(tree.get("c", None)?.is_none());
+    let [report] = &reports.take()[..] else {
+        panic!("should report get");
+    };
+    assert_eq!(SlowOperationKind::Get { key: "c".into() }, report.kind);
+    assert!(report.segments_read.is_empty());
+
+    assert!(tree.get("a", None)?.is_some());
+    assert_eq!(1, reports.take()[0].segments_read.len());
+
+    assert_eq!(
+        vec![None, Some("b".into())],
+        tree.get_many(["c", "b"], Some(5))?
+    );
+    let [report] = &reports.take()[..] else {
+        panic!("should report get_many");
+    };
+    assert_eq!(SlowOperationKind::GetMany { key_count: 2 }, report.kind);
+    assert_eq!(Some(5), report.seqno);
+    assert_eq!(1, report.segments_read.len());
+
+    // NOTE: Nothing is reported until the scan is dropped
+    let mut iter = tree.range("a"..="b", None, None);
+    assert!(iter.next().is_some());
+    assert!(reports.take().is_empty());
+    assert_eq!(1, iter.count());
+
+    let [report] = &reports.take()[..] else {
+        panic!("should report scan");
+    };
+    assert!(matches!(report.kind, SlowOperationKind::Scan { .. }));
+    assert_eq!(2, report.segments_read.len());
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_slow_log_get_many() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let reports = Arc::new(Reports::default());
+
+    let tree = Config::new(&folder)
+        .slow_operation_threshold(Duration::ZERO)
+        .slow_operation_observer(reports.clone())
+        .open_as_blob_tree()?;
+
+    tree.insert("a", "a".repeat(10_000), 0);
+    tree.flush_active_memtable(0)?;
+    reports.take();
+
+    assert_eq!(2, tree.get_many(["a", "b"], None)?.len());
+    let [report] = &reports.take()[..] else {
+        panic!("should report get_many");
+    };
+    assert_eq!(SlowOperationKind::GetMany { key_count: 2 }, report.kind);
+    assert_eq!(1, report.segments_read.len());
+
+    Ok(())
+}
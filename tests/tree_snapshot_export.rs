@@ -0,0 +1,164 @@
+use lsm_tree::{AbstractTree, Config, KvPair};
+use test_log::test;
+
+const ITEM_COUNT: usize = 100;
+
+fn collect(iter: impl Iterator<Item = lsm_tree::Result<KvPair>>) -> lsm_tree::Result<Vec<KvPair>> {
+    iter.collect()
+}
+
+#[test]
+fn tree_snapshot_export() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let export_folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open()?;
+
+    for x in 0..ITEM_COUNT as u64 {
+        tree.insert(x.to_be_bytes(), "old", x);
+    }
+    tree.flush_active_memtable(0)?;
+    tree.major_compact(u64::MAX, 0)?;
+
+    let seqno = ITEM_COUNT as u64;
+    tree.insert(0u64.to_be_bytes(), "new", seqno);
+    tree.remove(1u64.to_be_bytes(), seqno + 1);
+    tree.flush_active_memtable(0)?;
+
+    // NOTE: Visible to the snapshot, but not flushed yet
+    tree.insert(2u64.to_be_bytes(), "new", seqno + 2);
+
+    let snapshot = tree.snapshot(seqno + 2);
+    tree.insert(3u64.to_be_bytes(), "newer", seqno + 3);
+
+    snapshot.export(&export_folder)?;
+
+    let expected = collect(snapshot.iter())?;
+    drop(snapshot);
+    drop(tree);
+
+    let exported = Config::new(&export_folder).open()?;
+    assert_eq!(expected, collect(exported.iter(None, None))?);
+
+    assert_eq!(
+        b"new",
+        &*exported
+            .get(0u64.to_be_bytes(), None)?
+            .expect("should exist"),
+    );
+    assert!(!exported.contains_key(1u64.to_be_bytes(), None)?);
+    assert_eq!(
+        b"old",
+        &*exported
+            .get(3u64.to_be_bytes(), None)?
+            .expect("should exist"),
+    );
+    assert_eq!(ITEM_COUNT - 1, exported.len(None, None)?);
+
+    // NOTE: Older versions are still in the exported tree
+    assert_eq!(
+        b"old",
+        &*exported
+            .get(0u64.to_be_bytes(), Some(seqno))?
+            .expect("should exist"),
+    );
+
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn tree_snapshot_export_hard_link() -> lsm_tree::Result<()> {
+    use std::os::unix::fs::MetadataExt;
+
+    let folder = tempfile::tempdir()?;
+    let export_folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open()?;
+
+    tree.insert("a", "abc", 0);
+    let old = tree.flush_active_memtable(0)?.expect("should flush");
+
+    tree.insert("b", "abc", 1);
+    let new = tree.flush_active_memtable(0)?.expect("should flush");
+
+    tree.snapshot(1).export(&export_folder)?;
+
+    let segment_path =
+        |folder: &std::path::Path, id: u64| folder.join("segments").join(id.to_string());
+
+    // NOTE: Only the segment that contains versions newer than the snapshot is rewritten
+    assert_eq!(
+        2,
+        std::fs::metadata(segment_path(folder.path(), old.id()))?.nlink()
+    );
+    assert_eq!(
+        1,
+        std::fs::metadata(segment_path(folder.path(), new.id()))?.nlink()
+    );
+
+    let exported = Config::new(&export_folder).open()?;
+    assert_eq!(1, exported.segment_count());
+    assert!(exported.contains_key("a", None)?);
+    assert!(!exported.contains_key("b", None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_snapshot_export_existing_tree() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let export_folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open()?;
+    tree.insert("a", "abc", 0);
+
+    Config::new(&export_folder).open()?;
+
+    assert!(matches!(
+        tree.snapshot(1).export(&export_folder),
+        Err(lsm_tree::Error::Io(e)) if e.kind() == std::io::ErrorKind::AlreadyExists
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_snapshot_export() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let export_folder = tempfile::tempdir()?;
+
+    let big_value = "abc".repeat(10_000);
+
+    let tree = Config::new(&folder).open_as_blob_tree()?;
+
+    tree.insert("a", &big_value, 0);
+    tree.insert("b", "small", 1);
+    tree.flush_active_memtable(0)?;
+
+    tree.insert("c", &big_value, 2);
+
+    let snapshot = tree.snapshot(3);
+    tree.insert("a", "new", 3);
+    tree.flush_active_memtable(0)?;
+
+    snapshot.export(&export_folder)?;
+    drop(snapshot);
+    drop(tree);
+
+    let exported = Config::new(&export_folder).open_as_blob_tree()?;
+    assert_eq!(3, exported.len(None, None)?);
+    assert_eq!(
+        big_value.as_bytes(),
+        &*exported.get("a", None)?.expect("should exist"),
+    );
+    assert_eq!(b"small", &*exported.get("b", None)?.expect("should exist"));
+    assert_eq!(
+        big_value.as_bytes(),
+        &*exported.get("c", None)?.expect("should exist"),
+    );
+
+    assert!(exported.verify_references()?.is_consistent());
+
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use lsm_tree::{AbstractTree, Config, SeqNo};
+use test_log::test;
+
+#[test]
+fn tree_snapshot_gc_versions() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open()?;
+
+    tree.insert("a", "old", 0);
+    tree.flush_active_memtable(0)?;
+
+    let snapshot = tree.snapshot(1);
+    let named = tree.create_named_snapshot("backup", 3)?;
+    assert_eq!(Some(1), tree.lowest_snapshot_seqno());
+    assert_eq!(1, tree.gc_watermark());
+
+    tree.insert("a", "mid", 1);
+    tree.insert("a", "new", 2);
+    tree.flush_active_memtable(SeqNo::MAX)?;
+    tree.major_compact(u64::MAX, SeqNo::MAX)?;
+
+    assert_eq!(Some("old".as_bytes().into()), snapshot.get("a")?);
+    assert_eq!(Some("new".as_bytes().into()), named.get("a")?);
+
+    // NOTE: Version 1 is not visible to any snapshot, so it was evicted
+    assert_eq!(Some("old".as_bytes().into()), tree.get("a", Some(2))?);
+
+    drop(snapshot);
+    drop(named);
+    assert!(tree.release_named_snapshot("backup")?);
+
+    assert_eq!(None, tree.lowest_snapshot_seqno());
+    assert_eq!(SeqNo::MAX, tree.gc_watermark());
+
+    tree.major_compact(u64::MAX, tree.gc_watermark())?;
+    assert_eq!(None, tree.get("a", Some(1))?);
+    assert_eq!(Some("new".as_bytes().into()), tree.get("a", None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_snapshot_gc_tombstone() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open()?;
+
+    tree.insert("a", "abc", 0);
+    tree.flush_active_memtable(0)?;
+
+    let snapshot = tree.snapshot(1);
+
+    tree.remove("a", 1);
+    tree.flush_active_memtable(0)?;
+
+    // NOTE: The tombstone can not be evicted, even in the last level,
+    // because the snapshot keeps the version below it
+    tree.major_compact(u64::MAX, tree.gc_watermark())?;
+    assert!(snapshot.contains_key("a")?);
+    assert!(!tree.contains_key("a", None)?);
+
+    drop(snapshot);
+
+    tree.major_compact(u64::MAX, tree.gc_watermark())?;
+    assert!(tree.is_empty(None, None)?);
+    assert_eq!(None, tree.get("a", Some(1))?);
+
+    Ok(())
+}
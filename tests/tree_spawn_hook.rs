@@ -0,0 +1,75 @@
+use lsm_tree::{AbstractTree, BlockingTask, Config};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering::Relaxed},
+    Arc,
+};
+use test_log::test;
+
+const ITEM_COUNT: usize = 5_000;
+
+fn create_tree(folder: &tempfile::TempDir) -> lsm_tree::Result<()> {
+    let tree = Config::new(folder).data_block_size(1_024).open()?;
+
+    for x in 0..ITEM_COUNT as u64 {
+        tree.insert(x.to_be_bytes(), x.to_string().repeat(10), 0);
+    }
+    tree.flush_active_memtable(0)?;
+
+    Ok(())
+}
+
+#[test]
+fn tree_spawn_hook_prefetch() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    create_tree(&folder)?;
+
+    let spawned = Arc::new(AtomicUsize::default());
+
+    let tree = Config::new(&folder)
+        .scan_prefetch_blocks(8)
+        .spawn_hook(Arc::new({
+            let spawned = spawned.clone();
+
+            move |task: BlockingTask| {
+                spawned.fetch_add(1, Relaxed);
+                std::thread::spawn(task);
+            }
+        }))
+        .open()?;
+
+    assert_eq!(ITEM_COUNT, tree.iter(None, None).count());
+    assert!(spawned.load(Relaxed) > 0);
+
+    Ok(())
+}
+
+#[test]
+fn tree_spawn_hook_dropped_tasks() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    create_tree(&folder)?;
+
+    let dropped = Arc::new(AtomicUsize::default());
+
+    // NOTE: Simulates an executor that is shutting down
+    let tree = Config::new(&folder)
+        .scan_prefetch_blocks(8)
+        .spawn_hook(Arc::new({
+            let dropped = dropped.clone();
+
+            move |task: BlockingTask| {
+                dropped.fetch_add(1, Relaxed);
+                drop(task);
+            }
+        }))
+        .open()?;
+
+    for (idx, kv) in tree.iter(None, None).enumerate() {
+        let (key, _) = kv?;
+        assert_eq!(&*key, (idx as u64).to_be_bytes());
+    }
+
+    // NOTE: Read-ahead is disabled after the first dropped task
+    assert_eq!(1, dropped.load(Relaxed));
+
+    Ok(())
+}
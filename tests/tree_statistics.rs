@@ -0,0 +1,120 @@
+use lsm_tree::{AbstractTree, Config, Statistics};
+use std::sync::Arc;
+use test_log::test;
+
+#[test]
+fn tree_statistics() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?.into_path();
+
+    let stats = Arc::new(Statistics::default());
+    let tree = Config::new(folder).statistics(stats.clone()).open()?;
+
+    for key in ["a", "b", "c"] {
+        tree.insert(key, key, 0);
+    }
+    tree.flush_active_memtable(0)?;
+
+    for key in ["d", "e", "f"] {
+        tree.insert(key, key, 1);
+    }
+    tree.flush_active_memtable(0)?;
+
+    assert_eq!(2, stats.flush_count());
+    assert!(stats.flush_bytes_read() > 0);
+    assert_eq!(tree.disk_space(), stats.flush_bytes_written());
+
+    assert!(tree.get("a", None)?.is_some());
+    assert!(tree.get("a", None)?.is_some());
+    assert!(tree.get("x", None)?.is_none());
+
+    assert_eq!(3, stats.get_latency().count());
+    assert!(stats.bloom_checked() > 0);
+    assert!(stats.block_cache_misses() >= 1);
+    assert!(stats.block_cache_hits() >= 1);
+    assert!(stats.block_bytes_read() > 0);
+
+    assert_eq!(6, tree.iter(None, None).count());
+    assert_eq!(1, tree.prefix("a", None, None).count());
+    assert_eq!(2, stats.scan_latency().count());
+
+    let disk_space = tree.disk_space();
+    tree.major_compact(u64::MAX, 2)?;
+
+    assert_eq!(1, stats.compaction_count());
+    assert_eq!(disk_space, stats.compaction_bytes_read());
+    assert_eq!(tree.disk_space(), stats.compaction_bytes_written());
+
+    Ok(())
+}
+
+#[test]
+fn tree_amplification() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?.into_path();
+
+    let stats = Arc::new(Statistics::default());
+    let tree = Config::new(folder).statistics(stats.clone()).open()?;
+
+    assert_eq!(0.0, stats.write_amp());
+    assert_eq!(0.0, stats.read_amp());
+    assert_eq!(0.0, tree.space_amp());
+
+    for seqno in 0..3 {
+        for key in ["a", "b", "c"] {
+            tree.insert(key, key, seqno);
+        }
+        tree.flush_active_memtable(0)?;
+    }
+
+    assert_eq!(1.0, stats.write_amp());
+    assert_eq!(1.0, tree.space_amp());
+
+    assert!(tree.get("a", None)?.is_some());
+    assert_eq!(1.0, stats.read_amp());
+
+    tree.major_compact(u64::MAX, 3)?;
+    assert!(stats.write_amp() > 1.0);
+    assert_eq!(1.0, tree.space_amp());
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_space_amp() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?.into_path();
+
+    let tree = Config::new(folder).open_as_blob_tree()?;
+    assert_eq!(0.0, tree.space_amp());
+
+    let big_value = "a".repeat(10_000);
+
+    tree.insert("a", &big_value, 0);
+    tree.flush_active_memtable(0)?;
+    assert_eq!(1.0, tree.space_amp());
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_statistics() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?.into_path();
+
+    let stats = Arc::new(Statistics::default());
+    let tree = Config::new(folder)
+        .statistics(stats.clone())
+        .blob_file_separation_threshold(1)
+        .open_as_blob_tree()?;
+
+    for key in ["a", "b", "c"] {
+        tree.insert(key, key.repeat(100), 0);
+    }
+    tree.flush_active_memtable(0)?;
+
+    assert_eq!(1, stats.flush_count());
+    assert!(stats.flush_bytes_read() > 0);
+
+    // NOTE: Includes the blob files
+    assert!(stats.flush_bytes_written() > tree.index.disk_space());
+    assert!(stats.write_amp() > 0.0);
+
+    Ok(())
+}
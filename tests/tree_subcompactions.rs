@@ -0,0 +1,95 @@
+use lsm_tree::{AbstractTree, Config, KeyRange, SequenceNumberCounter};
+use test_log::test;
+
+const ITEM_COUNT: u64 = 2_000;
+
+fn key(x: u64) -> [u8; 8] {
+    x.to_be_bytes()
+}
+
+fn assert_disjoint(tree: &lsm_tree::Tree) {
+    let segments = tree
+        .levels
+        .read()
+        .expect("lock is poisoned")
+        .iter()
+        .cloned()
+        .collect::<Vec<_>>();
+
+    let ranges = segments
+        .iter()
+        .map(|segment| &segment.metadata.key_range)
+        .collect::<Vec<_>>();
+
+    assert!(KeyRange::is_disjoint(&ranges));
+}
+
+#[test]
+fn tree_subcompactions() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder)
+        .max_subcompactions(4)
+        .paranoid_checks(true)
+        .open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    for x in 0..ITEM_COUNT {
+        tree.insert(key(x), x.to_string().repeat(20), seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+
+    // NOTE: Creates a bunch of disjoint segments, whose key ranges split the next compaction
+    tree.major_compact(8_000, seqno.get())?;
+    assert!(tree.segment_count() > 4);
+    assert_disjoint(&tree);
+
+    for batch in 0..3 {
+        for x in (batch..ITEM_COUNT).step_by(3) {
+            tree.insert(key(x), "new", seqno.next());
+        }
+        tree.remove(key(batch * 100), seqno.next());
+        tree.flush_active_memtable(0)?;
+    }
+
+    tree.major_compact(8_000, seqno.get())?;
+    assert!(tree.segment_count() > 1);
+    assert_disjoint(&tree);
+
+    for x in 0..ITEM_COUNT {
+        let expected = if x == 0 || x == 100 || x == 200 {
+            None
+        } else {
+            Some("new".as_bytes().into())
+        };
+        assert_eq!(expected, tree.get(key(x), None)?, "key {x}");
+    }
+    assert_eq!(ITEM_COUNT as usize - 3, tree.len(None, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_subcompactions_range_tombstone() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let tree = Config::new(&folder)
+        .max_subcompactions(4)
+        .paranoid_checks(true)
+        .open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    for x in 0..ITEM_COUNT {
+        tree.insert(key(x), x.to_string().repeat(20), seqno.next());
+    }
+    tree.flush_active_memtable(0)?;
+    tree.major_compact(8_000, seqno.get())?;
+
+    // NOTE: The range tombstone spans multiple segments, so the compaction is not split
+    tree.remove_range(key(100)..key(1_900), seqno.next());
+    tree.flush_active_memtable(0)?;
+
+    tree.major_compact(8_000, seqno.get())?;
+    assert_disjoint(&tree);
+    assert_eq!(200, tree.len(None, None)?);
+
+    Ok(())
+}
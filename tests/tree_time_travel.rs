@@ -0,0 +1,77 @@
+use lsm_tree::{AbstractTree, Config, ManualClock};
+use std::{sync::Arc, time::Duration};
+use test_log::test;
+
+#[test]
+fn tree_time_travel_recover() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let clock = Arc::new(ManualClock::new(Duration::from_secs(1_000)));
+
+    {
+        let tree = Config::new(&folder).clock(clock.clone()).open()?;
+
+        tree.insert("a", "old", 0);
+        tree.insert("b", "old", 1);
+        tree.flush_active_memtable(0)?;
+
+        clock.advance(Duration::from_secs(60));
+        tree.insert("a", "new", 2);
+        tree.remove("b", 3);
+        tree.flush_active_memtable(0)?;
+
+        // NOTE: Not flushed, so not sampled
+        clock.advance(Duration::from_secs(60));
+        tree.insert("c", "new", 4);
+
+        assert_eq!(Some(2), tree.seqno_at_time(Duration::from_secs(1_059)));
+        assert_eq!(Some(4), tree.seqno_at_time(Duration::from_secs(1_060)));
+        assert_eq!(Some(4), tree.seqno_at_time(Duration::from_secs(2_000)));
+    }
+
+    let tree = Config::new(&folder).open()?;
+    assert_eq!(None, tree.seqno_at_time(Duration::from_secs(999)));
+
+    let snapshot = tree
+        .snapshot_at_time(Duration::from_secs(1_000))
+        .expect("should exist");
+    assert_eq!(Some("old".as_bytes().into()), snapshot.get("a")?);
+    assert_eq!(Some("old".as_bytes().into()), snapshot.get("b")?);
+
+    let snapshot = tree
+        .snapshot_at_time(Duration::from_secs(1_060))
+        .expect("should exist");
+    assert_eq!(Some("new".as_bytes().into()), snapshot.get("a")?);
+    assert!(!snapshot.contains_key("b")?);
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_time_travel() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let clock = Arc::new(ManualClock::new(Duration::from_secs(1_000)));
+    let tree = Config::new(&folder)
+        .clock(clock.clone())
+        .open_as_blob_tree()?;
+
+    let big_value = "abc".repeat(10_000);
+
+    tree.insert("a", &big_value, 0);
+    tree.flush_active_memtable(0)?;
+
+    clock.advance(Duration::from_secs(60));
+    tree.insert("a", "new", 1);
+    tree.flush_active_memtable(0)?;
+
+    let snapshot = tree
+        .snapshot_at_time(Duration::from_secs(1_000))
+        .expect("should exist");
+    assert_eq!(
+        big_value.as_bytes(),
+        &*snapshot.get("a")?.expect("should exist")
+    );
+
+    Ok(())
+}
@@ -0,0 +1,97 @@
+use lsm_tree::{AbstractTree, Config, ManualClock};
+use std::{sync::Arc, time::Duration};
+use test_log::test;
+
+fn tombstone_count(tree: &lsm_tree::Tree) -> u64 {
+    tree.levels
+        .read()
+        .expect("lock is poisoned")
+        .iter()
+        .map(|segment| segment.metadata.tombstone_count)
+        .sum()
+}
+
+#[test]
+fn tree_tombstone_grace_period() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let clock = Arc::new(ManualClock::new(Duration::from_secs(1_000)));
+
+    let tree = Config::new(&folder)
+        .clock(clock.clone())
+        .tombstone_grace_period(Duration::from_secs(60))
+        .open()?;
+
+    tree.insert("a", "abc", 0);
+    tree.insert("b", "abc", 1);
+    tree.remove("a", 2);
+    tree.flush_active_memtable(0)?;
+
+    // NOTE: The tombstone is too young to be dropped
+    tree.major_compact(u64::MAX, 3)?;
+    assert_eq!(1, tombstone_count(&tree));
+    assert!(!tree.contains_key("a", None)?);
+
+    clock.advance(Duration::from_secs(30));
+    tree.major_compact(u64::MAX, 3)?;
+    assert_eq!(1, tombstone_count(&tree));
+
+    // NOTE: The compacted segment was created 30 seconds after the tombstone was written,
+    // so only the flush sample proves its age
+    clock.advance(Duration::from_secs(31));
+    tree.major_compact(u64::MAX, 3)?;
+    assert_eq!(0, tombstone_count(&tree));
+    assert!(!tree.contains_key("a", None)?);
+    assert!(tree.contains_key("b", None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_tombstone_grace_period_by_segment_age() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let clock = Arc::new(ManualClock::new(Duration::from_secs(1_000)));
+
+    {
+        let tree = Config::new(&folder).clock(clock.clone()).open()?;
+
+        tree.insert("a", "abc", 0);
+        tree.remove("a", 1);
+        tree.flush_active_memtable(0)?;
+    }
+
+    // NOTE: Remove the seqno samples, so only the segment age is known
+    std::fs::remove_file(folder.path().join("seqno_time"))?;
+
+    let tree = Config::new(&folder)
+        .clock(clock.clone())
+        .tombstone_grace_period(Duration::from_secs(60))
+        .open()?;
+    assert_eq!(None, tree.seqno_at_time(Duration::from_secs(u64::MAX)));
+
+    tree.major_compact(u64::MAX, 2)?;
+    assert_eq!(1, tombstone_count(&tree));
+
+    clock.advance(Duration::from_secs(120));
+    tree.major_compact(u64::MAX, 2)?;
+    assert_eq!(0, tombstone_count(&tree));
+
+    Ok(())
+}
+
+#[test]
+fn tree_tombstone_grace_period_disabled() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open()?;
+
+    tree.insert("a", "abc", 0);
+    tree.remove("a", 1);
+    tree.flush_active_memtable(0)?;
+
+    tree.major_compact(u64::MAX, 2)?;
+    assert_eq!(0, tombstone_count(&tree));
+
+    Ok(())
+}
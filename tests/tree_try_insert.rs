@@ -0,0 +1,62 @@
+use lsm_tree::{AbstractTree, Config};
+use test_log::test;
+
+#[test]
+fn tree_try_insert_invalid() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open()?;
+
+    let long_key = vec![0; u16::MAX as usize + 1];
+
+    assert!(matches!(
+        tree.try_insert("", "abc", 0),
+        Err(lsm_tree::Error::InvalidInput(_))
+    ));
+    assert!(matches!(
+        tree.try_insert(long_key.clone(), "abc", 0),
+        Err(lsm_tree::Error::InvalidInput(_))
+    ));
+    assert!(matches!(
+        tree.try_remove("", 0),
+        Err(lsm_tree::Error::InvalidInput(_))
+    ));
+    assert!(matches!(
+        tree.try_remove_weak(long_key, 0),
+        Err(lsm_tree::Error::InvalidInput(_))
+    ));
+    assert!(tree.is_empty(None, None)?);
+
+    tree.try_insert("a", "abc", 0)?;
+    tree.try_insert("b", "abc", 1)?;
+    tree.try_remove("a", 2)?;
+    tree.try_remove_weak("b", 3)?;
+    assert!(tree.is_empty(None, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_try_insert_invalid() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open_as_blob_tree()?;
+
+    let big_value = "abc".repeat(10_000);
+
+    assert!(matches!(
+        tree.try_insert("", &big_value, 0),
+        Err(lsm_tree::Error::InvalidInput(_))
+    ));
+
+    tree.try_insert("a", &big_value, 0)?;
+    tree.flush_active_memtable(0)?;
+    assert_eq!(1, tree.blob_file_count());
+
+    assert_eq!(
+        &*tree.get("a", None)?.expect("should exist"),
+        big_value.as_bytes()
+    );
+
+    Ok(())
+}
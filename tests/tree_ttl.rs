@@ -0,0 +1,109 @@
+use lsm_tree::{AbstractTree, Config, ManualClock, SequenceNumberCounter};
+use std::{sync::Arc, time::Duration};
+use test_log::test;
+
+#[test]
+fn tree_ttl_read() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let clock = Arc::new(ManualClock::new(Duration::from_secs(1_000)));
+    let tree = Config::new(&folder).clock(clock.clone()).open()?;
+
+    tree.insert("a", "old", 0);
+    tree.insert_with_ttl("a", "abc", 1, Duration::from_secs(10));
+    tree.insert_with_ttl("b", "def", 2, Duration::from_secs(20));
+    tree.insert("c", "ghi", 3);
+
+    assert_eq!(Some("abc".as_bytes().into()), tree.get("a", None)?);
+    assert_eq!(Some("old".as_bytes().into()), tree.get("a", Some(1))?);
+    assert_eq!(3, tree.len(None, None)?);
+
+    clock.advance(Duration::from_secs(10));
+
+    // NOTE: The expired value shadows the older version, like a deletion
+    assert_eq!(None, tree.get("a", None)?);
+    assert_eq!(Some("def".as_bytes().into()), tree.get("b", None)?);
+    assert_eq!(
+        vec![
+            None,
+            Some("def".as_bytes().into()),
+            Some("ghi".as_bytes().into())
+        ],
+        tree.get_many(["a", "b", "c"], None)?,
+    );
+
+    let items = tree
+        .iter(None, None)
+        .collect::<lsm_tree::Result<Vec<_>>>()?;
+    assert_eq!(
+        vec![
+            ("b".as_bytes().into(), "def".as_bytes().into()),
+            ("c".as_bytes().into(), "ghi".as_bytes().into()),
+        ],
+        items,
+    );
+
+    tree.flush_active_memtable(0)?;
+    assert_eq!(None, tree.get("a", None)?);
+    assert_eq!(Some("def".as_bytes().into()), tree.get("b", None)?);
+
+    clock.advance(Duration::from_secs(10));
+    assert_eq!(None, tree.get("b", None)?);
+    assert_eq!(1, tree.len(None, None)?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_ttl_compaction() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let clock = Arc::new(ManualClock::new(Duration::from_secs(1_000)));
+    let tree = Config::new(&folder).clock(clock.clone()).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    for x in 0..100_u64 {
+        tree.insert_with_ttl(
+            x.to_be_bytes(),
+            "abc",
+            seqno.next(),
+            Duration::from_secs(x + 1),
+        );
+    }
+    tree.flush_active_memtable(0)?;
+    assert_eq!(100, tree.approximate_len());
+
+    clock.advance(Duration::from_secs(50));
+    assert_eq!(50, tree.len(None, None)?);
+
+    tree.major_compact(u64::MAX, seqno.get())?;
+    assert_eq!(50, tree.approximate_len());
+    assert_eq!(50, tree.len(None, None)?);
+
+    clock.advance(Duration::from_secs(50));
+    tree.major_compact(u64::MAX, seqno.get())?;
+    assert_eq!(0, tree.segment_count());
+
+    Ok(())
+}
+
+#[test]
+fn tree_ttl_recover() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let clock = Arc::new(ManualClock::new(Duration::from_secs(1_000)));
+
+    {
+        let tree = Config::new(&folder).clock(clock.clone()).open()?;
+        tree.insert_with_ttl("a", "abc", 0, Duration::from_secs(10));
+        tree.flush_active_memtable(0)?;
+    }
+
+    let tree = Config::new(&folder).clock(clock.clone()).open()?;
+    assert_eq!(Some("abc".as_bytes().into()), tree.get("a", None)?);
+
+    clock.advance(Duration::from_secs(10));
+    assert_eq!(None, tree.get("a", None)?);
+
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use lsm_tree::{AbstractTree, Config, KeyRange};
+use test_log::test;
+
+const ITEM_COUNT: u64 = 1_000;
+
+fn assert_close(expected: u64, estimate: u64) {
+    assert!(
+        estimate.abs_diff(expected) <= expected / 20,
+        "expected about {expected}, got {estimate}",
+    );
+}
+
+#[test]
+fn tree_estimated_unique_keys() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    {
+        let tree = Config::new(&folder).open()?;
+
+        let mut seqno = 0;
+
+        for _ in 0..3 {
+            for x in 0..ITEM_COUNT {
+                tree.insert(x.to_be_bytes(), "abc", seqno);
+                seqno += 1;
+            }
+            tree.flush_active_memtable(0)?;
+        }
+
+        // NOTE: Unflushed keys count as well
+        for x in ITEM_COUNT..ITEM_COUNT * 2 {
+            tree.insert(x.to_be_bytes(), "abc", seqno);
+            seqno += 1;
+        }
+
+        assert_eq!(3, tree.segment_count());
+        assert_eq!(ITEM_COUNT as usize * 4, tree.approximate_len());
+        assert_close(ITEM_COUNT * 2, tree.estimated_unique_keys()?);
+    }
+
+    // NOTE: Sketches are persisted in the segments
+    let tree = Config::new(&folder).open()?;
+    assert_close(ITEM_COUNT, tree.estimated_unique_keys()?);
+
+    Ok(())
+}
+
+#[test]
+fn tree_estimated_unique_keys_in_range() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open()?;
+
+    for x in 0..ITEM_COUNT {
+        tree.insert(x.to_be_bytes(), "abc", x);
+    }
+    tree.flush_active_memtable(0)?;
+
+    for x in ITEM_COUNT..ITEM_COUNT * 3 {
+        tree.insert(x.to_be_bytes(), "abc", x);
+    }
+    tree.flush_active_memtable(0)?;
+
+    let first = KeyRange::new((
+        0u64.to_be_bytes().into(),
+        (ITEM_COUNT - 1).to_be_bytes().into(),
+    ));
+    assert_close(ITEM_COUNT, tree.estimated_unique_keys_in_range(&first)?);
+
+    let none = KeyRange::new((
+        (ITEM_COUNT * 10).to_be_bytes().into(),
+        (ITEM_COUNT * 11).to_be_bytes().into(),
+    ));
+    assert_eq!(0, tree.estimated_unique_keys_in_range(&none)?);
+
+    assert_close(ITEM_COUNT * 3, tree.estimated_unique_keys()?);
+
+    Ok(())
+}
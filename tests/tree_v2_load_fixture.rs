@@ -16,7 +16,7 @@ fn tree_load_v2_corrupt() -> lsm_tree::Result<()> {
     let folder = "test_fixture/v2_tree_corrupt";
 
     let result = Config::new(folder).open()?;
-    assert_eq!(1, result.verify()?);
+    assert_eq!(1, result.verify()?.error_count());
 
     Ok(())
 }
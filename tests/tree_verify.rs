@@ -0,0 +1,97 @@
+use lsm_tree::{AbstractTree, Config, SequenceNumberCounter};
+use std::io::{Read, Seek, SeekFrom, Write};
+use test_log::test;
+
+const ITEM_COUNT: u64 = 1_000;
+
+#[test]
+fn tree_verify() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    for x in 0..ITEM_COUNT {
+        tree.insert(x.to_be_bytes(), x.to_string().repeat(10), seqno.next());
+    }
+    let segment = tree.flush_active_memtable(0)?.expect("should exist");
+
+    let report = segment.verify()?;
+    assert_eq!(segment.id(), report.segment_id);
+    assert_eq!(
+        segment.metadata.data_block_count as usize,
+        report.data_blocks_checked
+    );
+    assert!(report.data_blocks_checked > 1);
+    assert!(report.is_ok());
+
+    let report = tree.verify()?;
+    assert_eq!(1, report.segments.len());
+    assert!(report.is_ok());
+    assert_eq!(0, report.corrupt_segments().count());
+
+    Ok(())
+}
+
+#[test]
+fn tree_verify_corrupt_block() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open()?;
+    let seqno = SequenceNumberCounter::default();
+
+    for x in 0..ITEM_COUNT {
+        tree.insert(x.to_be_bytes(), x.to_string().repeat(10), seqno.next());
+    }
+    let segment = tree.flush_active_memtable(0)?.expect("should exist");
+
+    {
+        let mut file = std::fs::OpenOptions::new().read(true).write(true).open(
+            folder
+                .path()
+                .join("segments")
+                .join(segment.id().to_string()),
+        )?;
+
+        // NOTE: Flip a byte inside of the first data block
+        let mut byte = [0];
+        file.seek(SeekFrom::Start(100))?;
+        file.read_exact(&mut byte)?;
+        file.seek(SeekFrom::Start(100))?;
+        file.write_all(&[!byte[0]])?;
+        file.sync_all()?;
+    }
+
+    let report = tree.verify()?;
+    assert!(!report.is_ok());
+    assert_eq!(
+        vec![segment.id()],
+        report.corrupt_segments().collect::<Vec<_>>()
+    );
+
+    let segment_report = report.segments.first().expect("should exist");
+    assert_eq!(1, segment_report.corrupt_blocks.len());
+    assert_eq!(0, *segment_report.corrupt_blocks[0]);
+    assert!(segment_report.unordered_blocks.is_empty());
+    assert!(segment_report.index_mismatches.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_verify() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+    let big_value = b"neptune!".repeat(128_000);
+
+    let tree = Config::new(&folder).open_as_blob_tree()?;
+    tree.insert("a", &big_value, 0);
+    tree.insert("b", "small", 1);
+    tree.flush_active_memtable(0)?;
+
+    let report = tree.verify()?;
+    assert_eq!(1, report.segments.len());
+    assert_eq!(0, report.corrupt_blob_count);
+    assert!(report.is_ok());
+
+    Ok(())
+}
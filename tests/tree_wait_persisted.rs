@@ -0,0 +1,50 @@
+use lsm_tree::{AbstractTree, Config};
+use std::time::Duration;
+use test_log::test;
+
+#[test]
+fn tree_wait_persisted() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let tree = Config::new(&folder).open()?;
+
+    tree.insert("a", "abc", 0);
+    tree.insert("b", "abc", 1);
+    assert!(!tree.is_persisted(0));
+    assert!(!tree.wait_for_persisted(0, Some(Duration::ZERO)));
+
+    let waiter = std::thread::spawn({
+        let tree = tree.clone();
+        move || tree.wait_for_persisted(1, Some(Duration::from_secs(60)))
+    });
+
+    tree.flush_active_memtable(0)?;
+    assert!(waiter.join().expect("should join"));
+
+    assert!(tree.is_persisted(1));
+    assert!(!tree.is_persisted(2));
+    assert!(tree.wait_for_persisted(1, None));
+
+    Ok(())
+}
+
+#[test]
+fn blob_tree_wait_persisted() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let big_value = "abc".repeat(10_000);
+
+    let tree = Config::new(&folder).open_as_blob_tree()?;
+    tree.insert("a", &big_value, 0);
+
+    let waiter = std::thread::spawn({
+        let tree = tree.clone();
+        move || tree.wait_for_persisted(0, None)
+    });
+
+    tree.flush_active_memtable(0)?;
+    assert!(waiter.join().expect("should join"));
+    assert!(tree.is_persisted(0));
+
+    Ok(())
+}
@@ -0,0 +1,42 @@
+use lsm_tree::{AbstractTree, Config, FilterType};
+use test_log::test;
+
+#[test]
+fn tree_compaction_xor_filter() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?.into_path();
+
+    let tree = Config::new(&folder)
+        .compaction_filter_type(FilterType::Xor)
+        .open()?;
+
+    for key in 0u64..10_000 {
+        tree.insert(key.to_be_bytes(), "abc", 0);
+    }
+    tree.flush_active_memtable(0)?;
+
+    let bloom_size = tree.bloom_filter_size();
+
+    // NOTE: Major compaction writes into the last level, which uses 10 bits per key
+    tree.major_compact(u64::MAX, 0)?;
+
+    let xor_size = tree.bloom_filter_size();
+    assert!(xor_size > 0);
+    assert!(xor_size < 10_000 * 10 / 8);
+    assert!(xor_size < bloom_size);
+
+    for key in 0u64..10_000 {
+        assert!(tree.contains_key(key.to_be_bytes(), None)?);
+    }
+    assert!(!tree.contains_key(10_000u64.to_be_bytes(), None)?);
+
+    drop(tree);
+
+    let tree = Config::new(&folder).open()?;
+    assert_eq!(xor_size, tree.bloom_filter_size());
+
+    for key in 0u64..10_000 {
+        assert!(tree.contains_key(key.to_be_bytes(), None)?);
+    }
+
+    Ok(())
+}
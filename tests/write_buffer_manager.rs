@@ -0,0 +1,71 @@
+use lsm_tree::{AbstractTree, Config, WriteBufferManager};
+use std::sync::Arc;
+use test_log::test;
+
+#[test]
+fn write_buffer_manager() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let manager = Arc::new(WriteBufferManager::new(10_000));
+
+    let a = Config::new(folder.path().join("a"))
+        .write_buffer_manager(manager.clone())
+        .open()?;
+
+    let b = Config::new(folder.path().join("b"))
+        .write_buffer_manager(manager.clone())
+        .open_as_blob_tree()?;
+
+    assert_eq!(2, manager.tree_count());
+    assert_eq!(0, manager.usage());
+
+    a.insert("a", "a".repeat(4_000), 0);
+    b.insert("a", "a".repeat(2_000), 1);
+    assert!(!manager.is_exceeded());
+    assert_eq!(None, manager.tree_to_flush());
+
+    b.insert("b", "a".repeat(6_000), 2);
+    assert!(manager.is_exceeded());
+    assert_eq!(
+        u64::from(a.active_memtable_size()) + u64::from(b.active_memtable_size()),
+        manager.usage(),
+    );
+
+    // NOTE: b has the largest memtable, so flushing it frees the most memory
+    assert_eq!(Some(b.index.id), manager.tree_to_flush());
+    b.flush_active_memtable(0)?;
+    assert!(!manager.is_exceeded());
+    assert_eq!(None, manager.tree_to_flush());
+
+    // NOTE: Dropped trees are not tracked anymore
+    drop(a);
+    assert_eq!(1, manager.tree_count());
+
+    Ok(())
+}
+
+#[test]
+fn write_buffer_manager_sealed() -> lsm_tree::Result<()> {
+    let folder = tempfile::tempdir()?;
+
+    let manager = Arc::new(WriteBufferManager::new(5_000));
+
+    let a = Config::new(folder.path().join("a"))
+        .write_buffer_manager(manager.clone())
+        .open()?;
+
+    let b = Config::new(folder.path().join("b"))
+        .write_buffer_manager(manager.clone())
+        .open()?;
+
+    a.insert("a", "a".repeat(5_000), 0);
+    a.rotate_memtable();
+    b.insert("a", "a".repeat(100), 1);
+
+    // NOTE: The sealed memtable counts towards the limit,
+    // but only active memtables are flushed
+    assert!(manager.is_exceeded());
+    assert_eq!(Some(b.id), manager.tree_to_flush());
+
+    Ok(())
+}